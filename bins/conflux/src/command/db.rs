@@ -0,0 +1,626 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Offline inspection and recovery of the databases of a stopped node.
+
+use cfx_storage::{PersistedSnapshotInfoMap, SnapshotInspector};
+use cfx_types::H256;
+use cfxcore::{
+    block_data_manager::{
+        checkpoint::{restore_checkpoint, CheckpointManifest},
+        db_manager::DBManager,
+    },
+    db::NUM_COLUMNS,
+    pow::PowComputer,
+};
+use clap;
+use client::configuration::Configuration;
+use primitives::compute_block_number;
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+const COLUMN_NAMES: [&str; NUM_COLUMNS as usize] = [
+    "misc",
+    "blocks",
+    "tx_index",
+    "epoch_number",
+    "blamed_header_verified_roots",
+    "block_traces",
+    "hash_by_block_number",
+    "reward_by_pos_epoch",
+];
+
+#[derive(Debug, PartialEq)]
+pub enum DbCmd {
+    Stats,
+    Block(H256),
+    Header(H256),
+    Receipts(H256),
+    TxIndex(H256),
+    CheckEpochs { from: u64, to: Option<u64> },
+    CheckSnapshots,
+    Rollback { epoch: u64 },
+    RestoreCheckpoint(PathBuf),
+}
+
+impl DbCmd {
+    pub fn new(matches: &clap::ArgMatches) -> Result<Self, String> {
+        let cmd = match matches.subcommand() {
+            ("stats", Some(_)) => DbCmd::Stats,
+            ("block", Some(m)) => DbCmd::Block(parse_hash(m)?),
+            ("header", Some(m)) => DbCmd::Header(parse_hash(m)?),
+            ("receipts", Some(m)) => DbCmd::Receipts(parse_hash(m)?),
+            ("tx-index", Some(m)) => DbCmd::TxIndex(parse_hash(m)?),
+            ("check-epochs", Some(m)) => DbCmd::CheckEpochs {
+                from: parse_u64(m, "from")?.unwrap_or(0),
+                to: parse_u64(m, "to")?,
+            },
+            ("check-snapshots", Some(_)) => DbCmd::CheckSnapshots,
+            ("rollback", Some(m)) => DbCmd::Rollback {
+                epoch: parse_u64(m, "epoch")?
+                    .expect("CLI argument is required; qed"),
+            },
+            ("restore-checkpoint", Some(m)) => DbCmd::RestoreCheckpoint(
+                m.value_of("checkpoint")
+                    .expect("CLI argument is required; qed")
+                    .into(),
+            ),
+            _ => unreachable!(),
+        };
+        Ok(cmd)
+    }
+}
+
+fn parse_hash(matches: &clap::ArgMatches) -> Result<H256, String> {
+    let hash = matches
+        .value_of("hash")
+        .expect("CLI argument is required; qed");
+    H256::from_str(hash.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid hash {}: {:?}", hash, e))
+}
+
+fn parse_u64(
+    matches: &clap::ArgMatches, name: &str,
+) -> Result<Option<u64>, String> {
+    match matches.value_of(name) {
+        Some(v) => v
+            .parse()
+            .map(Some)
+            .map_err(|e| format!("Invalid {} {}: {:?}", name, v, e)),
+        None => Ok(None),
+    }
+}
+
+/// The databases are opened in place, so the node must be stopped. Only
+/// `rollback` and `restore-checkpoint` write to them.
+pub fn execute(conf: Configuration, cmd: DbCmd) -> Result<String, String> {
+    if cmd == DbCmd::CheckSnapshots {
+        return check_snapshots(&conf);
+    }
+
+    let (db_path, db_config) = conf.db_config();
+    if let DbCmd::RestoreCheckpoint(checkpoint) = &cmd {
+        let pos_db_dir = if CheckpointManifest::load(checkpoint)?.pos_db {
+            Some(conf.pos_db_dir()?)
+        } else {
+            None
+        };
+        let manifest = restore_checkpoint(
+            checkpoint,
+            Path::new(&conf.raw_conf.conflux_data_dir),
+            &db_path,
+            pos_db_dir.as_deref(),
+        )?;
+        return Ok(format!(
+            "Restored checkpoint of epoch {:?} at height {:?}",
+            manifest.epoch_hash, manifest.epoch_height
+        ));
+    }
+    if !db_path.join("CURRENT").exists() {
+        return Err(format!("No block db found at {:?}", db_path));
+    }
+    let system_db = if let DbCmd::Rollback { .. } = cmd {
+        db::open_database(db_path.to_str().unwrap(), &db_config)
+    } else {
+        db::open_database_read_only(db_path.to_str().unwrap(), &db_config)
+    }
+    .map_err(|e| format!("Failed to open block db: {:?}", e))?;
+    let pow = Arc::new(PowComputer::new(conf.pow_config().use_octopus()));
+    let db_manager = DBManager::new_from_rocksdb(system_db.clone(), pow);
+
+    match cmd {
+        DbCmd::Stats => stats(&system_db),
+        DbCmd::Block(hash) => block(&db_manager, &hash),
+        DbCmd::Header(hash) => db_manager
+            .block_header_from_db(&hash)
+            .map(|header| format!("{:#?}", header))
+            .ok_or_else(|| format!("Block header {:?} not found", hash)),
+        DbCmd::Receipts(hash) => receipts(&db_manager, &hash),
+        DbCmd::TxIndex(hash) => db_manager
+            .transaction_index_from_db(&hash)
+            .map(|index| format!("{:#?}", index))
+            .ok_or_else(|| format!("Transaction index {:?} not found", hash)),
+        DbCmd::CheckEpochs { from, to } => check_epochs(&db_manager, from, to),
+        DbCmd::Rollback { epoch } => {
+            let output = rollback(&conf, &db_manager, epoch)?;
+            system_db
+                .key_value()
+                .flush()
+                .map_err(|e| format!("Failed to flush block db: {:?}", e))?;
+            Ok(output)
+        }
+        DbCmd::CheckSnapshots | DbCmd::RestoreCheckpoint(_) => unreachable!(),
+    }
+}
+
+fn stats(system_db: &db::SystemDB) -> Result<String, String> {
+    let mut output = String::new();
+    writeln!(
+        output,
+        "{:<30} {:>15} {:>15} {:>15} {:>15}",
+        "column", "keys", "live data", "sst files", "mem tables"
+    )
+    .unwrap();
+    for (col, name) in COLUMN_NAMES.iter().enumerate() {
+        let stats = system_db
+            .key_value()
+            .column_statistics(col as u32)
+            .ok_or("Block db is closed")?;
+        writeln!(
+            output,
+            "{:<30} {:>15} {:>15} {:>15} {:>15}",
+            name,
+            stats.estimate_num_keys,
+            stats.estimate_live_data_size,
+            stats.total_sst_files_size,
+            stats.cur_size_all_mem_tables
+        )
+        .unwrap();
+    }
+    Ok(output)
+}
+
+fn block(db_manager: &DBManager, hash: &H256) -> Result<String, String> {
+    let block = db_manager
+        .block_from_db(hash)
+        .ok_or_else(|| format!("Block {:?} not found", hash))?;
+    let mut output = format!("{:#?}\ntransactions:\n", block.block_header);
+    for tx in &block.transactions {
+        writeln!(output, "  {:?}", tx.hash()).unwrap();
+    }
+    Ok(output)
+}
+
+fn receipts(db_manager: &DBManager, hash: &H256) -> Result<String, String> {
+    let result = db_manager
+        .block_execution_result_from_db(hash)
+        .ok_or_else(|| format!("Receipts of block {:?} not found", hash))?;
+    Ok(format!(
+        "executed in epoch {:?}\n{:#?}",
+        result.0, result.1.block_receipts
+    ))
+}
+
+/// Check the executed epoch sets in `COL_EPOCH_NUMBER` against the blocks and
+/// the execution results. If `to` is not given, the check stops at the first
+/// epoch without an executed epoch set.
+fn check_epochs(
+    db_manager: &DBManager, from: u64, to: Option<u64>,
+) -> Result<String, String> {
+    let mut output = String::new();
+    let mut errors = 0;
+    let mut last_executed_epoch = None;
+    let mut last_pivot: Option<H256> = None;
+    let mut epoch = from;
+    while to.map_or(true, |to| epoch <= to) {
+        let epoch_set = match db_manager
+            .executed_epoch_set_hashes_from_db(epoch)
+        {
+            Some(set) if !set.is_empty() => set,
+            _ => {
+                if to.is_some() {
+                    writeln!(output, "epoch {}: executed set missing", epoch)
+                        .unwrap();
+                    errors += 1;
+                    last_pivot = None;
+                    epoch += 1;
+                    continue;
+                }
+                break;
+            }
+        };
+        let pivot = *epoch_set.last().unwrap();
+
+        match db_manager.block_header_from_db(&pivot) {
+            None => {
+                writeln!(
+                    output,
+                    "epoch {}: pivot block {:?} missing",
+                    epoch, pivot
+                )
+                .unwrap();
+                errors += 1;
+            }
+            Some(header) => {
+                if header.height() != epoch {
+                    writeln!(
+                        output,
+                        "epoch {}: pivot block {:?} has height {}",
+                        epoch,
+                        pivot,
+                        header.height()
+                    )
+                    .unwrap();
+                    errors += 1;
+                }
+                if let Some(last_pivot) = &last_pivot {
+                    if header.parent_hash() != last_pivot {
+                        writeln!(
+                            output,
+                            "epoch {}: pivot block {:?} is not a child of \
+                             the previous pivot block {:?}",
+                            epoch, pivot, last_pivot
+                        )
+                        .unwrap();
+                        errors += 1;
+                    }
+                }
+            }
+        }
+
+        for hash in &epoch_set {
+            if db_manager.block_header_from_db(hash).is_none() {
+                writeln!(output, "epoch {}: block {:?} missing", epoch, hash)
+                    .unwrap();
+                errors += 1;
+            }
+            // Execution results may have been garbage collected, so only
+            // check the ones still present.
+            if let Some(result) =
+                db_manager.block_execution_result_from_db(hash)
+            {
+                if result.0 != pivot {
+                    writeln!(
+                        output,
+                        "epoch {}: block {:?} has receipts of epoch {:?}",
+                        epoch, hash, result.0
+                    )
+                    .unwrap();
+                    errors += 1;
+                }
+            }
+        }
+
+        if db_manager
+            .epoch_execution_commitment_from_db(&pivot)
+            .is_some()
+        {
+            last_executed_epoch = Some(epoch);
+        }
+        last_pivot = Some(pivot);
+        epoch += 1;
+    }
+
+    writeln!(
+        output,
+        "checked epochs {}..{}, last executed epoch {:?}, {} errors",
+        from, epoch, last_executed_epoch, errors
+    )
+    .unwrap();
+    if errors == 0 {
+        Ok(output)
+    } else {
+        Err(output)
+    }
+}
+
+fn check_snapshots(conf: &Configuration) -> Result<String, String> {
+    let inspector =
+        SnapshotInspector::open(&conf.storage_config(&conf.node_type()))
+            .map_err(|e| format!("Failed to open snapshots: {}", e))?;
+    let mut output = String::new();
+    let mut errors = 0;
+    for info in inspector.snapshot_infos() {
+        let snapshot_epoch_id = info.get_snapshot_epoch_id();
+        match inspector.check_snapshot(snapshot_epoch_id).map_err(|e| {
+            format!("Failed to check snapshot {:?}: {}", snapshot_epoch_id, e)
+        })? {
+            None => writeln!(
+                output,
+                "snapshot {:?} at height {}: not available",
+                snapshot_epoch_id, info.height
+            )
+            .unwrap(),
+            Some(result) => {
+                if !result.is_consistent() {
+                    errors += 1;
+                }
+                writeln!(
+                    output,
+                    "snapshot {:?} at height {}: {} key-values, {}\n  \
+                     expected root {:?}, recomputed root {:?}, mpt root {:?}",
+                    result.snapshot_epoch_id,
+                    result.height,
+                    result.key_value_count,
+                    if result.is_consistent() {
+                        "ok"
+                    } else {
+                        "CORRUPTED"
+                    },
+                    result.expected_merkle_root,
+                    result.kv_merkle_root,
+                    result.mpt_merkle_root,
+                )
+                .unwrap();
+            }
+        }
+    }
+    if errors == 0 {
+        Ok(output)
+    } else {
+        Err(output)
+    }
+}
+
+/// Roll the executed state back to `epoch`, so that the later epochs are
+/// executed again from the state of `epoch` when the node restarts.
+///
+/// The snapshots above `epoch` are dropped first, so that they are made again
+/// from the re-executed state, then the execution data of the later epochs
+/// is removed from the block db.
+fn rollback(
+    conf: &Configuration, db_manager: &DBManager, epoch: u64,
+) -> Result<String, String> {
+    if let Some((_, stable_checkpoint)) = db_manager.checkpoint_hashes_from_db()
+    {
+        let checkpoint_height = db_manager
+            .block_header_from_db(&stable_checkpoint)
+            .ok_or("Stable checkpoint block missing")?
+            .height();
+        if epoch < checkpoint_height {
+            return Err(format!(
+                "Cannot roll back to epoch {} before the stable checkpoint at \
+                 height {}",
+                epoch, checkpoint_height
+            ));
+        }
+    }
+    let target_pivot = db_manager
+        .executed_epoch_set_hashes_from_db(epoch)
+        .and_then(|set| set.last().cloned())
+        .ok_or_else(|| format!("Executed set of epoch {} missing", epoch))?;
+    let commitment = db_manager
+        .epoch_execution_commitment_from_db(&target_pivot)
+        .ok_or_else(|| format!("Epoch {} is not executed", epoch))?;
+
+    let storage_conf = conf.storage_config(&conf.node_type());
+    let snapshot_infos = PersistedSnapshotInfoMap::load_readonly(
+        &storage_conf.path_snapshot_info_db,
+    )
+    .map_err(|e| format!("Failed to load snapshot infos: {}", e))?;
+    let snapshot_epoch_id = &commitment
+        .state_root_with_aux_info
+        .aux_info
+        .snapshot_epoch_id;
+    if !snapshot_infos.contains_key(snapshot_epoch_id) {
+        return Err(format!(
+            "The state of epoch {} is no longer available, its snapshot {:?} \
+             is removed",
+            epoch, snapshot_epoch_id
+        ));
+    }
+    let snapshots_above = snapshot_infos
+        .values()
+        .filter(|info| info.height > epoch)
+        .count();
+    // The latest mpt snapshot in the isolated db can't be moved back.
+    if snapshots_above > 0 && storage_conf.use_isolated_db_for_mpt_table {
+        return Err(format!(
+            "Cannot roll back to epoch {} before {} snapshots when the mpt \
+             table is kept in an isolated db",
+            epoch, snapshots_above
+        ));
+    }
+    let removed_snapshots =
+        PersistedSnapshotInfoMap::remove_snapshot_infos_above(
+            &storage_conf.path_snapshot_info_db,
+            epoch,
+        )
+        .map_err(|e| format!("Failed to remove snapshot infos: {}", e))?;
+
+    let removed_epochs = remove_executed_epochs_after(db_manager, epoch);
+    Ok(format!(
+        "Rolled back to epoch {}, removed the execution data of {} epochs \
+         and {} snapshots",
+        epoch,
+        removed_epochs,
+        removed_snapshots.len()
+    ))
+}
+
+/// Remove everything written by the execution of the epochs after `epoch`:
+/// the execution commitments, contexts and witnesses of the pivot blocks, the
+/// receipts, rewards, traces, transaction indices and block numbers of the
+/// blocks, and the executed and skipped epoch sets. Returns the number of
+/// epochs removed.
+fn remove_executed_epochs_after(db_manager: &DBManager, epoch: u64) -> u64 {
+    let mut next_epoch = epoch + 1;
+    while let Some(epoch_set) = db_manager
+        .executed_epoch_set_hashes_from_db(next_epoch)
+        .filter(|set| !set.is_empty())
+    {
+        let pivot = *epoch_set.last().unwrap();
+        if let Some(context) = db_manager.execution_context_from_db(&pivot) {
+            for index in 0..epoch_set.len() as u64 {
+                db_manager.remove_hash_by_block_number_from_db(
+                    compute_block_number(context.start_block_number, index),
+                );
+            }
+        }
+        for hash in &epoch_set {
+            if let Some(block) = db_manager.block_from_db(hash) {
+                for tx in &block.transactions {
+                    // The index points to the block where the transaction is
+                    // executed, which may be in another epoch.
+                    if db_manager
+                        .transaction_index_from_db(&tx.hash())
+                        .map_or(false, |index| {
+                            epoch_set.contains(&index.block_hash)
+                        })
+                    {
+                        db_manager.remove_transaction_index_from_db(&tx.hash());
+                    }
+                }
+            }
+            db_manager.remove_block_execution_result_from_db(hash);
+            db_manager.remove_block_reward_result_from_db(hash);
+            db_manager.remove_block_trace_from_db(hash);
+        }
+        db_manager.remove_epoch_execution_commitment_from_db(&pivot);
+        db_manager.remove_epoch_execution_context_from_db(&pivot);
+        db_manager.remove_epoch_execution_witness_from_db(&pivot);
+        db_manager.remove_executed_epoch_set_hashes_from_db(next_epoch);
+        db_manager.remove_skipped_epoch_set_hashes_from_db(next_epoch);
+        next_epoch += 1;
+    }
+    next_epoch - epoch - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_epochs, remove_executed_epochs_after, NUM_COLUMNS};
+    use cfx_types::{Bloom, H256, U256};
+    use cfxcore::{
+        block_data_manager::{
+            db_manager::DBManager, BlockExecutionResult, DataVersionTuple,
+            EpochExecutionContext,
+        },
+        pow::PowComputer,
+    };
+    use primitives::{Block, BlockHeaderBuilder, BlockReceipts};
+    use std::sync::Arc;
+    use tempdir::TempDir;
+
+    fn new_db_manager(dir: &TempDir) -> DBManager {
+        let db_config = db::db_config(
+            dir.path(),
+            None,
+            Default::default(),
+            NUM_COLUMNS,
+            false,
+        );
+        let system_db =
+            db::open_database(dir.path().to_str().unwrap(), &db_config)
+                .unwrap();
+        DBManager::new_from_rocksdb(
+            system_db,
+            Arc::new(PowComputer::new(false)),
+        )
+    }
+
+    /// Insert a pivot chain of single-block epochs `0..epochs`, all executed.
+    fn insert_executed_chain(db_manager: &DBManager, epochs: u64) -> Vec<H256> {
+        let mut hashes: Vec<H256> = vec![];
+        for height in 0..epochs {
+            let header = BlockHeaderBuilder::new()
+                .with_parent_hash(hashes.last().cloned().unwrap_or_default())
+                .with_height(height)
+                .build();
+            let hash = header.hash();
+            let block = Block::new(header, vec![]);
+            db_manager.insert_block_header_to_db(&block.block_header);
+            db_manager.insert_block_body_to_db(&block);
+            db_manager
+                .insert_executed_epoch_set_hashes_to_db(height, &vec![hash]);
+            db_manager.insert_execution_context_to_db(
+                &hash,
+                &EpochExecutionContext {
+                    start_block_number: height,
+                },
+            );
+            db_manager.insert_hash_by_block_number_to_db(height, &hash);
+            db_manager.insert_block_execution_result_to_db(
+                &hash,
+                &DataVersionTuple(
+                    hash,
+                    BlockExecutionResult {
+                        block_receipts: Arc::new(BlockReceipts {
+                            receipts: vec![],
+                            block_number: height,
+                            secondary_reward: U256::zero(),
+                            tx_execution_error_messages: vec![],
+                        }),
+                        bloom: Bloom::zero(),
+                    },
+                ),
+            );
+            hashes.push(hash);
+        }
+        hashes
+    }
+
+    #[test]
+    fn test_check_epochs() {
+        let dir = TempDir::new("check_epochs").unwrap();
+        let db_manager = new_db_manager(&dir);
+        let hashes = insert_executed_chain(&db_manager, 10);
+        assert!(check_epochs(&db_manager, 0, None).is_ok());
+
+        // A missing pivot block is reported both as the pivot block and as
+        // a block of the epoch.
+        db_manager.remove_block_header_from_db(&hashes[5]);
+        let output = check_epochs(&db_manager, 0, Some(9)).unwrap_err();
+        assert!(output.contains("epoch 5: pivot block"));
+        assert!(output.contains("epoch 5: block"));
+        assert!(output.contains("2 errors"));
+
+        // The check ends at the first epoch without an executed set unless
+        // the end is given.
+        assert!(check_epochs(&db_manager, 6, None).is_ok());
+        let output = check_epochs(&db_manager, 6, Some(11)).unwrap_err();
+        assert!(output.contains("epoch 10: executed set missing"));
+        assert!(output.contains("epoch 11: executed set missing"));
+    }
+
+    #[test]
+    fn test_remove_executed_epochs_after() {
+        let dir = TempDir::new("rollback").unwrap();
+        let db_manager = new_db_manager(&dir);
+        let hashes = insert_executed_chain(&db_manager, 10);
+
+        assert_eq!(remove_executed_epochs_after(&db_manager, 6), 3);
+        for (height, hash) in hashes.iter().enumerate() {
+            let height = height as u64;
+            let kept = height <= 6;
+            assert_eq!(
+                db_manager
+                    .executed_epoch_set_hashes_from_db(height)
+                    .is_some(),
+                kept
+            );
+            assert_eq!(
+                db_manager.execution_context_from_db(hash).is_some(),
+                kept
+            );
+            assert_eq!(
+                db_manager.block_execution_result_from_db(hash).is_some(),
+                kept
+            );
+            assert_eq!(
+                db_manager.hash_by_block_number_from_db(&height).is_some(),
+                kept
+            );
+            // The blocks themselves are kept to execute them again.
+            assert!(db_manager.block_from_db(hash).is_some());
+        }
+        assert!(check_epochs(&db_manager, 0, None).is_ok());
+
+        // Nothing is left to remove.
+        assert_eq!(remove_executed_epochs_after(&db_manager, 6), 0);
+    }
+}
@@ -3,5 +3,7 @@
 // See http://www.gnu.org/licenses/
 
 pub mod account;
+pub mod db;
 pub mod helpers;
 pub mod rpc;
+pub mod state;
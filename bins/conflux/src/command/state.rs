@@ -0,0 +1,318 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Offline export of the state of a stopped node, and verification and import
+//! of snapshot archives.
+//!
+//! Snapshot archives are exported by a running node with `rpc local
+//! snapshot-export` only. The manifest of an archive carries the trusted blame
+//! block of the snapshot and the blame states up to it, which are found on the
+//! pivot chain of the consensus graph. A stopped node only has the blocks, and
+//! rebuilding the consensus graph replays all of them as the node does on
+//! startup, so exporting offline would cost as much as starting the node.
+
+use cfx_storage::{SnapshotDbManager, SnapshotInspector};
+use cfx_types::{Address, H256};
+use cfxcore::sync::{
+    snapshot_archive_file_name, verify_snapshot_archive,
+    SnapshotArchiveManifest,
+};
+use clap;
+use client::configuration::Configuration;
+use primitives::{
+    Account, CheckInput, CodeInfo, StorageKey, StorageKeyWithSpace,
+    StorageValue,
+};
+use rlp::Rlp;
+use serde_json::{json, Value};
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum StateCmd {
+    Dump {
+        snapshot: Option<H256>,
+        output: PathBuf,
+    },
+    VerifyArchive {
+        archive: PathBuf,
+    },
+    ImportArchive {
+        archive: PathBuf,
+    },
+}
+
+impl StateCmd {
+    pub fn new(matches: &clap::ArgMatches) -> Result<Self, String> {
+        let cmd = match matches.subcommand() {
+            ("dump", Some(m)) => StateCmd::Dump {
+                snapshot: match m.value_of("snapshot") {
+                    Some(hash) => Some(
+                        H256::from_str(hash.trim_start_matches("0x")).map_err(
+                            |e| format!("Invalid hash {}: {:?}", hash, e),
+                        )?,
+                    ),
+                    None => None,
+                },
+                output: m
+                    .value_of("output")
+                    .expect("CLI argument is required; qed")
+                    .into(),
+            },
+            ("verify-archive", Some(m)) => StateCmd::VerifyArchive {
+                archive: m
+                    .value_of("archive")
+                    .expect("CLI argument is required; qed")
+                    .into(),
+            },
+            ("import-archive", Some(m)) => StateCmd::ImportArchive {
+                archive: m
+                    .value_of("archive")
+                    .expect("CLI argument is required; qed")
+                    .into(),
+            },
+            _ => unreachable!(),
+        };
+        Ok(cmd)
+    }
+}
+
+/// The snapshots are opened in place, so the node must be stopped.
+pub fn execute(conf: Configuration, cmd: StateCmd) -> Result<String, String> {
+    match cmd {
+        StateCmd::Dump { snapshot, output } => dump(&conf, snapshot, output),
+        StateCmd::VerifyArchive { archive } => verify_archive(&conf, archive),
+        StateCmd::ImportArchive { archive } => import_archive(&conf, archive),
+    }
+}
+
+/// Write all key-values of a snapshot as JSON lines, in the order of the keys.
+/// The latest available snapshot is dumped if none is given.
+///
+/// Note that a snapshot is the state of the epoch `snapshot_epoch_count`
+/// epochs before the latest one, as the newer state lives in the delta mpts.
+fn dump(
+    conf: &Configuration, snapshot: Option<H256>, path: PathBuf,
+) -> Result<String, String> {
+    let inspector =
+        SnapshotInspector::open(&conf.storage_config(&conf.node_type()))
+            .map_err(|e| format!("Failed to open snapshots: {}", e))?;
+    let candidates: Vec<_> = match snapshot {
+        Some(snapshot_epoch_id) => {
+            vec![inspector.snapshot_info(&snapshot_epoch_id).ok_or_else(
+                || format!("Snapshot {:?} not found", snapshot_epoch_id),
+            )?]
+        }
+        None => inspector.snapshot_infos().into_iter().rev().collect(),
+    };
+
+    let file = File::create(&path)
+        .map_err(|e| format!("Failed to create {:?}: {:?}", path, e))?;
+    let mut writer = BufWriter::new(file);
+    for info in candidates {
+        let snapshot_epoch_id = info.get_snapshot_epoch_id();
+        let mut key_value_count = 0u64;
+        let dumped = inspector
+            .iterate_snapshot(snapshot_epoch_id, |key, value| {
+                let line = key_value_to_json(key, value)?;
+                writeln!(writer, "{}", line)?;
+                key_value_count += 1;
+                Ok(())
+            })
+            .map_err(|e| {
+                format!(
+                    "Failed to dump snapshot {:?}: {}",
+                    snapshot_epoch_id, e
+                )
+            })?;
+        if dumped {
+            writer
+                .flush()
+                .map_err(|e| format!("Failed to write {:?}: {:?}", path, e))?;
+            return Ok(format!(
+                "Dumped {} key-values of snapshot {:?} at height {} to {:?}",
+                key_value_count, snapshot_epoch_id, info.height, path
+            ));
+        }
+    }
+    Err("No snapshot available".into())
+}
+
+/// Restore a snapshot archive into a scratch directory next to it, which
+/// checks all the chunks against the merkle root of the snapshot.
+///
+/// The merkle root itself is only checked against the blame states of the
+/// chain by a node restoring the archive.
+fn verify_archive(
+    conf: &Configuration, archive: PathBuf,
+) -> Result<String, String> {
+    let mut work_dir = archive.clone().into_os_string();
+    work_dir.push(".verify");
+    let manifest = check_archive(conf, &archive, &PathBuf::from(work_dir))?;
+    Ok(format!(
+        "Snapshot {:?} at height {} with merkle root {:?} verified, {} chunks",
+        manifest.snapshot_to_sync.get_snapshot_epoch_id(),
+        manifest.snapshot_to_sync.height(),
+        manifest.snapshot_merkle_root,
+        manifest.number_chunks()
+    ))
+}
+
+/// Verify a snapshot archive as `verify-archive` does and copy it to the
+/// snapshot archive directory of the node, from which the node restores the
+/// snapshot when it syncs the snapshot.
+fn import_archive(
+    conf: &Configuration, archive: PathBuf,
+) -> Result<String, String> {
+    let archive_dir = conf.snapshot_archive_dir();
+    fs::create_dir_all(&archive_dir)
+        .map_err(|e| format!("Failed to create {:?}: {:?}", archive_dir, e))?;
+    let manifest =
+        check_archive(conf, &archive, &archive_dir.join("import.verify"))?;
+
+    let snapshot_epoch_id = manifest.snapshot_to_sync.get_snapshot_epoch_id();
+    let path = archive_dir.join(snapshot_archive_file_name(snapshot_epoch_id));
+    // The node never restores a partially copied archive.
+    let mut partial_path = path.clone().into_os_string();
+    partial_path.push(".partial");
+    fs::copy(&archive, &partial_path).map_err(|e| {
+        format!(
+            "Failed to copy {:?} to {:?}: {:?}",
+            archive, partial_path, e
+        )
+    })?;
+    fs::rename(&partial_path, &path).map_err(|e| {
+        format!("Failed to rename {:?} to {:?}: {:?}", partial_path, path, e)
+    })?;
+    Ok(format!(
+        "Snapshot {:?} at height {} imported to {:?}, restored when the node \
+         syncs the snapshot",
+        snapshot_epoch_id,
+        manifest.snapshot_to_sync.height(),
+        path
+    ))
+}
+
+/// Restore a snapshot archive into the scratch directory `work_dir`, which is
+/// removed afterwards.
+fn check_archive(
+    conf: &Configuration, archive: &Path, work_dir: &Path,
+) -> Result<SnapshotArchiveManifest, String> {
+    let file = File::open(archive)
+        .map_err(|e| format!("Failed to open {:?}: {:?}", archive, e))?;
+    if work_dir.exists() {
+        return Err(format!("{:?} exists, remove it first", work_dir));
+    }
+
+    let result = SnapshotDbManager::new(
+        conf.raw_conf.snapshot_db_backend,
+        work_dir.join("snapshot"),
+        /* max_open_snapshots = */ 1,
+        /* use_isolated_db_for_mpt_table = */ false,
+        /* use_isolated_db_for_mpt_table_height = */ None,
+        conf.raw_conf.era_epoch_count,
+    )
+    .map_err(|e| format!("Failed to create {:?}: {}", work_dir, e))
+    .and_then(|snapshot_db_manager| {
+        verify_snapshot_archive(BufReader::new(file), &snapshot_db_manager)
+            .map_err(|e| format!("Invalid snapshot archive: {}", e))
+    });
+    let removed = fs::remove_dir_all(work_dir);
+
+    // A failure to remove the scratch directory doesn't hide the result.
+    let manifest = result?;
+    removed.map_err(|e| format!("Failed to remove {:?}: {:?}", work_dir, e))?;
+    Ok(manifest)
+}
+
+fn to_hex(bytes: &[u8]) -> String { format!("0x{}", hex::encode(bytes)) }
+
+/// Convert a key-value of the state to a JSON object with the space and the
+/// address it belongs to.
+fn key_value_to_json(key: &[u8], value: &[u8]) -> cfx_storage::Result<Value> {
+    let access_key = StorageKeyWithSpace::from_key_bytes::<CheckInput>(key)?;
+    let (address_bytes, mut entry) = match access_key.key {
+        StorageKey::AccountKey(address_bytes) => {
+            let account = Account::new_from_rlp(
+                Address::from_slice(address_bytes),
+                &Rlp::new(value),
+            )
+            .map_err(|e| format!("Invalid account {:?}: {:?}", key, e))?;
+            let mut entry = json!({
+                "type": "account",
+                "balance": account.balance,
+                "nonce": account.nonce,
+                "codeHash": account.code_hash,
+            });
+            if access_key.space == cfx_types::Space::Native {
+                let native = json!({
+                    "stakingBalance": account.staking_balance,
+                    "collateralForStorage": account.collateral_for_storage,
+                    "accumulatedInterestReturn":
+                        account.accumulated_interest_return,
+                    "admin": account.admin,
+                });
+                entry
+                    .as_object_mut()
+                    .unwrap()
+                    .extend(native.as_object().unwrap().clone());
+            }
+            (address_bytes, entry)
+        }
+        StorageKey::StorageKey {
+            address_bytes,
+            storage_key,
+        } => {
+            let storage_value = rlp::decode::<StorageValue>(value)?;
+            (
+                address_bytes,
+                json!({
+                    "type": "storage",
+                    "key": to_hex(storage_key),
+                    "value": storage_value.value,
+                    "owner": storage_value.owner,
+                }),
+            )
+        }
+        StorageKey::CodeKey {
+            address_bytes,
+            code_hash_bytes,
+        } => {
+            let code_info = rlp::decode::<CodeInfo>(value)?;
+            (
+                address_bytes,
+                json!({
+                    "type": "code",
+                    "codeHash": to_hex(code_hash_bytes),
+                    "code": to_hex(&code_info.code),
+                    "owner": code_info.owner,
+                }),
+            )
+        }
+        StorageKey::StorageRootKey(address_bytes) => (
+            address_bytes,
+            json!({ "type": "storageLayout", "value": to_hex(value) }),
+        ),
+        StorageKey::CodeRootKey(address_bytes) => (
+            address_bytes,
+            json!({ "type": "codeRoot", "value": to_hex(value) }),
+        ),
+        StorageKey::DepositListKey(address_bytes) => (
+            address_bytes,
+            json!({ "type": "depositList", "value": to_hex(value) }),
+        ),
+        StorageKey::VoteListKey(address_bytes) => (
+            address_bytes,
+            json!({ "type": "voteList", "value": to_hex(value) }),
+        ),
+    };
+    let object = entry.as_object_mut().unwrap();
+    object.insert("space".into(), json!(access_key.space));
+    object.insert("address".into(), json!(to_hex(address_bytes)));
+    Ok(entry)
+}
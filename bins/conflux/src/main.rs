@@ -17,7 +17,11 @@ use client::{
     full::FullClient,
     light::LightClient,
 };
-use command::account::{AccountCmd, ImportAccounts, ListAccounts, NewAccount};
+use command::{
+    account::{AccountCmd, ImportAccounts, ListAccounts, NewAccount},
+    db::DbCmd,
+    state::StateCmd,
+};
 use log::{info, LevelFilter};
 use log4rs::{
     append::{console::ConsoleAppender, file::FileAppender},
@@ -202,6 +206,22 @@ fn handle_sub_command(matches: &ArgMatches) -> Result<Option<String>, String> {
         return Ok(Some(execute_output));
     }
 
+    // db sub-commands
+    if let ("db", Some(db_matches)) = matches.subcommand() {
+        let conf = Configuration::parse(matches)?;
+        let db_cmd = DbCmd::new(db_matches)?;
+        let execute_output = command::db::execute(conf, db_cmd)?;
+        return Ok(Some(execute_output));
+    }
+
+    // state sub-commands
+    if let ("state", Some(state_matches)) = matches.subcommand() {
+        let conf = Configuration::parse(matches)?;
+        let state_cmd = StateCmd::new(state_matches)?;
+        let execute_output = command::state::execute(conf, state_cmd)?;
+        return Ok(Some(execute_output));
+    }
+
     // general RPC commands
     let mut subcmd_matches = matches;
     while let Some(m) = subcmd_matches.subcommand().1 {
@@ -22,7 +22,7 @@ use cfxcore::{
 use lazy_static::lazy_static;
 use log::{debug, trace, warn};
 use metrics::{Gauge, GaugeUsize};
-use parking_lot::{Mutex, RwLock};
+use parking_lot::{Mutex, MutexGuard, RwLock};
 use primitives::{pos::PosBlockId, *};
 use std::{
     cmp::max,
@@ -52,6 +52,15 @@ enum MiningState {
     Stop,
 }
 
+/// The block timestamp controls of the dev mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DevClock {
+    /// Seconds added to the system time.
+    offset_secs: u64,
+    /// The exact timestamp of the next generated block.
+    next_timestamp: Option<u64>,
+}
+
 /// The interface for a conflux block generator
 pub struct BlockGenerator {
     pub pow_config: ProofOfWorkConfig,
@@ -65,6 +74,13 @@ pub struct BlockGenerator {
     workers: Mutex<Vec<(Worker, mpsc::Sender<ProofOfWorkProblem>)>>,
     pub stratum: RwLock<Option<Stratum>>,
     pos_verifier: Arc<PosVerifier>,
+    dev_clock: Mutex<DevClock>,
+    /// Held while the dev mode generates blocks, so the automatic block
+    /// generation and the dev RPCs do not mine concurrently.
+    dev_mining: Mutex<()>,
+    /// The blocks that new blocks should not reference, used by the dev mode
+    /// to abandon the blocks after a reverted snapshot.
+    excluded_referees: RwLock<HashSet<H256>>,
 }
 
 pub struct Worker {
@@ -162,6 +178,9 @@ impl BlockGenerator {
             workers: Mutex::new(Vec::new()),
             stratum: RwLock::new(None),
             pos_verifier,
+            dev_clock: Default::default(),
+            dev_mining: Default::default(),
+            excluded_referees: Default::default(),
         }
     }
 
@@ -223,6 +242,10 @@ impl BlockGenerator {
         // checkpoint making that happens before we acquire the inner lock
         referees
             .retain(|h| consensus_inner.hash_to_arena_indices.contains_key(h));
+        {
+            let excluded_referees = self.excluded_referees.read();
+            referees.retain(|h| !excluded_referees.contains(h));
+        }
         let mut expected_difficulty =
             consensus_inner.expected_difficulty(&parent_hash);
         let adaptive = if let Some(x) = adaptive_opt {
@@ -249,10 +272,7 @@ impl BlockGenerator {
             expected_difficulty = U256::from(difficulty);
         }
 
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let now = self.current_timestamp();
 
         // Adjust the timestamp of the currently mined block to be later
         // than or equal to its parent's.
@@ -753,6 +773,60 @@ impl BlockGenerator {
 
     pub fn pow_config(&self) -> ProofOfWorkConfig { self.pow_config.clone() }
 
+    /// Returns the timestamp for the next generated block, which is the system
+    /// time unless it is changed by the dev mode time controls.
+    fn current_timestamp(&self) -> u64 {
+        let mut dev_clock = self.dev_clock.lock();
+        if let Some(timestamp) = dev_clock.next_timestamp.take() {
+            return timestamp;
+        }
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + dev_clock.offset_secs
+    }
+
+    /// Moves the clock of the generated blocks forward by `secs` seconds, and
+    /// returns the total offset from the system time.
+    pub fn increase_time(&self, secs: u64) -> u64 {
+        let mut dev_clock = self.dev_clock.lock();
+        dev_clock.offset_secs += secs;
+        dev_clock.offset_secs
+    }
+
+    /// Sets the timestamp of the next generated block, and the clock of the
+    /// blocks after it continues from there. The timestamp is still adjusted
+    /// to be no earlier than the parent's.
+    pub fn set_next_block_timestamp(&self, timestamp: u64) {
+        let system_now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut dev_clock = self.dev_clock.lock();
+        dev_clock.offset_secs =
+            max(dev_clock.offset_secs, timestamp.saturating_sub(system_now));
+        dev_clock.next_timestamp = Some(timestamp);
+    }
+
+    /// Returns the dev mode clock, to be restored by `restore_dev_clock`.
+    pub fn dev_clock(&self) -> DevClock { *self.dev_clock.lock() }
+
+    pub fn restore_dev_clock(&self, dev_clock: DevClock) {
+        *self.dev_clock.lock() = dev_clock;
+    }
+
+    /// Pauses the automatic block generation of the dev mode until the
+    /// returned guard is dropped.
+    pub fn lock_dev_mining(&self) -> MutexGuard<'_, ()> {
+        self.dev_mining.lock()
+    }
+
+    /// Excludes `hashes` from the referees of the blocks generated later.
+    pub fn exclude_referees(&self, hashes: impl IntoIterator<Item = H256>) {
+        self.excluded_referees.write().extend(hashes);
+    }
+
     /// Start num_worker new workers
     pub fn start_new_worker(
         num_worker: u32, bg: Arc<BlockGenerator>,
@@ -945,6 +1019,7 @@ impl BlockGenerator {
                 _ => {}
             }
             if !self.sync.catch_up_mode() {
+                let _dev_mining = self.lock_dev_mining();
                 self.generate_block(
                     3000,
                     self.graph.verification_config.max_block_size_in_bytes,
@@ -1585,6 +1585,7 @@ impl TxReplayer {
 
     pub fn new(
         conflux_data_dir: &str, reset_db: bool, debug_snapshot_integrity: bool,
+        flat_state_cache_size: usize,
     ) -> errors::Result<TxReplayer> {
         if reset_db {
             match fs::remove_dir_all(conflux_data_dir) {
@@ -1603,6 +1604,7 @@ impl TxReplayer {
         if debug_snapshot_integrity {
             storage_configuration.debug_snapshot_checker_threads = 4;
         }
+        storage_configuration.flat_state_cache_size = flat_state_cache_size;
         let storage_manager =
             Arc::new(StorageManager::new(storage_configuration)?);
 
@@ -1857,6 +1859,9 @@ fn tx_replay(matches: ArgMatches) -> errors::Result<()> {
         matches.value_of("conflux_data_dir").unwrap(),
         matches.occurrences_of("reset_db") > 0,
         matches.occurrences_of("debug_check_snapshot_integrity") > 0,
+        matches
+            .value_of("flat_state_cache_size")
+            .map_or(0, |value| value.parse::<usize>().unwrap()),
     )?;
 
     let txs_to_process = match matches.value_of("txs_to_process") {
@@ -2117,6 +2122,13 @@ fn main() -> errors::Result<()> {
                 .long("skip")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("flat_state_cache_size")
+                .value_name("number of keys in the flat state cache")
+                .help("number of keys in the flat state cache, 0 to disable")
+                .long("flat_state_cache_size")
+                .takes_value(true),
+        )
         .get_matches_from(std::env::args().collect::<Vec<_>>());
 
     let command = matches
@@ -1,3 +1,4 @@
+use crate::consensus::consensus_inner::consensus_executor::EpochExecutionWitness;
 use cfx_execute_helper::exec_tracer::BlockExecTraces;
 use cfx_internal_common::{DatabaseDecodable, DatabaseEncodable};
 use cfx_types::{Address, Bloom, H256, U256};
@@ -388,3 +389,4 @@ impl_db_encoding_as_rlp!(EpochExecutionContext);
 impl_db_encoding_as_rlp!(BlockRewardResult);
 impl_db_encoding_as_rlp!(BlamedHeaderVerifiedRoots);
 impl_db_encoding_as_rlp!(PosRewardInfo);
+impl_db_encoding_as_rlp!(EpochExecutionWitness);
@@ -0,0 +1,587 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Online checkpoints of the databases of a running node.
+//!
+//! A checkpoint is a directory with the layout of the conflux data dir: the
+//! block db under `blockchain_db`, the snapshot info db, the snapshots and the
+//! delta mpts under `storage_db`, and the PoS ledger db and the PoS consensus
+//! db under `pos_db`. RocksDB databases are copied with RocksDB checkpoints,
+//! and SQLite databases with the SQLite online backup api.
+//!
+//! The execution of epochs is paused while the mutable databases are copied,
+//! so the state and the execution commitments in the checkpoint are at the
+//! same epoch. The snapshots are immutable, so they are kept open and copied
+//! after the execution is resumed. The PoS databases are copied as they are at
+//! that moment, as if the node stopped uncleanly.
+//!
+//! The manifest lists every file of the checkpoint with its size and its
+//! keccak checksum, so that an incomplete or modified checkpoint is rejected on
+//! restore.
+
+use crate::{
+    block_data_manager::BlockDataManager, pos::consensus::ConsensusDB,
+};
+use cfx_storage::storage_dir;
+use cfx_types::H256;
+use parking_lot::RwLockWriteGuard;
+use pos_ledger_db::PosLedgerDB;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tiny_keccak::{Hasher, Keccak};
+
+pub const CHECKPOINT_MANIFEST_FILE: &str = "checkpoint_manifest.json";
+pub const CHECKPOINT_VERSION: u32 = 2;
+/// The block db in a checkpoint.
+pub const CHECKPOINT_BLOCK_DB_DIR: &str = "blockchain_db";
+/// The PoS ledger db and the PoS consensus db in a checkpoint, which are
+/// restored into the PoS storage dir.
+pub const CHECKPOINT_POS_DB_DIR: &str = "pos_db";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointManifest {
+    pub version: u32,
+    /// Seconds since the unix epoch.
+    pub created_at: u64,
+    /// The last epoch computed before the execution is paused, None if no
+    /// epoch is computed since the node started.
+    pub epoch_hash: Option<H256>,
+    pub epoch_height: Option<u64>,
+    pub state_root: Option<H256>,
+    pub snapshot_epoch_ids: Vec<H256>,
+    /// The snapshot epochs on top of which the delta mpts are.
+    pub delta_mpt_epoch_ids: Vec<H256>,
+    /// Whether the PoS databases are in the checkpoint.
+    pub pos_db: bool,
+    pub files: Vec<CheckpointFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CheckpointFile {
+    /// Relative to the checkpoint dir, separated by `/`.
+    pub path: String,
+    pub size: u64,
+    /// The keccak hash of the content.
+    pub checksum: H256,
+}
+
+impl CheckpointManifest {
+    pub fn load(checkpoint_dir: &Path) -> Result<Self, String> {
+        let path = checkpoint_dir.join(CHECKPOINT_MANIFEST_FILE);
+        let file = File::open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("Invalid manifest {}: {}", path.display(), e))
+    }
+
+    /// Check that the checkpoint dir has all the files in the manifest with
+    /// the recorded sizes and checksums, and nothing else.
+    pub fn validate(&self, checkpoint_dir: &Path) -> Result<(), String> {
+        if self.version != CHECKPOINT_VERSION {
+            return Err(format!(
+                "Unsupported checkpoint version {}, expected {}",
+                self.version, CHECKPOINT_VERSION
+            ));
+        }
+        let files = list_files(checkpoint_dir)?;
+        for expected in &self.files {
+            match files.iter().find(|file| file.path == expected.path) {
+                None => {
+                    return Err(format!(
+                        "File {} is missing from the checkpoint",
+                        expected.path
+                    ))
+                }
+                Some(file) if file.size != expected.size => {
+                    return Err(format!(
+                        "File {} has {} bytes, but {} bytes are expected",
+                        expected.path, file.size, expected.size
+                    ))
+                }
+                Some(file) if file.checksum != expected.checksum => {
+                    return Err(format!(
+                        "File {} has checksum {:?}, but {:?} is expected",
+                        expected.path, file.checksum, expected.checksum
+                    ))
+                }
+                _ => {}
+            }
+        }
+        if let Some(unexpected) = files
+            .iter()
+            .find(|file| !self.files.iter().any(|f| f.path == file.path))
+        {
+            return Err(format!(
+                "File {} is not in the manifest",
+                unexpected.path
+            ));
+        }
+        let mut dirs = vec![
+            Path::new(CHECKPOINT_BLOCK_DB_DIR).to_path_buf(),
+            storage_dir::STORAGE_DIR.clone(),
+        ];
+        if self.pos_db {
+            dirs.extend(pos_db_dirs(Path::new(CHECKPOINT_POS_DB_DIR)));
+        }
+        for dir in &dirs {
+            if !checkpoint_dir.join(dir).is_dir() {
+                return Err(format!(
+                    "{} is missing from the checkpoint",
+                    dir.display()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Copy the block db, the storage and the PoS databases of a running node
+/// into `checkpoint_dir`, which must not exist, and write the manifest. The
+/// checkpoint dir is removed if the checkpoint fails.
+///
+/// `execution_pause` pauses the execution of epochs, with
+/// `last_computed_epoch` being the last epoch computed before the pause. It's
+/// released once the mutable databases are copied.
+pub fn create_checkpoint(
+    data_man: &BlockDataManager, checkpoint_dir: &Path,
+    execution_pause: RwLockWriteGuard<'_, ()>,
+    last_computed_epoch: Option<H256>,
+    pos_dbs: Option<(&PosLedgerDB, &ConsensusDB)>,
+) -> Result<CheckpointManifest, String> {
+    if checkpoint_dir.exists() {
+        return Err(format!("{} already exists", checkpoint_dir.display()));
+    }
+    fs::create_dir_all(checkpoint_dir).map_err(|e| {
+        format!("Failed to create {}: {}", checkpoint_dir.display(), e)
+    })?;
+    info!(
+        "Create checkpoint at {:?} after epoch {:?}",
+        checkpoint_dir, last_computed_epoch
+    );
+    write_checkpoint(
+        data_man,
+        checkpoint_dir,
+        execution_pause,
+        last_computed_epoch,
+        pos_dbs,
+    )
+    .map_err(|e| {
+        if let Err(remove_error) = fs::remove_dir_all(checkpoint_dir) {
+            warn!(
+                "Failed to remove the incomplete checkpoint {:?}: {}",
+                checkpoint_dir, remove_error
+            );
+        }
+        e
+    })
+}
+
+fn write_checkpoint(
+    data_man: &BlockDataManager, checkpoint_dir: &Path,
+    execution_pause: RwLockWriteGuard<'_, ()>,
+    last_computed_epoch: Option<H256>,
+    pos_dbs: Option<(&PosLedgerDB, &ConsensusDB)>,
+) -> Result<CheckpointManifest, String> {
+    let (storage_checkpoint, pinned_snapshots) = data_man
+        .storage_manager
+        .checkpoint(checkpoint_dir)
+        .map_err(|e| format!("Failed to checkpoint storage: {}", e))?;
+    data_man
+        .db_manager
+        .checkpoint(&checkpoint_dir.join(CHECKPOINT_BLOCK_DB_DIR))?;
+    if let Some((pos_ledger_db, consensus_db)) = pos_dbs {
+        let pos_db_dir = checkpoint_dir.join(CHECKPOINT_POS_DB_DIR);
+        fs::create_dir_all(&pos_db_dir).map_err(|e| {
+            format!("Failed to create {}: {}", pos_db_dir.display(), e)
+        })?;
+        pos_ledger_db.checkpoint(&pos_db_dir).map_err(|e| {
+            format!("Failed to checkpoint the PoS ledger db: {}", e)
+        })?;
+        consensus_db.checkpoint(&pos_db_dir).map_err(|e| {
+            format!("Failed to checkpoint the PoS consensus db: {}", e)
+        })?;
+    }
+
+    let (epoch_height, state_root) = match &last_computed_epoch {
+        Some(epoch_hash) => (
+            data_man
+                .block_header_by_hash(epoch_hash)
+                .map(|header| header.height()),
+            data_man
+                .get_epoch_execution_commitment_with_db(epoch_hash)
+                .map(|commitment| {
+                    commitment
+                        .state_root_with_aux_info
+                        .state_root
+                        .compute_state_root_hash()
+                }),
+        ),
+        None => (None, None),
+    };
+    drop(execution_pause);
+
+    data_man
+        .storage_manager
+        .checkpoint_snapshots(pinned_snapshots)
+        .map_err(|e| format!("Failed to checkpoint snapshots: {}", e))?;
+
+    let manifest = CheckpointManifest {
+        version: CHECKPOINT_VERSION,
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        epoch_hash: last_computed_epoch,
+        epoch_height,
+        state_root,
+        snapshot_epoch_ids: storage_checkpoint.snapshot_epoch_ids,
+        delta_mpt_epoch_ids: storage_checkpoint.delta_mpt_epoch_ids,
+        pos_db: pos_dbs.is_some(),
+        files: list_files(checkpoint_dir)?,
+    };
+    let manifest_path = checkpoint_dir.join(CHECKPOINT_MANIFEST_FILE);
+    File::create(&manifest_path)
+        .map_err(|e| e.to_string())
+        .and_then(|file| {
+            serde_json::to_writer_pretty(file, &manifest)
+                .map_err(|e| e.to_string())
+        })
+        .map_err(|e| {
+            format!("Failed to write {}: {}", manifest_path.display(), e)
+        })?;
+    Ok(manifest)
+}
+
+/// Replace the block db at `block_db_dir`, the storage of `data_dir` and the
+/// PoS databases in `pos_db_dir` with the checkpoint. `pos_db_dir` is only
+/// required when the checkpoint has the PoS databases. The node must be
+/// stopped.
+///
+/// The databases are copied next to the targets with a `.restoring` suffix
+/// first, and only renamed into place once all of them are copied. The
+/// replaced databases are kept with a `.before_restore` suffix.
+pub fn restore_checkpoint(
+    checkpoint_dir: &Path, data_dir: &Path, block_db_dir: &Path,
+    pos_db_dir: Option<&Path>,
+) -> Result<CheckpointManifest, String> {
+    let manifest = CheckpointManifest::load(checkpoint_dir)?;
+    manifest.validate(checkpoint_dir)?;
+
+    let mut targets = vec![
+        (
+            checkpoint_dir.join(CHECKPOINT_BLOCK_DB_DIR),
+            block_db_dir.to_path_buf(),
+        ),
+        (
+            checkpoint_dir.join(&*storage_dir::STORAGE_DIR),
+            data_dir.join(&*storage_dir::STORAGE_DIR),
+        ),
+    ];
+    if manifest.pos_db {
+        let pos_db_dir = pos_db_dir.ok_or(
+            "The checkpoint has the PoS databases, but the PoS storage dir \
+             is unknown",
+        )?;
+        targets.extend(
+            pos_db_dirs(&checkpoint_dir.join(CHECKPOINT_POS_DB_DIR))
+                .into_iter()
+                .zip(pos_db_dirs(pos_db_dir)),
+        );
+    }
+    for (_, target) in &targets {
+        let backup = backup_path(target);
+        if backup.exists() {
+            return Err(format!(
+                "{} already exists, remove it before restoring",
+                backup.display()
+            ));
+        }
+    }
+
+    for (source, target) in &targets {
+        let restoring = restoring_path(target);
+        let copied = remove_dir_if_exists(&restoring)
+            .and_then(|()| copy_dir(source, &restoring));
+        if let Err(e) = copied {
+            for (_, target) in &targets {
+                if let Err(e) = remove_dir_if_exists(&restoring_path(target)) {
+                    warn!("{}", e);
+                }
+            }
+            return Err(e);
+        }
+    }
+    for (_, target) in &targets {
+        if target.exists() {
+            fs::rename(target, backup_path(target)).map_err(|e| {
+                format!("Failed to move {} aside: {}", target.display(), e)
+            })?;
+        }
+        fs::rename(restoring_path(target), target).map_err(|e| {
+            format!("Failed to move the restored {}: {}", target.display(), e)
+        })?;
+    }
+    Ok(manifest)
+}
+
+/// The PoS ledger db and the PoS consensus db in the PoS storage dir `dir`.
+fn pos_db_dirs(dir: &Path) -> Vec<PathBuf> {
+    vec![
+        dir.join(PosLedgerDB::DB_NAME),
+        dir.join(ConsensusDB::DB_NAME),
+    ]
+}
+
+fn backup_path(path: &Path) -> PathBuf { with_suffix(path, ".before_restore") }
+
+fn restoring_path(path: &Path) -> PathBuf { with_suffix(path, ".restoring") }
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    path.into()
+}
+
+fn remove_dir_if_exists(dir: &Path) -> Result<(), String> {
+    if dir.exists() {
+        fs::remove_dir_all(dir).map_err(|e| {
+            format!("Failed to remove {}: {}", dir.display(), e)
+        })?;
+    }
+    Ok(())
+}
+
+fn copy_dir(source: &Path, target: &Path) -> Result<(), String> {
+    fs::create_dir_all(target)
+        .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+    let entries = fs::read_dir(source)
+        .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let target_path = target.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &target_path)?;
+        } else {
+            fs::copy(&path, &target_path).map_err(|e| {
+                format!("Failed to copy {}: {}", path.display(), e)
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// The keccak hash of the content of the file at `path`.
+fn file_checksum(path: &Path) -> Result<H256, String> {
+    let mut file = File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Keccak::v256();
+    let mut buf = vec![0u8; 1 << 20];
+    loop {
+        let read = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    let mut checksum = H256::zero();
+    hasher.finalize(checksum.as_bytes_mut());
+    Ok(checksum)
+}
+
+/// All files under `dir` except the manifest, sorted by path.
+fn list_files(dir: &Path) -> Result<Vec<CheckpointFile>, String> {
+    fn visit(
+        dir: &Path, prefix: &str, files: &mut Vec<CheckpointFile>,
+    ) -> Result<(), String> {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| e.to_string())?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let path = format!("{}{}", prefix, name);
+            let metadata = entry.metadata().map_err(|e| e.to_string())?;
+            if metadata.is_dir() {
+                visit(&entry.path(), &format!("{}/", path), files)?;
+            } else if path != CHECKPOINT_MANIFEST_FILE {
+                files.push(CheckpointFile {
+                    path,
+                    size: metadata.len(),
+                    checksum: file_checksum(&entry.path())?,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    visit(dir, "", &mut files)?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keccak_hash::keccak;
+    use tempdir::TempDir;
+
+    fn write_file(path: &Path, content: &[u8]) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn make_checkpoint(dir: &Path, pos_db: bool) -> CheckpointManifest {
+        write_file(&dir.join("blockchain_db/CURRENT"), b"MANIFEST-000001");
+        write_file(&dir.join("storage_db/snapshot_info_db"), b"sqlite");
+        write_file(&dir.join("storage_db/delta_mpts/rocksdb_00/CURRENT"), b"");
+        if pos_db {
+            write_file(&dir.join("pos_db/pos-ledger-db/CURRENT"), b"ledger");
+            write_file(&dir.join("pos_db/consensusdb/CURRENT"), b"consensus");
+        }
+        let manifest = CheckpointManifest {
+            version: CHECKPOINT_VERSION,
+            created_at: 0,
+            epoch_hash: None,
+            epoch_height: None,
+            state_root: None,
+            snapshot_epoch_ids: vec![],
+            delta_mpt_epoch_ids: vec![],
+            pos_db,
+            files: list_files(dir).unwrap(),
+        };
+        fs::write(
+            dir.join(CHECKPOINT_MANIFEST_FILE),
+            serde_json::to_vec(&manifest).unwrap(),
+        )
+        .unwrap();
+        manifest
+    }
+
+    #[test]
+    fn test_validate_checkpoint() {
+        let dir = TempDir::new("checkpoint").unwrap();
+        let manifest = make_checkpoint(dir.path(), /* pos_db = */ false);
+        assert_eq!(
+            manifest.files,
+            vec![
+                CheckpointFile {
+                    path: "blockchain_db/CURRENT".into(),
+                    size: 15,
+                    checksum: keccak(b"MANIFEST-000001"),
+                },
+                CheckpointFile {
+                    path: "storage_db/delta_mpts/rocksdb_00/CURRENT".into(),
+                    size: 0,
+                    checksum: keccak(b""),
+                },
+                CheckpointFile {
+                    path: "storage_db/snapshot_info_db".into(),
+                    size: 6,
+                    checksum: keccak(b"sqlite"),
+                },
+            ]
+        );
+        manifest.validate(dir.path()).unwrap();
+
+        write_file(&dir.path().join("storage_db/snapshot_info_db"), b"sql");
+        assert!(manifest.validate(dir.path()).is_err());
+        // Same size, another content.
+        write_file(&dir.path().join("storage_db/snapshot_info_db"), b"sqlit3");
+        assert!(manifest.validate(dir.path()).is_err());
+        write_file(&dir.path().join("storage_db/snapshot_info_db"), b"sqlite");
+        write_file(&dir.path().join("storage_db/extra"), b"");
+        assert!(manifest.validate(dir.path()).is_err());
+        fs::remove_file(dir.path().join("storage_db/extra")).unwrap();
+        fs::remove_file(dir.path().join("blockchain_db/CURRENT")).unwrap();
+        assert!(manifest.validate(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_restore_checkpoint() {
+        let checkpoint_dir = TempDir::new("checkpoint").unwrap();
+        make_checkpoint(checkpoint_dir.path(), /* pos_db = */ true);
+
+        let data_dir = TempDir::new("data").unwrap();
+        let block_db_dir = data_dir.path().join("blockchain_db");
+        let pos_db_dir = data_dir.path().join("pos_db/db");
+        write_file(&block_db_dir.join("CURRENT"), b"old");
+        // The PoS storage dir is required by a checkpoint with PoS databases.
+        assert!(restore_checkpoint(
+            checkpoint_dir.path(),
+            data_dir.path(),
+            &block_db_dir,
+            None,
+        )
+        .is_err());
+        assert_eq!(fs::read(block_db_dir.join("CURRENT")).unwrap(), b"old");
+
+        restore_checkpoint(
+            checkpoint_dir.path(),
+            data_dir.path(),
+            &block_db_dir,
+            Some(&pos_db_dir),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read(block_db_dir.join("CURRENT")).unwrap(),
+            b"MANIFEST-000001"
+        );
+        assert_eq!(
+            fs::read(
+                data_dir.path().join("blockchain_db.before_restore/CURRENT")
+            )
+            .unwrap(),
+            b"old"
+        );
+        assert!(data_dir
+            .path()
+            .join("storage_db/delta_mpts/rocksdb_00/CURRENT")
+            .exists());
+        assert_eq!(
+            fs::read(pos_db_dir.join("consensusdb/CURRENT")).unwrap(),
+            b"consensus"
+        );
+        assert!(!restoring_path(&block_db_dir).exists());
+
+        // The replaced databases are not overwritten by another restore.
+        assert!(restore_checkpoint(
+            checkpoint_dir.path(),
+            data_dir.path(),
+            &block_db_dir,
+            Some(&pos_db_dir),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_restore_invalid_checkpoint() {
+        let checkpoint_dir = TempDir::new("checkpoint").unwrap();
+        make_checkpoint(checkpoint_dir.path(), /* pos_db = */ false);
+        write_file(
+            &checkpoint_dir.path().join("storage_db/snapshot_info_db"),
+            b"sqlit3",
+        );
+
+        let data_dir = TempDir::new("data").unwrap();
+        let block_db_dir = data_dir.path().join("blockchain_db");
+        write_file(&block_db_dir.join("CURRENT"), b"old");
+        assert!(restore_checkpoint(
+            checkpoint_dir.path(),
+            data_dir.path(),
+            &block_db_dir,
+            None,
+        )
+        .is_err());
+        assert_eq!(fs::read(block_db_dir.join("CURRENT")).unwrap(), b"old");
+        assert!(!backup_path(&block_db_dir).exists());
+        assert!(!restoring_path(&block_db_dir).exists());
+    }
+}
@@ -0,0 +1,343 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Value compression of the block db.
+//!
+//! A compressed value is stored as
+//! `[COMPRESSED_VALUE_MARKER, COMPRESSION_FORMAT_VERSION, codec id, payload]`.
+//! The values written before compression are RLP encoded and never start with
+//! `COMPRESSED_VALUE_MARKER` unless they are the single byte `0x00`, so both
+//! kinds of values can live in the same column and are told apart on read.
+//! A value is stored uncompressed if compression does not make it smaller.
+//!
+//! The zstd codec can use a dictionary trained from the values of a column,
+//! which is stored in the db. There is at most one dictionary for a column and
+//! it's never replaced, so the values compressed with it stay readable.
+//!
+//! The values written before the codecs are configured are rewritten in a
+//! background thread, see `BlockDataManager::start_recompression`.
+
+use std::{
+    borrow::Cow,
+    fmt,
+    io::{self, Read, Write},
+    str::FromStr,
+    time::Duration,
+};
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
+
+pub const COMPRESSED_VALUE_MARKER: u8 = 0;
+pub const COMPRESSION_FORMAT_VERSION: u8 = 1;
+const COMPRESSED_VALUE_HEADER_LEN: usize = 3;
+
+const CODEC_ID_LZ4: u8 = 1;
+const CODEC_ID_ZSTD: u8 = 2;
+const CODEC_ID_ZSTD_DICTIONARY: u8 = 3;
+
+pub const DEFAULT_ZSTD_LEVEL: i32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockDbCodec {
+    None,
+    Lz4,
+    Zstd { level: i32 },
+}
+
+impl Default for BlockDbCodec {
+    fn default() -> Self { BlockDbCodec::None }
+}
+
+/// Parses `none`, `lz4`, `zstd` or `zstd:<level>`.
+impl FromStr for BlockDbCodec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None => match s {
+                "none" => Ok(BlockDbCodec::None),
+                "lz4" => Ok(BlockDbCodec::Lz4),
+                "zstd" => Ok(BlockDbCodec::Zstd {
+                    level: DEFAULT_ZSTD_LEVEL,
+                }),
+                _ => Err(format!("Unknown block db codec {}", s)),
+            },
+            Some(("zstd", level)) => {
+                let level = level.parse().map_err(|e| {
+                    format!("Invalid zstd level {}: {:?}", level, e)
+                })?;
+                if !zstd::compression_level_range().contains(&level) {
+                    return Err(format!("Invalid zstd level {}", level));
+                }
+                Ok(BlockDbCodec::Zstd { level })
+            }
+            _ => Err(format!("Unknown block db codec {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for BlockDbCodec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlockDbCodec::None => write!(f, "none"),
+            BlockDbCodec::Lz4 => write!(f, "lz4"),
+            BlockDbCodec::Zstd { level } => write!(f, "zstd:{}", level),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockDbCompressionConfig {
+    /// The codec of the headers, bodies, receipts and rewards of blocks.
+    pub blocks: BlockDbCodec,
+    /// The codec of the block traces.
+    pub block_traces: BlockDbCodec,
+    /// If set, a zstd dictionary of this size is trained for each column
+    /// compressed with zstd.
+    pub zstd_dictionary_size: Option<usize>,
+    /// The interval between the rounds of the background recompression.
+    pub recompression_interval: Duration,
+    /// The max number of epochs rewritten with the configured codecs in each
+    /// round.
+    pub recompression_epochs_per_round: u64,
+    /// A round stops rewriting epochs once it has run for this long.
+    pub recompression_round_time_budget: Duration,
+}
+
+impl Default for BlockDbCompressionConfig {
+    fn default() -> Self {
+        Self {
+            blocks: BlockDbCodec::None,
+            block_traces: BlockDbCodec::None,
+            zstd_dictionary_size: None,
+            recompression_interval: Duration::from_secs(10),
+            recompression_epochs_per_round: 100,
+            recompression_round_time_budget: Duration::from_secs(1),
+        }
+    }
+}
+
+impl BlockDbCompressionConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.blocks != BlockDbCodec::None
+            || self.block_traces != BlockDbCodec::None
+    }
+}
+
+/// A trained zstd dictionary, prepared for compression and decompression.
+pub struct ZstdDictionary {
+    encoder: EncoderDictionary<'static>,
+    decoder: DecoderDictionary<'static>,
+}
+
+impl ZstdDictionary {
+    pub fn new(dictionary: &[u8], level: i32) -> Self {
+        Self {
+            encoder: EncoderDictionary::copy(dictionary, level),
+            decoder: DecoderDictionary::copy(dictionary),
+        }
+    }
+
+    /// Train a dictionary from the samples concatenated in `sample_data`.
+    pub fn train(
+        sample_data: &[u8], sample_sizes: &[usize], max_size: usize,
+    ) -> io::Result<Vec<u8>> {
+        zstd::dict::from_continuous(sample_data, sample_sizes, max_size)
+    }
+}
+
+/// The codec id in the header of `value`, or `None` for an uncompressed
+/// value.
+pub fn compressed_codec_id(value: &[u8]) -> Option<u8> {
+    if value.len() >= COMPRESSED_VALUE_HEADER_LEN
+        && value[0] == COMPRESSED_VALUE_MARKER
+    {
+        Some(value[2])
+    } else {
+        None
+    }
+}
+
+/// The codec id written by `compress_value` with these arguments, or `None`
+/// if the value is not compressed.
+pub fn codec_id(codec: BlockDbCodec, with_dictionary: bool) -> Option<u8> {
+    match codec {
+        BlockDbCodec::None => None,
+        BlockDbCodec::Lz4 => Some(CODEC_ID_LZ4),
+        BlockDbCodec::Zstd { .. } if with_dictionary => {
+            Some(CODEC_ID_ZSTD_DICTIONARY)
+        }
+        BlockDbCodec::Zstd { .. } => Some(CODEC_ID_ZSTD),
+    }
+}
+
+/// Compress `raw` with `codec`. The dictionary is only used by zstd. Returns
+/// `None` if the value should be stored uncompressed.
+pub fn compress_value(
+    codec: BlockDbCodec, dictionary: Option<&ZstdDictionary>, raw: &[u8],
+) -> io::Result<Option<Vec<u8>>> {
+    let codec_id = match codec_id(codec, dictionary.is_some()) {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    let mut value = Vec::with_capacity(raw.len());
+    value.extend_from_slice(&[
+        COMPRESSED_VALUE_MARKER,
+        COMPRESSION_FORMAT_VERSION,
+        codec_id,
+    ]);
+    match (codec, dictionary) {
+        (BlockDbCodec::Lz4, _) => {
+            value.extend_from_slice(&lz4_flex::compress_prepend_size(raw))
+        }
+        (BlockDbCodec::Zstd { .. }, Some(dictionary)) => {
+            let mut encoder = zstd::stream::Encoder::with_prepared_dictionary(
+                value,
+                &dictionary.encoder,
+            )?;
+            encoder.write_all(raw)?;
+            value = encoder.finish()?;
+        }
+        (BlockDbCodec::Zstd { level }, None) => {
+            value.extend_from_slice(&zstd::bulk::compress(raw, level)?)
+        }
+        (BlockDbCodec::None, _) => unreachable!(),
+    }
+    if value.len() < raw.len() {
+        Ok(Some(value))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Decompress a value read from the db. `dictionary` is only called for the
+/// values compressed with a dictionary.
+pub fn decompress_value<'a, F>(
+    value: &'a [u8], dictionary: F,
+) -> io::Result<Cow<'a, [u8]>>
+where F: FnOnce() -> Option<std::sync::Arc<ZstdDictionary>> {
+    let codec_id = match compressed_codec_id(value) {
+        Some(id) => id,
+        None => return Ok(Cow::Borrowed(value)),
+    };
+    if value[1] != COMPRESSION_FORMAT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unsupported compression format version {}", value[1]),
+        ));
+    }
+    let payload = &value[COMPRESSED_VALUE_HEADER_LEN..];
+    let mut raw = Vec::new();
+    match codec_id {
+        CODEC_ID_LZ4 => {
+            raw =
+                lz4_flex::decompress_size_prepended(payload).map_err(|e| {
+                    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+                })?;
+        }
+        CODEC_ID_ZSTD => {
+            zstd::stream::Decoder::new(payload)?.read_to_end(&mut raw)?;
+        }
+        CODEC_ID_ZSTD_DICTIONARY => {
+            let dictionary = dictionary().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "Zstd dictionary of the column is missing",
+                )
+            })?;
+            zstd::stream::Decoder::with_prepared_dictionary(
+                payload,
+                &dictionary.decoder,
+            )?
+            .read_to_end(&mut raw)?;
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unknown codec id {}", codec_id),
+            ))
+        }
+    }
+    Ok(Cow::Owned(raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn sample_value(i: usize) -> Vec<u8> {
+        format!(
+            "{{\"index\":{},\"from\":\"0x1234567890abcdef\",\"gas\":21000,\
+             \"status\":\"success\",\"logs\":[]}}",
+            i
+        )
+        .repeat(4)
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_parse_codec() {
+        assert_eq!("none".parse(), Ok(BlockDbCodec::None));
+        assert_eq!("lz4".parse(), Ok(BlockDbCodec::Lz4));
+        assert_eq!(
+            "zstd".parse(),
+            Ok(BlockDbCodec::Zstd {
+                level: DEFAULT_ZSTD_LEVEL
+            })
+        );
+        assert_eq!("zstd:19".parse(), Ok(BlockDbCodec::Zstd { level: 19 }));
+        assert!("zstd:x".parse::<BlockDbCodec>().is_err());
+        assert!("gzip".parse::<BlockDbCodec>().is_err());
+    }
+
+    #[test]
+    fn test_compress_roundtrip() {
+        let raw = sample_value(1);
+        for codec in [BlockDbCodec::Lz4, BlockDbCodec::Zstd { level: 3 }] {
+            let value = compress_value(codec, None, &raw).unwrap().unwrap();
+            assert!(value.len() < raw.len());
+            assert_eq!(compressed_codec_id(&value), codec_id(codec, false));
+            assert_eq!(&*decompress_value(&value, || None).unwrap(), &raw[..]);
+        }
+        assert!(compress_value(BlockDbCodec::None, None, &raw)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_uncompressed_value() {
+        // An RLP list is passed through.
+        let raw = rlp::encode_list::<u64, _>(&[1, 2, 3]);
+        assert_eq!(compressed_codec_id(&raw), None);
+        assert_eq!(&*decompress_value(&raw, || None).unwrap(), &raw[..]);
+        // Incompressible values are kept as they are.
+        assert!(compress_value(BlockDbCodec::Lz4, None, &raw)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_compress_with_dictionary() {
+        let samples: Vec<_> = (0..1000).map(sample_value).collect();
+        let sample_sizes: Vec<_> = samples.iter().map(Vec::len).collect();
+        let dictionary = Arc::new(ZstdDictionary::new(
+            &ZstdDictionary::train(&samples.concat(), &sample_sizes, 4096)
+                .unwrap(),
+            DEFAULT_ZSTD_LEVEL,
+        ));
+        let codec = BlockDbCodec::Zstd {
+            level: DEFAULT_ZSTD_LEVEL,
+        };
+        let raw = sample_value(1001);
+        let value = compress_value(codec, Some(&dictionary), &raw)
+            .unwrap()
+            .unwrap();
+        assert_eq!(compressed_codec_id(&value), codec_id(codec, true));
+        assert!(decompress_value(&value, || None).is_err());
+        assert_eq!(
+            &*decompress_value(&value, || Some(dictionary.clone())).unwrap(),
+            &raw[..]
+        );
+    }
+}
@@ -1,10 +1,16 @@
 use crate::{
     block_data_manager::{
+        compression::{
+            codec_id, compress_value, compressed_codec_id, decompress_value,
+            BlockDbCodec, BlockDbCompressionConfig, ZstdDictionary,
+            DEFAULT_ZSTD_LEVEL,
+        },
         db_decode_list, db_encode_list, BlamedHeaderVerifiedRoots,
         BlockExecutionResultWithEpoch, BlockRewardResult, BlockTracesWithEpoch,
         CheckpointHashes, DataVersionTuple, EpochExecutionContext,
         LocalBlockInfo, PosRewardInfo,
     },
+    consensus::consensus_inner::consensus_executor::EpochExecutionWitness,
     db::{
         COL_BLAMED_HEADER_VERIFIED_ROOTS, COL_BLOCKS, COL_BLOCK_TRACES,
         COL_EPOCH_NUMBER, COL_HASH_BY_BLOCK_NUMBER, COL_MISC,
@@ -18,14 +24,22 @@ use cfx_internal_common::{
     DatabaseDecodable, DatabaseEncodable, EpochExecutionCommitment,
 };
 use cfx_storage::{
-    storage_db::KeyValueDbTrait, KvdbRocksdb, KvdbSqlite, KvdbSqliteStatements,
+    storage_db::KeyValueDbTrait, KvdbInmemory, KvdbRocksdb, KvdbSqlite,
+    KvdbSqliteStatements, SqliteConnection,
 };
 use cfx_types::H256;
 use db::SystemDB;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use metrics::{Counter, CounterUsize, Gauge, GaugeUsize};
+use parking_lot::RwLock;
 use primitives::{Block, BlockHeader, SignedTransaction, TransactionIndex};
 use rlp::Rlp;
-use std::{collections::HashMap, fs, path::Path, sync::Arc};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
@@ -37,8 +51,63 @@ const EPOCH_CONSENSUS_EXECUTION_INFO_SUFFIX_BYTE: u8 = 5;
 const EPOCH_EXECUTED_BLOCK_SET_SUFFIX_BYTE: u8 = 6;
 const EPOCH_SKIPPED_BLOCK_SET_SUFFIX_BYTE: u8 = 7;
 const BLOCK_REWARD_RESULT_SUFFIX_BYTE: u8 = 8;
+const EPOCH_EXECUTION_WITNESS_SUFFIX_BYTE: u8 = 9;
 const BLOCK_TERMINAL_KEY: &[u8] = b"block_terminals";
 const GC_PROGRESS_KEY: &[u8] = b"gc_progress";
+const RECOMPRESSION_PROGRESS_KEY: &[u8] = b"recompression_progress";
+const RECOMPRESSION_CODECS_KEY: &[u8] = b"recompression_codecs";
+const ZSTD_DICTIONARY_KEY_PREFIX: &[u8] = b"zstd_dictionary_";
+/// The min number of values to train a zstd dictionary from.
+const ZSTD_DICTIONARY_MIN_SAMPLES: usize = 256;
+/// The bytes of samples read for each byte of a zstd dictionary, as zstd
+/// recommends about 100 times the dictionary size.
+const ZSTD_DICTIONARY_SAMPLE_BYTES_PER_DICTIONARY_BYTE: usize = 100;
+
+lazy_static! {
+    static ref BLOCKS_COMPRESSION_METRICS: CompressionMetrics =
+        CompressionMetrics::register("blocks");
+    static ref BLOCK_TRACES_COMPRESSION_METRICS: CompressionMetrics =
+        CompressionMetrics::register("block_traces");
+    static ref RECOMPRESSED_VALUES: Arc<dyn Counter<usize>> =
+        CounterUsize::register_with_group("block_db", "recompressed_values");
+}
+
+/// The bytes written to a compressed column before and after compression.
+struct CompressionMetrics {
+    raw_bytes: Arc<dyn Counter<usize>>,
+    stored_bytes: Arc<dyn Counter<usize>>,
+    /// The stored bytes in percent of the raw bytes.
+    ratio: Arc<dyn Gauge<usize>>,
+}
+
+impl CompressionMetrics {
+    fn register(column: &str) -> Self {
+        Self {
+            raw_bytes: CounterUsize::register_with_group(
+                "block_db",
+                &format!("{}_raw_bytes", column),
+            ),
+            stored_bytes: CounterUsize::register_with_group(
+                "block_db",
+                &format!("{}_stored_bytes", column),
+            ),
+            ratio: GaugeUsize::register_with_group(
+                "block_db",
+                &format!("{}_compression_ratio", column),
+            ),
+        }
+    }
+
+    fn update(&self, raw_len: usize, stored_len: usize) {
+        self.raw_bytes.inc(raw_len);
+        self.stored_bytes.inc(stored_len);
+        let raw_bytes = self.raw_bytes.count();
+        if raw_bytes != 0 {
+            self.ratio
+                .update(self.stored_bytes.count() * 100 / raw_bytes);
+        }
+    }
+}
 
 #[derive(Clone, Copy, Hash, Ord, PartialOrd, Eq, PartialEq, EnumIter)]
 enum DBTable {
@@ -82,6 +151,20 @@ fn sqlite_db_table(table: DBTable) -> String {
 pub struct DBManager {
     table_db: HashMap<DBTable, Box<dyn KeyValueDbTrait<ValueType = Box<[u8]>>>>,
     pow: Arc<PowComputer>,
+    backend: DBBackend,
+    compression: BlockDbCompressionConfig,
+    /// The zstd dictionaries of the columns, `None` if a column has no
+    /// dictionary in the db.
+    zstd_dictionaries: RwLock<HashMap<DBTable, Option<Arc<ZstdDictionary>>>>,
+}
+
+/// The databases behind the tables.
+enum DBBackend {
+    Rocksdb(Arc<SystemDB>),
+    /// The directory of the sqlite databases, one for each table.
+    Sqlite(PathBuf),
+    /// The tables are kept in memory, for tests and fuzzing.
+    Inmemory,
 }
 
 impl DBManager {
@@ -98,7 +181,13 @@ impl DBManager {
                     as Box<dyn KeyValueDbTrait<ValueType = Box<[u8]>>>,
             );
         }
-        Self { table_db, pow }
+        Self {
+            table_db,
+            pow,
+            backend: DBBackend::Rocksdb(db),
+            compression: Default::default(),
+            zstd_dictionaries: Default::default(),
+        }
     }
 }
 
@@ -131,7 +220,287 @@ impl DBManager {
                     as Box<dyn KeyValueDbTrait<ValueType = Box<[u8]>>>,
             );
         }
-        Self { table_db, pow }
+        Self {
+            table_db,
+            pow,
+            backend: DBBackend::Sqlite(db_path.to_path_buf()),
+            compression: Default::default(),
+            zstd_dictionaries: Default::default(),
+        }
+    }
+}
+
+impl DBManager {
+    pub fn new_inmemory(pow: Arc<PowComputer>) -> Self {
+        let mut table_db = HashMap::new();
+        for table in DBTable::iter() {
+            table_db.insert(
+                table,
+                Box::new(KvdbInmemory::default())
+                    as Box<dyn KeyValueDbTrait<ValueType = Box<[u8]>>>,
+            );
+        }
+        Self {
+            table_db,
+            pow,
+            backend: DBBackend::Inmemory,
+            compression: Default::default(),
+            zstd_dictionaries: Default::default(),
+        }
+    }
+}
+
+impl DBManager {
+    /// Compress the values written to the block and trace columns. The
+    /// values are always decompressed on read, whatever the config is.
+    pub fn with_compression(
+        mut self, compression: BlockDbCompressionConfig,
+    ) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Rewrite the values of a block with the configured codecs. The values
+    /// of the epoch are also rewritten for a pivot block. Returns the number
+    /// of rewritten values.
+    pub fn recompress_block_in_db(&self, hash: &H256, is_pivot: bool) -> usize {
+        block_value_keys(hash, is_pivot)
+            .iter()
+            .filter(|(table, key)| self.recompress_value(*table, key))
+            .count()
+    }
+
+    fn recompress_value(&self, table: DBTable, db_key: &[u8]) -> bool {
+        let codec = self.codec(table);
+        let with_dictionary = match codec {
+            BlockDbCodec::Zstd { .. } => {
+                self.encoding_dictionary(table).is_some()
+            }
+            _ => false,
+        };
+        let db = self.table_db.get(&table).unwrap();
+        let stored = match db.get(db_key).expect("db read failure") {
+            Some(value) => value,
+            None => return false,
+        };
+        if compressed_codec_id(&stored) == codec_id(codec, with_dictionary) {
+            return false;
+        }
+        let raw = self.decode_value(table, &stored);
+        let value = self.encode_value(table, raw.into_vec());
+        if value[..] == stored[..] {
+            return false;
+        }
+        db.put(db_key, &value).expect("db insertion failure");
+        RECOMPRESSED_VALUES.inc(1);
+        true
+    }
+
+    /// Train the zstd dictionaries of the columns which have none yet, from
+    /// the values of `block_hashes`, and store them in the db. The values
+    /// written later are compressed with the dictionaries. The samples are
+    /// read from the db and bounded by the dictionary size.
+    pub fn train_zstd_dictionaries(&self, block_hashes: &[(H256, bool)]) {
+        let max_size = match self.compression.zstd_dictionary_size {
+            Some(size) => size,
+            None => return,
+        };
+        let max_sample_bytes =
+            max_size * ZSTD_DICTIONARY_SAMPLE_BYTES_PER_DICTIONARY_BYTE;
+        for table in [DBTable::Blocks, DBTable::BlockTraces] {
+            if !matches!(self.codec(table), BlockDbCodec::Zstd { .. })
+                || self.zstd_dictionary(table).is_some()
+            {
+                continue;
+            }
+            let mut sample_data = Vec::new();
+            let mut sample_sizes = Vec::new();
+            let values = block_hashes
+                .iter()
+                .flat_map(|(hash, is_pivot)| block_value_keys(hash, *is_pivot))
+                .filter(|(key_table, _)| *key_table == table)
+                .filter_map(|(_, key)| self.load_from_db(table, &key));
+            for value in values {
+                if sample_data.len() >= max_sample_bytes {
+                    break;
+                }
+                if sample_data.len() + value.len() > max_sample_bytes {
+                    continue;
+                }
+                sample_data.extend_from_slice(&value);
+                sample_sizes.push(value.len());
+            }
+            if sample_sizes.len() < ZSTD_DICTIONARY_MIN_SAMPLES {
+                continue;
+            }
+            let table_str = sqlite_db_table(table);
+            match ZstdDictionary::train(&sample_data, &sample_sizes, max_size) {
+                Ok(dictionary) => {
+                    // The dictionary is persisted before any value is
+                    // compressed with it.
+                    self.insert_to_db(
+                        DBTable::Misc,
+                        &zstd_dictionary_key(table),
+                        dictionary.clone(),
+                    );
+                    self.zstd_dictionaries.write().insert(
+                        table,
+                        Some(Arc::new(ZstdDictionary::new(
+                            &dictionary,
+                            self.zstd_level(table),
+                        ))),
+                    );
+                    info!(
+                        "Trained zstd dictionary of {} bytes for {}",
+                        dictionary.len(),
+                        table_str
+                    );
+                }
+                Err(e) => warn!(
+                    "Failed to train zstd dictionary for {}: {}",
+                    table_str, e
+                ),
+            }
+        }
+    }
+
+    /// The epoch to continue the recompression from. The recompression
+    /// starts over if the codecs have changed.
+    pub fn recompression_progress_from_db(&self) -> u64 {
+        let codecs = self.load_from_db(DBTable::Misc, RECOMPRESSION_CODECS_KEY);
+        if codecs.as_deref() != Some(self.codecs_description().as_bytes()) {
+            return 0;
+        }
+        self.load_decodable_val(DBTable::Misc, RECOMPRESSION_PROGRESS_KEY)
+            .unwrap_or(0)
+    }
+
+    pub fn insert_recompression_progress_to_db(&self, next_to_process: u64) {
+        self.insert_to_db(
+            DBTable::Misc,
+            RECOMPRESSION_CODECS_KEY,
+            self.codecs_description().into_bytes(),
+        );
+        self.insert_encodable_val(
+            DBTable::Misc,
+            RECOMPRESSION_PROGRESS_KEY,
+            &next_to_process,
+        );
+    }
+
+    fn codecs_description(&self) -> String {
+        format!(
+            "blocks={},block_traces={},zstd_dictionary={}",
+            self.compression.blocks,
+            self.compression.block_traces,
+            self.compression.zstd_dictionary_size.is_some()
+        )
+    }
+
+    fn codec(&self, table: DBTable) -> BlockDbCodec {
+        match table {
+            DBTable::Blocks => self.compression.blocks,
+            DBTable::BlockTraces => self.compression.block_traces,
+            _ => BlockDbCodec::None,
+        }
+    }
+
+    fn zstd_level(&self, table: DBTable) -> i32 {
+        match self.codec(table) {
+            BlockDbCodec::Zstd { level } => level,
+            _ => DEFAULT_ZSTD_LEVEL,
+        }
+    }
+
+    fn zstd_dictionary(&self, table: DBTable) -> Option<Arc<ZstdDictionary>> {
+        if let Some(dictionary) = self.zstd_dictionaries.read().get(&table) {
+            return dictionary.clone();
+        }
+        let dictionary = self
+            .load_from_db(DBTable::Misc, &zstd_dictionary_key(table))
+            .map(|raw| {
+                Arc::new(ZstdDictionary::new(&raw, self.zstd_level(table)))
+            });
+        self.zstd_dictionaries
+            .write()
+            .entry(table)
+            .or_insert(dictionary)
+            .clone()
+    }
+
+    /// The dictionary to compress the values of `table` with, if dictionaries
+    /// are enabled.
+    fn encoding_dictionary(
+        &self, table: DBTable,
+    ) -> Option<Arc<ZstdDictionary>> {
+        if self.compression.zstd_dictionary_size.is_none() {
+            return None;
+        }
+        self.zstd_dictionary(table)
+    }
+
+    fn encode_value(&self, table: DBTable, raw: Vec<u8>) -> Vec<u8> {
+        let codec = self.codec(table);
+        let metrics = match table {
+            DBTable::Blocks => &*BLOCKS_COMPRESSION_METRICS,
+            DBTable::BlockTraces => &*BLOCK_TRACES_COMPRESSION_METRICS,
+            _ => return raw,
+        };
+        if codec == BlockDbCodec::None {
+            return raw;
+        }
+        let dictionary = match codec {
+            BlockDbCodec::Zstd { .. } => self.encoding_dictionary(table),
+            _ => None,
+        };
+        let raw_len = raw.len();
+        let value = compress_value(codec, dictionary.as_deref(), &raw)
+            .expect("db value compression failure")
+            .unwrap_or(raw);
+        metrics.update(raw_len, value.len());
+        value
+    }
+
+    fn decode_value(&self, table: DBTable, value: &[u8]) -> Box<[u8]> {
+        decompress_value(value, || self.zstd_dictionary(table))
+            .expect("db value decompression failure")
+            .into_owned()
+            .into_boxed_slice()
+    }
+}
+
+impl DBManager {
+    /// Make a consistent copy of the block db at `path`, while it's in use.
+    pub fn checkpoint(&self, path: &Path) -> Result<(), String> {
+        match &self.backend {
+            DBBackend::Rocksdb(db) => db
+                .key_value()
+                .checkpoint(path)
+                .map_err(|e| format!("Failed to checkpoint block db: {}", e)),
+            DBBackend::Sqlite(db_path) => {
+                fs::create_dir_all(path).map_err(|e| {
+                    format!("Failed to create {}: {}", path.display(), e)
+                })?;
+                for table in DBTable::iter() {
+                    let table_str = sqlite_db_table(table);
+                    SqliteConnection::open(
+                        db_path.join(&table_str),
+                        /* readonly = */ true,
+                        SqliteConnection::default_open_flags(),
+                    )
+                    .and_then(|connection| {
+                        connection.backup(path.join(&table_str))
+                    })
+                    .map_err(|e| {
+                        format!("Failed to backup table {}: {}", table_str, e)
+                    })?;
+                }
+                Ok(())
+            }
+            DBBackend::Inmemory => {
+                Err("An in-memory block db can not be checkpointed".into())
+            }
+        }
     }
 }
 
@@ -215,6 +584,13 @@ impl DBManager {
         )
     }
 
+    pub fn remove_hash_by_block_number_from_db(&self, block_number: u64) {
+        self.remove_from_db(
+            DBTable::HashByBlockNumber,
+            &block_number.to_be_bytes(),
+        )
+    }
+
     /// Store block info to db. Block info includes block status and
     /// the sequence number when the block enters consensus graph.
     /// The db key is the block hash plus one extra byte, so we can get better
@@ -405,6 +781,20 @@ impl DBManager {
         )
     }
 
+    pub fn remove_executed_epoch_set_hashes_from_db(&self, epoch: u64) {
+        self.remove_from_db(
+            DBTable::EpochNumbers,
+            &executed_epoch_set_key(epoch)[0..9],
+        )
+    }
+
+    pub fn remove_skipped_epoch_set_hashes_from_db(&self, epoch: u64) {
+        self.remove_from_db(
+            DBTable::EpochNumbers,
+            &skipped_epoch_set_key(epoch)[0..9],
+        )
+    }
+
     pub fn insert_terminals_to_db(&self, terminals: &Vec<H256>) {
         self.insert_encodable_list(
             DBTable::Misc,
@@ -474,6 +864,29 @@ impl DBManager {
         self.remove_from_db(DBTable::Blocks, &epoch_execution_context_key(hash))
     }
 
+    pub fn insert_epoch_execution_witness_to_db(
+        &self, hash: &H256, witness: &EpochExecutionWitness,
+    ) {
+        self.insert_encodable_val(
+            DBTable::Blocks,
+            &epoch_execution_witness_key(hash),
+            witness,
+        )
+    }
+
+    pub fn epoch_execution_witness_from_db(
+        &self, hash: &H256,
+    ) -> Option<EpochExecutionWitness> {
+        self.load_decodable_val(
+            DBTable::Blocks,
+            &epoch_execution_witness_key(hash),
+        )
+    }
+
+    pub fn remove_epoch_execution_witness_from_db(&self, hash: &H256) {
+        self.remove_from_db(DBTable::Blocks, &epoch_execution_witness_key(hash))
+    }
+
     pub fn insert_gc_progress_to_db(&self, next_to_process: u64) {
         self.insert_encodable_val(
             DBTable::Misc,
@@ -508,6 +921,7 @@ impl DBManager {
     /// The functions below are private utils used by the DBManager to access
     /// database
     fn insert_to_db(&self, table: DBTable, db_key: &[u8], value: Vec<u8>) {
+        let value = self.encode_value(table, value);
         self.table_db
             .get(&table)
             .unwrap()
@@ -524,11 +938,21 @@ impl DBManager {
     }
 
     fn load_from_db(&self, table: DBTable, db_key: &[u8]) -> Option<Box<[u8]>> {
-        self.table_db
+        let value = self
+            .table_db
             .get(&table)
             .unwrap()
             .get(db_key)
-            .expect("db read failure")
+            .expect("db read failure")?;
+        match table {
+            // Only these columns may have compressed values.
+            DBTable::Blocks | DBTable::BlockTraces
+                if compressed_codec_id(&value).is_some() =>
+            {
+                Some(self.decode_value(table, &value))
+            }
+            _ => Some(value),
+        }
     }
 
     fn insert_encodable_val<V>(
@@ -585,6 +1009,38 @@ fn blamed_header_verified_roots_key(block_height: u64) -> [u8; 8] {
     height_key
 }
 
+/// The keys of the values of a block, and of its epoch for a pivot block, in
+/// the columns which may be compressed.
+fn block_value_keys(hash: &H256, is_pivot: bool) -> Vec<(DBTable, Vec<u8>)> {
+    let mut keys = vec![
+        (DBTable::Blocks, hash.as_bytes().to_vec()),
+        (DBTable::Blocks, local_block_info_key(hash)),
+        (DBTable::Blocks, block_body_key(hash)),
+        (DBTable::Blocks, block_execution_result_key(hash)),
+        (DBTable::Blocks, block_reward_result_key(hash)),
+        (DBTable::BlockTraces, hash.as_bytes().to_vec()),
+    ];
+    if is_pivot {
+        keys.extend_from_slice(&[
+            (DBTable::Blocks, epoch_execution_context_key(hash)),
+            (
+                DBTable::Blocks,
+                epoch_consensus_epoch_execution_commitment_key(hash),
+            ),
+            (DBTable::Blocks, epoch_execution_witness_key(hash)),
+        ]);
+    }
+    keys
+}
+
+fn zstd_dictionary_key(table: DBTable) -> Vec<u8> {
+    [
+        ZSTD_DICTIONARY_KEY_PREFIX,
+        sqlite_db_table(table).as_bytes(),
+    ]
+    .concat()
+}
+
 fn block_body_key(block_hash: &H256) -> Vec<u8> {
     append_suffix(block_hash, BLOCK_BODY_SUFFIX_BYTE)
 }
@@ -619,6 +1075,10 @@ fn epoch_consensus_epoch_execution_commitment_key(hash: &H256) -> Vec<u8> {
     append_suffix(hash, EPOCH_CONSENSUS_EXECUTION_INFO_SUFFIX_BYTE)
 }
 
+fn epoch_execution_witness_key(hash: &H256) -> Vec<u8> {
+    append_suffix(hash, EPOCH_EXECUTION_WITNESS_SUFFIX_BYTE)
+}
+
 impl MallocSizeOf for DBManager {
     fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
         // Here we only handle the case that all columns are stored within the
@@ -629,3 +1089,70 @@ impl MallocSizeOf for DBManager {
             .size_of(ops)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_compressed_and_legacy_values() {
+        let dir = TempDir::new("db_manager_compression").unwrap();
+        let pow = Arc::new(PowComputer::new(false));
+        let raw = rlp::encode_list::<u64, _>(&vec![7u64; 500]).to_vec();
+
+        let db_manager = DBManager::new_from_sqlite(dir.path(), pow.clone());
+        db_manager.insert_to_db(DBTable::Blocks, b"legacy", raw.clone());
+        drop(db_manager);
+
+        let db_manager = DBManager::new_from_sqlite(dir.path(), pow)
+            .with_compression(BlockDbCompressionConfig {
+                blocks: BlockDbCodec::Zstd {
+                    level: DEFAULT_ZSTD_LEVEL,
+                },
+                ..Default::default()
+            });
+        db_manager.insert_to_db(DBTable::Blocks, b"new", raw.clone());
+        let stored = |key: &[u8]| {
+            db_manager.table_db[&DBTable::Blocks]
+                .get(key)
+                .unwrap()
+                .unwrap()
+        };
+        assert!(compressed_codec_id(&stored(b"new")).is_some());
+        assert!(compressed_codec_id(&stored(b"legacy")).is_none());
+        for key in [&b"new"[..], &b"legacy"[..]] {
+            assert_eq!(
+                &*db_manager.load_from_db(DBTable::Blocks, key).unwrap(),
+                &raw[..]
+            );
+        }
+
+        assert!(db_manager.recompress_value(DBTable::Blocks, b"legacy"));
+        assert!(!db_manager.recompress_value(DBTable::Blocks, b"legacy"));
+        assert!(compressed_codec_id(&stored(b"legacy")).is_some());
+        assert_eq!(
+            &*db_manager.load_from_db(DBTable::Blocks, b"legacy").unwrap(),
+            &raw[..]
+        );
+    }
+
+    #[test]
+    fn test_inmemory_tables() {
+        let db_manager =
+            DBManager::new_inmemory(Arc::new(PowComputer::new(false)));
+        db_manager.insert_to_db(DBTable::Blocks, b"key", b"block".to_vec());
+        db_manager.insert_to_db(DBTable::Misc, b"key", b"misc".to_vec());
+        assert_eq!(
+            db_manager.load_from_db(DBTable::Blocks, b"key").as_deref(),
+            Some(&b"block"[..])
+        );
+        assert_eq!(
+            db_manager.load_from_db(DBTable::Misc, b"key").as_deref(),
+            Some(&b"misc"[..])
+        );
+        db_manager.remove_from_db(DBTable::Blocks, b"key");
+        assert!(db_manager.load_from_db(DBTable::Blocks, b"key").is_none());
+        assert!(db_manager.checkpoint(Path::new("checkpoint")).is_err());
+    }
+}
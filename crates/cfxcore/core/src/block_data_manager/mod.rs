@@ -5,14 +5,16 @@
 use crate::{
     cache_config::CacheConfig,
     cache_manager::{CacheId, CacheManager, CacheSize},
-    consensus::consensus_inner::consensus_executor::RewardExecutionInfo,
+    consensus::consensus_inner::consensus_executor::{
+        EpochExecutionWitness, RewardExecutionInfo,
+    },
     ext_db::SystemDB,
     pow::{PowComputer, TargetDifficultyManager},
 };
 use cfx_executor::internal_contract::make_staking_events;
 use cfx_storage::{
-    state_manager::StateIndex, utils::guarded_value::*, StorageManager,
-    StorageManagerTrait,
+    state_manager::StateIndex, utils::guarded_value::*, PruningMode,
+    StorageManager, StorageManagerTrait,
 };
 use cfx_types::{Bloom, Space, H256};
 use malloc_size_of::{new_malloc_size_ops, MallocSizeOf, MallocSizeOfOps};
@@ -27,16 +29,24 @@ use primitives::{
 use rlp::DecoderError;
 use std::{
     collections::{HashMap, HashSet},
-    sync::Arc,
+    sync::{
+        mpsc::{channel, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Instant,
 };
 use threadpool::ThreadPool;
 pub mod block_data_types;
+pub mod checkpoint;
+pub mod compression;
 pub mod db_gc_manager;
 pub mod db_manager;
 pub mod tx_data_manager;
 use crate::{
     block_data_manager::{
-        db_manager::DBManager, tx_data_manager::TransactionDataManager,
+        compression::BlockDbCompressionConfig, db_manager::DBManager,
+        tx_data_manager::TransactionDataManager,
     },
     consensus::pos_handler::PosVerifier,
 };
@@ -51,7 +61,7 @@ use cfx_internal_common::{
 use db_gc_manager::GCProgress;
 use metrics::{register_meter_with_group, Meter, MeterTimer};
 use primitives::pos::PosBlockId;
-use std::{hash::Hash, path::Path, time::Duration};
+use std::{cmp::min, hash::Hash, path::Path, time::Duration};
 
 lazy_static! {
     static ref TX_POOL_RECOVER_TIMER: Arc<dyn Meter> =
@@ -144,6 +154,14 @@ pub struct BlockDataManager {
     cache_man: Arc<Mutex<CacheManager<CacheId>>>,
     pub target_difficulty_manager: TargetDifficultyManager,
     gc_progress: Arc<Mutex<GCProgress>>,
+    /// The next epoch to rewrite with the configured codecs.
+    recompression_progress: Mutex<u64>,
+    /// Held while the values of an epoch are removed by the GC or rewritten
+    /// by the recompression, so a removed value is never written back.
+    #[ignore_malloc_size_of = "ignored"]
+    db_rewrite_lock: Mutex<()>,
+    #[ignore_malloc_size_of = "ignored"]
+    recompression_thread: Mutex<Option<(Sender<()>, JoinHandle<()>)>>,
 
     /// This maintains the boundary height of available state and commitments
     /// (executed but not deleted or in `ExecutionTaskQueue`).
@@ -169,9 +187,10 @@ pub struct BlockDataManager {
 }
 
 impl BlockDataManager {
+    /// `db` is the rocksdb ledger, which is only needed by `DbType::Rocksdb`.
     pub fn new(
-        cache_conf: CacheConfig, true_genesis: Arc<Block>, db: Arc<SystemDB>,
-        storage_manager: Arc<StorageManager>,
+        cache_conf: CacheConfig, true_genesis: Arc<Block>,
+        db: Option<Arc<SystemDB>>, storage_manager: Arc<StorageManager>,
         worker_pool: Arc<Mutex<ThreadPool>>, config: DataManagerConfiguration,
         pow: Arc<PowComputer>,
     ) -> Self {
@@ -188,14 +207,21 @@ impl BlockDataManager {
             worker_pool,
         );
         let db_manager = match config.db_type {
-            DbType::Rocksdb => DBManager::new_from_rocksdb(db, pow.clone()),
+            DbType::Rocksdb => DBManager::new_from_rocksdb(
+                db.expect("The rocksdb ledger is opened; qed"),
+                pow.clone(),
+            ),
             DbType::Sqlite => DBManager::new_from_sqlite(
                 Path::new("./sqlite_db"),
                 pow.clone(),
             ),
-        };
+            DbType::Inmemory => DBManager::new_inmemory(pow.clone()),
+        }
+        .with_compression(config.db_compression.clone());
         let previous_db_progress =
             db_manager.gc_progress_from_db().unwrap_or(0);
+        let recompression_progress =
+            db_manager.recompression_progress_from_db();
 
         let data_man = Self {
             block_headers: RwLock::new(HashMap::new()),
@@ -237,6 +263,9 @@ impl BlockDataManager {
             gc_progress: Arc::new(Mutex::new(GCProgress::new(
                 previous_db_progress,
             ))),
+            recompression_progress: Mutex::new(recompression_progress),
+            db_rewrite_lock: Default::default(),
+            recompression_thread: Default::default(),
         };
 
         data_man.initialize_instance_id();
@@ -1201,9 +1230,26 @@ impl BlockDataManager {
         self.epoch_execution_commitments.write().remove(block_hash);
     }
 
+    /// The witness of the execution is removed together, as the execution
+    /// can't be checked against the state afterwards.
     pub fn remove_epoch_execution_commitment_from_db(&self, block_hash: &H256) {
         self.db_manager
             .remove_epoch_execution_commitment_from_db(block_hash);
+        self.db_manager
+            .remove_epoch_execution_witness_from_db(block_hash);
+    }
+
+    pub fn insert_epoch_execution_witness(
+        &self, witness: &EpochExecutionWitness,
+    ) {
+        self.db_manager
+            .insert_epoch_execution_witness_to_db(&witness.epoch_hash, witness)
+    }
+
+    pub fn epoch_execution_witness_by_hash(
+        &self, epoch_hash: &H256,
+    ) -> Option<EpochExecutionWitness> {
+        self.db_manager.epoch_execution_witness_from_db(epoch_hash)
     }
 
     pub fn remove_epoch_execution_context(&self, block_hash: &H256) {
@@ -1627,26 +1673,43 @@ impl BlockDataManager {
         }
     }
 
+    pub fn pruning_mode(&self) -> PruningMode { self.config.pruning_mode }
+
     pub fn earliest_epoch_with_block_body(&self) -> u64 {
-        match self.config.additional_maintained_block_body_epoch_count {
-            Some(defer) => self.gc_progress.lock().gc_end - defer as u64,
-            None => 0,
-        }
+        self.earliest_epoch_with_defer(
+            self.config.additional_maintained_block_body_epoch_count,
+        )
     }
 
     pub fn earliest_epoch_with_execution_result(&self) -> u64 {
-        match self
-            .config
-            .additional_maintained_execution_result_epoch_count
-        {
-            Some(defer) => self.gc_progress.lock().gc_end - defer as u64,
-            None => 0,
-        }
+        self.earliest_epoch_with_defer(
+            self.config
+                .additional_maintained_execution_result_epoch_count,
+        )
     }
 
     pub fn earliest_epoch_with_trace(&self) -> u64 {
-        match self.config.additional_maintained_trace_epoch_count {
-            Some(defer) => self.gc_progress.lock().gc_end - defer as u64,
+        self.earliest_epoch_with_defer(
+            self.config.additional_maintained_trace_epoch_count,
+        )
+    }
+
+    pub fn earliest_epoch_with_transaction_index(&self) -> u64 {
+        self.earliest_epoch_with_defer(
+            self.config
+                .additional_maintained_transaction_index_epoch_count,
+        )
+    }
+
+    pub fn earliest_epoch_with_state(&self) -> u64 {
+        self.state_availability_boundary.read().lower_bound
+    }
+
+    fn earliest_epoch_with_defer(&self, maybe_defer: Option<usize>) -> u64 {
+        match maybe_defer {
+            Some(defer) => {
+                self.gc_progress.lock().gc_end.saturating_sub(defer as u64)
+            }
             None => 0,
         }
     }
@@ -1666,6 +1729,7 @@ impl BlockDataManager {
         debug!("Start database GC, range={:?}", maybe_range);
         if let Some((start, end)) = maybe_range {
             for base_epoch in start..end {
+                let _db_rewrite_guard = self.db_rewrite_lock.lock();
                 self.gc_base_epoch(base_epoch);
             }
             let mut gc_progress = self.gc_progress.lock();
@@ -1676,6 +1740,90 @@ impl BlockDataManager {
         }
     }
 
+    /// Start rewriting the block data with the configured codecs in
+    /// background, every `recompression_interval`, if compression is enabled.
+    pub fn start_recompression(self: &Arc<Self>) -> Result<(), String> {
+        if !self.config.db_compression.is_enabled() {
+            return Ok(());
+        }
+        let interval = self.config.db_compression.recompression_interval;
+        let data_man = Arc::downgrade(self);
+        let (exit_signaler, exit_signal_receiver) = channel();
+        let joiner = thread::Builder::new()
+            .name("Block DB Recompression".into())
+            .spawn(move || loop {
+                match exit_signal_receiver.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    _ => break,
+                }
+                match data_man.upgrade() {
+                    Some(data_man) => data_man.recompress_database(),
+                    None => break,
+                }
+            })
+            .map_err(|e| format!("Failed to start recompression: {}", e))?;
+        *self.recompression_thread.lock() = Some((exit_signaler, joiner));
+        Ok(())
+    }
+
+    /// Rewrite the block data with the configured codecs, for the epochs
+    /// before the current checkpoint which are not modified any more. At most
+    /// `recompression_epochs_per_round` epochs are processed in a round, and
+    /// the round stops once it exceeds `recompression_round_time_budget`.
+    /// The missing zstd dictionaries are trained from the epochs of the round
+    /// before they are rewritten.
+    fn recompress_database(&self) {
+        let compression = &self.config.db_compression;
+        let round_start = Instant::now();
+        let mut progress = self.recompression_progress.lock();
+        let start = *progress;
+        let end = min(
+            self.gc_progress.lock().gc_end,
+            start + compression.recompression_epochs_per_round,
+        );
+        if start >= end {
+            return;
+        }
+        let epochs: Vec<Vec<(H256, bool)>> = (start..end)
+            .map(|epoch| {
+                let epoch_blocks = self
+                    .all_epoch_set_hashes_from_db(epoch)
+                    .unwrap_or_default();
+                let pivot_index = epoch_blocks.len().saturating_sub(1);
+                epoch_blocks
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, hash)| (hash, i == pivot_index))
+                    .collect()
+            })
+            .collect();
+        self.db_manager.train_zstd_dictionaries(&epochs.concat());
+        let mut next_epoch = start;
+        let mut rewritten = 0;
+        for epoch_blocks in &epochs {
+            // At least one epoch is rewritten in each round.
+            if next_epoch > start
+                && round_start.elapsed()
+                    >= compression.recompression_round_time_budget
+            {
+                break;
+            }
+            let _db_rewrite_guard = self.db_rewrite_lock.lock();
+            for (hash, is_pivot) in epoch_blocks {
+                rewritten +=
+                    self.db_manager.recompress_block_in_db(hash, *is_pivot);
+            }
+            next_epoch += 1;
+        }
+        *progress = next_epoch;
+        self.db_manager
+            .insert_recompression_progress_to_db(next_epoch);
+        debug!(
+            "Recompressed epochs {}..{}, {} values rewritten",
+            start, next_epoch, rewritten
+        );
+    }
+
     /// Garbage collect different types of data in the corresponding epoch based
     /// on `base_epoch` and the `additional_maintained*` parameters of these
     /// data types.
@@ -1787,10 +1935,26 @@ impl BlockDataManager {
     }
 }
 
+impl Drop for BlockDataManager {
+    fn drop(&mut self) {
+        if let Some((exit_signaler, joiner)) =
+            self.recompression_thread.get_mut().take()
+        {
+            drop(exit_signaler);
+            // The last reference may be dropped by the recompression thread.
+            if joiner.thread().id() != thread::current().id() {
+                joiner.join().ok();
+            }
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
 pub enum DbType {
     Rocksdb,
     Sqlite,
+    /// Keep the block db in memory, for tests and fuzzing.
+    Inmemory,
 }
 
 pub struct DataManagerConfiguration {
@@ -1805,6 +1969,8 @@ pub struct DataManagerConfiguration {
     pub additional_maintained_transaction_index_epoch_count: Option<usize>,
     pub checkpoint_gc_time_in_epoch_count: usize,
     pub strict_tx_index_gc: bool,
+    pub pruning_mode: PruningMode,
+    pub db_compression: BlockDbCompressionConfig,
 }
 
 impl MallocSizeOf for DataManagerConfiguration {
@@ -1816,18 +1982,22 @@ impl DataManagerConfiguration {
         persist_tx_index: bool, persist_block_number_index: bool,
         tx_cache_index_maintain_timeout: Duration, db_type: DbType,
     ) -> Self {
+        let pruning_mode = PruningMode::default();
+        let defer_epochs = pruning_mode.block_data_defer_epochs();
         Self {
             persist_tx_index,
             persist_block_number_index,
             tx_cache_index_maintain_timeout,
             db_type,
-            additional_maintained_block_body_epoch_count: None,
-            additional_maintained_execution_result_epoch_count: None,
-            additional_maintained_reward_epoch_count: None,
-            additional_maintained_trace_epoch_count: None,
-            additional_maintained_transaction_index_epoch_count: None,
+            additional_maintained_block_body_epoch_count: defer_epochs,
+            additional_maintained_execution_result_epoch_count: defer_epochs,
+            additional_maintained_reward_epoch_count: defer_epochs,
+            additional_maintained_trace_epoch_count: defer_epochs,
+            additional_maintained_transaction_index_epoch_count: defer_epochs,
             checkpoint_gc_time_in_epoch_count: 1,
             strict_tx_index_gc: true,
+            pruning_mode,
+            db_compression: Default::default(),
         }
     }
 }
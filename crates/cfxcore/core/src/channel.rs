@@ -2,7 +2,7 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
-use crate::UniqueId;
+use crate::{client::PivotChainReorg, UniqueId};
 use cfx_types::H256;
 use parking_lot::RwLock;
 use std::{collections::BTreeMap, sync::Arc, time::Duration};
@@ -111,6 +111,7 @@ impl<T: Clone> Channel<T> {
 pub struct Notifications {
     pub new_block_hashes: Arc<Channel<H256>>,
     pub epochs_ordered: Arc<Channel<(u64, Vec<H256>)>>,
+    pub pivot_chain_reorgs: Arc<Channel<PivotChainReorg>>,
     pub blame_verification_results: Arc<Channel<(u64, Option<u64>)>>, /* <height, witness> */
 }
 
@@ -119,6 +120,7 @@ impl Notifications {
         Arc::new(Notifications {
             new_block_hashes: Arc::new(Channel::new("new-block-hashes")),
             epochs_ordered: Arc::new(Channel::new("epochs-executed")),
+            pivot_chain_reorgs: Arc::new(Channel::new("pivot-chain-reorgs")),
             blame_verification_results: Arc::new(Channel::new(
                 "blame-verification-results",
             )),
@@ -12,6 +12,18 @@ pub enum ChainMessageType {
     SignedPrivateTransaction(H256, Vec<u8>),
 }
 
+/// A pivot chain switch that reverts the epochs of the old pivot chain from
+/// `fork_at`. It is sent before the epochs of the new pivot chain segment.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PivotChainReorg {
+    /// The first epoch where the old and new pivot chains differ.
+    pub fork_at: u64,
+    /// Pivot block hashes of the reverted epochs, starting at `fork_at`.
+    pub old_pivots: Vec<H256>,
+    /// Pivot block hashes of the new epochs, starting at `fork_at`.
+    pub new_pivots: Vec<H256>,
+}
+
 /// Used by `ChainNotify` `new_blocks()`
 #[allow(dead_code)]
 pub struct NewBlocks {}
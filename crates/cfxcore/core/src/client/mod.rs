@@ -17,3 +17,5 @@
 //! Blockchain database client.
 
 mod chain_notify;
+
+pub use self::chain_notify::PivotChainReorg;
@@ -0,0 +1,141 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_executor::state::DevStateChange;
+use cfx_types::H256;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+
+/// The number of epochs whose applied changes are kept for re-execution. The
+/// changes of older epochs are dropped, so re-executing them no longer
+/// reproduces the dev mode state.
+const MAX_APPLIED_EPOCHS: usize = 10_000;
+
+/// The state changes requested by the dev mode RPCs.
+///
+/// A pending change is applied before the transactions of the first epoch
+/// executed on the local pivot chain whose epoch number is at least the one
+/// given with the change. It is recorded with that epoch, so that
+/// re-executing the epoch, on or off the pivot chain, produces the same
+/// state.
+#[derive(Default)]
+pub struct DevStateChanges {
+    inner: Mutex<DevStateChangesInner>,
+}
+
+#[derive(Default)]
+struct DevStateChangesInner {
+    /// The pending changes and the earliest epoch number to apply them.
+    pending: Vec<(u64, DevStateChange)>,
+    applied: HashMap<H256, Vec<DevStateChange>>,
+    /// The keys of `applied` in insertion order, used to evict the oldest.
+    applied_order: VecDeque<H256>,
+}
+
+impl DevStateChanges {
+    pub fn push(&self, change: DevStateChange, min_epoch_number: u64) {
+        self.inner.lock().pending.push((min_epoch_number, change));
+    }
+
+    /// Returns the changes to apply before executing the epoch of
+    /// `epoch_hash`. The pending changes are only taken by an epoch on the
+    /// local pivot chain.
+    pub(super) fn changes_for_epoch(
+        &self, epoch_hash: &H256, epoch_number: u64, on_local_pivot: bool,
+    ) -> Vec<DevStateChange> {
+        let inner = &mut *self.inner.lock();
+        if let Some(changes) = inner.applied.get(epoch_hash) {
+            return changes.clone();
+        }
+        if !on_local_pivot {
+            return Vec::new();
+        }
+        let (ready, pending) = inner.pending.drain(..).partition::<Vec<_>, _>(
+            |(min_epoch_number, _)| *min_epoch_number <= epoch_number,
+        );
+        inner.pending = pending;
+        if ready.is_empty() {
+            return Vec::new();
+        }
+        let changes: Vec<_> =
+            ready.into_iter().map(|(_, change)| change).collect();
+        inner.applied.insert(*epoch_hash, changes.clone());
+        inner.applied_order.push_back(*epoch_hash);
+        while inner.applied_order.len() > MAX_APPLIED_EPOCHS {
+            if let Some(oldest) = inner.applied_order.pop_front() {
+                inner.applied.remove(&oldest);
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cfx_types::{Address, AddressSpaceUtil, U256};
+
+    fn balance(value: u64) -> DevStateChange {
+        DevStateChange::Balance {
+            address: Address::repeat_byte(1).with_native_space(),
+            balance: U256::from(value),
+        }
+    }
+
+    #[test]
+    fn test_changes_for_epoch() {
+        let changes = DevStateChanges::default();
+        changes.push(balance(1), 10);
+        changes.push(balance(2), 12);
+
+        // Not on the local pivot chain, or not reached the epoch yet.
+        assert!(changes
+            .changes_for_epoch(&H256::repeat_byte(10), 10, false)
+            .is_empty());
+        assert!(changes
+            .changes_for_epoch(&H256::repeat_byte(9), 9, true)
+            .is_empty());
+
+        assert_eq!(
+            changes.changes_for_epoch(&H256::repeat_byte(10), 10, true),
+            vec![balance(1)]
+        );
+        // Re-executing the epoch, even off the pivot chain, reproduces it.
+        assert_eq!(
+            changes.changes_for_epoch(&H256::repeat_byte(10), 10, false),
+            vec![balance(1)]
+        );
+        // Taken only once.
+        assert!(changes
+            .changes_for_epoch(&H256::repeat_byte(11), 11, true)
+            .is_empty());
+        assert_eq!(
+            changes.changes_for_epoch(&H256::repeat_byte(13), 13, true),
+            vec![balance(2)]
+        );
+    }
+
+    #[test]
+    fn test_applied_changes_bounded() {
+        let changes = DevStateChanges::default();
+        let epochs = MAX_APPLIED_EPOCHS as u64 + 1;
+        for epoch in 0..epochs {
+            changes.push(balance(epoch), epoch);
+            let hash = H256::from_low_u64_be(epoch);
+            assert_eq!(
+                changes.changes_for_epoch(&hash, epoch, true),
+                vec![balance(epoch)]
+            );
+        }
+
+        let inner = changes.inner.lock();
+        assert_eq!(inner.applied.len(), MAX_APPLIED_EPOCHS);
+        assert_eq!(inner.applied_order.len(), MAX_APPLIED_EPOCHS);
+        // The oldest epoch is evicted.
+        assert!(!inner.applied.contains_key(&H256::from_low_u64_be(0)));
+        assert!(inner
+            .applied
+            .contains_key(&H256::from_low_u64_be(epochs - 1)));
+    }
+}
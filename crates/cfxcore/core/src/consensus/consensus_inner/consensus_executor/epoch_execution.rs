@@ -1,18 +1,30 @@
 use super::ConsensusExecutionHandler;
-use std::{collections::BTreeSet, convert::From, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashSet},
+    convert::From,
+    sync::Arc,
+};
 
-use alloy_rpc_types_trace::geth::GethDebugTracingOptions;
+use alloy_rpc_types_trace::{geth::GethDebugTracingOptions, parity::TraceType};
 use cfx_parameters::genesis::GENESIS_ACCOUNT_ADDRESS;
-use geth_tracer::{GethTraceWithHash, GethTracer, TxExecContext};
+use geth_tracer::{
+    GethTraceWithHash, GethTracer, ParityTraceKey, ParityTraceWithHash,
+    ParityTracer, TxExecContext,
+};
 use pow_types::StakingEvent;
 
-use cfx_statedb::{Error as DbErrorKind, Result as DbResult};
+use cfx_statedb::{Error as DbErrorKind, Result as DbResult, StateDb};
+use cfx_storage::{
+    StateWitnessRecorder, StorageStateTrait, WitnessRecordingStorage,
+};
 use cfx_types::{AddressSpaceUtil, Space, SpaceMap, H256, U256};
+use parking_lot::Mutex;
 use primitives::{
     receipt::BlockReceipts, Action, Block, BlockNumber, Receipt,
     SignedTransaction, TransactionIndex,
 };
 
+use super::witness::{BlockPosInfo, EpochExecutionWitness};
 use crate::{
     block_data_manager::BlockDataManager,
     consensus::consensus_inner::consensus_executor::GOOD_TPS_METER,
@@ -23,7 +35,10 @@ use cfx_execute_helper::{
     tx_outcome::{make_process_tx_outcome, ProcessTxOutcome},
 };
 use cfx_executor::{
-    executive::{ExecutiveContext, TransactOptions, TransactSettings},
+    executive::{
+        execute_in_parallel, ExecutionOutcome, ExecutiveContext, ForkDbs,
+        TransactOptions, TransactSettings,
+    },
     internal_contract::{
         block_hash_slot, epoch_hash_slot, initialize_internal_contract_accounts,
     },
@@ -32,10 +47,16 @@ use cfx_executor::{
         initialize_or_update_dao_voted_params, State,
     },
 };
-use cfx_vm_types::Env;
+use cfx_vm_types::{Env, Spec};
+
+/// Blocks with fewer transactions are executed sequentially.
+const PARALLEL_EXECUTION_MIN_TXS: usize = 4;
 
 pub enum VirtualCall<'a> {
     GethTrace(GethTask<'a>),
+    ParityTrace(ParityTask<'a>),
+    /// Re-execute the epoch of a witness.
+    Witness(WitnessTask<'a>),
 }
 
 pub struct GethTask<'a> {
@@ -44,11 +65,33 @@ pub struct GethTask<'a> {
     pub(super) answer: &'a mut Vec<GethTraceWithHash>,
 }
 
+pub struct ParityTask<'a> {
+    pub(super) tx_hash: Option<H256>,
+    pub(super) trace_types: HashSet<TraceType>,
+    pub(super) answer: &'a mut Vec<ParityTraceWithHash>,
+}
+
+pub struct WitnessTask<'a> {
+    pub(super) witness: &'a EpochExecutionWitness,
+}
+
+impl<'a> ParityTask<'a> {
+    fn need_trace(&self, transaction: &SignedTransaction) -> bool {
+        self.tx_hash.map_or(true, |hash| transaction.hash() == hash)
+    }
+
+    fn need_state_diff(&self, transaction: &SignedTransaction) -> bool {
+        self.trace_types.contains(&TraceType::StateDiff)
+            && self.need_trace(transaction)
+    }
+}
+
 impl ConsensusExecutionHandler {
     pub(super) fn process_epoch_transactions<'a>(
         &self, state: &mut State, epoch_blocks: &Vec<Arc<Block>>,
         start_block_number: u64, on_local_pivot: bool,
         virtual_call: Option<VirtualCall<'a>>,
+        witness_recorder: Option<&Arc<Mutex<StateWitnessRecorder>>>,
     ) -> DbResult<Vec<Arc<BlockReceipts>>> {
         self.prefetch_storage_for_execution(&*state, epoch_blocks);
 
@@ -81,6 +124,10 @@ impl ConsensusExecutionHandler {
             start_block_number,
         );
 
+        let parent_hash = pivot_block.block_header.parent_hash();
+        let fork_dbs =
+            self.fork_parent_state(parent_hash, &context, witness_recorder);
+
         for (idx, block) in epoch_blocks.iter().enumerate() {
             if idx > 0 {
                 block_context.next_block(block);
@@ -90,11 +137,18 @@ impl ConsensusExecutionHandler {
                 &block_context,
                 state,
                 &mut epoch_recorder,
+                fork_dbs.as_ref(),
             )?;
         }
 
-        if let Some(VirtualCall::GethTrace(task)) = context.virtual_call {
-            std::mem::swap(&mut epoch_recorder.geth_traces, task.answer);
+        match context.virtual_call {
+            Some(VirtualCall::GethTrace(task)) => {
+                std::mem::swap(&mut epoch_recorder.geth_traces, task.answer);
+            }
+            Some(VirtualCall::ParityTrace(task)) => {
+                std::mem::swap(&mut epoch_recorder.parity_traces, task.answer);
+            }
+            Some(VirtualCall::Witness(_)) | None => {}
         }
 
         if !dry_run && self.pos_verifier.pos_option().is_some() {
@@ -159,6 +213,98 @@ impl ConsensusExecutionHandler {
         }
     }
 
+    /// The databases for the parallel executor, which are views of the state
+    /// of the parent epoch taken from the shared pool. Return `None` if the
+    /// epoch should be executed sequentially.
+    ///
+    /// The reads of the parallel executor bypass the storage of the epoch, so
+    /// when the witness of the epoch is recorded, the databases record their
+    /// reads with `witness_recorder` too, and they aren't pooled.
+    fn fork_parent_state(
+        &self, parent_hash: &H256, context: &EpochProcessContext,
+        witness_recorder: Option<&Arc<Mutex<StateWitnessRecorder>>>,
+    ) -> Option<ForkDbs<'_, impl Fn() -> DbResult<StateDb> + Sync + '_>> {
+        if self.parallel_executor.is_none() || context.virtual_call.is_some() {
+            return None;
+        }
+        let parent_hash = *parent_hash;
+        let recorder = witness_recorder.cloned();
+        let open = move || {
+            let storage = self.open_state_storage(&parent_hash)?;
+            Ok(StateDb::new(match &recorder {
+                Some(recorder) => {
+                    Box::new(WitnessRecordingStorage::with_recorder(
+                        storage,
+                        recorder.clone(),
+                    ))
+                }
+                None => storage,
+            }))
+        };
+        let fork_dbs = if witness_recorder.is_some() {
+            ForkDbs::unpooled(open)
+        } else {
+            self.fork_db_pool.fork(parent_hash, open)
+        };
+        // Fall back to the sequential execution if the state of the parent
+        // epoch cannot be opened.
+        match fork_dbs.take() {
+            Ok(db) => {
+                fork_dbs.put(db);
+                Some(fork_dbs)
+            }
+            Err(e) => {
+                warn!(
+                    "Fail to open state {:?} for parallel execution: {:?}",
+                    parent_hash, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Open a view of the committed state of an executed epoch.
+    fn open_state_db(&self, epoch_hash: &H256) -> DbResult<StateDb> {
+        Ok(StateDb::new(self.open_state_storage(epoch_hash)?))
+    }
+
+    fn open_state_storage(
+        &self, epoch_hash: &H256,
+    ) -> DbResult<Box<dyn StorageStateTrait>> {
+        let state_index = self
+            .data_man
+            .get_state_readonly_index(epoch_hash)
+            .ok_or("state index not found")?;
+        Ok(self
+            .data_man
+            .storage_manager
+            .get_state_no_commit(
+                state_index,
+                /* try_open = */ false,
+                None,
+            )?
+            .ok_or("state not found")?)
+    }
+
+    /// The PoS status seen by the transactions of the block following
+    /// `last_hash`.
+    pub(super) fn block_pos_info(&self, last_hash: &H256) -> BlockPosInfo {
+        let last_block_header = &self.data_man.block_header_by_hash(last_hash);
+
+        let pos_id = last_block_header
+            .as_ref()
+            .and_then(|header| header.pos_reference().as_ref());
+        let pos_view = pos_id.and_then(|id| self.pos_verifier.get_pos_view(id));
+        let finalized_epoch = pos_id
+            .and_then(|id| self.pos_verifier.get_pivot_decision(id))
+            .and_then(|hash| self.data_man.block_header_by_hash(&hash))
+            .map(|header| header.height());
+        BlockPosInfo {
+            pos_view,
+            finalized_epoch,
+        }
+    }
+
     fn make_block_env(&self, block_context: &BlockProcessContext) -> Env {
         let BlockProcessContext {
             epoch_context:
@@ -166,6 +312,7 @@ impl ConsensusExecutionHandler {
                     pivot_block,
                     base_gas_price,
                     burnt_gas_price,
+                    ref virtual_call,
                     ..
                 },
             block,
@@ -173,17 +320,16 @@ impl ConsensusExecutionHandler {
             last_hash,
         } = *block_context;
 
-        let last_block_header = &self.data_man.block_header_by_hash(&last_hash);
-
-        let pos_id = last_block_header
-            .as_ref()
-            .and_then(|header| header.pos_reference().as_ref());
-        let pos_view_number =
-            pos_id.and_then(|id| self.pos_verifier.get_pos_view(id));
-        let pivot_decision_epoch = pos_id
-            .and_then(|id| self.pos_verifier.get_pivot_decision(id))
-            .and_then(|hash| self.data_man.block_header_by_hash(&hash))
-            .map(|header| header.height());
+        let BlockPosInfo {
+            pos_view,
+            finalized_epoch,
+        } = match virtual_call {
+            Some(VirtualCall::Witness(task)) => {
+                let idx = block_number - task.witness.start_block_number;
+                task.witness.pos_info[idx as usize].clone()
+            }
+            _ => self.block_pos_info(&last_hash),
+        };
 
         let epoch_height = pivot_block.block_header.height();
         let chain_id = self.machine.params().chain_id_map(epoch_height);
@@ -197,8 +343,8 @@ impl ConsensusExecutionHandler {
             last_hash,
             gas_limit: U256::from(block.block_header.gas_limit()),
             epoch_height,
-            pos_view: pos_view_number,
-            finalized_epoch: pivot_decision_epoch,
+            pos_view,
+            finalized_epoch,
             transaction_epoch_bound: self
                 .verification_config
                 .transaction_epoch_bound,
@@ -207,10 +353,14 @@ impl ConsensusExecutionHandler {
         }
     }
 
-    fn process_block_transactions(
+    fn process_block_transactions<F>(
         &self, block_context: &BlockProcessContext, state: &mut State,
         epoch_recorder: &mut EpochProcessRecorder,
-    ) -> DbResult<()> {
+        fork_dbs: Option<&ForkDbs<F>>,
+    ) -> DbResult<()>
+    where
+        F: Fn() -> DbResult<StateDb> + Sync,
+    {
         let BlockProcessContext {
             epoch_context: &EpochProcessContext { on_local_pivot, .. },
             block,
@@ -237,16 +387,39 @@ impl ConsensusExecutionHandler {
         let mut block_recorder =
             BlockProcessRecorder::new(epoch_recorder.evm_tx_idx);
 
-        for (idx, transaction) in block.transactions.iter().enumerate() {
-            self.process_transaction(
-                idx,
-                transaction,
-                block_context,
-                state,
-                &mut env,
-                on_local_pivot,
-                &mut block_recorder,
-            )?;
+        if let Some(outcomes) = self.execute_block_in_parallel(
+            block_context,
+            state,
+            &env,
+            fork_dbs,
+        )? {
+            let spec = self.machine.spec(env.number, env.epoch_height);
+            for (idx, (transaction, outcome)) in
+                block.transactions.iter().zip(outcomes).enumerate()
+            {
+                self.record_transaction_outcome(
+                    idx,
+                    transaction,
+                    outcome,
+                    block_context,
+                    &mut env,
+                    &spec,
+                    on_local_pivot,
+                    &mut block_recorder,
+                );
+            }
+        } else {
+            for (idx, transaction) in block.transactions.iter().enumerate() {
+                self.process_transaction(
+                    idx,
+                    transaction,
+                    block_context,
+                    state,
+                    &mut env,
+                    on_local_pivot,
+                    &mut block_recorder,
+                )?;
+            }
         }
 
         block_recorder.finish_block(
@@ -264,37 +437,181 @@ impl ConsensusExecutionHandler {
         block_context: &BlockProcessContext, state: &mut State, env: &mut Env,
         on_local_pivot: bool, recorder: &mut BlockProcessRecorder,
     ) -> DbResult<()> {
-        let rpc_index = recorder.tx_idx[transaction.space()];
+        let spec = self.machine.spec(env.number, env.epoch_height);
 
-        let block = &block_context.block;
-        let dry_run = block_context.epoch_context.dry_run;
+        let need_state_diff = match block_context.epoch_context.virtual_call {
+            Some(VirtualCall::ParityTrace(ref task)) => {
+                task.need_state_diff(transaction)
+            }
+            _ => false,
+        };
 
-        let machine = self.machine.as_ref();
+        let execution_outcome = if need_state_diff {
+            self.execute_transaction_with_state_diff(
+                transaction,
+                block_context,
+                state,
+                env,
+                &spec,
+            )?
+        } else {
+            let execution_outcome = self.execute_transaction(
+                transaction,
+                block_context,
+                state,
+                env,
+                &spec,
+            )?;
+
+            if let Some(burnt_fee) = execution_outcome
+                .try_as_executed()
+                .and_then(|e| e.burnt_fee)
+            {
+                state.burn_by_cip1559(burnt_fee);
+            };
+            execution_outcome
+        };
 
-        let spec = machine.spec(env.number, env.epoch_height);
+        self.record_transaction_outcome(
+            idx,
+            transaction,
+            execution_outcome,
+            block_context,
+            env,
+            &spec,
+            on_local_pivot,
+            recorder,
+        );
+        Ok(())
+    }
 
+    fn execute_transaction(
+        &self, transaction: &Arc<SignedTransaction>,
+        block_context: &BlockProcessContext, state: &mut State, env: &Env,
+        spec: &Spec,
+    ) -> DbResult<ExecutionOutcome> {
         let options = TransactOptions {
             observer: self.make_observer(transaction, block_context),
             settings: TransactSettings::all_checks(),
         };
 
-        let execution_outcome =
-            ExecutiveContext::new(state, env, machine, &spec)
-                .transact(transaction, options)?;
-        execution_outcome.log(transaction, &block_context.block.hash());
+        ExecutiveContext::new(state, env, &self.machine, spec)
+            .transact(transaction, options)
+    }
+
+    /// Execute a transaction traced with the state diff. The transaction is
+    /// executed in a fork of the state like the parallel executor does, so
+    /// the accounts it touched can be read from the states before and after
+    /// it.
+    fn execute_transaction_with_state_diff(
+        &self, transaction: &Arc<SignedTransaction>,
+        block_context: &BlockProcessContext, state: &mut State, env: &Env,
+        spec: &Spec,
+    ) -> DbResult<ExecutionOutcome> {
+        let parent_hash = *block_context
+            .epoch_context
+            .pivot_block
+            .block_header
+            .parent_hash();
+
+        let mut speculation = state.speculate();
+        let pre_state = speculation.fork(self.open_state_db(&parent_hash)?);
+        let mut post_state =
+            speculation.fork(self.open_state_db(&parent_hash)?);
+
+        let mut outcome = self.execute_transaction(
+            transaction,
+            block_context,
+            &mut post_state,
+            env,
+            spec,
+        )?;
+
+        if let Some(trace) = outcome
+            .try_as_executed_mut()
+            .and_then(|e| e.ext_result.get_mut::<ParityTraceKey>())
+        {
+            let space = transaction.space();
+            let pre = trace.touched.snapshot(&pre_state, space)?;
+            let post = trace.touched.snapshot(&post_state, space)?;
+            trace.set_state_diff(pre, post);
+        }
 
-        if let Some(burnt_fee) = execution_outcome
-            .try_as_executed()
-            .and_then(|e| e.burnt_fee)
+        drop(pre_state);
+        let (changes, _) = post_state.into_speculative_changes();
+        speculation.merge(changes);
+        if let Some(burnt_fee) =
+            outcome.try_as_executed().and_then(|e| e.burnt_fee)
         {
-            state.burn_by_cip1559(burnt_fee);
+            speculation.burn_by_cip1559(burnt_fee);
+        }
+        Ok(outcome)
+    }
+
+    /// Execute the transactions of a block with the parallel executor. Return
+    /// `None` if the block should be executed sequentially.
+    fn execute_block_in_parallel<F>(
+        &self, block_context: &BlockProcessContext, state: &mut State,
+        env: &Env, fork_dbs: Option<&ForkDbs<F>>,
+    ) -> DbResult<Option<Vec<ExecutionOutcome>>>
+    where
+        F: Fn() -> DbResult<StateDb> + Sync,
+    {
+        let transactions = &block_context.block.transactions;
+        let (pool, fork_dbs) = match (self.parallel_executor.as_ref(), fork_dbs)
+        {
+            (Some(pool), Some(fork_dbs))
+                if transactions.len() >= PARALLEL_EXECUTION_MIN_TXS =>
+            {
+                (pool, fork_dbs)
+            }
+            _ => return Ok(None),
         };
 
+        let spec = self.machine.spec(env.number, env.epoch_height);
+        let (outcomes, stats) = execute_in_parallel(
+            state,
+            transactions.len(),
+            pool,
+            fork_dbs,
+            |state, idx| {
+                self.execute_transaction(
+                    &transactions[idx],
+                    block_context,
+                    state,
+                    env,
+                    &spec,
+                )
+            },
+            |outcome| outcome.try_as_executed().and_then(|e| e.burnt_fee),
+        )?;
+        debug!(
+            "parallel execution: block={:?} txs={} reexecuted={}",
+            block_context.block.hash(),
+            stats.transactions,
+            stats.reexecuted
+        );
+        Ok(Some(outcomes))
+    }
+
+    fn record_transaction_outcome(
+        &self, idx: usize, transaction: &Arc<SignedTransaction>,
+        execution_outcome: ExecutionOutcome,
+        block_context: &BlockProcessContext, env: &mut Env, spec: &Spec,
+        on_local_pivot: bool, recorder: &mut BlockProcessRecorder,
+    ) {
+        let rpc_index = recorder.tx_idx[transaction.space()];
+
+        let block = &block_context.block;
+        let dry_run = block_context.epoch_context.dry_run;
+
+        execution_outcome.log(transaction, &block_context.block.hash());
+
         let r = make_process_tx_outcome(
             execution_outcome,
             &mut env.accumulated_gas_used,
             transaction.hash,
-            &spec,
+            spec,
         );
 
         if r.receipt.tx_success() {
@@ -308,7 +625,7 @@ impl ConsensusExecutionHandler {
 
         if !on_local_pivot || tx_skipped || dry_run {
             // Skip transaction index persist
-            return Ok(());
+            return;
         }
 
         let hash = transaction.hash();
@@ -345,8 +662,6 @@ impl ConsensusExecutionHandler {
 
             *evm_tx_index += 1;
         }
-
-        Ok(())
     }
 
     fn make_observer(
@@ -363,34 +678,49 @@ impl ConsensusExecutionHandler {
             Observer::with_no_tracing()
         };
 
-        if let Some(VirtualCall::GethTrace(ref task)) =
-            block_context.epoch_context.virtual_call
-        {
-            let need_trace =
-                task.tx_hash.map_or(true, |hash| transaction.hash() == hash);
-            let support_tracer = matches!(
-                task.opts.tracer,
-                Some(BuiltInTracer(
-                    FourByteTracer | CallTracer | PreStateTracer | NoopTracer
-                )) | None
-            );
-            let tx_gas_limit = transaction.gas_limit().as_u64();
-
-            if need_trace && support_tracer {
-                observer.geth_tracer = Some(GethTracer::new(
-                    TxExecContext {
-                        tx_gas_limit,
-                        block_height: block_context
-                            .epoch_context
-                            .pivot_block
-                            .block_header
-                            .height(),
-                        block_number: block_context.block_number,
-                    },
-                    Arc::clone(&self.machine),
-                    task.opts.clone(),
-                ))
+        let tx_exec_context = || TxExecContext {
+            tx_gas_limit: transaction.gas_limit().as_u64(),
+            block_height: block_context
+                .epoch_context
+                .pivot_block
+                .block_header
+                .height(),
+            block_number: block_context.block_number,
+        };
+
+        match block_context.epoch_context.virtual_call {
+            Some(VirtualCall::GethTrace(ref task)) => {
+                let need_trace = task
+                    .tx_hash
+                    .map_or(true, |hash| transaction.hash() == hash);
+                let support_tracer = matches!(
+                    task.opts.tracer,
+                    Some(BuiltInTracer(
+                        FourByteTracer
+                            | CallTracer
+                            | PreStateTracer
+                            | NoopTracer
+                    )) | None
+                );
+
+                if need_trace && support_tracer {
+                    observer.geth_tracer = Some(GethTracer::new(
+                        tx_exec_context(),
+                        Arc::clone(&self.machine),
+                        task.opts.clone(),
+                    ))
+                }
             }
+            Some(VirtualCall::ParityTrace(ref task)) => {
+                if task.need_trace(transaction) {
+                    observer.parity_tracer = Some(ParityTracer::new(
+                        tx_exec_context(),
+                        Arc::clone(&self.machine),
+                        task.trace_types.clone(),
+                    ))
+                }
+            }
+            Some(VirtualCall::Witness(_)) | None => {}
         }
         observer
     }
@@ -514,6 +844,7 @@ struct EpochProcessRecorder {
     staking_events: Vec<StakingEvent>,
     repack_tx: Vec<Arc<SignedTransaction>>,
     geth_traces: Vec<GethTraceWithHash>,
+    parity_traces: Vec<ParityTraceWithHash>,
 
     evm_tx_idx: usize,
 }
@@ -527,6 +858,7 @@ struct BlockProcessRecorder {
     tx_error_msg: Vec<String>,
     traces: Vec<TransactionExecTraces>,
     geth_traces: Vec<GethTraceWithHash>,
+    parity_traces: Vec<ParityTraceWithHash>,
     repack_tx: Vec<Arc<SignedTransaction>>,
     staking_events: Vec<StakingEvent>,
 
@@ -542,6 +874,7 @@ impl BlockProcessRecorder {
             tx_error_msg: vec![],
             traces: vec![],
             geth_traces: vec![],
+            parity_traces: vec![],
             repack_tx: vec![],
             staking_events: vec![],
             tx_idx,
@@ -580,6 +913,14 @@ impl BlockProcessRecorder {
             });
         }
 
+        if let Some(trace) = r.parity_trace {
+            self.parity_traces.push(ParityTraceWithHash {
+                trace: trace.results,
+                tx_hash: tx.hash(),
+                space: tx.space(),
+            });
+        }
+
         match tx.space() {
             Space::Native => {
                 self.tx_idx[Space::Native] += 1;
@@ -623,6 +964,7 @@ impl BlockProcessRecorder {
         epoch_recorder.staking_events.extend(self.staking_events);
         epoch_recorder.repack_tx.extend(self.repack_tx);
         epoch_recorder.geth_traces.extend(self.geth_traces);
+        epoch_recorder.parity_traces.extend(self.parity_traces);
 
         epoch_recorder.evm_tx_idx = self.tx_idx[Space::Ethereum];
 
@@ -2,11 +2,21 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
+mod dev_state;
 mod epoch_execution;
+mod witness;
+
+pub use self::{
+    dev_state::DevStateChanges,
+    witness::{
+        AuthorReward, BlockPosInfo, CommittedExecutionRoots,
+        EpochExecutionWitness, EpochPosUpdate, EpochRewards, PosVotes,
+    },
+};
 
 use core::convert::TryFrom;
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     convert::From,
     fmt::{Debug, Formatter},
     sync::{
@@ -18,7 +28,7 @@ use std::{
 };
 
 use hash::KECCAK_EMPTY_LIST_RLP;
-use parking_lot::{Mutex, RwLock};
+use parking_lot::{Mutex, RwLock, RwLockWriteGuard};
 use rayon::{ThreadPool, ThreadPoolBuilder};
 use rustc_hex::ToHex;
 
@@ -29,11 +39,12 @@ use cfx_parameters::consensus::*;
 use cfx_statedb::{Result as DbResult, StateDb};
 use cfx_storage::{
     defaults::DEFAULT_EXECUTION_PREFETCH_THREADS, StateIndex,
-    StorageManagerTrait,
+    StorageManagerTrait, StorageState, StorageStateTrait,
+    WitnessRecordingStorage,
 };
 use cfx_types::{
-    address_util::AddressUtil, AddressSpaceUtil, AllChainID, BigEndianHash,
-    Space, H160, H256, KECCAK_EMPTY_BLOOM, U256, U512,
+    address_util::AddressUtil, AllChainID, BigEndianHash, Space, H160, H256,
+    KECCAK_EMPTY_BLOOM, U256, U512,
 };
 use metrics::{register_meter_with_group, Meter, MeterTimer};
 use primitives::{
@@ -62,19 +73,16 @@ use cfx_execute_helper::estimation::{
     EstimateExt, EstimateRequest, EstimationContext,
 };
 use cfx_executor::{
-    executive::ExecutionOutcome,
+    executive::{ExecutionOutcome, ForkDbPool},
     machine::Machine,
-    state::{
-        distribute_pos_interest, update_pos_status, CleanupMode, State,
-        StateCommitResult,
-    },
+    state::{State, StateCommitResult},
 };
 use cfx_vm_types::{Env, Spec};
-use geth_tracer::GethTraceWithHash;
+use geth_tracer::{GethTraceWithHash, ParityTraceWithHash};
 
-use alloy_rpc_types_trace::geth::GethDebugTracingOptions;
+use alloy_rpc_types_trace::{geth::GethDebugTracingOptions, parity::TraceType};
 
-use self::epoch_execution::{GethTask, VirtualCall};
+use self::epoch_execution::{GethTask, ParityTask, VirtualCall};
 
 lazy_static! {
     static ref CONSENSIS_EXECUTION_TIMER: Arc<dyn Meter> =
@@ -650,6 +658,27 @@ impl ConsensusExecutor {
         )
     }
 
+    pub fn collect_blocks_parity_trace(
+        &self, epoch_id: H256, epoch_num: u64, blocks: &Vec<Arc<Block>>,
+        trace_types: HashSet<TraceType>, tx_hash: Option<H256>,
+    ) -> CoreResult<Vec<ParityTraceWithHash>> {
+        self.handler.collect_blocks_parity_trace(
+            epoch_id,
+            epoch_num,
+            blocks,
+            trace_types,
+            tx_hash,
+        )
+    }
+
+    pub fn verify_epoch_witness(
+        &self, witness: &EpochExecutionWitness, epoch_blocks: &Vec<Arc<Block>>,
+        committed: &CommittedExecutionRoots,
+    ) -> CoreResult<EpochExecutionCommitment> {
+        self.handler
+            .verify_epoch_witness(witness, epoch_blocks, committed)
+    }
+
     pub fn stop(&self) {
         // `stopped` is used to allow the execution thread to stopped even the
         // queue is not empty and `ExecutionTask::Stop` has not been
@@ -828,6 +857,14 @@ pub struct ConsensusExecutionHandler {
     machine: Arc<Machine>,
     pos_verifier: Arc<PosVerifier>,
     execution_state_prefetcher: Option<ThreadPool>,
+    parallel_executor: Option<ThreadPool>,
+    /// The databases of the parallel executor, shared by all the epochs.
+    fork_db_pool: ForkDbPool,
+    pub dev_state_changes: DevStateChanges,
+    /// Held for read while an epoch is computed, and for write while the
+    /// execution is paused.
+    execution_pause: RwLock<()>,
+    last_computed_epoch: Mutex<Option<H256>>,
 }
 
 impl ConsensusExecutionHandler {
@@ -837,6 +874,14 @@ impl ConsensusExecutionHandler {
         verification_config: VerificationConfig, machine: Arc<Machine>,
         pos_verifier: Arc<PosVerifier>,
     ) -> Self {
+        let parallel_executor =
+            config.parallel_execution_threads.map(|num_threads| {
+                ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .thread_name(|i| format!("parallel_execution_{}", i))
+                    .build()
+                    .unwrap()
+            });
         ConsensusExecutionHandler {
             tx_pool,
             data_man,
@@ -856,9 +901,23 @@ impl ConsensusExecutionHandler {
             } else {
                 None
             },
+            parallel_executor,
+            fork_db_pool: ForkDbPool::new(),
+            dev_state_changes: Default::default(),
+            execution_pause: Default::default(),
+            last_computed_epoch: Default::default(),
         }
     }
 
+    /// Pause the execution of epochs until the returned guard is dropped, and
+    /// return the last epoch computed before the pause. The epoch being
+    /// computed, if any, is finished first.
+    pub fn pause_execution(&self) -> (RwLockWriteGuard<'_, ()>, Option<H256>) {
+        let guard = self.execution_pause.write();
+        let last_computed_epoch = *self.last_computed_epoch.lock();
+        (guard, last_computed_epoch)
+    }
+
     /// Always return `true` for now
     fn handle_execution_work(&self, task: ExecutionTask) -> bool {
         debug!("Receive execution task: {:?}", task);
@@ -912,6 +971,15 @@ impl ConsensusExecutionHandler {
         &self, pivot_block: &Block,
         recover_mpt_during_construct_pivot_state: bool,
     ) -> DbResult<State> {
+        let storage = self
+            .new_storage(pivot_block, recover_mpt_during_construct_pivot_state);
+        State::new(StateDb::new(storage))
+    }
+
+    fn new_storage(
+        &self, pivot_block: &Block,
+        recover_mpt_during_construct_pivot_state: bool,
+    ) -> Box<dyn StorageStateTrait> {
         let state_root_with_aux_info = &self
             .data_man
             .get_epoch_execution_commitment(
@@ -928,8 +996,7 @@ impl ConsensusExecutionHandler {
             self.data_man.get_snapshot_epoch_count(),
         );
 
-        let storage = self
-            .data_man
+        self.data_man
             .storage_manager
             .get_state_for_next_epoch(
                 state_index,
@@ -937,10 +1004,29 @@ impl ConsensusExecutionHandler {
             )
             .expect("No db error")
             // Unwrapping is safe because the state exists.
-            .expect("State exists");
+            .expect("State exists")
+    }
 
-        let state_db = StateDb::new(storage);
-        State::new(state_db)
+    /// Open the committed state of the parent epoch, with which the reads of
+    /// the epoch are proved in its witness.
+    fn open_parent_state_for_witness(
+        &self, pivot_block: &Block,
+    ) -> DbResult<StorageState> {
+        let parent_hash = pivot_block.block_header.parent_hash();
+        let parent_state_root = &self
+            .data_man
+            .get_epoch_execution_commitment(parent_hash)
+            .ok_or("parent epoch not executed")?
+            .state_root_with_aux_info;
+        Ok(self
+            .data_man
+            .storage_manager
+            .get_state_no_commit_inner(
+                StateIndex::new_for_readonly(parent_hash, parent_state_root),
+                /* try_open = */ false,
+                /* open_mpt_snapshot = */ false,
+            )?
+            .ok_or("parent state not found")?)
     }
 
     pub fn epoch_executed_and_recovered(
@@ -991,6 +1077,7 @@ impl ConsensusExecutionHandler {
         // FIXME: Question: where to calculate if we should make a snapshot?
         // FIXME: Currently we make the snapshotting decision when committing
         // FIXME: a new state.
+        let _execution_pause = self.execution_pause.read();
 
         // persist block number index
         // note: we need to persist before execution because in some cases,
@@ -1024,6 +1111,7 @@ impl ConsensusExecutionHandler {
                 &pivot_block_header,
                 on_local_pivot,
             );
+            *self.last_computed_epoch.lock() = Some(*epoch_hash);
             return;
         }
 
@@ -1043,9 +1131,37 @@ impl ConsensusExecutionHandler {
             epoch_blocks.len(),
         );
 
-        let mut state = self
-            .new_state(pivot_block, recover_mpt_during_construct_pivot_state)
-            .expect("Cannot init state");
+        let dev_state_changes = self.dev_state_changes.changes_for_epoch(
+            epoch_hash,
+            pivot_block_header.height(),
+            on_local_pivot,
+        );
+
+        let mut storage = self
+            .new_storage(pivot_block, recover_mpt_during_construct_pivot_state);
+        // The state changes of the dev mode can't be re-executed from a
+        // witness.
+        let mut witness_recorder = None;
+        if self.config.execution_witness && dev_state_changes.is_empty() {
+            match self.open_parent_state_for_witness(pivot_block) {
+                Ok(parent_state) => {
+                    let recording_storage =
+                        WitnessRecordingStorage::new(storage, parent_state);
+                    witness_recorder = Some(recording_storage.recorder());
+                    storage = Box::new(recording_storage);
+                }
+                Err(e) => warn!(
+                    "Fail to record the witness of epoch {:?}: {:?}",
+                    epoch_hash, e
+                ),
+            }
+        }
+        let mut state =
+            State::new(StateDb::new(storage)).expect("Cannot init state");
+
+        for change in dev_state_changes {
+            state.apply_dev_state_change(&change).expect("db error");
+        }
 
         let epoch_receipts = self
             .process_epoch_transactions(
@@ -1054,6 +1170,7 @@ impl ConsensusExecutionHandler {
                 start_block_number,
                 on_local_pivot,
                 /* virtual_call */ None,
+                witness_recorder.as_ref(),
             )
             // TODO: maybe propagate the error all the way up so that the
             // program may restart by itself.
@@ -1062,28 +1179,28 @@ impl ConsensusExecutionHandler {
         let current_block_number =
             start_block_number + epoch_receipts.len() as u64 - 1;
 
-        if let Some(reward_execution_info) = reward_execution_info {
-            let spec = self
-                .machine
-                .spec(current_block_number, pivot_block.block_header.height());
-            // Calculate the block reward for blocks inside the epoch
-            // All transaction fees are shared among blocks inside one epoch
-            self.process_rewards_and_fees(
-                &mut state,
-                &reward_execution_info,
-                epoch_hash,
-                on_local_pivot,
-                debug_record.as_deref_mut(),
-                spec,
-            );
-        }
+        let epoch_rewards =
+            reward_execution_info.as_ref().map(|reward_execution_info| {
+                let spec = self.machine.spec(
+                    current_block_number,
+                    pivot_block.block_header.height(),
+                );
+                // Calculate the block reward for blocks inside the epoch
+                // All transaction fees are shared among blocks inside one
+                // epoch
+                self.process_rewards_and_fees(
+                    &mut state,
+                    &reward_execution_info,
+                    epoch_hash,
+                    on_local_pivot,
+                    debug_record.as_deref_mut(),
+                    spec,
+                )
+            });
 
-        self.process_pos_interest(
-            &mut state,
-            pivot_block,
-            current_block_number,
-        )
-        .expect("db error");
+        let pos_update = self
+            .process_pos_interest(&mut state, pivot_block, current_block_number)
+            .expect("db error");
 
         let commit_result = state
             .commit(*epoch_hash, debug_record.as_deref_mut())
@@ -1108,10 +1225,30 @@ impl ConsensusExecutionHandler {
             "compute_epoch: on_local_pivot={}, epoch={:?} state_root={:?} receipt_root={:?}, logs_bloom_hash={:?}",
             on_local_pivot, epoch_hash, commit_result.state_root, epoch_execution_commitment.receipts_root, epoch_execution_commitment.logs_bloom_hash,
         );
+
+        if let Some(recorder) = witness_recorder {
+            let witness = self.make_epoch_witness(
+                recorder,
+                &epoch_blocks,
+                start_block_number,
+                epoch_rewards,
+                pos_update,
+            );
+            match witness {
+                Ok(witness) => {
+                    self.data_man.insert_epoch_execution_witness(&witness)
+                }
+                Err(e) => warn!(
+                    "Fail to record the witness of epoch {:?}: {:?}",
+                    epoch_hash, e
+                ),
+            }
+        }
         self.data_man
             .state_availability_boundary
             .write()
             .adjust_upper_bound(&pivot_block.block_header);
+        *self.last_computed_epoch.lock() = Some(*epoch_hash);
     }
 
     fn update_on_skipped_execution(
@@ -1153,7 +1290,21 @@ impl ConsensusExecutionHandler {
     fn process_pos_interest(
         &self, state: &mut State, pivot_block: &Block,
         current_block_number: u64,
-    ) -> DbResult<()> {
+    ) -> DbResult<EpochPosUpdate> {
+        let pos_update = self.compute_pos_update(pivot_block);
+        if let Some(account_rewards) =
+            pos_update.apply(state, current_block_number)?
+        {
+            self.data_man.insert_pos_reward(
+                pos_update.interest_pos_epoch.expect("distributed"),
+                &PosRewardInfo::new(account_rewards, pivot_block.hash()),
+            )
+        }
+        Ok(pos_update)
+    }
+
+    fn compute_pos_update(&self, pivot_block: &Block) -> EpochPosUpdate {
+        let mut pos_update = EpochPosUpdate::default();
         // TODO(peilun): Specify if we unlock before or after executing the
         // transactions.
         let maybe_parent_pos_ref = self
@@ -1175,12 +1326,11 @@ impl ConsensusExecutionHandler {
             // The pos_reference is continuous, so after seeing a new
             // pos_reference, we only need to process the new
             // unlock_txs in it.
-            for (unlock_node_id, votes) in self
+            for (node_id, votes) in self
                 .pos_verifier
                 .get_unlock_nodes(current_pos_ref, parent_pos_ref)
             {
-                debug!("unlock node: {:?} {}", unlock_node_id, votes);
-                update_pos_status(state, unlock_node_id, votes)?;
+                pos_update.unlocked.push(PosVotes { node_id, votes });
             }
             if let Some((pos_epoch, reward_event)) = self
                 .pos_verifier
@@ -1189,19 +1339,17 @@ impl ConsensusExecutionHandler {
                 .and_then(|x| x.first())
             {
                 debug!("distribute_pos_interest: {:?}", reward_event);
-                let account_rewards: Vec<(H160, H256, U256)> =
-                    distribute_pos_interest(
-                        state,
-                        reward_event.rewards(),
-                        current_block_number,
-                    )?;
-                self.data_man.insert_pos_reward(
-                    *pos_epoch,
-                    &PosRewardInfo::new(account_rewards, pivot_block.hash()),
-                )
+                pos_update.interest_pos_epoch = Some(*pos_epoch);
+                pos_update.interest_points = reward_event
+                    .rewards()
+                    .map(|(node_id, votes)| PosVotes {
+                        node_id: *node_id,
+                        votes,
+                    })
+                    .collect();
             }
         }
-        Ok(())
+        pos_update
     }
 
     fn notify_txpool(
@@ -1245,7 +1393,7 @@ impl ConsensusExecutionHandler {
         &self, state: &mut State, reward_info: &RewardExecutionInfo,
         epoch_later: &H256, on_local_pivot: bool,
         mut debug_record: Option<&mut ComputeEpochDebugRecord>, spec: Spec,
-    ) {
+    ) -> EpochRewards {
         /// (Fee, SetOfPackingBlockHash)
         struct TxExecutionInfo(U256, BTreeSet<H256>);
 
@@ -1521,16 +1669,12 @@ impl ConsensusExecutionHandler {
 
         debug!("Give rewards merged_reward={:?}", merged_rewards);
 
+        let mut rewards = Vec::with_capacity(merged_rewards.len());
         for (address, reward) in merged_rewards {
-            if spec.is_valid_address(&address) {
-                state
-                    .add_balance(
-                        &address.with_native_space(),
-                        &reward,
-                        CleanupMode::ForceCreate,
-                    )
-                    .unwrap();
-            }
+            rewards.push(AuthorReward {
+                author: address,
+                reward,
+            });
 
             if let Some(debug_out) = &mut debug_record {
                 debug_out
@@ -1546,14 +1690,13 @@ impl ConsensusExecutionHandler {
                 });
             }
         }
-        let new_mint = total_base_reward + allocated_secondary_reward;
-        if new_mint >= burnt_fee {
-            // The very likely case
-            state.add_total_issued(new_mint - burnt_fee);
-        } else {
-            // The very unlikely case
-            state.sub_total_issued(burnt_fee - new_mint);
-        }
+        let epoch_rewards = EpochRewards {
+            rewards,
+            new_mint: total_base_reward + allocated_secondary_reward,
+            burnt_fee,
+        };
+        epoch_rewards.apply(state, &spec).unwrap();
+        epoch_rewards
     }
 
     fn recompute_states(
@@ -1573,6 +1716,7 @@ impl ConsensusExecutionHandler {
             start_block_number,
             false,
             /* virtual_call */ None,
+            /* witness_recorder */ None,
         )
     }
 
@@ -1698,6 +1842,42 @@ impl ConsensusExecutionHandler {
             start_block_number,
             false,
             Some(virtual_call),
+            /* witness_recorder */ None,
+        )?;
+
+        Ok(answer)
+    }
+
+    pub fn collect_blocks_parity_trace(
+        &self, epoch_id: H256, epoch_num: u64, blocks: &Vec<Arc<Block>>,
+        trace_types: HashSet<TraceType>, tx_hash: Option<H256>,
+    ) -> CoreResult<Vec<ParityTraceWithHash>> {
+        let state_space = None;
+        let mut state = self.get_state_by_epoch_id_and_space(
+            &epoch_id,
+            epoch_num,
+            state_space,
+        )?;
+
+        let start_block_number = self
+            .data_man
+            .get_epoch_execution_context(&epoch_id)
+            .map(|v| v.start_block_number)
+            .expect("should exist");
+
+        let mut answer = vec![];
+        let virtual_call = VirtualCall::ParityTrace(ParityTask {
+            tx_hash,
+            trace_types,
+            answer: &mut answer,
+        });
+        self.process_epoch_transactions(
+            &mut state,
+            blocks,
+            start_block_number,
+            false,
+            Some(virtual_call),
+            /* witness_recorder */ None,
         )?;
 
         Ok(answer)
@@ -1744,4 +1924,10 @@ impl ConsensusExecutionHandler {
 
 pub struct ConsensusExecutionConfiguration {
     pub executive_trace: bool,
+    /// Record the witness of the execution of each epoch, with which the
+    /// epoch can be re-executed statelessly.
+    pub execution_witness: bool,
+    /// Execute the transactions of a block optimistically in parallel with
+    /// this number of threads, or sequentially if `None`.
+    pub parallel_execution_threads: Option<usize>,
 }
@@ -0,0 +1,279 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use super::{
+    epoch_execution::{VirtualCall, WitnessTask},
+    ConsensusExecutionHandler,
+};
+use crate::{
+    errors::Result as CoreResult,
+    verification::{compute_receipts_root, compute_transaction_root},
+};
+use cfx_executor::state::{
+    distribute_pos_interest, update_pos_status, CleanupMode, State,
+};
+use cfx_internal_common::{EpochExecutionCommitment, StateRootWithAuxInfo};
+use cfx_statedb::{Result as DbResult, StateDb};
+use cfx_storage::{StateWitness, StateWitnessRecorder, WitnessStorage};
+use cfx_types::{AddressSpaceUtil, H160, H256, U256};
+use cfx_vm_types::Spec;
+use parking_lot::Mutex;
+use primitives::{Block, BlockHeaderBuilder};
+use rlp_derive::{RlpDecodable, RlpEncodable};
+use std::sync::Arc;
+
+/// The witness of the execution of an epoch, with which the epoch can be
+/// re-executed from its blocks without the state of the node.
+///
+/// The resulting state root, receipts root and logs bloom hash are checked
+/// against the roots committed by the pivot chain, see
+/// `verify_epoch_witness`. So
+/// `pos_info`, `rewards` and `pos_update`, which are computed from the history
+/// of the tree-graph and the PoS chain, are only hints for the re-execution.
+///
+/// The verifier of a witness trusts:
+/// - `parent_state_root`, which must be checked against the state root of the
+///   parent epoch committed by the pivot chain;
+/// - the blocks of the epoch other than the pivot block, which must be checked
+///   against the epoch of the pivot block in the tree-graph;
+/// - the completeness of the prefix reads, as only the existence of the
+///   key-values returned can be proved.
+#[derive(Clone, Debug, RlpEncodable, RlpDecodable)]
+pub struct EpochExecutionWitness {
+    pub epoch_hash: H256,
+    pub start_block_number: u64,
+    /// The PoS status seen by each block of the epoch.
+    pub pos_info: Vec<BlockPosInfo>,
+    pub parent_state_root: StateRootWithAuxInfo,
+    /// The resulting state root, of which the snapshot root and the
+    /// intermediate delta root aren't recomputed by the re-execution.
+    pub state_root: StateRootWithAuxInfo,
+    /// The rewards of the epoch executed for the reward, if any.
+    pub rewards: Option<EpochRewards>,
+    pub pos_update: EpochPosUpdate,
+    pub state: StateWitness,
+}
+
+/// The roots of the execution of an epoch committed by the pivot chain.
+///
+/// The deferred roots of a pivot header with blame `b` are the vector roots of
+/// the roots of the `b + 1` epochs it covers, so the roots of an epoch can't
+/// be read from the header deferring it unless its blame is 0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommittedExecutionRoots {
+    pub state_root_hash: H256,
+    pub receipts_root: H256,
+    pub logs_bloom_hash: H256,
+}
+
+/// The PoS status seen by the transactions of a block.
+#[derive(Clone, Debug, Default, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct BlockPosInfo {
+    pub pos_view: Option<u64>,
+    pub finalized_epoch: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct AuthorReward {
+    pub author: H160,
+    pub reward: U256,
+}
+
+/// The block rewards and transaction fees given in an epoch.
+#[derive(Clone, Debug, Default, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct EpochRewards {
+    pub rewards: Vec<AuthorReward>,
+    pub new_mint: U256,
+    pub burnt_fee: U256,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct PosVotes {
+    pub node_id: H256,
+    pub votes: u64,
+}
+
+/// The changes of the PoS chain applied at the end of an epoch.
+#[derive(Clone, Debug, Default, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct EpochPosUpdate {
+    pub unlocked: Vec<PosVotes>,
+    /// The PoS epoch whose interest is distributed, if any.
+    pub interest_pos_epoch: Option<u64>,
+    pub interest_points: Vec<PosVotes>,
+}
+
+impl EpochRewards {
+    pub fn apply(&self, state: &mut State, spec: &Spec) -> DbResult<()> {
+        for AuthorReward { author, reward } in &self.rewards {
+            if spec.is_valid_address(author) {
+                state.add_balance(
+                    &author.with_native_space(),
+                    reward,
+                    CleanupMode::ForceCreate,
+                )?;
+            }
+        }
+        if self.new_mint >= self.burnt_fee {
+            // The very likely case
+            state.add_total_issued(self.new_mint - self.burnt_fee);
+        } else {
+            // The very unlikely case
+            state.sub_total_issued(self.burnt_fee - self.new_mint);
+        }
+        Ok(())
+    }
+}
+
+impl EpochPosUpdate {
+    /// Return the interest given to the accounts, if distributed.
+    pub fn apply(
+        &self, state: &mut State, current_block_number: u64,
+    ) -> DbResult<Option<Vec<(H160, H256, U256)>>> {
+        for PosVotes { node_id, votes } in &self.unlocked {
+            debug!("unlock node: {:?} {}", node_id, votes);
+            update_pos_status(state, *node_id, *votes)?;
+        }
+        if self.interest_pos_epoch.is_none() {
+            return Ok(None);
+        }
+        let points = self
+            .interest_points
+            .iter()
+            .map(|PosVotes { node_id, votes }| (node_id, *votes));
+        let account_rewards =
+            distribute_pos_interest(state, points, current_block_number)?;
+        Ok(Some(account_rewards))
+    }
+}
+
+impl ConsensusExecutionHandler {
+    /// Make the witness of an executed epoch from the accesses recorded by the
+    /// execution.
+    pub(super) fn make_epoch_witness(
+        &self, recorder: Arc<Mutex<StateWitnessRecorder>>,
+        epoch_blocks: &Vec<Arc<Block>>, start_block_number: u64,
+        rewards: Option<EpochRewards>, pos_update: EpochPosUpdate,
+    ) -> DbResult<EpochExecutionWitness> {
+        let pivot_block = epoch_blocks.last().expect("Not empty");
+        let epoch_hash = pivot_block.hash();
+        let parent_hash = pivot_block.block_header.parent_hash();
+
+        let parent_commitment = self
+            .data_man
+            .get_epoch_execution_commitment_with_db(parent_hash)
+            .ok_or("parent epoch not executed")?;
+        let commitment = self
+            .data_man
+            .get_epoch_execution_commitment_with_db(&epoch_hash)
+            .ok_or("epoch not executed")?;
+
+        let state = Arc::try_unwrap(recorder)
+            .map_err(|_| "witness recorder still in use")?
+            .into_inner()
+            .finish(
+                &parent_commitment.state_root_with_aux_info,
+                &commitment.state_root_with_aux_info,
+            )?;
+
+        let mut last_hash = *parent_hash;
+        let pos_info = epoch_blocks
+            .iter()
+            .map(|block| {
+                let pos_info = self.block_pos_info(&last_hash);
+                last_hash = block.hash();
+                pos_info
+            })
+            .collect();
+
+        Ok(EpochExecutionWitness {
+            epoch_hash,
+            start_block_number,
+            pos_info,
+            parent_state_root: parent_commitment.state_root_with_aux_info,
+            state_root: commitment.state_root_with_aux_info,
+            rewards,
+            pos_update,
+            state,
+        })
+    }
+
+    /// Re-execute the epoch of a witness with the key-values in it only, and
+    /// check the result against `committed`, the roots of the epoch committed
+    /// by the pivot chain. Neither the state nor the block data of the node is
+    /// read.
+    ///
+    /// `epoch_blocks` are the blocks of the epoch in the execution order with
+    /// the pivot block at last, whose transactions are recovered.
+    pub fn verify_epoch_witness(
+        &self, witness: &EpochExecutionWitness, epoch_blocks: &Vec<Arc<Block>>,
+        committed: &CommittedExecutionRoots,
+    ) -> CoreResult<EpochExecutionCommitment> {
+        let pivot_block = match epoch_blocks.last() {
+            Some(block) if block.hash() == witness.epoch_hash => block,
+            _ => bail!("the pivot block mismatches the epoch of the witness"),
+        };
+        if witness.pos_info.len() != epoch_blocks.len() {
+            bail!("the PoS status of the witness mismatches the blocks");
+        }
+        for block in epoch_blocks {
+            if *block.block_header.transactions_root()
+                != compute_transaction_root(&block.transactions)
+            {
+                bail!("the transactions of block {:?} mismatch", block.hash());
+            }
+        }
+
+        let storage = WitnessStorage::new(
+            witness.state.clone(),
+            &witness.parent_state_root,
+            witness.state_root.clone(),
+        )?;
+        let mut state = State::new(StateDb::new(Box::new(storage)))?;
+
+        let receipts = self.process_epoch_transactions(
+            &mut state,
+            epoch_blocks,
+            witness.start_block_number,
+            /* on_local_pivot = */ false,
+            Some(VirtualCall::Witness(WitnessTask { witness })),
+            /* witness_recorder */ None,
+        )?;
+
+        let current_block_number =
+            witness.start_block_number + receipts.len() as u64 - 1;
+        if let Some(rewards) = &witness.rewards {
+            let spec = self
+                .machine
+                .spec(current_block_number, pivot_block.block_header.height());
+            rewards.apply(&mut state, &spec)?;
+        }
+        witness.pos_update.apply(&mut state, current_block_number)?;
+
+        // The state root is recomputed by the storage of the witness.
+        let commit_result = state.commit(witness.epoch_hash, None)?;
+        let commitment = EpochExecutionCommitment {
+            state_root_with_aux_info: commit_result.state_root,
+            receipts_root: compute_receipts_root(&receipts),
+            logs_bloom_hash: BlockHeaderBuilder::compute_block_logs_bloom_hash(
+                &receipts,
+            ),
+        };
+        if commitment.state_root_with_aux_info.aux_info.state_root_hash
+            != committed.state_root_hash
+        {
+            bail!("the state root of the witness mismatches the pivot chain");
+        }
+        if commitment.receipts_root != committed.receipts_root {
+            bail!(
+                "the receipts root of the witness mismatches the pivot chain"
+            );
+        }
+        if commitment.logs_bloom_hash != committed.logs_bloom_hash {
+            bail!(
+                "the logs bloom hash of the witness mismatches the pivot chain"
+            );
+        }
+        Ok(commitment)
+    }
+}
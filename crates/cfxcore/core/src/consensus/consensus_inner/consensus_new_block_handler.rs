@@ -6,6 +6,7 @@ use super::blame_verifier::BlameVerifier;
 use crate::{
     block_data_manager::{BlockDataManager, BlockStatus, LocalBlockInfo},
     channel::Channel,
+    client::PivotChainReorg,
     consensus::{
         consensus_inner::{
             confirmation_meter::ConfirmationMeter,
@@ -45,6 +46,9 @@ pub struct ConsensusNewBlockHandler {
     /// Each element is <epoch_number, epoch_hashes>
     epochs_sender: Arc<Channel<(u64, Vec<H256>)>>,
 
+    /// Channel used to send pivot chain switches to PubSub
+    reorg_sender: Arc<Channel<PivotChainReorg>>,
+
     /// API used for verifying blaming on light nodes.
     blame_verifier: Mutex<BlameVerifier>,
 
@@ -66,6 +70,7 @@ impl ConsensusNewBlockHandler {
         pivot_hint: Option<Arc<PivotHint>>,
     ) -> Self {
         let epochs_sender = notifications.epochs_ordered.clone();
+        let reorg_sender = notifications.pivot_chain_reorgs.clone();
         let blame_verifier =
             Mutex::new(BlameVerifier::new(data_man.clone(), notifications));
 
@@ -77,12 +82,18 @@ impl ConsensusNewBlockHandler {
             executor,
             statistics,
             epochs_sender,
+            reorg_sender,
             blame_verifier,
             node_type,
             pivot_hint,
         }
     }
 
+    /// The channel of the pivot chain switches sent to PubSub.
+    pub fn pivot_chain_reorgs(&self) -> &Arc<Channel<PivotChainReorg>> {
+        &self.reorg_sender
+    }
+
     /// Return (old_era_block_set, new_era_block_set).
     /// `old_era_block_set` includes the blocks in the past of
     /// `new_era_block_arena_index`. `new_era_block_set` includes all other
@@ -1151,6 +1162,9 @@ impl ConsensusNewBlockHandler {
         // chain height (end of the pivot chain).
         let mut fork_at;
         let old_pivot_chain_len = inner.pivot_chain.len();
+        // The pivot blocks removed from the pivot chain, starting at
+        // ``fork_at``.
+        let mut reverted_pivots = Vec::new();
 
         // Update consensus inner with a possibly new pos_reference.
         inner.update_pos_pivot_decision(me);
@@ -1305,6 +1319,7 @@ impl ConsensusNewBlockHandler {
                 for discarded_idx in
                     inner.pivot_chain.split_off(fork_pivot_index)
                 {
+                    reverted_pivots.push(inner.arena[discarded_idx].hash);
                     // Reset the epoch_number of the discarded fork
                     inner.reset_epoch_number_in_epoch(discarded_idx);
                     ConsensusNewBlockHandler::try_clear_blockset_in_own_view_of_epoch(inner,
@@ -1582,6 +1597,22 @@ impl ConsensusNewBlockHandler {
         let from = capped_fork_at;
         let to = inner.pivot_index_to_height(inner.pivot_chain.len());
 
+        // send the pivot chain switch to pubsub before the new epochs
+        let num_stable_reverted = (capped_fork_at - fork_at) as usize;
+        if reverted_pivots.len() > num_stable_reverted {
+            self.reorg_sender.send(PivotChainReorg {
+                fork_at: from,
+                old_pivots: reverted_pivots.split_off(num_stable_reverted),
+                new_pivots: (from..to)
+                    .map(|epoch_number| {
+                        inner.arena
+                            [inner.get_pivot_block_arena_index(epoch_number)]
+                        .hash
+                    })
+                    .collect(),
+            });
+        }
+
         for epoch_number in from..to {
             let arena_index = inner.get_pivot_block_arena_index(epoch_number);
             let epoch_hashes = inner.get_epoch_block_hashes(arena_index);
@@ -1620,7 +1651,6 @@ impl ConsensusNewBlockHandler {
             // but we can assume that the deferred block are executed.
             self.data_man
                 .storage_manager
-                .get_storage_manager()
                 .maintain_state_confirmed(
                     inner,
                     inner.cur_era_stable_height,
@@ -1628,8 +1658,10 @@ impl ConsensusNewBlockHandler {
                     confirmed_height,
                     &self.data_man.state_availability_boundary,
                 )
-                // FIXME: propogate error.
-                .expect(&concat!(file!(), ":", line!(), ":", column!()));
+                .unwrap_or_else(|e| {
+                    // The maintenance is retried on the next confirmed epoch.
+                    error!("Failed to maintain the confirmed state: {}", e)
+                });
             self.set_block_tx_packed(inner, me);
             self.delayed_tx_recycle_in_skipped_blocks(inner, capped_fork_at);
 
@@ -2093,7 +2125,6 @@ impl ConsensusNewBlockHandler {
 
                     self.data_man
                         .storage_manager
-                        .get_storage_manager()
                         .maintain_state_confirmed(
                             inner,
                             inner.cur_era_stable_height,
@@ -2101,13 +2132,12 @@ impl ConsensusNewBlockHandler {
                             confirmed_height,
                             &self.data_man.state_availability_boundary,
                         )
-                        .expect(&concat!(
-                            file!(),
-                            ":",
-                            line!(),
-                            ":",
-                            column!()
-                        ));
+                        .unwrap_or_else(|e| {
+                            error!(
+                                "Failed to maintain the confirmed state: {}",
+                                e
+                            )
+                        });
                 }
             }
         }
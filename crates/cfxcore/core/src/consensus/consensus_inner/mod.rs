@@ -4202,4 +4202,23 @@ impl StateMaintenanceTrait for ConsensusGraphInner {
         self.data_man
             .remove_epoch_execution_commitment_from_db(block_hash)
     }
+
+    fn is_epoch_state_root_verified(&self, epoch_id: &EpochId) -> Option<bool> {
+        let arena_index = *self.hash_to_arena_indices.get(epoch_id)?;
+        let pivot_index =
+            self.height_to_pivot_index(self.arena[arena_index].height);
+        if pivot_index >= self.pivot_chain.len()
+            || self.pivot_chain[pivot_index] != arena_index
+        {
+            return None;
+        }
+        let committing_pivot_index =
+            pivot_index + DEFERRED_STATE_EPOCH_COUNT as usize;
+        if committing_pivot_index >= self.pivot_chain.len() {
+            return None;
+        }
+        self.arena[self.pivot_chain[committing_pivot_index]]
+            .data
+            .state_valid
+    }
 }
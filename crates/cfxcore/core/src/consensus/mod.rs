@@ -14,7 +14,10 @@ use self::pivot_hint::{PivotHint, PivotHintConfig};
 
 use super::consensus::consensus_inner::{
     confirmation_meter::ConfirmationMeter,
-    consensus_executor::ConsensusExecutor,
+    consensus_executor::{
+        CommittedExecutionRoots, ConsensusExecutor, DevStateChanges,
+        EpochExecutionWitness,
+    },
     consensus_new_block_handler::ConsensusNewBlockHandler,
 };
 pub use crate::consensus::{
@@ -23,6 +26,7 @@ pub use crate::consensus::{
 };
 use crate::{
     block_data_manager::{
+        checkpoint::{create_checkpoint, CheckpointManifest},
         BlockDataManager, BlockExecutionResultWithEpoch, DataVersionTuple,
     },
     consensus::{
@@ -49,10 +53,10 @@ use cfx_execute_helper::{
 use cfx_executor::{
     executive::ExecutionOutcome, spec::CommonParams, state::State,
 };
-use geth_tracer::GethTraceWithHash;
+use geth_tracer::{GethTraceWithHash, ParityTraceWithHash};
 
-use alloy_rpc_types_trace::geth::GethDebugTracingOptions;
-use cfx_internal_common::ChainIdParams;
+use alloy_rpc_types_trace::{geth::GethDebugTracingOptions, parity::TraceType};
+use cfx_internal_common::{ChainIdParams, EpochExecutionCommitment};
 use cfx_parameters::{
     consensus::*,
     consensus_internal::REWARD_EPOCH_COUNT,
@@ -64,7 +68,8 @@ use cfx_parameters::{
 use cfx_rpc_cfx_types::PhantomBlock;
 use cfx_statedb::StateDb;
 use cfx_storage::{
-    state::StateTrait, state_manager::StateManagerTrait, StorageState,
+    state::StateTrait, state_manager::StateManagerTrait,
+    Result as StorageResult, SnapshotVerification, StorageState,
 };
 use cfx_types::{AddressWithSpace, AllChainID, Bloom, Space, H256, U256};
 use either::Either;
@@ -82,14 +87,15 @@ use primitives::{
     log_entry::LocalizedLogEntry,
     pos::PosBlockId,
     receipt::Receipt,
-    Block, EpochId, EpochNumber, SignedTransaction, TransactionIndex,
-    TransactionStatus,
+    Block, BlockHeaderBuilder, EpochId, EpochNumber, SignedTransaction,
+    TransactionIndex, TransactionStatus,
 };
 use rayon::prelude::*;
 use std::{
     any::Any,
     cmp::{max, min},
     collections::HashSet,
+    path::Path,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -378,6 +384,12 @@ impl ConsensusGraph {
         }
     }
 
+    /// The state changes requested by the dev mode RPCs, which are applied
+    /// to the epochs executed later.
+    pub fn dev_state_changes(&self) -> &DevStateChanges {
+        &self.executor.handler.dev_state_changes
+    }
+
     /// Determine whether the next mined block should have adaptive weight or
     /// not
     pub fn check_mining_adaptive_block(
@@ -1448,6 +1460,41 @@ impl ConsensusGraph {
         &self, epoch_num: u64, tx_hash: Option<H256>,
         opts: GethDebugTracingOptions,
     ) -> CoreResult<Vec<GethTraceWithHash>> {
+        let (parent_pivot_block_hash, parent_epoch_num, blocks) =
+            self.get_epoch_blocks_to_replay(epoch_num)?;
+
+        self.collect_blocks_geth_trace(
+            parent_pivot_block_hash,
+            parent_epoch_num,
+            &blocks,
+            opts,
+            tx_hash,
+        )
+    }
+
+    /// Re-execute an epoch and collect the parity style traces of its
+    /// transactions, or only the transaction `tx_hash` if specified.
+    pub fn collect_epoch_parity_trace(
+        &self, epoch_num: u64, tx_hash: Option<H256>,
+        trace_types: HashSet<TraceType>,
+    ) -> CoreResult<Vec<ParityTraceWithHash>> {
+        let (parent_pivot_block_hash, parent_epoch_num, blocks) =
+            self.get_epoch_blocks_to_replay(epoch_num)?;
+
+        self.collect_blocks_parity_trace(
+            parent_pivot_block_hash,
+            parent_epoch_num,
+            &blocks,
+            trace_types,
+            tx_hash,
+        )
+    }
+
+    /// Return the blocks of an epoch, together with the hash and the number
+    /// of the parent epoch whose state they are executed on.
+    fn get_epoch_blocks_to_replay(
+        &self, epoch_num: u64,
+    ) -> CoreResult<(H256, u64, Vec<Arc<Block>>)> {
         let epoch = EpochNumber::Number(epoch_num);
         self.validate_stated_epoch(&epoch)?;
 
@@ -1468,16 +1515,10 @@ impl ConsensusGraph {
             .expect("blocks exist");
 
         let pivot_block = blocks.last().expect("Not empty");
-        let parent_pivot_block_hash = pivot_block.block_header.parent_hash();
+        let parent_pivot_block_hash = *pivot_block.block_header.parent_hash();
         let parent_epoch_num = pivot_block.block_header.height() - 1;
 
-        self.collect_blocks_geth_trace(
-            *parent_pivot_block_hash,
-            parent_epoch_num,
-            &blocks,
-            opts,
-            tx_hash,
-        )
+        Ok((parent_pivot_block_hash, parent_epoch_num, blocks))
     }
 
     pub fn collect_blocks_geth_trace(
@@ -1489,6 +1530,184 @@ impl ConsensusGraph {
         )
     }
 
+    pub fn collect_blocks_parity_trace(
+        &self, epoch_id: H256, epoch_num: u64, blocks: &Vec<Arc<Block>>,
+        trace_types: HashSet<TraceType>, tx_hash: Option<H256>,
+    ) -> CoreResult<Vec<ParityTraceWithHash>> {
+        self.executor.collect_blocks_parity_trace(
+            epoch_id,
+            epoch_num,
+            blocks,
+            trace_types,
+            tx_hash,
+        )
+    }
+
+    /// Return the witness recorded for the execution of an epoch, if any.
+    pub fn get_epoch_execution_witness(
+        &self, epoch: EpochNumber,
+    ) -> CoreResult<Option<EpochExecutionWitness>> {
+        let epoch_hash = self.get_hash_from_epoch_number(epoch)?;
+        Ok(self.data_man.epoch_execution_witness_by_hash(&epoch_hash))
+    }
+
+    /// Re-execute the epoch of a witness statelessly with the blocks of the
+    /// epoch on the pivot chain, and check the result against the roots of the
+    /// epoch committed by the pivot chain, see `committed_execution_roots_of`.
+    /// See `EpochExecutionWitness` for what the result can be trusted for.
+    pub fn verify_epoch_execution_witness(
+        &self, witness: &EpochExecutionWitness,
+    ) -> CoreResult<EpochExecutionCommitment> {
+        let epoch_height =
+            match self.data_man.block_header_by_hash(&witness.epoch_hash) {
+                Some(header) => header.height(),
+                None => bail!("the epoch of the witness is unknown"),
+            };
+        let epoch_block_hashes =
+            self.get_block_hashes_by_epoch(EpochNumber::Number(epoch_height))?;
+        if epoch_block_hashes.last() != Some(&witness.epoch_hash) {
+            bail!("the epoch of the witness is not on the pivot chain");
+        }
+        let epoch_blocks = self
+            .data_man
+            .blocks_by_hash_list(
+                &epoch_block_hashes,
+                false, /* update_cache */
+            )
+            .ok_or("blocks of the epoch not found")?;
+
+        // The parent state root is trusted by the re-execution.
+        let parent_height = epoch_height
+            .checked_sub(1)
+            .ok_or("the genesis epoch has no witness")?;
+        if witness
+            .parent_state_root
+            .state_root
+            .compute_state_root_hash()
+            != self
+                .committed_execution_roots_of(parent_height)?
+                .state_root_hash
+        {
+            bail!(
+                "the parent state root of the witness mismatches the pivot chain"
+            );
+        }
+        let committed = self.committed_execution_roots_of(epoch_height)?;
+
+        self.executor
+            .verify_epoch_witness(witness, &epoch_blocks, &committed)
+    }
+
+    /// The roots of the execution of the epoch at `epoch_height`, checked
+    /// against the first pivot header whose deferred roots commit to them,
+    /// like `LedgerInfo::state_root_witness_of`.
+    ///
+    /// A header with blame `b` commits to the vector roots of the `b + 1`
+    /// epochs it covers, which are recomputed from the local execution. The
+    /// headers blamed by their descendants commit to wrong roots, so they are
+    /// skipped.
+    fn committed_execution_roots_of(
+        &self, epoch_height: u64,
+    ) -> CoreResult<CommittedExecutionRoots> {
+        let local_roots_of = |height| -> CoreResult<CommittedExecutionRoots> {
+            let hash =
+                self.get_hash_from_epoch_number(EpochNumber::Number(height))?;
+            let commitment = self
+                .data_man
+                .get_epoch_execution_commitment_with_db(&hash)
+                .ok_or("the epoch is not executed")?;
+            Ok(CommittedExecutionRoots {
+                state_root_hash: commitment
+                    .state_root_with_aux_info
+                    .aux_info
+                    .state_root_hash,
+                receipts_root: commitment.receipts_root,
+                logs_bloom_hash: commitment.logs_bloom_hash,
+            })
+        };
+
+        let deferring_height = epoch_height + DEFERRED_STATE_EPOCH_COUNT;
+        for height in deferring_height..=self.best_epoch_number() {
+            let hash =
+                self.get_hash_from_epoch_number(EpochNumber::Number(height))?;
+            let header = self
+                .data_man
+                .block_header_by_hash(&hash)
+                .ok_or("pivot block header not found")?;
+            let covered_height = height.saturating_sub(header.blame() as u64);
+            // The deferred roots of the header don't cover the epoch.
+            if covered_height > deferring_height {
+                continue;
+            }
+
+            let mut state_root_hashes = vec![];
+            let mut receipts_roots = vec![];
+            let mut logs_bloom_hashes = vec![];
+            for covered in (covered_height..=height).rev() {
+                let roots = local_roots_of(
+                    covered.saturating_sub(DEFERRED_STATE_EPOCH_COUNT),
+                )?;
+                state_root_hashes.push(roots.state_root_hash);
+                receipts_roots.push(roots.receipts_root);
+                logs_bloom_hashes.push(roots.logs_bloom_hash);
+            }
+            if BlockHeaderBuilder::compute_blame_state_root_vec_root(
+                state_root_hashes,
+            ) == *header.deferred_state_root()
+                && BlockHeaderBuilder::compute_blame_state_root_vec_root(
+                    receipts_roots,
+                ) == *header.deferred_receipts_root()
+                && BlockHeaderBuilder::compute_blame_state_root_vec_root(
+                    logs_bloom_hashes,
+                ) == *header.deferred_logs_bloom_hash()
+            {
+                return local_roots_of(epoch_height);
+            }
+        }
+        bail!("no pivot header commits to the execution of the epoch yet")
+    }
+
+    /// Start verifying the snapshots in background if
+    /// `snapshot_verify_interval` is configured.
+    pub fn start_snapshot_verifier(&self) -> StorageResult<()> {
+        self.data_man
+            .storage_manager
+            .get_storage_manager_arc()
+            .start_snapshot_verifier(self.inner.clone())
+    }
+
+    /// Verify the merkle roots of all snapshots kept by the node against the
+    /// committed state roots, and rebuild the inconsistent ones if `rebuild`.
+    pub fn verify_snapshots(
+        &self, rebuild: bool,
+    ) -> StorageResult<Vec<SnapshotVerification>> {
+        self.data_man
+            .storage_manager
+            .get_storage_manager_arc()
+            .verify_snapshots(&self.inner, rebuild)
+    }
+
+    /// Copy the block db, the storage and the PoS databases into
+    /// `checkpoint_dir`, with the execution of epochs paused while the
+    /// mutable databases are copied, see `block_data_manager::checkpoint`.
+    pub fn create_checkpoint(
+        &self, checkpoint_dir: &Path,
+    ) -> Result<CheckpointManifest, String> {
+        let pos_verifier = self.inner.read().pos_verifier.clone();
+        let pos_dbs = pos_verifier
+            .pos_option()
+            .map(|pos| (&**pos.pos_ledger_db(), &**pos.consensus_db()));
+        let (execution_pause, last_computed_epoch) =
+            self.executor.handler.pause_execution();
+        create_checkpoint(
+            &self.data_man,
+            checkpoint_dir,
+            execution_pause,
+            last_computed_epoch,
+            pos_dbs,
+        )
+    }
+
     /// Get the number of processed blocks (i.e., the number of calls to
     /// on_new_block()
     pub fn get_processed_block_count(&self) -> usize {
@@ -114,11 +114,19 @@ pub fn genesis_block(
     let mut genesis_block_author = test_net_version;
     genesis_block_author.set_user_account_type_bits();
 
-    initialize_internal_contract_accounts(
-        &mut state,
-        machine.internal_contracts().initialized_at_genesis(),
-    )
-    .expect("no db error");
+    // A forked state already contains the internal contracts, the genesis
+    // allocations and the global parameters, e.g. the total issued tokens, of
+    // the forked chain, so only the local accounts are funded and added to
+    // the supply on top.
+    let forked = storage_manager.is_forked();
+
+    if !forked {
+        initialize_internal_contract_accounts(
+            &mut state,
+            machine.internal_contracts().initialized_at_genesis(),
+        )
+        .expect("no db error");
+    }
     trace!("genesis_accounts: {:?}", genesis_accounts);
     for (addr, balance) in genesis_accounts {
         state
@@ -133,7 +141,9 @@ pub fn genesis_block(
 
     let genesis_token_count =
         U256::from(GENESIS_TOKEN_COUNT_IN_CFX) * U256::from(ONE_CFX_IN_DRIP);
-    state.add_total_issued(genesis_token_count);
+    if !forked {
+        state.add_total_issued(genesis_token_count);
+    }
     let two_year_unlock_token_count =
         U256::from(TWO_YEAR_UNLOCK_TOKEN_COUNT_IN_CFX)
             * U256::from(ONE_CFX_IN_DRIP);
@@ -142,13 +152,15 @@ pub fn genesis_block(
 
     let genesis_account_init_balance =
         U256::from(ONE_CFX_IN_DRIP) * 100 + genesis_token_count;
-    state
-        .add_balance(
-            &genesis_account_address,
-            &genesis_account_init_balance,
-            CleanupMode::NoEmpty,
-        )
-        .unwrap();
+    if !forked {
+        state
+            .add_balance(
+                &genesis_account_address,
+                &genesis_account_init_balance,
+                CleanupMode::NoEmpty,
+            )
+            .unwrap();
+    }
 
     let mut debug_record = Some(ComputeEpochDebugRecord::default());
 
@@ -284,7 +296,7 @@ pub fn genesis_block(
         ),
     ];
 
-    if need_to_execute {
+    if need_to_execute && !forked {
         const CREATE2FACTORY_TX_INDEX: usize = 1;
         /*
         const TWO_YEAR_UNLOCK_TX_INDEX: usize = 2;
@@ -330,7 +342,7 @@ pub fn genesis_block(
         }
     }
 
-    if let Some(initial_nodes) = initial_nodes {
+    if let Some(initial_nodes) = initial_nodes.as_ref().filter(|_| !forked) {
         for node in &initial_nodes.initial_nodes {
             let stake_balance = U256::from(node.voting_power) * *POS_VOTE_PRICE;
             // TODO(lpl): Pass in signed tx so they can be retired.
@@ -357,9 +369,11 @@ pub fn genesis_block(
         }
     }
 
-    state
-        .genesis_special_remove_account(&genesis_account_address.address)
-        .expect("Clean account failed");
+    if !forked {
+        state
+            .genesis_special_remove_account(&genesis_account_address.address)
+            .expect("Clean account failed");
+    }
 
     let state_root = state
         .compute_state_root_for_genesis(
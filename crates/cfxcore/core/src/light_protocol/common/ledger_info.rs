@@ -11,12 +11,12 @@ use cfx_parameters::consensus::DEFERRED_STATE_EPOCH_COUNT;
 
 use cfx_storage::{
     state::{State, StateDbGetOriginalMethods, StateTrait},
-    StateProof, StorageRootProof,
+    StateProof, StateRootWitness, StorageRootProof,
 };
 use cfx_types::{Address, AddressSpaceUtil, Bloom, H256};
 use primitives::{
     Block, BlockHeader, BlockHeaderBuilder, BlockReceipts, CheckInput,
-    EpochNumber, StorageKeyWithSpace, StorageRoot,
+    EpochNumber, StateRoot, StorageKeyWithSpace, StorageRoot,
 };
 
 pub struct LedgerInfo {
@@ -89,6 +89,14 @@ impl LedgerInfo {
         &self, height: u64,
     ) -> Result<H256, Error> {
         let epoch = height.saturating_sub(DEFERRED_STATE_EPOCH_COUNT);
+        Ok(self.correct_state_root_of(epoch)?.compute_state_root_hash())
+    }
+
+    /// Get the state root of `epoch` based on local execution information.
+    #[inline]
+    pub fn correct_state_root_of(
+        &self, epoch: u64,
+    ) -> Result<StateRoot, Error> {
         let pivot = self.pivot_hash_of(epoch)?;
 
         let commitments = self
@@ -102,10 +110,7 @@ impl LedgerInfo {
                 )))
             })?;
 
-        Ok(commitments
-            .state_root_with_aux_info
-            .state_root
-            .compute_state_root_hash())
+        Ok(commitments.state_root_with_aux_info.state_root)
     }
 
     /// Get the correct deferred receipts root of the block at `height` on the
@@ -315,6 +320,44 @@ impl LedgerInfo {
         Ok((0..(blame + 1)).map(|ii| witness - ii).collect())
     }
 
+    /// Get the first header on the pivot chain whose deferred state root
+    /// commits to the correct state root of `epoch`, together with the state
+    /// root hashes seen by it. Returns `None` if there is no such header yet,
+    /// e.g. for the latest `DEFERRED_STATE_EPOCH_COUNT` epochs.
+    pub fn state_root_witness_of(
+        &self, epoch: u64,
+    ) -> Result<Option<StateRootWitness>, Error> {
+        let height = epoch + DEFERRED_STATE_EPOCH_COUNT;
+
+        for witness in height..=self.consensus.best_epoch_number() {
+            let header = self.pivot_header_of(witness)?;
+
+            // the DSR of `witness` does not cover the header at `height`
+            if witness - header.blame() as u64 > height {
+                continue;
+            }
+
+            let state_root_hashes = self
+                .headers_seen_by_witness(witness)?
+                .into_iter()
+                .map(|h| self.correct_deferred_state_root_hash_of(h))
+                .collect::<Result<Vec<H256>, Error>>()?;
+
+            // skip headers blamed by their descendants
+            if BlockHeaderBuilder::compute_blame_state_root_vec_root(
+                state_root_hashes.clone(),
+            ) == *header.deferred_state_root()
+            {
+                return Ok(Some(StateRootWitness {
+                    header,
+                    state_root_hashes,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
     /// Get all correct state roots, receipts roots, and bloom hashes seen by
     /// the header at height `witness`.
     #[inline]
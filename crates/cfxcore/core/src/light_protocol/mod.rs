@@ -22,6 +22,7 @@ pub const LIGHT_PROTO_V2: ProtocolVersion = ProtocolVersion(2);
 
 use error::handle as handle_error;
 
+pub use common::LedgerInfo;
 pub use config::Configuration as LightNodeConfiguration;
 pub use error::Error;
 pub use handler::Handler;
@@ -41,6 +41,9 @@ pub struct ConsensusDB {
 }
 
 impl ConsensusDB {
+    /// The dir of the db under the db root path.
+    pub const DB_NAME: &'static str = "consensusdb";
+
     /// new
     pub fn new<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
         let column_families = vec![
@@ -52,7 +55,7 @@ impl ConsensusDB {
             STAKING_EVENTS_CF_NAME,
         ];
 
-        let path = db_root_path.as_ref().join("consensusdb");
+        let path = db_root_path.as_ref().join(Self::DB_NAME);
         let instant = Instant::now();
         let mut opts = Options::default();
         opts.create_if_missing(true);
@@ -69,6 +72,13 @@ impl ConsensusDB {
         Self { db }
     }
 
+    /// Copy the db into `db_root_path` while it's in use, so that `new` on
+    /// `db_root_path` opens the copy.
+    pub fn checkpoint<P: AsRef<Path>>(&self, db_root_path: P) -> Result<()> {
+        self.db
+            .checkpoint(db_root_path.as_ref().join(Self::DB_NAME))
+    }
+
     /// get_data
     pub fn get_data(
         &self,
@@ -238,6 +238,9 @@ pub struct PosLedgerDB {
 }
 
 impl PosLedgerDB {
+    /// The dir of the db under the db root path.
+    pub const DB_NAME: &'static str = "pos-ledger-db";
+
     fn column_families() -> Vec<ColumnFamilyName> {
         vec![
             /* LedgerInfo CF = */ DEFAULT_CF_NAME,
@@ -289,7 +292,7 @@ impl PosLedgerDB {
             "Do not set prune_window when opening readonly.",
         );
 
-        let path = db_root_path.as_ref().join("pos-ledger-db");
+        let path = db_root_path.as_ref().join(Self::DB_NAME);
         let instant = Instant::now();
 
         let mut rocksdb_opts = gen_rocksdb_options(&rocksdb_config);
@@ -338,6 +341,13 @@ impl PosLedgerDB {
         update_rocksdb_properties(&self.db)
     }
 
+    /// Copy the db into `db_root_path` while it's in use, so that `open` on
+    /// `db_root_path` opens the copy.
+    pub fn checkpoint<P: AsRef<Path>>(&self, db_root_path: P) -> Result<()> {
+        self.db
+            .checkpoint(db_root_path.as_ref().join(Self::DB_NAME))
+    }
+
     /// Returns ledger infos reflecting epoch bumps starting with the given
     /// epoch. If there are no more than `MAX_NUM_EPOCH_ENDING_LEDGER_INFO`
     /// results, this function returns all of them, otherwise the first
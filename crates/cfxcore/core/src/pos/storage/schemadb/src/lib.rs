@@ -454,6 +454,18 @@ impl DB {
         Ok(())
     }
 
+    /// Creates a RocksDB checkpoint of the db at `path`, which must not
+    /// exist. SST files are hard-linked when `path` is on the same file
+    /// system, so the checkpoint is cheap regardless of the db size.
+    pub fn checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.inner
+            .new_checkpointer()
+            .and_then(|mut checkpointer| {
+                checkpointer.create_at(path.as_ref(), None, 0)
+            })
+            .map_err(convert_rocksdb_err)
+    }
+
     pub fn get_property(
         &self, cf_name: &str, property_name: &str,
     ) -> Result<u64> {
@@ -4,6 +4,8 @@
 mod error;
 pub mod message;
 pub mod request_manager;
+#[cfg(any(test, feature = "testonly_code"))]
+pub mod simulation;
 mod state;
 
 #[cfg(test)]
@@ -18,7 +20,13 @@ pub mod utils;
 
 pub use self::{
     error::Error,
-    state::StateSyncConfiguration,
+    state::{
+        snapshot_archive::{
+            export_snapshot_archive, snapshot_archive_file_name,
+            verify_snapshot_archive, SnapshotArchiveManifest,
+        },
+        StateSyncConfiguration, StateSyncProgress,
+    },
     synchronization_graph::{
         SharedSynchronizationGraph, SyncGraphConfig, SyncGraphStatistics,
         SynchronizationGraph, SynchronizationGraphInner,
@@ -35,7 +43,8 @@ pub use self::{
         SynchronizationProtocolHandler, CHECK_RPC_REQUEST_TIMER,
     },
     synchronization_service::{
-        SharedSynchronizationService, SynchronizationService,
+        SharedSynchronizationService, SyncProgress, SyncStart,
+        SynchronizationService,
     },
     synchronization_state::{SynchronizationPeerState, SynchronizationState},
 };
@@ -0,0 +1,341 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! An in-process simulation of a network of nodes for consensus and sync
+//! tests. Every node runs its own `SynchronizationGraph` and
+//! `ConsensusGraph`. Mining and block propagation are driven by a virtual
+//! clock and a seeded random generator, so a simulation with the same seed
+//! and the same scripted actions always produces the same block graph.
+
+mod network;
+mod node;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::{
+    network::{LatencyConfig, SimulatedNetwork},
+    node::{NodeBehavior, SimulatedNode},
+};
+
+use cfx_types::H256;
+use primitives::Block;
+use rand_08::{prelude::StdRng, Rng, SeedableRng};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    sync::Arc,
+};
+
+pub type NodeId = usize;
+
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+    pub node_count: usize,
+    pub seed: u64,
+    /// The average interval between two blocks mined in the whole network,
+    /// in virtual milliseconds. Set to `0` to only mine blocks explicitly
+    /// with `Simulation::mine_block`.
+    pub block_interval_ms: u64,
+    /// The relative hash power of each node. All nodes have the same hash
+    /// power if it is empty.
+    pub hash_power: Vec<u64>,
+    pub latency: LatencyConfig,
+    pub adaptive_weight_beta: u64,
+    pub heavy_block_difficulty_ratio: u64,
+    pub timer_chain_block_difficulty_ratio: u64,
+    pub timer_chain_beta: u64,
+    pub era_epoch_count: u64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            node_count: 4,
+            seed: 0,
+            block_interval_ms: 500,
+            hash_power: Vec::new(),
+            latency: LatencyConfig::default(),
+            adaptive_weight_beta: 1000,
+            heavy_block_difficulty_ratio: 240,
+            timer_chain_block_difficulty_ratio: 180,
+            timer_chain_beta: 240,
+            era_epoch_count: 50000,
+        }
+    }
+}
+
+enum Event {
+    /// A node mines a block, and the next mining event is scheduled.
+    Mine,
+    Deliver {
+        from: NodeId,
+        to: NodeId,
+        block: Arc<Block>,
+    },
+}
+
+struct ScheduledEvent {
+    time_ms: u64,
+    /// Breaks the ties of events at the same time by the scheduling order.
+    seq: u64,
+    event: Event,
+}
+
+impl PartialEq for ScheduledEvent {
+    fn eq(&self, other: &Self) -> bool {
+        (self.time_ms, self.seq) == (other.time_ms, other.seq)
+    }
+}
+
+impl Eq for ScheduledEvent {}
+
+impl PartialOrd for ScheduledEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScheduledEvent {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.time_ms, self.seq).cmp(&(other.time_ms, other.seq))
+    }
+}
+
+/// A network of `SimulatedNode`s on a virtual clock.
+///
+/// Events are processed one at a time in the order of their virtual time,
+/// and each node waits for its consensus graph to process a delivered block
+/// before the next event, so the outcome does not depend on the thread
+/// scheduling or the wall clock.
+pub struct Simulation {
+    config: SimulationConfig,
+    pub nodes: Vec<SimulatedNode>,
+    pub network: SimulatedNetwork,
+    rng: StdRng,
+    now_ms: u64,
+    next_seq: u64,
+    next_nonce: u64,
+    events: BinaryHeap<Reverse<ScheduledEvent>>,
+    pending_deliveries: usize,
+    mining: bool,
+    mining_scheduled: bool,
+}
+
+impl Simulation {
+    pub fn new(config: SimulationConfig) -> Self {
+        assert!(config.node_count > 0);
+        assert!(
+            config.hash_power.is_empty()
+                || config.hash_power.len() == config.node_count
+        );
+        let nodes: Vec<_> = (0..config.node_count)
+            .map(|id| SimulatedNode::new(id, &config))
+            .collect();
+        for node in &nodes {
+            assert_eq!(node.genesis_hash(), nodes[0].genesis_hash());
+        }
+
+        let mut simulation = Simulation {
+            network: SimulatedNetwork::new(config.node_count, config.latency),
+            rng: StdRng::seed_from_u64(config.seed),
+            nodes,
+            now_ms: 0,
+            next_seq: 0,
+            next_nonce: 0,
+            events: BinaryHeap::new(),
+            pending_deliveries: 0,
+            mining: false,
+            mining_scheduled: false,
+            config,
+        };
+        if simulation.config.block_interval_ms > 0 {
+            simulation.start_mining();
+        }
+        simulation
+    }
+
+    pub fn now_ms(&self) -> u64 { self.now_ms }
+
+    /// Starts mining blocks at the rate of `block_interval_ms`.
+    pub fn start_mining(&mut self) {
+        assert!(self.config.block_interval_ms > 0);
+        self.mining = true;
+        if !self.mining_scheduled {
+            self.schedule_next_mining();
+        }
+    }
+
+    pub fn stop_mining(&mut self) { self.mining = false; }
+
+    pub fn set_behavior(&mut self, node: NodeId, behavior: NodeBehavior) {
+        self.nodes[node].behavior = behavior;
+    }
+
+    /// Mines a block by `node` at the current virtual time and propagates it
+    /// according to the behavior of the node.
+    pub fn mine_block(&mut self, node: NodeId) -> H256 {
+        self.next_nonce += 1;
+        // Sample in `(0, 1]` so that the PoW quality is finite.
+        let pow_sample = 1.0 - self.rng.gen::<f64>();
+        let block = self.nodes[node].mine_block(
+            self.now_ms,
+            self.next_nonce,
+            pow_sample,
+        );
+        let hash = block.hash();
+        debug!(
+            "simulation: node {} mined block {:?} at {}ms",
+            node, hash, self.now_ms
+        );
+        if self.nodes[node].behavior == NodeBehavior::Honest {
+            self.broadcast(node, block, None);
+        }
+        hash
+    }
+
+    /// Broadcasts all the blocks withheld by `node`.
+    pub fn release_withheld_blocks(&mut self, node: NodeId) {
+        for block in self.nodes[node].take_withheld_blocks() {
+            self.broadcast(node, block, None);
+        }
+    }
+
+    /// Drops the links between the partitions. See
+    /// `SimulatedNetwork::split_network`.
+    pub fn split_network(&mut self, partitions: &[Vec<NodeId>]) {
+        self.network.split_network(partitions);
+    }
+
+    /// Restores all the links, and sends each node the blocks which its
+    /// peers have received while they were disconnected, as the sync
+    /// protocol does after reconnection.
+    pub fn heal_network(&mut self) {
+        self.network.heal_network();
+        for from in 0..self.nodes.len() {
+            if self.nodes[from].behavior != NodeBehavior::Honest {
+                continue;
+            }
+            for to in 0..self.nodes.len() {
+                let missing: Vec<_> = self.nodes[from]
+                    .blocks()
+                    .iter()
+                    .filter(|block| !self.nodes[to].knows_block(&block.hash()))
+                    .cloned()
+                    .collect();
+                for block in missing {
+                    self.send(from, to, block);
+                }
+            }
+        }
+    }
+
+    /// Processes all the events up to the virtual time `time_ms`.
+    pub fn run_until(&mut self, time_ms: u64) {
+        while let Some(Reverse(next)) = self.events.peek() {
+            if next.time_ms > time_ms {
+                break;
+            }
+            self.process_next_event();
+        }
+        self.now_ms = self.now_ms.max(time_ms);
+    }
+
+    pub fn run_for(&mut self, duration_ms: u64) {
+        self.run_until(self.now_ms + duration_ms);
+    }
+
+    /// Stops mining and processes events until all the blocks in flight are
+    /// delivered.
+    pub fn run_until_delivered(&mut self) {
+        self.stop_mining();
+        while self.pending_deliveries > 0 {
+            self.process_next_event();
+        }
+    }
+
+    /// Returns whether all the nodes have the same pivot chain.
+    pub fn is_converged(&self) -> bool {
+        let best = self.nodes[0].best_block_hash();
+        self.nodes.iter().all(|node| node.best_block_hash() == best)
+    }
+
+    fn process_next_event(&mut self) {
+        let Reverse(scheduled) = self.events.pop().expect("no scheduled event");
+        self.now_ms = scheduled.time_ms;
+        match scheduled.event {
+            Event::Mine => {
+                self.mining_scheduled = false;
+                if self.mining {
+                    let miner = self.sample_miner();
+                    self.mine_block(miner);
+                    self.schedule_next_mining();
+                }
+            }
+            Event::Deliver { from, to, block } => {
+                self.pending_deliveries -= 1;
+                let is_new = self.nodes[to].receive_block(block.clone());
+                if is_new && self.nodes[to].behavior == NodeBehavior::Honest {
+                    self.broadcast(to, block, Some(from));
+                }
+            }
+        }
+    }
+
+    /// Sends `block` to all the peers of `from` except `except`.
+    fn broadcast(
+        &mut self, from: NodeId, block: Arc<Block>, except: Option<NodeId>,
+    ) {
+        for to in 0..self.nodes.len() {
+            if to != from && Some(to) != except {
+                self.send(from, to, block.clone());
+            }
+        }
+    }
+
+    fn send(&mut self, from: NodeId, to: NodeId, block: Arc<Block>) {
+        if let Some(delay) = self.network.sample_delay(from, to, &mut self.rng)
+        {
+            self.pending_deliveries += 1;
+            self.schedule(
+                self.now_ms + delay,
+                Event::Deliver { from, to, block },
+            );
+        }
+    }
+
+    fn schedule(&mut self, time_ms: u64, event: Event) {
+        self.next_seq += 1;
+        self.events.push(Reverse(ScheduledEvent {
+            time_ms,
+            seq: self.next_seq,
+            event,
+        }));
+    }
+
+    /// Block arrivals of the whole network form a Poisson process, so the
+    /// interval to the next block is exponentially distributed.
+    fn schedule_next_mining(&mut self) {
+        let sample = 1.0 - self.rng.gen::<f64>();
+        let interval =
+            (-sample.ln() * self.config.block_interval_ms as f64) as u64;
+        self.mining_scheduled = true;
+        self.schedule(self.now_ms + interval, Event::Mine);
+    }
+
+    fn sample_miner(&mut self) -> NodeId {
+        if self.config.hash_power.is_empty() {
+            return self.rng.gen_range(0..self.nodes.len());
+        }
+        let total: u64 = self.config.hash_power.iter().sum();
+        let mut target = self.rng.gen_range(0..total);
+        for (node, power) in self.config.hash_power.iter().enumerate() {
+            if target < *power {
+                return node;
+            }
+            target -= power;
+        }
+        unreachable!()
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use super::NodeId;
+use rand_08::{prelude::StdRng, Rng};
+use std::collections::{HashMap, HashSet};
+
+/// The delay of a message between two nodes, in virtual milliseconds. Each
+/// message takes `base_ms` plus a uniformly sampled jitter in
+/// `[0, jitter_ms]`.
+#[derive(Clone, Copy, Debug)]
+pub struct LatencyConfig {
+    pub base_ms: u64,
+    pub jitter_ms: u64,
+}
+
+impl Default for LatencyConfig {
+    fn default() -> Self {
+        LatencyConfig {
+            base_ms: 100,
+            jitter_ms: 100,
+        }
+    }
+}
+
+/// `SimulatedNetwork` decides whether and when a message sent from one node
+/// arrives at another. Like the `NetworkPlayground` of the PoS tests, links
+/// can be dropped individually or by splitting the nodes into partitions.
+/// A dropped link loses the messages sent over it; the lost blocks are
+/// synced again when the link is restored.
+pub struct SimulatedNetwork {
+    node_count: usize,
+    default_latency: LatencyConfig,
+    link_latency: HashMap<(NodeId, NodeId), LatencyConfig>,
+    /// Directed links `(from, to)` whose messages are dropped.
+    dropped_links: HashSet<(NodeId, NodeId)>,
+}
+
+impl SimulatedNetwork {
+    pub fn new(node_count: usize, default_latency: LatencyConfig) -> Self {
+        SimulatedNetwork {
+            node_count,
+            default_latency,
+            link_latency: HashMap::new(),
+            dropped_links: HashSet::new(),
+        }
+    }
+
+    pub fn set_link_latency(
+        &mut self, from: NodeId, to: NodeId, latency: LatencyConfig,
+    ) {
+        self.link_latency.insert((from, to), latency);
+    }
+
+    pub fn drop_link(&mut self, from: NodeId, to: NodeId) {
+        self.dropped_links.insert((from, to));
+    }
+
+    pub fn restore_link(&mut self, from: NodeId, to: NodeId) {
+        self.dropped_links.remove(&(from, to));
+    }
+
+    /// Drop all the links between nodes in different partitions. The nodes
+    /// not listed in any partition are isolated from all other nodes.
+    pub fn split_network(&mut self, partitions: &[Vec<NodeId>]) {
+        let mut partition_of = HashMap::new();
+        for (i, partition) in partitions.iter().enumerate() {
+            for node in partition {
+                partition_of.insert(*node, i);
+            }
+        }
+        for from in 0..self.node_count {
+            for to in 0..self.node_count {
+                if from == to {
+                    continue;
+                }
+                match (partition_of.get(&from), partition_of.get(&to)) {
+                    (Some(a), Some(b)) if a == b => {}
+                    _ => self.drop_link(from, to),
+                }
+            }
+        }
+    }
+
+    /// Restore all the dropped links.
+    pub fn heal_network(&mut self) { self.dropped_links.clear(); }
+
+    pub fn is_connected(&self, from: NodeId, to: NodeId) -> bool {
+        from != to && !self.dropped_links.contains(&(from, to))
+    }
+
+    /// Returns the delay of a message from `from` to `to`, or `None` if the
+    /// message is dropped.
+    pub fn sample_delay(
+        &self, from: NodeId, to: NodeId, rng: &mut StdRng,
+    ) -> Option<u64> {
+        if !self.is_connected(from, to) {
+            return None;
+        }
+        let latency = self
+            .link_latency
+            .get(&(from, to))
+            .unwrap_or(&self.default_latency);
+        Some(latency.base_ms + rng.gen_range(0..=latency.jitter_ms))
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use super::{NodeId, SimulationConfig};
+use crate::{
+    block_data_manager::{BlockDataManager, DbType},
+    consensus::ConsensusGraphTrait,
+    pow,
+    sync::{utils::initialize_synchronization_graph, SynchronizationGraph},
+    ConsensusGraph,
+};
+use cfx_parameters::consensus::GENESIS_GAS_LIMIT;
+use cfx_types::{address_util::AddressUtil, Address, H256, U256};
+use primitives::{Block, BlockHeaderBuilder};
+use std::{collections::HashSet, sync::Arc};
+
+/// All simulated blocks use the minimum difficulty of the test mode, so the
+/// weight of a block only depends on its sampled PoW quality.
+const SIMULATION_DIFFICULTY: u64 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeBehavior {
+    /// Broadcasts its own blocks and relays the received ones.
+    Honest,
+    /// Mines a private chain on top of its own blocks and keeps them until
+    /// `Simulation::release_withheld_blocks` is called. The blocks received
+    /// from other nodes are not relayed.
+    Withholding,
+}
+
+/// A node in the simulation, with its own `SynchronizationGraph`,
+/// `ConsensusGraph` and in-memory databases.
+pub struct SimulatedNode {
+    pub id: NodeId,
+    pub behavior: NodeBehavior,
+    pub sync: Arc<SynchronizationGraph>,
+    pub consensus: Arc<ConsensusGraph>,
+    pub data_man: Arc<BlockDataManager>,
+    genesis_timestamp: u64,
+    author: Address,
+    known_blocks: HashSet<H256>,
+    /// All the blocks known by this node in the order they are received,
+    /// used to sync the peers after a link is restored.
+    blocks: Vec<Arc<Block>>,
+    withheld_blocks: Vec<Arc<Block>>,
+}
+
+impl SimulatedNode {
+    pub(super) fn new(id: NodeId, config: &SimulationConfig) -> Self {
+        // Nothing is written to disk by the in-memory databases.
+        let (sync, consensus, data_man, genesis_block) =
+            initialize_synchronization_graph(
+                /* db_dir = */ "",
+                config.adaptive_weight_beta,
+                config.heavy_block_difficulty_ratio,
+                config.timer_chain_block_difficulty_ratio,
+                config.timer_chain_beta,
+                config.era_epoch_count,
+                DbType::Inmemory,
+            );
+        let mut author = Address::from_low_u64_be(id as u64 + 1);
+        author.set_user_account_type_bits();
+        let genesis_hash = genesis_block.hash();
+
+        SimulatedNode {
+            id,
+            behavior: NodeBehavior::Honest,
+            sync,
+            consensus,
+            data_man,
+            genesis_timestamp: genesis_block.block_header.timestamp(),
+            author,
+            known_blocks: [genesis_hash].into_iter().collect(),
+            blocks: Vec::new(),
+            withheld_blocks: Vec::new(),
+        }
+    }
+
+    pub fn genesis_hash(&self) -> H256 { self.data_man.true_genesis.hash() }
+
+    pub fn knows_block(&self, hash: &H256) -> bool {
+        self.known_blocks.contains(hash)
+    }
+
+    pub fn blocks(&self) -> &[Arc<Block>] { &self.blocks }
+
+    pub fn withheld_blocks(&self) -> &[Arc<Block>] { &self.withheld_blocks }
+
+    pub fn best_block_hash(&self) -> H256 { self.consensus.best_block_hash() }
+
+    pub fn best_epoch_number(&self) -> u64 {
+        self.consensus.best_epoch_number()
+    }
+
+    pub fn best_timer_chain_height(&self) -> u64 {
+        self.consensus.inner.read().best_timer_chain_height()
+    }
+
+    /// The pivot chain from the genesis to the best block.
+    pub fn pivot_chain(&self) -> Vec<H256> {
+        let inner = self.consensus.inner.read();
+        (0..=self.best_epoch_number())
+            .map(|epoch| {
+                inner
+                    .get_pivot_hash_from_epoch_number(epoch)
+                    .expect("pivot block exists")
+            })
+            .collect()
+    }
+
+    /// Inserts a block into the sync graph and waits until the consensus
+    /// graph has processed all the blocks that become ready. Returns `false`
+    /// if the block is already known.
+    pub(super) fn receive_block(&mut self, block: Arc<Block>) -> bool {
+        if !self.known_blocks.insert(block.hash()) {
+            return false;
+        }
+        self.blocks.push(block.clone());
+
+        let mut header = block.block_header.clone();
+        self.sync.insert_block_header(
+            &mut header,
+            false, /* need_to_verify */
+            true,  /* bench_mode */
+            false, /* insert_to_consensus */
+            true,  /* persistent */
+        );
+        self.sync.insert_block(
+            (*block).clone(),
+            false, /* need_to_verify */
+            false, /* persistent */
+            false, /* recover_from_db */
+        );
+        // The consensus graph runs in its own worker thread, wait for it so
+        // that every node processes its blocks in a deterministic order.
+        self.sync.wait_for_consensus_worker();
+        true
+    }
+
+    /// Mines a block at the virtual time `now_ms`. The PoW quality is
+    /// `difficulty / pow_sample`, where `pow_sample` is uniform in `(0, 1]`,
+    /// so heavy blocks and timer chain blocks appear at their natural rate.
+    pub(super) fn mine_block(
+        &mut self, now_ms: u64, nonce: u64, pow_sample: f64,
+    ) -> Arc<Block> {
+        let best_info = self.consensus.best_info();
+        let (parent_hash, referees) = match self.behavior {
+            NodeBehavior::Withholding if !self.withheld_blocks.is_empty() => {
+                (self.withheld_blocks.last().unwrap().hash(), Vec::new())
+            }
+            _ => {
+                let mut referees =
+                    best_info.bounded_terminal_block_hashes.clone();
+                referees.retain(|r| *r != best_info.best_block_hash);
+                (best_info.best_block_hash, referees)
+            }
+        };
+        let parent_height = self
+            .data_man
+            .block_header_by_hash(&parent_hash)
+            .expect("parent exists")
+            .height();
+
+        let difficulty = U256::from(SIMULATION_DIFFICULTY);
+        let adaptive = self.consensus.check_mining_adaptive_block(
+            &mut *self.consensus.inner.write(),
+            &parent_hash,
+            &referees,
+            &difficulty,
+            None, /* pos_reference */
+        );
+        let nonce = U256::from(nonce);
+        let mut builder = BlockHeaderBuilder::new();
+        let mut header = builder
+            .with_parent_hash(parent_hash)
+            .with_height(parent_height + 1)
+            .with_timestamp(self.genesis_timestamp + now_ms / 1000)
+            .with_referee_hashes(referees)
+            .with_gas_limit(GENESIS_GAS_LIMIT.into())
+            .with_nonce(nonce)
+            .with_difficulty(difficulty)
+            .with_adaptive(adaptive)
+            .with_author(self.author)
+            .build();
+        header.compute_hash();
+        let pow_quality =
+            U256::from((SIMULATION_DIFFICULTY as f64 / pow_sample) as u64);
+        header.pow_hash = Some(pow::pow_quality_to_hash(&pow_quality, &nonce));
+
+        let block = Arc::new(Block::new(header, vec![]));
+        self.receive_block(block.clone());
+        if self.behavior == NodeBehavior::Withholding {
+            self.withheld_blocks.push(block.clone());
+        }
+        block
+    }
+
+    pub(super) fn take_withheld_blocks(&mut self) -> Vec<Arc<Block>> {
+        std::mem::take(&mut self.withheld_blocks)
+    }
+}
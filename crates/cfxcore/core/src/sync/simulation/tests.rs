@@ -0,0 +1,155 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use super::{NodeBehavior, Simulation, SimulationConfig};
+
+#[test]
+fn test_same_seed_same_pivot_chain() {
+    let run = || {
+        let mut simulation = Simulation::new(SimulationConfig {
+            seed: 7,
+            ..Default::default()
+        });
+        simulation.run_for(30_000);
+        simulation.run_until_delivered();
+        assert!(simulation.is_converged());
+        simulation.nodes[0].pivot_chain()
+    };
+    let pivot_chain = run();
+    assert!(pivot_chain.len() > 1);
+    assert_eq!(pivot_chain, run());
+}
+
+#[test]
+fn test_partition_heal_switches_pivot_chain() {
+    let mut simulation = Simulation::new(SimulationConfig {
+        seed: 1,
+        ..Default::default()
+    });
+    simulation.run_for(5_000);
+    simulation.run_until_delivered();
+    assert!(simulation.is_converged());
+
+    simulation.split_network(&[vec![0, 1], vec![2, 3]]);
+    simulation.start_mining();
+    simulation.run_for(20_000);
+    simulation.run_until_delivered();
+    let best_before_heal: Vec<_> = simulation
+        .nodes
+        .iter()
+        .map(|node| node.best_block_hash())
+        .collect();
+    assert_eq!(best_before_heal[0], best_before_heal[1]);
+    assert_eq!(best_before_heal[2], best_before_heal[3]);
+    assert_ne!(best_before_heal[0], best_before_heal[2]);
+
+    simulation.heal_network();
+    simulation.run_until_delivered();
+    assert!(simulation.is_converged());
+
+    // One of the partitions has switched its pivot chain to the other one.
+    let pivot_chain = simulation.nodes[0].pivot_chain();
+    let switched = best_before_heal
+        .iter()
+        .filter(|hash| !pivot_chain.contains(hash))
+        .count();
+    assert_eq!(switched, 2);
+}
+
+#[test]
+fn test_withheld_chain_takes_over_pivot_chain() {
+    let mut simulation = Simulation::new(SimulationConfig {
+        seed: 2,
+        block_interval_ms: 0,
+        ..Default::default()
+    });
+    simulation.set_behavior(3, NodeBehavior::Withholding);
+
+    let mut withheld = Vec::new();
+    for _ in 0..10 {
+        withheld.push(simulation.mine_block(3));
+    }
+    for i in 0..5 {
+        simulation.mine_block(i % 3);
+        simulation.run_until_delivered();
+    }
+    for node in &simulation.nodes[..3] {
+        assert!(!node.knows_block(&withheld[0]));
+    }
+    assert_eq!(simulation.nodes[3].withheld_blocks().len(), 10);
+
+    simulation.release_withheld_blocks(3);
+    simulation.run_until_delivered();
+    assert!(simulation.is_converged());
+    assert!(simulation.nodes[3].withheld_blocks().is_empty());
+    assert_eq!(
+        simulation.nodes[0].best_block_hash(),
+        *withheld.last().unwrap()
+    );
+}
+
+#[test]
+fn test_withheld_chain_reorg_notification() {
+    let mut simulation = Simulation::new(SimulationConfig {
+        seed: 2,
+        block_interval_ms: 0,
+        ..Default::default()
+    });
+    simulation.set_behavior(3, NodeBehavior::Withholding);
+
+    let mut withheld = Vec::new();
+    for _ in 0..10 {
+        withheld.push(simulation.mine_block(3));
+    }
+    for i in 0..5 {
+        simulation.mine_block(i % 3);
+        simulation.run_until_delivered();
+    }
+    let old_pivot_chain = simulation.nodes[0].pivot_chain();
+    assert_eq!(old_pivot_chain.len(), 6);
+
+    let pivot_chain_reorgs = simulation.nodes[0]
+        .consensus
+        .new_block_handler
+        .pivot_chain_reorgs()
+        .clone();
+    let mut receiver = pivot_chain_reorgs.subscribe();
+    simulation.release_withheld_blocks(3);
+    simulation.run_until_delivered();
+    assert!(simulation.is_converged());
+
+    // All the honest epochs are reverted at once, and the new pivot chain
+    // segment is the withheld chain as far as it's received at the switch.
+    let mut reorgs = Vec::new();
+    while let Ok(reorg) = receiver.try_recv() {
+        reorgs.push(reorg);
+    }
+    assert_eq!(reorgs.len(), 1);
+    let reorg = &reorgs[0];
+    assert_eq!(reorg.fork_at, 1);
+    assert_eq!(reorg.old_pivots, old_pivot_chain[1..]);
+    assert!(!reorg.new_pivots.is_empty());
+    assert_eq!(reorg.new_pivots, withheld[..reorg.new_pivots.len()]);
+    assert_eq!(simulation.nodes[0].pivot_chain()[1..], withheld[..]);
+    pivot_chain_reorgs.unsubscribe(receiver.id);
+}
+
+#[test]
+fn test_timer_chain_agreement() {
+    let mut simulation = Simulation::new(SimulationConfig {
+        seed: 3,
+        timer_chain_block_difficulty_ratio: 4,
+        timer_chain_beta: 8,
+        ..Default::default()
+    });
+    simulation.run_for(30_000);
+    simulation.run_until_delivered();
+    assert!(simulation.is_converged());
+
+    let timer_chain_height = simulation.nodes[0].best_timer_chain_height();
+    assert!(timer_chain_height > 0);
+    for node in &simulation.nodes {
+        assert_eq!(node.best_timer_chain_height(), timer_chain_height);
+    }
+}
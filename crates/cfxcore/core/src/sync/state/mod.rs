@@ -2,6 +2,7 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
+pub mod snapshot_archive;
 mod snapshot_chunk_sync;
 mod state_sync_candidate;
 mod state_sync_chunk;
@@ -9,5 +10,5 @@ mod state_sync_manifest;
 pub mod storage;
 
 pub use self::snapshot_chunk_sync::{
-    SnapshotChunkSync, StateSyncConfiguration, Status,
+    SnapshotChunkSync, StateSyncConfiguration, StateSyncProgress, Status,
 };
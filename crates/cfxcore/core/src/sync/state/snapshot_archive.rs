@@ -0,0 +1,563 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! A portable archive of a snapshot, to bootstrap a node from a file instead
+//! of downloading the snapshot from peers.
+//!
+//! The archive holds what a syncing node receives from peers for a snapshot:
+//! the initial manifest response with the blame states and the epoch receipts,
+//! followed by all the chunks of the manifest in order. Every section is
+//! framed with its length and checksum:
+//!
+//! ```text
+//! archive = magic | frame(manifest) | frame(chunk) ...
+//! frame   = len: u64 big endian | keccak(payload) | payload
+//! ```
+//!
+//! The content of an archive is not trusted. On import, the blame states are
+//! checked against the trusted blame block of the importing node, and the
+//! chunks are restored through `FullSyncVerifier`, as for a snapshot synced
+//! from peers.
+
+use crate::{
+    block_data_manager::BlockExecutionResult,
+    sync::{
+        message::{Context, SnapshotManifestRequest},
+        state::{
+            state_sync_chunk::restore::Restorer,
+            state_sync_manifest::snapshot_manifest_manager::{
+                RelatedData, SnapshotManifestManager,
+            },
+            storage::{Chunk, ChunkKey, RangedManifest, SnapshotSyncCandidate},
+        },
+        Error, SynchronizationGraph, SynchronizationProtocolHandler,
+    },
+};
+use cfx_storage::{
+    state_manager::StateManager, FullSyncVerifier, SnapshotDbManager,
+};
+use cfx_types::H256;
+use keccak_hash::keccak;
+use network::NetworkContext;
+use primitives::{EpochId, MerkleHash, StateRoot};
+use rlp::{Encodable, Rlp};
+use rlp_derive::{RlpDecodable, RlpEncodable};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufReader, Read, Write},
+    path::Path,
+    sync::Arc,
+};
+
+const SNAPSHOT_ARCHIVE_MAGIC: &[u8; 8] = b"CFXSNAP1";
+
+/// Guard against allocating for a corrupted frame length.
+const MAX_FRAME_SIZE: u64 = 1 << 30;
+
+/// The initial manifest response of a snapshot, for the trusted blame block of
+/// the snapshot on the exporting node.
+#[derive(RlpEncodable, RlpDecodable)]
+pub struct SnapshotArchiveManifest {
+    pub snapshot_to_sync: SnapshotSyncCandidate,
+    pub trusted_blame_block: H256,
+    /// The complete manifest, so `next` is always `None`.
+    pub manifest: RangedManifest,
+    pub snapshot_merkle_root: MerkleHash,
+    pub state_root_vec: Vec<StateRoot>,
+    pub receipt_blame_vec: Vec<H256>,
+    pub bloom_blame_vec: Vec<H256>,
+    pub block_receipts: Vec<BlockExecutionResult>,
+}
+
+impl SnapshotArchiveManifest {
+    pub fn number_chunks(&self) -> usize {
+        self.manifest.chunk_boundaries.len() + 1
+    }
+}
+
+pub struct SnapshotArchiveWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> SnapshotArchiveWriter<W> {
+    pub fn new(
+        mut writer: W, manifest: &SnapshotArchiveManifest,
+    ) -> Result<Self, Error> {
+        writer.write_all(SNAPSHOT_ARCHIVE_MAGIC)?;
+        let mut archive = Self { writer };
+        archive.write_frame(&manifest.rlp_bytes())?;
+        Ok(archive)
+    }
+
+    /// The chunks must be written in the order of the manifest.
+    pub fn write_chunk(&mut self, chunk: &Chunk) -> Result<(), Error> {
+        self.write_frame(&chunk.rlp_bytes())
+    }
+
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_frame(&mut self, payload: &[u8]) -> Result<(), Error> {
+        self.writer
+            .write_all(&(payload.len() as u64).to_be_bytes())?;
+        self.writer.write_all(keccak(payload).as_bytes())?;
+        self.writer.write_all(payload)?;
+        Ok(())
+    }
+}
+
+pub struct SnapshotArchiveReader<R: Read> {
+    reader: R,
+    /// The keys of the chunks not read yet.
+    chunk_keys: VecDeque<ChunkKey>,
+}
+
+impl<R: Read> SnapshotArchiveReader<R> {
+    /// Open an archive and read its manifest.
+    pub fn new(
+        mut reader: R,
+    ) -> Result<(Self, SnapshotArchiveManifest), Error> {
+        let mut magic = [0u8; SNAPSHOT_ARCHIVE_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != SNAPSHOT_ARCHIVE_MAGIC {
+            bail!(Error::InvalidSnapshotManifest(
+                "not a snapshot archive".into()
+            ));
+        }
+        let manifest: SnapshotArchiveManifest =
+            Rlp::new(&read_frame(&mut reader)?).as_val()?;
+        if manifest.manifest.next.is_some() {
+            bail!(Error::InvalidSnapshotManifest(
+                "incomplete manifest in snapshot archive".into()
+            ));
+        }
+        let chunk_keys = RangedManifest::convert_boundaries_to_chunks(
+            manifest.manifest.chunk_boundaries.clone(),
+        );
+        Ok((
+            Self {
+                reader,
+                chunk_keys: chunk_keys.into(),
+            },
+            manifest,
+        ))
+    }
+
+    /// Read the next chunk with its key, or `None` after the last chunk.
+    pub fn next_chunk(&mut self) -> Result<Option<(ChunkKey, Chunk)>, Error> {
+        let chunk_key = match self.chunk_keys.pop_front() {
+            Some(chunk_key) => chunk_key,
+            None => return Ok(None),
+        };
+        let chunk: Chunk = Rlp::new(&read_frame(&mut self.reader)?).as_val()?;
+        chunk.validate(&chunk_key)?;
+        Ok(Some((chunk_key, chunk)))
+    }
+}
+
+fn read_frame(reader: &mut impl Read) -> Result<Vec<u8>, Error> {
+    let mut len = [0u8; 8];
+    reader.read_exact(&mut len)?;
+    let len = u64::from_be_bytes(len);
+    if len > MAX_FRAME_SIZE {
+        bail!(Error::InvalidSnapshotChunk(format!(
+            "frame of {} bytes in snapshot archive",
+            len
+        )));
+    }
+    let mut checksum = H256::zero();
+    reader.read_exact(checksum.as_bytes_mut())?;
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload)?;
+    if keccak(&payload) != checksum {
+        bail!(Error::InvalidSnapshotChunk(
+            "checksum mismatch in snapshot archive".into()
+        ));
+    }
+    Ok(payload)
+}
+
+/// Write the snapshot of `snapshot_epoch_id` to an archive, with the manifest
+/// a syncing node would receive for it from this node.
+pub fn export_snapshot_archive<W: Write>(
+    graph: &SynchronizationGraph, snapshot_epoch_id: &EpochId, chunk_size: u64,
+    writer: W,
+) -> Result<SnapshotArchiveManifest, Error> {
+    let height = graph
+        .data_man
+        .block_header_by_hash(snapshot_epoch_id)
+        .ok_or_else(|| {
+            format!("Snapshot block {:?} not found", snapshot_epoch_id)
+        })?
+        .height();
+    let snapshot_to_sync = SnapshotSyncCandidate::FullSync {
+        height,
+        snapshot_epoch_id: *snapshot_epoch_id,
+    };
+    let trusted_blame_block = graph
+        .consensus
+        .get_trusted_blame_block_for_snapshot(snapshot_epoch_id)
+        .ok_or_else(|| {
+            Error::Msg(
+                "The trusted blame block of the snapshot is not available"
+                    .into(),
+            )
+        })?;
+
+    let storage_manager = &graph.data_man.storage_manager;
+    let (manifest, snapshot_merkle_root) = RangedManifest::load(
+        &snapshot_to_sync,
+        None,
+        storage_manager,
+        chunk_size,
+        usize::MAX,
+    )?
+    .ok_or_else(|| format!("Snapshot {:?} not found", snapshot_epoch_id))?;
+    let (state_root_vec, receipt_blame_vec, bloom_blame_vec) =
+        SnapshotManifestRequest::get_blame_states(
+            &snapshot_to_sync,
+            &trusted_blame_block,
+            graph,
+        )
+        .ok_or_else(|| {
+            Error::Msg(
+                "The blame states of the snapshot are not available".into(),
+            )
+        })?;
+    let block_receipts =
+        SnapshotManifestRequest::get_block_receipts(&snapshot_to_sync, graph)
+            .ok_or_else(|| {
+            Error::Msg(
+                "The epoch receipts of the snapshot are not available".into(),
+            )
+        })?;
+    let archive_manifest = SnapshotArchiveManifest {
+        snapshot_to_sync,
+        trusted_blame_block,
+        manifest,
+        snapshot_merkle_root,
+        state_root_vec,
+        receipt_blame_vec,
+        bloom_blame_vec,
+        block_receipts,
+    };
+
+    let mut archive = SnapshotArchiveWriter::new(writer, &archive_manifest)?;
+    for chunk_key in RangedManifest::convert_boundaries_to_chunks(
+        archive_manifest.manifest.chunk_boundaries.clone(),
+    ) {
+        let chunk = Chunk::load(
+            snapshot_epoch_id,
+            &chunk_key,
+            storage_manager,
+            // The same bound as serving the chunk to peers.
+            chunk_size * 2,
+        )?
+        .ok_or_else(|| format!("Snapshot {:?} not found", snapshot_epoch_id))?;
+        archive.write_chunk(&chunk)?;
+    }
+    archive.finish()?;
+    Ok(archive_manifest)
+}
+
+/// Check the checksums of an archive and restore its chunks through
+/// `FullSyncVerifier` with `snapshot_db_manager`, without checking the blame
+/// states. The restored snapshot is left as a temporary snapshot in the
+/// snapshot directory of `snapshot_db_manager`.
+pub fn verify_snapshot_archive<R: Read>(
+    reader: R, snapshot_db_manager: &SnapshotDbManager,
+) -> Result<SnapshotArchiveManifest, Error> {
+    let (mut archive, manifest) = SnapshotArchiveReader::new(reader)?;
+    manifest.manifest.validate(&manifest.snapshot_merkle_root)?;
+    let mut verifier = FullSyncVerifier::new(
+        manifest.number_chunks(),
+        manifest.manifest.chunk_boundaries.clone(),
+        manifest.manifest.chunk_boundary_proofs.clone(),
+        manifest.snapshot_merkle_root,
+        snapshot_db_manager,
+        manifest.snapshot_to_sync.get_snapshot_epoch_id(),
+        manifest.snapshot_to_sync.height(),
+    )?;
+    while let Some((chunk_key, chunk)) = archive.next_chunk()? {
+        if !verifier.restore_chunk(
+            &chunk_key.upper_bound_excl,
+            &chunk.keys,
+            chunk.values,
+        )? {
+            bail!(Error::InvalidSnapshotChunk(format!(
+                "chunk {:?} mismatches the manifest",
+                chunk_key
+            )));
+        }
+    }
+    if !verifier.is_completed() {
+        bail!(Error::InvalidSnapshotChunk("missing chunks".into()));
+    }
+    Ok(manifest)
+}
+
+/// The name of the archive of `snapshot_epoch_id` in the snapshot archive
+/// directory, which a node restores the snapshot from when it syncs it.
+pub fn snapshot_archive_file_name(snapshot_epoch_id: &EpochId) -> String {
+    format!("{:x}.cfxsnap", snapshot_epoch_id)
+}
+
+/// A snapshot archive to restore the snapshot to sync from, of which the
+/// manifest is validated against the blame states of the chain.
+pub(super) struct SnapshotArchiveImport {
+    archive: SnapshotArchiveReader<BufReader<File>>,
+    manifest: SnapshotArchiveManifest,
+    related_data: RelatedData,
+}
+
+/// Open a snapshot archive to restore the snapshot to sync from, as if it's
+/// synced from peers.
+///
+/// Fail if the archive is not for `epoch_to_sync` or was exported with a
+/// different trusted blame block, in which case the snapshot should be synced
+/// from peers.
+pub(super) fn open_snapshot_archive(
+    path: &Path, epoch_to_sync: &EpochId, trusted_blame_block: &H256,
+    io: &dyn NetworkContext, sync_handler: &SynchronizationProtocolHandler,
+) -> Result<SnapshotArchiveImport, Error> {
+    let (archive, mut manifest) =
+        SnapshotArchiveReader::new(BufReader::new(File::open(path)?))?;
+    if manifest.snapshot_to_sync.get_snapshot_epoch_id() != epoch_to_sync {
+        bail!(Error::InvalidSnapshotManifest(format!(
+            "the archive is for {:?}, but the snapshot to sync is {:?}",
+            manifest.snapshot_to_sync, epoch_to_sync
+        )));
+    }
+    if manifest.trusted_blame_block != *trusted_blame_block {
+        bail!(Error::InvalidSnapshotManifest(format!(
+            "the archive is exported with trusted blame block {:?}, but ours \
+             is {:?}",
+            manifest.trusted_blame_block, trusted_blame_block
+        )));
+    }
+
+    let ctx = Context {
+        // node_id is not used here
+        node_id: Default::default(),
+        io,
+        manager: sync_handler,
+    };
+    let (
+        blame_vec_offset,
+        true_state_root_by_blame_info,
+        snapshot_info,
+        parent_snapshot_info,
+    ) = SnapshotManifestManager::validate_blame_states(
+        &ctx,
+        epoch_to_sync,
+        trusted_blame_block,
+        &manifest.state_root_vec,
+        &manifest.receipt_blame_vec,
+        &manifest.bloom_blame_vec,
+    )
+    .ok_or_else(|| {
+        Error::InvalidSnapshotManifest(
+            "invalid blame state in snapshot archive".into(),
+        )
+    })?;
+    let epoch_receipts = SnapshotManifestManager::validate_epoch_receipts(
+        &ctx,
+        blame_vec_offset,
+        epoch_to_sync,
+        &manifest.receipt_blame_vec,
+        &manifest.bloom_blame_vec,
+        &manifest.block_receipts,
+    )
+    .ok_or_else(|| {
+        Error::InvalidSnapshotManifest(
+            "invalid epoch receipts in snapshot archive".into(),
+        )
+    })?;
+    manifest.manifest.validate(&snapshot_info.merkle_root)?;
+
+    let related_data = RelatedData {
+        true_state_root_by_blame_info,
+        blame_vec_offset,
+        receipt_blame_vec: std::mem::take(&mut manifest.receipt_blame_vec),
+        bloom_blame_vec: std::mem::take(&mut manifest.bloom_blame_vec),
+        epoch_receipts,
+        snapshot_info,
+        parent_snapshot_info,
+    };
+    Ok(SnapshotArchiveImport {
+        archive,
+        manifest,
+        related_data,
+    })
+}
+
+impl SnapshotArchiveImport {
+    /// Restore all the chunks of the archive into the snapshot to sync, which
+    /// takes a while for a large snapshot.
+    pub fn restore(
+        mut self, state_manager: Arc<StateManager>,
+    ) -> Result<RelatedData, Error> {
+        let snapshot_info = &self.related_data.snapshot_info;
+        let snapshot_epoch_id = *snapshot_info.get_snapshot_epoch_id();
+        info!(
+            "restore snapshot {:?} from archive, {} chunks",
+            self.manifest.snapshot_to_sync,
+            self.manifest.number_chunks()
+        );
+        let mut restorer =
+            Restorer::new(snapshot_epoch_id, snapshot_info.merkle_root);
+        restorer.initialize_verifier(FullSyncVerifier::new(
+            self.manifest.number_chunks(),
+            self.manifest.manifest.chunk_boundaries.clone(),
+            self.manifest.manifest.chunk_boundary_proofs.clone(),
+            snapshot_info.merkle_root,
+            state_manager
+                .get_storage_manager()
+                .get_snapshot_manager()
+                .get_snapshot_db_manager(),
+            &snapshot_epoch_id,
+            snapshot_info.height,
+        )?);
+        while let Some((chunk_key, chunk)) = self.archive.next_chunk()? {
+            if !restorer.append(chunk_key.clone(), chunk) {
+                bail!(Error::InvalidSnapshotChunk(format!(
+                    "chunk {:?} in snapshot archive fails the verification",
+                    chunk_key
+                )));
+            }
+        }
+        restorer.finalize_restoration(
+            state_manager,
+            self.related_data.snapshot_info.clone(),
+            self.related_data.parent_snapshot_info.clone(),
+            self.related_data
+                .true_state_root_by_blame_info
+                .state_root
+                .delta_root,
+        )?;
+        Ok(self.related_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cfx_storage::{
+        tests::{snapshot_merkle_root, DumpedMptKvIterator},
+        SnapshotDbBackend,
+    };
+    use tempdir::TempDir;
+
+    fn test_chunk() -> Chunk {
+        let mut chunk = Chunk::default();
+        for i in 0..100u8 {
+            chunk.keys.push(vec![i, i.wrapping_mul(7), 0xaa]);
+            chunk.values.push(vec![i; 1 + i as usize % 5]);
+        }
+        chunk
+    }
+
+    fn test_manifest(chunk: &Chunk) -> SnapshotArchiveManifest {
+        let kvs = DumpedMptKvIterator {
+            kv: chunk
+                .keys
+                .iter()
+                .zip(&chunk.values)
+                .map(|(k, v)| (k.clone(), v[..].into()))
+                .collect(),
+        };
+        SnapshotArchiveManifest {
+            snapshot_to_sync: SnapshotSyncCandidate::FullSync {
+                height: 0,
+                snapshot_epoch_id: H256::repeat_byte(1),
+            },
+            trusted_blame_block: H256::repeat_byte(2),
+            manifest: RangedManifest::default(),
+            snapshot_merkle_root: snapshot_merkle_root(&kvs).unwrap(),
+            state_root_vec: vec![],
+            receipt_blame_vec: vec![],
+            bloom_blame_vec: vec![],
+            block_receipts: vec![],
+        }
+    }
+
+    fn write_archive(
+        manifest: &SnapshotArchiveManifest, chunk: &Chunk,
+    ) -> Vec<u8> {
+        let mut archive =
+            SnapshotArchiveWriter::new(Vec::new(), manifest).unwrap();
+        archive.write_chunk(chunk).unwrap();
+        archive.finish().unwrap()
+    }
+
+    fn verify(archive: &[u8]) -> Result<SnapshotArchiveManifest, Error> {
+        let dir = TempDir::new("snapshot_archive").unwrap();
+        let snapshot_db_manager = SnapshotDbManager::new(
+            SnapshotDbBackend::Sqlite,
+            dir.path().join("snapshot"),
+            /* max_open_snapshots */ 1,
+            /* use_isolated_db_for_mpt_table */ false,
+            /* use_isolated_db_for_mpt_table_height */ None,
+            /* era_epoch_count */ 50000,
+        )
+        .unwrap();
+        verify_snapshot_archive(archive, &snapshot_db_manager)
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let chunk = test_chunk();
+        let manifest = test_manifest(&chunk);
+        let archive = write_archive(&manifest, &chunk);
+
+        let (mut reader, read_manifest) =
+            SnapshotArchiveReader::new(&archive[..]).unwrap();
+        assert_eq!(read_manifest.rlp_bytes(), manifest.rlp_bytes());
+        let (chunk_key, read_chunk) = reader.next_chunk().unwrap().unwrap();
+        assert_eq!(chunk_key.upper_bound_excl, None);
+        assert_eq!(read_chunk.keys, chunk.keys);
+        assert_eq!(read_chunk.values, chunk.values);
+        assert!(reader.next_chunk().unwrap().is_none());
+
+        let verified = verify(&archive).unwrap();
+        assert_eq!(
+            verified.snapshot_merkle_root,
+            manifest.snapshot_merkle_root
+        );
+    }
+
+    #[test]
+    fn test_corrupted_archive_rejected() {
+        let chunk = test_chunk();
+        let manifest = test_manifest(&chunk);
+        let archive = write_archive(&manifest, &chunk);
+
+        // A flipped byte in the chunk payload fails the checksum.
+        let mut corrupted = archive.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert!(verify(&corrupted).is_err());
+
+        // So does a truncated archive.
+        assert!(verify(&archive[..archive.len() - 1]).is_err());
+
+        let mut wrong_magic = archive.clone();
+        wrong_magic[0] ^= 1;
+        assert!(SnapshotArchiveReader::new(&wrong_magic[..]).is_err());
+
+        // A tampered value with a valid checksum fails the merkle root.
+        let mut tampered = test_chunk();
+        tampered.values[50] = b"tampered".to_vec();
+        assert!(verify(&write_archive(&manifest, &tampered)).is_err());
+
+        // A missing chunk is rejected as well.
+        let truncated = SnapshotArchiveWriter::new(Vec::new(), &manifest)
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert!(verify(&truncated).is_err());
+    }
+}
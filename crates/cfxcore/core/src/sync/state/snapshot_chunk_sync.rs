@@ -9,6 +9,7 @@ use crate::sync::{
         StateSyncCandidateRequest,
     },
     state::{
+        snapshot_archive::{open_snapshot_archive, snapshot_archive_file_name},
         state_sync_candidate::state_sync_candidate_manager::StateSyncCandidateManager,
         state_sync_chunk::snapshot_chunk_manager::{
             SnapshotChunkConfig, SnapshotChunkManager,
@@ -30,7 +31,9 @@ use primitives::EpochId;
 use std::{
     collections::HashSet,
     fmt::{Debug, Formatter},
+    path::{Path, PathBuf},
     sync::Arc,
+    thread,
     time::{Duration, Instant},
 };
 
@@ -41,6 +44,7 @@ pub enum Status {
     StartCandidateSync,
     DownloadingManifest(Instant),
     DownloadingChunks(Instant),
+    RestoringArchive(Instant),
     Completed,
     Invalid,
 }
@@ -49,6 +53,21 @@ impl Default for Status {
     fn default() -> Self { Status::Inactive }
 }
 
+impl Status {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Inactive => "inactive",
+            Status::RequestingCandidates => "requestingCandidates",
+            Status::StartCandidateSync => "startCandidateSync",
+            Status::DownloadingManifest(_) => "downloadingManifest",
+            Status::DownloadingChunks(_) => "downloadingChunks",
+            Status::RestoringArchive(_) => "restoringArchive",
+            Status::Completed => "completed",
+            Status::Invalid => "invalid",
+        }
+    }
+}
+
 impl Debug for Status {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         let status = match self {
@@ -63,6 +82,9 @@ impl Debug for Status {
             Status::DownloadingChunks(t) => {
                 format!("downloading chunks ({:?})", t.elapsed())
             }
+            Status::RestoringArchive(t) => {
+                format!("restoring snapshot archive ({:?})", t.elapsed())
+            }
             Status::Completed => "completed".into(),
             Status::Invalid => "invalid".into(),
         };
@@ -71,6 +93,19 @@ impl Debug for Status {
     }
 }
 
+/// A snapshot of the state sync progress for RPC and logging.
+#[derive(Clone, Debug, Default)]
+pub struct StateSyncProgress {
+    pub status: Status,
+    /// The snapshot epoch being synced and its height.
+    pub snapshot: Option<(EpochId, u64)>,
+    /// The number of chunks announced in the manifest received so far.
+    pub manifest_chunks: usize,
+    pub downloaded_chunks: usize,
+    /// Only known after the whole manifest is received.
+    pub total_chunks: Option<usize>,
+}
+
 // TODO: Implement OneStepSync / IncSync as this is currently only implemented
 // for FullSync.
 struct Inner {
@@ -83,6 +118,8 @@ struct Inner {
 
     related_data: Option<RelatedData>,
     manifest_attempts: usize,
+    // The snapshot archive is only tried once.
+    snapshot_archive_tried: bool,
 }
 
 impl Default for Inner {
@@ -98,6 +135,7 @@ impl Inner {
             chunk_manager: None,
             manifest_manager: None,
             manifest_attempts: 0,
+            snapshot_archive_tried: false,
         }
     }
 
@@ -205,6 +243,34 @@ impl SnapshotChunkSync {
 
     pub fn status(&self) -> Status { self.inner.read().status }
 
+    pub fn snapshot_archive_dir(&self) -> &Path {
+        &self.config.snapshot_archive_dir
+    }
+
+    pub fn progress(&self) -> StateSyncProgress {
+        let inner = self.inner.read();
+        let mut progress = StateSyncProgress {
+            status: inner.status,
+            ..Default::default()
+        };
+        if let Some(chunk_manager) = &inner.chunk_manager {
+            progress.snapshot = Some((
+                *chunk_manager.snapshot_candidate.get_snapshot_epoch_id(),
+                chunk_manager.snapshot_candidate.height(),
+            ));
+            progress.manifest_chunks = chunk_manager.num_total();
+            progress.downloaded_chunks = chunk_manager.num_downloaded();
+            progress.total_chunks = Some(chunk_manager.num_total());
+        } else if let Some(manifest_manager) = &inner.manifest_manager {
+            progress.snapshot = Some((
+                *manifest_manager.snapshot_candidate.get_snapshot_epoch_id(),
+                manifest_manager.snapshot_candidate.height(),
+            ));
+            progress.manifest_chunks = manifest_manager.chunk_boundaries.len();
+        }
+        progress
+    }
+
     pub fn handle_snapshot_manifest_response(
         &self, ctx: &Context, response: SnapshotManifestResponse,
         request: &SnapshotManifestRequest,
@@ -376,6 +442,11 @@ impl SnapshotChunkSync {
             );
         }
 
+        if let Status::RestoringArchive(_) = inner.status {
+            // The snapshot archive is being restored in the background.
+            return;
+        }
+
         debug!("sync state status before updating: {:?}", *inner);
         self.check_timeout(
             &mut *inner,
@@ -483,6 +554,22 @@ impl SnapshotChunkSync {
             inner.status = Status::Inactive;
         }
 
+        if inner.status == Status::Inactive && !inner.snapshot_archive_tried {
+            if let Some(path) = self.config.snapshot_archive(&epoch_to_sync) {
+                inner.snapshot_archive_tried = true;
+                if self.restore_from_archive(
+                    &mut *inner,
+                    path,
+                    current_era_genesis,
+                    &epoch_to_sync,
+                    io,
+                    sync_handler,
+                ) {
+                    return;
+                }
+            }
+        }
+
         if inner.status == Status::Inactive {
             // New era started or all candidates fail, we should restart
             // candidates sync
@@ -501,6 +588,85 @@ impl SnapshotChunkSync {
         debug!("sync state status after updating: {:?}", *inner);
     }
 
+    /// Restore the snapshot to sync from a local snapshot archive instead of
+    /// downloading it from peers. The archive is validated against our own
+    /// trusted blame block as a manifest from peers is, and its chunks are
+    /// restored in a background thread, so we fall back to peers if it is
+    /// stale or invalid.
+    fn restore_from_archive(
+        &self, inner: &mut Inner, path: PathBuf, current_era_genesis: EpochId,
+        epoch_to_sync: &EpochId, io: &dyn NetworkContext,
+        sync_handler: &SynchronizationProtocolHandler,
+    ) -> bool {
+        let trusted_blame_block = match sync_handler
+            .graph
+            .consensus
+            .get_trusted_blame_block_for_snapshot(epoch_to_sync)
+        {
+            Some(block) => block,
+            None => {
+                warn!("trusted blame block is unavailable, skip snapshot archive {:?}", path);
+                return false;
+            }
+        };
+        let import = match open_snapshot_archive(
+            &path,
+            epoch_to_sync,
+            &trusted_blame_block,
+            io,
+            sync_handler,
+        ) {
+            Ok(import) => import,
+            Err(e) => {
+                warn!(
+                    "failed to open snapshot archive {:?}: {}, sync from peers",
+                    path, e
+                );
+                return false;
+            }
+        };
+        info!(
+            "restoring snapshot {:?} from archive {:?}",
+            epoch_to_sync, path
+        );
+
+        let state_manager = sync_handler.graph.data_man.storage_manager.clone();
+        let shared_inner = self.inner.clone();
+        let snapshot_epoch_id = *epoch_to_sync;
+        let spawned = thread::Builder::new()
+            .name("restore_snapshot_archive".into())
+            .spawn(move || {
+                let result = import.restore(state_manager);
+                let mut inner = shared_inner.write();
+                match result {
+                    Ok(related_data) => {
+                        info!(
+                            "snapshot {:?} restored from archive",
+                            snapshot_epoch_id
+                        );
+                        inner.related_data = Some(related_data);
+                        inner.status = Status::Completed;
+                    }
+                    Err(e) => {
+                        warn!(
+                            "failed to restore snapshot archive {:?}: {}, sync from peers",
+                            path, e
+                        );
+                        inner.status = Status::Inactive;
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("failed to restore snapshot archive: {:?}", e);
+            return false;
+        }
+        // Keep the era so that `update_status` returns early for the
+        // completed status.
+        inner.sync_candidate_manager.current_era_genesis = current_era_genesis;
+        inner.status = Status::RestoringArchive(Instant::now());
+        true
+    }
+
     fn check_timeout(&self, inner: &mut Inner, ctx: &Context) {
         inner
             .sync_candidate_manager
@@ -525,9 +691,29 @@ pub struct StateSyncConfiguration {
     pub chunk_request_timeout: Duration,
     pub manifest_request_timeout: Duration,
     pub max_downloading_manifest_attempts: usize,
+    /// A snapshot archive to restore the snapshot to sync from, if any.
+    pub snapshot_archive: Option<PathBuf>,
+    /// The directory of the snapshot archives exported or imported by this
+    /// node, where the archive of the snapshot to sync is restored from if
+    /// `snapshot_archive` is not set.
+    pub snapshot_archive_dir: PathBuf,
 }
 
 impl StateSyncConfiguration {
+    fn snapshot_archive(&self, epoch_to_sync: &EpochId) -> Option<PathBuf> {
+        if self.snapshot_archive.is_some() {
+            return self.snapshot_archive.clone();
+        }
+        let path = self
+            .snapshot_archive_dir
+            .join(snapshot_archive_file_name(epoch_to_sync));
+        if path.exists() {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
     fn chunk_config(&self) -> SnapshotChunkConfig {
         SnapshotChunkConfig {
             max_downloading_chunks: self.max_downloading_chunks,
@@ -205,6 +205,14 @@ impl SnapshotChunkManager {
 
     pub fn is_inactive(&self) -> bool { self.active_peers.is_empty() }
 
+    pub fn num_downloaded(&self) -> usize { self.num_downloaded }
+
+    pub fn num_total(&self) -> usize {
+        self.pending_chunks.len()
+            + self.downloading_chunks.len()
+            + self.num_downloaded
+    }
+
     pub fn set_active_peers(&mut self, new_active_peers: HashSet<NodeId>) {
         self.active_peers = new_active_peers;
     }
@@ -59,6 +59,14 @@ impl SnapshotSyncCandidate {
             } => snapshot_epoch_id,
         }
     }
+
+    pub fn height(&self) -> u64 {
+        match self {
+            SnapshotSyncCandidate::OneStepSync { height, .. } => *height,
+            SnapshotSyncCandidate::FullSync { height, .. } => *height,
+            SnapshotSyncCandidate::IncSync { height, .. } => *height,
+        }
+    }
 }
 
 impl Encodable for SnapshotSyncCandidate {
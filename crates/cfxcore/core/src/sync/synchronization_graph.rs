@@ -16,7 +16,7 @@ use std::{
 
 use cfx_parameters::consensus_internal::ELASTICITY_MULTIPLIER;
 use futures::executor::block_on;
-use parking_lot::RwLock;
+use parking_lot::{Condvar, Mutex, RwLock};
 use slab::Slab;
 use tokio::sync::mpsc::error::TryRecvError;
 use unexpected::{Mismatch, OutOfBounds};
@@ -996,6 +996,9 @@ pub struct SynchronizationGraph {
     /// to indicate whether the worker is now finished all pending blocks.
     /// Since the critical section is very short, a `Mutex` is enough.
     consensus_unprocessed_count: Arc<AtomicUsize>,
+    /// Notified by the consensus worker when `consensus_unprocessed_count`
+    /// drops to zero.
+    consensus_worker_idle: Arc<(Mutex<()>, Condvar)>,
 
     /// Channel used to send block hashes to `ConsensusGraph` and PubSub.
     /// Each element is <block_hash, ignore_body>
@@ -1047,6 +1050,7 @@ impl SynchronizationGraph {
         // It should not be initialized to `true` now, otherwise consensus
         // worker will be blocked on waiting the first block forever.
         let consensus_unprocessed_count = Arc::new(AtomicUsize::new(0));
+        let consensus_worker_idle = Arc::new((Mutex::new(()), Condvar::new()));
         let mut consensus_receiver = notifications.new_block_hashes.subscribe();
         let inner = Arc::new(RwLock::new(
             SynchronizationGraphInner::with_genesis_block(
@@ -1071,6 +1075,7 @@ impl SynchronizationGraph {
             consensus: consensus.clone(),
             statistics: consensus.get_statistics().clone(),
             consensus_unprocessed_count: consensus_unprocessed_count.clone(),
+            consensus_worker_idle: consensus_worker_idle.clone(),
             new_block_hashes: notifications.new_block_hashes.clone(),
             machine,
         };
@@ -1170,7 +1175,11 @@ impl SynchronizationGraph {
                         consensus.on_new_block(
                             &hash,
                         );
-                        consensus_unprocessed_count.fetch_sub(1, Ordering::SeqCst);
+                        if consensus_unprocessed_count.fetch_sub(1, Ordering::SeqCst) == 1 {
+                            let (lock, idle) = &*consensus_worker_idle;
+                            let _guard = lock.lock();
+                            idle.notify_all();
+                        }
                     }
                 }
             })
@@ -1947,6 +1956,16 @@ impl SynchronizationGraph {
         self.consensus_unprocessed_count.load(Ordering::SeqCst) != 0
     }
 
+    /// Block until the consensus worker has processed all the blocks sent to
+    /// it.
+    pub fn wait_for_consensus_worker(&self) {
+        let (lock, idle) = &*self.consensus_worker_idle;
+        let mut guard = lock.lock();
+        while self.is_consensus_worker_busy() {
+            idle.wait(&mut guard);
+        }
+    }
+
     pub fn is_fill_block_completed(&self) -> bool {
         self.inner.read().block_to_fill_set.is_empty()
     }
@@ -3,6 +3,7 @@
 // See http://www.gnu.org/licenses/
 
 use crate::{
+    channel::Channel,
     sync::{
         message::DynamicCapability,
         state::{SnapshotChunkSync, Status},
@@ -101,6 +102,8 @@ impl SynchronizationPhaseManagerInner {
 
 pub struct SynchronizationPhaseManager {
     inner: RwLock<SynchronizationPhaseManagerInner>,
+    /// Notified with the new phase on every phase change.
+    pub phase_changes: Arc<Channel<SyncPhaseType>>,
 }
 
 impl SynchronizationPhaseManager {
@@ -114,6 +117,7 @@ impl SynchronizationPhaseManager {
             inner: RwLock::new(SynchronizationPhaseManagerInner::new(
                 initial_phase_type,
             )),
+            phase_changes: Arc::new(Channel::new("sync-phase-changes")),
         };
 
         sync_manager.register_phase(Arc::new(
@@ -157,9 +161,17 @@ impl SynchronizationPhaseManager {
         &self, phase_type: SyncPhaseType, io: &dyn NetworkContext,
         sync_handler: &SynchronizationProtocolHandler,
     ) {
-        self.inner.write().change_phase_to(phase_type);
+        let changed = {
+            let mut inner = self.inner.write();
+            let changed = inner.current_phase != phase_type;
+            inner.change_phase_to(phase_type);
+            changed
+        };
         let current_phase = self.get_current_phase();
         current_phase.start(io, sync_handler);
+        if changed {
+            self.phase_changes.send(phase_type);
+        }
     }
 
     pub fn try_initialize(
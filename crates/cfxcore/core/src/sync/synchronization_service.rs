@@ -6,19 +6,97 @@ use super::{
     Error, SharedSynchronizationGraph, SynchronizationProtocolHandler,
 };
 use crate::{
+    channel::Channel,
     light_protocol::Provider as LightProvider,
     sync::{
-        request_manager::RequestManager, synchronization_phases::SyncPhaseType,
+        export_snapshot_archive, request_manager::RequestManager,
+        state::StateSyncProgress, synchronization_phases::SyncPhaseType,
         synchronization_protocol_handler::ProtocolConfiguration,
-        StateSyncConfiguration, SynchronizationPhaseTrait,
+        SnapshotArchiveManifest, StateSyncConfiguration,
+        SynchronizationPhaseTrait,
     },
     ConsensusGraph, NodeType,
 };
 use cfx_types::H256;
 use malloc_size_of_derive::MallocSizeOf as DeriveMallocSizeOf;
 use network::{NetworkService, ProtocolId};
+use parking_lot::Mutex;
 use primitives::{transaction::SignedTransaction, Block};
-use std::sync::Arc;
+use std::{
+    cmp::max,
+    ffi::OsStr,
+    fs::{self, File},
+    io::BufWriter,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// The sync progress exposed through RPC.
+#[derive(Clone, Debug)]
+pub struct SyncProgress {
+    pub phase: SyncPhaseType,
+    pub catch_up_mode: bool,
+    /// The best epoch when the progress is first queried in the current
+    /// phase.
+    pub starting_epoch: u64,
+    pub current_epoch: u64,
+    /// The median best epoch of the peers, or `current_epoch` if it is
+    /// unknown.
+    pub target_epoch: u64,
+    /// The number of blocks whose headers are in the sync graph.
+    pub header_count: u64,
+    /// The number of blocks inserted into consensus.
+    pub block_count: u64,
+    pub state_sync: StateSyncProgress,
+    /// Estimated with the average epoch progress since `starting_epoch`.
+    pub eta: Option<Duration>,
+}
+
+impl SyncProgress {
+    /// Extrapolate the time to reach `target_epoch` from the epochs synced
+    /// in `elapsed` since `starting_epoch`.
+    pub fn estimate_eta(
+        elapsed: Duration, starting_epoch: u64, current_epoch: u64,
+        target_epoch: u64,
+    ) -> Option<Duration> {
+        let progressed = current_epoch.saturating_sub(starting_epoch);
+        let remaining = target_epoch.saturating_sub(current_epoch);
+        if progressed == 0 || remaining == 0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(
+            elapsed.as_secs_f64() * remaining as f64 / progressed as f64,
+        ))
+    }
+}
+
+/// The sync phase the ETA is estimated in, and the time and the best epoch
+/// when it started.
+#[derive(Clone, Copy, Debug)]
+pub struct SyncStart {
+    pub phase: SyncPhaseType,
+    pub time: Instant,
+    pub epoch: u64,
+}
+
+impl SyncStart {
+    /// Restart from `current_epoch` if the phase has changed since the last
+    /// call, as the phases progress at very different speeds.
+    pub fn update(
+        sync_start: &mut Option<SyncStart>, phase: SyncPhaseType,
+        current_epoch: u64, now: Instant,
+    ) -> SyncStart {
+        match sync_start {
+            Some(start) if start.phase == phase => *start,
+            _ => *sync_start.insert(SyncStart {
+                phase,
+                time: now,
+                epoch: current_epoch,
+            }),
+        }
+    }
+}
 
 #[derive(DeriveMallocSizeOf)]
 pub struct SynchronizationService {
@@ -27,6 +105,8 @@ pub struct SynchronizationService {
     protocol_handler: Arc<SynchronizationProtocolHandler>,
     #[ignore_malloc_size_of = "insignificant"]
     protocol: ProtocolId,
+    #[ignore_malloc_size_of = "insignificant"]
+    sync_start: Mutex<Option<SyncStart>>,
 }
 
 impl SynchronizationService {
@@ -54,6 +134,7 @@ impl SynchronizationService {
             network,
             protocol_handler: sync_handler,
             protocol: *b"cfx",
+            sync_start: Mutex::new(None),
         }
     }
 
@@ -73,6 +154,87 @@ impl SynchronizationService {
         self.protocol_handler.phase_manager.get_current_phase()
     }
 
+    /// Notified with the new phase on every sync phase change.
+    pub fn sync_phase_changes(&self) -> Arc<Channel<SyncPhaseType>> {
+        self.protocol_handler.phase_manager.phase_changes.clone()
+    }
+
+    pub fn sync_progress(&self) -> SyncProgress {
+        let handler = &self.protocol_handler;
+        let phase = handler.phase_manager.get_current_phase().phase_type();
+        let consensus = &handler.graph.consensus;
+        let current_epoch = consensus.best_epoch_number();
+        let target_epoch = max(
+            handler.syn.median_epoch_from_normal_peers().unwrap_or(0),
+            current_epoch,
+        );
+
+        let start = SyncStart::update(
+            &mut self.sync_start.lock(),
+            phase,
+            current_epoch,
+            Instant::now(),
+        );
+        let eta = SyncProgress::estimate_eta(
+            start.time.elapsed(),
+            start.epoch,
+            current_epoch,
+            target_epoch,
+        );
+
+        SyncProgress {
+            phase,
+            catch_up_mode: handler.catch_up_mode(),
+            starting_epoch: start.epoch,
+            current_epoch,
+            target_epoch,
+            header_count: handler.graph.block_count() as u64,
+            block_count: consensus.block_count(),
+            state_sync: handler.state_sync.progress(),
+            eta,
+        }
+    }
+
+    /// Export the snapshot of the stable checkpoint, which a node catching
+    /// up restores in place of syncing it from peers, to the file
+    /// `file_name` in the snapshot archive directory.
+    pub fn export_snapshot_archive(
+        &self, file_name: &str,
+    ) -> Result<SnapshotArchiveManifest, Error> {
+        if Path::new(file_name).file_name() != Some(OsStr::new(file_name)) {
+            bail!(Error::Msg(format!(
+                "{:?} is not a file name in the snapshot archive directory",
+                file_name
+            )));
+        }
+        let handler = &self.protocol_handler;
+        let archive_dir = handler.state_sync.snapshot_archive_dir();
+        fs::create_dir_all(archive_dir)?;
+        let path = archive_dir.join(file_name);
+        // Write to a temporary file so that a partial archive is never
+        // restored.
+        let partial_path = archive_dir.join(format!("{}.partial", file_name));
+
+        let snapshot_epoch_id = handler.graph.consensus.get_to_sync_epoch_id();
+        let writer = BufWriter::new(File::create(&partial_path)?);
+        let manifest = export_snapshot_archive(
+            &handler.graph,
+            &snapshot_epoch_id,
+            handler.protocol_config.chunk_size_byte,
+            writer,
+        );
+        match manifest {
+            Ok(manifest) => {
+                fs::rename(&partial_path, &path)?;
+                Ok(manifest)
+            }
+            Err(e) => {
+                fs::remove_file(&partial_path).ok();
+                Err(e)
+            }
+        }
+    }
+
     pub fn append_received_transactions(
         &self, transactions: Vec<Arc<SignedTransaction>>,
     ) {
@@ -6,7 +6,7 @@ use crate::{
     block_data_manager::DbType,
     sync::{
         utils::{create_simple_block_impl, initialize_synchronization_graph},
-        SynchronizationGraphNode,
+        SyncPhaseType, SyncProgress, SyncStart, SynchronizationGraphNode,
     },
 };
 use cfx_types::{BigEndianHash, H256, U256};
@@ -15,7 +15,7 @@ use std::{
     fs,
     sync::Arc,
     thread::sleep,
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 #[test]
@@ -235,3 +235,71 @@ fn test_remove_expire_blocks() {
         sleep(Duration::from_millis(300));
     }
 }
+
+#[test]
+fn test_sync_progress_eta() {
+    let eta = |elapsed_secs, starting, current, target| {
+        SyncProgress::estimate_eta(
+            Duration::from_secs(elapsed_secs),
+            starting,
+            current,
+            target,
+        )
+    };
+
+    // 100 epochs in 10s, 1000 epochs to go.
+    assert_eq!(eta(10, 100, 200, 1200), Some(Duration::from_secs(100)));
+    // No progress yet.
+    assert_eq!(eta(10, 100, 100, 1200), None);
+    // Caught up, or ahead of the peers.
+    assert_eq!(eta(10, 100, 200, 200), None);
+    assert_eq!(eta(10, 100, 200, 150), None);
+    // The best epoch went back after a pivot chain switch.
+    assert_eq!(eta(10, 100, 50, 1200), None);
+}
+
+#[test]
+fn test_sync_start_reset_on_phase_change() {
+    let t0 = Instant::now();
+    let t1 = t0 + Duration::from_secs(10);
+    let t2 = t0 + Duration::from_secs(20);
+    let mut sync_start = None;
+
+    let start = SyncStart::update(
+        &mut sync_start,
+        SyncPhaseType::CatchUpRecoverBlockHeaderFromDB,
+        100,
+        t0,
+    );
+    assert_eq!((start.time, start.epoch), (t0, 100));
+
+    // The start is kept within a phase.
+    let start = SyncStart::update(
+        &mut sync_start,
+        SyncPhaseType::CatchUpRecoverBlockHeaderFromDB,
+        500,
+        t1,
+    );
+    assert_eq!((start.time, start.epoch), (t0, 100));
+
+    // The start is reset when the phase changes.
+    let start = SyncStart::update(
+        &mut sync_start,
+        SyncPhaseType::CatchUpSyncBlock,
+        600,
+        t1,
+    );
+    assert_eq!(start.phase, SyncPhaseType::CatchUpSyncBlock);
+    assert_eq!((start.time, start.epoch), (t1, 600));
+    let start = SyncStart::update(
+        &mut sync_start,
+        SyncPhaseType::CatchUpSyncBlock,
+        700,
+        t2,
+    );
+    assert_eq!((start.time, start.epoch), (t1, 600));
+    assert_eq!(
+        SyncProgress::estimate_eta(t2 - start.time, start.epoch, 700, 1700),
+        Some(Duration::from_secs(100))
+    );
+}
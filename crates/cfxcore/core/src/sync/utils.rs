@@ -13,7 +13,7 @@ use cfx_parameters::{
     tx_pool::TXPOOL_DEFAULT_NONCE_BITS,
     WORKER_COMPUTATION_PARALLELISM,
 };
-use cfx_storage::{StorageConfiguration, StorageManager};
+use cfx_storage::{SnapshotDbBackend, StorageConfiguration, StorageManager};
 use cfx_types::{
     address_util::AddressUtil, Address, AddressSpaceUtil, AllChainID, H256,
     U256,
@@ -107,34 +107,45 @@ pub fn create_simple_block(
     )
 }
 
+/// With `DbType::Inmemory`, the storage is kept in memory too and nothing is
+/// written to `db_dir`.
 pub fn initialize_data_manager(
     db_dir: &str, dbtype: DbType, pow: Arc<PowComputer>, vm: VmFactory,
 ) -> (Arc<BlockDataManager>, Arc<Block>) {
-    let ledger_db = db::open_database(
-        db_dir,
-        &db::db_config(
-            Path::new(db_dir),
-            Some(128),
-            db::DatabaseCompactionProfile::default(),
-            NUM_COLUMNS,
-            false,
+    let ledger_db = match dbtype {
+        DbType::Rocksdb => Some(
+            db::open_database(
+                db_dir,
+                &db::db_config(
+                    Path::new(db_dir),
+                    Some(128),
+                    db::DatabaseCompactionProfile::default(),
+                    NUM_COLUMNS,
+                    false,
+                ),
+            )
+            .map_err(|e| format!("Failed to open database {:?}", e))
+            .unwrap(),
         ),
-    )
-    .map_err(|e| format!("Failed to open database {:?}", e))
-    .unwrap();
+        DbType::Sqlite | DbType::Inmemory => None,
+    };
 
     let worker_thread_pool = Arc::new(Mutex::new(ThreadPool::with_name(
         "Tx Recover".into(),
         WORKER_COMPUTATION_PARALLELISM,
     )));
 
+    let mut storage_conf = StorageConfiguration::new_default(
+        db_dir,
+        cfx_parameters::consensus::SNAPSHOT_EPOCHS_CAPACITY,
+        cfx_parameters::consensus::ERA_DEFAULT_EPOCH_COUNT,
+    );
+    if let DbType::Inmemory = dbtype {
+        storage_conf.snapshot_db_backend = SnapshotDbBackend::Inmemory;
+    }
     let storage_manager = Arc::new(
-        StorageManager::new(StorageConfiguration::new_default(
-            db_dir,
-            cfx_parameters::consensus::SNAPSHOT_EPOCHS_CAPACITY,
-            cfx_parameters::consensus::ERA_DEFAULT_EPOCH_COUNT,
-        ))
-        .expect("Failed to initialize storage."),
+        StorageManager::new(storage_conf)
+            .expect("Failed to initialize storage."),
     );
 
     let mut genesis_accounts = HashMap::new();
@@ -165,7 +176,7 @@ pub fn initialize_data_manager(
     let data_man = Arc::new(BlockDataManager::new(
         CacheConfig::default(),
         genesis_block.clone(),
-        ledger_db.clone(),
+        ledger_db,
         storage_manager,
         worker_thread_pool,
         DataManagerConfiguration::new(
@@ -278,6 +289,8 @@ pub fn initialize_synchronization_graph_with_data_manager(
         notifications.clone(),
         ConsensusExecutionConfiguration {
             executive_trace: false,
+            execution_witness: false,
+            parallel_execution_threads: None,
         },
         verification_config.clone(),
         NodeType::Archive,
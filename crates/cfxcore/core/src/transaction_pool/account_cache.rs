@@ -36,4 +36,20 @@ impl AccountCache {
     ) -> DbResult<bool> {
         self.state.check_contract_whitelist(contract_address, user)
     }
+
+    pub fn get_evm_sponsor_balance_and_bound(
+        &self, contract_address: &Address,
+    ) -> DbResult<(U256, U256)> {
+        Ok((
+            self.state.evm_sponsor_balance_for_gas(contract_address)?,
+            self.state.evm_sponsor_gas_bound(contract_address)?,
+        ))
+    }
+
+    pub fn check_evm_commission_privilege(
+        &self, contract_address: &Address, user: &Address,
+    ) -> DbResult<bool> {
+        self.state
+            .check_evm_contract_whitelist(contract_address, user)
+    }
 }
@@ -1384,7 +1384,9 @@ impl TransactionPoolInner {
                 }
                 Transaction::Ethereum(ref utx) => {
                     need_balance += utx.value().clone();
-                    need_balance += estimate_gas_fee;
+                    if sponsored_gas == U256::from(0) {
+                        need_balance += estimate_gas_fee;
+                    }
                 }
             }
 
@@ -1440,11 +1442,14 @@ impl TransactionPoolInner {
     ) -> StateDbResult<(U256, u64)> {
         let sender = transaction.sender();
 
-        // Filter out espace transactions
-        let utx = if let Transaction::Native(ref utx) = transaction.unsigned {
-            utx
-        } else {
-            return Ok(Default::default());
+        let utx = match transaction.unsigned {
+            Transaction::Native(ref utx) => utx,
+            Transaction::Ethereum(_) => {
+                return Ok((
+                    Self::get_evm_sponsored_gas(account_cache, transaction)?,
+                    0,
+                ));
+            }
         };
 
         // Keep contract call only
@@ -1497,6 +1502,42 @@ impl TransactionPoolInner {
 
         Ok((sponsored_gas, sponsored_collateral))
     }
+
+    /// eSpace contracts can only sponsor gas (CIP-147). The sponsor
+    /// information is empty before the activation.
+    fn get_evm_sponsored_gas(
+        account_cache: &AccountCache, transaction: &SignedTransaction,
+    ) -> StateDbResult<U256> {
+        let contract_address = match transaction.action() {
+            Action::Call(callee) => callee,
+            Action::Create => {
+                return Ok(Default::default());
+            }
+        };
+
+        let (sponsor_balance, gas_bound) = account_cache
+            .get_evm_sponsor_balance_and_bound(&contract_address)?;
+        if sponsor_balance.is_zero()
+            || !account_cache.check_evm_commission_privilege(
+                &contract_address,
+                &transaction.sender().address,
+            )?
+        {
+            return Ok(Default::default());
+        }
+
+        let estimated_gas = Self::estimated_gas_fee(
+            transaction.gas().clone(),
+            transaction.gas_price().clone(),
+        );
+        Ok(
+            if estimated_gas <= gas_bound && estimated_gas <= sponsor_balance {
+                transaction.gas().clone()
+            } else {
+                0.into()
+            },
+        )
+    }
 }
 
 #[cfg(test)]
@@ -128,6 +128,24 @@ impl<'a> EstimationContext<'a> {
                 }
             }
         }
+        // eSpace contracts can only sponsor gas (CIP-147).
+        if let (Transaction::Ethereum(_), SponsoredType::Gas) =
+            (&tx.unsigned, &ty)
+        {
+            if let Action::Call(to) = tx.action() {
+                let has_sponsor =
+                    self.state.evm_sponsor_for_gas(&to)?.is_some();
+                if has_sponsor
+                    && self.state.is_contract_with_code(&to.with_evm_space())?
+                    && self.state.check_evm_contract_whitelist(
+                        &to,
+                        &tx.sender().address,
+                    )?
+                {
+                    return Ok(Some(*to));
+                }
+            }
+        }
         Ok(None)
     }
 
@@ -325,10 +343,18 @@ impl<'a> EstimationContext<'a> {
             && executed.gas_sponsor_paid
         {
             let contract = gas_sponsored_contract_if_eligible_sender.unwrap();
-            let enough_balance = executed.fee
-                <= self.state.sponsor_balance_for_gas(&contract)?;
-            let enough_bound =
-                executed.fee <= self.state.sponsor_gas_bound(&contract)?;
+            let (sponsor_balance, gas_bound) = match tx.space() {
+                Space::Native => (
+                    self.state.sponsor_balance_for_gas(&contract)?,
+                    self.state.sponsor_gas_bound(&contract)?,
+                ),
+                Space::Ethereum => (
+                    self.state.evm_sponsor_balance_for_gas(&contract)?,
+                    self.state.evm_sponsor_gas_bound(&contract)?,
+                ),
+            };
+            let enough_balance = executed.fee <= sponsor_balance;
+            let enough_bound = executed.fee <= gas_bound;
             if !(enough_balance && enough_bound) {
                 debug!("Transaction estimate unset \"sponsor_paid\" because of not enough sponsor balance / gas bound.");
                 executed.gas_sponsor_paid = false;
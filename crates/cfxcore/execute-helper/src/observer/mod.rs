@@ -10,16 +10,17 @@ use cfx_executor::{
     machine::Machine,
 };
 use cfx_vm_tracer_derive::{AsTracer, DrainTrace};
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
-use alloy_rpc_types_trace::geth::GethDebugTracingOptions;
-use geth_tracer::{GethTracer, TxExecContext};
+use alloy_rpc_types_trace::{geth::GethDebugTracingOptions, parity::TraceType};
+use geth_tracer::{GethTracer, ParityTracer, TxExecContext};
 
 #[derive(AsTracer, DrainTrace)]
 pub struct Observer {
     pub tracer: Option<ExecTracer>,
     pub gas_man: Option<GasMan>,
     pub geth_tracer: Option<GethTracer>,
+    pub parity_tracer: Option<ParityTracer>,
 }
 
 impl Observer {
@@ -28,6 +29,7 @@ impl Observer {
             tracer: Some(ExecTracer::default()),
             gas_man: None,
             geth_tracer: None,
+            parity_tracer: None,
         }
     }
 
@@ -36,6 +38,7 @@ impl Observer {
             tracer: None,
             gas_man: None,
             geth_tracer: None,
+            parity_tracer: None,
         }
     }
 
@@ -44,6 +47,7 @@ impl Observer {
             tracer: Some(ExecTracer::default()),
             gas_man: Some(GasMan::default()),
             geth_tracer: None,
+            parity_tracer: None,
         }
     }
 
@@ -55,6 +59,23 @@ impl Observer {
             tracer: None,
             gas_man: None,
             geth_tracer: Some(GethTracer::new(tx_exec_context, machine, opts)),
+            parity_tracer: None,
+        }
+    }
+
+    pub fn parity_tracer(
+        tx_exec_context: TxExecContext, machine: Arc<Machine>,
+        trace_types: HashSet<TraceType>,
+    ) -> Self {
+        Observer {
+            tracer: None,
+            gas_man: None,
+            geth_tracer: None,
+            parity_tracer: Some(ParityTracer::new(
+                tx_exec_context,
+                machine,
+                trace_types,
+            )),
         }
     }
 }
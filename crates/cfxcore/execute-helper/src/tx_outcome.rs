@@ -7,7 +7,7 @@ use pow_types::StakingEvent;
 use primitives::Receipt;
 
 use alloy_rpc_types_trace::geth::GethTrace;
-use geth_tracer::GethTraceKey;
+use geth_tracer::{GethTraceKey, ParityTrace, ParityTraceKey};
 
 use super::{
     observer::exec_tracer::{ExecTrace, ExecTraceKey},
@@ -22,6 +22,7 @@ pub struct ProcessTxOutcome {
     pub tx_exec_error_msg: String,
     pub consider_repacked: bool,
     pub geth_trace: Option<GethTrace>,
+    pub parity_trace: Option<ParityTrace>,
 }
 
 fn tx_traces(outcome: &ExecutionOutcome) -> Vec<ExecTrace> {
@@ -37,12 +38,19 @@ fn geth_traces(outcome: &ExecutionOutcome) -> Option<GethTrace> {
         .and_then(|executed| executed.ext_result.get::<GethTraceKey>().cloned())
 }
 
+fn parity_traces(outcome: &ExecutionOutcome) -> Option<ParityTrace> {
+    outcome.try_as_executed().and_then(|executed| {
+        executed.ext_result.get::<ParityTraceKey>().cloned()
+    })
+}
+
 pub fn make_process_tx_outcome(
     outcome: ExecutionOutcome, accumulated_gas_used: &mut U256, tx_hash: H256,
     spec: &Spec,
 ) -> ProcessTxOutcome {
     let tx_traces = tx_traces(&outcome);
     let geth_trace = geth_traces(&outcome);
+    let parity_trace = parity_traces(&outcome);
     let tx_exec_error_msg = outcome.error_message();
     let consider_repacked = outcome.consider_repacked();
     let receipt = outcome.make_receipt(accumulated_gas_used, spec);
@@ -59,5 +67,6 @@ pub fn make_process_tx_outcome(
         tx_exec_error_msg,
         consider_repacked,
         geth_trace,
+        parity_trace,
     }
 }
@@ -176,6 +176,7 @@ impl<'a> ContextTrait for Context<'a> {
         if self.is_static_or_reentrancy() {
             Err(vm::Error::MutableCallInStaticContext)
         } else {
+            self.tracer.trace_storage_write(&receiver, &key, &value);
             self.state
                 .set_storage(
                     &receiver,
@@ -0,0 +1,418 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Runner of the Ethereum state tests against eSpace.
+//!
+//! The fixtures in the format of the `GeneralStateTests` filled by
+//! `ethereum/tests` and `ethereum/execution-spec-tests` are read from
+//! `tests/res/GeneralStateTests` in this crate, or from the directory in the
+//! `ETH_STATE_TESTS` environment variable, e.g. a checkout of the whole
+//! upstream suite. Every JSON file there must be a state test fixture.
+//! The vendored fixtures leave out the state root hashes and the
+//! transaction bytes, which the runner doesn't read.
+//!
+//! Only the `Cancun` post states are checked. The state trie of Conflux is
+//! not the Ethereum one and eSpace charges gas to the sender and the miner
+//! differently, so the post state root can't be compared. Instead the runner
+//! compares the logs hash, and the nonce, code and storage of the accounts in
+//! the expected post state, as well as their balance except for the sender
+//! and the coinbase.
+
+use super::{ExecutiveContext, TransactOptions};
+use crate::{
+    machine::{Machine, VmFactory},
+    state::{get_state_for_genesis_write, DevStateChange, State},
+};
+use cfx_bytes::Bytes;
+use cfx_types::{
+    Address, AddressSpaceUtil, AddressWithSpace, BigEndianHash, Space,
+    SpaceMap, H256, U256,
+};
+use cfx_vm_types::Env;
+use cfxkey::Secret;
+use keccak_hash::keccak;
+use primitives::{
+    transaction::{
+        Action, Eip1559Transaction, Eip155Transaction, Eip2930Transaction,
+        EthereumTransaction,
+    },
+    AccessList, LogEntry, Transaction,
+};
+use rlp::RlpStream;
+use rustc_hex::FromHex;
+use serde::{Deserialize, Deserializer};
+use serde_derive::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+const FORK: &str = "Cancun";
+
+/// Chain id used by the fixtures.
+const CHAIN_ID: u32 = 1;
+
+struct HexBytes(Bytes);
+
+impl<'de> Deserialize<'de> for HexBytes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let hex = String::deserialize(deserializer)?;
+        let bytes = hex
+            .trim_start_matches("0x")
+            .from_hex()
+            .map_err(serde::de::Error::custom)?;
+        Ok(HexBytes(bytes))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StateTest {
+    env: TestEnv,
+    pre: BTreeMap<Address, TestAccount>,
+    transaction: TestTransaction,
+    post: BTreeMap<String, Vec<PostState>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TestEnv {
+    current_coinbase: Address,
+    current_gas_limit: U256,
+    current_number: U256,
+    current_timestamp: U256,
+    current_difficulty: Option<U256>,
+    current_random: Option<H256>,
+    current_base_fee: Option<U256>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TestAccount {
+    balance: U256,
+    code: HexBytes,
+    nonce: U256,
+    #[serde(default)]
+    storage: BTreeMap<U256, U256>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TestTransaction {
+    data: Vec<HexBytes>,
+    gas_limit: Vec<U256>,
+    value: Vec<U256>,
+    to: String,
+    nonce: U256,
+    secret_key: H256,
+    gas_price: Option<U256>,
+    max_fee_per_gas: Option<U256>,
+    max_priority_fee_per_gas: Option<U256>,
+    access_lists: Option<Vec<Option<AccessList>>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PostState {
+    logs: H256,
+    indexes: PostIndexes,
+    expect_exception: Option<String>,
+    state: Option<BTreeMap<Address, TestAccount>>,
+}
+
+#[derive(Deserialize)]
+struct PostIndexes {
+    data: usize,
+    gas: usize,
+    value: usize,
+}
+
+impl TestTransaction {
+    fn build(&self, indexes: &PostIndexes) -> Result<Transaction, String> {
+        let action = if self.to.is_empty() {
+            Action::Create
+        } else {
+            Action::Call(self.to.parse().map_err(|e| format!("{:?}", e))?)
+        };
+        let data = self.data.get(indexes.data).ok_or("data index")?.0.clone();
+        let gas = *self.gas_limit.get(indexes.gas).ok_or("gas index")?;
+        let value = *self.value.get(indexes.value).ok_or("value index")?;
+        let access_list = self
+            .access_lists
+            .as_ref()
+            .and_then(|lists| lists.get(indexes.data).cloned().flatten());
+
+        let transaction = match (self.max_fee_per_gas, access_list) {
+            (Some(max_fee_per_gas), access_list) => {
+                EthereumTransaction::Eip1559(Eip1559Transaction {
+                    chain_id: CHAIN_ID,
+                    nonce: self.nonce,
+                    max_priority_fee_per_gas: self
+                        .max_priority_fee_per_gas
+                        .unwrap_or_default(),
+                    max_fee_per_gas,
+                    gas,
+                    action,
+                    value,
+                    data,
+                    access_list: access_list.unwrap_or_default(),
+                })
+            }
+            (None, Some(access_list)) => {
+                EthereumTransaction::Eip2930(Eip2930Transaction {
+                    chain_id: CHAIN_ID,
+                    nonce: self.nonce,
+                    gas_price: self.gas_price.unwrap_or_default(),
+                    gas,
+                    action,
+                    value,
+                    data,
+                    access_list,
+                })
+            }
+            (None, None) => EthereumTransaction::Eip155(Eip155Transaction {
+                nonce: self.nonce,
+                gas_price: self.gas_price.unwrap_or_default(),
+                gas,
+                action,
+                value,
+                chain_id: Some(CHAIN_ID),
+                data,
+            }),
+        };
+        Ok(Transaction::Ethereum(transaction))
+    }
+}
+
+impl TestEnv {
+    fn to_env(&self) -> Env {
+        let base_fee = self.current_base_fee.unwrap_or_default();
+        let mut env = Env::default();
+        env.chain_id.insert(Space::Native, CHAIN_ID);
+        env.chain_id.insert(Space::Ethereum, CHAIN_ID);
+        env.number = self.current_number.as_u64();
+        env.epoch_height = env.number;
+        env.author = self.current_coinbase;
+        env.timestamp = self.current_timestamp.as_u64();
+        env.difficulty = match self.current_random {
+            Some(random) => random.into_uint(),
+            None => self.current_difficulty.unwrap_or_default(),
+        };
+        env.gas_limit = self.current_gas_limit;
+        env.base_gas_price = SpaceMap::new(U256::zero(), base_fee);
+        env.burnt_gas_price = SpaceMap::new(U256::zero(), base_fee);
+        env
+    }
+}
+
+fn evm_address(address: &Address) -> AddressWithSpace {
+    address.with_evm_space()
+}
+
+fn storage_key(key: &U256) -> Vec<u8> {
+    H256::from_uint(key).as_bytes().to_vec()
+}
+
+fn prepare_state(pre: &BTreeMap<Address, TestAccount>) -> State {
+    let mut state = get_state_for_genesis_write();
+    for (address, account) in pre {
+        let address = evm_address(address);
+        let mut changes = vec![];
+        if !account.code.0.is_empty() {
+            changes.push(DevStateChange::Code {
+                address,
+                code: account.code.0.clone(),
+            });
+        }
+        changes.push(DevStateChange::Balance {
+            address,
+            balance: account.balance,
+        });
+        for (key, value) in &account.storage {
+            changes.push(DevStateChange::Storage {
+                address,
+                key: H256::from_uint(key),
+                value: *value,
+            });
+        }
+        for change in &changes {
+            state.apply_dev_state_change(change).expect("no db error");
+        }
+        state
+            .set_nonce(&address, &account.nonce)
+            .expect("no db error");
+    }
+    state
+}
+
+/// The logs hash of the fixtures, the hash of the rlp list of logs without
+/// the space.
+fn logs_hash(logs: &[LogEntry]) -> H256 {
+    let mut s = RlpStream::new_list(logs.len());
+    for log in logs {
+        s.begin_list(3);
+        s.append(&log.address);
+        s.append_list(&log.topics);
+        s.append(&log.data);
+    }
+    keccak(s.out())
+}
+
+fn check_account(
+    state: &State, address: &Address, expected: &TestAccount,
+    pre: Option<&TestAccount>, check_balance: bool,
+) -> Result<(), String> {
+    let with_space = evm_address(address);
+    let nonce = state.nonce(&with_space).expect("no db error");
+    if nonce != expected.nonce {
+        return Err(format!(
+            "{:?}: nonce {} != {}",
+            address, nonce, expected.nonce
+        ));
+    }
+    let balance = state.balance(&with_space).expect("no db error");
+    if check_balance && balance != expected.balance {
+        return Err(format!(
+            "{:?}: balance {} != {}",
+            address, balance, expected.balance
+        ));
+    }
+    let code = state.code(&with_space).expect("no db error");
+    let code = code.as_ref().map_or(&[][..], |code| &code[..]);
+    if code != &expected.code.0[..] {
+        return Err(format!("{:?}: code mismatch", address));
+    }
+    // Storage entries of the pre state missing in the post state are cleared.
+    let keys: BTreeSet<&U256> = expected
+        .storage
+        .keys()
+        .chain(pre.into_iter().flat_map(|pre| pre.storage.keys()))
+        .collect();
+    for key in keys {
+        let value = state
+            .storage_at(&with_space, &storage_key(key))
+            .expect("no db error");
+        let expected_value =
+            expected.storage.get(key).cloned().unwrap_or_default();
+        if value != expected_value {
+            return Err(format!(
+                "{:?}: storage {:#x} {:#x} != {:#x}",
+                address, key, value, expected_value
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn run_post_state(
+    machine: &Machine, test: &StateTest, post: &PostState,
+) -> Result<(), String> {
+    let mut state = prepare_state(&test.pre);
+    let env = test.env.to_env();
+    let spec = machine.spec(env.number, env.epoch_height);
+
+    let secret = Secret::from(test.transaction.secret_key);
+    let tx = test.transaction.build(&post.indexes)?.sign(&secret);
+    let sender = tx.sender().address;
+
+    let outcome = ExecutiveContext::new(&mut state, &env, machine, &spec)
+        .transact(&tx, TransactOptions::default())
+        .map_err(|e| format!("db error {:?}", e))?;
+
+    match (&post.expect_exception, outcome.try_as_executed()) {
+        (Some(exception), Some(_)) => {
+            return Err(format!("expected exception {}", exception));
+        }
+        (Some(_), None) => return Ok(()),
+        (None, None) => {
+            return Err(format!("not executed: {:?}", outcome));
+        }
+        (None, Some(_)) => {}
+    }
+
+    let logs_hash = logs_hash(&outcome.transaction_logs());
+    if logs_hash != post.logs {
+        return Err(format!("logs hash {:?} != {:?}", logs_hash, post.logs));
+    }
+
+    for (address, expected) in post.state.iter().flatten() {
+        let check_balance =
+            *address != sender && *address != test.env.current_coinbase;
+        check_account(
+            &state,
+            address,
+            expected,
+            test.pre.get(address),
+            check_balance,
+        )?;
+    }
+    Ok(())
+}
+
+fn collect_fixtures(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = fs::read_dir(dir).unwrap_or_else(|e| {
+        panic!("failed to read state tests in {}: {}", dir.display(), e)
+    });
+    for entry in entries {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_fixtures(&path, files);
+        } else if path.extension().map_or(false, |ext| ext == "json") {
+            files.push(path);
+        }
+    }
+}
+
+#[test]
+fn eth_state_tests() {
+    let dir = match env::var("ETH_STATE_TESTS") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/res/GeneralStateTests"),
+    };
+    let mut files = vec![];
+    collect_fixtures(&dir, &mut files);
+    files.sort();
+
+    let machine = Machine::new_with_builtin(
+        Default::default(),
+        VmFactory::new(1024 * 32),
+    );
+    let mut executed = 0;
+    let mut failures = vec![];
+    for file in files {
+        let content = fs::read_to_string(&file).unwrap();
+        let tests: BTreeMap<String, StateTest> = serde_json::from_str(&content)
+            .unwrap_or_else(|e| {
+                panic!("invalid state test {}: {}", file.display(), e)
+            });
+        for (name, test) in &tests {
+            for (index, post) in
+                test.post.get(FORK).into_iter().flatten().enumerate()
+            {
+                executed += 1;
+                if let Err(e) = run_post_state(&machine, test, post) {
+                    failures.push(format!(
+                        "{}: {}[{}]: {}",
+                        file.display(),
+                        name,
+                        index,
+                        e
+                    ));
+                }
+            }
+        }
+    }
+
+    assert!(executed > 0, "no {} state test in {}", FORK, dir.display());
+    assert!(
+        failures.is_empty(),
+        "{} of {} state tests failed:\n{}",
+        failures.len(),
+        executed,
+        failures.join("\n")
+    );
+}
@@ -149,6 +149,16 @@ impl ExecutionOutcome {
         }
     }
 
+    #[inline]
+    pub fn try_as_executed_mut(&mut self) -> Option<&mut Executed> {
+        match self {
+            NotExecutedDrop(_) | NotExecutedToReconsiderPacking(_) => None,
+            ExecutionErrorBumpNonce(_, executed) | Finished(executed) => {
+                Some(executed)
+            }
+        }
+    }
+
     #[inline]
     pub fn gas_fee(&self) -> U256 {
         let executed = unwrap_or_return!(self.try_as_executed());
@@ -237,7 +237,57 @@ impl<'a, O: ExecutiveObserver> FreshExecutive<'a, O> {
 
         if sender.space == Space::Ethereum {
             assert_eq!(storage_cost, U256::zero());
-            let sender_cost = U512::from(tx.value()) + gas_cost;
+
+            // Check if the eSpace contract will pay transaction fee for the
+            // sender (CIP-147).
+            let mut gas_sponsor_eligible = false;
+            let mut sponsor_balance_for_gas = U512::zero();
+            if let (true, Action::Call(ref address)) =
+                (spec.cip147, tx.action())
+            {
+                if state.is_contract_with_code(&address.with_evm_space())?
+                    && state.check_evm_contract_whitelist(
+                        address,
+                        &sender.address,
+                    )?
+                {
+                    gas_sponsor_eligible = gas_cost
+                        <= U512::from(state.evm_sponsor_gas_bound(address)?);
+                    sponsor_balance_for_gas =
+                        U512::from(state.evm_sponsor_balance_for_gas(address)?);
+                }
+            }
+            let gas_sponsored =
+                gas_sponsor_eligible && sponsor_balance_for_gas >= gas_cost;
+
+            let sender_intended_cost = if gas_sponsor_eligible {
+                U512::from(tx.value())
+            } else {
+                U512::from(tx.value()) + gas_cost
+            };
+            let total_cost = if gas_sponsored {
+                U512::from(tx.value())
+            } else {
+                U512::from(tx.value()) + gas_cost
+            };
+
+            // The same as Core Space, the sender is not responsible for the
+            // sponsor running out of balance.
+            if sender_balance >= sender_intended_cost
+                && sender_balance < total_cost
+            {
+                return Ok(Err(
+                    ExecutionOutcome::NotExecutedToReconsiderPacking(
+                        ToRepackError::NotEnoughCashFromSponsor {
+                            required_gas_cost: gas_cost,
+                            gas_sponsor_balance: sponsor_balance_for_gas,
+                            required_storage_cost: storage_cost,
+                            storage_sponsor_balance: U256::zero(),
+                        },
+                    ),
+                ));
+            }
+
             return Ok(Ok(CostInfo {
                 sender_balance,
                 base_gas: self.base_gas,
@@ -245,9 +295,9 @@ impl<'a, O: ExecutiveObserver> FreshExecutive<'a, O> {
                 gas_price,
                 burnt_gas_price,
                 storage_cost,
-                sender_intended_cost: sender_cost,
-                total_cost: sender_cost,
-                gas_sponsored: false,
+                sender_intended_cost,
+                total_cost,
+                gas_sponsored,
                 storage_sponsored: false,
                 storage_sponsor_eligible: false,
             }));
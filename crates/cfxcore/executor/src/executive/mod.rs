@@ -1,9 +1,12 @@
 // Copyright 2019 Conflux Foundation. All rights reserved.
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
+#[cfg(test)]
+mod eth_state_tests;
 pub mod executed;
 pub mod execution_outcome;
 mod fresh_executive;
+mod parallel;
 mod pre_checked_executive;
 #[cfg(test)]
 mod tests;
@@ -22,6 +25,9 @@ use pre_checked_executive::PreCheckedExecutive;
 
 pub use executed::{string_revert_reason_decode, Executed};
 pub use execution_outcome::{ExecutionError, ExecutionOutcome, TxDropError};
+pub use parallel::{
+    execute_in_parallel, ForkDbPool, ForkDbs, ParallelExecutionStats,
+};
 pub use transact_options::{
     ChargeCollateral, TransactOptions, TransactSettings,
 };
@@ -0,0 +1,160 @@
+//! Optimistic parallel execution of the transactions in a block.
+//!
+//! All the transactions are first executed speculatively in parallel, each on
+//! its own fork of the state. The results are then merged in the transaction
+//! order. A transaction whose fork has observed anything changed by an earlier
+//! transaction of the block is executed again on a fork of the merged state,
+//! so the outcomes and the final state are identical to executing the
+//! transactions one by one.
+
+use crate::state::{Speculation, SpeculativeChanges, State};
+use cfx_statedb::{Result as DbResult, StateDbGeneric as StateDb};
+use cfx_types::{H256, U256};
+use parking_lot::Mutex;
+use rayon::{prelude::*, ThreadPool};
+
+/// Databases for the forks, which are views of the committed state of an
+/// executed epoch. They are opened on demand and reused as long as the same
+/// state is forked, so one pool serves all the epochs.
+#[derive(Default)]
+pub struct ForkDbPool {
+    // The state the idle databases are views of.
+    idle: Mutex<(H256, Vec<StateDb>)>,
+}
+
+impl ForkDbPool {
+    pub fn new() -> Self { Default::default() }
+
+    /// The databases forking the state `state_id`, which are opened with
+    /// `open`. The idle databases of other states are dropped.
+    pub fn fork<F>(&self, state_id: H256, open: F) -> ForkDbs<'_, F> {
+        let mut idle = self.idle.lock();
+        if idle.0 != state_id {
+            *idle = (state_id, vec![]);
+        }
+        ForkDbs {
+            pool: Some(self),
+            state_id,
+            open,
+        }
+    }
+
+    /// The number of idle databases.
+    pub fn num_idle(&self) -> usize { self.idle.lock().1.len() }
+}
+
+/// The databases of a [`ForkDbPool`] forking one state.
+pub struct ForkDbs<'a, F> {
+    pool: Option<&'a ForkDbPool>,
+    state_id: H256,
+    open: F,
+}
+
+impl<F> ForkDbs<'_, F> {
+    /// Databases which are opened with `open` for each fork and dropped
+    /// afterwards, for the views of a state which can't be shared.
+    pub fn unpooled(open: F) -> Self {
+        ForkDbs {
+            pool: None,
+            state_id: H256::zero(),
+            open,
+        }
+    }
+}
+
+impl<F: Fn() -> DbResult<StateDb>> ForkDbs<'_, F> {
+    pub fn take(&self) -> DbResult<StateDb> {
+        let db = self.pool.and_then(|pool| {
+            let mut idle = pool.idle.lock();
+            if idle.0 == self.state_id {
+                idle.1.pop()
+            } else {
+                None
+            }
+        });
+        match db {
+            Some(db) => Ok(db),
+            None => (self.open)(),
+        }
+    }
+
+    /// Return a database to the pool, unless another state has been forked
+    /// since.
+    pub fn put(&self, db: StateDb) {
+        if let Some(pool) = self.pool {
+            let mut idle = pool.idle.lock();
+            if idle.0 == self.state_id {
+                idle.1.push(db);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ParallelExecutionStats {
+    pub transactions: usize,
+    /// The transactions executed again after the speculative execution.
+    pub reexecuted: usize,
+}
+
+/// Execute `num_txs` transactions on `state` with `execute`, which is called
+/// with the transaction index. The CIP-1559 fee returned by `burnt_fee` is
+/// burnt after each transaction is merged, like the sequential execution does.
+///
+/// `execute` may be called more than once for a transaction, and only its
+/// last output is returned.
+pub fn execute_in_parallel<O, F, E, B>(
+    state: &mut State, num_txs: usize, pool: &ThreadPool,
+    fork_dbs: &ForkDbs<F>, execute: E, burnt_fee: B,
+) -> DbResult<(Vec<O>, ParallelExecutionStats)>
+where
+    O: Send,
+    F: Fn() -> DbResult<StateDb> + Sync,
+    E: Fn(&mut State, usize) -> DbResult<O> + Sync,
+    B: Fn(&O) -> Option<U256>,
+{
+    let mut speculation = state.speculate();
+
+    let run = |speculation: &Speculation,
+               index: usize|
+     -> DbResult<(O, SpeculativeChanges)> {
+        let mut fork = speculation.fork(fork_dbs.take()?);
+        let output = execute(&mut fork, index)?;
+        let (changes, db) = fork.into_speculative_changes();
+        fork_dbs.put(db);
+        Ok((output, changes))
+    };
+
+    let speculative: Vec<_> = pool.install(|| {
+        (0..num_txs)
+            .into_par_iter()
+            .map(|index| run(&speculation, index).ok())
+            .collect()
+    });
+
+    let mut stats = ParallelExecutionStats {
+        transactions: num_txs,
+        reexecuted: 0,
+    };
+    let mut outputs = Vec::with_capacity(num_txs);
+    for (index, result) in speculative.into_iter().enumerate() {
+        // A failed speculative execution is retried, the error is returned
+        // only if it happens again on the merged state.
+        let (output, changes) = match result {
+            Some((output, changes)) if speculation.validate(&changes) => {
+                (output, changes)
+            }
+            _ => {
+                stats.reexecuted += 1;
+                run(&speculation, index)?
+            }
+        };
+        speculation.merge(changes);
+        if let Some(fee) = burnt_fee(&output) {
+            speculation.burn_by_cip1559(fee);
+        }
+        outputs.push(output);
+    }
+
+    Ok((outputs, stats))
+}
@@ -127,15 +127,29 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
                 Action::Create => Address::zero(),
                 Action::Call(ref address) => *address,
             };
-            tracer.trace_internal_transfer(
-                AddressPocket::SponsorBalanceForGas(code_address),
-                AddressPocket::GasPayment,
-                actual_gas_cost,
-            );
-
-            self.context
-                .state
-                .sub_sponsor_balance_for_gas(&code_address, &actual_gas_cost)?;
+            if self.tx.space() == Space::Ethereum {
+                tracer.trace_internal_transfer(
+                    AddressPocket::EvmSponsorBalanceForGas(code_address),
+                    AddressPocket::GasPayment,
+                    actual_gas_cost,
+                );
+                self.context.state.sub_evm_sponsor_balance_for_gas(
+                    &code_address,
+                    &actual_gas_cost,
+                    sender.address,
+                    &mut self.substate,
+                )?;
+            } else {
+                tracer.trace_internal_transfer(
+                    AddressPocket::SponsorBalanceForGas(code_address),
+                    AddressPocket::GasPayment,
+                    actual_gas_cost,
+                );
+                self.context.state.sub_sponsor_balance_for_gas(
+                    &code_address,
+                    &actual_gas_cost,
+                )?;
+            }
         }
 
         // Don't subtract total_evm_balance here. It is maintained properly in
@@ -436,6 +450,40 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
             }
         }
 
+        // Refund the eSpace sponsor and clear the sponsorship as in Core
+        // Space, so a contract recreated at the same address starts afresh.
+        if spec.cip147 {
+            for contract_address in parent_substate
+                .suicides
+                .iter()
+                .filter(|x| x.space == Space::Ethereum)
+                .map(|x| &x.address)
+            {
+                if let Some(sponsor) =
+                    state.evm_sponsor_for_gas(contract_address)?
+                {
+                    let sponsor_balance_for_gas =
+                        state.evm_sponsor_balance_for_gas(contract_address)?;
+                    tracer.trace_internal_transfer(
+                        AddressPocket::EvmSponsorBalanceForGas(
+                            *contract_address,
+                        ),
+                        AddressPocket::Balance(sponsor.with_evm_space()),
+                        sponsor_balance_for_gas,
+                    );
+                    state.add_balance(
+                        &sponsor.with_evm_space(),
+                        &sponsor_balance_for_gas,
+                        cleanup_mode(&mut substate, spec),
+                    )?;
+                }
+                state.clear_evm_sponsor_entries(
+                    contract_address,
+                    &mut substate,
+                )?;
+            }
+        }
+
         for contract_address in &parent_substate.suicides {
             if contract_address.space == Space::Native {
                 let contract_address = contract_address.address;
@@ -513,7 +561,19 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
         let state = &mut context.state;
         let mut tracer = self.observer.as_tracer();
 
-        if cost.gas_sponsored {
+        if cost.gas_sponsored && self.tx.space() == Space::Ethereum {
+            tracer.trace_internal_transfer(
+                AddressPocket::GasPayment,
+                AddressPocket::EvmSponsorBalanceForGas(params.code_address),
+                refund_value.clone(),
+            );
+            state.add_evm_sponsor_balance_for_gas(
+                &params.code_address,
+                &refund_value,
+                params.storage_owner,
+                &mut self.substate,
+            )?;
+        } else if cost.gas_sponsored {
             tracer.trace_internal_transfer(
                 AddressPocket::GasPayment,
                 AddressPocket::SponsorBalanceForGas(params.code_address),
@@ -4,15 +4,21 @@
 
 use super::*;
 use crate::{
+    internal_contract::core_map,
     machine::{Machine, VmFactory},
     state::{get_state_by_epoch_id, get_state_for_genesis_write, CleanupMode},
     substate::Substate,
 };
 use cfx_internal_common::debug::ComputeEpochDebugRecord;
 use cfx_parameters::{
-    internal_contract_addresses::STORAGE_INTEREST_STAKING_CONTRACT_ADDRESS,
+    internal_contract_addresses::{
+        CROSS_SPACE_CONTRACT_ADDRESS,
+        SPONSOR_WHITELIST_CONTROL_CONTRACT_ADDRESS,
+        STORAGE_INTEREST_STAKING_CONTRACT_ADDRESS,
+    },
     staking::*,
 };
+use cfx_statedb::StateDb;
 use cfx_types::{
     address_util::AddressUtil, Address, AddressSpaceUtil, BigEndianHash, U256,
     U512,
@@ -22,13 +28,17 @@ use cfx_vm_types::{
     self as vm, ActionParams, ActionValue, CallType, CreateContractAddress,
     CreateType, Env,
 };
-use cfxkey::{Generator, Random};
+use cfxkey::{Generator, KeyPair, Random};
 use primitives::{
     storage::STORAGE_LAYOUT_REGULAR_V0,
-    transaction::{native_transaction::NativeTransaction, Action},
+    transaction::{
+        native_transaction::NativeTransaction, Action, Eip155Transaction,
+        EthereumTransaction,
+    },
     EpochId, Transaction,
 };
 use rustc_hex::FromHex;
+use solidity_abi::ABIEncodable;
 use std::{
     cmp::{self, min},
     str::FromStr,
@@ -2240,3 +2250,583 @@ fn test_push0() {
         assert!(matches!(error, vm::Error::BadInstruction { .. }));
     }
 }
+
+// Increment the slot 0.
+const COUNTER_CODE: &str = "60005460010160005500";
+// Increment the slot of the caller.
+const CALLER_COUNTER_CODE: &str = "3354600101335500";
+// Clear the slot of the caller.
+const CALLER_CLEAR_CODE: &str = "6000335500";
+// Increment the slot 0, and revert if it reaches 3.
+const REVERTING_COUNTER_CODE: &str =
+    "60005460010180600055600311601457600080fd5b00";
+
+/// Fund `keys` with `balances` and deploy `contracts` in the state of a new
+/// epoch.
+fn make_parallel_test_state(
+    keys: &[KeyPair], balances: &[u64], contracts: &[(Address, &str)],
+) -> EpochId {
+    let mut state = get_state_for_genesis_write();
+    for (key, balance) in keys.iter().zip(balances) {
+        state
+            .add_balance(
+                &key.address().with_native_space(),
+                &U256::from(*balance),
+                CleanupMode::NoEmpty,
+            )
+            .unwrap();
+    }
+    for (address, code) in contracts {
+        let address = address.with_native_space();
+        state
+            .new_contract_with_admin(
+                &address,
+                &Address::zero(),
+                U256::zero(),
+                Some(STORAGE_LAYOUT_REGULAR_V0),
+                false,
+            )
+            .unwrap();
+        state
+            .init_code(&address, code.from_hex().unwrap(), Address::zero())
+            .unwrap();
+    }
+    let epoch_id = EpochId::from_uint(&U256::from(1));
+    state.commit_for_test(epoch_id).unwrap();
+    epoch_id
+}
+
+fn contract_address_for_test(index: u64) -> Address {
+    let mut address = Address::from_low_u64_be(0x1000 + index);
+    address.set_contract_type_bits();
+    address
+}
+
+/// Signs the transactions of a test, keeping track of the nonces.
+struct TestTxSigner {
+    keys: Vec<KeyPair>,
+    nonces: Vec<u64>,
+}
+
+impl TestTxSigner {
+    fn new(keys: Vec<KeyPair>) -> Self {
+        let nonces = vec![0; keys.len()];
+        TestTxSigner { keys, nonces }
+    }
+
+    fn sign(
+        &mut self, sender: usize, action: Action, value: u64, data: Vec<u8>,
+    ) -> SignedTransaction {
+        let nonce = self.nonces[sender];
+        self.nonces[sender] += 1;
+        Transaction::from(NativeTransaction {
+            nonce: nonce.into(),
+            gas_price: U256::from(1),
+            gas: U256::from(300_000),
+            value: U256::from(value),
+            action,
+            storage_limit: 1024,
+            epoch_height: 0,
+            chain_id: 1,
+            data,
+        })
+        .sign(self.keys[sender].secret())
+    }
+
+    fn call(&mut self, sender: usize, contract: Address) -> SignedTransaction {
+        self.sign(sender, Action::Call(contract), 0, vec![])
+    }
+}
+
+/// Execute `txs` on the state of `epoch_id` both sequentially and in
+/// parallel, check that the outcomes and the resulting states are the same,
+/// and return the statistics of the parallel execution.
+fn assert_parallel_matches_sequential(
+    epoch_id: &EpochId, txs: &[SignedTransaction],
+) -> ParallelExecutionStats {
+    let mut env = Env::default();
+    env.gas_limit = U256::from(10_000_000);
+    let machine = make_byzantium_machine(16);
+    let spec = machine.spec_for_test(env.number);
+
+    let execute = |state: &mut State, tx: &SignedTransaction| {
+        ExecutiveContext::new(state, &env, &machine, &spec)
+            .transact(tx, TransactOptions::default())
+    };
+    let burnt_fee = |outcome: &ExecutionOutcome| {
+        outcome.try_as_executed().and_then(|e| e.burnt_fee)
+    };
+
+    let mut state = get_state_by_epoch_id(epoch_id);
+    let mut sequential_outcomes = vec![];
+    for tx in txs {
+        let outcome = execute(&mut state, tx).unwrap();
+        if let Some(fee) = burnt_fee(&outcome) {
+            state.burn_by_cip1559(fee);
+        }
+        sequential_outcomes.push(format!("{:?}", outcome));
+    }
+    let sequential_root = state.compute_state_root_for_genesis(None).unwrap();
+
+    // The in-memory test storage is only visible to the current thread, so
+    // the databases of the forks are opened in advance.
+    let dbs = parking_lot::Mutex::new(
+        (0..2 * txs.len())
+            .map(|_| StateDb::new_for_unit_test_with_epoch(epoch_id))
+            .collect::<Vec<_>>(),
+    );
+    let fork_db_pool = ForkDbPool::new();
+    let fork_dbs = fork_db_pool.fork(*epoch_id, || {
+        Ok(dbs.lock().pop().expect("Enough databases"))
+    });
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+    let mut state = get_state_by_epoch_id(epoch_id);
+    let (outcomes, stats) = execute_in_parallel(
+        &mut state,
+        txs.len(),
+        &pool,
+        &fork_dbs,
+        |state, index| execute(state, &txs[index]),
+        burnt_fee,
+    )
+    .unwrap();
+    let parallel_outcomes: Vec<_> = outcomes
+        .iter()
+        .map(|outcome| format!("{:?}", outcome))
+        .collect();
+    let parallel_root = state.compute_state_root_for_genesis(None).unwrap();
+
+    assert_eq!(parallel_outcomes, sequential_outcomes);
+    assert_eq!(parallel_root, sequential_root);
+    assert_eq!(stats.transactions, txs.len());
+    stats
+}
+
+#[test]
+fn test_parallel_execution_matches_sequential() {
+    let keys: Vec<_> = (0..8).map(|_| Random.generate().unwrap()).collect();
+    let receiver = Random.generate().unwrap().address();
+    let epoch_id =
+        make_parallel_test_state(&keys, &[1_000_000_000_000_000_000; 8], &[]);
+
+    let mut signer = TestTxSigner::new(keys.clone());
+    // Independent transfers, transfers to the same account, a sender funded
+    // by an earlier transaction, consecutive transactions of a sender, and
+    // contract creations occupying storage.
+    let mut txs = vec![];
+    for i in 0..keys.len() {
+        txs.push(signer.sign(i, Action::Call(receiver), 1_000_000, vec![]));
+    }
+    for i in 0..3 {
+        let to = keys[i + 1].address();
+        txs.push(signer.sign(i, Action::Call(to), 1_000_000, vec![]));
+    }
+    for i in 4..keys.len() {
+        let code = "3331600055".from_hex().unwrap();
+        txs.push(signer.sign(i, Action::Create, 1_000_000, code));
+    }
+    txs.push(signer.sign(
+        1,
+        Action::Call(keys[7].address()),
+        1_000_000,
+        vec![],
+    ));
+
+    let stats = assert_parallel_matches_sequential(&epoch_id, &txs);
+    // The transfers to `receiver` conflict with each other.
+    assert!(stats.reexecuted >= keys.len() - 1);
+}
+
+#[test]
+fn test_parallel_execution_same_slot_writes() {
+    let keys: Vec<_> = (0..8).map(|_| Random.generate().unwrap()).collect();
+    let counter = contract_address_for_test(0);
+    let epoch_id = make_parallel_test_state(
+        &keys,
+        &[1_000_000_000_000_000_000; 8],
+        &[(counter, COUNTER_CODE)],
+    );
+
+    // Every transaction reads and writes the same slot, and takes over its
+    // storage collateral from the previous writer.
+    let mut signer = TestTxSigner::new(keys.clone());
+    let mut txs = vec![];
+    for round in 0..2 {
+        for i in 0..keys.len() {
+            txs.push(signer.call((i + round) % keys.len(), counter));
+        }
+    }
+
+    let stats = assert_parallel_matches_sequential(&epoch_id, &txs);
+    assert_eq!(stats.reexecuted, txs.len() - 1);
+}
+
+#[test]
+fn test_parallel_execution_storage_collateral() {
+    let keys: Vec<_> = (0..6).map(|_| Random.generate().unwrap()).collect();
+    let counter = contract_address_for_test(0);
+    let clear = contract_address_for_test(1);
+    // The last sender can pay the gas but not the collateral of a slot, so
+    // its writes fail unless it is funded by an earlier transaction.
+    let mut balances = vec![1_000_000_000_000_000_000; keys.len()];
+    *balances.last_mut().unwrap() = 1_000_000_000_000;
+    let epoch_id = make_parallel_test_state(
+        &keys,
+        &balances,
+        &[(counter, CALLER_COUNTER_CODE), (clear, CALLER_CLEAR_CODE)],
+    );
+
+    let poor = keys.len() - 1;
+    let mut signer = TestTxSigner::new(keys.clone());
+    let mut txs = vec![];
+    // Occupy a slot each, then release some of them and occupy them again.
+    for i in 0..keys.len() {
+        txs.push(signer.call(i, counter));
+    }
+    for i in 0..3 {
+        txs.push(signer.call(i, clear));
+        txs.push(signer.call(i, counter));
+    }
+    // Fund the poor sender and let it pay the collateral this time.
+    let poor_address = keys[poor].address();
+    txs.push(signer.sign(
+        0,
+        Action::Call(poor_address),
+        100_000_000_000_000_000,
+        vec![],
+    ));
+    txs.push(signer.call(poor, counter));
+    txs.push(signer.call(poor, clear));
+
+    assert_parallel_matches_sequential(&epoch_id, &txs);
+}
+
+#[test]
+fn test_parallel_execution_reverts() {
+    let keys: Vec<_> = (0..6).map(|_| Random.generate().unwrap()).collect();
+    let counter = contract_address_for_test(0);
+    let epoch_id = make_parallel_test_state(
+        &keys,
+        &[1_000_000_000_000_000_000; 6],
+        &[(counter, REVERTING_COUNTER_CODE)],
+    );
+
+    // The first two calls succeed and all the later ones revert, which only
+    // the merged state tells.
+    let mut signer = TestTxSigner::new(keys.clone());
+    let mut txs: Vec<_> =
+        (0..keys.len()).map(|i| signer.call(i, counter)).collect();
+    // A reverted transaction still pays for the gas, which the next
+    // transaction of the sender depends on.
+    for i in 0..keys.len() {
+        txs.push(signer.sign(i, Action::Call(keys[0].address()), 1, vec![]));
+    }
+
+    let stats = assert_parallel_matches_sequential(&epoch_id, &txs);
+    assert!(stats.reexecuted >= keys.len() - 1);
+}
+
+#[test]
+fn test_fork_db_pool() {
+    let opened = std::cell::Cell::new(0);
+    let open = || {
+        opened.set(opened.get() + 1);
+        Ok(StateDb::new_for_unit_test())
+    };
+    let pool = ForkDbPool::new();
+
+    // The databases are reused across the epochs forking the same state.
+    let fork_dbs = pool.fork(H256::from_low_u64_be(1), open);
+    let db = fork_dbs.take().unwrap();
+    fork_dbs.put(db);
+    let fork_dbs = pool.fork(H256::from_low_u64_be(1), open);
+    let db = fork_dbs.take().unwrap();
+    assert_eq!(opened.get(), 1);
+
+    // Forking another state drops the idle databases, and the databases of
+    // the earlier state are not returned to the pool.
+    let other_fork_dbs = pool.fork(H256::from_low_u64_be(2), open);
+    fork_dbs.put(db);
+    assert_eq!(pool.num_idle(), 0);
+    let db = other_fork_dbs.take().unwrap();
+    assert_eq!(opened.get(), 2);
+    other_fork_dbs.put(db);
+    assert_eq!(pool.num_idle(), 1);
+
+    // The unpooled databases are opened for each fork.
+    let unpooled_fork_dbs = ForkDbs::unpooled(open);
+    let db = unpooled_fork_dbs.take().unwrap();
+    unpooled_fork_dbs.put(db);
+    unpooled_fork_dbs.take().unwrap();
+    assert_eq!(opened.get(), 4);
+    assert_eq!(pool.num_idle(), 1);
+}
+
+// Store the caller in the slot 0.
+const CALLER_STORE_CODE: &str = "3360005500";
+
+/// Deploy a Core Space contract storing its caller, and fund an eSpace
+/// account to call it through the cross-space contract.
+fn make_core_call_test_state() -> (State, AddressWithSpace, KeyPair) {
+    let mut state = get_state_for_genesis_write();
+    let contract = contract_address_for_test(0).with_native_space();
+    state
+        .new_contract_with_admin(
+            &contract,
+            &Address::zero(),
+            U256::zero(),
+            Some(STORAGE_LAYOUT_REGULAR_V0),
+            false,
+        )
+        .unwrap();
+    state
+        .init_code(
+            &contract,
+            CALLER_STORE_CODE.from_hex().unwrap(),
+            Address::zero(),
+        )
+        .unwrap();
+
+    let key = Random.generate().unwrap();
+    state
+        .add_balance(
+            &key.address().with_evm_space(),
+            &U256::from(1_000_000_000_000_000_000u64),
+            CleanupMode::NoEmpty,
+        )
+        .unwrap();
+    (state, contract, key)
+}
+
+fn sign_evm_tx(
+    key: &KeyPair, nonce: u64, action: Action, value: U256, data: Vec<u8>,
+) -> SignedTransaction {
+    Transaction::Ethereum(EthereumTransaction::Eip155(Eip155Transaction {
+        nonce: nonce.into(),
+        gas_price: U256::from(1),
+        gas: U256::from(1_000_000),
+        action,
+        value,
+        chain_id: Some(1),
+        data,
+    }))
+    .sign(key.secret())
+}
+
+fn call_core_tx(
+    key: &KeyPair, nonce: u64, receiver: &Address, value: u64,
+) -> SignedTransaction {
+    // callCore(bytes20,bytes)
+    let mut data = "a1069e1a".from_hex().unwrap();
+    data.extend((receiver.0, Vec::<u8>::new()).abi_encode());
+    sign_evm_tx(
+        key,
+        nonce,
+        Action::Call(CROSS_SPACE_CONTRACT_ADDRESS),
+        U256::from(value),
+        data,
+    )
+}
+
+#[test]
+fn test_call_core_without_collateral_sponsor() {
+    let (mut state, contract, key) = make_core_call_test_state();
+    let env = Env::default();
+    let machine = make_byzantium_machine(16);
+    let spec = machine.spec_for_test(env.number);
+    let sender = key.address().with_evm_space();
+    let mapped_sender = core_map(key.address());
+    let balance = state.balance(&sender).unwrap();
+
+    // The mapped sender can not pay for the slot, so the whole cross-space
+    // call is rolled back, including the value transfer.
+    let tx = call_core_tx(&key, 0, &contract.address, 1000);
+    let outcome = ExecutiveContext::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::default())
+        .unwrap();
+    let executed = match outcome {
+        ExecutionOutcome::ExecutionErrorBumpNonce(_, executed) => executed,
+        outcome => panic!("The call should fail: {:?}", outcome),
+    };
+    assert!(executed.storage_collateralized.is_empty());
+    assert_eq!(
+        state.storage_at(&contract, &[0u8; 32]).unwrap(),
+        U256::zero()
+    );
+    assert_eq!(state.balance(&sender).unwrap(), balance - executed.fee);
+    assert_eq!(state.balance(&mapped_sender).unwrap(), U256::zero());
+    assert_eq!(state.balance(&contract).unwrap(), U256::zero());
+    assert_eq!(
+        state
+            .collateral_for_storage(&mapped_sender.address)
+            .unwrap(),
+        U256::zero()
+    );
+    assert_eq!(state.nonce(&sender).unwrap(), U256::one());
+}
+
+#[test]
+fn test_call_core_collateral_sponsored() {
+    let (mut state, contract, key) = make_core_call_test_state();
+    let env = Env::default();
+    let machine = make_byzantium_machine(16);
+    let spec = machine.spec_for_test(env.number);
+    let mapped_sender = core_map(key.address());
+
+    let sponsor = Random.generate().unwrap().address();
+    state
+        .set_sponsor_for_collateral(
+            &contract.address,
+            &sponsor,
+            &COLLATERAL_DRIPS_PER_STORAGE_KEY,
+            false,
+        )
+        .unwrap();
+    let mut substate = Substate::new();
+    state.checkpoint();
+    state
+        .add_to_contract_whitelist(
+            contract.address,
+            Address::zero(),
+            mapped_sender.address,
+            &mut substate,
+        )
+        .unwrap();
+    state.discard_checkpoint();
+
+    // The collateral sponsor of the callee pays for the slot instead of the
+    // mapped sender.
+    let tx = call_core_tx(&key, 0, &contract.address, 1000);
+    let executed = ExecutiveContext::new(&mut state, &env, &machine, &spec)
+        .transact(&tx, TransactOptions::default())
+        .unwrap()
+        .into_success_executed()
+        .unwrap();
+    assert_eq!(executed.storage_collateralized.len(), 1);
+    assert_eq!(executed.storage_collateralized[0].address, contract.address);
+    assert_eq!(
+        executed.storage_collateralized[0].collaterals,
+        COLLATERAL_UNITS_PER_STORAGE_KEY.into()
+    );
+    assert_eq!(
+        state.storage_at(&contract, &[0u8; 32]).unwrap(),
+        H256::from(mapped_sender.address).into_uint()
+    );
+    assert_eq!(state.balance(&contract).unwrap(), U256::from(1000));
+    assert_eq!(
+        state
+            .collateral_for_storage(&mapped_sender.address)
+            .unwrap(),
+        U256::zero()
+    );
+}
+
+#[test]
+fn test_evm_sponsor_admin_and_suicide() {
+    let mut state = get_state_for_genesis_write();
+    let env = Env::default();
+    let machine = make_byzantium_machine(16);
+    let spec = machine.spec_for_test(env.number);
+    let admin = Random.generate().unwrap();
+    let other = Random.generate().unwrap();
+    for key in [&admin, &other] {
+        state
+            .add_balance(
+                &key.address().with_evm_space(),
+                &U256::from(1_000_000_000_000_000_000u64),
+                CleanupMode::NoEmpty,
+            )
+            .unwrap();
+    }
+    let transact = |state: &mut State, tx: &SignedTransaction| {
+        ExecutiveContext::new(state, &env, &machine, &spec)
+            .transact(tx, TransactOptions::default())
+            .unwrap()
+    };
+
+    // The runtime code is `selfdestruct(msg.sender)`.
+    let code = "6133ff6000526002601ef3".from_hex().unwrap();
+    let contract = contract_address(
+        CreateContractAddress::FromSenderNonce,
+        0,
+        &admin.address().with_evm_space(),
+        &U256::zero(),
+        &code,
+    )
+    .0;
+    let tx = sign_evm_tx(&admin, 0, Action::Create, U256::zero(), code);
+    assert!(transact(&mut state, &tx)
+        .try_as_success_executed()
+        .is_some());
+    assert_eq!(
+        state.evm_contract_admin(&contract.address).unwrap(),
+        admin.address()
+    );
+
+    // addPrivilegeByAdmin(address,address[])
+    let mut data = "22effe84".from_hex().unwrap();
+    data.extend((contract.address, vec![Address::zero()]).abi_encode());
+    let action = Action::Call(SPONSOR_WHITELIST_CONTROL_CONTRACT_ADDRESS);
+    let tx = sign_evm_tx(&other, 0, action.clone(), U256::zero(), data.clone());
+    assert!(transact(&mut state, &tx)
+        .try_as_success_executed()
+        .is_none());
+    assert!(!state
+        .check_evm_contract_whitelist(&contract.address, &other.address())
+        .unwrap());
+    let tx = sign_evm_tx(&admin, 1, action.clone(), U256::zero(), data);
+    assert!(transact(&mut state, &tx)
+        .try_as_success_executed()
+        .is_some());
+    assert!(state
+        .check_evm_contract_whitelist(&contract.address, &other.address())
+        .unwrap());
+
+    // setSponsorForGas(address,uint256)
+    let sponsor_balance = U256::from(1_000_000_000_000_000u64);
+    let mut data = "3e3e6428".from_hex().unwrap();
+    data.extend((contract.address, U256::from(10)).abi_encode());
+    let tx = sign_evm_tx(&admin, 2, action, sponsor_balance, data);
+    assert!(transact(&mut state, &tx)
+        .try_as_success_executed()
+        .is_some());
+    assert_eq!(
+        state
+            .evm_sponsor_balance_for_gas(&contract.address)
+            .unwrap(),
+        sponsor_balance
+    );
+
+    // The sponsor gets back the sponsor balance when the contract is killed,
+    // and the sponsorship is cleared.
+    let balance = state.balance(&admin.address().with_evm_space()).unwrap();
+    let tx = sign_evm_tx(
+        &admin,
+        3,
+        Action::Call(contract.address),
+        U256::zero(),
+        vec![],
+    );
+    let executed = transact(&mut state, &tx).into_success_executed().unwrap();
+    assert_eq!(
+        state.balance(&admin.address().with_evm_space()).unwrap(),
+        balance - executed.fee + sponsor_balance
+    );
+    assert_eq!(state.evm_sponsor_for_gas(&contract.address).unwrap(), None);
+    assert_eq!(
+        state
+            .evm_sponsor_balance_for_gas(&contract.address)
+            .unwrap(),
+        U256::zero()
+    );
+    assert_eq!(
+        state.evm_contract_admin(&contract.address).unwrap(),
+        Address::zero()
+    );
+    assert!(!state
+        .check_evm_contract_whitelist(&contract.address, &other.address())
+        .unwrap());
+}
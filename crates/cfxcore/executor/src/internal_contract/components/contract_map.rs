@@ -4,12 +4,18 @@ use cfx_types::{Address, AddressWithSpace, Space};
 use cfx_vm_types::Spec;
 use primitives::BlockNumber;
 
-use super::{super::contracts::all_internal_contracts, InternalContractTrait};
+use super::{
+    super::contracts::{all_evm_space_contracts, all_internal_contracts},
+    InternalContractTrait,
+};
 use crate::spec::CommonParams;
 
 #[derive(Default)]
 pub struct InternalContractMap {
     builtin: BTreeMap<Address, Box<dyn InternalContractTrait>>,
+    /// The internal contracts hosted in eSpace. They hold no account states,
+    /// so they are not recorded in `activation_info`.
+    evm_space: BTreeMap<Address, Box<dyn InternalContractTrait>>,
     activation_info: BTreeMap<BlockNumber, Vec<Address>>,
 }
 
@@ -45,8 +51,14 @@ impl InternalContractMap {
                 .push(address);
         }
 
+        let evm_space = all_evm_space_contracts()
+            .into_iter()
+            .map(|contract| (*contract.address(), contract))
+            .collect();
+
         Self {
             builtin,
+            evm_space,
             activation_info,
         }
     }
@@ -72,10 +84,11 @@ impl InternalContractMap {
     pub fn contract(
         &self, address: &AddressWithSpace, spec: &Spec,
     ) -> Option<&Box<dyn InternalContractTrait>> {
-        if address.space != Space::Native {
-            return None;
-        }
-        self.builtin
+        let contracts = match address.space {
+            Space::Native => &self.builtin,
+            Space::Ethereum => &self.evm_space,
+        };
+        contracts
             .get(&address.address)
             .filter(|&contract| contract.is_active(spec))
     }
@@ -44,6 +44,24 @@ group_impl_is_active!(
     DeployEip1820,
 );
 
+make_solidity_contract! {
+    /// The eSpace side of the cross-space call, hosted at the same address as
+    /// `CrossSpaceCall` in eSpace. It calls Core Space contracts on behalf of
+    /// the mapped Core Space address of the eSpace caller.
+    pub struct CoreSpaceCall(CROSS_SPACE_CONTRACT_ADDRESS, generate_core_fn_table, initialize: |params: &CommonParams| params.transition_numbers.cip146, is_active: |spec: &Spec| spec.cip146);
+}
+
+fn generate_core_fn_table() -> SolFnTable {
+    make_function_table!(CallToCore, StaticCallToCore, MappedCoreAddress)
+}
+
+group_impl_is_active!(
+    |spec: &Spec| spec.cip146,
+    CallToCore,
+    StaticCallToCore,
+    MappedCoreAddress,
+);
+
 make_solidity_event! {
     pub struct CallEvent("Call(bytes20,bytes20,uint256,uint256,bytes)", indexed: (Bytes20, Bytes20), non_indexed: (U256, U256, Bytes));
 }
@@ -271,6 +289,85 @@ impl SimpleExecutionTrait for DeployEip1820 {
     }
 }
 
+make_solidity_function! {
+    struct CallToCore((Bytes20,Bytes), "callCore(bytes20,bytes)", Bytes);
+}
+
+impl_function_type!(CallToCore, "payable_write");
+
+impl UpfrontPaymentTrait for CallToCore {
+    fn upfront_gas_payment(
+        &self, (ref receiver, ref data): &(Bytes20, Bytes),
+        params: &ActionParams, context: &InternalRefContext,
+    ) -> DbResult<U256> {
+        call_core_gas(H160(*receiver), params, context, data)
+    }
+}
+
+impl ExecutionTrait for CallToCore {
+    fn execute_inner(
+        &self, (to, data): (Bytes20, Bytes), params: &ActionParams,
+        gas_left: U256, context: &mut InternalRefContext,
+    ) -> InternalTrapResult<Bytes> {
+        let trap = call_to_core(
+            H160(to),
+            data,
+            CallType::Call,
+            params,
+            gas_left,
+            context,
+        );
+        process_trap(trap, PhantomData)
+    }
+}
+
+make_solidity_function! {
+    struct StaticCallToCore((Bytes20,Bytes), "staticCallCore(bytes20,bytes)", Bytes);
+}
+
+impl_function_type!(StaticCallToCore, "query");
+
+impl UpfrontPaymentTrait for StaticCallToCore {
+    fn upfront_gas_payment(
+        &self, _: &(Bytes20, Bytes), _params: &ActionParams,
+        context: &InternalRefContext,
+    ) -> DbResult<U256> {
+        Ok(static_call_gas(context.spec))
+    }
+}
+
+impl ExecutionTrait for StaticCallToCore {
+    fn execute_inner(
+        &self, (to, data): (Bytes20, Bytes), params: &ActionParams,
+        gas_left: U256, context: &mut InternalRefContext,
+    ) -> InternalTrapResult<Bytes> {
+        let trap = call_to_core(
+            H160(to),
+            data,
+            CallType::StaticCall,
+            params,
+            gas_left,
+            context,
+        );
+        process_trap(trap, PhantomData)
+    }
+}
+
+make_solidity_function! {
+    struct MappedCoreAddress(Address, "mappedCoreAddress(address)", Bytes20);
+}
+
+impl_function_type!(MappedCoreAddress, "query", gas: |spec: &Spec| spec.sha3_gas);
+
+impl SimpleExecutionTrait for MappedCoreAddress {
+    fn execute_inner(
+        &self, addr: Address, _params: &ActionParams,
+        _context: &mut InternalRefContext,
+    ) -> vm::Result<Bytes20> {
+        Ok(core_map(addr).address.0)
+    }
+}
+
 pub fn is_call_create_sig(data: &[u8]) -> bool {
     data == TransferToEVM::FUNC_SIG
         || data == CreateToEVM::FUNC_SIG
@@ -289,6 +386,9 @@ fn test_cross_space_contract_sig() {
     check_func_signature!(MappedBalance, "142b37c7");
     check_func_signature!(MappedNonce, "b5914944");
     check_func_signature!(DeployEip1820, "36201722");
+    check_func_signature!(CallToCore, "a1069e1a");
+    check_func_signature!(StaticCallToCore, "337be688");
+    check_func_signature!(MappedCoreAddress, "f3671330");
 
     check_event_signature!(
         CallEvent,
@@ -0,0 +1,243 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use super::{super::impls::sponsor::*, preludes::*};
+use cfx_parameters::internal_contract_addresses::SPONSOR_WHITELIST_CONTROL_CONTRACT_ADDRESS;
+use cfx_types::{Address, AddressSpaceUtil, U256};
+
+make_solidity_contract! {
+    /// The eSpace side of the sponsorship, hosted at the same address as
+    /// `SponsorWhitelistControl` in eSpace. It only supports the gas
+    /// sponsorship, since eSpace has no storage collateral.
+    pub struct EvmSponsorWhitelistControl(SPONSOR_WHITELIST_CONTROL_CONTRACT_ADDRESS, generate_fn_table, initialize: |params: &CommonParams| params.transition_numbers.cip147, is_active: |spec: &Spec| spec.cip147);
+}
+
+fn generate_fn_table() -> SolFnTable {
+    make_function_table!(
+        SetSponsorForGas,
+        AddPrivilegeByAdmin,
+        RemovePrivilegeByAdmin,
+        GetSponsorForGas,
+        GetSponsoredBalanceForGas,
+        GetSponsoredGasFeeUpperBound,
+        IsWhitelisted,
+        IsAllWhitelisted
+    )
+}
+
+group_impl_is_active!(
+    |spec: &Spec| spec.cip147,
+    SetSponsorForGas,
+    AddPrivilegeByAdmin,
+    RemovePrivilegeByAdmin,
+    GetSponsorForGas,
+    GetSponsoredBalanceForGas,
+    GetSponsoredGasFeeUpperBound,
+    IsWhitelisted,
+    IsAllWhitelisted,
+);
+
+make_solidity_function! {
+    struct SetSponsorForGas((Address, U256), "setSponsorForGas(address,uint256)");
+}
+impl_function_type!(SetSponsorForGas, "payable_write", gas: |spec: &Spec| 3 * spec.sstore_reset_gas);
+
+impl SimpleExecutionTrait for SetSponsorForGas {
+    fn execute_inner(
+        &self, inputs: (Address, U256), params: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<()> {
+        set_evm_sponsor_for_gas(inputs.0, inputs.1, params, context)
+    }
+}
+
+make_solidity_function! {
+    struct AddPrivilegeByAdmin((Address,Vec<Address>), "addPrivilegeByAdmin(address,address[])");
+}
+impl_function_type!(AddPrivilegeByAdmin, "non_payable_write");
+
+impl UpfrontPaymentTrait for AddPrivilegeByAdmin {
+    fn upfront_gas_payment(
+        &self, (_contract, addresses): &(Address, Vec<Address>),
+        _: &ActionParams, context: &InternalRefContext,
+    ) -> DbResult<U256> {
+        Ok(U256::from(context.spec.sstore_reset_gas) * addresses.len())
+    }
+}
+
+impl SimpleExecutionTrait for AddPrivilegeByAdmin {
+    fn execute_inner(
+        &self, (contract, addresses): (Address, Vec<Address>),
+        params: &ActionParams, context: &mut InternalRefContext,
+    ) -> vm::Result<()> {
+        check_evm_contract_admin(&contract, params, context)?;
+        for user in addresses {
+            context.state.add_to_evm_contract_whitelist(
+                contract,
+                params.storage_owner,
+                user,
+                context.substate,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+make_solidity_function! {
+    struct RemovePrivilegeByAdmin((Address,Vec<Address>), "removePrivilegeByAdmin(address,address[])");
+}
+impl_function_type!(RemovePrivilegeByAdmin, "non_payable_write");
+
+impl UpfrontPaymentTrait for RemovePrivilegeByAdmin {
+    fn upfront_gas_payment(
+        &self, (_contract, addresses): &(Address, Vec<Address>),
+        _: &ActionParams, context: &InternalRefContext,
+    ) -> DbResult<U256> {
+        Ok(U256::from(context.spec.sstore_reset_gas) * addresses.len())
+    }
+}
+
+impl SimpleExecutionTrait for RemovePrivilegeByAdmin {
+    fn execute_inner(
+        &self, (contract, addresses): (Address, Vec<Address>),
+        params: &ActionParams, context: &mut InternalRefContext,
+    ) -> vm::Result<()> {
+        check_evm_contract_admin(&contract, params, context)?;
+        for user in addresses {
+            context.state.remove_from_evm_contract_whitelist(
+                contract,
+                params.storage_owner,
+                user,
+                context.substate,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Only the admin of an existing eSpace contract manages its whitelist. The
+/// eSpace addresses carry no account type, so a contract can not manage its
+/// own whitelist as in Core Space.
+fn check_evm_contract_admin(
+    contract: &Address, params: &ActionParams, context: &InternalRefContext,
+) -> vm::Result<()> {
+    if !context
+        .state
+        .is_contract_with_code(&contract.with_evm_space())?
+    {
+        return Err(vm::Error::InternalContract(
+            "not allowed to set the whitelist of non-contract account".into(),
+        ));
+    }
+    if params.sender != context.state.evm_contract_admin(contract)? {
+        return Err(vm::Error::InternalContract(
+            "only the admin can set the whitelist".into(),
+        ));
+    }
+    Ok(())
+}
+
+make_solidity_function! {
+    struct GetSponsorForGas(Address, "getSponsorForGas(address)", Address);
+}
+impl_function_type!(GetSponsorForGas, "query", gas: |spec: &Spec| spec.sload_gas);
+
+impl SimpleExecutionTrait for GetSponsorForGas {
+    fn execute_inner(
+        &self, input: Address, _: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<Address> {
+        Ok(context
+            .state
+            .evm_sponsor_for_gas(&input)?
+            .unwrap_or_default())
+    }
+}
+
+make_solidity_function! {
+    struct GetSponsoredBalanceForGas(Address, "getSponsoredBalanceForGas(address)", U256);
+}
+impl_function_type!(GetSponsoredBalanceForGas, "query", gas: |spec: &Spec| spec.sload_gas);
+
+impl SimpleExecutionTrait for GetSponsoredBalanceForGas {
+    fn execute_inner(
+        &self, input: Address, _: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<U256> {
+        Ok(context.state.evm_sponsor_balance_for_gas(&input)?)
+    }
+}
+
+make_solidity_function! {
+    struct GetSponsoredGasFeeUpperBound(Address, "getSponsoredGasFeeUpperBound(address)", U256);
+}
+impl_function_type!(GetSponsoredGasFeeUpperBound, "query", gas: |spec: &Spec| spec.sload_gas);
+
+impl SimpleExecutionTrait for GetSponsoredGasFeeUpperBound {
+    fn execute_inner(
+        &self, input: Address, _: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<U256> {
+        Ok(context.state.evm_sponsor_gas_bound(&input)?)
+    }
+}
+
+make_solidity_function! {
+    struct IsWhitelisted((Address,Address), "isWhitelisted(address,address)", bool);
+}
+impl_function_type!(IsWhitelisted, "query", gas: |spec: &Spec| 2 * spec.sload_gas);
+
+impl SimpleExecutionTrait for IsWhitelisted {
+    fn execute_inner(
+        &self, (contract, user): (Address, Address), _: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<bool> {
+        if context
+            .state
+            .is_contract_with_code(&contract.with_evm_space())?
+        {
+            Ok(context
+                .state
+                .check_evm_contract_whitelist(&contract, &user)?)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+make_solidity_function! {
+    struct IsAllWhitelisted(Address, "isAllWhitelisted(address)", bool);
+}
+impl_function_type!(IsAllWhitelisted, "query", gas: |spec: &Spec| 2 * spec.sload_gas);
+
+impl SimpleExecutionTrait for IsAllWhitelisted {
+    fn execute_inner(
+        &self, contract: Address, _: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<bool> {
+        if context
+            .state
+            .is_contract_with_code(&contract.with_evm_space())?
+        {
+            Ok(context
+                .state
+                .check_evm_contract_whitelist(&contract, &Address::zero())?)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+#[test]
+fn test_evm_sponsor_contract_sig() {
+    // The interface is a subset of the Core Space `SponsorWhitelistControl`.
+    check_func_signature!(SetSponsorForGas, "3e3e6428");
+    check_func_signature!(AddPrivilegeByAdmin, "22effe84");
+    check_func_signature!(RemovePrivilegeByAdmin, "217e055b");
+    check_func_signature!(GetSponsorForGas, "33a1af31");
+    check_func_signature!(GetSponsoredBalanceForGas, "b3b28fac");
+    check_func_signature!(GetSponsoredGasFeeUpperBound, "d665f9dd");
+    check_func_signature!(IsWhitelisted, "b6b35272");
+    check_func_signature!(IsAllWhitelisted, "79b47faa");
+}
@@ -5,6 +5,7 @@
 mod admin;
 mod context;
 pub(super) mod cross_space;
+mod evm_sponsor;
 mod future;
 pub(super) mod params_control;
 pub(super) mod pos;
@@ -63,6 +64,14 @@ pub fn all_internal_contracts() -> Vec<Box<dyn super::InternalContractTrait>> {
     ]
 }
 
+/// Internal contracts hosted in eSpace. They are not initialized as accounts.
+pub fn all_evm_space_contracts() -> Vec<Box<dyn super::InternalContractTrait>> {
+    vec![
+        Box::new(cross_space::CoreSpaceCall::instance()),
+        Box::new(evm_sponsor::EvmSponsorWhitelistControl::instance()),
+    ]
+}
+
 use crate::state::State;
 use cfx_statedb::Result as DbResult;
 use cfx_types::{Address, AddressSpaceUtil, U256};
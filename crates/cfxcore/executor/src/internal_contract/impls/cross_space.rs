@@ -9,10 +9,14 @@ use crate::{
     substate::cleanup_mode,
 };
 
-use cfx_parameters::block::CROSS_SPACE_GAS_RATIO;
+use cfx_parameters::{
+    block::CROSS_SPACE_GAS_RATIO,
+    internal_contract_addresses::CROSS_SPACE_CONTRACT_ADDRESS,
+};
 use cfx_statedb::Result as DbResult;
 use cfx_types::{
-    Address, AddressSpaceUtil, AddressWithSpace, Space, H256, U256,
+    address_util::AddressUtil, Address, AddressSpaceUtil, AddressWithSpace,
+    Space, H256, U256,
 };
 use cfx_vm_interpreter::Finalize;
 use cfx_vm_types::{
@@ -115,6 +119,37 @@ pub fn call_gas(
         + return_log_gas)
 }
 
+pub fn call_core_gas(
+    receiver: Address, params: &ActionParams, context: &InternalRefContext,
+    data: &[u8],
+) -> DbResult<U256> {
+    let transaction_gas =
+        gas_required_for(/* is_create */ false, data, None, context.spec)
+            + context.spec.tx_gas as u64;
+
+    let new_account = !context
+        .state
+        .exists_and_not_null(&receiver.with_native_space())?;
+    let new_account_gas = if new_account {
+        context.spec.call_new_account_gas
+    } else {
+        0
+    };
+
+    let transfer_gas = if params.value.value() > U256::zero() {
+        context.spec.call_value_transfer_gas
+    } else {
+        0
+    };
+
+    let call_gas =
+        U256::from(context.spec.call_gas) + new_account_gas + transfer_gas;
+
+    let address_mapping_gas = context.spec.sha3_gas * 2;
+
+    Ok(call_gas + transaction_gas + address_mapping_gas)
+}
+
 pub fn static_call_gas(spec: &Spec) -> U256 {
     let call_gas = U256::from(spec.call_gas);
     let address_mapping_gas = spec.sha3_gas * 2;
@@ -138,6 +173,9 @@ pub struct Resume {
     pub params: ActionParams,
     pub gas_retained: U256,
     pub wait_return_log: bool,
+    /// The storage owner of the sub-call which can not pay storage
+    /// collateral. The call fails if it occupies storage for this owner.
+    pub unpaid_storage_owner: Option<Address>,
 }
 
 impl Resumable for Resume {
@@ -150,6 +188,7 @@ impl Resumable for Resume {
                     result: Err(e),
                     apply_state: false,
                     wait_return_log: self.wait_return_log,
+                    unpaid_storage_owner: None,
                 });
             }
         };
@@ -185,6 +224,7 @@ impl Resumable for Resume {
                 apply_state,
             }),
             wait_return_log: self.wait_return_log,
+            unpaid_storage_owner: self.unpaid_storage_owner,
         })
     }
 }
@@ -194,12 +234,29 @@ pub struct PassResult {
     result: vm::Result<GasLeft>,
     apply_state: bool,
     wait_return_log: bool,
+    unpaid_storage_owner: Option<Address>,
 }
 
 impl Executable for PassResult {
     fn execute(
         self: Box<Self>, mut context: Context,
     ) -> DbResult<ExecutableOutcome> {
+        // The sub-call substate has been accrued into the substate of this
+        // frame. Failing here reverts the sub-call together with this frame.
+        let mut result = self.result;
+        if let Some(owner) = self.unpaid_storage_owner {
+            let (occupied, _) = context
+                .internal_ref()
+                .substate
+                .get_collateral_change(&owner);
+            if self.apply_state && occupied > 0 {
+                result = Err(vm::Error::InternalContract(
+                    "Occupy Core Space storage without collateral sponsor"
+                        .into(),
+                ));
+            }
+        }
+
         if self.wait_return_log {
             ReturnEvent::log(
                 &(),
@@ -210,8 +267,7 @@ impl Executable for PassResult {
             .expect("Must have no static flag");
         }
 
-        let result = self
-            .result
+        let result = result
             .and_then(|r| r.charge_return_data_gas(context.spec()))
             .finalize(context);
         Ok(ExecutableOutcome::Return(result))
@@ -222,6 +278,31 @@ pub fn evm_map(address: Address) -> AddressWithSpace {
     Address::from(keccak(&address)).with_evm_space()
 }
 
+/// The mapped Core Space address of an eSpace address. It is always a user
+/// account address.
+pub fn core_map(address: Address) -> AddressWithSpace {
+    let mut mapped = Address::from(keccak(&address));
+    mapped.set_user_account_type_bits();
+    mapped.with_native_space()
+}
+
+/// A cross-space call can not be made in a frame which is entered by another
+/// cross-space call in the reversed direction (e.g., eSpace -> Core Space ->
+/// eSpace), so the phantom transactions and traces of a transaction only come
+/// from one space.
+fn check_cross_space_reentrancy(
+    entered_space: Space, context: &InternalRefContext,
+) -> vm::Result<()> {
+    if !context.spec.cip146 {
+        return Ok(());
+    }
+    let reversed_entry = CROSS_SPACE_CONTRACT_ADDRESS.with_space(entered_space);
+    if context.callstack.contains_key(&reversed_entry) {
+        internal_bail!("Reentrancy of the cross-space call");
+    }
+    Ok(())
+}
+
 pub fn process_trap<T>(
     result: vm::Result<(ActionParams, Box<dyn Resumable>)>,
     _phantom: PhantomData<T>,
@@ -239,6 +320,7 @@ pub fn call_to_evmcore(
     if context.depth >= context.spec.max_depth {
         internal_bail!("Exceed Depth");
     }
+    check_cross_space_reentrancy(Space::Ethereum, context)?;
 
     let value = params.value.value();
 
@@ -309,6 +391,7 @@ pub fn call_to_evmcore(
             params: params.clone(),
             gas_retained: reserved_gas,
             wait_return_log,
+            unpaid_storage_owner: None,
         }),
     ))
 }
@@ -320,6 +403,7 @@ pub fn create_to_evmcore(
     if context.depth >= context.spec.max_depth {
         internal_bail!("Exceed Depth");
     }
+    check_cross_space_reentrancy(Space::Ethereum, context)?;
 
     let call_gas = gas_left / CROSS_SPACE_GAS_RATIO
         + if params.value.value() > U256::zero() {
@@ -395,6 +479,96 @@ pub fn create_to_evmcore(
             params: params.clone(),
             gas_retained: reserved_gas,
             wait_return_log: true,
+            unpaid_storage_owner: None,
+        }),
+    ))
+}
+
+pub fn call_to_core(
+    receiver: Address, data: Vec<u8>, call_type: CallType,
+    params: &ActionParams, gas_left: U256, context: &mut InternalRefContext,
+) -> vm::Result<(ActionParams, Box<dyn Resumable>)> {
+    if context.depth >= context.spec.max_depth {
+        internal_bail!("Exceed Depth");
+    }
+    check_cross_space_reentrancy(Space::Native, context)?;
+
+    let value = params.value.value();
+
+    let call_gas = gas_left / CROSS_SPACE_GAS_RATIO
+        + if value > U256::zero() {
+            U256::from(context.spec.call_stipend)
+        } else {
+            U256::zero()
+        };
+    let reserved_gas = gas_left - gas_left / CROSS_SPACE_GAS_RATIO;
+
+    let mapped_sender = core_map(params.sender);
+    let mapped_origin = core_map(params.original_sender);
+
+    context.state.transfer_balance(
+        &params.address.with_evm_space(),
+        &mapped_sender,
+        &value,
+        cleanup_mode(context.substate, context.spec),
+    )?;
+    context.state.sub_total_evm_tokens(value);
+    context.tracer.trace_internal_transfer(
+        AddressPocket::Balance(params.address.with_evm_space()),
+        AddressPocket::Balance(mapped_sender),
+        value,
+    );
+
+    let address = receiver.with_native_space();
+
+    let code = context.state.code(&address)?;
+    let code_hash = context.state.code_hash(&address)?;
+
+    // The mapped sender can only act through this contract, so the collateral
+    // locked or refunded on it could never be spent again. As in a sponsored
+    // Core Space transaction, the collateral sponsor of the callee pays for
+    // the storage occupied in Core Space, if it whitelists the mapped sender.
+    // Otherwise the call must not occupy any storage.
+    let storage_sponsored = context.state.is_contract_with_code(&address)?
+        && context.state.check_contract_whitelist(
+            &address.address,
+            &mapped_sender.address,
+        )?
+        && context
+            .state
+            .sponsor_for_collateral(&address.address)?
+            .is_some();
+    let (storage_owner, unpaid_storage_owner) = if storage_sponsored {
+        (address.address, None)
+    } else {
+        (mapped_sender.address, Some(mapped_sender.address))
+    };
+
+    let next_params = ActionParams {
+        space: Space::Native,
+        sender: mapped_sender.address,
+        address: address.address,
+        value: ActionValue::Transfer(value),
+        code_address: address.address,
+        original_sender: mapped_origin.address,
+        storage_owner,
+        gas: call_gas,
+        gas_price: params.gas_price,
+        code,
+        code_hash,
+        data: Some(data),
+        call_type,
+        create_type: CreateType::None,
+        params_type: vm::ParamsType::Separate,
+    };
+
+    Ok((
+        next_params,
+        Box::new(Resume {
+            params: params.clone(),
+            gas_retained: reserved_gas,
+            wait_return_log: false,
+            unpaid_storage_owner,
         }),
     ))
 }
@@ -268,3 +268,93 @@ pub fn remove_privilege(
     }
     Ok(())
 }
+
+/// Implementation of `setSponsorForGas(address,uint256)` of eSpace contracts
+/// (CIP-147).
+pub fn set_evm_sponsor_for_gas(
+    contract_address: Address, upper_bound: U256, params: &ActionParams,
+    context: &mut InternalRefContext,
+) -> vm::Result<()> {
+    let sponsor = &params.sender;
+
+    if !context
+        .state
+        .is_contract_with_code(&contract_address.with_evm_space())?
+    {
+        internal_bail!("not allowed to sponsor non-contract account");
+    }
+
+    let (spec, state, substate): (&Spec, &mut State, &mut Substate) =
+        (context.spec, context.state, context.substate);
+
+    let sponsor_balance = state.balance(&params.address.with_evm_space())?;
+
+    if sponsor_balance / U256::from(1000) < upper_bound {
+        internal_bail!("sponsor should at least sponsor upper_bound * 1000");
+    }
+
+    let prev_sponsor = state.evm_sponsor_for_gas(&contract_address)?;
+    let prev_sponsor_balance =
+        state.evm_sponsor_balance_for_gas(&contract_address)?;
+    let prev_upper_bound = state.evm_sponsor_gas_bound(&contract_address)?;
+    let sponsor_changed = prev_sponsor.as_ref().map_or_else(
+        || !sponsor.is_zero(),
+        |prev_sponsor| prev_sponsor != sponsor,
+    );
+    // The rules of replacing the sponsor or charging the sponsor balance are
+    // the same as the Core Space ones.
+    if sponsor_changed && sponsor_balance <= prev_sponsor_balance {
+        internal_bail!("sponsor_balance is not exceed previous sponsor");
+    }
+    if prev_sponsor_balance >= prev_upper_bound
+        && upper_bound < prev_upper_bound
+    {
+        if sponsor_changed {
+            internal_bail!("upper_bound is not exceed previous sponsor");
+        } else {
+            internal_bail!("cannot change upper_bound to a smaller one");
+        }
+    }
+
+    // The deposited balance is kept out of any account balance, but it is
+    // still counted in the total eSpace tokens.
+    let new_sponsor_balance = if sponsor_changed {
+        // refund to previous sponsor
+        if let Some(prev_sponsor) = prev_sponsor {
+            context.tracer.trace_internal_transfer(
+                AddressPocket::EvmSponsorBalanceForGas(contract_address),
+                AddressPocket::Balance(prev_sponsor.with_evm_space()),
+                prev_sponsor_balance,
+            );
+            state.add_balance(
+                &prev_sponsor.with_evm_space(),
+                &prev_sponsor_balance,
+                cleanup_mode(substate, &spec),
+            )?;
+        }
+        sponsor_balance
+    } else {
+        sponsor_balance + prev_sponsor_balance
+    };
+
+    context.tracer.trace_internal_transfer(
+        AddressPocket::Balance(params.address.with_evm_space()),
+        AddressPocket::EvmSponsorBalanceForGas(contract_address),
+        sponsor_balance,
+    );
+    state.sub_balance(
+        &params.address.with_evm_space(),
+        &sponsor_balance,
+        &mut cleanup_mode(substate, &spec),
+    )?;
+    state.set_evm_sponsor_for_gas(
+        &contract_address,
+        sponsor,
+        &new_sponsor_balance,
+        &upper_bound,
+        params.storage_owner,
+        substate,
+    )?;
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use impl_tools::autoimpl;
 use impl_trait_for_tuples::impl_for_tuples;
 
-#[impl_for_tuples(3)]
+#[impl_for_tuples(4)]
 #[autoimpl(for<T: trait + ?Sized> &mut T)]
 pub trait CheckpointTracer {
     fn trace_checkpoint(&mut self) {}
@@ -4,7 +4,7 @@ use cfx_vm_types::InterpreterInfo;
 use impl_tools::autoimpl;
 use impl_trait_for_tuples::impl_for_tuples;
 
-#[impl_for_tuples(3)]
+#[impl_for_tuples(4)]
 #[autoimpl(for<T: trait + ?Sized> &mut T)]
 pub trait OpcodeTracer {
     fn do_trace_opcode(&self, _enabled: &mut bool) {}
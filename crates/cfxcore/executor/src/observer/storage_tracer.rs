@@ -1,6 +1,18 @@
+use cfx_types::{AddressWithSpace, U256};
+
 use impl_tools::autoimpl;
 use impl_trait_for_tuples::impl_for_tuples;
 
-#[impl_for_tuples(3)]
+#[impl_for_tuples(4)]
 #[autoimpl(for<T: trait + ?Sized> &mut T)]
-pub trait StorageTracer {}
+pub trait StorageTracer {
+    /// Called when a contract writes its storage with `SSTORE`. The write may
+    /// still be reverted with the frame.
+    fn trace_storage_write(
+        &mut self, address: &AddressWithSpace, key: &[u8], value: &U256,
+    ) {
+        let _ = address;
+        let _ = key;
+        let _ = value;
+    }
+}
@@ -115,6 +115,13 @@ pub struct TransitionsBlockNumber {
     pub cip144: BlockNumber,
     /// CIP-145: Fix Receipts upon `NotEnoughBalance` Error
     pub cip145: BlockNumber,
+    /// CIP-146: Cross-Space Call from eSpace to Core Space
+    pub cip146: BlockNumber,
+    /// CIP-147: Gas Sponsorship for eSpace Contracts
+    pub cip147: BlockNumber,
+    /// CIP-148: BLOBHASH and BLOBBASEFEE Opcodes, and TLOAD Reading the
+    /// Transient Storage
+    pub cip148: BlockNumber,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -193,6 +200,9 @@ impl CommonParams {
         spec.cip137 = number >= self.transition_numbers.cip137;
         spec.cip144 = number >= self.transition_numbers.cip144;
         spec.cip145 = number >= self.transition_numbers.cip145;
+        spec.cip146 = number >= self.transition_numbers.cip146;
+        spec.cip147 = number >= self.transition_numbers.cip147;
+        spec.cip148 = number >= self.transition_numbers.cip148;
         spec.cip1559 = height >= self.transition_heights.cip1559;
         spec.cancun_opcodes = number >= self.transition_numbers.cancun_opcodes;
         if spec.cancun_opcodes {
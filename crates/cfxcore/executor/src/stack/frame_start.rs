@@ -172,6 +172,16 @@ fn transfer_exec_balance_and_init_contract(
             storage_layout,
             spec.cip107,
         )?;
+        // The eSpace accounts have no admin field, so the admin managing the
+        // gas sponsorship whitelist is kept by the sponsor control contract.
+        if params.space == Space::Ethereum && spec.cip147 {
+            state.set_evm_contract_admin(
+                &receiver.address,
+                &params.original_sender,
+                params.storage_owner,
+                substate,
+            )?;
+        }
     } else {
         // In contract creation, the `params.value` should never be
         // `Apparent`.
@@ -5,6 +5,10 @@ use cfx_statedb::{
     Result as DbResult, StateDbExt, StateDbGeneric as StateDb,
 };
 use cfx_types::U256;
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
 
 /// Manages specially-treated global variables during execution.
 ///
@@ -23,8 +27,22 @@ use cfx_types::U256;
 // TODO: Incorporating these variables into existing cache/checkpoint logic
 // would make the code clean, but it would be difficult to achieve back forward
 // compatibility.
-#[derive(Copy, Clone, Debug)]
-pub(super) struct GlobalStat([U256; TOTAL_GLOBAL_PARAMS]);
+#[derive(Clone, Debug)]
+pub(super) struct GlobalStat {
+    values: [U256; TOTAL_GLOBAL_PARAMS],
+    /// Only set in a speculative fork, shared by the checkpoints of the fork
+    /// so that the accesses in reverted frames are also recorded.
+    access: Option<Arc<GlobalStatAccess>>,
+}
+
+/// The global statistic variables accessed by a speculative fork.
+#[derive(Debug)]
+struct GlobalStatAccess {
+    /// The values when the fork is made.
+    initial: [U256; TOTAL_GLOBAL_PARAMS],
+    /// Bit mask of the variables whose values have been observed.
+    observed: AtomicU32,
+}
 
 impl GlobalStat {
     /// Make new global statistical variables with their initialization value.
@@ -39,7 +57,14 @@ impl GlobalStat {
         for_all_global_param_keys! {
             init_value::<Key>(&mut ans);
         }
-        GlobalStat(ans)
+        GlobalStat::from_values(ans)
+    }
+
+    fn from_values(values: [U256; TOTAL_GLOBAL_PARAMS]) -> Self {
+        GlobalStat {
+            values,
+            access: None,
+        }
     }
 
     /// Get loaded global statistic variables from the database.
@@ -56,7 +81,7 @@ impl GlobalStat {
         for_all_global_param_keys! {
             load_value::<Key>(&mut ans, db)?;
         }
-        Ok(GlobalStat(ans))
+        Ok(GlobalStat::from_values(ans))
     }
 
     /// Assert the global statistic variables have never been inited in the
@@ -95,17 +120,97 @@ impl GlobalStat {
         }
         use global_params::*;
         for_all_global_param_keys! {
-            commit_param::<Key>(&self.0, db, debug_record.as_deref_mut())?;
+            commit_param::<Key>(&self.values, db, debug_record.as_deref_mut())?;
         }
         Ok(())
     }
 
     /// Get the owned value of a variable
-    pub fn get<T: GlobalParamKey>(&self) -> U256 { self.0[T::ID] }
+    pub fn get<T: GlobalParamKey>(&self) -> U256 {
+        self.observe(T::ID);
+        self.values[T::ID]
+    }
 
     /// Get the immutable reference of a variable
-    pub fn refr<T: GlobalParamKey>(&self) -> &U256 { &self.0[T::ID] }
+    pub fn refr<T: GlobalParamKey>(&self) -> &U256 {
+        self.observe(T::ID);
+        &self.values[T::ID]
+    }
 
     /// Get the mutable reference of a variable
-    pub fn val<T: GlobalParamKey>(&mut self) -> &mut U256 { &mut self.0[T::ID] }
+    pub fn val<T: GlobalParamKey>(&mut self) -> &mut U256 {
+        self.observe(T::ID);
+        &mut self.values[T::ID]
+    }
+
+    /// Increase a variable without observing its value, so that the increases
+    /// in speculative forks commute.
+    pub fn add<T: GlobalParamKey>(&mut self, v: U256) {
+        self.values[T::ID] += v;
+    }
+
+    /// Decrease a variable without observing its value, so that the decreases
+    /// in speculative forks commute.
+    pub fn sub<T: GlobalParamKey>(&mut self, v: U256) {
+        self.values[T::ID] -= v;
+    }
+
+    fn observe(&self, id: usize) {
+        if let Some(access) = &self.access {
+            access.observed.fetch_or(1 << id, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Speculative execution support.
+impl GlobalStat {
+    /// Make a copy which records the accesses to the variables.
+    pub fn fork(&self) -> Self {
+        GlobalStat {
+            values: self.values,
+            access: Some(Arc::new(GlobalStatAccess {
+                initial: self.values,
+                observed: AtomicU32::new(0),
+            })),
+        }
+    }
+
+    /// Bit mask of the variables observed by a fork.
+    pub fn observed(&self) -> u32 {
+        self.access
+            .as_ref()
+            .map_or(0, |access| access.observed.load(Ordering::Relaxed))
+    }
+
+    /// Apply the changes made in a fork, returning the bit mask of the changed
+    /// variables. The changes are applied as deltas, which equals to taking
+    /// the values of the fork for the variables not changed since the fork is
+    /// made.
+    pub fn merge_fork(&mut self, fork: &GlobalStat) -> u32 {
+        let initial = &fork
+            .access
+            .as_ref()
+            .expect("Only a fork can be merged")
+            .initial;
+        let mut changed = 0;
+        for id in 0..TOTAL_GLOBAL_PARAMS {
+            if fork.values[id] != initial[id] {
+                let delta = fork.values[id].overflowing_sub(initial[id]).0;
+                self.values[id] = self.values[id].overflowing_add(delta).0;
+                changed |= 1 << id;
+            }
+        }
+        changed
+    }
+
+    /// Bit mask of the variables which differ from `other`.
+    pub fn diff(&self, other: &GlobalStat) -> u32 {
+        let mut changed = 0;
+        for id in 0..TOTAL_GLOBAL_PARAMS {
+            if self.values[id] != other.values[id] {
+                changed |= 1 << id;
+            }
+        }
+        changed
+    }
 }
@@ -95,4 +95,12 @@ impl AccountEntry {
             Cached(acc, dirty_bit) => Cached(acc.clone_account(), *dirty_bit),
         }
     }
+
+    /// Clone the account with a clear dirty bit, as if it was just loaded.
+    pub fn clone_as_loaded(&self) -> AccountEntry {
+        match self {
+            DbAbsent => DbAbsent,
+            Cached(acc, _) => Cached(acc.clone_account(), false),
+        }
+    }
 }
@@ -12,7 +12,7 @@ use cfx_types::{Address, Space, U256};
 use primitives::{
     SkipInputCheck, StorageKey, StorageKeyWithSpace, StorageValue,
 };
-use std::collections::hash_map::Entry::*;
+use std::collections::{hash_map::Entry::*, BTreeSet};
 
 use super::OverlayAccount;
 
@@ -61,6 +61,26 @@ impl OverlayAccount {
         );
     }
 
+    /// The non-zero storage keys with `key_prefix`, given the entries with
+    /// the prefix in the db. The keys only written in the cache are
+    /// included, and the keys cleared in the cache are excluded.
+    pub fn storage_keys_with_prefix(
+        &self, db_entries: impl Iterator<Item = (Vec<u8>, Box<[u8]>)>,
+        key_prefix: &[u8],
+    ) -> BTreeSet<Vec<u8>> {
+        let write_cache = self.storage_write_cache.read();
+        let mut keys: BTreeSet<_> = db_entries
+            .filter_map(|(k, _)| decode_storage_key(&k))
+            .filter(|k| !write_cache.contains_key(k))
+            .collect();
+        for (k, v) in write_cache.iter() {
+            if k.starts_with(key_prefix) && !v.value.is_zero() {
+                keys.insert(k.clone());
+            }
+        }
+        keys
+    }
+
     pub fn delete_storage_range(
         &mut self, db_deletion_log: impl Iterator<Item = (Vec<u8>, Box<[u8]>)>,
         key_prefix: &[u8], substate: &mut Substate,
@@ -44,7 +44,7 @@ impl State {
     ) -> DbResult<Option<AccountReadGuard>> {
         let mut cache = self.cache.write();
         let account_entry =
-            Self::fetch_account_mut(&mut cache, &self.db, address, require)?;
+            self.fetch_account_mut(&mut cache, address, require)?;
 
         Ok(if !account_entry.is_db_absent() {
             Some(RwLockReadGuard::map(
@@ -83,8 +83,7 @@ impl State {
         // codebase.
 
         // Load the account and insert to cache
-        let mut account_entry =
-            AccountEntry::new_loaded(self.db.get_account(address)?);
+        let mut account_entry = self.load_account(address)?;
         Self::load_account_ext_fields(require, &mut account_entry, &self.db)?;
 
         self.cache.write().insert(*address, account_entry);
@@ -156,7 +155,7 @@ impl State {
     where F: Fn(&AddressWithSpace) -> DbResult<OverlayAccount> {
         let mut cache = self.cache.write();
         let account_entry =
-            Self::fetch_account_mut(&mut cache, &self.db, address, require)?;
+            self.fetch_account_mut(&mut cache, address, require)?;
 
         // Save the value before modification into the checkpoint.
         self.copy_cache_entry_to_checkpoint(*address, account_entry);
@@ -181,20 +180,35 @@ impl State {
     /// Retrieves data using a read-through caching strategy and automatically
     /// loads extension fields as required.
     fn fetch_account_mut<'a>(
-        cache: &'a mut HashMap<AddressWithSpace, AccountEntry>, db: &StateDb,
+        &self, cache: &'a mut HashMap<AddressWithSpace, AccountEntry>,
         address: &AddressWithSpace, require: RequireFields,
     ) -> DbResult<&'a mut AccountEntry> {
         let account_entry = match cache.entry(*address) {
             Occupied(e) => e.into_mut(),
             Vacant(e) => {
                 let address = *e.key();
-                e.insert(AccountEntry::new_loaded(db.get_account(&address)?))
+                e.insert(self.load_account(&address)?)
             }
         };
-        Self::load_account_ext_fields(require, account_entry, db)?;
+        Self::load_account_ext_fields(require, account_entry, &self.db)?;
         Ok(account_entry)
     }
 
+    /// Load an account entry which is not in the cache. A speculative fork
+    /// takes a clean copy of the entry in the forked state if there is one.
+    fn load_account(
+        &self, address: &AddressWithSpace,
+    ) -> DbResult<AccountEntry> {
+        if let Some(entry) = self
+            .speculative_base
+            .as_ref()
+            .and_then(|base| base.get(address))
+        {
+            return Ok(entry.clone_as_loaded());
+        }
+        Ok(AccountEntry::new_loaded(self.db.get_account(address)?))
+    }
+
     /// Load required extension fields of an account as required.
     fn load_account_ext_fields(
         require: RequireFields, account_entry: &mut AccountEntry, db: &StateDb,
@@ -53,7 +53,7 @@ impl State {
     /// the creation of the next checkpoint.
     pub(crate) fn checkpoint(&mut self) -> usize {
         self.checkpoints.get_mut().push_checkpoint(CheckpointLayer {
-            global_stat: self.global_stat,
+            global_stat: self.global_stat.clone(),
             entries: HashMap::new(),
         })
     }
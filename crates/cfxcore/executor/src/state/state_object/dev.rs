@@ -0,0 +1,90 @@
+use super::State;
+use crate::{state::CleanupMode, substate::Substate};
+use cfx_bytes::Bytes;
+use cfx_statedb::Result as DbResult;
+use cfx_types::{Address, AddressWithSpace, Space, H256, U256};
+use primitives::storage::STORAGE_LAYOUT_REGULAR_V0;
+
+/// A direct change of an account made by the dev mode RPCs, bypassing the
+/// transaction execution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DevStateChange {
+    Balance {
+        address: AddressWithSpace,
+        balance: U256,
+    },
+    Code {
+        address: AddressWithSpace,
+        code: Bytes,
+    },
+    Storage {
+        address: AddressWithSpace,
+        key: H256,
+        value: U256,
+    },
+}
+
+impl State {
+    pub fn apply_dev_state_change(
+        &mut self, change: &DevStateChange,
+    ) -> DbResult<()> {
+        match change {
+            DevStateChange::Balance { address, balance } => {
+                self.dev_set_balance(address, *balance)
+            }
+            DevStateChange::Code { address, code } => {
+                if !self.exists(address)? {
+                    self.new_contract_with_admin(
+                        address,
+                        &Address::zero(),
+                        U256::zero(),
+                        Some(STORAGE_LAYOUT_REGULAR_V0),
+                        false, /* cip107 */
+                    )?;
+                }
+                self.init_code(address, code.clone(), Address::zero())
+            }
+            DevStateChange::Storage {
+                address,
+                key,
+                value,
+            } => {
+                let old_value =
+                    self.storage_entry_at(address, key.as_bytes())?;
+                // The entry is written without an owner, so no storage
+                // collateral is charged for it.
+                self.write_account_or_new_lock(address)?.set_storage(
+                    key.as_bytes().to_vec(),
+                    *value,
+                    old_value,
+                    Address::zero(),
+                    &mut Substate::new(),
+                )
+            }
+        }
+    }
+
+    /// Sets the balance of an account and keeps the total issued tokens
+    /// consistent with it.
+    fn dev_set_balance(
+        &mut self, address: &AddressWithSpace, balance: U256,
+    ) -> DbResult<()> {
+        let current = self.balance(address)?;
+        if balance > current {
+            let delta = balance - current;
+            self.add_balance(address, &delta, CleanupMode::ForceCreate)?;
+            self.add_total_issued(delta);
+            if address.space == Space::Ethereum {
+                self.add_total_evm_tokens(delta);
+            }
+        } else if balance < current {
+            let delta = current - balance;
+            self.sub_balance(address, &delta, &mut CleanupMode::NoEmpty)?;
+            self.sub_total_issued(delta);
+            if address.space == Space::Ethereum {
+                self.sub_total_evm_tokens(delta);
+            }
+        }
+        Ok(())
+    }
+}
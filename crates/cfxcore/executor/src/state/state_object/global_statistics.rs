@@ -12,7 +12,7 @@ impl State {
 
     /// Maintain `total_issued_tokens`.
     pub fn add_total_issued(&mut self, v: U256) {
-        *self.global_stat.val::<TotalIssued>() += v;
+        self.global_stat.add::<TotalIssued>(v);
     }
 
     /// Maintain `total_issued_tokens`. This is only used in the extremely
@@ -23,11 +23,11 @@ impl State {
     }
 
     pub fn add_total_pos_staking(&mut self, v: U256) {
-        *self.global_stat.val::<TotalPosStaking>() += v;
+        self.global_stat.add::<TotalPosStaking>(v);
     }
 
     pub fn add_total_evm_tokens(&mut self, v: U256) {
-        *self.global_stat.val::<TotalEvmToken>() += v;
+        self.global_stat.add::<TotalEvmToken>(v);
     }
 
     pub fn sub_total_evm_tokens(&mut self, v: U256) {
@@ -74,11 +74,11 @@ impl State {
     pub fn add_converted_storage_point(
         &mut self, from_balance: U256, from_collateral: U256,
     ) {
-        *self.global_stat.val::<TotalIssued>() -=
-            from_balance + from_collateral;
-        *self.global_stat.val::<TotalStorage>() -= from_collateral;
-        *self.global_stat.val::<UsedStoragePoints>() += from_collateral;
-        *self.global_stat.val::<ConvertedStoragePoints>() +=
-            from_balance + from_collateral;
+        self.global_stat
+            .sub::<TotalIssued>(from_balance + from_collateral);
+        self.global_stat.sub::<TotalStorage>(from_collateral);
+        self.global_stat.add::<UsedStoragePoints>(from_collateral);
+        self.global_stat
+            .add::<ConvertedStoragePoints>(from_balance + from_collateral);
     }
 }
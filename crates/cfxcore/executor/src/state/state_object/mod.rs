@@ -24,6 +24,9 @@ mod collateral;
 /// Implements functions for committing `State` changes to db.
 mod commit;
 
+/// Implements the direct state changes made by the dev mode RPCs.
+mod dev;
+
 /// Implements access functions global statistic variables of `State`.
 mod global_statistics;
 
@@ -43,14 +46,20 @@ mod storage_entry;
 
 mod reward;
 
+/// Speculation: Implements the forks of `State` for executing transactions
+/// optimistically in parallel, and the validation and merging of their changes.
+mod speculation;
+
 #[cfg(test)]
 mod tests;
 
 pub use self::{
     collateral::{initialize_cip107, settle_collateral_for_all},
     commit::StateCommitResult,
+    dev::DevStateChange,
     pos::{distribute_pos_interest, update_pos_status},
     reward::initialize_cip137,
+    speculation::{Speculation, SpeculativeChanges},
     sponsor::COMMISSION_PRIVILEGE_SPECIAL_KEY,
     staking::initialize_or_update_dao_voted_params,
 };
@@ -67,7 +76,10 @@ use crate::substate::Substate;
 use cfx_statedb::{Result as DbResult, StateDbExt, StateDbGeneric as StateDb};
 use cfx_types::AddressWithSpace;
 use parking_lot::RwLock;
-use std::collections::{BTreeSet, HashMap};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
 
 /// A caching and checkpoint layer built upon semantically meaningful database
 /// interfaces, providing interfaces and logics for managing accounts and global
@@ -88,6 +100,10 @@ pub struct State {
 
     /// Checkpoint layers for the account entries
     checkpoints: RwLock<LazyDiscardedVec<CheckpointLayer>>,
+
+    /// Only set in a speculative fork: the account entries of the forked
+    /// state, which are read before the backend database.
+    speculative_base: Option<Arc<HashMap<AddressWithSpace, AccountEntry>>>,
 }
 
 impl State {
@@ -106,6 +122,7 @@ impl State {
             cache: Default::default(),
             checkpoints: Default::default(),
             global_stat: world_stat,
+            speculative_base: None,
         })
     }
 
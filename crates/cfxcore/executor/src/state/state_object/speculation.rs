@@ -0,0 +1,124 @@
+//! Speculation: Implements the forks of `State` for executing transactions
+//! optimistically in parallel, and the validation and merging of their
+//! changes.
+//!
+//! During a speculation, the account entries of the state are frozen into a
+//! shared base. A fork reads the accounts through the base before its own
+//! database, which must be a view of the same committed state as the database
+//! of the forked state. The changes of a fork are valid as long as none of the
+//! accounts and global statistic variables it has observed are changed by the
+//! changes merged after the fork is made.
+
+use super::{AccountEntry, State};
+use crate::state::global_stat::GlobalStat;
+use cfx_statedb::StateDbGeneric as StateDb;
+use cfx_types::{AddressWithSpace, U256};
+use std::{
+    collections::{hash_map::Entry::Vacant, HashMap, HashSet},
+    mem,
+    sync::Arc,
+};
+
+/// A speculation over a `State`, which restores the state when dropped.
+pub struct Speculation<'a> {
+    state: &'a mut State,
+    base: Arc<HashMap<AddressWithSpace, AccountEntry>>,
+    /// Accounts changed by the merged changes.
+    written_accounts: HashSet<AddressWithSpace>,
+    /// Bit mask of the global statistic variables changed by the merged
+    /// changes.
+    written_params: u32,
+}
+
+/// The changes made in a fork.
+pub struct SpeculativeChanges {
+    cache: HashMap<AddressWithSpace, AccountEntry>,
+    global_stat: GlobalStat,
+}
+
+impl State {
+    /// Start a speculation. The state must not have checkpoints.
+    pub fn speculate(&mut self) -> Speculation {
+        assert!(self.no_checkpoint());
+        assert!(self.speculative_base.is_none());
+        let base = Arc::new(mem::take(self.cache.get_mut()));
+        Speculation {
+            state: self,
+            base,
+            written_accounts: HashSet::new(),
+            written_params: 0,
+        }
+    }
+
+    /// Finish a fork, returning its changes and its database for reuse.
+    pub fn into_speculative_changes(self) -> (SpeculativeChanges, StateDb) {
+        assert!(self.no_checkpoint());
+        assert!(self.speculative_base.is_some());
+        let changes = SpeculativeChanges {
+            cache: self.cache.into_inner(),
+            global_stat: self.global_stat,
+        };
+        (changes, self.db)
+    }
+}
+
+impl<'a> Speculation<'a> {
+    /// Make a fork of the current state with `db`, which must be a view of
+    /// the committed state of the database of the speculated state.
+    pub fn fork(&self, db: StateDb) -> State {
+        State {
+            db,
+            cache: Default::default(),
+            global_stat: self.state.global_stat.fork(),
+            checkpoints: Default::default(),
+            speculative_base: Some(self.base.clone()),
+        }
+    }
+
+    /// Check whether the changes are still valid, i.e., nothing observed by
+    /// the fork has been changed by the changes merged after the fork is made.
+    ///
+    /// Every account in the cache of the fork is taken as observed.
+    pub fn validate(&self, changes: &SpeculativeChanges) -> bool {
+        changes.global_stat.observed() & self.written_params == 0
+            && changes
+                .cache
+                .keys()
+                .all(|address| !self.written_accounts.contains(address))
+    }
+
+    /// Merge the changes of a fork. The forks made before must have been
+    /// dropped.
+    pub fn merge(&mut self, changes: SpeculativeChanges) {
+        let base = Arc::get_mut(&mut self.base)
+            .expect("All the forks have been dropped");
+        for (address, entry) in changes.cache {
+            if entry.is_dirty() {
+                base.insert(address, entry);
+                self.written_accounts.insert(address);
+            } else if let Vacant(e) = base.entry(address) {
+                // Keep the loaded entry like a sequential execution does.
+                e.insert(entry);
+            }
+        }
+        self.written_params |=
+            self.state.global_stat.merge_fork(&changes.global_stat);
+    }
+
+    /// Burn the fee of a merged transaction as in `State::burn_by_cip1559`.
+    pub fn burn_by_cip1559(&mut self, by: U256) {
+        let before = self.state.global_stat.clone();
+        self.state.burn_by_cip1559(by);
+        self.written_params |= self.state.global_stat.diff(&before);
+    }
+}
+
+impl<'a> Drop for Speculation<'a> {
+    fn drop(&mut self) {
+        let base = mem::take(&mut self.base);
+        *self.state.cache.get_mut() =
+            Arc::try_unwrap(base).unwrap_or_else(|base| {
+                base.iter().map(|(k, v)| (*k, v.clone_account())).collect()
+            });
+    }
+}
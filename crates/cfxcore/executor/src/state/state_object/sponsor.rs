@@ -5,7 +5,8 @@ use cfx_statedb::{
     Result as DbResult,
 };
 use cfx_types::{
-    maybe_address, Address, AddressSpaceUtil, AddressWithSpace, U256,
+    maybe_address, Address, AddressSpaceUtil, AddressWithSpace, BigEndianHash,
+    H256, U256,
 };
 use primitives::{SponsorInfo, StorageKey};
 
@@ -113,9 +114,10 @@ impl State {
             .write_native_account_lock(&address)?
             .set_sponsor_for_collateral(sponsor, sponsor_balance, prop);
 
-        *self.global_stat.val::<TotalIssued>() -= converted_storage_points;
-        *self.global_stat.val::<ConvertedStoragePoints>() +=
-            converted_storage_points;
+        self.global_stat
+            .sub::<TotalIssued>(converted_storage_points);
+        self.global_stat
+            .add::<ConvertedStoragePoints>(converted_storage_points);
         Ok(converted_storage_points)
     }
 
@@ -204,6 +206,226 @@ impl State {
         Ok(())
     }
 
+    // Sponsor for gas of eSpace contracts (CIP-147)
+    //
+    // The eSpace storage has no owner and occupies no collateral, but the
+    // writes still go through the substate of the caller like the Core Space
+    // ones.
+
+    pub fn evm_sponsor_for_gas(
+        &self, address: &Address,
+    ) -> DbResult<Option<Address>> {
+        let value =
+            self.evm_sponsor_entry(address, EvmSponsorField::SponsorForGas)?;
+        Ok(maybe_address(&Address::from(H256::from_uint(&value))))
+    }
+
+    pub fn set_evm_sponsor_for_gas(
+        &mut self, address: &Address, sponsor: &Address,
+        sponsor_balance: &U256, upper_bound: &U256, storage_owner: Address,
+        substate: &mut Substate,
+    ) -> DbResult<()> {
+        self.set_evm_sponsor_entry(
+            address,
+            EvmSponsorField::SponsorForGas,
+            H256::from(*sponsor).into_uint(),
+            storage_owner,
+            substate,
+        )?;
+        self.set_evm_sponsor_entry(
+            address,
+            EvmSponsorField::SponsorBalanceForGas,
+            *sponsor_balance,
+            storage_owner,
+            substate,
+        )?;
+        self.set_evm_sponsor_entry(
+            address,
+            EvmSponsorField::SponsorGasBound,
+            *upper_bound,
+            storage_owner,
+            substate,
+        )
+    }
+
+    pub fn evm_sponsor_balance_for_gas(
+        &self, address: &Address,
+    ) -> DbResult<U256> {
+        self.evm_sponsor_entry(address, EvmSponsorField::SponsorBalanceForGas)
+    }
+
+    pub fn add_evm_sponsor_balance_for_gas(
+        &mut self, address: &Address, by: &U256, storage_owner: Address,
+        substate: &mut Substate,
+    ) -> DbResult<()> {
+        return_if!(by.is_zero());
+
+        let balance = self.evm_sponsor_balance_for_gas(address)?;
+        self.set_evm_sponsor_entry(
+            address,
+            EvmSponsorField::SponsorBalanceForGas,
+            balance + by,
+            storage_owner,
+            substate,
+        )
+    }
+
+    pub fn sub_evm_sponsor_balance_for_gas(
+        &mut self, address: &Address, by: &U256, storage_owner: Address,
+        substate: &mut Substate,
+    ) -> DbResult<()> {
+        return_if!(by.is_zero());
+
+        let balance = self.evm_sponsor_balance_for_gas(address)?;
+        assert!(balance >= *by);
+        self.set_evm_sponsor_entry(
+            address,
+            EvmSponsorField::SponsorBalanceForGas,
+            balance - by,
+            storage_owner,
+            substate,
+        )
+    }
+
+    pub fn evm_sponsor_gas_bound(&self, address: &Address) -> DbResult<U256> {
+        self.evm_sponsor_entry(address, EvmSponsorField::SponsorGasBound)
+    }
+
+    /// The admin of an eSpace contract, who manages its whitelist. It is the
+    /// original sender of the contract creation.
+    pub fn evm_contract_admin(&self, address: &Address) -> DbResult<Address> {
+        let value = self.evm_sponsor_entry(address, EvmSponsorField::Admin)?;
+        Ok(Address::from(H256::from_uint(&value)))
+    }
+
+    pub fn set_evm_contract_admin(
+        &mut self, address: &Address, admin: &Address, storage_owner: Address,
+        substate: &mut Substate,
+    ) -> DbResult<()> {
+        self.set_evm_sponsor_entry(
+            address,
+            EvmSponsorField::Admin,
+            H256::from(*admin).into_uint(),
+            storage_owner,
+            substate,
+        )
+    }
+
+    pub fn check_evm_contract_whitelist(
+        &self, contract_address: &Address, user: &Address,
+    ) -> DbResult<bool> {
+        let special_value = self.storage_at(
+            &evm_sponsor_address(),
+            &special_sponsor_key(contract_address),
+        )?;
+        if !special_value.is_zero() {
+            Ok(true)
+        } else {
+            self.storage_at(
+                &evm_sponsor_address(),
+                &sponsor_key(contract_address, user),
+            )
+            .map(|x| !x.is_zero())
+        }
+    }
+
+    pub fn add_to_evm_contract_whitelist(
+        &mut self, contract_address: Address, storage_owner: Address,
+        user: Address, substate: &mut Substate,
+    ) -> DbResult<()> {
+        self.set_evm_sponsor_storage(
+            sponsor_key(&contract_address, &user),
+            COMMISSION_PRIVILEGE_STORAGE_VALUE.clone(),
+            storage_owner,
+            substate,
+        )
+    }
+
+    pub fn remove_from_evm_contract_whitelist(
+        &mut self, contract_address: Address, storage_owner: Address,
+        user: Address, substate: &mut Substate,
+    ) -> DbResult<()> {
+        self.set_evm_sponsor_storage(
+            sponsor_key(&contract_address, &user),
+            U256::zero(),
+            storage_owner,
+            substate,
+        )
+    }
+
+    /// Clear the sponsor information, the admin and the whitelist of a killed
+    /// eSpace contract. All of their keys start with the contract address.
+    pub fn clear_evm_sponsor_entries(
+        &mut self, contract_address: &Address, substate: &mut Substate,
+    ) -> DbResult<()> {
+        let address = evm_sponsor_address();
+        let storage_key_prefix = StorageKey::new_storage_key(
+            &address.address,
+            contract_address.as_ref(),
+        )
+        .with_evm_space();
+        let db_entries = self
+            .db
+            .delete_all::<access_mode::Read>(storage_key_prefix, None)?
+            .into_iter();
+        let keys = match self.read_account_lock(&address)? {
+            Some(account) => account.storage_keys_with_prefix(
+                db_entries,
+                contract_address.as_ref(),
+            ),
+            None => return Ok(()),
+        };
+        for key in keys {
+            self.set_evm_sponsor_storage(
+                key,
+                U256::zero(),
+                Address::zero(),
+                substate,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn evm_sponsor_entry(
+        &self, address: &Address, field: EvmSponsorField,
+    ) -> DbResult<U256> {
+        self.storage_at(
+            &evm_sponsor_address(),
+            &evm_sponsor_info_key(address, field),
+        )
+    }
+
+    fn set_evm_sponsor_entry(
+        &mut self, address: &Address, field: EvmSponsorField, value: U256,
+        storage_owner: Address, substate: &mut Substate,
+    ) -> DbResult<()> {
+        self.set_evm_sponsor_storage(
+            evm_sponsor_info_key(address, field),
+            value,
+            storage_owner,
+            substate,
+        )
+    }
+
+    fn set_evm_sponsor_storage(
+        &mut self, key: Vec<u8>, value: U256, storage_owner: Address,
+        substate: &mut Substate,
+    ) -> DbResult<()> {
+        let address = evm_sponsor_address();
+        let old_value = self.storage_entry_at(&address, &key)?;
+        return_if!(old_value.value == value);
+
+        // The account may not exist before the first sponsorship entry is
+        // written.
+        self.write_account_or_new_lock(&address)?.set_storage(
+            key,
+            value,
+            old_value,
+            storage_owner,
+            substate,
+        )
+    }
+
     pub fn record_storage_and_whitelist_entries_release(
         &mut self, address: &Address, substate: &mut Substate, cip131: bool,
     ) -> DbResult<()> {
@@ -238,6 +460,31 @@ fn sponsor_address() -> AddressWithSpace {
     SPONSOR_WHITELIST_CONTROL_CONTRACT_ADDRESS.with_native_space()
 }
 
+/// The eSpace account which keeps the sponsor information and whitelists of
+/// eSpace contracts.
+#[inline]
+fn evm_sponsor_address() -> AddressWithSpace {
+    SPONSOR_WHITELIST_CONTROL_CONTRACT_ADDRESS.with_evm_space()
+}
+
+#[derive(Clone, Copy)]
+#[repr(u8)]
+enum EvmSponsorField {
+    SponsorForGas = 0,
+    SponsorBalanceForGas = 1,
+    SponsorGasBound = 2,
+    Admin = 3,
+}
+
+/// The sponsor information keys have 21 bytes, so they never collide with the
+/// 40-bytes whitelist keys.
+fn evm_sponsor_info_key(contract: &Address, field: EvmSponsorField) -> Vec<u8> {
+    let mut key = Vec::with_capacity(Address::len_bytes() + 1);
+    key.extend_from_slice(contract.as_bytes());
+    key.push(field as u8);
+    key
+}
+
 #[inline]
 fn sponsor_key(contract: &Address, user: &Address) -> Vec<u8> {
     let mut key = Vec::with_capacity(Address::len_bytes() * 2);
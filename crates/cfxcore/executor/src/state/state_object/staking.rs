@@ -103,7 +103,7 @@ impl State {
             current_block_number,
             cip_97,
         );
-        *self.global_stat.val::<TotalStaking>() += *amount;
+        self.global_stat.add::<TotalStaking>(*amount);
         Ok(())
     }
 
@@ -123,7 +123,7 @@ impl State {
 
         // the interest will be put in balance.
         self.add_total_issued(interest);
-        *self.global_stat.val::<TotalStaking>() -= *amount;
+        self.global_stat.sub::<TotalStaking>(*amount);
         Ok(interest)
     }
 }
@@ -1025,6 +1025,161 @@ fn create_contract_fail_previous_storage() {
     // TODO(69): checking ownership
 }
 
+#[test]
+fn evm_sponsor_balance_for_gas() {
+    let mut state = get_state_for_genesis_write();
+    let mut substate = Substate::new();
+    let contract = Address::random();
+    let sponsor = Address::random();
+
+    state
+        .set_evm_sponsor_for_gas(
+            &contract,
+            &sponsor,
+            &U256::from(1000),
+            &U256::from(10),
+            sponsor,
+            &mut substate,
+        )
+        .unwrap();
+    state
+        .sub_evm_sponsor_balance_for_gas(
+            &contract,
+            &U256::from(300),
+            sponsor,
+            &mut substate,
+        )
+        .unwrap();
+    state
+        .add_evm_sponsor_balance_for_gas(
+            &contract,
+            &U256::from(100),
+            sponsor,
+            &mut substate,
+        )
+        .unwrap();
+    assert_eq!(state.evm_sponsor_for_gas(&contract).unwrap(), Some(sponsor));
+    assert_eq!(
+        state.evm_sponsor_balance_for_gas(&contract).unwrap(),
+        U256::from(800)
+    );
+    assert_eq!(
+        state.evm_sponsor_gas_bound(&contract).unwrap(),
+        U256::from(10)
+    );
+    // The eSpace storage occupies no collateral.
+    assert!(substate.keys_for_collateral_changed().is_empty());
+}
+
+#[test]
+#[should_panic]
+fn evm_sponsor_balance_for_gas_underflow() {
+    let mut state = get_state_for_genesis_write();
+    let mut substate = Substate::new();
+    let contract = Address::random();
+    let sponsor = Address::random();
+
+    state
+        .set_evm_sponsor_for_gas(
+            &contract,
+            &sponsor,
+            &U256::from(1000),
+            &U256::from(10),
+            sponsor,
+            &mut substate,
+        )
+        .unwrap();
+    let _ = state.sub_evm_sponsor_balance_for_gas(
+        &contract,
+        &U256::from(1001),
+        sponsor,
+        &mut substate,
+    );
+}
+
+#[test]
+fn clear_evm_sponsor_entries() {
+    let mut state = get_state_for_genesis_write();
+    let mut substate = Substate::new();
+    let contract = Address::random();
+    let other_contract = Address::random();
+    let admin = Address::random();
+    let users: Vec<_> = (0..3).map(|_| Address::random()).collect();
+
+    for address in [&contract, &other_contract] {
+        state
+            .set_evm_sponsor_for_gas(
+                address,
+                &admin,
+                &U256::from(1000),
+                &U256::from(10),
+                admin,
+                &mut substate,
+            )
+            .unwrap();
+        state
+            .set_evm_contract_admin(address, &admin, admin, &mut substate)
+            .unwrap();
+        state
+            .add_to_evm_contract_whitelist(
+                *address,
+                admin,
+                users[0],
+                &mut substate,
+            )
+            .unwrap();
+    }
+    let epoch_id: EpochId = BigEndianHash::from_uint(&U256::from(1));
+    state.commit_for_test(epoch_id).unwrap();
+
+    // The entries are both in the db and in the cache.
+    let mut state = get_state_by_epoch_id(&epoch_id);
+    state
+        .add_to_evm_contract_whitelist(contract, admin, users[1], &mut substate)
+        .unwrap();
+    state
+        .add_to_evm_contract_whitelist(contract, admin, users[2], &mut substate)
+        .unwrap();
+    state
+        .remove_from_evm_contract_whitelist(
+            contract,
+            admin,
+            users[2],
+            &mut substate,
+        )
+        .unwrap();
+    state
+        .clear_evm_sponsor_entries(&contract, &mut substate)
+        .unwrap();
+
+    assert_eq!(state.evm_sponsor_for_gas(&contract).unwrap(), None);
+    assert_eq!(
+        state.evm_sponsor_balance_for_gas(&contract).unwrap(),
+        U256::zero()
+    );
+    assert_eq!(
+        state.evm_sponsor_gas_bound(&contract).unwrap(),
+        U256::zero()
+    );
+    assert_eq!(
+        state.evm_contract_admin(&contract).unwrap(),
+        Address::zero()
+    );
+    for user in &users {
+        assert!(!state.check_evm_contract_whitelist(&contract, user).unwrap());
+    }
+
+    // The entries of the other contract are kept.
+    assert_eq!(
+        state.evm_sponsor_for_gas(&other_contract).unwrap(),
+        Some(admin)
+    );
+    assert_eq!(state.evm_contract_admin(&other_contract).unwrap(), admin);
+    assert!(state
+        .check_evm_contract_whitelist(&other_contract, &users[0])
+        .unwrap());
+}
+
 #[test]
 fn test_automatic_collateral_normal_account() {
     let mut state = get_state_for_genesis_write();
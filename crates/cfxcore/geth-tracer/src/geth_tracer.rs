@@ -1,6 +1,7 @@
 use crate::{
     config::TracingInspectorConfig,
     fourbyte::FourByteInspector,
+    parity_builder::ParityTraceBuilder,
     tracing_inspector::TracingInspector,
     types::{LogCallOrder, TxExecContext},
     utils::{to_alloy_address, to_alloy_h256, to_alloy_u256},
@@ -49,7 +50,6 @@ impl GethTracer {
         tx_exec_context: TxExecContext, machine: Arc<Machine>,
         opts: GethDebugTracingOptions,
     ) -> Self {
-        let TxExecContext { tx_gas_limit, .. } = tx_exec_context;
         let config = match opts.tracer {
             Some(GethDebugTracerType::BuiltInTracer(builtin_tracer)) => {
                 match builtin_tracer {
@@ -80,6 +80,14 @@ impl GethTracer {
             None => TracingInspectorConfig::from_geth_config(&opts.config),
         };
 
+        Self::with_config(tx_exec_context, machine, opts, config)
+    }
+
+    pub(crate) fn with_config(
+        tx_exec_context: TxExecContext, machine: Arc<Machine>,
+        opts: GethDebugTracingOptions, config: TracingInspectorConfig,
+    ) -> Self {
+        let TxExecContext { tx_gas_limit, .. } = tx_exec_context;
         Self {
             inner: TracingInspector::new(config, machine, tx_exec_context),
             fourbyte_inspector: FourByteInspector::new(),
@@ -120,6 +128,10 @@ impl GethTracer {
 
     pub fn gas_used(&self) -> u64 { self.tx_gas_limit - self.gas_left }
 
+    pub(crate) fn into_parity_builder(self) -> ParityTraceBuilder {
+        self.inner.into_parity_builder()
+    }
+
     pub fn drain(self) -> GethTrace {
         let trace = match self.tracer_type() {
             Some(t) => match t {
@@ -5,16 +5,23 @@ mod fourbyte;
 mod gas;
 mod geth_builder;
 mod geth_tracer;
+mod parity_builder;
+mod parity_tracer;
 mod tracing_inspector;
 mod types;
 mod utils;
+mod vm_trace;
 
 use arena::CallTraceArena;
 use config::TracingInspectorConfig;
 use geth_builder::GethTraceBuilder;
+use parity_builder::ParityTraceBuilder;
 
 pub use geth_tracer::{GethTraceKey, GethTracer};
-pub use types::{GethTraceWithHash, TxExecContext};
+pub use parity_tracer::{
+    ParityTrace, ParityTraceKey, ParityTracer, StateSnapshot, TouchedState,
+};
+pub use types::{GethTraceWithHash, ParityTraceWithHash, TxExecContext};
 pub use utils::{
     from_alloy_address, to_alloy_address, to_alloy_h256, to_alloy_u256,
 };
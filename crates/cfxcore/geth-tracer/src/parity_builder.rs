@@ -0,0 +1,204 @@
+// Copyright 2023-2024 Paradigm.xyz
+// This file is part of reth.
+// Reth is a modular, contributor-friendly and blazing-fast implementation of
+// the Ethereum protocol
+
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+use crate::{
+    config::TraceStyle,
+    types::{CallKind, CallTraceNode},
+};
+use alloy_primitives::{Bytes, U64};
+use alloy_rpc_types_trace::parity::{
+    Action, CallAction, CallOutput, CallType, CreateAction, CreateOutput,
+    SelfdestructAction, TraceOutput, TransactionTrace,
+};
+
+/// A type for creating parity style traces
+///
+/// Note that the state diff and the vm trace are not built from the call
+/// trace arena, see [ParityTracer](crate::ParityTracer).
+#[derive(Clone, Debug)]
+pub struct ParityTraceBuilder {
+    /// Recorded trace nodes
+    nodes: Vec<CallTraceNode>,
+}
+
+impl ParityTraceBuilder {
+    /// Returns a new instance of the builder
+    pub fn new(nodes: Vec<CallTraceNode>) -> Self { Self { nodes } }
+
+    /// Returns the output of the root call.
+    pub fn output(&self) -> Bytes {
+        self.nodes
+            .first()
+            .map(|node| node.trace.output.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns an iterator over all nodes to trace
+    ///
+    /// This excludes nodes that represent calls to precompiles.
+    fn iter_traceable_nodes(&self) -> impl Iterator<Item = &CallTraceNode> {
+        self.nodes.iter().filter(|node| !node.is_precompile())
+    }
+
+    /// Returns the trace addresses of all call nodes in the set
+    ///
+    /// Each entry in the returned vector represents the [Self::trace_address]
+    /// of the corresponding node in the nodes set.
+    fn trace_address(&self, idx: usize) -> Vec<usize> {
+        let mut graph = vec![];
+        let mut node = &self.nodes[idx];
+        if node.is_precompile() {
+            return graph;
+        }
+        while let Some(parent) = node.parent {
+            // the index of the child call in the arena
+            let child_idx = self.nodes[parent]
+                .children
+                .iter()
+                .position(|child| *child == node.idx)
+                .expect("child exists in parent");
+            graph.push(child_idx);
+            node = &self.nodes[parent];
+        }
+        graph.reverse();
+        graph
+    }
+
+    /// Returns the parity call traces of the transaction.
+    pub fn into_transaction_traces(self) -> Vec<TransactionTrace> {
+        let mut traces = Vec::with_capacity(self.nodes.len());
+        for node in self.iter_traceable_nodes() {
+            let trace_address = self.trace_address(node.idx);
+            traces.push(parity_transaction_trace(node, trace_address));
+
+            if node.is_selfdestruct() {
+                // selfdestructs are not recorded as individual call traces but
+                // are derived from the call trace and are added as additional
+                // `TransactionTrace` objects in the trace array
+                let addr = {
+                    let last = traces.last_mut().expect("exists");
+                    let mut addr = last.trace_address.clone();
+                    addr.push(last.subtraces);
+                    // need to account for the additional selfdestruct trace
+                    last.subtraces += 1;
+                    addr
+                };
+                if let Some(trace) = parity_selfdestruct_trace(node, addr) {
+                    traces.push(trace);
+                }
+            }
+        }
+        traces
+    }
+}
+
+/// Converts this node into a parity `TransactionTrace`
+fn parity_transaction_trace(
+    node: &CallTraceNode, trace_address: Vec<usize>,
+) -> TransactionTrace {
+    let action = parity_action(node);
+    let result = if node.trace.is_error() && !node.trace.is_revert() {
+        // if the trace is an error that is not a revert, the result is None
+        None
+    } else {
+        Some(parity_trace_output(node))
+    };
+    let error = node.trace.as_error_msg(TraceStyle::Parity);
+    TransactionTrace {
+        action,
+        error,
+        result,
+        trace_address,
+        subtraces: node.children.len(),
+    }
+}
+
+/// Returns the `Output` for a parity trace
+fn parity_trace_output(node: &CallTraceNode) -> TraceOutput {
+    if node.kind().is_any_create() {
+        TraceOutput::Create(CreateOutput {
+            gas_used: U64::from(node.trace.gas_used),
+            code: node.trace.output.clone(),
+            address: node.trace.address,
+        })
+    } else {
+        TraceOutput::Call(CallOutput {
+            gas_used: U64::from(node.trace.gas_used),
+            output: node.trace.output.clone(),
+        })
+    }
+}
+
+/// If the trace is a selfdestruct, returns the `TransactionTrace` for it.
+fn parity_selfdestruct_trace(
+    node: &CallTraceNode, trace_address: Vec<usize>,
+) -> Option<TransactionTrace> {
+    let refund_address = node.trace.selfdestruct_refund_target?;
+    Some(TransactionTrace {
+        action: Action::Selfdestruct(SelfdestructAction {
+            address: node.trace.address,
+            refund_address,
+            balance: node.trace.value,
+        }),
+        error: None,
+        result: None,
+        trace_address,
+        subtraces: 0,
+    })
+}
+
+/// Returns the `Action` for a parity trace
+fn parity_action(node: &CallTraceNode) -> Action {
+    if node.kind().is_any_create() {
+        Action::Create(CreateAction {
+            from: node.trace.caller,
+            value: node.trace.value,
+            gas: U64::from(node.trace.gas_limit),
+            init: node.trace.data.clone(),
+        })
+    } else {
+        Action::Call(CallAction {
+            from: node.trace.caller,
+            to: node.trace.address,
+            value: node.trace.value,
+            gas: U64::from(node.trace.gas_limit),
+            input: node.trace.data.clone(),
+            call_type: parity_call_type(node.kind()),
+        })
+    }
+}
+
+fn parity_call_type(kind: CallKind) -> CallType {
+    match kind {
+        CallKind::Call => CallType::Call,
+        CallKind::StaticCall => CallType::StaticCall,
+        CallKind::CallCode => CallType::CallCode,
+        CallKind::DelegateCall => CallType::DelegateCall,
+        CallKind::Create | CallKind::Create2 => CallType::None,
+    }
+}
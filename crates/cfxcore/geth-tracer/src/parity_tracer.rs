@@ -0,0 +1,372 @@
+use crate::{
+    config::TracingInspectorConfig,
+    geth_tracer::GethTracer,
+    types::TxExecContext,
+    utils::{to_alloy_address, to_alloy_u256},
+    vm_trace::VmTraceRecorder,
+};
+use alloy_primitives::{Bytes, B256, U64};
+use alloy_rpc_types_trace::{
+    geth::GethDebugTracingOptions,
+    parity::{
+        AccountDiff, ChangedType, Delta, StateDiff, TraceResults, TraceType,
+    },
+};
+use cfx_executor::{
+    machine::Machine,
+    observer::{
+        AddressPocket, CallTracer, CheckpointTracer, DrainTrace,
+        InternalTransferTracer, OpcodeTracer, StorageTracer,
+    },
+    stack::{FrameResult, FrameReturn},
+    state::State,
+};
+use cfx_statedb::Result as DbResult;
+use cfx_types::{
+    Address as CfxAddress, AddressSpaceUtil, AddressWithSpace, Space, U256,
+};
+use cfx_vm_types::{ActionParams, InterpreterInfo};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    sync::Arc,
+};
+
+/// Tracer for the parity style `trace_replay*` and `trace_call*` RPCs.
+///
+/// The call traces are recorded by a [GethTracer] and the vm trace by a
+/// [VmTraceRecorder]. The state diff cannot be built by an observer, so the
+/// tracer only records the touched accounts and storage, whose values before
+/// and after the transaction are compared by the caller with
+/// [TouchedState::snapshot] and [ParityTrace::set_state_diff].
+pub struct ParityTracer {
+    trace_types: HashSet<TraceType>,
+    call_tracer: GethTracer,
+    vm_tracer: Option<VmTraceRecorder>,
+    touched: TouchedState,
+    // spaces of the unfinished creates, to touch the created contracts
+    create_spaces: Vec<Space>,
+}
+
+impl ParityTracer {
+    pub fn new(
+        tx_exec_context: TxExecContext, machine: Arc<Machine>,
+        trace_types: HashSet<TraceType>,
+    ) -> Self {
+        let spec = machine
+            .spec(tx_exec_context.block_number, tx_exec_context.block_height);
+        let vm_tracer = trace_types
+            .contains(&TraceType::VmTrace)
+            .then(|| VmTraceRecorder::new(spec.cancun_opcodes));
+        let call_tracer = GethTracer::with_config(
+            tx_exec_context,
+            machine,
+            GethDebugTracingOptions::default(),
+            TracingInspectorConfig::default_parity(),
+        );
+        Self {
+            trace_types,
+            call_tracer,
+            vm_tracer,
+            touched: Default::default(),
+            create_spaces: vec![],
+        }
+    }
+
+    fn touch(&mut self, address: AddressWithSpace) {
+        if self.trace_types.contains(&TraceType::StateDiff) {
+            self.touched.accounts.entry(address).or_default();
+        }
+    }
+
+    pub fn drain(self) -> ParityTrace {
+        let builder = self.call_tracer.into_parity_builder();
+        let output = builder.output();
+        let trace = if self.trace_types.contains(&TraceType::Trace) {
+            builder.into_transaction_traces()
+        } else {
+            vec![]
+        };
+        ParityTrace {
+            results: TraceResults {
+                output,
+                trace,
+                vm_trace: self.vm_tracer.map(VmTraceRecorder::into_vm_trace),
+                state_diff: None,
+            },
+            touched: self.touched,
+        }
+    }
+}
+
+/// The trace of a transaction recorded by a [ParityTracer].
+#[derive(Debug, Clone)]
+pub struct ParityTrace {
+    pub results: TraceResults,
+    /// The accounts and storage touched by the transaction, empty if the
+    /// state diff is not requested.
+    pub touched: TouchedState,
+}
+
+impl ParityTrace {
+    pub fn set_state_diff(&mut self, pre: StateSnapshot, post: StateSnapshot) {
+        self.results.state_diff = Some(state_diff(pre, post));
+    }
+}
+
+impl DrainTrace for ParityTracer {
+    fn drain_trace(self, map: &mut typemap::ShareDebugMap) {
+        map.insert::<ParityTraceKey>(self.drain());
+    }
+}
+
+pub struct ParityTraceKey;
+
+impl typemap::Key for ParityTraceKey {
+    type Value = ParityTrace;
+}
+
+impl CheckpointTracer for ParityTracer {}
+
+impl InternalTransferTracer for ParityTracer {
+    fn trace_internal_transfer(
+        &mut self, from: AddressPocket, to: AddressPocket, _value: U256,
+    ) {
+        for pocket in [from, to] {
+            if let AddressPocket::Balance(address) = pocket {
+                self.touch(address);
+            }
+        }
+    }
+}
+
+impl StorageTracer for ParityTracer {
+    fn trace_storage_write(
+        &mut self, address: &AddressWithSpace, key: &[u8], _value: &U256,
+    ) {
+        if self.trace_types.contains(&TraceType::StateDiff) {
+            self.touched
+                .accounts
+                .entry(*address)
+                .or_default()
+                .insert(key.to_vec());
+        }
+    }
+}
+
+impl CallTracer for ParityTracer {
+    fn record_call(&mut self, params: &ActionParams) {
+        self.touch(params.sender.with_space(params.space));
+        self.touch(params.address.with_space(params.space));
+        if let Some(vm_tracer) = self.vm_tracer.as_mut() {
+            vm_tracer.start_frame(code_of(params));
+        }
+        self.call_tracer.record_call(params);
+    }
+
+    fn record_call_result(&mut self, result: &FrameResult) {
+        if let Some(vm_tracer) = self.vm_tracer.as_mut() {
+            let success = matches!(result, Ok(r) if r.apply_state);
+            vm_tracer.end_frame(U256::from(success as u8));
+        }
+        self.call_tracer.record_call_result(result);
+    }
+
+    fn record_create(&mut self, params: &ActionParams) {
+        self.touch(params.sender.with_space(params.space));
+        self.create_spaces.push(params.space);
+        if let Some(vm_tracer) = self.vm_tracer.as_mut() {
+            vm_tracer.start_frame(code_of(params));
+        }
+        self.call_tracer.record_create(params);
+    }
+
+    fn record_create_result(&mut self, result: &FrameResult) {
+        let space = self.create_spaces.pop().expect("create started");
+        let created = match result {
+            Ok(FrameReturn {
+                create_address: Some(address),
+                apply_state: true,
+                ..
+            }) => Some(*address),
+            _ => None,
+        };
+        if let Some(address) = created {
+            self.touch(address.with_space(space));
+        }
+        if let Some(vm_tracer) = self.vm_tracer.as_mut() {
+            let push = created.map_or(U256::zero(), |address| {
+                U256::from_big_endian(address.as_bytes())
+            });
+            vm_tracer.end_frame(push);
+        }
+        self.call_tracer.record_create_result(result);
+    }
+}
+
+impl OpcodeTracer for ParityTracer {
+    fn do_trace_opcode(&self, enabled: &mut bool) {
+        if self.vm_tracer.is_some() {
+            *enabled |= true;
+        }
+    }
+
+    fn initialize_interp(&mut self, gas_limit: U256) {
+        self.call_tracer.initialize_interp(gas_limit);
+    }
+
+    fn step(&mut self, interp: &dyn InterpreterInfo) {
+        if let Some(vm_tracer) = self.vm_tracer.as_mut() {
+            vm_tracer.step(interp);
+        }
+        self.call_tracer.step(interp);
+    }
+
+    fn step_end(&mut self, interp: &dyn InterpreterInfo) {
+        if let Some(vm_tracer) = self.vm_tracer.as_mut() {
+            vm_tracer.step_end(interp);
+        }
+        self.call_tracer.step_end(interp);
+    }
+
+    fn selfdestruct(
+        &mut self, contract: &CfxAddress, target: &CfxAddress, value: U256,
+    ) {
+        self.call_tracer.selfdestruct(contract, target, value);
+    }
+}
+
+fn code_of(params: &ActionParams) -> Bytes {
+    params
+        .code
+        .as_ref()
+        .map(|code| Bytes::copy_from_slice(code))
+        .unwrap_or_default()
+}
+
+/// The accounts and the storage keys touched by a transaction.
+#[derive(Debug, Clone, Default)]
+pub struct TouchedState {
+    accounts: BTreeMap<AddressWithSpace, BTreeSet<Vec<u8>>>,
+}
+
+impl TouchedState {
+    /// Read the touched accounts of `space` from `state`.
+    pub fn snapshot(
+        &self, state: &State, space: Space,
+    ) -> DbResult<StateSnapshot> {
+        let mut accounts = BTreeMap::new();
+        for (address, keys) in &self.accounts {
+            if address.space != space {
+                continue;
+            }
+            let account = if state.exists(address)? {
+                let mut storage = BTreeMap::new();
+                for key in keys {
+                    storage
+                        .insert(key.clone(), state.storage_at(address, key)?);
+                }
+                Some(AccountSnapshot {
+                    balance: state.balance(address)?,
+                    nonce: state.nonce(address)?,
+                    code: state
+                        .code(address)?
+                        .map(|code| Bytes::copy_from_slice(&code))
+                        .unwrap_or_default(),
+                    storage,
+                })
+            } else {
+                None
+            };
+            accounts.insert(address.address, account);
+        }
+        Ok(StateSnapshot { accounts })
+    }
+}
+
+/// The touched accounts read from a state, `None` for the accounts not
+/// existing.
+pub struct StateSnapshot {
+    accounts: BTreeMap<CfxAddress, Option<AccountSnapshot>>,
+}
+
+struct AccountSnapshot {
+    balance: U256,
+    nonce: U256,
+    code: Bytes,
+    storage: BTreeMap<Vec<u8>, U256>,
+}
+
+fn state_diff(pre: StateSnapshot, mut post: StateSnapshot) -> StateDiff {
+    let mut diff = StateDiff::default();
+    for (address, pre) in pre.accounts {
+        let post = post.accounts.remove(&address).flatten();
+        let (pre, post) = (pre.as_ref(), post.as_ref());
+        let account = AccountDiff {
+            balance: delta(
+                pre.map(|a| to_alloy_u256(a.balance)),
+                post.map(|a| to_alloy_u256(a.balance)),
+            ),
+            nonce: delta(
+                pre.map(|a| U64::from(a.nonce.low_u64())),
+                post.map(|a| U64::from(a.nonce.low_u64())),
+            ),
+            code: delta(
+                pre.map(|a| a.code.clone()),
+                post.map(|a| a.code.clone()),
+            ),
+            storage: storage_diff(pre, post),
+        };
+        let unchanged = matches!(account.balance, Delta::Unchanged)
+            && matches!(account.nonce, Delta::Unchanged)
+            && matches!(account.code, Delta::Unchanged)
+            && account.storage.is_empty();
+        if !unchanged {
+            diff.0.insert(to_alloy_address(address), account);
+        }
+    }
+    diff
+}
+
+fn storage_diff(
+    pre: Option<&AccountSnapshot>, post: Option<&AccountSnapshot>,
+) -> BTreeMap<B256, Delta<B256>> {
+    let keys: BTreeSet<_> = pre
+        .into_iter()
+        .chain(post)
+        .flat_map(|a| a.storage.keys())
+        .collect();
+    let mut diff = BTreeMap::new();
+    for key in keys {
+        let value = |account: Option<&AccountSnapshot>| {
+            account.map(|a| a.storage.get(key).cloned().unwrap_or_default())
+        };
+        // the storage of an account created or killed is compared with empty
+        let (from, to) = match (value(pre), value(post)) {
+            (Some(from), None) => (Some(from).filter(|v| !v.is_zero()), None),
+            (None, Some(to)) => (None, Some(to).filter(|v| !v.is_zero())),
+            values => values,
+        };
+        let delta = delta(from.map(to_b256), to.map(to_b256));
+        if !matches!(delta, Delta::Unchanged) && key.len() == 32 {
+            diff.insert(B256::from_slice(key), delta);
+        }
+    }
+    diff
+}
+
+fn delta<T: PartialEq>(from: Option<T>, to: Option<T>) -> Delta<T> {
+    match (from, to) {
+        (None, Some(to)) => Delta::Added(to),
+        (Some(from), None) => Delta::Removed(from),
+        (Some(from), Some(to)) if from != to => {
+            Delta::Changed(ChangedType { from, to })
+        }
+        _ => Delta::Unchanged,
+    }
+}
+
+fn to_b256(value: U256) -> B256 {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    B256::from(bytes)
+}
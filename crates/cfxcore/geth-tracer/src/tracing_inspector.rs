@@ -36,7 +36,8 @@ use super::{
         StorageChange, StorageChangeReason,
     },
     utils::{gas_used, stack_push_count, to_alloy_address, to_alloy_u256},
-    CallTraceArena, GethTraceBuilder, TracingInspectorConfig,
+    CallTraceArena, GethTraceBuilder, ParityTraceBuilder,
+    TracingInspectorConfig,
 };
 use cfx_types::{Space, H160};
 
@@ -151,10 +152,10 @@ impl TracingInspector {
     }
 
     /// Consumes the Inspector and returns a [ParityTraceBuilder].
-    // #[inline]
-    // pub fn into_parity_builder(self) -> ParityTraceBuilder {
-    //     ParityTraceBuilder::new(self.traces.arena, self.spec_id, self.config)
-    // }
+    #[inline]
+    pub fn into_parity_builder(self) -> ParityTraceBuilder {
+        ParityTraceBuilder::new(self.traces.arena)
+    }
 
     /// Consumes the Inspector and returns a [GethTraceBuilder].
     #[inline]
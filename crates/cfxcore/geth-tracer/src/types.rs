@@ -30,8 +30,12 @@
 //! Types for representing call trace items.
 use crate::{config::TraceStyle, utils, utils::convert_memory};
 use alloy_primitives::{Address, Bytes, LogData, U256};
-use alloy_rpc_types_trace::geth::{
-    CallFrame, CallLogFrame, GethDefaultTracingOptions, GethTrace, StructLog,
+use alloy_rpc_types_trace::{
+    geth::{
+        CallFrame, CallLogFrame, GethDefaultTracingOptions, GethTrace,
+        StructLog,
+    },
+    parity::TraceResults,
 };
 use cfx_types::{Space, H256};
 use cfx_vm_types::CallType as CfxCallType;
@@ -611,6 +615,12 @@ pub struct GethTraceWithHash {
     pub space: Space,
 }
 
+pub struct ParityTraceWithHash {
+    pub trace: TraceResults,
+    pub tx_hash: H256,
+    pub space: Space,
+}
+
 #[derive(Clone)]
 pub struct TxExecContext {
     pub tx_gas_limit: u64,
@@ -0,0 +1,197 @@
+//! Recorder of the parity style vm traces.
+//!
+//! The interpreter calls `step_end` of a call-like instruction before the
+//! sub-call starts, so a frame started right after such an instruction is its
+//! sub-trace. The values pushed by a call-like instruction are only known when
+//! the sub-call returns.
+
+use crate::utils::{stack_push_count, to_alloy_u256};
+use alloy_primitives::Bytes;
+use alloy_rpc_types_trace::parity::{
+    MemoryDelta, StorageDelta, VmExecutedOperation, VmInstruction, VmTrace,
+};
+use cfx_types::U256 as CfxU256;
+use cfx_vm_types::InterpreterInfo;
+use revm::interpreter::{opcode, OpCode};
+
+#[derive(Default)]
+pub(crate) struct VmTraceRecorder {
+    frames: Vec<VmTraceFrame>,
+    root: Option<VmTrace>,
+    cancun_opcodes: bool,
+}
+
+struct VmTraceFrame {
+    trace: VmTrace,
+    pending: Option<PendingStep>,
+    /// The index of the last instruction if it is a call-like instruction
+    /// waiting for its sub-trace. Cleared by the next step.
+    call_op: Option<usize>,
+}
+
+struct PendingStep {
+    pc: usize,
+    op: u8,
+    gas: u64,
+    mem_write: Option<(usize, usize)>,
+    store: Option<StorageDelta>,
+}
+
+impl VmTraceRecorder {
+    pub fn new(cancun_opcodes: bool) -> Self {
+        Self {
+            cancun_opcodes,
+            ..Default::default()
+        }
+    }
+
+    pub fn start_frame(&mut self, code: Bytes) {
+        self.frames.push(VmTraceFrame {
+            trace: VmTrace { code, ops: vec![] },
+            pending: None,
+            call_op: None,
+        });
+    }
+
+    /// Finish the current frame. `push` is the value pushed to the stack of
+    /// the parent frame by the call-like instruction.
+    pub fn end_frame(&mut self, push: CfxU256) {
+        let frame = match self.frames.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+        match self.frames.last_mut() {
+            Some(parent) => {
+                if let Some(idx) = parent.call_op.take() {
+                    let instruction = &mut parent.trace.ops[idx];
+                    instruction.sub = Some(frame.trace);
+                    if let Some(ex) = instruction.ex.as_mut() {
+                        ex.push = vec![to_alloy_u256(push)];
+                    }
+                }
+            }
+            None => self.root = Some(frame.trace),
+        }
+    }
+
+    pub fn step(&mut self, interp: &dyn InterpreterInfo) {
+        let frame = match self.frames.last_mut() {
+            Some(frame) => frame,
+            None => return,
+        };
+        // the previous call-like instruction did not make a sub-call
+        frame.call_op = None;
+
+        let op = interp.current_opcode();
+        let stack = interp.stack();
+        let store = match (op, stack.len()) {
+            (opcode::SSTORE, len) if len >= 2 => Some(StorageDelta {
+                key: to_alloy_u256(stack[len - 1]),
+                val: to_alloy_u256(stack[len - 2]),
+            }),
+            _ => None,
+        };
+        frame.pending = Some(PendingStep {
+            pc: interp.program_counter() as usize,
+            op,
+            gas: interp.gas_remainning().as_u64(),
+            mem_write: memory_write(op, stack),
+            store,
+        });
+    }
+
+    pub fn step_end(&mut self, interp: &dyn InterpreterInfo) {
+        let cancun_opcodes = self.cancun_opcodes;
+        let frame = match self.frames.last_mut() {
+            Some(frame) => frame,
+            None => return,
+        };
+        let step = match frame.pending.take() {
+            Some(step) => step,
+            None => return,
+        };
+
+        let used = interp.gas_remainning().as_u64();
+        let stack = interp.stack();
+        let op = OpCode::new(step.op);
+        let is_calllike = op.map_or(false, |op| is_calllike_opcode(op.get()));
+        // the results of call-like instructions are pushed after the sub-call
+        let num_pushed = if is_calllike {
+            0
+        } else {
+            stack_push_count(step.op, cancun_opcodes)
+        };
+        let push = stack[stack.len().saturating_sub(num_pushed)..]
+            .iter()
+            .map(|v| to_alloy_u256(*v))
+            .collect();
+        let mem = step.mem_write.and_then(|(off, size)| {
+            let data = interp.mem().get(off..off.checked_add(size)?)?;
+            Some(MemoryDelta {
+                off,
+                data: Bytes::copy_from_slice(data),
+            })
+        });
+
+        frame.trace.ops.push(VmInstruction {
+            pc: step.pc,
+            cost: step.gas.saturating_sub(used),
+            ex: Some(VmExecutedOperation {
+                used,
+                push,
+                mem,
+                store: step.store,
+            }),
+            sub: None,
+            op: op.map(|op| op.to_string()),
+            idx: None,
+        });
+        if is_calllike {
+            frame.call_op = Some(frame.trace.ops.len() - 1);
+        }
+    }
+
+    pub fn into_vm_trace(mut self) -> VmTrace {
+        // frames left unfinished by an aborted execution
+        while !self.frames.is_empty() {
+            self.end_frame(CfxU256::zero());
+        }
+        self.root.unwrap_or_default()
+    }
+}
+
+fn is_calllike_opcode(op: u8) -> bool {
+    matches!(
+        op,
+        opcode::CALL
+            | opcode::CALLCODE
+            | opcode::DELEGATECALL
+            | opcode::STATICCALL
+            | opcode::CREATE
+            | opcode::CREATE2
+    )
+}
+
+/// Returns the memory range written by the instruction, given the stack before
+/// it is executed. The outputs of the sub-calls are not included.
+fn memory_write(op: u8, stack: &[CfxU256]) -> Option<(usize, usize)> {
+    // the n-th item from the top of the stack
+    let arg = |n: usize| -> Option<CfxU256> {
+        stack.len().checked_sub(n + 1).map(|idx| stack[idx])
+    };
+    let (off, size) = match op {
+        opcode::MSTORE => (arg(0)?, CfxU256::from(32)),
+        opcode::MSTORE8 => (arg(0)?, CfxU256::one()),
+        opcode::CALLDATACOPY
+        | opcode::CODECOPY
+        | opcode::RETURNDATACOPY
+        | opcode::MCOPY => (arg(0)?, arg(2)?),
+        opcode::EXTCODECOPY => (arg(1)?, arg(3)?),
+        _ => return None,
+    };
+    let max = CfxU256::from(usize::MAX);
+    if size.is_zero() || off > max || size > max {
+        return None;
+    }
+    Some((off.as_usize(), size.as_usize()))
+}
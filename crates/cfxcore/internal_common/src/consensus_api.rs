@@ -15,4 +15,10 @@ pub trait StateMaintenanceTrait {
     ) -> Option<EpochExecutionCommitment>;
 
     fn remove_epoch_execution_commitment_from_db(&self, block_hash: &EpochId);
+
+    /// Whether the local state root of the pivot epoch is the one committed
+    /// by the pivot block deferred after it. None if the epoch is not on the
+    /// pivot chain of the current era or the committing block doesn't exist
+    /// yet.
+    fn is_epoch_state_root_verified(&self, epoch_id: &EpochId) -> Option<bool>;
 }
@@ -9,6 +9,8 @@ pub enum AddressPocket {
     SponsorBalanceForGas(Address),
     SponsorBalanceForStorage(Address),
     MintBurn,
+    /// The sponsor balance for gas of an eSpace contract (CIP-147).
+    EvmSponsorBalanceForGas(Address),
     GasPayment,
 }
 
@@ -20,7 +22,8 @@ impl AddressPocket {
             | StakingBalance(addr)
             | StorageCollateral(addr)
             | SponsorBalanceForGas(addr)
-            | SponsorBalanceForStorage(addr) => Some(addr),
+            | SponsorBalanceForStorage(addr)
+            | EvmSponsorBalanceForGas(addr) => Some(addr),
             MintBurn | GasPayment => None,
         }
     }
@@ -35,7 +38,9 @@ impl AddressPocket {
             Balance(_) => "balance",
             StakingBalance(_) => "staking_balance",
             StorageCollateral(_) => "storage_collateral",
-            SponsorBalanceForGas(_) => "sponsor_balance_for_gas",
+            SponsorBalanceForGas(_) | EvmSponsorBalanceForGas(_) => {
+                "sponsor_balance_for_gas"
+            }
             SponsorBalanceForStorage(_) => "sponsor_balance_for_collateral",
             MintBurn => "mint_or_burn",
             GasPayment => "gas_payment",
@@ -47,6 +52,7 @@ impl AddressPocket {
         match self {
             Balance(AddressWithSpace { space, .. }) => space.clone().into(),
             MintBurn | GasPayment => "none",
+            EvmSponsorBalanceForGas(_) => Space::Ethereum.into(),
             _ => Space::Native.into(),
         }
     }
@@ -68,6 +74,7 @@ impl AddressPocket {
                 space: Space::Ethereum,
                 ..
             }) => 7,
+            EvmSponsorBalanceForGas(_) => 8,
         }
     }
 }
@@ -105,6 +112,7 @@ impl Decodable for AddressPocket {
             7 => rlp
                 .val_at(1)
                 .map(|addr: Address| Balance(addr.with_evm_space())),
+            8 => rlp.val_at(1).map(EvmSponsorBalanceForGas),
             _ => {
                 Err(DecoderError::Custom("Invalid internal transfer address."))
             }
@@ -74,13 +74,8 @@ impl TraceFilter {
         for trace in tx_traces.0 {
             match &trace.action {
                 Action::Call(call) => {
-                    if call.space == self.space {
-                        if let Some(parent_subtraces) = sublen_stack.last_mut()
-                        {
-                            *parent_subtraces += 1;
-                        }
-                    }
-                    sublen_stack.push(0);
+                    self.count_subtrace(&mut sublen_stack, call.space);
+                    sublen_stack.push((call.space, 0));
                     if call.space == self.space
                         && self.from_address.matches(&call.from)
                         && self.to_address.matches(&call.to)
@@ -94,13 +89,8 @@ impl TraceFilter {
                     }
                 }
                 Action::Create(create) => {
-                    if create.space == self.space {
-                        if let Some(parent_subtraces) = sublen_stack.last_mut()
-                        {
-                            *parent_subtraces += 1;
-                        }
-                    }
-                    sublen_stack.push(0);
+                    self.count_subtrace(&mut sublen_stack, create.space);
+                    sublen_stack.push((create.space, 0));
                     if create.space == self.space
                         && self.from_address.matches(&create.from)
                         // TODO(lpl): openethereum uses `to_address` to filter the contract address.
@@ -122,7 +112,7 @@ impl TraceFilter {
                         // we do not check if the type
                         // is correct here.
                         trace_pairs[index].1 = Some(trace);
-                        let subtraces =
+                        let (_, subtraces) =
                             sublen_stack.pop().expect("stack_index matches");
                         trace_pairs[index].2 = subtraces;
                     } else {
@@ -141,6 +131,24 @@ impl TraceFilter {
             .collect())
     }
 
+    /// Count a call or create in `space` as a subtrace of its closest ancestor
+    /// in the filtered space. With cross-space calls, the ancestor may be
+    /// separated from it by the frames in the other space.
+    fn count_subtrace(
+        &self, sublen_stack: &mut Vec<(Space, usize)>, space: Space,
+    ) {
+        if space != self.space {
+            return;
+        }
+        if let Some((_, parent_subtraces)) = sublen_stack
+            .iter_mut()
+            .rev()
+            .find(|(parent_space, _)| *parent_space == self.space)
+        {
+            *parent_subtraces += 1;
+        }
+    }
+
     /// Return filtered Native actions with their orders kept.
     ///
     /// `from_address`, `to_address`, `action_types`, and `space` in `filter`
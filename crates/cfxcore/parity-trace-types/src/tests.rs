@@ -1,7 +1,9 @@
 use super::{
-    action_types::{Action, Call},
+    action_types::{Action, Call, CallResult, Outcome},
+    filter::TraceFilter,
     trace_types::{BlockExecTraces, ExecTrace, TransactionExecTraces},
 };
+use cfx_types::Space;
 use cfx_vm_types::CallType;
 use rlp::*;
 
@@ -68,3 +70,58 @@ fn test_trace_serialization() {
     let decoded = ::rlp::decode(&encoded).expect("error decoding block traces");
     assert_eq!(block_traces, decoded);
 }
+
+#[test]
+fn test_filter_nested_cross_space_traces() {
+    let call = |space| ExecTrace {
+        action: Action::Call(Call {
+            space,
+            from: Default::default(),
+            to: Default::default(),
+            value: 0.into(),
+            gas: 0.into(),
+            input: vec![],
+            call_type: CallType::Call,
+        }),
+        valid: true,
+    };
+    let result = || ExecTrace {
+        action: Action::CallResult(CallResult {
+            outcome: Outcome::Success,
+            gas_left: 0.into(),
+            return_data: vec![],
+        }),
+        valid: true,
+    };
+
+    // eSpace -> Core Space -> eSpace, and a Core Space call at the top level
+    // of the eSpace frame.
+    let tx_traces = TransactionExecTraces(vec![
+        call(Space::Ethereum),
+        call(Space::Native),
+        call(Space::Ethereum),
+        result(),
+        result(),
+        call(Space::Native),
+        result(),
+        result(),
+    ]);
+
+    let eth_pairs = TraceFilter::space_filter(Space::Ethereum)
+        .filter_trace_pairs(tx_traces.clone())
+        .unwrap();
+    let eth_subtraces: Vec<usize> = eth_pairs
+        .iter()
+        .map(|(_, _, subtraces)| *subtraces)
+        .collect();
+    assert_eq!(eth_subtraces, vec![1, 0]);
+
+    let native_pairs = TraceFilter::space_filter(Space::Native)
+        .filter_trace_pairs(tx_traces)
+        .unwrap();
+    let native_subtraces: Vec<usize> = native_pairs
+        .iter()
+        .map(|(_, _, subtraces)| *subtraces)
+        .collect();
+    assert_eq!(native_subtraces, vec![0, 0]);
+}
@@ -0,0 +1,60 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Compares executing basic blocks at once with the untraced step by step
+//! execution, which the interpreter did before analysing basic blocks.
+
+use cfx_types::Address;
+use cfx_vm_interpreter::{Factory, VMType};
+use cfx_vm_types::{
+    tests::{test_finalize, MockContext},
+    ActionParams, Context,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+use keccak_hash::keccak;
+use rustc_hex::FromHex;
+use std::sync::Arc;
+
+/// 100 token transfers from the caller, each updating two entries of a
+/// `mapping(address => uint)` at slot 0, as an ERC20 `transfer` does.
+const ERC20_TRANSFERS: &str = "60645b8015603657336000526000602052604060002080546001900390556002600052604060002080546001019055600190036002565b00";
+
+/// 200 quotes of a constant product swap with a 0.3% fee, updating the
+/// reserves after each one, as a Uniswap V2 pair does.
+const CONSTANT_PRODUCT_SWAPS: &str = "620f4240621e848060c85b8015603157620f368880846103e802019083020482039150826103e801925060019003600a565b5060005500";
+
+fn exec(factory: &Factory, code: &Arc<Vec<u8>>) {
+    let mut params = ActionParams::default();
+    params.address = Address::from_low_u64_be(5);
+    params.gas = 10_000_000.into();
+    params.code_hash = keccak(&**code);
+    params.code = Some(code.clone());
+    let mut context = MockContext::new_spec();
+
+    let vm = factory.create(params, context.spec(), context.depth());
+    test_finalize(vm.exec(&mut context).ok().unwrap()).unwrap();
+}
+
+fn bench_workload(c: &mut Criterion, name: &str, code: &str) {
+    let factory = Factory::new(VMType::Interpreter, 4 * 1024 * 1024);
+    let baseline = factory.clone().without_basic_blocks();
+    let code = Arc::new(code.from_hex::<Vec<u8>>().unwrap());
+    c.bench_function(&format!("{} basic blocks", name), |b| {
+        b.iter(|| exec(&factory, &code))
+    });
+    c.bench_function(&format!("{} step by step", name), |b| {
+        b.iter(|| exec(&baseline, &code))
+    });
+}
+
+fn erc20_benchmark(c: &mut Criterion) {
+    bench_workload(c, "ERC20 transfers", ERC20_TRANSFERS);
+}
+
+fn defi_benchmark(c: &mut Criterion) {
+    bench_workload(c, "Constant product swaps", CONSTANT_PRODUCT_SWAPS);
+}
+
+criterion_group!(benches, erc20_benchmark, defi_benchmark);
+criterion_main!(benches);
@@ -32,6 +32,7 @@ pub struct Factory {
     evm: VMType,
     evm_cache: Arc<SharedCache<false>>,
     evm_cache_cancun: Arc<SharedCache<true>>,
+    basic_blocks: bool,
 }
 
 impl Factory {
@@ -53,24 +54,28 @@ impl Factory {
                 self.evm_cache_cancun.clone(),
                 spec,
                 depth,
+                self.basic_blocks,
             )),
             (true, false) => Box::new(Interpreter::<usize, false>::new(
                 params,
                 self.evm_cache.clone(),
                 spec,
                 depth,
+                self.basic_blocks,
             )),
             (false, true) => Box::new(Interpreter::<U256, true>::new(
                 params,
                 self.evm_cache_cancun.clone(),
                 spec,
                 depth,
+                self.basic_blocks,
             )),
             (false, false) => Box::new(Interpreter::<U256, false>::new(
                 params,
                 self.evm_cache.clone(),
                 spec,
                 depth,
+                self.basic_blocks,
             )),
         }
     }
@@ -82,9 +87,18 @@ impl Factory {
             evm,
             evm_cache: Arc::new(SharedCache::new(cache_size)),
             evm_cache_cancun: Arc::new(SharedCache::new(cache_size)),
+            basic_blocks: true,
         }
     }
 
+    /// Executes the instructions one by one instead of in basic blocks, as
+    /// the interpreter does for opcode tracers. Only meant to compare the two
+    /// in tests and benchmarks.
+    pub fn without_basic_blocks(mut self) -> Self {
+        self.basic_blocks = false;
+        self
+    }
+
     fn can_fit_in_usize(gas: &U256) -> bool {
         gas == &U256::from(gas.low_u64() as usize)
     }
@@ -97,6 +111,7 @@ impl Default for Factory {
             evm: VMType::Interpreter,
             evm_cache: Arc::new(SharedCache::default()),
             evm_cache_cancun: Arc::new(SharedCache::default()),
+            basic_blocks: true,
         }
     }
 }
@@ -162,8 +162,10 @@ enum_with_from_u8! {
         #[doc = "base fee for EIP-1559 (EIP-3198)"]
         BASEFEE = 0x48,
 
-        // BLOBHASH=0x49
-        // BLOBBASEFEE=0x4A
+        #[doc = "get versioned hashes of the blobs (EIP-4844), always zero as transactions carry no blob (CIP-148)"]
+        BLOBHASH = 0x49,
+        #[doc = "get the blob base fee (EIP-7516), always the minimum as transactions carry no blob (CIP-148)"]
+        BLOBBASEFEE = 0x4a,
 
         #[doc = "remove item from stack"]
         POP = 0x50,
@@ -379,6 +381,11 @@ impl Instruction {
         if instruction == Some(BASEFEE) && !spec.cip1559 {
             instruction = None;
         }
+        if (instruction == Some(BLOBHASH) || instruction == Some(BLOBBASEFEE))
+            && !spec.cip148
+        {
+            instruction = None;
+        }
         return instruction;
     }
 
@@ -555,6 +562,8 @@ lazy_static! {
         arr[CHAINID as usize] = Some(InstructionInfo::new("CHAINID", 0, 1, GasPriceTier::Base));
         arr[SELFBALANCE as usize] = Some(InstructionInfo::new("SELFBALANCE", 0, 1, GasPriceTier::Low));
         arr[BASEFEE as usize] = Some(InstructionInfo::new("BASEFEE", 0, 1, GasPriceTier::VeryLow));
+        arr[BLOBHASH as usize] = Some(InstructionInfo::new("BLOBHASH", 1, 1, GasPriceTier::VeryLow));
+        arr[BLOBBASEFEE as usize] = Some(InstructionInfo::new("BLOBBASEFEE", 0, 1, GasPriceTier::Base));
         arr[POP as usize] = Some(InstructionInfo::new("POP", 1, 0, GasPriceTier::Base));
         arr[MLOAD as usize] = Some(InstructionInfo::new("MLOAD", 1, 1, GasPriceTier::VeryLow));
         arr[MSTORE as usize] = Some(InstructionInfo::new("MSTORE", 2, 0, GasPriceTier::VeryLow));
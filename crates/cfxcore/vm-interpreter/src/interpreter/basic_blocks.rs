@@ -0,0 +1,604 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Basic block analysis of contract code.
+//!
+//! The code is split once per code hash into runs of instructions which only
+//! touch the stack and the call parameters. The gas of such an instruction
+//! only depends on the spec and it can not fail once the stack is known to be
+//! deep enough, so the interpreter checks the gas and the stack once for the
+//! whole block and then executes it without any per-instruction bookkeeping.
+//!
+//! A `PUSH` followed by a `JUMP` or `JUMPI` to a valid jump destination is
+//! fused into the exit of the block, so the jump destination doesn't need to
+//! be pushed, popped and verified at runtime.
+
+use super::{stack::VecStack, InterpreterParams};
+use crate::{
+    evm::CostType,
+    instructions::{self, Instruction},
+};
+use bit_set::BitSet;
+use cfx_types::U256;
+use cfx_vm_types::Spec;
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use std::{cmp, mem};
+
+/// The data besides the stack which the instructions of a block may read.
+/// None of them can change while a block runs.
+pub struct BlockEnv<'a> {
+    pub params: &'a InterpreterParams,
+    pub mem_size: usize,
+    pub return_data_size: usize,
+}
+
+/// Executes an instruction of a basic block, given the operand computed when
+/// the code is analysed.
+type Handler = fn(&mut VecStack<U256>, &BlockEnv, &U256);
+
+macro_rules! stack_op_handlers {
+    ($($name:ident),* $(,)?) => {
+        $(
+            pub fn $name(
+                stack: &mut VecStack<U256>, _: &BlockEnv, _: &U256,
+            ) {
+                stack_ops::$name(stack)
+            }
+        )*
+    };
+}
+
+mod handlers {
+    use super::{
+        super::{
+            address_to_u256,
+            stack::{Stack, VecStack},
+            stack_ops,
+        },
+        BlockEnv,
+    };
+    use cfx_types::U256;
+    use cfx_vm_types::ActionValue;
+
+    stack_op_handlers!(
+        pop, add, mul, sub, div, modulo, sdiv, smod, not, lt, slt, gt, sgt, eq,
+        iszero, and, or, xor, byte, addmod, mulmod, signextend, shl, shr, sar,
+    );
+
+    /// `PUSH*`, `PC` and `CODESIZE`, whose values are known from the code.
+    pub fn push_operand(
+        stack: &mut VecStack<U256>, _: &BlockEnv, operand: &U256,
+    ) {
+        stack.push(*operand);
+    }
+
+    pub fn dup(stack: &mut VecStack<U256>, _: &BlockEnv, position: &U256) {
+        let val = *stack.peek(position.low_u64() as usize);
+        stack.push(val);
+    }
+
+    pub fn swap(stack: &mut VecStack<U256>, _: &BlockEnv, position: &U256) {
+        stack.swap_with_top(position.low_u64() as usize);
+    }
+
+    pub fn address(stack: &mut VecStack<U256>, env: &BlockEnv, _: &U256) {
+        stack.push(address_to_u256(env.params.address));
+    }
+
+    pub fn origin(stack: &mut VecStack<U256>, env: &BlockEnv, _: &U256) {
+        stack.push(address_to_u256(env.params.original_sender));
+    }
+
+    pub fn caller(stack: &mut VecStack<U256>, env: &BlockEnv, _: &U256) {
+        stack.push(address_to_u256(env.params.sender));
+    }
+
+    pub fn callvalue(stack: &mut VecStack<U256>, env: &BlockEnv, _: &U256) {
+        stack.push(match env.params.value {
+            ActionValue::Transfer(val) | ActionValue::Apparent(val) => val,
+        });
+    }
+
+    pub fn calldataload(stack: &mut VecStack<U256>, env: &BlockEnv, _: &U256) {
+        stack_ops::calldataload(stack, env.params.data.as_ref());
+    }
+
+    pub fn calldatasize(stack: &mut VecStack<U256>, env: &BlockEnv, _: &U256) {
+        stack.push(U256::from(env.params.data.as_ref().map_or(0, |l| l.len())));
+    }
+
+    pub fn returndatasize(
+        stack: &mut VecStack<U256>, env: &BlockEnv, _: &U256,
+    ) {
+        stack.push(U256::from(env.return_data_size));
+    }
+
+    pub fn msize(stack: &mut VecStack<U256>, env: &BlockEnv, _: &U256) {
+        stack.push(U256::from(env.mem_size));
+    }
+}
+
+lazy_static! {
+    /// The handlers of the instructions which may be part of a basic block,
+    /// indexed by opcode.
+    ///
+    /// Instructions with a dynamic gas cost, memory expansion or access to the
+    /// state are excluded, as well as those which depend on the spec (`PUSH0`,
+    /// `BASEFEE`) or on the remaining gas (`GAS`). `JUMPDEST`, and `STOP`,
+    /// `JUMP` and `JUMPI` which end a block, have no handler and are part of
+    /// the block structure instead.
+    static ref DISPATCH_TABLE: [Option<Handler>; 0x100] = {
+        let mut table: [Option<Handler>; 0x100] = [None; 0x100];
+        table[instructions::ADD as usize] = Some(handlers::add);
+        table[instructions::MUL as usize] = Some(handlers::mul);
+        table[instructions::SUB as usize] = Some(handlers::sub);
+        table[instructions::DIV as usize] = Some(handlers::div);
+        table[instructions::SDIV as usize] = Some(handlers::sdiv);
+        table[instructions::MOD as usize] = Some(handlers::modulo);
+        table[instructions::SMOD as usize] = Some(handlers::smod);
+        table[instructions::ADDMOD as usize] = Some(handlers::addmod);
+        table[instructions::MULMOD as usize] = Some(handlers::mulmod);
+        table[instructions::SIGNEXTEND as usize] = Some(handlers::signextend);
+        table[instructions::LT as usize] = Some(handlers::lt);
+        table[instructions::GT as usize] = Some(handlers::gt);
+        table[instructions::SLT as usize] = Some(handlers::slt);
+        table[instructions::SGT as usize] = Some(handlers::sgt);
+        table[instructions::EQ as usize] = Some(handlers::eq);
+        table[instructions::ISZERO as usize] = Some(handlers::iszero);
+        table[instructions::AND as usize] = Some(handlers::and);
+        table[instructions::OR as usize] = Some(handlers::or);
+        table[instructions::XOR as usize] = Some(handlers::xor);
+        table[instructions::NOT as usize] = Some(handlers::not);
+        table[instructions::BYTE as usize] = Some(handlers::byte);
+        table[instructions::SHL as usize] = Some(handlers::shl);
+        table[instructions::SHR as usize] = Some(handlers::shr);
+        table[instructions::SAR as usize] = Some(handlers::sar);
+        table[instructions::ADDRESS as usize] = Some(handlers::address);
+        table[instructions::ORIGIN as usize] = Some(handlers::origin);
+        table[instructions::CALLER as usize] = Some(handlers::caller);
+        table[instructions::CALLVALUE as usize] = Some(handlers::callvalue);
+        table[instructions::CALLDATALOAD as usize] =
+            Some(handlers::calldataload);
+        table[instructions::CALLDATASIZE as usize] =
+            Some(handlers::calldatasize);
+        table[instructions::CODESIZE as usize] = Some(handlers::push_operand);
+        table[instructions::RETURNDATASIZE as usize] =
+            Some(handlers::returndatasize);
+        table[instructions::POP as usize] = Some(handlers::pop);
+        table[instructions::PC as usize] = Some(handlers::push_operand);
+        table[instructions::MSIZE as usize] = Some(handlers::msize);
+        for opcode in instructions::PUSH1 as usize..=instructions::PUSH32 as usize
+        {
+            table[opcode] = Some(handlers::push_operand);
+        }
+        for opcode in instructions::DUP1 as usize..=instructions::DUP16 as usize {
+            table[opcode] = Some(handlers::dup);
+        }
+        for opcode in
+            instructions::SWAP1 as usize..=instructions::SWAP16 as usize
+        {
+            table[opcode] = Some(handlers::swap);
+        }
+        table
+    };
+}
+
+/// Whether an instruction may be part of a basic block.
+fn is_block_instruction(instruction: Instruction) -> bool {
+    DISPATCH_TABLE[instruction as usize].is_some()
+        || matches!(
+            instruction,
+            instructions::JUMPDEST
+                | instructions::STOP
+                | instructions::JUMP
+                | instructions::JUMPI
+        )
+}
+
+/// An instruction of a basic block, resolved to its handler.
+#[derive(Debug, Clone, Copy)]
+pub struct BlockOp {
+    #[cfg(test)]
+    instruction: Instruction,
+    handler: Handler,
+    operand: U256,
+}
+
+impl BlockOp {
+    #[inline(always)]
+    pub fn exec(&self, stack: &mut VecStack<U256>, env: &BlockEnv) {
+        (self.handler)(stack, env, &self.operand)
+    }
+}
+
+/// How the execution continues after the instructions of a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockExit {
+    /// Continue at the end of the block.
+    Fallthrough,
+    /// `STOP`.
+    Stop,
+    /// `JUMP` to the destination on the stack, verified at runtime.
+    DynamicJump,
+    /// `JUMPI` to the destination on the stack, verified at runtime.
+    DynamicJumpIf,
+    /// Fused `PUSH` + `JUMP` to a valid jump destination.
+    Jump(usize),
+    /// Fused `PUSH` + `JUMPI` to a valid jump destination. Continues at the
+    /// end of the block if the condition is zero.
+    JumpIf(usize),
+}
+
+#[derive(Debug)]
+pub struct BasicBlock {
+    /// Position of the first instruction.
+    pub start: usize,
+    /// Position right after the last instruction.
+    pub end: usize,
+    /// Instructions to execute, excluding `JUMPDEST` and the instructions
+    /// of the exit.
+    pub instructions: Box<[BlockOp]>,
+    /// Number of instructions in each gas price tier, excluding `JUMPDEST`.
+    tier_counts: [u32; 7],
+    /// Number of `JUMPDEST`, which can only be the first instruction.
+    pub jumpdests: u32,
+    /// Minimal stack size needed to run the block without underflow.
+    pub stack_required: usize,
+    /// Maximal stack growth while running the block.
+    pub stack_growth: usize,
+    pub exit: BlockExit,
+}
+
+impl BasicBlock {
+    /// Total gas of the block, which only depends on the spec.
+    pub fn gas_cost<Cost: CostType>(&self, spec: &Spec) -> Cost {
+        let gas = self
+            .tier_counts
+            .iter()
+            .zip(spec.tier_step_gas.iter())
+            .map(|(count, gas)| *count as usize * gas)
+            .sum::<usize>()
+            + self.jumpdests as usize;
+        Cost::from(gas)
+    }
+}
+
+struct BlockBuilder<const CANCUN: bool> {
+    start: usize,
+    instructions: Vec<BlockOp>,
+    tier_counts: [u32; 7],
+    jumpdests: u32,
+    stack_height: isize,
+    stack_required: isize,
+    stack_growth: isize,
+}
+
+impl<const CANCUN: bool> BlockBuilder<CANCUN> {
+    fn new(start: usize) -> Self {
+        BlockBuilder {
+            start,
+            instructions: vec![],
+            tier_counts: [0; 7],
+            jumpdests: 0,
+            stack_height: 0,
+            stack_required: 0,
+            stack_growth: 0,
+        }
+    }
+
+    /// Accounts the gas and the stack effect of the instruction.
+    fn account(&mut self, instruction: Instruction) {
+        let info = instruction.info::<CANCUN>();
+        if instruction == instructions::JUMPDEST {
+            self.jumpdests += 1;
+        } else {
+            self.tier_counts[info.tier.idx()] += 1;
+        }
+        self.stack_required = self
+            .stack_required
+            .max(info.args as isize - self.stack_height);
+        self.stack_height += info.ret as isize - info.args as isize;
+        self.stack_growth = self.stack_growth.max(self.stack_height);
+    }
+
+    fn push(
+        &mut self, instruction: Instruction, handler: Handler, operand: U256,
+    ) {
+        self.account(instruction);
+        self.instructions.push(BlockOp {
+            #[cfg(test)]
+            instruction,
+            handler,
+            operand,
+        });
+    }
+
+    fn finish(self, end: usize, exit: BlockExit) -> BasicBlock {
+        BasicBlock {
+            start: self.start,
+            end,
+            instructions: self.instructions.into_boxed_slice(),
+            tier_counts: self.tier_counts,
+            jumpdests: self.jumpdests,
+            stack_required: self.stack_required as usize,
+            stack_growth: self.stack_growth as usize,
+            exit,
+        }
+    }
+}
+
+/// The basic blocks of a contract code.
+#[derive(Debug, Default)]
+pub struct BasicBlocks {
+    block_starts: BitSet,
+    blocks: Vec<BasicBlock>,
+}
+
+impl BasicBlocks {
+    pub fn new<const CANCUN: bool>(code: &[u8], jump_dests: &BitSet) -> Self {
+        let mut blocks = vec![];
+        let mut current: Option<BlockBuilder<CANCUN>> = None;
+        let mut position = 0;
+
+        while position < code.len() {
+            let instruction = match Instruction::from_u8(code[position]) {
+                Some(instruction) if is_block_instruction(instruction) => {
+                    instruction
+                }
+                other => {
+                    // Left to the per-instruction path.
+                    if let Some(block) = current.take() {
+                        blocks.push(
+                            block.finish(position, BlockExit::Fallthrough),
+                        );
+                    }
+                    position += 1 + other
+                        .and_then(|instruction| instruction.push_bytes())
+                        .unwrap_or(0);
+                    continue;
+                }
+            };
+
+            if instruction == instructions::JUMPDEST {
+                if let Some(block) = current.take() {
+                    blocks.push(block.finish(position, BlockExit::Fallthrough));
+                }
+            }
+            let block =
+                current.get_or_insert_with(|| BlockBuilder::new(position));
+            let next = position + 1 + instruction.push_bytes().unwrap_or(0);
+
+            let exit = match instruction {
+                instructions::JUMPDEST => {
+                    block.account(instruction);
+                    position = next;
+                    continue;
+                }
+                instructions::STOP => BlockExit::Stop,
+                instructions::JUMP => BlockExit::DynamicJump,
+                instructions::JUMPI => BlockExit::DynamicJumpIf,
+                _ => {
+                    let operand = if instruction.is_push() {
+                        // Truncated at the end of the code, as `CodeReader`
+                        // reads it.
+                        U256::from(
+                            &code[position + 1..cmp::min(next, code.len())],
+                        )
+                    } else if let Some(dup) = instruction.dup_position() {
+                        dup.into()
+                    } else if let Some(swap) = instruction.swap_position() {
+                        swap.into()
+                    } else if instruction == instructions::PC {
+                        position.into()
+                    } else if instruction == instructions::CODESIZE {
+                        code.len().into()
+                    } else {
+                        U256::zero()
+                    };
+
+                    let fused_exit = if instruction.is_push()
+                        && next < code.len()
+                        && operand < U256::from(code.len())
+                        && jump_dests.contains(operand.low_u64() as usize)
+                    {
+                        match Instruction::from_u8(code[next]) {
+                            Some(instructions::JUMP) => Some(BlockExit::Jump(
+                                operand.low_u64() as usize,
+                            )),
+                            Some(instructions::JUMPI) => Some(
+                                BlockExit::JumpIf(operand.low_u64() as usize),
+                            ),
+                            _ => None,
+                        }
+                    } else {
+                        None
+                    };
+                    if let Some(exit) = fused_exit {
+                        let jump = Instruction::from_u8(code[next])
+                            .expect("checked above; qed");
+                        block.account(instruction);
+                        block.account(jump);
+                        let block = current.take().expect("just inserted; qed");
+                        blocks.push(block.finish(next + 1, exit));
+                        position = next + 1;
+                        continue;
+                    }
+
+                    let handler = DISPATCH_TABLE[instruction as usize]
+                        .expect("is_block_instruction checked above; qed");
+                    block.push(instruction, handler, operand);
+                    position = next;
+                    continue;
+                }
+            };
+
+            // `STOP`, `JUMP` or `JUMPI` ends the block.
+            block.account(instruction);
+            let block = current.take().expect("just inserted; qed");
+            blocks.push(block.finish(next, exit));
+            position = next;
+        }
+        if let Some(block) = current.take() {
+            blocks.push(block.finish(position, BlockExit::Fallthrough));
+        }
+
+        let mut block_starts = BitSet::with_capacity(code.len());
+        for block in &blocks {
+            block_starts.insert(block.start);
+        }
+        blocks.shrink_to_fit();
+
+        BasicBlocks {
+            block_starts,
+            blocks,
+        }
+    }
+
+    /// Returns the block starting at the given position, if any.
+    #[inline]
+    pub fn block_at(&self, position: usize) -> Option<&BasicBlock> {
+        if !self.block_starts.contains(position) {
+            return None;
+        }
+        self.blocks
+            .binary_search_by_key(&position, |block| block.start)
+            .ok()
+            .map(|index| &self.blocks[index])
+    }
+
+    #[cfg(test)]
+    pub fn blocks(&self) -> &[BasicBlock] { &self.blocks }
+}
+
+impl MallocSizeOf for BasicBlocks {
+    fn size_of(&self, _ops: &mut MallocSizeOfOps) -> usize {
+        // dealing in bits here
+        self.block_starts.capacity() / 8
+            + self.blocks.capacity() * mem::size_of::<BasicBlock>()
+            + self
+                .blocks
+                .iter()
+                .map(|block| block.instructions.len())
+                .sum::<usize>()
+                * mem::size_of::<BlockOp>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BasicBlock, BasicBlocks, BlockExit};
+    use crate::instructions::{self, Instruction};
+    use bit_set::BitSet;
+    use cfx_vm_types::Spec;
+    use rustc_hex::FromHex;
+
+    fn basic_blocks(code: &str, jump_dests: &[usize]) -> BasicBlocks {
+        let code: Vec<u8> = code.from_hex().unwrap();
+        let jump_dests: BitSet = jump_dests.iter().cloned().collect();
+        BasicBlocks::new::<false>(&code, &jump_dests)
+    }
+
+    fn instructions(block: &BasicBlock) -> Vec<Instruction> {
+        block.instructions.iter().map(|op| op.instruction).collect()
+    }
+
+    #[test]
+    fn test_split_at_jumpdest_and_state_access() {
+        // 0000 60 01   PUSH1 01
+        // 0002 60 02   PUSH1 02
+        // 0004 01      ADD
+        // 0005 5B      JUMPDEST
+        // 0006 80      DUP1
+        // 0007 54      SLOAD
+        // 0008 50      POP
+        // 0009 00      STOP
+        let blocks = basic_blocks("60016002015B80545000", &[5]);
+        let blocks = blocks.blocks();
+
+        assert_eq!(blocks.len(), 3);
+        assert_eq!((blocks[0].start, blocks[0].end), (0, 5));
+        assert_eq!(
+            instructions(&blocks[0]),
+            [instructions::PUSH1, instructions::PUSH1, instructions::ADD]
+        );
+        assert_eq!(blocks[0].instructions[1].operand, 2.into());
+        assert_eq!(blocks[0].stack_required, 0);
+        assert_eq!(blocks[0].stack_growth, 2);
+        assert_eq!(blocks[0].gas_cost::<usize>(&Spec::default()), 9);
+
+        assert_eq!((blocks[1].start, blocks[1].end), (5, 7));
+        assert_eq!(instructions(&blocks[1]), [instructions::DUP1]);
+        assert_eq!(blocks[1].stack_required, 1);
+        assert_eq!(blocks[1].stack_growth, 1);
+        assert_eq!(blocks[1].gas_cost::<usize>(&Spec::default()), 4);
+
+        assert_eq!((blocks[2].start, blocks[2].end), (8, 10));
+        assert_eq!(instructions(&blocks[2]), [instructions::POP]);
+        assert_eq!(blocks[2].exit, BlockExit::Stop);
+        assert_eq!(blocks[2].stack_required, 1);
+        assert_eq!(blocks[2].stack_growth, 0);
+    }
+
+    #[test]
+    fn test_fuse_push_and_jump() {
+        // 0000 60 01   PUSH1 01
+        // 0002 60 08   PUSH1 08
+        // 0004 57      JUMPI
+        // 0005 60 08   PUSH1 08
+        // 0007 56      JUMP
+        // 0008 5B      JUMPDEST
+        // 0009 60 00   PUSH1 00
+        // 000b 56      JUMP
+        let blocks = basic_blocks("60016008576008565B600056", &[8]);
+        let blocks = blocks.blocks();
+
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].exit, BlockExit::JumpIf(8));
+        assert_eq!(instructions(&blocks[0]), [instructions::PUSH1]);
+        assert_eq!(blocks[0].end, 5);
+        assert_eq!(blocks[0].stack_growth, 2);
+        assert_eq!(blocks[0].gas_cost::<usize>(&Spec::default()), 16);
+
+        assert_eq!(blocks[1].exit, BlockExit::Jump(8));
+        assert!(blocks[1].instructions.is_empty());
+        assert_eq!(blocks[1].stack_required, 0);
+        assert_eq!(blocks[1].stack_growth, 1);
+
+        // Not a valid jump destination, verified at runtime.
+        assert_eq!(blocks[2].exit, BlockExit::DynamicJump);
+        assert_eq!(instructions(&blocks[2]), [instructions::PUSH1]);
+        assert_eq!(blocks[2].gas_cost::<usize>(&Spec::default()), 12);
+    }
+
+    #[test]
+    fn test_resolve_operands() {
+        // 0000 58      PC
+        // 0001 38      CODESIZE
+        // 0002 81      DUP2
+        // 0003 90      SWAP1
+        // 0004 00      STOP
+        let blocks = basic_blocks("5838819000", &[]);
+        let blocks = blocks.blocks();
+
+        assert_eq!(blocks.len(), 1);
+        let operands: Vec<_> = blocks[0]
+            .instructions
+            .iter()
+            .map(|op| op.operand.low_u64())
+            .collect();
+        assert_eq!(operands, [0, 5, 1, 1]);
+        assert_eq!(blocks[0].exit, BlockExit::Stop);
+    }
+
+    #[test]
+    fn test_lookup_block_start() {
+        let blocks = basic_blocks("60016002015B80545000", &[5]);
+
+        assert_eq!(blocks.block_at(0).unwrap().start, 0);
+        assert_eq!(blocks.block_at(5).unwrap().start, 5);
+        assert!(blocks.block_at(2).is_none());
+        assert!(blocks.block_at(7).is_none());
+        assert!(blocks.block_at(100).is_none());
+    }
+}
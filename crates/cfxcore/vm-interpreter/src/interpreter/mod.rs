@@ -24,15 +24,18 @@
 
 //! Rust VM implementation
 
+mod basic_blocks;
 #[macro_use]
 mod informant;
 mod gasometer;
 mod memory;
 mod shared_cache;
 mod stack;
+mod stack_ops;
 
 pub use self::shared_cache::SharedCache;
 use self::{
+    basic_blocks::{BasicBlock, BasicBlocks, BlockEnv, BlockExit},
     gasometer::Gasometer,
     memory::Memory,
     stack::{Stack, VecStack},
@@ -43,30 +46,19 @@ use super::{
 };
 use bit_set::BitSet;
 use cfx_bytes::Bytes;
-use cfx_types::{Address, BigEndianHash, Space, H256, U256, U512};
+use cfx_types::{Address, BigEndianHash, Space, H256, U256};
 use cfx_vm_types::{
     self as vm, ActionParams, ActionValue, CallType, ContractCreateResult,
     CreateContractAddress, GasLeft, InstructionResult, InterpreterInfo,
     MessageCallResult, ParamsType, ReturnData, Spec, TrapError, TrapKind,
 };
 use keccak_hash::keccak;
-use std::{cmp, convert::TryFrom, marker::PhantomData, mem, sync::Arc};
+use std::{cmp, marker::PhantomData, mem, sync::Arc};
 
 const GASOMETER_PROOF: &str = "If gasometer is None, Err is immediately returned in step; this function is only called by step; qed";
 
 type ProgramCounter = usize;
 
-const ONE: U256 = U256([1, 0, 0, 0]);
-const TWO: U256 = U256([2, 0, 0, 0]);
-const TWO_POW_5: U256 = U256([0x20, 0, 0, 0]);
-const TWO_POW_8: U256 = U256([0x100, 0, 0, 0]);
-const TWO_POW_16: U256 = U256([0x10000, 0, 0, 0]);
-const TWO_POW_24: U256 = U256([0x1000000, 0, 0, 0]);
-const TWO_POW_64: U256 = U256([0, 0x1, 0, 0]); // 0x1 00000000 00000000
-const TWO_POW_96: U256 = U256([0, 0x100000000, 0, 0]); //0x1 00000000 00000000 00000000
-const TWO_POW_224: U256 = U256([0, 0, 0, 0x100000000]); //0x1 00000000 00000000 00000000 00000000 00000000 00000000 00000000
-const TWO_POW_248: U256 = U256([0, 0, 0, 0x100000000000000]); //0x1 00000000 00000000 00000000 00000000 00000000 00000000 00000000 000000
-
 /// Maximal subroutine stack size as specified in
 /// https://eips.ethereum.org/EIPS/eip-2315.
 pub const MAX_SUB_STACK_SIZE: usize = 1023;
@@ -170,6 +162,8 @@ pub struct Interpreter<Cost: CostType, const CANCUN: bool> {
     done: bool,
     valid_jump_destinations: Option<Arc<BitSet>>,
     valid_subroutine_destinations: Option<Arc<BitSet>>,
+    basic_blocks: Option<Arc<BasicBlocks>>,
+    use_basic_blocks: bool,
     gasometer: Option<Gasometer<Cost>>,
     stack: VecStack<U256>,
     return_stack: Vec<usize>,
@@ -295,7 +289,7 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
     /// Create a new `Interpreter` instance with shared cache.
     pub fn new(
         mut params: ActionParams, cache: Arc<SharedCache<CANCUN>>, spec: &Spec,
-        depth: usize,
+        depth: usize, use_basic_blocks: bool,
     ) -> Interpreter<Cost, CANCUN> {
         let reader = CodeReader::new(
             params.code.take().expect("VM always called with code; qed"),
@@ -317,6 +311,8 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
             informant,
             valid_jump_destinations,
             valid_subroutine_destinations,
+            basic_blocks: None,
+            use_basic_blocks,
             gasometer,
             stack,
             return_stack,
@@ -367,22 +363,27 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
     ) -> InterpreterResult {
         let result = match self.resume_result.take() {
             Some(result) => result,
-            None => {
-                if self.do_trace {
-                    context.trace_step(self);
-                }
+            // Tracers need a hook on every instruction, so the basic blocks
+            // are only used without them.
+            None => match self.exec_basic_block(context) {
+                Some(result) => result,
+                None => {
+                    if self.do_trace {
+                        context.trace_step(self);
+                    }
 
-                let op_result = self.exec_instruction(context);
+                    let op_result = self.exec_instruction(context);
 
-                if self.do_trace {
-                    context.trace_step_end(self);
-                }
+                    if self.do_trace {
+                        context.trace_step_end(self);
+                    }
 
-                match op_result {
-                    Ok(result) => result,
-                    Err(e) => return e,
+                    match op_result {
+                        Ok(result) => result,
+                        Err(e) => return e,
+                    }
                 }
-            }
+            },
         };
 
         if let InstructionResult::Trap(trap) = result {
@@ -492,6 +493,90 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
         InterpreterResult::Continue
     }
 
+    /// Executes the basic block starting at the current position at once.
+    ///
+    /// Returns `None` if there is no such block or if it might run out of gas
+    /// or stack, in which case the instructions are executed one by one so
+    /// that the error is reported at the exact instruction.
+    fn exec_basic_block(
+        &mut self, context: &mut dyn vm::Context,
+    ) -> Option<InstructionResult<Cost>> {
+        if self.do_trace || !self.use_basic_blocks {
+            return None;
+        }
+        let basic_blocks = match &self.basic_blocks {
+            Some(basic_blocks) => basic_blocks.clone(),
+            None => {
+                let basic_blocks = self
+                    .cache
+                    .basic_blocks(&self.params.code_hash, &self.reader.code);
+                self.basic_blocks = Some(basic_blocks.clone());
+                basic_blocks
+            }
+        };
+        let block = basic_blocks.block_at(self.reader.position)?;
+
+        let spec = context.spec();
+        let stack_size = self.stack.size();
+        if stack_size < block.stack_required
+            || stack_size + block.stack_growth > spec.stack_limit
+        {
+            return None;
+        }
+        let gas_cost = block.gas_cost::<Cost>(spec);
+        let gasometer = self.gasometer.as_mut().expect(GASOMETER_PROOF);
+        if gasometer.verify_gas(&gas_cost).is_err() {
+            return None;
+        }
+        gasometer.current_gas = gasometer.current_gas - gas_cost;
+
+        Some(self.exec_basic_block_inner(block))
+    }
+
+    fn exec_basic_block_inner(
+        &mut self, block: &BasicBlock,
+    ) -> InstructionResult<Cost> {
+        let env = BlockEnv {
+            params: &self.params,
+            mem_size: self.mem.size(),
+            return_data_size: self.return_data.len(),
+        };
+        for op in block.instructions.iter() {
+            op.exec(&mut self.stack, &env);
+        }
+
+        match block.exit {
+            BlockExit::Fallthrough => {
+                self.reader.position = block.end;
+            }
+            BlockExit::Stop => return InstructionResult::StopExecution,
+            BlockExit::Jump(destination) => {
+                self.reader.position = destination;
+            }
+            BlockExit::JumpIf(destination) => {
+                let condition = self.stack.pop_back();
+                self.reader.position = if condition.is_zero() {
+                    block.end
+                } else {
+                    destination
+                };
+            }
+            BlockExit::DynamicJump => {
+                let jump = self.stack.pop_back();
+                return InstructionResult::JumpToPosition(jump);
+            }
+            BlockExit::DynamicJumpIf => {
+                let jump = self.stack.pop_back();
+                let condition = self.stack.pop_back();
+                if !condition.is_zero() {
+                    return InstructionResult::JumpToPosition(jump);
+                }
+                self.reader.position = block.end;
+            }
+        }
+        InstructionResult::Ok
+    }
+
     fn verify_instruction(
         &self, context: &dyn vm::Context, _instruction: Instruction,
         info: &InstructionInfo,
@@ -700,7 +785,13 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
                     // TLOAD
                     let mut key = vec![0; 32];
                     self.stack.pop_back().to_big_endian(key.as_mut());
-                    let word = context.storage_at(&key)?;
+                    // Before CIP-148, TLOAD read the persistent storage. Kept
+                    // for the blocks executed with it.
+                    let word = if context.spec().cip148 {
+                        context.transient_storage_at(&key)?
+                    } else {
+                        context.storage_at(&key)?
+                    };
                     self.stack.push(word);
                 }
             }
@@ -1127,23 +1218,10 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
                     }
                 });
             }
-            instructions::CALLDATALOAD => {
-                let big_id = self.stack.pop_back();
-                let id = big_id.low_u64() as usize;
-                let max = id.wrapping_add(32);
-                if let Some(data) = self.params.data.as_ref() {
-                    let bound = cmp::min(data.len(), max);
-                    if id < bound && big_id < U256::from(data.len()) {
-                        let mut v = [0u8; 32];
-                        v[0..bound - id].clone_from_slice(&data[id..bound]);
-                        self.stack.push(U256::from(&v[..]))
-                    } else {
-                        self.stack.push(U256::zero())
-                    }
-                } else {
-                    self.stack.push(U256::zero())
-                }
-            }
+            instructions::CALLDATALOAD => stack_ops::calldataload(
+                &mut self.stack,
+                self.params.data.as_ref(),
+            ),
             instructions::CALLDATASIZE => {
                 self.stack.push(U256::from(
                     self.params.data.as_ref().map_or(0, |l| l.len()),
@@ -1214,6 +1292,15 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
                 self.stack
                     .push(context.env().base_gas_price[context.space()]);
             }
+            instructions::BLOBHASH => {
+                // Transactions carry no blob, so any index is out of range.
+                self.stack.pop_back();
+                self.stack.push(U256::zero());
+            }
+            instructions::BLOBBASEFEE => {
+                // The blob base fee when there is no excess blob gas.
+                self.stack.push(U256::one());
+            }
             instructions::BLOCKHASH => {
                 let block_number = self.stack.pop_back();
                 let block_hash = context.blockhash(&block_number)?;
@@ -1288,287 +1375,37 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
                 );
                 self.stack.swap_with_top(position)
             }
-            instructions::POP => {
-                self.stack.pop_back();
-            }
-            instructions::ADD => {
-                let a = self.stack.pop_back();
-                let b = self.stack.pop_back();
-                self.stack.push(a.overflowing_add(b).0);
-            }
-            instructions::MUL => {
-                let a = self.stack.pop_back();
-                let b = self.stack.pop_back();
-                self.stack.push(a.overflowing_mul(b).0);
-            }
-            instructions::SUB => {
-                let a = self.stack.pop_back();
-                let b = self.stack.pop_back();
-                self.stack.push(a.overflowing_sub(b).0);
-            }
-            instructions::DIV => {
-                let a = self.stack.pop_back();
-                let b = self.stack.pop_back();
-                self.stack.push(
-                    if !b.is_zero() {
-                        // match b {
-                        //     ONE => a,
-                        //     TWO => a >> 1,
-                        //     TWO_POW_5 => a >> 5,
-                        //     TWO_POW_8 => a >> 8,
-                        //     TWO_POW_16 => a >> 16,
-                        //     TWO_POW_24 => a >> 24,
-                        //     TWO_POW_64 => a >> 64,
-                        //     TWO_POW_96 => a >> 96,
-                        //     TWO_POW_224 => a >> 224,
-                        //     TWO_POW_248 => a >> 248,
-                        //     _ => a / b,
-                        // }
-                        if b == ONE {
-                            a
-                        } else if b == TWO {
-                            a >> 1
-                        } else if b == TWO_POW_5 {
-                            a >> 5
-                        } else if b == TWO_POW_8 {
-                            a >> 8
-                        } else if b == TWO_POW_16 {
-                            a >> 16
-                        } else if b == TWO_POW_24 {
-                            a >> 24
-                        } else if b == TWO_POW_64 {
-                            a >> 64
-                        } else if b == TWO_POW_96 {
-                            a >> 96
-                        } else if b == TWO_POW_224 {
-                            a >> 224
-                        } else if b == TWO_POW_248 {
-                            a >> 248
-                        } else {
-                            a / b
-                        }
-                    } else {
-                        U256::zero()
-                    },
-                );
-            }
-            instructions::MOD => {
-                let a = self.stack.pop_back();
-                let b = self.stack.pop_back();
-                self.stack
-                    .push(if !b.is_zero() { a % b } else { U256::zero() });
-            }
-            instructions::SDIV => {
-                let (a, sign_a) = get_and_reset_sign(self.stack.pop_back());
-                let (b, sign_b) = get_and_reset_sign(self.stack.pop_back());
-
-                // -2^255
-                let min = (U256::one() << 255) - U256::one();
-                self.stack.push(
-                    if b.is_zero() {
-                        U256::zero()
-                    } else if a == min && b == !U256::zero() {
-                        min
-                    } else {
-                        let c = a / b;
-                        set_sign(c, sign_a ^ sign_b)
-                    },
-                );
-            }
-            instructions::SMOD => {
-                let ua = self.stack.pop_back();
-                let ub = self.stack.pop_back();
-                let (a, sign_a) = get_and_reset_sign(ua);
-                let b = get_and_reset_sign(ub).0;
-
-                self.stack.push(
-                    if !b.is_zero() {
-                        let c = a % b;
-                        set_sign(c, sign_a)
-                    } else {
-                        U256::zero()
-                    },
-                );
-            }
+            instructions::POP => stack_ops::pop(&mut self.stack),
+            instructions::ADD => stack_ops::add(&mut self.stack),
+            instructions::MUL => stack_ops::mul(&mut self.stack),
+            instructions::SUB => stack_ops::sub(&mut self.stack),
+            instructions::DIV => stack_ops::div(&mut self.stack),
+            instructions::MOD => stack_ops::modulo(&mut self.stack),
+            instructions::SDIV => stack_ops::sdiv(&mut self.stack),
+            instructions::SMOD => stack_ops::smod(&mut self.stack),
             instructions::EXP => {
                 let base = self.stack.pop_back();
                 let expon = self.stack.pop_back();
                 let res = base.overflowing_pow(expon).0;
                 self.stack.push(res);
             }
-            instructions::NOT => {
-                let a = self.stack.pop_back();
-                self.stack.push(!a);
-            }
-            instructions::LT => {
-                let a = self.stack.pop_back();
-                let b = self.stack.pop_back();
-                self.stack.push(Self::bool_to_u256(a < b));
-            }
-            instructions::SLT => {
-                let (a, neg_a) = get_and_reset_sign(self.stack.pop_back());
-                let (b, neg_b) = get_and_reset_sign(self.stack.pop_back());
-
-                let is_positive_lt = a < b && !(neg_a | neg_b);
-                let is_negative_lt = a > b && (neg_a & neg_b);
-                let has_different_signs = neg_a && !neg_b;
-
-                self.stack.push(Self::bool_to_u256(
-                    is_positive_lt | is_negative_lt | has_different_signs,
-                ));
-            }
-            instructions::GT => {
-                let a = self.stack.pop_back();
-                let b = self.stack.pop_back();
-                self.stack.push(Self::bool_to_u256(a > b));
-            }
-            instructions::SGT => {
-                let (a, neg_a) = get_and_reset_sign(self.stack.pop_back());
-                let (b, neg_b) = get_and_reset_sign(self.stack.pop_back());
-
-                let is_positive_gt = a > b && !(neg_a | neg_b);
-                let is_negative_gt = a < b && (neg_a & neg_b);
-                let has_different_signs = !neg_a && neg_b;
-
-                self.stack.push(Self::bool_to_u256(
-                    is_positive_gt | is_negative_gt | has_different_signs,
-                ));
-            }
-            instructions::EQ => {
-                let a = self.stack.pop_back();
-                let b = self.stack.pop_back();
-                self.stack.push(Self::bool_to_u256(a == b));
-            }
-            instructions::ISZERO => {
-                let a = self.stack.pop_back();
-                self.stack.push(Self::bool_to_u256(a.is_zero()));
-            }
-            instructions::AND => {
-                let a = self.stack.pop_back();
-                let b = self.stack.pop_back();
-                self.stack.push(a & b);
-            }
-            instructions::OR => {
-                let a = self.stack.pop_back();
-                let b = self.stack.pop_back();
-                self.stack.push(a | b);
-            }
-            instructions::XOR => {
-                let a = self.stack.pop_back();
-                let b = self.stack.pop_back();
-                self.stack.push(a ^ b);
-            }
-            instructions::BYTE => {
-                let word = self.stack.pop_back();
-                let val = self.stack.pop_back();
-                let byte = match word < U256::from(32) {
-                    true => {
-                        (val >> (8 * (31 - word.low_u64() as usize)))
-                            & U256::from(0xff)
-                    }
-                    false => U256::zero(),
-                };
-                self.stack.push(byte);
-            }
-            instructions::ADDMOD => {
-                let a = self.stack.pop_back();
-                let b = self.stack.pop_back();
-                let c = self.stack.pop_back();
-
-                self.stack.push(
-                    if !c.is_zero() {
-                        // upcast to 512
-                        let a5 = U512::from(a);
-                        let res = a5.overflowing_add(U512::from(b)).0;
-                        let x = res % U512::from(c);
-                        U256::try_from(x).expect("U512 % U256 fits U256; qed")
-                    } else {
-                        U256::zero()
-                    },
-                );
-            }
-            instructions::MULMOD => {
-                let a = self.stack.pop_back();
-                let b = self.stack.pop_back();
-                let c = self.stack.pop_back();
-
-                self.stack.push(
-                    if !c.is_zero() {
-                        let a5 = U512::from(a);
-                        let res = a5.overflowing_mul(U512::from(b)).0;
-                        let x = res % U512::from(c);
-                        U256::try_from(x).expect("U512 % U256 fits U256; qed")
-                    } else {
-                        U256::zero()
-                    },
-                );
-            }
-            instructions::SIGNEXTEND => {
-                let bit = self.stack.pop_back();
-                if bit < U256::from(32) {
-                    let number = self.stack.pop_back();
-                    let bit_position = (bit.low_u64() * 8 + 7) as usize;
-
-                    let bit = number.bit(bit_position);
-                    let mask = (U256::one() << bit_position) - U256::one();
-                    self.stack
-                        .push(if bit { number | !mask } else { number & mask });
-                }
-            }
-            instructions::SHL => {
-                const CONST_256: U256 = U256([256, 0, 0, 0]);
-
-                let shift = self.stack.pop_back();
-                let value = self.stack.pop_back();
-
-                let result = if shift >= CONST_256 {
-                    U256::zero()
-                } else {
-                    value << (shift.as_u32() as usize)
-                };
-                self.stack.push(result);
-            }
-            instructions::SHR => {
-                const CONST_256: U256 = U256([256, 0, 0, 0]);
-
-                let shift = self.stack.pop_back();
-                let value = self.stack.pop_back();
-
-                let result = if shift >= CONST_256 {
-                    U256::zero()
-                } else {
-                    value >> (shift.as_u32() as usize)
-                };
-                self.stack.push(result);
-            }
-            instructions::SAR => {
-                // We cannot use get_and_reset_sign/set_sign here, because the
-                // rounding looks different.
-
-                const CONST_256: U256 = U256([256, 0, 0, 0]);
-                const CONST_HIBIT: U256 = U256([0, 0, 0, 0x8000000000000000]);
-
-                let shift = self.stack.pop_back();
-                let value = self.stack.pop_back();
-                let sign = value & CONST_HIBIT != U256::zero();
-
-                let result = if shift >= CONST_256 {
-                    if sign {
-                        U256::max_value()
-                    } else {
-                        U256::zero()
-                    }
-                } else {
-                    let shift = shift.as_u32() as usize;
-                    let mut shifted = value >> shift;
-                    if sign {
-                        shifted =
-                            shifted | (U256::max_value() << (256 - shift));
-                    }
-                    shifted
-                };
-                self.stack.push(result);
-            }
+            instructions::NOT => stack_ops::not(&mut self.stack),
+            instructions::LT => stack_ops::lt(&mut self.stack),
+            instructions::SLT => stack_ops::slt(&mut self.stack),
+            instructions::GT => stack_ops::gt(&mut self.stack),
+            instructions::SGT => stack_ops::sgt(&mut self.stack),
+            instructions::EQ => stack_ops::eq(&mut self.stack),
+            instructions::ISZERO => stack_ops::iszero(&mut self.stack),
+            instructions::AND => stack_ops::and(&mut self.stack),
+            instructions::OR => stack_ops::or(&mut self.stack),
+            instructions::XOR => stack_ops::xor(&mut self.stack),
+            instructions::BYTE => stack_ops::byte(&mut self.stack),
+            instructions::ADDMOD => stack_ops::addmod(&mut self.stack),
+            instructions::MULMOD => stack_ops::mulmod(&mut self.stack),
+            instructions::SIGNEXTEND => stack_ops::signextend(&mut self.stack),
+            instructions::SHL => stack_ops::shl(&mut self.stack),
+            instructions::SHR => stack_ops::shr(&mut self.stack),
+            instructions::SAR => stack_ops::sar(&mut self.stack),
         };
         Ok(InstructionResult::Ok)
     }
@@ -1661,14 +1498,6 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
             Err(vm::Error::BadJumpDestination { destination: jump })
         }
     }
-
-    fn bool_to_u256(val: bool) -> U256 {
-        if val {
-            U256::one()
-        } else {
-            U256::zero()
-        }
-    }
 }
 
 impl<Cost: CostType, const CANCUN: bool> InterpreterInfo
@@ -1702,20 +1531,6 @@ impl<Cost: CostType, const CANCUN: bool> InterpreterInfo
     fn contract_address(&self) -> Address { self.params.address }
 }
 
-fn get_and_reset_sign(value: U256) -> (U256, bool) {
-    let U256(arr) = value;
-    let sign = arr[3].leading_zeros() == 0;
-    (set_sign(value, sign), sign)
-}
-
-fn set_sign(value: U256, sign: bool) -> U256 {
-    if sign {
-        (!U256::zero() ^ value).overflowing_add(U256::one()).0
-    } else {
-        value
-    }
-}
-
 #[inline]
 fn u256_to_address(value: &U256) -> Address {
     let addr: H256 = BigEndianHash::from_uint(value);
@@ -1728,7 +1543,7 @@ fn address_to_u256(value: Address) -> U256 { H256::from(value).into_uint() }
 #[cfg(test)]
 mod tests {
     use crate::{factory::Factory, vmtype::VMType};
-    use cfx_types::Address;
+    use cfx_types::{Address, U256};
     use cfx_vm_types::{
         self as vm,
         tests::{test_finalize, MockContext},
@@ -1799,4 +1614,65 @@ mod tests {
 
         assert_eq!(err, cfx_vm_types::Error::OutOfBounds);
     }
+
+    fn exec_code(
+        code: &str, gas: u64, basic_blocks: bool,
+    ) -> (vm::Result<U256>, MockContext) {
+        let mut params = ActionParams::default();
+        params.address = Address::from_low_u64_be(5);
+        params.sender = Address::from_low_u64_be(6);
+        params.original_sender = Address::from_low_u64_be(7);
+        params.value = ActionValue::Transfer(100.into());
+        params.data = Some(vec![0xff; 40]);
+        params.gas = gas.into();
+        params.code = Some(Arc::new(code.from_hex().unwrap()));
+        let mut context = MockContext::new_spec();
+        context
+            .balances
+            .insert(Address::from_low_u64_be(5), 1_000_000_000.into());
+
+        let mut factory = Factory::new(VMType::Interpreter, 1024 * 32);
+        if !basic_blocks {
+            factory = factory.without_basic_blocks();
+        }
+        let result = {
+            let vm = factory.create(params, context.spec(), context.depth());
+            test_finalize(vm.exec(&mut context).ok().unwrap())
+        };
+        (result, context)
+    }
+
+    #[test]
+    fn basic_blocks_should_match_step_by_step_execution() {
+        // Sums 1..=10 into storage slot 0 in a loop.
+        let sum = "6000600a5b801560155780910190600190036004565b50600055";
+        // Adds up CALLER, CALLVALUE, CALLDATALOAD(0), CALLDATASIZE, ADDRESS,
+        // ORIGIN, MSIZE, RETURNDATASIZE, PC and CODESIZE into storage slot 0.
+        let env = "3334016000350136013001320159013d0158013801600055";
+
+        for code in [sum, env] {
+            let (result, context) = exec_code(code, 100_000, true);
+            let (expected, expected_context) = exec_code(code, 100_000, false);
+
+            assert!(result.is_ok());
+            assert_eq!(result, expected);
+            assert_eq!(context.store, expected_context.store);
+        }
+        let (_, context) = exec_code(sum, 100_000, true);
+        assert_eq!(context.store.get(&vec![0; 32]), Some(&U256::from(55)));
+
+        // Running out of gas or stack inside a block reports the same error.
+        for (code, gas) in [(sum, 300), ("6001600201", 5), ("600101", 100)] {
+            let (result, _) = exec_code(code, gas, true);
+            let (expected, _) = exec_code(code, gas, false);
+            assert!(result.is_err());
+            assert_eq!(result, expected);
+        }
+
+        // A dynamic jump to an invalid destination fails in both.
+        let (result, _) = exec_code("600356", 100, true);
+        let (expected, _) = exec_code("600356", 100, false);
+        assert!(result.is_err());
+        assert_eq!(result, expected);
+    }
 }
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use super::basic_blocks::BasicBlocks;
 use crate::instructions::{self, Instruction};
 use bit_set::BitSet;
 use cfx_types::H256;
@@ -44,11 +45,14 @@ impl MallocSizeOf for Bits {
 struct CacheItem {
     jump_destination: Bits,
     sub_entrypoint: Bits,
+    basic_blocks: Arc<BasicBlocks>,
 }
 
 impl MallocSizeOf for CacheItem {
     fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
-        self.jump_destination.size_of(ops) + self.sub_entrypoint.size_of(ops)
+        self.jump_destination.size_of(ops)
+            + self.sub_entrypoint.size_of(ops)
+            + self.basic_blocks.size_of(ops)
     }
 }
 
@@ -70,25 +74,32 @@ impl<const CANCUN: bool> SharedCache<CANCUN> {
     pub fn jump_and_sub_destinations(
         &self, code_hash: &H256, code: &[u8],
     ) -> (Arc<BitSet>, Arc<BitSet>) {
+        let d = self.analyse(code_hash, code);
+        (d.jump_destination.0, d.sub_entrypoint.0)
+    }
+
+    /// Get the basic blocks of a contract.
+    pub(super) fn basic_blocks(
+        &self, code_hash: &H256, code: &[u8],
+    ) -> Arc<BasicBlocks> {
+        self.analyse(code_hash, code).basic_blocks
+    }
+
+    fn analyse(&self, code_hash: &H256, code: &[u8]) -> CacheItem {
         if code_hash == &KECCAK_EMPTY {
-            let cache_item = Self::find_jump_and_sub_destinations(code);
-            return (
-                cache_item.jump_destination.0,
-                cache_item.sub_entrypoint.0,
-            );
+            return Self::analyse_code(code);
         }
 
         if let Some(d) = self.jump_destinations.lock().get_mut(code_hash) {
-            return (d.jump_destination.0.clone(), d.sub_entrypoint.0.clone());
+            return d.clone();
         }
 
-        let d = Self::find_jump_and_sub_destinations(code);
+        let d = Self::analyse_code(code);
         self.jump_destinations.lock().insert(*code_hash, d.clone());
-
-        (d.jump_destination.0, d.sub_entrypoint.0)
+        d
     }
 
-    fn find_jump_and_sub_destinations(code: &[u8]) -> CacheItem {
+    fn analyse_code(code: &[u8]) -> CacheItem {
         let mut jump_dests = BitSet::with_capacity(code.len());
         let mut sub_entrypoints = BitSet::with_capacity(code.len());
         let mut position = 0;
@@ -115,9 +126,11 @@ impl<const CANCUN: bool> SharedCache<CANCUN> {
         }
 
         jump_dests.shrink_to_fit();
+        let basic_blocks = BasicBlocks::new::<CANCUN>(code, &jump_dests);
         CacheItem {
             jump_destination: Bits(Arc::new(jump_dests)),
             sub_entrypoint: Bits(Arc::new(sub_entrypoints)),
+            basic_blocks: Arc::new(basic_blocks),
         }
     }
 }
@@ -141,8 +154,7 @@ fn test_find_jump_destinations() {
     let code: Vec<u8> = "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff5b01600055".from_hex().unwrap();
 
     // when
-    let cache_item =
-        SharedCache::<false>::find_jump_and_sub_destinations(&code);
+    let cache_item = SharedCache::<false>::analyse_code(&code);
 
     // then
     assert!(cache_item
@@ -167,8 +179,7 @@ fn test_find_jump_destinations_not_in_data_segments() {
     let code: Vec<u8> = "600656605B565B6004".from_hex().unwrap();
 
     // when
-    let cache_item =
-        SharedCache::<false>::find_jump_and_sub_destinations(&code);
+    let cache_item = SharedCache::<false>::analyse_code(&code);
 
     // then
     assert!(cache_item.jump_destination.0.iter().eq(vec![6].into_iter()));
@@ -184,8 +195,7 @@ fn test_find_sub_entrypoints() {
         "6800000000000000000c5e005c60115e5d5c5d".from_hex().unwrap();
 
     // when
-    let cache_item =
-        SharedCache::<false>::find_jump_and_sub_destinations(&code);
+    let cache_item = SharedCache::<false>::analyse_code(&code);
 
     // then
     assert!(cache_item.jump_destination.0.is_empty());
@@ -209,8 +219,7 @@ fn test_find_jump_and_sub_allowing_unknown_opcodes() {
     let code: Vec<u8> = "5BCC5C".from_hex().unwrap();
 
     // when
-    let cache_item =
-        SharedCache::<false>::find_jump_and_sub_destinations(&code);
+    let cache_item = SharedCache::<false>::analyse_code(&code);
 
     // then
     assert!(cache_item.jump_destination.0.iter().eq(vec![0].into_iter()));
@@ -0,0 +1,402 @@
+// Copyright 2019-2020 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! The instructions which only operate on the stack, shared by the step by
+//! step execution and the dispatch table of the basic blocks.
+
+use super::stack::{Stack, VecStack};
+use cfx_bytes::Bytes;
+use cfx_types::{U256, U512};
+use std::{cmp, convert::TryFrom};
+
+const ONE: U256 = U256([1, 0, 0, 0]);
+const TWO: U256 = U256([2, 0, 0, 0]);
+const TWO_POW_5: U256 = U256([0x20, 0, 0, 0]);
+const TWO_POW_8: U256 = U256([0x100, 0, 0, 0]);
+const TWO_POW_16: U256 = U256([0x10000, 0, 0, 0]);
+const TWO_POW_24: U256 = U256([0x1000000, 0, 0, 0]);
+const TWO_POW_64: U256 = U256([0, 0x1, 0, 0]); // 0x1 00000000 00000000
+const TWO_POW_96: U256 = U256([0, 0x100000000, 0, 0]); //0x1 00000000 00000000 00000000
+const TWO_POW_224: U256 = U256([0, 0, 0, 0x100000000]); //0x1 00000000 00000000 00000000 00000000 00000000 00000000 00000000
+const TWO_POW_248: U256 = U256([0, 0, 0, 0x100000000000000]); //0x1 00000000 00000000 00000000 00000000 00000000 00000000 00000000 000000
+
+#[inline(always)]
+pub(super) fn pop(stack: &mut VecStack<U256>) { stack.pop_back(); }
+
+#[inline(always)]
+pub(super) fn add(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    let b = stack.pop_back();
+    stack.push(a.overflowing_add(b).0);
+}
+
+#[inline(always)]
+pub(super) fn mul(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    let b = stack.pop_back();
+    stack.push(a.overflowing_mul(b).0);
+}
+
+#[inline(always)]
+pub(super) fn sub(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    let b = stack.pop_back();
+    stack.push(a.overflowing_sub(b).0);
+}
+
+#[inline(always)]
+pub(super) fn div(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    let b = stack.pop_back();
+    stack.push(
+        if !b.is_zero() {
+            // match b {
+            //     ONE => a,
+            //     TWO => a >> 1,
+            //     TWO_POW_5 => a >> 5,
+            //     TWO_POW_8 => a >> 8,
+            //     TWO_POW_16 => a >> 16,
+            //     TWO_POW_24 => a >> 24,
+            //     TWO_POW_64 => a >> 64,
+            //     TWO_POW_96 => a >> 96,
+            //     TWO_POW_224 => a >> 224,
+            //     TWO_POW_248 => a >> 248,
+            //     _ => a / b,
+            // }
+            if b == ONE {
+                a
+            } else if b == TWO {
+                a >> 1
+            } else if b == TWO_POW_5 {
+                a >> 5
+            } else if b == TWO_POW_8 {
+                a >> 8
+            } else if b == TWO_POW_16 {
+                a >> 16
+            } else if b == TWO_POW_24 {
+                a >> 24
+            } else if b == TWO_POW_64 {
+                a >> 64
+            } else if b == TWO_POW_96 {
+                a >> 96
+            } else if b == TWO_POW_224 {
+                a >> 224
+            } else if b == TWO_POW_248 {
+                a >> 248
+            } else {
+                a / b
+            }
+        } else {
+            U256::zero()
+        },
+    );
+}
+
+#[inline(always)]
+pub(super) fn modulo(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    let b = stack.pop_back();
+    stack.push(if !b.is_zero() { a % b } else { U256::zero() });
+}
+
+#[inline(always)]
+pub(super) fn sdiv(stack: &mut VecStack<U256>) {
+    let (a, sign_a) = get_and_reset_sign(stack.pop_back());
+    let (b, sign_b) = get_and_reset_sign(stack.pop_back());
+
+    // -2^255
+    let min = (U256::one() << 255) - U256::one();
+    stack.push(
+        if b.is_zero() {
+            U256::zero()
+        } else if a == min && b == !U256::zero() {
+            min
+        } else {
+            let c = a / b;
+            set_sign(c, sign_a ^ sign_b)
+        },
+    );
+}
+
+#[inline(always)]
+pub(super) fn smod(stack: &mut VecStack<U256>) {
+    let ua = stack.pop_back();
+    let ub = stack.pop_back();
+    let (a, sign_a) = get_and_reset_sign(ua);
+    let b = get_and_reset_sign(ub).0;
+
+    stack.push(
+        if !b.is_zero() {
+            let c = a % b;
+            set_sign(c, sign_a)
+        } else {
+            U256::zero()
+        },
+    );
+}
+
+#[inline(always)]
+pub(super) fn not(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    stack.push(!a);
+}
+
+#[inline(always)]
+pub(super) fn lt(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    let b = stack.pop_back();
+    stack.push(bool_to_u256(a < b));
+}
+
+#[inline(always)]
+pub(super) fn slt(stack: &mut VecStack<U256>) {
+    let (a, neg_a) = get_and_reset_sign(stack.pop_back());
+    let (b, neg_b) = get_and_reset_sign(stack.pop_back());
+
+    let is_positive_lt = a < b && !(neg_a | neg_b);
+    let is_negative_lt = a > b && (neg_a & neg_b);
+    let has_different_signs = neg_a && !neg_b;
+
+    stack.push(bool_to_u256(
+        is_positive_lt | is_negative_lt | has_different_signs,
+    ));
+}
+
+#[inline(always)]
+pub(super) fn gt(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    let b = stack.pop_back();
+    stack.push(bool_to_u256(a > b));
+}
+
+#[inline(always)]
+pub(super) fn sgt(stack: &mut VecStack<U256>) {
+    let (a, neg_a) = get_and_reset_sign(stack.pop_back());
+    let (b, neg_b) = get_and_reset_sign(stack.pop_back());
+
+    let is_positive_gt = a > b && !(neg_a | neg_b);
+    let is_negative_gt = a < b && (neg_a & neg_b);
+    let has_different_signs = !neg_a && neg_b;
+
+    stack.push(bool_to_u256(
+        is_positive_gt | is_negative_gt | has_different_signs,
+    ));
+}
+
+#[inline(always)]
+pub(super) fn eq(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    let b = stack.pop_back();
+    stack.push(bool_to_u256(a == b));
+}
+
+#[inline(always)]
+pub(super) fn iszero(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    stack.push(bool_to_u256(a.is_zero()));
+}
+
+#[inline(always)]
+pub(super) fn and(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    let b = stack.pop_back();
+    stack.push(a & b);
+}
+
+#[inline(always)]
+pub(super) fn or(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    let b = stack.pop_back();
+    stack.push(a | b);
+}
+
+#[inline(always)]
+pub(super) fn xor(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    let b = stack.pop_back();
+    stack.push(a ^ b);
+}
+
+#[inline(always)]
+pub(super) fn byte(stack: &mut VecStack<U256>) {
+    let word = stack.pop_back();
+    let val = stack.pop_back();
+    let byte = match word < U256::from(32) {
+        true => {
+            (val >> (8 * (31 - word.low_u64() as usize))) & U256::from(0xff)
+        }
+        false => U256::zero(),
+    };
+    stack.push(byte);
+}
+
+#[inline(always)]
+pub(super) fn addmod(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    let b = stack.pop_back();
+    let c = stack.pop_back();
+
+    stack.push(
+        if !c.is_zero() {
+            // upcast to 512
+            let a5 = U512::from(a);
+            let res = a5.overflowing_add(U512::from(b)).0;
+            let x = res % U512::from(c);
+            U256::try_from(x).expect("U512 % U256 fits U256; qed")
+        } else {
+            U256::zero()
+        },
+    );
+}
+
+#[inline(always)]
+pub(super) fn mulmod(stack: &mut VecStack<U256>) {
+    let a = stack.pop_back();
+    let b = stack.pop_back();
+    let c = stack.pop_back();
+
+    stack.push(
+        if !c.is_zero() {
+            let a5 = U512::from(a);
+            let res = a5.overflowing_mul(U512::from(b)).0;
+            let x = res % U512::from(c);
+            U256::try_from(x).expect("U512 % U256 fits U256; qed")
+        } else {
+            U256::zero()
+        },
+    );
+}
+
+#[inline(always)]
+pub(super) fn signextend(stack: &mut VecStack<U256>) {
+    let bit = stack.pop_back();
+    if bit < U256::from(32) {
+        let number = stack.pop_back();
+        let bit_position = (bit.low_u64() * 8 + 7) as usize;
+
+        let bit = number.bit(bit_position);
+        let mask = (U256::one() << bit_position) - U256::one();
+        stack.push(if bit { number | !mask } else { number & mask });
+    }
+}
+
+#[inline(always)]
+pub(super) fn shl(stack: &mut VecStack<U256>) {
+    const CONST_256: U256 = U256([256, 0, 0, 0]);
+
+    let shift = stack.pop_back();
+    let value = stack.pop_back();
+
+    let result = if shift >= CONST_256 {
+        U256::zero()
+    } else {
+        value << (shift.as_u32() as usize)
+    };
+    stack.push(result);
+}
+
+#[inline(always)]
+pub(super) fn shr(stack: &mut VecStack<U256>) {
+    const CONST_256: U256 = U256([256, 0, 0, 0]);
+
+    let shift = stack.pop_back();
+    let value = stack.pop_back();
+
+    let result = if shift >= CONST_256 {
+        U256::zero()
+    } else {
+        value >> (shift.as_u32() as usize)
+    };
+    stack.push(result);
+}
+
+#[inline(always)]
+pub(super) fn sar(stack: &mut VecStack<U256>) {
+    // We cannot use get_and_reset_sign/set_sign here, because the
+    // rounding looks different.
+
+    const CONST_256: U256 = U256([256, 0, 0, 0]);
+    const CONST_HIBIT: U256 = U256([0, 0, 0, 0x8000000000000000]);
+
+    let shift = stack.pop_back();
+    let value = stack.pop_back();
+    let sign = value & CONST_HIBIT != U256::zero();
+
+    let result = if shift >= CONST_256 {
+        if sign {
+            U256::max_value()
+        } else {
+            U256::zero()
+        }
+    } else {
+        let shift = shift.as_u32() as usize;
+        let mut shifted = value >> shift;
+        if sign {
+            shifted = shifted | (U256::max_value() << (256 - shift));
+        }
+        shifted
+    };
+    stack.push(result);
+}
+
+#[inline(always)]
+pub(super) fn calldataload(stack: &mut VecStack<U256>, data: Option<&Bytes>) {
+    let big_id = stack.pop_back();
+    let id = big_id.low_u64() as usize;
+    let max = id.wrapping_add(32);
+    if let Some(data) = data {
+        let bound = cmp::min(data.len(), max);
+        if id < bound && big_id < U256::from(data.len()) {
+            let mut v = [0u8; 32];
+            v[0..bound - id].clone_from_slice(&data[id..bound]);
+            stack.push(U256::from(&v[..]))
+        } else {
+            stack.push(U256::zero())
+        }
+    } else {
+        stack.push(U256::zero())
+    }
+}
+
+fn bool_to_u256(val: bool) -> U256 {
+    if val {
+        U256::one()
+    } else {
+        U256::zero()
+    }
+}
+
+fn get_and_reset_sign(value: U256) -> (U256, bool) {
+    let U256(arr) = value;
+    let sign = arr[3].leading_zeros() == 0;
+    (set_sign(value, sign), sign)
+}
+
+fn set_sign(value: U256, sign: bool) -> U256 {
+    if sign {
+        (!U256::zero() ^ value).overflowing_add(U256::one()).0
+    } else {
+        value
+    }
+}
@@ -1316,6 +1316,80 @@ fn test_create_in_staticcall(factory: super::Factory) {
     assert_eq!(ctx.calls.len(), 0);
 }
 
+evm_test! {test_transient_storage: test_transient_storage_int}
+fn test_transient_storage(factory: super::Factory) {
+    // TSTORE 0x2a at 1, then TLOAD 1 and SSTORE it at 0.
+    let code = "602a60015d60015c60005500".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ctx = MockContext::new_spec();
+    ctx.spec.cancun_opcodes = true;
+
+    // Before CIP-148, TLOAD reads the persistent storage.
+    {
+        let vm = factory.create(params.clone(), ctx.spec(), ctx.depth());
+        test_finalize(vm.exec(&mut ctx).ok().unwrap()).unwrap();
+    }
+    assert_store(
+        &ctx,
+        0,
+        "0000000000000000000000000000000000000000000000000000000000000000",
+    );
+
+    ctx.spec.cip148 = true;
+    {
+        let vm = factory.create(params, ctx.spec(), ctx.depth());
+        test_finalize(vm.exec(&mut ctx).ok().unwrap()).unwrap();
+    }
+
+    assert_store(
+        &ctx,
+        0,
+        "000000000000000000000000000000000000000000000000000000000000002a",
+    );
+    let mut key = vec![0; 32];
+    U256::one().to_big_endian(key.as_mut());
+    assert_eq!(ctx.store.get(&key), None);
+    assert_eq!(ctx.transient_store.get(&key), Some(&U256::from(0x2a)));
+}
+
+evm_test! {test_blob_opcodes: test_blob_opcodes_int}
+fn test_blob_opcodes(factory: super::Factory) {
+    // SSTORE BLOBHASH(0) at 0 and BLOBBASEFEE at 1.
+    let code = "6000496000554a60015500".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ctx = MockContext::new_spec();
+
+    let err = {
+        let vm = factory.create(params.clone(), ctx.spec(), ctx.depth());
+        test_finalize(vm.exec(&mut ctx).ok().unwrap()).unwrap_err()
+    };
+    assert_eq!(err, vm::Error::BadInstruction { instruction: 0x49 });
+
+    ctx.spec.cip148 = true;
+    let gas_left = {
+        let vm = factory.create(params, ctx.spec(), ctx.depth());
+        test_finalize(vm.exec(&mut ctx).ok().unwrap()).unwrap()
+    };
+
+    assert_eq!(gas_left, U256::from(89_986));
+    assert_store(
+        &ctx,
+        0,
+        "0000000000000000000000000000000000000000000000000000000000000000",
+    );
+    assert_store(
+        &ctx,
+        1,
+        "0000000000000000000000000000000000000000000000000000000000000001",
+    );
+}
+
 fn assert_set_contains<T: Debug + Eq + PartialEq + Hash>(
     set: &HashSet<T>, val: &T,
 ) {
@@ -185,6 +185,13 @@ pub struct Spec {
     pub cip144: bool,
     /// CIP-145: Fix Receipts upon `NotEnoughBalance` Error
     pub cip145: bool,
+    /// CIP-146: Cross-Space Call from eSpace to Core Space
+    pub cip146: bool,
+    /// CIP-147: Gas Sponsorship for eSpace Contracts
+    pub cip147: bool,
+    /// CIP-148: BLOBHASH and BLOBBASEFEE Opcodes, and TLOAD Reading the
+    /// Transient Storage
+    pub cip148: bool,
 }
 
 /// Wasm cost table
@@ -337,6 +344,9 @@ impl Spec {
             cip133_core: false,
             cip137: false,
             cip145: false,
+            cip146: false,
+            cip147: false,
+            cip148: false,
             cip1559: false,
             cancun_opcodes: false,
             cip144: false,
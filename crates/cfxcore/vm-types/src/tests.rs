@@ -63,6 +63,7 @@ pub struct MockCall {
 #[derive(Default)]
 pub struct MockContext {
     pub store: HashMap<Vec<u8>, U256>,
+    pub transient_store: HashMap<Vec<u8>, U256>,
     pub suicides: HashSet<Address>,
     pub calls: HashSet<MockCall>,
     pub sstore_clears: i128,
@@ -123,13 +124,18 @@ impl Context for MockContext {
         Ok(())
     }
 
-    fn transient_storage_at(&self, _key: &Vec<u8>) -> Result<U256> {
-        Ok(U256::zero())
+    fn transient_storage_at(&self, key: &Vec<u8>) -> Result<U256> {
+        Ok(self
+            .transient_store
+            .get(key)
+            .unwrap_or(&U256::zero())
+            .clone())
     }
 
     fn transient_set_storage(
-        &mut self, _key: Vec<u8>, _value: U256,
+        &mut self, key: Vec<u8>, value: U256,
     ) -> Result<()> {
+        self.transient_store.insert(key, value);
         Ok(())
     }
 
@@ -9,6 +9,7 @@ use jsonrpc_ws_server::Server as WsServer;
 use crate::{
     common::{initialize_not_light_node_modules, ClientComponents},
     configuration::Configuration,
+    rpc::GraphQLServer,
 };
 use blockgen::BlockGenerator;
 use cfxcore::{
@@ -34,6 +35,7 @@ pub struct ArchiveClientExtraComponents {
     pub pow: Arc<PowComputer>,
     pub eth_rpc_http_server: Option<HttpServer>,
     pub eth_rpc_ws_server: Option<WsServer>,
+    pub graphql_server: Option<GraphQLServer>,
 }
 
 impl MallocSizeOf for ArchiveClientExtraComponents {
@@ -74,6 +76,7 @@ impl ArchiveClient {
             runtime,
             eth_rpc_http_server,
             eth_rpc_ws_server,
+            graphql_server,
         ) = initialize_not_light_node_modules(
             &mut conf,
             exit,
@@ -97,6 +100,7 @@ impl ArchiveClient {
                 pow,
                 eth_rpc_http_server,
                 eth_rpc_ws_server,
+                graphql_server,
             },
         }))
     }
@@ -0,0 +1,598 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Reads the state of a remote node over its public RPC, so that a local dev
+//! node can fork off the remote chain at a fixed epoch.
+//!
+//! The storage entries are rebuilt from the `cfx_*` and `eth_*` RPCs, which
+//! don't expose everything kept in the storage layer:
+//!   * Storage owners are unknown, so the collateral of the forked storage
+//!     entries is attributed to the contract itself.
+//!   * The code owner is approximated by the contract admin.
+//!   * Storage layouts aren't exposed either. The regular layout is the only
+//!     one in use, so it is assumed for every address, which is harmless for
+//!     the addresses without storage.
+//!   * The storage of a contract can only be enumerated, e.g. to destroy the
+//!     contract, if the remote node exposes `debug_storageRangeAt`.
+//!   * The global parameters, such as the total issued tokens, are rebuilt from
+//!     the supply, collateral, interest, PoS and vote RPCs.
+
+use std::{
+    sync::{
+        mpsc::{channel, Sender},
+        Arc,
+    },
+    thread,
+};
+
+use cfx_addr::Network;
+use cfx_parameters::{
+    internal_contract_addresses::STORAGE_INTEREST_STAKING_CONTRACT_ADDRESS,
+    staking::DRIPS_PER_STORAGE_COLLATERAL_UNIT,
+};
+use cfx_statedb::global_params::*;
+use cfx_storage::{ForkStateSource, MptKeyValue, Result as StorageResult};
+use cfx_types::{
+    address_util::AddressUtil, Address, AddressSpaceUtil, Space, H256, U256,
+};
+use keccak_hash::{keccak, KECCAK_EMPTY};
+use network::NetworkConfiguration;
+use parking_lot::Mutex;
+use primitives::{
+    account::StoragePoints, storage::STORAGE_LAYOUT_REGULAR_V0, Account,
+    CodeInfo, DepositInfo, DepositList, SponsorInfo, StorageKey,
+    StorageKeyWithSpace, StorageValue, VoteStakeInfo, VoteStakeList,
+};
+use rlp::Rlp;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::rpc::types::{
+    Account as RpcAccount, Bytes, RpcAddress, SponsorInfo as RpcSponsorInfo,
+    U64,
+};
+
+/// The page size of the `debug_storageRangeAt` requests.
+const STORAGE_RANGE_PAGE_SIZE: usize = 256;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcStorageRange<K> {
+    storage: Vec<RpcStorageRangeEntry<K>>,
+    next_key: Option<K>,
+}
+
+#[derive(Deserialize)]
+struct RpcStorageRangeEntry<K> {
+    key: K,
+    value: U256,
+    #[serde(default)]
+    owner: Option<RpcAddress>,
+}
+
+struct RpcRequest {
+    method: &'static str,
+    params: Value,
+    response: Sender<Result<Value, String>>,
+}
+
+pub struct RpcForkStateSource {
+    network: Network,
+    /// The forked epoch number, as a hex string.
+    epoch: String,
+    // The blocking http client can't be used from the async rpc handlers, so
+    // requests are sent from a dedicated thread. We need `Mutex` to make the
+    // struct `Sync`.
+    request_sender: Mutex<Sender<RpcRequest>>,
+}
+
+impl RpcForkStateSource {
+    /// Forks off the state of `url` at `epoch`, or at its latest state if
+    /// `epoch` is `None`.
+    pub fn new(url: String, epoch: Option<u64>) -> Result<Self, String> {
+        let (request_sender, request_receiver) = channel::<RpcRequest>();
+        thread::Builder::new()
+            .name("Fork state rpc".into())
+            .spawn(move || {
+                let client = reqwest::blocking::Client::new();
+                for request in request_receiver {
+                    let result = send_request(
+                        &client,
+                        &url,
+                        request.method,
+                        request.params,
+                    );
+                    request.response.send(result).ok();
+                }
+            })
+            .map_err(|e| format!("failed to spawn fork rpc thread: {}", e))?;
+
+        let mut source = Self {
+            network: Network::Main,
+            epoch: String::new(),
+            request_sender: Mutex::new(request_sender),
+        };
+        let status: Value = source.call("cfx_getStatus", json!([]))?;
+        let network_id: U64 = serde_json::from_value(
+            status["networkId"].clone(),
+        )
+        .map_err(|e| format!("invalid cfx_getStatus response: {}", e))?;
+        source.network = NetworkConfiguration::network_id_to_known_cfx_network(
+            network_id.as_u64(),
+        );
+        let epoch = match epoch {
+            Some(epoch) => epoch,
+            None => source
+                .call::<U64>("cfx_epochNumber", json!(["latest_state"]))?
+                .as_u64(),
+        };
+        source.epoch = format!("{:#x}", epoch);
+        info!(
+            "Forking state from network {} at epoch {}",
+            source.network, source.epoch
+        );
+        Ok(source)
+    }
+
+    fn call<T: DeserializeOwned>(
+        &self, method: &'static str, params: Value,
+    ) -> Result<T, String> {
+        let (response_sender, response_receiver) = channel();
+        self.request_sender
+            .lock()
+            .send(RpcRequest {
+                method,
+                params,
+                response: response_sender,
+            })
+            .map_err(|_| "fork rpc thread exited".to_string())?;
+        let result = response_receiver
+            .recv()
+            .map_err(|_| "fork rpc thread exited".to_string())??;
+        serde_json::from_value(result)
+            .map_err(|e| format!("invalid {} response: {}", method, e))
+    }
+
+    fn rpc_address(&self, address: &[u8]) -> Result<RpcAddress, String> {
+        RpcAddress::try_from_h160(Address::from_slice(address), self.network)
+    }
+
+    fn get_native(&self, key: StorageKey) -> Result<Option<Vec<u8>>, String> {
+        let epoch = &self.epoch;
+        Ok(match key {
+            StorageKey::AccountKey(address) => {
+                let rpc_address = self.rpc_address(address)?;
+                let rpc_account: RpcAccount =
+                    self.call("cfx_getAccount", json!([rpc_address, epoch]))?;
+                let address = Address::from_slice(address);
+                let mut account =
+                    Account::new_empty(&address.with_native_space());
+                account.balance = rpc_account.balance;
+                account.nonce = rpc_account.nonce;
+                account.code_hash = rpc_account.code_hash;
+                account.staking_balance = rpc_account.staking_balance;
+                account.accumulated_interest_return =
+                    rpc_account.accumulated_interest_return;
+                account.admin = rpc_account.admin.hex_address;
+                // `collateralForStorage` includes the storage points used by
+                // the sponsor.
+                let mut used_storage_points = U256::zero();
+                if address.is_contract_address()
+                    || account.code_hash != KECCAK_EMPTY
+                {
+                    let sponsor_info: RpcSponsorInfo = self.call(
+                        "cfx_getSponsorInfo",
+                        json!([rpc_address, epoch]),
+                    )?;
+                    let unit = *DRIPS_PER_STORAGE_COLLATERAL_UNIT;
+                    used_storage_points =
+                        sponsor_info.used_storage_points * unit;
+                    let unused_storage_points =
+                        sponsor_info.available_storage_points * unit;
+                    account.sponsor_info = SponsorInfo {
+                        sponsor_for_gas: sponsor_info
+                            .sponsor_for_gas
+                            .hex_address,
+                        sponsor_for_collateral: sponsor_info
+                            .sponsor_for_collateral
+                            .hex_address,
+                        sponsor_gas_bound: sponsor_info.sponsor_gas_bound,
+                        sponsor_balance_for_gas: sponsor_info
+                            .sponsor_balance_for_gas,
+                        sponsor_balance_for_collateral: sponsor_info
+                            .sponsor_balance_for_collateral,
+                        storage_points: if used_storage_points.is_zero()
+                            && unused_storage_points.is_zero()
+                        {
+                            None
+                        } else {
+                            Some(StoragePoints {
+                                unused: unused_storage_points,
+                                used: used_storage_points,
+                            })
+                        },
+                    };
+                }
+                account.collateral_for_storage = rpc_account
+                    .collateral_for_storage
+                    .saturating_sub(used_storage_points);
+                if account == Account::new_empty(&address.with_native_space()) {
+                    None
+                } else {
+                    Some(rlp::encode(&account).to_vec())
+                }
+            }
+            StorageKey::CodeKey {
+                address_bytes,
+                code_hash_bytes,
+            } => {
+                let rpc_address = self.rpc_address(address_bytes)?;
+                let code: Bytes =
+                    self.call("cfx_getCode", json!([rpc_address, epoch]))?;
+                if code.0.is_empty()
+                    || keccak(&code.0).as_bytes() != code_hash_bytes
+                {
+                    None
+                } else {
+                    let admin: Option<RpcAddress> =
+                        self.call("cfx_getAdmin", json!([rpc_address, epoch]))?;
+                    let code_info = CodeInfo {
+                        code: Arc::new(code.0),
+                        owner: admin.map_or(Address::zero(), |a| a.hex_address),
+                    };
+                    Some(rlp::encode(&code_info).to_vec())
+                }
+            }
+            StorageKey::StorageKey {
+                address_bytes,
+                storage_key,
+            } if address_bytes
+                == STORAGE_INTEREST_STAKING_CONTRACT_ADDRESS.as_bytes()
+                && storage_key.len() != 32 =>
+            {
+                self.global_param(storage_key)?
+                    .map(|value| rlp::encode(&value).to_vec())
+            }
+            StorageKey::StorageKey {
+                address_bytes,
+                storage_key,
+            } if storage_key.len() == 32 => {
+                let rpc_address = self.rpc_address(address_bytes)?;
+                let value: Option<H256> = self.call(
+                    "cfx_getStorageAt",
+                    json!([rpc_address, H256::from_slice(storage_key), epoch]),
+                )?;
+                value
+                    .map(|value| U256::from_big_endian(value.as_bytes()))
+                    .filter(|value| !value.is_zero())
+                    .map(|value| {
+                        rlp::encode(&StorageValue { value, owner: None })
+                            .to_vec()
+                    })
+            }
+            StorageKey::DepositListKey(address) => {
+                let deposit_list: Vec<DepositInfo> = self.call(
+                    "cfx_getDepositList",
+                    json!([self.rpc_address(address)?, epoch]),
+                )?;
+                Some(deposit_list)
+                    .filter(|list| !list.is_empty())
+                    .map(|list| rlp::encode(&DepositList(list)).to_vec())
+            }
+            StorageKey::VoteListKey(address) => {
+                let vote_list: Vec<VoteStakeInfo> = self.call(
+                    "cfx_getVoteList",
+                    json!([self.rpc_address(address)?, epoch]),
+                )?;
+                Some(vote_list)
+                    .filter(|list| !list.is_empty())
+                    .map(|list| rlp::encode(&VoteStakeList(list)).to_vec())
+            }
+            StorageKey::StorageRootKey(_) => {
+                Some(STORAGE_LAYOUT_REGULAR_V0.to_bytes())
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns the value of a global parameter as stored in the storage layer,
+    /// or `None` if `key` is not a global parameter.
+    fn global_param(&self, key: &[u8]) -> Result<Option<U256>, String> {
+        let epoch = &self.epoch;
+        let field = |method: &'static str, field: &str| {
+            let value: Value = self.call(method, json!([epoch]))?;
+            serde_json::from_value::<U256>(value[field].clone())
+                .map_err(|e| format!("invalid {} response: {}", method, e))
+        };
+        let value = if key == InterestRate::KEY {
+            self.call("cfx_getInterestRate", json!([epoch]))?
+        } else if key == AccumulateInterestRate::KEY {
+            self.call("cfx_getAccumulateInterestRate", json!([epoch]))?
+        } else if key == TotalIssued::KEY {
+            field("cfx_getSupplyInfo", "totalIssued")?
+        } else if key == TotalStaking::KEY {
+            field("cfx_getSupplyInfo", "totalStaking")?
+        } else if key == TotalStorage::KEY {
+            field("cfx_getSupplyInfo", "totalCollateral")?
+        } else if key == TotalEvmToken::KEY {
+            field("cfx_getSupplyInfo", "totalEspaceTokens")?
+        } else if key == UsedStoragePoints::KEY {
+            field("cfx_getCollateralInfo", "usedStoragePoints")?
+                * *DRIPS_PER_STORAGE_COLLATERAL_UNIT
+        } else if key == ConvertedStoragePoints::KEY {
+            field("cfx_getCollateralInfo", "convertedStoragePoints")?
+                * *DRIPS_PER_STORAGE_COLLATERAL_UNIT
+        } else if key == TotalPosStaking::KEY {
+            field("cfx_getPoSEconomics", "totalPosStakingTokens")?
+        } else if key == DistributablePoSInterest::KEY {
+            field("cfx_getPoSEconomics", "distributablePosInterest")?
+        } else if key == LastDistributeBlock::KEY {
+            field("cfx_getPoSEconomics", "lastDistributeBlock")?
+        } else if key == PowBaseReward::KEY {
+            field("cfx_getParamsFromVote", "powBaseReward")?
+        } else if key == BaseFeeProp::KEY {
+            field("cfx_getParamsFromVote", "baseFeeShareProp")?
+        } else if key == TotalBurnt1559::KEY {
+            self.call("cfx_getFeeBurnt", json!([epoch]))?
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(value))
+    }
+
+    /// Reads the storage entries of a contract whose keys start with
+    /// `key_prefix`, page by page.
+    fn storage_entries(
+        &self, address: &[u8], space: Space, key_prefix: &[u8],
+    ) -> Result<Vec<(Vec<u8>, StorageValue)>, String> {
+        let epoch = &self.epoch;
+        let mut entries = vec![];
+        let mut start = key_prefix.to_vec();
+        loop {
+            let (page, next_key) = match space {
+                Space::Native => {
+                    let range: RpcStorageRange<Bytes> = self.call(
+                        "debug_storageRangeAt",
+                        json!([
+                            epoch,
+                            self.rpc_address(address)?,
+                            Bytes(start),
+                            STORAGE_RANGE_PAGE_SIZE
+                        ]),
+                    )?;
+                    let page: Vec<_> = range
+                        .storage
+                        .into_iter()
+                        .map(|entry| {
+                            let owner = entry.owner.map(|a| a.hex_address);
+                            (entry.key.0, entry.value, owner)
+                        })
+                        .collect();
+                    (page, range.next_key.map(|key| key.0))
+                }
+                Space::Ethereum => {
+                    let mut key_start = H256::zero();
+                    key_start.as_bytes_mut()[..start.len()]
+                        .copy_from_slice(&start);
+                    let range: RpcStorageRange<H256> = self.call(
+                        "debug_storageRangeAt",
+                        json!([
+                            epoch,
+                            Address::from_slice(address),
+                            key_start,
+                            STORAGE_RANGE_PAGE_SIZE
+                        ]),
+                    )?;
+                    let page: Vec<_> = range
+                        .storage
+                        .into_iter()
+                        .map(|entry| (entry.key.0.to_vec(), entry.value, None))
+                        .collect();
+                    (page, range.next_key.map(|key| key.0.to_vec()))
+                }
+            };
+            for (key, value, owner) in page {
+                // The keys are returned in order, so the entries with the
+                // prefix end at the first key without it.
+                if !key.starts_with(key_prefix) {
+                    return Ok(entries);
+                }
+                entries.push((key, StorageValue { value, owner }));
+            }
+            match next_key {
+                Some(next_key) if next_key.starts_with(key_prefix) => {
+                    start = next_key
+                }
+                _ => return Ok(entries),
+            }
+        }
+    }
+
+    fn get_raw(
+        &self, access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Vec<u8>>, String> {
+        match access_key.space {
+            Space::Native => self.get_native(access_key.key),
+            Space::Ethereum => self.get_evm(access_key.key),
+        }
+    }
+
+    fn get_all_raw(
+        &self, prefix: StorageKeyWithSpace,
+    ) -> Result<Vec<MptKeyValue>, String> {
+        let space = prefix.space;
+        let mut kvs = vec![];
+        let (address, key_prefix) = match prefix.key {
+            StorageKey::StorageRootKey(address) => {
+                kvs.push((
+                    prefix.to_key_bytes(),
+                    STORAGE_LAYOUT_REGULAR_V0.to_bytes().into(),
+                ));
+                (address, &[][..])
+            }
+            StorageKey::StorageKey {
+                address_bytes,
+                storage_key,
+            } => (address_bytes, storage_key),
+            StorageKey::CodeRootKey(address) => {
+                let account_key =
+                    StorageKey::AccountKey(address).with_space(space);
+                let account = match self.get_raw(account_key)? {
+                    Some(raw) => Account::new_from_rlp(
+                        Address::from_slice(address),
+                        &Rlp::new(&raw),
+                    )
+                    .map_err(|e| format!("invalid account: {:?}", e))?,
+                    None => return Ok(kvs),
+                };
+                if account.code_hash != KECCAK_EMPTY {
+                    let code_key = StorageKey::CodeKey {
+                        address_bytes: address,
+                        code_hash_bytes: account.code_hash.as_bytes(),
+                    }
+                    .with_space(space);
+                    if let Some(code) = self.get_raw(code_key)? {
+                        kvs.push((code_key.to_key_bytes(), code.into()));
+                    }
+                }
+                return Ok(kvs);
+            }
+            _ => {
+                return Err(format!(
+                    "can not enumerate {:?} on the forked chain",
+                    prefix
+                ))
+            }
+        };
+        for (key, value) in self.storage_entries(address, space, key_prefix)? {
+            kvs.push((
+                StorageKey::new_storage_key(
+                    &Address::from_slice(address),
+                    &key,
+                )
+                .with_space(space)
+                .to_key_bytes(),
+                rlp::encode(&value).to_vec().into(),
+            ));
+        }
+        Ok(kvs)
+    }
+
+    fn get_evm(&self, key: StorageKey) -> Result<Option<Vec<u8>>, String> {
+        let epoch = &self.epoch;
+        Ok(match key {
+            StorageKey::AccountKey(address) => {
+                let address = Address::from_slice(address);
+                let balance: U256 =
+                    self.call("eth_getBalance", json!([address, epoch]))?;
+                let nonce: U256 = self
+                    .call("eth_getTransactionCount", json!([address, epoch]))?;
+                let code: Bytes =
+                    self.call("eth_getCode", json!([address, epoch]))?;
+                let code_hash = if code.0.is_empty() {
+                    KECCAK_EMPTY
+                } else {
+                    keccak(&code.0)
+                };
+                let mut account = Account::new_empty_with_balance(
+                    &address.with_evm_space(),
+                    &balance,
+                    &nonce,
+                );
+                account.code_hash = code_hash;
+                if balance.is_zero() && nonce.is_zero() && code.0.is_empty() {
+                    None
+                } else {
+                    Some(rlp::encode(&account).to_vec())
+                }
+            }
+            StorageKey::CodeKey {
+                address_bytes,
+                code_hash_bytes,
+            } => {
+                let code: Bytes = self.call(
+                    "eth_getCode",
+                    json!([Address::from_slice(address_bytes), epoch]),
+                )?;
+                if code.0.is_empty()
+                    || keccak(&code.0).as_bytes() != code_hash_bytes
+                {
+                    None
+                } else {
+                    let code_info = CodeInfo {
+                        code: Arc::new(code.0),
+                        owner: Address::zero(),
+                    };
+                    Some(rlp::encode(&code_info).to_vec())
+                }
+            }
+            StorageKey::StorageKey {
+                address_bytes,
+                storage_key,
+            } if storage_key.len() == 32 => {
+                let value: H256 = self.call(
+                    "eth_getStorageAt",
+                    json!([
+                        Address::from_slice(address_bytes),
+                        U256::from_big_endian(storage_key),
+                        epoch
+                    ]),
+                )?;
+                Some(U256::from_big_endian(value.as_bytes()))
+                    .filter(|value| !value.is_zero())
+                    .map(|value| {
+                        rlp::encode(&StorageValue { value, owner: None })
+                            .to_vec()
+                    })
+            }
+            StorageKey::StorageRootKey(_) => {
+                Some(STORAGE_LAYOUT_REGULAR_V0.to_bytes())
+            }
+            _ => None,
+        })
+    }
+}
+
+impl ForkStateSource for RpcForkStateSource {
+    fn get(
+        &self, access_key: StorageKeyWithSpace,
+    ) -> StorageResult<Option<Box<[u8]>>> {
+        let value = self.get_raw(access_key).map_err(|e| {
+            format!("failed to fetch {:?} from forked chain: {}", access_key, e)
+        })?;
+        Ok(value.map(Vec::into_boxed_slice))
+    }
+
+    fn get_all(
+        &self, access_key_prefix: StorageKeyWithSpace,
+    ) -> StorageResult<Vec<MptKeyValue>> {
+        Ok(self.get_all_raw(access_key_prefix).map_err(|e| {
+            format!(
+                "failed to fetch {:?} from forked chain: {}",
+                access_key_prefix, e
+            )
+        })?)
+    }
+}
+
+fn send_request(
+    client: &reqwest::blocking::Client, url: &str, method: &str, params: Value,
+) -> Result<Value, String> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+    let mut response: Value = client
+        .post(url)
+        .json(&request)
+        .send()
+        .and_then(|response| response.json())
+        .map_err(|e| format!("{} request failed: {}", method, e))?;
+    if !response["error"].is_null() {
+        return Err(format!("{} failed: {}", method, response["error"]));
+    }
+    Ok(response["result"].take())
+}
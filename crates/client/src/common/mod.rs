@@ -67,12 +67,15 @@ use crate::{
             eth_pubsub::PubSubClient as EthPubSubClient, pubsub::PubSubClient,
         },
         setup_debug_rpc_apis, setup_public_eth_rpc_apis, setup_public_rpc_apis,
+        GraphQLServer,
     },
     GENESIS_VERSION,
 };
 use cfxcore::consensus::pos_handler::read_initial_nodes_from_file;
+use fork_source::RpcForkStateSource;
 
 pub mod delegate_convert;
+pub mod fork_source;
 pub mod shutdown_handler;
 
 /// Hold all top-level components for a type of client.
@@ -224,10 +227,21 @@ pub fn initialize_common_modules(
         .map_err(|e| format!("Failed to open database {:?}", e))?;
 
     let secret_store = Arc::new(SecretStore::new());
-    let storage_manager = Arc::new(
+    let mut storage_manager =
         StorageManager::new(conf.storage_config(&node_type))
-            .expect("Failed to initialize storage."),
-    );
+            .expect("Failed to initialize storage.");
+    if let Some(fork_url) = &conf.raw_conf.dev_fork_url {
+        if !conf.is_dev_mode() {
+            return Err("dev_fork_url is only allowed in dev mode".into());
+        }
+        storage_manager = storage_manager.with_fork_source(Box::new(
+            RpcForkStateSource::new(
+                fork_url.clone(),
+                conf.raw_conf.dev_fork_epoch,
+            )?,
+        ));
+    }
+    let storage_manager = Arc::new(storage_manager);
     {
         let storage_manager_log_weak_ptr = Arc::downgrade(&storage_manager);
         let exit_clone = exit.clone();
@@ -312,12 +326,13 @@ pub fn initialize_common_modules(
     let data_man = Arc::new(BlockDataManager::new(
         cache_config,
         Arc::new(genesis_block),
-        ledger_db.clone(),
+        Some(ledger_db.clone()),
         storage_manager,
         worker_thread_pool,
         conf.data_mananger_config(),
         pow.clone(),
     ));
+    data_man.start_recompression()?;
 
     let network = {
         let mut rng = StdRng::from_rng(OsRng).unwrap();
@@ -381,6 +396,9 @@ pub fn initialize_common_modules(
         pivot_hint,
         conf.common_params(),
     ));
+    consensus.start_snapshot_verifier().map_err(|e| {
+        format!("Failed to start the snapshot verifier {:?}", e)
+    })?;
 
     for terminal in data_man
         .terminals_from_db()
@@ -482,6 +500,7 @@ pub fn initialize_not_light_node_modules(
         Runtime,
         Option<HttpServer>,
         Option<WSServer>,
+        Option<GraphQLServer>,
     ),
     String,
 > {
@@ -525,6 +544,8 @@ pub fn initialize_not_light_node_modules(
         consensus.clone(),
     ));
     sync.register().unwrap();
+    pubsub.set_sync_service(sync.clone());
+    eth_pubsub.set_sync_service(sync.clone());
 
     if let Some(print_memory_usage_period_s) =
         conf.raw_conf.print_memory_usage_period_s
@@ -726,6 +747,12 @@ pub fn initialize_not_light_node_modules(
         ),
     )?;
 
+    let graphql_server = super::rpc::start_graphql(
+        conf.graphql_config(),
+        consensus.clone(),
+        *network.get_network_type(),
+    )?;
+
     network.start();
 
     Ok((
@@ -745,6 +772,7 @@ pub fn initialize_not_light_node_modules(
         runtime,
         eth_rpc_http_server,
         eth_rpc_ws_server,
+        graphql_server,
     ))
 }
 
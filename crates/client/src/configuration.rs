@@ -18,13 +18,17 @@ use cfx_parameters::{
 };
 use cfx_storage::{
     defaults::DEFAULT_DEBUG_SNAPSHOT_CHECKER_THREADS, storage_dir,
-    ConsensusParam, ProvideExtraSnapshotSyncConfig, StorageConfiguration,
+    ConsensusParam, ProvideExtraSnapshotSyncConfig, PruningMode,
+    SnapshotDbBackend, StorageConfiguration,
 };
 use cfx_types::{
     parse_hex_string, Address, AllChainID, Space, SpaceMap, H256, U256,
 };
 use cfxcore::{
-    block_data_manager::{DataManagerConfiguration, DbType},
+    block_data_manager::{
+        compression::{BlockDbCodec, BlockDbCompressionConfig},
+        DataManagerConfiguration, DbType,
+    },
     block_parameters::*,
     cache_config::{
         DEFAULT_INVALID_BLOCK_HASH_CACHE_SIZE_IN_COUNT,
@@ -44,6 +48,7 @@ use cfxcore::{
     transaction_pool::TxPoolConfig,
     NodeType,
 };
+use diem_config::config::NodeConfig;
 use diem_types::term_state::{
     pos_state_config::PosStateConfig, IN_QUEUE_LOCKED_VIEWS,
     OUT_QUEUE_LOCKED_VIEWS, ROUND_PER_TERM, TERM_ELECTED_SIZE, TERM_MAX_SIZE,
@@ -54,8 +59,8 @@ use primitives::block_header::CIP112_TRANSITION_HEIGHT;
 use txgen::TransactionGeneratorConfig;
 
 use crate::rpc::{
-    impls::RpcImplConfiguration, rpc_apis::ApiSet, HttpConfiguration,
-    TcpConfiguration, WsConfiguration,
+    impls::RpcImplConfiguration, rpc_apis::ApiSet, GraphQLConfiguration,
+    HttpConfiguration, TcpConfiguration, WsConfiguration,
 };
 
 lazy_static! {
@@ -116,6 +121,10 @@ build_config! {
         // Only effective in `dev` mode
         (dev_block_interval_ms, (Option<u64>), None)
         (dev_pack_tx_immediately, (Option<bool>), None)
+        // Fork the state of the node behind this RPC url at `dev_fork_epoch`,
+        // or at its latest state if unset. Only effective in `dev` mode.
+        (dev_fork_url, (Option<String>), None)
+        (dev_fork_epoch, (Option<u64>), None)
         (enable_state_expose, (bool), false)
         (generate_tx, (bool), false)
         (generate_tx_period_us, (Option<u64>), Some(100_000))
@@ -171,6 +180,9 @@ build_config! {
         (next_hardfork_transition_height, (Option<u64>), None)
         (cip1559_transition_height, (Option<u64>), None)
         (cancun_opcodes_transition_number, (Option<u64>), None)
+        (cip146_transition_number, (Option<u64>), None)
+        (cip147_transition_number, (Option<u64>), None)
+        (cip148_transition_number, (Option<u64>), None)
         (referee_bound, (usize), REFEREE_DEFAULT_BOUND)
         (params_dao_vote_period, (u64), DAO_PARAMETER_VOTE_PERIOD)
         (timer_chain_beta, (u64), TIMER_CHAIN_DEFAULT_BETA)
@@ -202,6 +214,9 @@ build_config! {
         (jsonrpc_ws_max_payload_bytes, (usize), 30 * 1024 * 1024)
         (jsonrpc_http_eth_port, (Option<u16>), None)
         (jsonrpc_ws_eth_port, (Option<u16>), None)
+        (graphql_port, (Option<u16>), None)
+        (graphql_max_complexity, (usize), 10_000)
+        (graphql_max_depth, (usize), 16)
         // The network_id, if unset, defaults to the chain_id.
         // Only override the network_id for local experiments,
         // when user would like to keep the existing blockchain data
@@ -245,6 +260,7 @@ build_config! {
         (received_tx_index_maintain_timeout_ms, (u64), 300_000)
         (request_block_with_public, (bool), false)
         (send_tx_period_ms, (u64), 1300)
+        (snapshot_archive_path, (Option<String>), None)
         (snapshot_candidate_request_timeout_ms, (u64), 10_000)
         (snapshot_chunk_request_timeout_ms, (u64), 30_000)
         (snapshot_manifest_request_timeout_ms, (u64), 30_000)
@@ -296,7 +312,11 @@ build_config! {
         (additional_maintained_trace_epoch_count, (Option<usize>), None)
         (additional_maintained_transaction_index_epoch_count, (Option<usize>), None)
         (block_cache_gc_period_ms, (u64), 5_000)
+        (block_db_compression_dictionary_size, (Option<usize>), None)
         (block_db_dir, (Option<String>), None)
+        (block_db_recompression_epochs_per_round, (u64), 100)
+        (block_db_recompression_interval_ms, (u64), 10_000)
+        (block_db_recompression_round_budget_ms, (u64), 1_000)
         (block_db_type, (String), "rocksdb".to_string())
         (checkpoint_gc_time_in_era_count, (f64), 0.5)
         // The conflux data dir, if unspecified, is the workdir where conflux is started.
@@ -315,7 +335,10 @@ build_config! {
         (storage_single_mpt_cache_start_size, (u32), cfx_storage::defaults::DEFAULT_DELTA_MPTS_CACHE_START_SIZE * 2)
         (storage_single_mpt_slab_idle_size, (u32), cfx_storage::defaults::DEFAULT_DELTA_MPTS_SLAB_IDLE_SIZE * 2)
         (storage_max_open_snapshots, (u16), cfx_storage::defaults::DEFAULT_MAX_OPEN_SNAPSHOTS)
+        (storage_flat_state_cache_size, (usize), 0)
         (storage_max_open_mpt_count, (u32), cfx_storage::defaults::DEFAULT_MAX_OPEN_MPT)
+        (storage_snapshot_self_heal, (bool), false)
+        (storage_snapshot_verify_interval_secs, (Option<u64>), None)
         (strict_tx_index_gc, (bool), true)
         (sync_state_starting_epoch, (Option<u64>), None)
         (sync_state_epoch_gap, (Option<u64>), None)
@@ -336,6 +359,9 @@ build_config! {
         (print_memory_usage_period_s, (Option<u64>), None)
         (target_block_gas_limit, (u64), DEFAULT_TARGET_BLOCK_GAS_LIMIT)
         (executive_trace, (bool), false)
+        (execution_witness, (bool), false)
+        (parallel_execution, (bool), false)
+        (parallel_execution_threads, (usize), 8)
         (check_status_genesis, (bool), true)
         (packing_gas_limit_block_count, (u64), 10)
         (poll_lifetime_in_seconds, (Option<u32>), None)
@@ -418,7 +444,16 @@ build_config! {
             (Vec<ProvideExtraSnapshotSyncConfig>),
             vec![ProvideExtraSnapshotSyncConfig::StableCheckpoint],
             ProvideExtraSnapshotSyncConfig::parse_config_list)
+        // `none`, `lz4`, `zstd` or `zstd:<level>`.
+        (block_db_compression, (BlockDbCodec), BlockDbCodec::None, BlockDbCodec::from_str)
+        (block_db_trace_compression, (BlockDbCodec), BlockDbCodec::None, BlockDbCodec::from_str)
         (node_type, (Option<NodeType>), None, NodeType::from_str)
+        // `archive`, `full` or `pruned:N`. If not set, it's derived from the
+        // node type. The explicitly set `additional_maintained*` take priority.
+        (pruning_mode, (Option<PruningMode>), None, PruningMode::from_str)
+        // `sqlite` or `rocksdb`. It can not be changed for an existing data
+        // dir.
+        (snapshot_db_backend, (SnapshotDbBackend), SnapshotDbBackend::Sqlite, SnapshotDbBackend::from_str)
         (public_rpc_apis, (ApiSet), ApiSet::Safe, ApiSet::from_str)
         (public_evm_rpc_apis, (ApiSet), ApiSet::Evm, ApiSet::from_str)
         (single_mpt_space, (Option<Space>), None, |s| match s {
@@ -563,6 +598,20 @@ impl Configuration {
         cache_config
     }
 
+    /// The dir of the PoS ledger db and the PoS consensus db, which is set by
+    /// the PoS config file.
+    pub fn pos_db_dir(&self) -> Result<PathBuf, String> {
+        let pos_config_path = self
+            .raw_conf
+            .pos_config_path
+            .as_ref()
+            .ok_or("No pos config!")?;
+        let mut pos_config = NodeConfig::load(pos_config_path)
+            .map_err(|e| format!("Failed to load node config: e={:?}", e))?;
+        pos_config.set_data_dir(pos_config.data_dir().to_path_buf());
+        Ok(pos_config.storage.dir())
+    }
+
     pub fn db_config(&self) -> (PathBuf, DatabaseConfig) {
         let db_dir: PathBuf = match &self.raw_conf.block_db_dir {
             Some(dir) => dir.into(),
@@ -827,6 +876,8 @@ impl Configuration {
             keep_snapshot_before_stable_checkpoint: self
                 .raw_conf
                 .keep_snapshot_before_stable_checkpoint,
+            pruning_mode: self.pruning_mode(),
+            snapshot_db_backend: self.raw_conf.snapshot_db_backend,
             use_isolated_db_for_mpt_table: self
                 .raw_conf
                 .use_isolated_db_for_mpt_table,
@@ -834,6 +885,12 @@ impl Configuration {
                 .raw_conf
                 .use_isolated_db_for_mpt_table_height,
             keep_era_genesis_snapshot: self.raw_conf.keep_era_genesis_snapshot,
+            snapshot_verify_interval: self
+                .raw_conf
+                .storage_snapshot_verify_interval_secs
+                .map(Duration::from_secs),
+            snapshot_self_heal: self.raw_conf.storage_snapshot_self_heal,
+            flat_state_cache_size: self.raw_conf.storage_flat_state_cache_size,
         }
     }
 
@@ -954,9 +1011,21 @@ impl Configuration {
             max_downloading_manifest_attempts: self
                 .raw_conf
                 .max_downloading_manifest_attempts,
+            snapshot_archive: self
+                .raw_conf
+                .snapshot_archive_path
+                .as_ref()
+                .map(PathBuf::from),
+            snapshot_archive_dir: self.snapshot_archive_dir(),
         }
     }
 
+    /// The directory of the snapshot archives exported by
+    /// `debug_exportSnapshotArchive` or imported by `state import-archive`.
+    pub fn snapshot_archive_dir(&self) -> PathBuf {
+        Path::new(&self.raw_conf.conflux_data_dir).join("snapshot_archive")
+    }
+
     pub fn data_mananger_config(&self) -> DataManagerConfiguration {
         let mut conf = DataManagerConfiguration {
             persist_tx_index: self.raw_conf.persist_tx_index,
@@ -992,33 +1061,37 @@ impl Configuration {
                 * self.raw_conf.era_epoch_count as f64)
                 as usize,
             strict_tx_index_gc: self.raw_conf.strict_tx_index_gc,
+            pruning_mode: self.pruning_mode(),
+            db_compression: self.block_db_compression_config(),
         };
 
         // By default, we do not keep the block data for additional period,
-        // but `node_type = "archive"` is a shortcut for keeping all them.
-        if !matches!(self.raw_conf.node_type, Some(NodeType::Archive)) {
+        // but `pruning_mode = "archive"` (the default of `node_type =
+        // "archive"`) is a shortcut for keeping all them, and
+        // `pruning_mode = "pruned:N"` keeps them for N epochs.
+        if let Some(defer) = conf.pruning_mode.block_data_defer_epochs() {
             if conf.additional_maintained_block_body_epoch_count.is_none() {
-                conf.additional_maintained_block_body_epoch_count = Some(0);
+                conf.additional_maintained_block_body_epoch_count = Some(defer);
             }
             if conf
                 .additional_maintained_execution_result_epoch_count
                 .is_none()
             {
                 conf.additional_maintained_execution_result_epoch_count =
-                    Some(0);
+                    Some(defer);
             }
             if conf
                 .additional_maintained_transaction_index_epoch_count
                 .is_none()
             {
                 conf.additional_maintained_transaction_index_epoch_count =
-                    Some(0);
+                    Some(defer);
             }
             if conf.additional_maintained_reward_epoch_count.is_none() {
-                conf.additional_maintained_reward_epoch_count = Some(0);
+                conf.additional_maintained_reward_epoch_count = Some(defer);
             }
             if conf.additional_maintained_trace_epoch_count.is_none() {
-                conf.additional_maintained_trace_epoch_count = Some(0);
+                conf.additional_maintained_trace_epoch_count = Some(defer);
             }
         }
         if conf.additional_maintained_transaction_index_epoch_count != Some(0) {
@@ -1100,6 +1173,7 @@ impl Configuration {
                     self.is_dev_mode()
                         && self.raw_conf.dev_block_interval_ms.is_none()
                 }),
+            dev_mode: self.is_dev_mode(),
             max_payload_bytes: self.raw_conf.jsonrpc_ws_max_payload_bytes,
             enable_metrics: self.raw_conf.rpc_enable_metrics,
             poll_lifetime_in_seconds: self.raw_conf.poll_lifetime_in_seconds,
@@ -1148,6 +1222,15 @@ impl Configuration {
         )
     }
 
+    pub fn graphql_config(&self) -> GraphQLConfiguration {
+        GraphQLConfiguration::new(
+            None,
+            self.raw_conf.graphql_port,
+            self.raw_conf.graphql_max_complexity,
+            self.raw_conf.graphql_max_depth,
+        )
+    }
+
     pub fn local_tcp_config(&self) -> TcpConfiguration {
         TcpConfiguration::new(
             Some((127, 0, 0, 1)),
@@ -1178,6 +1261,12 @@ impl Configuration {
     pub fn execution_config(&self) -> ConsensusExecutionConfiguration {
         ConsensusExecutionConfiguration {
             executive_trace: self.raw_conf.executive_trace,
+            execution_witness: self.raw_conf.execution_witness,
+            parallel_execution_threads: if self.raw_conf.parallel_execution {
+                Some(self.raw_conf.parallel_execution_threads)
+            } else {
+                None
+            },
         }
     }
 
@@ -1300,6 +1389,35 @@ impl Configuration {
         self.raw_conf.node_type.unwrap_or(NodeType::Full)
     }
 
+    pub fn pruning_mode(&self) -> PruningMode {
+        match self.raw_conf.pruning_mode {
+            Some(pruning_mode) => pruning_mode,
+            None => match self.raw_conf.node_type {
+                Some(NodeType::Archive) => PruningMode::Archive,
+                _ => PruningMode::default(),
+            },
+        }
+    }
+
+    pub fn block_db_compression_config(&self) -> BlockDbCompressionConfig {
+        BlockDbCompressionConfig {
+            blocks: self.raw_conf.block_db_compression,
+            block_traces: self.raw_conf.block_db_trace_compression,
+            zstd_dictionary_size: self
+                .raw_conf
+                .block_db_compression_dictionary_size,
+            recompression_interval: Duration::from_millis(
+                self.raw_conf.block_db_recompression_interval_ms,
+            ),
+            recompression_epochs_per_round: self
+                .raw_conf
+                .block_db_recompression_epochs_per_round,
+            recompression_round_time_budget: Duration::from_millis(
+                self.raw_conf.block_db_recompression_round_budget_ms,
+            ),
+        }
+    }
+
     pub fn pos_state_config(&self) -> PosStateConfig {
         // The current implementation requires the round number to be an even
         // number.
@@ -1468,6 +1586,31 @@ impl Configuration {
             .or(self.raw_conf.next_hardfork_transition_number)
             .unwrap_or(default_transition_time);
 
+        //
+        // Cross-space call from eSpace (CIP-146)
+        //
+        params.transition_numbers.cip146 = self
+            .raw_conf
+            .cip146_transition_number
+            .unwrap_or(default_transition_time);
+
+        //
+        // Gas sponsorship for eSpace contracts (CIP-147)
+        //
+        params.transition_numbers.cip147 = self
+            .raw_conf
+            .cip147_transition_number
+            .unwrap_or(default_transition_time);
+
+        //
+        // BLOBHASH and BLOBBASEFEE opcodes, and TLOAD reading the transient
+        // storage (CIP-148)
+        //
+        params.transition_numbers.cip148 = self
+            .raw_conf
+            .cip148_transition_number
+            .unwrap_or(default_transition_time);
+
         if params.transition_heights.cip1559
             < self.raw_conf.pos_reference_enable_height
         {
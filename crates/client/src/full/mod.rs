@@ -9,6 +9,7 @@ use jsonrpc_ws_server::Server as WsServer;
 use crate::{
     common::{initialize_not_light_node_modules, ClientComponents},
     configuration::Configuration,
+    rpc::GraphQLServer,
 };
 use blockgen::BlockGenerator;
 use cfxcore::{
@@ -34,6 +35,7 @@ pub struct FullClientExtraComponents {
     pub pow: Arc<PowComputer>,
     pub eth_rpc_http_server: Option<HttpServer>,
     pub eth_rpc_ws_server: Option<WsServer>,
+    pub graphql_server: Option<GraphQLServer>,
 }
 
 impl MallocSizeOf for FullClientExtraComponents {
@@ -67,6 +69,7 @@ impl FullClient {
             runtime,
             eth_rpc_http_server,
             eth_rpc_ws_server,
+            graphql_server,
         ) = initialize_not_light_node_modules(&mut conf, exit, NodeType::Full)?;
         Ok(Box::new(ClientComponents {
             data_manager_weak_ptr: Arc::downgrade(&data_man),
@@ -86,6 +89,7 @@ impl FullClient {
                 pow,
                 eth_rpc_http_server,
                 eth_rpc_ws_server,
+                graphql_server,
             },
         }))
     }
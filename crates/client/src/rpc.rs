@@ -2,6 +2,7 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
+use cfx_rpc::TraceApi;
 use jsonrpc_core::{MetaIoHandler, RemoteProcedure, Value};
 use jsonrpc_http_server::{
     Server as HttpServer, ServerBuilder as HttpServerBuilder,
@@ -19,6 +20,7 @@ use std::sync::Arc;
 mod authcodes;
 pub mod errors;
 pub mod extractor;
+mod graphql;
 mod helpers;
 mod http_common;
 pub mod impls;
@@ -34,10 +36,14 @@ pub use cfxcore::errors::{
     BoxFuture as CoreBoxFuture, Error as CoreError, Result as CoreResult,
 };
 pub use errors::{error_codes, invalid_params};
+pub use graphql::{start_graphql, GraphQLServer};
 
 use self::{
     impls::{
-        cfx::{CfxHandler, LocalRpcImpl, RpcImpl, TestRpcImpl, TraceHandler},
+        cfx::{
+            CfxHandler, DevRpcImpl, LocalRpcImpl, RpcImpl, TestRpcImpl,
+            TraceHandler,
+        },
         cfx_filter::CfxFilterClient,
         common::RpcImpl as CommonImpl,
         eth_pubsub::PubSubClient as EthPubSubClient,
@@ -53,9 +59,10 @@ use self::{
         cfx::Cfx,
         cfx_filter::CfxFilter,
         debug::LocalRpc,
+        dev::DevRpc,
         eth_space::{
-            eth::Eth, eth_filter::EthFilter, eth_pubsub::EthPubSub,
-            trace::Trace as EthTrace,
+            dev::EthDev, eth::Eth, eth_filter::EthFilter,
+            eth_pubsub::EthPubSub, trace::Trace as EthTrace,
         },
         pool::TransactionPool,
         pos::Pos,
@@ -70,7 +77,9 @@ use crate::{
     configuration::Configuration,
     rpc::{
         impls::{
-            eth::{EthHandler, EthTraceHandler, GethDebugHandler},
+            eth::{
+                EthDevHandler, EthHandler, EthTraceHandler, GethDebugHandler,
+            },
             eth_filter::EthFilterClient,
             RpcImplConfiguration,
         },
@@ -82,7 +91,7 @@ use crate::{
 use interceptor::{MetricsInterceptor, ThrottleInterceptor};
 pub use metadata::Metadata;
 pub use server_configuration::{
-    HttpConfiguration, TcpConfiguration, WsConfiguration,
+    GraphQLConfiguration, HttpConfiguration, TcpConfiguration, WsConfiguration,
 };
 use std::collections::HashSet;
 
@@ -120,6 +129,10 @@ pub fn setup_debug_rpc_apis(
     common: Arc<CommonImpl>, rpc: Arc<RpcImpl>, pubsub: PubSubClient,
     eth_pubsub: EthPubSubClient, conf: &Configuration,
 ) -> MetaIoHandler<Metadata> {
+    let mut debug_apis = ApiSet::All.list_apis();
+    if conf.is_dev_mode() {
+        debug_apis.insert(Api::Dev);
+    }
     setup_rpc_apis(
         common,
         rpc,
@@ -127,7 +140,7 @@ pub fn setup_debug_rpc_apis(
         eth_pubsub,
         &conf.raw_conf.throttling_conf,
         "rpc_local",
-        ApiSet::All.list_apis(),
+        debug_apis,
     )
 }
 
@@ -189,6 +202,10 @@ fn setup_rpc_apis(
                         *rpc.sync.network.get_network_type(),
                         rpc.consensus.clone(),
                     ),
+                    replay_handler: TraceApi::new(
+                        rpc.consensus.clone(),
+                        rpc.config.max_estimation_gas_limit,
+                    ),
                 }
                 .to_delegate();
                 extend_with_interceptor(
@@ -299,6 +316,14 @@ fn setup_rpc_apis(
                     PoSInterceptor::new(common.pos_handler.clone());
                 handler.extend_with(RpcProxy::new(pos, pos_interceptor));
             }
+            Api::Dev => {
+                handler.extend_with(DevRpcImpl::new(rpc.clone()).to_delegate());
+            }
+            Api::EthDev => {
+                info!("Add EVM dev methods");
+                handler
+                    .extend_with(EthDevHandler::new(rpc.clone()).to_delegate());
+            }
         }
     }
 
@@ -433,6 +458,9 @@ fn setup_rpc_apis_light(
             Api::Pos => {
                 warn!("Light nodes do not support PoS RPC");
             }
+            Api::Dev | Api::EthDev => {
+                warn!("Light nodes do not support dev RPC");
+            }
         }
     }
     handler
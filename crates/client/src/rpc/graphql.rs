@@ -0,0 +1,159 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! An EIP-1767 style GraphQL endpoint served over HTTP at `/graphql`.
+
+mod schema;
+
+use self::schema::{GraphQLContext, Query};
+use super::server_configuration::GraphQLConfiguration;
+use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+use cfx_addr::Network;
+use cfxcore::ConsensusGraph;
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use std::{convert::Infallible, sync::Arc, time::Duration};
+use tokio::{runtime::Runtime, sync::oneshot};
+
+pub type GraphQLSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+const GRAPHQL_PATH: &str = "/graphql";
+const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+
+/// A running GraphQL server, shut down when dropped.
+pub struct GraphQLServer {
+    runtime: Option<Runtime>,
+    close: Option<oneshot::Sender<()>>,
+}
+
+impl Drop for GraphQLServer {
+    fn drop(&mut self) {
+        if let Some(close) = self.close.take() {
+            let _ = close.send(());
+        }
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_timeout(Duration::from_secs(1));
+        }
+    }
+}
+
+pub fn build_schema(
+    conf: &GraphQLConfiguration, consensus: Arc<ConsensusGraph>,
+    network: Network,
+) -> GraphQLSchema {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .data(GraphQLContext::new(consensus, network))
+        .limit_complexity(conf.max_complexity)
+        .limit_depth(conf.max_depth)
+        .finish()
+}
+
+pub fn start_graphql(
+    conf: GraphQLConfiguration, consensus: Arc<ConsensusGraph>,
+    network: Network,
+) -> Result<Option<GraphQLServer>, String> {
+    if !conf.enabled {
+        return Ok(None);
+    }
+
+    let schema = build_schema(&conf, consensus, network);
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .thread_name("graphql")
+        .enable_all()
+        .build()
+        .map_err(|e| format!("GraphQL error: {}", e))?;
+    let (close, closed) = oneshot::channel::<()>();
+
+    let server = {
+        let _guard = runtime.enter();
+        Server::try_bind(&conf.address)
+            .map_err(|e| {
+                format!("GraphQL error: {} (addr = {})", e, conf.address)
+            })?
+            .serve(make_service_fn(move |_| {
+                let schema = schema.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| {
+                        handle_request(schema.clone(), req)
+                    }))
+                }
+            }))
+            .with_graceful_shutdown(async {
+                closed.await.ok();
+            })
+    };
+    runtime.spawn(async move {
+        if let Err(e) = server.await {
+            warn!("GraphQL server error: {}", e);
+        }
+    });
+
+    Ok(Some(GraphQLServer {
+        runtime: Some(runtime),
+        close: Some(close),
+    }))
+}
+
+async fn handle_request(
+    schema: GraphQLSchema, req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    if req.uri().path() != GRAPHQL_PATH {
+        return Ok(error_response(StatusCode::NOT_FOUND, "Not found"));
+    }
+    if req.method() != Method::POST {
+        return Ok(error_response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            "Only POST requests are supported",
+        ));
+    }
+
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(body) if body.len() <= MAX_REQUEST_BYTES => body,
+        Ok(_) => {
+            return Ok(error_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "Request too large",
+            ))
+        }
+        Err(e) => {
+            return Ok(error_response(StatusCode::BAD_REQUEST, &e.to_string()))
+        }
+    };
+    let request: async_graphql::Request = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            return Ok(error_response(
+                StatusCode::BAD_REQUEST,
+                &format!("Invalid request: {}", e),
+            ))
+        }
+    };
+
+    let response = schema.execute(request).await;
+    Ok(json_response(
+        StatusCode::OK,
+        serde_json::to_vec(&response).expect("Response is serializable; qed"),
+    ))
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+    json_response(
+        status,
+        serde_json::to_vec(
+            &serde_json::json!({ "errors": [{ "message": message }] }),
+        )
+        .expect("Error is serializable; qed"),
+    )
+}
+
+fn json_response(status: StatusCode, body: Vec<u8>) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .expect("Response is valid; qed")
+}
@@ -0,0 +1,783 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! The GraphQL schema, modelled after EIP-1767 and extended with Conflux
+//! epochs, referee blocks and the two address spaces. Core space addresses
+//! are base32 encoded, eSpace addresses are 0x-prefixed hex strings.
+
+use crate::rpc::types::Bytes;
+use async_graphql::{scalar, Context, Enum, InputObject, Object, Result};
+use cfx_addr::{cfx_addr_decode, cfx_addr_encode, EncodingOptions, Network};
+use cfx_statedb::{StateDb, StateDbExt};
+use cfx_types::{
+    Address, AddressSpaceUtil, AddressWithSpace, BigEndianHash, Space, H256,
+    U256,
+};
+use cfxcore::{ConsensusGraph, ConsensusGraphTrait};
+use primitives::{
+    filter::{LogFilter, LogFilterParams},
+    log_entry::LocalizedLogEntry,
+    Action, BlockReceipts, EpochNumber, LogEntry, Receipt, SignedTransaction,
+    StorageKey, StorageValue, TransactionStatus,
+};
+use std::{cmp::min, str::FromStr, sync::Arc};
+
+/// The maximum number of epochs a single `epochs` or `logs` query may cover.
+pub const MAX_EPOCH_RANGE: u64 = 1000;
+
+// The assumed lengths of the list fields without a size argument when
+// computing the complexity of a query.
+const EPOCH_BLOCKS_COMPLEXITY: usize = 10;
+const BLOCK_REFEREES_COMPLEXITY: usize = 10;
+const BLOCK_TRANSACTIONS_COMPLEXITY: usize = 100;
+const TRANSACTION_LOGS_COMPLEXITY: usize = 10;
+
+/// The shared state of the resolvers.
+pub struct GraphQLContext {
+    consensus: Arc<ConsensusGraph>,
+    network: Network,
+}
+
+impl GraphQLContext {
+    pub fn new(consensus: Arc<ConsensusGraph>, network: Network) -> Self {
+        GraphQLContext { consensus, network }
+    }
+
+    fn block(&self, hash: &H256) -> Option<Block> {
+        self.consensus
+            .get_data_manager()
+            .block_by_hash(hash, false /* update_cache */)
+            .map(Block)
+    }
+
+    /// The receipts of the block executed in its current epoch, `None` if
+    /// the block is not executed on the current pivot chain yet.
+    fn block_receipts(&self, hash: &H256) -> Option<Arc<BlockReceipts>> {
+        let epoch = self.consensus.get_block_epoch_number(hash)?;
+        let pivot = self
+            .consensus
+            .get_hash_from_epoch_number(EpochNumber::Number(epoch))
+            .ok()?;
+        self.consensus
+            .get_data_manager()
+            .block_execution_result_by_hash_with_epoch(
+                hash, &pivot, false, /* update_pivot_assumption */
+                false, /* update_cache */
+            )
+            .map(|result| result.block_receipts)
+    }
+
+    fn epoch(&self, number: u64) -> Option<Epoch> {
+        self.consensus
+            .get_block_hashes_by_epoch(EpochNumber::Number(number))
+            .ok()
+            .map(|hashes| Epoch { number, hashes })
+    }
+
+    fn format_address(&self, address: &Address, space: Space) -> String {
+        match space {
+            Space::Native => cfx_addr_encode(
+                &address.0,
+                self.network,
+                EncodingOptions::Simple,
+            )
+            .expect("20 byte address is valid; qed"),
+            Space::Ethereum => format!("{:?}", address),
+        }
+    }
+
+    fn parse_address(&self, address: &str) -> Result<AddressWithSpace> {
+        parse_address(address, self.network)
+    }
+}
+
+fn parse_address(address: &str, network: Network) -> Result<AddressWithSpace> {
+    if address.starts_with("0x") {
+        return Ok(Address::from_str(&address[2..])
+            .map_err(|e| format!("Invalid address {}: {}", address, e))?
+            .with_evm_space());
+    }
+    let decoded = cfx_addr_decode(address)
+        .map_err(|e| format!("Invalid address {}: {}", address, e))?;
+    if decoded.network != network {
+        return Err(format!(
+            "Address {} does not belong to network {}",
+            address, network
+        )
+        .into());
+    }
+    Ok(decoded
+        .hex_address
+        .ok_or_else(|| format!("Invalid address {}: wrong length", address))?
+        .with_native_space())
+}
+
+/// A 32 byte hash or word, as a 0x-prefixed hex string.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bytes32(pub H256);
+scalar!(
+    Bytes32,
+    "Bytes32",
+    "A 32 byte hash or word, as a hex string."
+);
+
+/// A 256 bit unsigned integer, as a 0x-prefixed hex string.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BigInt(pub U256);
+scalar!(
+    BigInt,
+    "BigInt",
+    "A 256 bit unsigned integer, as a hex string."
+);
+
+/// Arbitrary bytes, as a 0x-prefixed hex string.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Data(pub Bytes);
+scalar!(Data, "Bytes", "Arbitrary bytes, as a hex string.");
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+#[graphql(name = "Space")]
+pub enum AddressSpace {
+    /// The Core space.
+    Native,
+    /// The eSpace.
+    Ethereum,
+}
+
+impl From<Space> for AddressSpace {
+    fn from(space: Space) -> Self {
+        match space {
+            Space::Native => AddressSpace::Native,
+            Space::Ethereum => AddressSpace::Ethereum,
+        }
+    }
+}
+
+impl From<AddressSpace> for Space {
+    fn from(space: AddressSpace) -> Self {
+        match space {
+            AddressSpace::Native => Space::Native,
+            AddressSpace::Ethereum => Space::Ethereum,
+        }
+    }
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// The epoch with the given number, the latest executed epoch if omitted.
+    async fn epoch(
+        &self, ctx: &Context<'_>, number: Option<u64>,
+    ) -> Option<Epoch> {
+        let c = ctx.data_unchecked::<GraphQLContext>();
+        let number = number
+            .unwrap_or_else(|| c.consensus.best_executed_state_epoch_number());
+        c.epoch(number)
+    }
+
+    /// The epochs in the inclusive range `[from, to]`, up to the latest
+    /// known epoch.
+    #[graphql(complexity = "(to.saturating_sub(from) as usize)
+        .saturating_add(1)
+        .saturating_mul(child_complexity)")]
+    async fn epochs(
+        &self, ctx: &Context<'_>, from: u64, to: u64,
+    ) -> Result<Vec<Epoch>> {
+        check_epoch_range(from, to)?;
+        let c = ctx.data_unchecked::<GraphQLContext>();
+        Ok((from..=to).map_while(|number| c.epoch(number)).collect())
+    }
+
+    /// The block with the given hash.
+    async fn block(&self, ctx: &Context<'_>, hash: Bytes32) -> Option<Block> {
+        ctx.data_unchecked::<GraphQLContext>().block(&hash.0)
+    }
+
+    /// The packed transaction with the given hash.
+    async fn transaction(
+        &self, ctx: &Context<'_>, hash: Bytes32,
+    ) -> Option<Transaction> {
+        let c = ctx.data_unchecked::<GraphQLContext>();
+        let (tx, info) = c.consensus.get_transaction_info_by_hash(&hash.0)?;
+        if info.tx_index.is_phantom {
+            return None;
+        }
+        Some(Transaction {
+            tx: Arc::new(tx),
+            block_hash: info.tx_index.block_hash,
+            index: info.tx_index.real_index,
+        })
+    }
+
+    /// The state of an account at the given epoch, the latest executed epoch
+    /// if omitted. The space is derived from the address format.
+    async fn account(
+        &self, ctx: &Context<'_>, address: String, epoch: Option<u64>,
+    ) -> Result<Account> {
+        let c = ctx.data_unchecked::<GraphQLContext>();
+        Ok(Account {
+            address: c.parse_address(&address)?,
+            epoch: epoch.map_or(EpochNumber::LatestState, EpochNumber::Number),
+        })
+    }
+
+    /// The logs matching the filter, at most `limit` of them.
+    #[graphql(complexity = "limit.saturating_mul(child_complexity)")]
+    async fn logs(
+        &self, ctx: &Context<'_>, filter: LogFilterInput,
+        #[graphql(default = 1000)] limit: usize,
+    ) -> Result<Vec<Log>> {
+        let c = ctx.data_unchecked::<GraphQLContext>();
+        let best_epoch = c.consensus.best_executed_state_epoch_number();
+        let to_epoch = min(filter.to_epoch.unwrap_or(best_epoch), best_epoch);
+        check_epoch_range(filter.from_epoch, to_epoch)?;
+
+        let space = filter.space.map_or(Space::Native, Into::into);
+        let address = match filter.addresses {
+            None => None,
+            Some(addresses) => Some(
+                addresses
+                    .iter()
+                    .map(|a| {
+                        let a = c.parse_address(a)?;
+                        if a.space != space {
+                            return Err(format!(
+                                "Address {} is not in the filtered space",
+                                c.format_address(&a.address, a.space)
+                            )
+                            .into());
+                        }
+                        Ok(a.address)
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+        };
+        let mut topics: Vec<Option<Vec<H256>>> = filter
+            .topics
+            .unwrap_or_default()
+            .into_iter()
+            .map(|t| t.map(|t| t.into_iter().map(|t| t.0).collect()))
+            .collect();
+        if topics.len() > 4 {
+            return Err("At most 4 topics are allowed".into());
+        }
+        topics.resize(4, None);
+
+        let params = LogFilterParams {
+            address,
+            topics,
+            trusted: false,
+            space,
+        };
+
+        // Scan epoch by epoch to stop as soon as `limit` logs are found.
+        let mut logs = Vec::new();
+        for epoch in filter.from_epoch..=to_epoch {
+            if logs.len() >= limit {
+                break;
+            }
+            let epoch_logs = c.consensus.logs(LogFilter::EpochLogFilter {
+                from_epoch: EpochNumber::Number(epoch),
+                to_epoch: EpochNumber::Number(epoch),
+                params: params.clone(),
+            })?;
+            let remaining = limit - logs.len();
+            logs.extend(epoch_logs.into_iter().take(remaining).map(Log::from));
+        }
+        Ok(logs)
+    }
+}
+
+fn check_epoch_range(from: u64, to: u64) -> Result<()> {
+    if from > to {
+        return Err(format!("Invalid epoch range {}..{}", from, to).into());
+    }
+    if to - from >= MAX_EPOCH_RANGE {
+        return Err(format!(
+            "Epoch range {}..{} exceeds the limit of {} epochs",
+            from, to, MAX_EPOCH_RANGE
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[derive(InputObject)]
+pub struct LogFilterInput {
+    /// The first epoch to search.
+    from_epoch: u64,
+    /// The last epoch to search, the latest executed epoch if omitted.
+    to_epoch: Option<u64>,
+    /// If given, a log must be produced by one of these contracts.
+    addresses: Option<Vec<String>>,
+    /// If given, the n-th topic of a log must match one of the n-th topics
+    /// given, where `null` matches any topic.
+    topics: Option<Vec<Option<Vec<Bytes32>>>>,
+    /// The space to search, the Core space if omitted.
+    space: Option<AddressSpace>,
+}
+
+/// An epoch, i.e. a pivot block and the blocks it confirms.
+pub struct Epoch {
+    number: u64,
+    // The blocks in execution order, the pivot block is the last one.
+    hashes: Vec<H256>,
+}
+
+#[Object]
+impl Epoch {
+    async fn number(&self) -> u64 { self.number }
+
+    async fn pivot_block(&self, ctx: &Context<'_>) -> Option<Block> {
+        ctx.data_unchecked::<GraphQLContext>()
+            .block(self.hashes.last()?)
+    }
+
+    /// The blocks of the epoch in execution order.
+    #[graphql(
+        complexity = "EPOCH_BLOCKS_COMPLEXITY.saturating_mul(child_complexity)"
+    )]
+    async fn blocks(&self, ctx: &Context<'_>) -> Vec<Block> {
+        let c = ctx.data_unchecked::<GraphQLContext>();
+        self.hashes.iter().filter_map(|h| c.block(h)).collect()
+    }
+}
+
+pub struct Block(Arc<primitives::Block>);
+
+#[Object]
+impl Block {
+    async fn hash(&self) -> Bytes32 { Bytes32(self.0.hash()) }
+
+    async fn parent_hash(&self) -> Bytes32 {
+        Bytes32(*self.0.block_header.parent_hash())
+    }
+
+    async fn parent(&self, ctx: &Context<'_>) -> Option<Block> {
+        ctx.data_unchecked::<GraphQLContext>()
+            .block(self.0.block_header.parent_hash())
+    }
+
+    async fn height(&self) -> u64 { self.0.block_header.height() }
+
+    /// The epoch confirming the block, `null` if not confirmed yet.
+    async fn epoch_number(&self, ctx: &Context<'_>) -> Option<u64> {
+        ctx.data_unchecked::<GraphQLContext>()
+            .consensus
+            .get_block_epoch_number(&self.0.hash())
+    }
+
+    /// Whether the block is on the current pivot chain.
+    async fn is_pivot(&self, ctx: &Context<'_>) -> bool {
+        let c = ctx.data_unchecked::<GraphQLContext>();
+        let height = self.0.block_header.height();
+        c.consensus
+            .get_hash_from_epoch_number(EpochNumber::Number(height))
+            .map_or(false, |pivot| pivot == self.0.hash())
+    }
+
+    async fn miner(&self, ctx: &Context<'_>) -> String {
+        ctx.data_unchecked::<GraphQLContext>()
+            .format_address(self.0.block_header.author(), Space::Native)
+    }
+
+    async fn timestamp(&self) -> u64 { self.0.block_header.timestamp() }
+
+    async fn difficulty(&self) -> BigInt {
+        BigInt(*self.0.block_header.difficulty())
+    }
+
+    async fn gas_limit(&self) -> BigInt {
+        BigInt(*self.0.block_header.gas_limit())
+    }
+
+    async fn base_fee_per_gas(&self, space: AddressSpace) -> Option<BigInt> {
+        self.0
+            .block_header
+            .base_price()
+            .map(|prices| BigInt(*prices.in_space(space.into())))
+    }
+
+    async fn deferred_state_root(&self) -> Bytes32 {
+        Bytes32(*self.0.block_header.deferred_state_root())
+    }
+
+    async fn referee_hashes(&self) -> Vec<Bytes32> {
+        self.0
+            .block_header
+            .referee_hashes()
+            .iter()
+            .map(|h| Bytes32(*h))
+            .collect()
+    }
+
+    #[graphql(
+        complexity = "BLOCK_REFEREES_COMPLEXITY.saturating_mul(child_complexity)"
+    )]
+    async fn referees(&self, ctx: &Context<'_>) -> Vec<Block> {
+        let c = ctx.data_unchecked::<GraphQLContext>();
+        self.0
+            .block_header
+            .referee_hashes()
+            .iter()
+            .filter_map(|h| c.block(h))
+            .collect()
+    }
+
+    async fn transaction_count(&self) -> usize { self.0.transactions.len() }
+
+    /// The transactions of both spaces packed in the block.
+    #[graphql(complexity = "BLOCK_TRANSACTIONS_COMPLEXITY
+        .saturating_mul(child_complexity)")]
+    async fn transactions(&self) -> Vec<Transaction> {
+        let hash = self.0.hash();
+        self.0
+            .transactions
+            .iter()
+            .enumerate()
+            .map(|(index, tx)| Transaction {
+                tx: tx.clone(),
+                block_hash: hash,
+                index,
+            })
+            .collect()
+    }
+}
+
+pub struct Transaction {
+    tx: Arc<SignedTransaction>,
+    block_hash: H256,
+    index: usize,
+}
+
+impl Transaction {
+    fn receipts(&self, ctx: &Context<'_>) -> Option<Arc<BlockReceipts>> {
+        ctx.data_unchecked::<GraphQLContext>()
+            .block_receipts(&self.block_hash)
+    }
+
+    fn receipt<'a>(&self, receipts: &'a BlockReceipts) -> Option<&'a Receipt> {
+        receipts.receipts.get(self.index)
+    }
+}
+
+#[Object]
+impl Transaction {
+    async fn hash(&self) -> Bytes32 { Bytes32(self.tx.hash()) }
+
+    async fn space(&self) -> AddressSpace { self.tx.space().into() }
+
+    async fn nonce(&self) -> BigInt { BigInt(*self.tx.nonce()) }
+
+    /// The index of the transaction in its block.
+    async fn index(&self) -> usize { self.index }
+
+    async fn from(&self, ctx: &Context<'_>) -> String {
+        let sender = self.tx.sender();
+        ctx.data_unchecked::<GraphQLContext>()
+            .format_address(&sender.address, sender.space)
+    }
+
+    /// The receiver, `null` for contract creations.
+    async fn to(&self, ctx: &Context<'_>) -> Option<String> {
+        match self.tx.action() {
+            Action::Create => None,
+            Action::Call(to) => Some(
+                ctx.data_unchecked::<GraphQLContext>()
+                    .format_address(to, self.tx.space()),
+            ),
+        }
+    }
+
+    async fn value(&self) -> BigInt { BigInt(*self.tx.value()) }
+
+    async fn gas_price(&self) -> BigInt { BigInt(*self.tx.gas_price()) }
+
+    async fn gas(&self) -> BigInt { BigInt(*self.tx.gas()) }
+
+    async fn input_data(&self) -> Data { Data(self.tx.data().clone().into()) }
+
+    async fn block(&self, ctx: &Context<'_>) -> Option<Block> {
+        ctx.data_unchecked::<GraphQLContext>()
+            .block(&self.block_hash)
+    }
+
+    /// 1 if the execution succeeded, 0 if it failed, `null` if the
+    /// transaction is not executed yet or skipped.
+    async fn status(&self, ctx: &Context<'_>) -> Option<u64> {
+        let receipts = self.receipts(ctx)?;
+        match self.receipt(&receipts)?.outcome_status {
+            TransactionStatus::Success => Some(1),
+            TransactionStatus::Failure => Some(0),
+            TransactionStatus::Skipped => None,
+        }
+    }
+
+    async fn gas_used(&self, ctx: &Context<'_>) -> Option<BigInt> {
+        let receipts = self.receipts(ctx)?;
+        let prior_gas_used = match self.index {
+            0 => U256::zero(),
+            i => receipts.receipts.get(i - 1)?.accumulated_gas_used,
+        };
+        Some(BigInt(
+            self.receipt(&receipts)?.accumulated_gas_used - prior_gas_used,
+        ))
+    }
+
+    async fn cumulative_gas_used(&self, ctx: &Context<'_>) -> Option<BigInt> {
+        let receipts = self.receipts(ctx)?;
+        Some(BigInt(self.receipt(&receipts)?.accumulated_gas_used))
+    }
+
+    async fn gas_fee(&self, ctx: &Context<'_>) -> Option<BigInt> {
+        let receipts = self.receipts(ctx)?;
+        Some(BigInt(self.receipt(&receipts)?.gas_fee))
+    }
+
+    #[graphql(complexity = "TRANSACTION_LOGS_COMPLEXITY
+        .saturating_mul(child_complexity)")]
+    async fn logs(&self, ctx: &Context<'_>) -> Option<Vec<Log>> {
+        let receipts = self.receipts(ctx)?;
+        Some(
+            self.receipt(&receipts)?
+                .logs
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| Log {
+                    entry: entry.clone(),
+                    block_hash: self.block_hash,
+                    transaction_index: self.index,
+                    index,
+                })
+                .collect(),
+        )
+    }
+}
+
+pub struct Log {
+    entry: LogEntry,
+    block_hash: H256,
+    transaction_index: usize,
+    // The index of the log in its transaction.
+    index: usize,
+}
+
+impl From<LocalizedLogEntry> for Log {
+    fn from(log: LocalizedLogEntry) -> Self {
+        Log {
+            entry: log.entry,
+            block_hash: log.block_hash,
+            transaction_index: log.transaction_index,
+            index: log.transaction_log_index,
+        }
+    }
+}
+
+#[Object]
+impl Log {
+    /// The index of the log in its transaction.
+    async fn index(&self) -> usize { self.index }
+
+    async fn space(&self) -> AddressSpace { self.entry.space.into() }
+
+    /// The contract emitting the log.
+    async fn address(&self, ctx: &Context<'_>) -> String {
+        ctx.data_unchecked::<GraphQLContext>()
+            .format_address(&self.entry.address, self.entry.space)
+    }
+
+    async fn topics(&self) -> Vec<Bytes32> {
+        self.entry.topics.iter().map(|t| Bytes32(*t)).collect()
+    }
+
+    async fn data(&self) -> Data { Data(self.entry.data.clone().into()) }
+
+    async fn transaction(&self, ctx: &Context<'_>) -> Option<Transaction> {
+        let block = ctx
+            .data_unchecked::<GraphQLContext>()
+            .consensus
+            .get_data_manager()
+            .block_by_hash(&self.block_hash, false /* update_cache */)?;
+        Some(Transaction {
+            tx: block.transactions.get(self.transaction_index)?.clone(),
+            block_hash: self.block_hash,
+            index: self.transaction_index,
+        })
+    }
+}
+
+pub struct Account {
+    address: AddressWithSpace,
+    epoch: EpochNumber,
+}
+
+impl Account {
+    fn state_db(&self, ctx: &Context<'_>) -> Result<StateDb> {
+        let consensus = &ctx.data_unchecked::<GraphQLContext>().consensus;
+        Ok(match self.address.space {
+            Space::Native => consensus
+                .get_state_db_by_epoch_number(self.epoch.clone(), "epoch")?,
+            Space::Ethereum => consensus.get_eth_state_db_by_epoch_number(
+                self.epoch.clone(),
+                "epoch",
+            )?,
+        })
+    }
+
+    fn get_account(
+        &self, ctx: &Context<'_>,
+    ) -> Result<Option<primitives::Account>> {
+        Ok(self.state_db(ctx)?.get_account(&self.address)?)
+    }
+}
+
+#[Object]
+impl Account {
+    async fn address(&self, ctx: &Context<'_>) -> String {
+        ctx.data_unchecked::<GraphQLContext>()
+            .format_address(&self.address.address, self.address.space)
+    }
+
+    async fn space(&self) -> AddressSpace { self.address.space.into() }
+
+    async fn balance(&self, ctx: &Context<'_>) -> Result<BigInt> {
+        Ok(BigInt(
+            self.get_account(ctx)?
+                .map_or(U256::zero(), |acc| acc.balance),
+        ))
+    }
+
+    async fn nonce(&self, ctx: &Context<'_>) -> Result<BigInt> {
+        Ok(BigInt(
+            self.get_account(ctx)?.map_or(U256::zero(), |acc| acc.nonce),
+        ))
+    }
+
+    /// The staked balance, always 0 in the eSpace.
+    async fn staking_balance(&self, ctx: &Context<'_>) -> Result<BigInt> {
+        Ok(BigInt(
+            self.get_account(ctx)?
+                .map_or(U256::zero(), |acc| acc.staking_balance),
+        ))
+    }
+
+    /// The storage collateral, always 0 in the eSpace.
+    async fn collateral_for_storage(
+        &self, ctx: &Context<'_>,
+    ) -> Result<BigInt> {
+        Ok(BigInt(
+            self.get_account(ctx)?
+                .map_or(U256::zero(), |acc| acc.collateral_for_storage),
+        ))
+    }
+
+    async fn code(&self, ctx: &Context<'_>) -> Result<Data> {
+        let state_db = self.state_db(ctx)?;
+        let code = match state_db.get_account(&self.address)? {
+            Some(acc) => state_db
+                .get_code(&self.address, &acc.code_hash)?
+                .map_or_else(Vec::new, |code| (*code.code).clone()),
+            None => vec![],
+        };
+        Ok(Data(Bytes::new(code)))
+    }
+
+    async fn storage(
+        &self, ctx: &Context<'_>, slot: Bytes32,
+    ) -> Result<Bytes32> {
+        let state_db = self.state_db(ctx)?;
+        let key =
+            StorageKey::new_storage_key(&self.address.address, slot.0.as_ref())
+                .with_space(self.address.space);
+        Ok(Bytes32(
+            state_db
+                .get::<StorageValue>(key)?
+                .map_or_else(H256::zero, |entry| H256::from_uint(&entry.value)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+
+    #[test]
+    fn test_parse_address() {
+        let network = Network::Main;
+        let address = Address::from_low_u64_be(0x1234);
+        let base32 =
+            cfx_addr_encode(&address.0, network, EncodingOptions::Simple)
+                .unwrap();
+
+        assert_eq!(
+            parse_address(&base32, network).unwrap(),
+            address.with_native_space()
+        );
+        assert_eq!(
+            parse_address(&format!("{:?}", address), network).unwrap(),
+            address.with_evm_space()
+        );
+        assert!(parse_address(&base32, Network::Test).is_err());
+        assert!(parse_address("0x1234", network).is_err());
+    }
+
+    #[test]
+    fn test_query_cost_limits() {
+        let schema = Schema::build(Query, EmptyMutation, EmptySubscription)
+            .limit_complexity(100)
+            .limit_depth(4)
+            .finish();
+        let execute = |query: &str| {
+            futures::executor::block_on(schema.execute(query)).errors
+        };
+
+        // the query is rejected before any resolver runs
+        let errors = execute("{ epochs(from: 0, to: 99) { number } }");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("too complex"));
+
+        // the cost does not overflow
+        let errors = execute(&format!(
+            "{{ epochs(from: 0, to: {}) {{ number blocks {{ hash }} }} }}",
+            i64::MAX
+        ));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("too complex"));
+        let errors = execute(&format!(
+            "{{ logs(filter: {{ fromEpoch: 0 }}, limit: {}) {{ index data }} }}",
+            i64::MAX
+        ));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("too complex"));
+
+        // the list fields without a size argument are counted several times
+        let errors = execute("{ epoch { blocks { transactions { hash } } } }");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("too complex"));
+        let errors = execute(
+            "{ block(hash: \"0x0000000000000000000000000000000000000000000000000000000000000000\") \
+             { referees { referees { hash } } } }",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("too complex"));
+        let errors = execute(
+            "{ logs(filter: { fromEpoch: 0 }, limit: 10) \
+             { transaction { logs { index } } } }",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("too complex"));
+
+        let errors = execute(
+            "{ block(hash: \"0x0000000000000000000000000000000000000000000000000000000000000000\") \
+             { parent { parent { parent { hash } } } } }",
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("nested too deep"));
+    }
+}
@@ -5,6 +5,7 @@
 mod epoch_queue;
 mod poll_filter;
 mod poll_manager;
+mod reorg_tracker;
 mod subscribers;
 
 pub use self::{
@@ -16,4 +17,5 @@ pub use self::{
 pub use cfx_rpc::helpers::MAX_FEE_HISTORY_CACHE_BLOCK_COUNT;
 pub use cfx_rpc_primitives::{maybe_vec_into, VariadicValue};
 pub use epoch_queue::EpochQueue;
+pub use reorg_tracker::{ReorgTracker, Reverted};
 pub use subscribers::{Id as SubscriberId, Subscribers};
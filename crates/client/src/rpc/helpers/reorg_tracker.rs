@@ -0,0 +1,223 @@
+// Copyright 2020 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_types::H256;
+use cfxcore::client::PivotChainReorg;
+use std::{cmp::min, collections::VecDeque};
+
+/// ReorgTracker remembers the epochs already published to a subscriber, so
+/// that on a pivot chain reorg the published items can be revoked in reverse
+/// order. The pivot chain reorgs reported by consensus are merged so that the
+/// new pivot chain segment can be reported together with the reverted one.
+pub struct ReorgTracker<T> {
+    // published epochs as (epoch number, pivot hash, published items)
+    published: VecDeque<(u64, H256, Vec<T>)>,
+
+    // the latest known pivot chain segment as (first epoch, pivot hashes)
+    pivots: Option<(u64, Vec<H256>)>,
+}
+
+/// The epochs reverted by a pivot chain reorg.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Reverted<T> {
+    /// The first reverted epoch.
+    pub fork_at: u64,
+    /// Pivot block hashes of the reverted epochs, in ascending order.
+    pub old_pivots: Vec<H256>,
+    /// Pivot block hashes of the new pivot chain from `fork_at`, as far as
+    /// known from the reorg notifications.
+    pub new_pivots: Vec<H256>,
+    /// The published items of the reverted epochs, the latest first.
+    pub items: Vec<T>,
+}
+
+impl<T> Default for ReorgTracker<T> {
+    fn default() -> Self {
+        Self {
+            published: VecDeque::new(),
+            pivots: None,
+        }
+    }
+}
+
+impl<T> ReorgTracker<T> {
+    pub fn on_pivot_chain_reorg(&mut self, reorg: PivotChainReorg) {
+        if let Some((start, pivots)) = &mut self.pivots {
+            // extend the known segment if the reorg does not leave a gap
+            if *start <= reorg.fork_at
+                && reorg.fork_at - *start <= pivots.len() as u64
+            {
+                pivots.truncate((reorg.fork_at - *start) as usize);
+                pivots.extend(reorg.new_pivots);
+                return;
+            }
+        }
+        self.pivots = Some((reorg.fork_at, reorg.new_pivots));
+    }
+
+    /// Record the items published for `epoch`.
+    pub fn push(&mut self, epoch: u64, pivot: H256, items: Vec<T>) {
+        self.published.push_back((epoch, pivot, items));
+    }
+
+    /// Revert all published epochs from `fork_at`. Return `None` if no
+    /// published epoch is reverted.
+    pub fn revert(&mut self, fork_at: u64) -> Option<Reverted<T>> {
+        let mut old_pivots = vec![];
+        let mut items = vec![];
+        while matches!(self.published.back(), Some((e, _, _)) if *e >= fork_at)
+        {
+            let (_, pivot, epoch_items) = self.published.pop_back().unwrap();
+            old_pivots.push(pivot);
+            items.extend(epoch_items.into_iter().rev());
+        }
+        if old_pivots.is_empty() {
+            return None;
+        }
+        old_pivots.reverse();
+
+        let new_pivots = match &self.pivots {
+            Some((start, pivots)) if *start <= fork_at => pivots
+                .iter()
+                .skip((fork_at - *start) as usize)
+                .cloned()
+                .collect(),
+            _ => vec![],
+        };
+
+        Some(Reverted {
+            fork_at,
+            old_pivots,
+            new_pivots,
+            items,
+        })
+    }
+
+    /// Forget the epochs before `epoch`, which cannot be reverted anymore.
+    pub fn prune(&mut self, epoch: u64) {
+        while matches!(self.published.front(), Some((e, _, _)) if *e < epoch) {
+            self.published.pop_front();
+        }
+        if let Some((start, pivots)) = &mut self.pivots {
+            if *start < epoch {
+                let n = min(pivots.len(), (epoch - *start) as usize);
+                pivots.drain(..n);
+                *start += n as u64;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn h(n: u64) -> H256 { H256::from_low_u64_be(n) }
+
+    fn reorg(fork_at: u64, new_pivots: &[u64]) -> PivotChainReorg {
+        PivotChainReorg {
+            fork_at,
+            old_pivots: vec![],
+            new_pivots: new_pivots.iter().map(|n| h(*n)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_no_reorgs() {
+        let mut tracker = ReorgTracker::default();
+
+        tracker.push(0, h(0), vec![0, 1]);
+        tracker.push(1, h(1), vec![2]);
+        tracker.push(2, h(2), vec![]);
+
+        assert_eq!(tracker.revert(3), None);
+    }
+
+    #[test]
+    fn test_shallow_reorgs() {
+        let mut tracker = ReorgTracker::default();
+
+        tracker.push(0, h(0), vec![0, 1]);
+        tracker.push(1, h(1), vec![2, 3]);
+        tracker.push(2, h(2), vec![4]);
+        tracker.on_pivot_chain_reorg(reorg(2, &[12, 13]));
+
+        assert_eq!(
+            tracker.revert(2),
+            Some(Reverted {
+                fork_at: 2,
+                old_pivots: vec![h(2)],
+                new_pivots: vec![h(12), h(13)],
+                items: vec![4],
+            })
+        );
+
+        tracker.push(2, h(12), vec![5]);
+        tracker.push(3, h(13), vec![6, 7]);
+        tracker.on_pivot_chain_reorg(reorg(3, &[23]));
+
+        assert_eq!(
+            tracker.revert(3),
+            Some(Reverted {
+                fork_at: 3,
+                old_pivots: vec![h(13)],
+                new_pivots: vec![h(23)],
+                items: vec![7, 6],
+            })
+        );
+    }
+
+    #[test]
+    fn test_deep_reorgs() {
+        let mut tracker = ReorgTracker::default();
+
+        for epoch in 0..10 {
+            tracker.push(epoch, h(epoch), vec![2 * epoch, 2 * epoch + 1]);
+        }
+
+        // two consecutive switches before the new epochs are published
+        tracker.on_pivot_chain_reorg(reorg(3, &[103, 104, 105, 106]));
+        tracker.on_pivot_chain_reorg(reorg(5, &[205, 206, 207]));
+
+        assert_eq!(
+            tracker.revert(3),
+            Some(Reverted {
+                fork_at: 3,
+                old_pivots: (3..10).map(h).collect(),
+                new_pivots: vec![h(103), h(104), h(205), h(206), h(207)],
+                items: (6..20).rev().collect(),
+            })
+        );
+        assert_eq!(
+            tracker.revert(0).map(|r| r.items),
+            Some(vec![5, 4, 3, 2, 1, 0])
+        );
+    }
+
+    #[test]
+    fn test_prune() {
+        let mut tracker = ReorgTracker::default();
+
+        for epoch in 0..5 {
+            tracker.push(epoch, h(epoch), vec![epoch]);
+        }
+        tracker.on_pivot_chain_reorg(reorg(1, &[11, 12, 13, 14]));
+        tracker.prune(3);
+
+        assert_eq!(
+            tracker.revert(0),
+            Some(Reverted {
+                fork_at: 0,
+                old_pivots: vec![h(3), h(4)],
+                new_pivots: vec![],
+                items: vec![4, 3],
+            })
+        );
+        tracker.push(3, h(13), vec![3]);
+        assert_eq!(
+            tracker.revert(3).map(|r| r.new_pivots),
+            Some(vec![h(13), h(14)])
+        );
+    }
+}
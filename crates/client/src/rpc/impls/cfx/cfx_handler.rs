@@ -17,12 +17,13 @@ use crate::rpc::{
         VoteParamsInfo, WrapTransaction, U64 as HexU64,
     },
 };
-use blockgen::BlockGenerator;
+use blockgen::{BlockGenerator, DevClock};
 use cfx_execute_helper::estimation::{decode_error, EstimateExt};
 use cfx_executor::{
     executive::{ExecutionError, ExecutionOutcome, TxDropError},
     internal_contract::storage_point_prop,
 };
+use cfx_rpc::helpers::{account_range, state_range_limit, storage_range};
 use cfx_statedb::{
     global_params::{
         AccumulateInterestRate, BaseFeeProp, DistributablePoSInterest,
@@ -32,13 +33,14 @@ use cfx_statedb::{
     StateDbExt,
 };
 use cfx_types::{
-    Address, AddressSpaceUtil, BigEndianHash, Space, H256, H520, U128, U256,
-    U64,
+    Address, AddressSpaceUtil, AddressWithSpace, BigEndianHash, Space, H256,
+    H520, U128, U256, U64,
 };
 use cfx_vm_types::Error as VmError;
 use cfxcore::{
     block_data_manager::BlockExecutionResult,
     errors::account_result_to_rpc_result,
+    light_protocol::LedgerInfo,
     state_exposer::STATE_EXPOSER,
     transaction_pool::TransactionPoolError,
     verification::{compute_epoch_receipt_proof, EpochReceiptProof},
@@ -48,6 +50,10 @@ use cfxcore::{
 use cfxcore_accounts::AccountProvider;
 use delegate::delegate;
 use diem_types::transaction::TransactionPayload;
+use futures::{
+    channel::oneshot,
+    future::{FutureExt, TryFutureExt},
+};
 use jsonrpc_core::{BoxFuture, Error as JsonRpcError, Result as JsonRpcResult};
 use network::{
     node_table::{Node, NodeId},
@@ -56,20 +62,26 @@ use network::{
 use parking_lot::Mutex;
 use primitives::{
     filter::LogFilter, receipt::EVM_SPACE_SUCCESS, Account, Block, BlockHeader,
-    BlockReceipts, DepositInfo, SignedTransaction, StorageKey, StorageRoot,
-    StorageValue, Transaction, TransactionIndex, TransactionStatus,
-    TransactionWithSignature, VoteStakeInfo,
+    BlockReceipts, DepositInfo, SignedTransaction, StateRoot, StorageKey,
+    StorageRoot, StorageValue, Transaction, TransactionIndex,
+    TransactionStatus, TransactionWithSignature, VoteStakeInfo,
 };
 use random_crash::*;
 use rlp::Rlp;
 use rustc_hex::ToHex;
 use std::{
-    collections::BTreeMap, net::SocketAddr, sync::Arc, thread, time::Duration,
+    cmp::max,
+    collections::{BTreeMap, HashSet},
+    net::SocketAddr,
+    path::Path,
+    sync::Arc,
+    thread,
+    time::Duration,
 };
 use txgen::{DirectTransactionGenerator, TransactionGenerator};
 // To convert from RpcResult to BoxFuture by delegate! macro automatically.
 use crate::{
-    common::delegate_convert,
+    common::delegate_convert::{self, into_jsonrpc_result},
     rpc::{
         errors::{
             call_execution_error, internal_error, invalid_params,
@@ -79,25 +91,28 @@ use crate::{
             common::{self, RpcImpl as CommonImpl},
             RpcImplConfiguration,
         },
-        traits::{cfx::Cfx, debug::LocalRpc, test::TestRpc},
+        traits::{cfx::Cfx, debug::LocalRpc, dev::DevRpc, test::TestRpc},
         types::{
             eth::Transaction as EthTransaction, pos::Block as PosBlock,
             Account as RpcAccount, AccountPendingInfo,
-            AccountPendingTransactions, BlameInfo, Block as RpcBlock,
-            BlockHashOrEpochNumber, Bytes, CfxRpcLogFilter,
-            CheckBalanceAgainstTransactionResponse, ConsensusGraphStates,
+            AccountPendingTransactions, AccountProof, AccountRange, BlameInfo,
+            Block as RpcBlock, BlockHashOrEpochNumber, Bytes, CfxRpcLogFilter,
+            CheckBalanceAgainstTransactionResponse, CheckpointSummary,
+            ConsensusGraphStates, EpochExecutionWitnessVerification,
             EpochNumber, EstimateGasAndCollateralResponse, Log as RpcLog,
             PackedOrExecuted, Receipt as RpcReceipt,
-            RewardInfo as RpcRewardInfo, Status as RpcStatus,
-            StorageCollateralInfo, SyncGraphStates,
+            RewardInfo as RpcRewardInfo, SnapshotArchiveSummary,
+            SnapshotVerification, StateRootCommitment, StateRootProof,
+            Status as RpcStatus, StorageCollateralInfo, StorageRange,
+            StorageSlotProof, SyncGraphStates, SyncStatus as RpcSyncStatus,
             Transaction as RpcTransaction, TransactionRequest,
         },
-        CoreResult,
+        CoreBoxFuture, CoreResult,
     },
 };
 use cfx_addr::Network;
 use cfx_execute_helper::estimation::EstimateRequest;
-use cfx_executor::state::State;
+use cfx_executor::state::{DevStateChange, State};
 use cfx_parameters::{
     consensus_internal::REWARD_EPOCH_COUNT,
     genesis::{
@@ -105,7 +120,7 @@ use cfx_parameters::{
     },
     staking::{BLOCKS_PER_YEAR, DRIPS_PER_STORAGE_COLLATERAL_UNIT},
 };
-use cfx_storage::state::StateDbGetOriginalMethods;
+use cfx_storage::state::{StateDbGetOriginalMethods, StateTrait};
 use cfxcore::{
     consensus::{MaybeExecutedTxExtraInfo, TransactionInfo},
     consensus_parameters::DEFERRED_STATE_EPOCH_COUNT,
@@ -135,6 +150,26 @@ pub struct RpcImpl {
     maybe_txgen: Option<Arc<TransactionGenerator>>,
     maybe_direct_txgen: Option<Arc<Mutex<DirectTransactionGenerator>>>,
     accounts: Arc<AccountProvider>,
+    dev_state: Mutex<DevRpcState>,
+}
+
+/// The state kept by the dev mode RPCs.
+#[derive(Default)]
+struct DevRpcState {
+    /// The snapshots indexed by the snapshot id.
+    snapshots: Vec<DevSnapshot>,
+    /// The accounts whose transactions are accepted without a signature.
+    impersonated_accounts: HashSet<AddressWithSpace>,
+}
+
+/// The dev mode state recorded by `dev_snapshot` and restored by
+/// `dev_revert`.
+struct DevSnapshot {
+    best_block: H256,
+    dev_clock: DevClock,
+    impersonated_accounts: HashSet<AddressWithSpace>,
+    /// The transactions in the pool.
+    pool: Vec<Arc<SignedTransaction>>,
 }
 
 impl RpcImpl {
@@ -154,6 +189,7 @@ impl RpcImpl {
             maybe_direct_txgen,
             config,
             accounts,
+            dev_state: Default::default(),
         }
     }
 
@@ -492,29 +528,43 @@ impl RpcImpl {
         let r = self.send_transaction_with_signature(tx);
         if r.is_ok() && self.config.dev_pack_tx_immediately {
             // Try to pack and execute this new tx.
-            for _ in 0..DEFERRED_STATE_EPOCH_COUNT {
-                let generated = self.generate_one_block(
-                    1, /* num_txs */
-                    self.sync
-                        .get_synchronization_graph()
-                        .verification_config
-                        .max_block_size_in_bytes,
-                )?;
-                loop {
-                    // Wait for the new block to be fully processed, so all
-                    // generated blocks form a chain for
-                    // `tx` to be executed.
-                    if self.consensus.best_block_hash() == generated {
-                        break;
-                    } else {
-                        thread::sleep(Duration::from_millis(10));
-                    }
-                }
-            }
+            self.generate_blocks_and_wait(
+                DEFERRED_STATE_EPOCH_COUNT,
+                1, /* num_txs */
+            )?;
         }
         r
     }
 
+    /// Generates `num_blocks` blocks on top of the best block, each packing
+    /// at most `num_txs` transactions from the pool, and returns the hash of
+    /// the last one.
+    fn generate_blocks_and_wait(
+        &self, num_blocks: u64, num_txs: usize,
+    ) -> CoreResult<H256> {
+        let _dev_mining = self.block_gen.lock_dev_mining();
+        let mut generated = self.consensus.best_block_hash();
+        for _ in 0..num_blocks {
+            generated = self.generate_one_block(
+                num_txs,
+                self.sync
+                    .get_synchronization_graph()
+                    .verification_config
+                    .max_block_size_in_bytes,
+            )?;
+            self.wait_for_best_block(&generated);
+        }
+        Ok(generated)
+    }
+
+    /// Waits for a generated block to be fully processed, so the blocks
+    /// generated after it form a chain.
+    fn wait_for_best_block(&self, hash: &H256) {
+        while self.consensus.best_block_hash() != *hash {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
     fn storage_at(
         &self, address: RpcAddress, position: U256,
         block_hash_or_epoch_number: Option<BlockHashOrEpochNumber>,
@@ -594,7 +644,24 @@ impl RpcImpl {
             "tx",
             self.sync.network.get_network_type(),
         )?;
+        self.fill_transaction_request(&mut tx)?;
+
+        let epoch_height = consensus_graph.best_epoch_number();
+        let chain_id = consensus_graph.best_chain_id();
+        tx.sign_with(
+            epoch_height,
+            chain_id.in_native_space(),
+            password,
+            self.accounts.clone(),
+        )
+    }
 
+    /// Fills the nonce, gas, storage limit and gas price of `tx` if they are
+    /// not set.
+    fn fill_transaction_request(
+        &self, tx: &mut TransactionRequest,
+    ) -> CoreResult<()> {
+        let consensus_graph = self.consensus_graph();
         if tx.nonce.is_none() {
             let nonce = consensus_graph.next_nonce(
                 Address::from(tx.from.clone().ok_or("from should have")?)
@@ -610,9 +677,6 @@ impl RpcImpl {
             debug!("after loading nonce in latest state, tx = {:?}", tx);
         }
 
-        let epoch_height = consensus_graph.best_epoch_number();
-        let chain_id = consensus_graph.best_chain_id();
-
         if tx.gas.is_none() || tx.storage_limit.is_none() {
             let estimate =
                 self.estimate_gas_and_collateral(tx.clone(), None)?;
@@ -633,13 +697,7 @@ impl RpcImpl {
                 tx.gas_price.replace(gas_price.unwrap());
             }
         }
-
-        tx.sign_with(
-            epoch_height,
-            chain_id.in_native_space(),
-            password,
-            self.accounts.clone(),
-        )
+        Ok(())
     }
 
     fn send_transaction(
@@ -682,6 +740,135 @@ impl RpcImpl {
         Ok(Some(root))
     }
 
+    fn get_proof(
+        &self, address: RpcAddress, storage_keys: Vec<U256>,
+        epoch_num: Option<EpochNumber>,
+    ) -> CoreResult<AccountProof> {
+        self.check_address_network(address.network)?;
+        let epoch_num = epoch_num.unwrap_or(EpochNumber::LatestState).into();
+
+        info!(
+            "RPC Request: cfx_getProof address={:?} storage_keys={:?} epoch={:?}",
+            address, storage_keys, epoch_num
+        );
+
+        // Resolve the epoch number first so that all entries and state roots
+        // are of the same epoch even if the pivot chain changes meanwhile.
+        let epoch = self
+            .consensus_graph()
+            .get_height_from_epoch_number(epoch_num)?;
+        let state = self.consensus.get_storage_state_by_epoch_number(
+            primitives::EpochNumber::Number(epoch),
+            "epoch_num",
+        )?;
+        let hex_address = &address.hex_address;
+
+        let (account_entry, account_proof) = state
+            .get_original_raw_with_proof(
+                StorageKey::new_account_key(hex_address).with_native_space(),
+            )?;
+        let account = match &account_entry {
+            Some(raw) => account_result_to_rpc_result(
+                "address",
+                Account::new_from_rlp(*hex_address, &Rlp::new(raw)),
+            )?,
+            None => account_result_to_rpc_result(
+                "address",
+                Account::new_empty_with_balance(
+                    &hex_address.with_native_space(),
+                    &U256::zero(), /* balance */
+                    &U256::zero(), /* nonce */
+                ),
+            )?,
+        };
+
+        let (storage_root, storage_root_proof) = state
+            .get_original_storage_root_with_proof(
+                &hex_address.with_native_space(),
+            )?;
+
+        let mut storage_proof = Vec::with_capacity(storage_keys.len());
+        for key in storage_keys {
+            let position = H256::from_uint(&key);
+            let (entry, proof) = state.get_original_raw_with_proof(
+                StorageKey::new_storage_key(hex_address, position.as_ref())
+                    .with_native_space(),
+            )?;
+            let value = match &entry {
+                Some(raw) => Some(H256::from_uint(
+                    &rlp::decode::<StorageValue>(raw)?.value,
+                )),
+                None => None,
+            };
+            storage_proof.push(StorageSlotProof {
+                key,
+                value,
+                entry: entry.map(|raw| Bytes::new(raw.into_vec())),
+                proof: Bytes::new(rlp::encode(&proof)),
+            });
+        }
+
+        let ledger = LedgerInfo::new(self.consensus.clone());
+        let state_root = self.state_root_proof(
+            &ledger,
+            epoch,
+            state.get_state_root()?.state_root,
+        )?;
+        let snapshot_epoch_count =
+            self.consensus.get_data_manager().get_snapshot_epoch_count() as u64;
+        // The keys of the intermediate delta MPT are padded with the state
+        // root of the epoch one snapshot period earlier.
+        let prev_snapshot_state_root = if epoch > snapshot_epoch_count {
+            let prev_epoch = epoch - snapshot_epoch_count;
+            Some(self.state_root_proof(
+                &ledger,
+                prev_epoch,
+                ledger.correct_state_root_of(prev_epoch)?,
+            )?)
+        } else {
+            None
+        };
+
+        Ok(AccountProof {
+            address,
+            epoch_number: epoch.into(),
+            account: account_entry.map(|raw| Bytes::new(raw.into_vec())),
+            balance: account.balance,
+            nonce: account.nonce,
+            code_hash: account.code_hash,
+            staking_balance: account.staking_balance,
+            collateral_for_storage: account.collateral_for_storage,
+            accumulated_interest_return: account.accumulated_interest_return,
+            account_proof: Bytes::new(rlp::encode(&account_proof)),
+            storage_root,
+            storage_root_proof: Bytes::new(rlp::encode(&storage_root_proof)),
+            storage_proof,
+            state_root,
+            prev_snapshot_state_root,
+        })
+    }
+
+    /// The state root of `epoch` with the pivot block header committing to
+    /// it.
+    fn state_root_proof(
+        &self, ledger: &LedgerInfo, epoch: u64, state_root: StateRoot,
+    ) -> CoreResult<StateRootProof> {
+        let commitment = ledger.state_root_witness_of(epoch)?.map(|witness| {
+            StateRootCommitment {
+                block_hash: witness.header.hash(),
+                pos_reference: *witness.header.pos_reference(),
+                pos_finalized: witness.header.height()
+                    <= self.consensus.latest_finalized_epoch_number(),
+                witness: Bytes::new(rlp::encode(&witness)),
+            }
+        });
+        Ok(StateRootProof {
+            epoch_number: epoch.into(),
+            state_root,
+            commitment,
+        })
+    }
+
     fn send_usable_genesis_accounts(
         &self, account_start_index: usize,
     ) -> CoreResult<Bytes> {
@@ -1509,6 +1696,10 @@ impl RpcImpl {
         Ok(self.sync.current_sync_phase().name().into())
     }
 
+    fn get_sync_status(&self) -> CoreResult<RpcSyncStatus> {
+        Ok(RpcSyncStatus::new(&self.sync.sync_progress()))
+    }
+
     /// Return the pivot chain block hashes in `height_range` (inclusive) and
     /// their subtree weight. If it's none, return all pivot chain from
     /// `cur_era_genesis` to chain tip.
@@ -2107,6 +2298,116 @@ impl RpcImpl {
         self.get_transactions(&block, pivot, epoch_number)
     }
 
+    fn epoch_execution_witness(
+        &self, epoch: EpochNumber,
+    ) -> CoreResult<Option<Bytes>> {
+        let witness = self
+            .consensus_graph()
+            .get_epoch_execution_witness(epoch.into())?;
+        Ok(witness.map(|witness| Bytes::new(rlp::encode(&witness))))
+    }
+
+    fn verify_epoch_execution_witness(
+        &self, witness: Bytes,
+    ) -> CoreResult<EpochExecutionWitnessVerification> {
+        let witness =
+            Rlp::new(&witness.into_vec()).as_val().map_err(|err| {
+                invalid_params("witness", format!("Decode error: {:?}", err))
+            })?;
+        let commitment = self
+            .consensus_graph()
+            .verify_epoch_execution_witness(&witness)?;
+        Ok(commitment.into())
+    }
+
+    fn account_range(
+        &self, epoch: EpochNumber, start: Option<RpcAddress>,
+        max_results: Option<usize>, no_code: Option<bool>,
+    ) -> CoreResult<AccountRange> {
+        if let Some(start) = &start {
+            self.check_address_network(start.network)?;
+        }
+        let mut state = self
+            .consensus
+            .get_storage_state_by_epoch_number(epoch.into(), "epoch")?;
+        let range = account_range(
+            &mut state,
+            Space::Native,
+            &start.map_or(Address::zero(), |start| start.hex_address),
+            state_range_limit(max_results),
+            !no_code.unwrap_or(false),
+        )?;
+        Ok(AccountRange::try_from(
+            range,
+            *self.sync.network.get_network_type(),
+        )?)
+    }
+
+    fn storage_range_at(
+        &self, epoch: EpochNumber, address: RpcAddress,
+        key_start: Option<Bytes>, max_result: Option<usize>,
+    ) -> CoreResult<StorageRange> {
+        self.check_address_network(address.network)?;
+        let mut state = self
+            .consensus
+            .get_storage_state_by_epoch_number(epoch.into(), "epoch")?;
+        let range = storage_range(
+            &mut state,
+            &address.hex_address.with_native_space(),
+            &key_start.map_or(vec![], Bytes::into_vec),
+            state_range_limit(max_result),
+        )?;
+        Ok(StorageRange::try_from(
+            range,
+            *self.sync.network.get_network_type(),
+        )?)
+    }
+
+    fn export_snapshot_archive(
+        &self, file_name: String,
+    ) -> CoreBoxFuture<SnapshotArchiveSummary> {
+        // The export reads the whole snapshot, so it doesn't block the RPC
+        // worker.
+        let sync = self.sync.clone();
+        let (sender, receiver) = oneshot::channel();
+        let spawned = thread::Builder::new()
+            .name("export_snapshot_archive".into())
+            .spawn(move || {
+                sender.send(sync.export_snapshot_archive(&file_name)).ok();
+            });
+        let fut = async move {
+            spawned.map_err(|e| {
+                format!("Failed to export snapshot archive: {:?}", e)
+            })?;
+            let manifest = receiver
+                .await
+                .map_err(|_| "The snapshot archive export is interrupted")?
+                .map_err(|e| {
+                    format!("Failed to export snapshot archive: {}", e)
+                })?;
+            Ok((&manifest).into())
+        };
+        Box::new(fut.boxed().compat())
+    }
+
+    fn verify_snapshots(
+        &self, rebuild: Option<bool>,
+    ) -> CoreResult<Vec<SnapshotVerification>> {
+        let verifications = self
+            .consensus_graph()
+            .verify_snapshots(rebuild.unwrap_or(false))
+            .map_err(|e| format!("Failed to verify snapshots: {}", e))?;
+        Ok(verifications.iter().map(Into::into).collect())
+    }
+
+    fn create_checkpoint(&self, path: String) -> CoreResult<CheckpointSummary> {
+        let manifest = self
+            .consensus_graph()
+            .create_checkpoint(Path::new(&path))
+            .map_err(|e| format!("Failed to create checkpoint: {}", e))?;
+        Ok((&manifest).into())
+    }
+
     fn get_transactions(
         &self, blocks: &Vec<Arc<Block>>, pivot: &Arc<Block>, epoch_number: u64,
     ) -> JsonRpcResult<Vec<WrapTransaction>> {
@@ -2280,6 +2581,255 @@ impl RpcImpl {
     }
 }
 
+/// The most blocks mined after a snapshot that `dev_revert` abandons. It
+/// mines more empty blocks than it abandons, so reverting to an older
+/// snapshot is rejected instead of blocking the RPC for long.
+const MAX_DEV_REVERT_ABANDONED_BLOCKS: usize = 1000;
+
+/// The dev mode RPCs, shared by the Core Space and eSpace namespaces.
+///
+/// Every call that changes the chain generates blocks until its effect is
+/// visible in the latest state, like `dev_pack_tx_immediately` does for new
+/// transactions.
+impl RpcImpl {
+    fn check_dev_mode(&self) -> CoreResult<()> {
+        if !self.config.dev_mode {
+            let mut rpc_error = JsonRpcError::method_not_found();
+            rpc_error.message = "dev RPCs only allowed in dev mode.".into();
+            bail!(rpc_error)
+        }
+        Ok(())
+    }
+
+    /// Mines `DEFERRED_STATE_EPOCH_COUNT` blocks with the transactions in the
+    /// pool and returns the best block hash. If `timestamp` is set, it's the
+    /// timestamp of the first mined block.
+    pub fn dev_mine(&self, timestamp: Option<u64>) -> CoreResult<H256> {
+        self.check_dev_mode()?;
+        if let Some(timestamp) = timestamp {
+            self.block_gen.set_next_block_timestamp(timestamp);
+        }
+        self.generate_blocks_and_wait(DEFERRED_STATE_EPOCH_COUNT, 3000)
+    }
+
+    /// Moves the timestamps of the blocks mined later forward by `secs`, and
+    /// returns the total offset from the system time in seconds.
+    pub fn dev_increase_time(&self, secs: u64) -> CoreResult<u64> {
+        self.check_dev_mode()?;
+        Ok(self.block_gen.increase_time(secs))
+    }
+
+    pub fn dev_set_next_block_timestamp(
+        &self, timestamp: u64,
+    ) -> CoreResult<()> {
+        self.check_dev_mode()?;
+        self.block_gen.set_next_block_timestamp(timestamp);
+        Ok(())
+    }
+
+    /// Records the current best block, the dev mode clock, the impersonated
+    /// accounts and the transaction pool, and returns the snapshot id.
+    pub fn dev_snapshot(&self) -> CoreResult<u64> {
+        self.check_dev_mode()?;
+        let _dev_mining = self.block_gen.lock_dev_mining();
+        let mut dev_state = self.dev_state.lock();
+        let snapshot = DevSnapshot {
+            best_block: self.consensus.best_block_hash(),
+            dev_clock: self.block_gen.dev_clock(),
+            impersonated_accounts: dev_state.impersonated_accounts.clone(),
+            pool: self.tx_pool.content(None).1,
+        };
+        dev_state.snapshots.push(snapshot);
+        Ok(dev_state.snapshots.len() as u64 - 1)
+    }
+
+    /// Reverts the chain to the snapshot of `snapshot_id`, and removes the
+    /// snapshot and all the snapshots taken after it. Returns `false` if the
+    /// snapshot does not exist.
+    ///
+    /// Unlike `evm_revert` of Hardhat and Anvil, the block DAG is not rolled
+    /// back. The blocks mined after the snapshot are abandoned by mining a
+    /// longer chain of empty blocks on top of the snapshot block, so the
+    /// state becomes the same as when the snapshot was taken, but the epoch
+    /// and block numbers keep increasing. At most
+    /// `MAX_DEV_REVERT_ABANDONED_BLOCKS` blocks can be abandoned. The dev
+    /// mode clock, the impersonated accounts and the transaction pool are
+    /// restored to the snapshot. Block generation is paused while reverting.
+    pub fn dev_revert(&self, snapshot_id: u64) -> CoreResult<bool> {
+        self.check_dev_mode()?;
+        let _dev_mining = self.block_gen.lock_dev_mining();
+        let mut dev_state = self.dev_state.lock();
+        let snapshot_block = match dev_state.snapshots.get(snapshot_id as usize)
+        {
+            Some(snapshot) => snapshot.best_block,
+            None => return Ok(false),
+        };
+        let snapshot_epoch = self
+            .consensus
+            .get_block_epoch_number(&snapshot_block)
+            .ok_or("snapshot block is not on the pivot chain")?;
+
+        let mut abandoned = Vec::new();
+        for epoch in snapshot_epoch + 1..=self.consensus.best_epoch_number() {
+            abandoned.extend(self.consensus.get_block_hashes_by_epoch(
+                primitives::EpochNumber::Number(epoch),
+            )?);
+        }
+        // The tips that are not in any epoch yet.
+        abandoned.extend(
+            self.consensus
+                .best_info()
+                .bounded_terminal_block_hashes
+                .iter()
+                .filter(|hash| {
+                    self.consensus.get_block_epoch_number(hash).is_none()
+                })
+                .cloned(),
+        );
+        debug!(
+            "dev revert to {:?}, abandon {} blocks",
+            snapshot_block,
+            abandoned.len()
+        );
+        let abandoned_count = abandoned.len();
+        if abandoned_count > MAX_DEV_REVERT_ABANDONED_BLOCKS {
+            bail!(invalid_params(
+                "snapshot_id",
+                format!(
+                    "{} blocks are mined after the snapshot, more than the \
+                     {} blocks a revert can abandon",
+                    abandoned_count, MAX_DEV_REVERT_ABANDONED_BLOCKS
+                )
+            ));
+        }
+        self.block_gen.exclude_referees(abandoned);
+
+        // Each abandoned block can add to the weight of the old pivot chain,
+        // so one more block is enough unless there are heavy blocks.
+        let mut parent = snapshot_block;
+        let mut generated = 0;
+        while generated
+            < max(abandoned_count + 1, DEFERRED_STATE_EPOCH_COUNT as usize)
+            || self.consensus.best_block_hash() != parent
+        {
+            if generated
+                > 2 * abandoned_count + DEFERRED_STATE_EPOCH_COUNT as usize
+            {
+                bail!(internal_error_msg(
+                    "failed to switch the pivot chain to the snapshot"
+                ));
+            }
+            parent = self.block_gen.generate_custom_block_with_parent(
+                parent,
+                vec![], /* referee */
+                vec![], /* transactions */
+                false,  /* adaptive */
+                None,   /* maybe_custom */
+            )?;
+            generated += 1;
+        }
+
+        let snapshot = dev_state
+            .snapshots
+            .drain(snapshot_id as usize..)
+            .next()
+            .expect("snapshot exists");
+        self.block_gen.restore_dev_clock(snapshot.dev_clock);
+        dev_state.impersonated_accounts = snapshot.impersonated_accounts;
+        self.tx_pool.clear_tx_pool();
+        let (_, failures) =
+            self.tx_pool.insert_new_signed_transactions(snapshot.pool);
+        if !failures.is_empty() {
+            warn!(
+                "dev revert: {} transactions are not restored to the pool: \
+                 {:?}",
+                failures.len(),
+                failures
+            );
+        }
+        Ok(true)
+    }
+
+    /// Queues a direct change of an account and mines blocks until it is
+    /// visible in the latest state.
+    pub fn dev_set_state(&self, change: DevStateChange) -> CoreResult<()> {
+        self.check_dev_mode()?;
+        info!("RPC Request: dev set state {:?}", change);
+        // Apply the change after the current best block, so reverting to a
+        // snapshot taken before also reverts the change.
+        self.consensus_graph()
+            .dev_state_changes()
+            .push(change, self.consensus.best_epoch_number() + 1);
+        self.generate_blocks_and_wait(DEFERRED_STATE_EPOCH_COUNT, 0)?;
+        Ok(())
+    }
+
+    pub fn dev_impersonate_account(
+        &self, address: AddressWithSpace, impersonate: bool,
+    ) -> CoreResult<()> {
+        self.check_dev_mode()?;
+        let mut dev_state = self.dev_state.lock();
+        if impersonate {
+            dev_state.impersonated_accounts.insert(address);
+        } else {
+            dev_state.impersonated_accounts.remove(&address);
+        }
+        Ok(())
+    }
+
+    /// Packs a transaction of an impersonated account without a signature
+    /// and executes it.
+    ///
+    /// The transaction skips the pool and is packed directly in a new block.
+    /// Its sender cannot be recovered from the block in the database, so the
+    /// node cannot re-execute it after a restart.
+    pub fn dev_send_impersonated_transaction(
+        &self, tx: SignedTransaction,
+    ) -> CoreResult<H256> {
+        self.check_dev_mode()?;
+        if !self
+            .dev_state
+            .lock()
+            .impersonated_accounts
+            .contains(&tx.sender())
+        {
+            bail!(invalid_params(
+                "from",
+                format!("{:?} is not impersonated", tx.sender().address)
+            ));
+        }
+        let hash = tx.hash();
+        {
+            let _dev_mining = self.block_gen.lock_dev_mining();
+            let generated = self.block_gen.generate_custom_block(
+                vec![Arc::new(tx)],
+                None, /* adaptive */
+            );
+            self.wait_for_best_block(&generated);
+        }
+        self.generate_blocks_and_wait(DEFERRED_STATE_EPOCH_COUNT - 1, 0)?;
+        Ok(hash)
+    }
+
+    fn dev_send_transaction(
+        &self, mut tx: TransactionRequest,
+    ) -> CoreResult<H256> {
+        info!("RPC Request: dev_sendTransaction, tx = {:?}", tx);
+        tx.check_rpc_address_network(
+            "tx",
+            self.sync.network.get_network_type(),
+        )?;
+        self.fill_transaction_request(&mut tx)?;
+        let consensus_graph = self.consensus_graph();
+        let signed_tx = tx.clone().sign_call(
+            consensus_graph.best_epoch_number(),
+            consensus_graph.best_chain_id().in_native_space(),
+            tx.gas,
+        )?;
+        self.dev_send_impersonated_transaction(signed_tx)
+    }
+}
+
 #[allow(dead_code)]
 pub struct CfxHandler {
     common: Arc<CommonImpl>,
@@ -2353,10 +2903,12 @@ impl Cfx for CfxHandler {
             fn transaction_by_hash(&self, hash: H256) -> BoxFuture<Option<RpcTransaction>>;
             fn transaction_receipt(&self, tx_hash: H256) -> BoxFuture<Option<RpcReceipt>>;
             fn storage_root(&self, address: RpcAddress, epoch_num: Option<EpochNumber>) -> BoxFuture<Option<StorageRoot>>;
+            fn get_proof(&self, address: RpcAddress, storage_keys: Vec<U256>, epoch_num: Option<EpochNumber>) -> BoxFuture<AccountProof>;
             fn get_supply_info(&self, epoch_num: Option<EpochNumber>) -> JsonRpcResult<TokenSupplyInfo>;
             fn get_collateral_info(&self, epoch_num: Option<EpochNumber>) -> JsonRpcResult<StorageCollateralInfo>;
             fn get_vote_params(&self, epoch_num: Option<EpochNumber>) -> JsonRpcResult<VoteParamsInfo>;
             fn get_fee_burnt(&self, epoch_num: Option<EpochNumber>) -> JsonRpcResult<U256>;
+            fn get_sync_status(&self) -> JsonRpcResult<RpcSyncStatus>;
         }
     }
 }
@@ -2481,6 +3033,114 @@ impl LocalRpc for LocalRpcImpl {
             fn sign_transaction(&self, tx: TransactionRequest, password: Option<String>) -> JsonRpcResult<String>;
             fn transactions_by_epoch(&self, epoch_number: U64) -> JsonRpcResult<Vec<WrapTransaction>>;
             fn transactions_by_block(&self, block_hash: H256) -> JsonRpcResult<Vec<WrapTransaction>>;
+            fn epoch_execution_witness(&self, epoch: EpochNumber) -> JsonRpcResult<Option<Bytes>>;
+            fn verify_epoch_execution_witness(&self, witness: Bytes) -> JsonRpcResult<EpochExecutionWitnessVerification>;
+            fn account_range(&self, epoch: EpochNumber, start: Option<RpcAddress>, max_results: Option<usize>, no_code: Option<bool>) -> JsonRpcResult<AccountRange>;
+            fn storage_range_at(&self, epoch: EpochNumber, address: RpcAddress, key_start: Option<Bytes>, max_result: Option<usize>) -> JsonRpcResult<StorageRange>;
+            fn export_snapshot_archive(&self, file_name: String) -> BoxFuture<SnapshotArchiveSummary>;
+            fn verify_snapshots(&self, rebuild: Option<bool>) -> JsonRpcResult<Vec<SnapshotVerification>>;
+            fn create_checkpoint(&self, path: String) -> JsonRpcResult<CheckpointSummary>;
         }
     }
 }
+
+pub struct DevRpcImpl {
+    rpc_impl: Arc<RpcImpl>,
+}
+
+impl DevRpcImpl {
+    pub fn new(rpc_impl: Arc<RpcImpl>) -> Self { DevRpcImpl { rpc_impl } }
+
+    fn set_state(
+        &self, address: RpcAddress,
+        change: impl FnOnce(AddressWithSpace) -> DevStateChange,
+    ) -> CoreResult<()> {
+        self.rpc_impl.check_address_network(address.network)?;
+        self.rpc_impl
+            .dev_set_state(change(address.hex_address.with_native_space()))
+    }
+
+    fn impersonate(
+        &self, address: RpcAddress, impersonate: bool,
+    ) -> CoreResult<()> {
+        self.rpc_impl.check_address_network(address.network)?;
+        self.rpc_impl.dev_impersonate_account(
+            address.hex_address.with_native_space(),
+            impersonate,
+        )
+    }
+}
+
+impl DevRpc for DevRpcImpl {
+    fn mine(&self, timestamp: Option<HexU64>) -> JsonRpcResult<H256> {
+        into_jsonrpc_result(
+            self.rpc_impl.dev_mine(timestamp.map(|t| t.as_u64())),
+        )
+    }
+
+    fn increase_time(&self, seconds: HexU64) -> JsonRpcResult<U64> {
+        into_jsonrpc_result(
+            self.rpc_impl
+                .dev_increase_time(seconds.as_u64())
+                .map(U64::from),
+        )
+    }
+
+    fn set_next_block_timestamp(&self, timestamp: HexU64) -> JsonRpcResult<()> {
+        into_jsonrpc_result(
+            self.rpc_impl
+                .dev_set_next_block_timestamp(timestamp.as_u64()),
+        )
+    }
+
+    fn snapshot(&self) -> JsonRpcResult<U64> {
+        into_jsonrpc_result(self.rpc_impl.dev_snapshot().map(U64::from))
+    }
+
+    fn revert(&self, snapshot_id: HexU64) -> JsonRpcResult<bool> {
+        into_jsonrpc_result(self.rpc_impl.dev_revert(snapshot_id.as_u64()))
+    }
+
+    fn set_balance(
+        &self, address: RpcAddress, balance: U256,
+    ) -> JsonRpcResult<()> {
+        into_jsonrpc_result(self.set_state(address, |address| {
+            DevStateChange::Balance { address, balance }
+        }))
+    }
+
+    fn set_code(&self, address: RpcAddress, code: Bytes) -> JsonRpcResult<()> {
+        into_jsonrpc_result(self.set_state(address, |address| {
+            DevStateChange::Code {
+                address,
+                code: code.into_vec(),
+            }
+        }))
+    }
+
+    fn set_storage_at(
+        &self, address: RpcAddress, position: U256, value: H256,
+    ) -> JsonRpcResult<()> {
+        into_jsonrpc_result(self.set_state(address, |address| {
+            DevStateChange::Storage {
+                address,
+                key: H256::from_uint(&position),
+                value: value.into_uint(),
+            }
+        }))
+    }
+
+    fn impersonate_account(&self, address: RpcAddress) -> JsonRpcResult<()> {
+        into_jsonrpc_result(self.impersonate(address, true))
+    }
+
+    fn stop_impersonating_account(
+        &self, address: RpcAddress,
+    ) -> JsonRpcResult<()> {
+        into_jsonrpc_result(self.impersonate(address, false))
+    }
+
+    fn send_transaction(&self, tx: TransactionRequest) -> JsonRpcResult<H256> {
+        into_jsonrpc_result(self.rpc_impl.dev_send_transaction(tx))
+    }
+}
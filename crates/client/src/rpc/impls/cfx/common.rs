@@ -24,13 +24,11 @@ use crate::rpc::{
     CoreResult,
 };
 
-use bigdecimal::BigDecimal;
 use clap::crate_version;
 use jsonrpc_core::{
     Error as RpcError, Result as JsonRpcResult, Value as RpcValue,
 };
 use keccak_hash::keccak;
-use num_bigint::{BigInt, ToBigInt};
 use parking_lot::{Condvar, Mutex};
 
 use crate::rpc::types::pos::{Block as RpcPosBlock, Decision};
@@ -38,6 +36,7 @@ use cfx_addr::Network;
 use cfx_parameters::{
     rpc::GAS_PRICE_DEFAULT_VALUE, staking::DRIPS_PER_STORAGE_COLLATERAL_UNIT,
 };
+use cfx_rpc::helpers::scaled_confirmation_risk;
 use cfx_types::{
     Address, AddressSpaceUtil, Space, H160, H256, H520, U128, U256, U512, U64,
 };
@@ -344,22 +343,7 @@ impl RpcImpl {
         let result = consensus_graph
             .confirmation_meter
             .confirmation_risk_by_hash(inner, block_hash.into());
-        if result.is_none() {
-            Ok(None)
-        } else {
-            let risk: BigDecimal = result.unwrap().into();
-            let scale = BigInt::parse_bytes(b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF", 16).expect("failed to unwrap U256::max into bigInt");
-
-            //TODO: there's a precision problem here, it should be fine under a
-            // (2^256 - 1) scale
-            let scaled_risk: BigInt = (risk * scale)
-                .to_bigint()
-                .expect("failed to convert scaled risk to bigInt");
-            let (sign, big_endian_bytes) = scaled_risk.to_bytes_be();
-            assert_ne!(sign, num_bigint::Sign::Minus);
-            let rpc_result = U256::from(big_endian_bytes.as_slice());
-            Ok(Some(rpc_result.into()))
-        }
+        Ok(result.map(scaled_confirmation_risk))
     }
 
     pub fn block_by_hash(
@@ -865,6 +849,21 @@ impl RpcImpl {
             latest_state,
             network_id: self.network.network_id().into(),
             pending_tx_number: tx_count.into(),
+            pruning_mode: self.data_man.pruning_mode().to_string(),
+            earliest_state: self.data_man.earliest_epoch_with_state().into(),
+            earliest_block_body: self
+                .data_man
+                .earliest_epoch_with_block_body()
+                .into(),
+            earliest_receipt: self
+                .data_man
+                .earliest_epoch_with_execution_result()
+                .into(),
+            earliest_trace: self.data_man.earliest_epoch_with_trace().into(),
+            earliest_transaction_index: self
+                .data_man
+                .earliest_epoch_with_transaction_index()
+                .into(),
         })
     }
 
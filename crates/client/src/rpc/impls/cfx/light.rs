@@ -40,15 +40,19 @@ use crate::{
             cfx::check_rpc_address_network,
             pos::{Block as PosBlock, PoSEpochReward},
             Account as RpcAccount, AccountPendingInfo,
-            AccountPendingTransactions, BlameInfo, Block as RpcBlock,
-            BlockHashOrEpochNumber, Bytes, CfxFeeHistory, CfxRpcLogFilter,
-            CheckBalanceAgainstTransactionResponse, ConsensusGraphStates,
-            EpochNumber, EstimateGasAndCollateralResponse, FeeHistory,
-            Log as RpcLog, PoSEconomics, Receipt as RpcReceipt,
-            RewardInfo as RpcRewardInfo, RpcAddress, SponsorInfo,
-            StatOnGasLoad, Status as RpcStatus, StorageCollateralInfo,
-            SyncGraphStates, TokenSupplyInfo, Transaction as RpcTransaction,
-            TransactionRequest, VoteParamsInfo, WrapTransaction, U64 as HexU64,
+            AccountPendingTransactions, AccountProof, AccountRange, BlameInfo,
+            Block as RpcBlock, BlockHashOrEpochNumber, Bytes, CfxFeeHistory,
+            CfxRpcLogFilter, CheckBalanceAgainstTransactionResponse,
+            CheckpointSummary, ConsensusGraphStates,
+            EpochExecutionWitnessVerification, EpochNumber,
+            EstimateGasAndCollateralResponse, FeeHistory, Log as RpcLog,
+            PoSEconomics, Receipt as RpcReceipt, RewardInfo as RpcRewardInfo,
+            RpcAddress, SnapshotArchiveSummary, SnapshotVerification,
+            SponsorInfo, StatOnGasLoad, Status as RpcStatus,
+            StorageCollateralInfo, StorageRange, SyncGraphStates,
+            SyncStatus as RpcSyncStatus, TokenSupplyInfo,
+            Transaction as RpcTransaction, TransactionRequest, VoteParamsInfo,
+            WrapTransaction, U64 as HexU64,
         },
         CoreBoxFuture, CoreResult,
     },
@@ -1267,6 +1271,8 @@ impl Cfx for CfxHandler {
         fn get_pos_reward_by_epoch(&self, epoch: EpochNumber) -> JsonRpcResult<Option<PoSEpochReward>>;
         fn get_fee_burnt(&self, epoch: Option<EpochNumber>) -> JsonRpcResult<U256>;
         fn max_priority_fee_per_gas(&self) -> BoxFuture<U256>;
+        fn get_sync_status(&self) -> JsonRpcResult<RpcSyncStatus>;
+        fn get_proof(&self, address: RpcAddress, storage_keys: Vec<U256>, epoch_num: Option<EpochNumber>) -> BoxFuture<AccountProof>;
     }
 }
 
@@ -1378,5 +1384,12 @@ impl LocalRpc for DebugRpcImpl {
         fn sync_graph_state(&self) -> JsonRpcResult<SyncGraphStates>;
         fn transactions_by_epoch(&self, epoch_number: U64) -> JsonRpcResult<Vec<WrapTransaction>>;
         fn transactions_by_block(&self, block_hash: H256) -> JsonRpcResult<Vec<WrapTransaction>>;
+        fn epoch_execution_witness(&self, epoch: EpochNumber) -> JsonRpcResult<Option<Bytes>>;
+        fn verify_epoch_execution_witness(&self, witness: Bytes) -> JsonRpcResult<EpochExecutionWitnessVerification>;
+        fn account_range(&self, epoch: EpochNumber, start: Option<RpcAddress>, max_results: Option<usize>, no_code: Option<bool>) -> JsonRpcResult<AccountRange>;
+        fn storage_range_at(&self, epoch: EpochNumber, address: RpcAddress, key_start: Option<Bytes>, max_result: Option<usize>) -> JsonRpcResult<StorageRange>;
+        fn export_snapshot_archive(&self, file_name: String) -> BoxFuture<SnapshotArchiveSummary>;
+        fn verify_snapshots(&self, rebuild: Option<bool>) -> JsonRpcResult<Vec<SnapshotVerification>>;
+        fn create_checkpoint(&self, path: String) -> JsonRpcResult<CheckpointSummary>;
     }
 }
@@ -6,5 +6,7 @@ pub mod pool;
 pub mod pubsub;
 mod trace;
 
-pub use cfx_handler::{CfxHandler, LocalRpcImpl, RpcImpl, TestRpcImpl};
+pub use cfx_handler::{
+    CfxHandler, DevRpcImpl, LocalRpcImpl, RpcImpl, TestRpcImpl,
+};
 pub use trace::TraceHandler;
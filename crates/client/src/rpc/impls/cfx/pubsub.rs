@@ -4,12 +4,12 @@
 
 use crate::rpc::{
     errors,
-    helpers::{EpochQueue, SubscriberId, Subscribers},
+    helpers::{EpochQueue, ReorgTracker, Reverted, SubscriberId, Subscribers},
     metadata::Metadata,
     traits::pubsub::PubSub,
     types::{
         pubsub::{self, SubscriptionEpoch},
-        Header as RpcHeader, Log as RpcLog,
+        Header as RpcHeader, Log as RpcLog, SyncStatus as RpcSyncStatus,
     },
 };
 use cfx_addr::Network;
@@ -19,7 +19,8 @@ use cfx_parameters::{
 };
 use cfx_types::{Space, H256};
 use cfxcore::{
-    channel::Channel, BlockDataManager, Notifications, SharedConsensusGraph,
+    channel::Channel, client::PivotChainReorg, BlockDataManager, Notifications,
+    SharedConsensusGraph, SharedSynchronizationService,
 };
 use futures::{
     compat::Future01CompatExt,
@@ -51,7 +52,10 @@ pub struct PubSubClient {
     heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
     epochs_subscribers: Arc<RwLock<Subscribers<Client>>>,
     logs_subscribers: Arc<RwLock<Subscribers<(Client, LogFilter)>>>,
+    syncing_subscribers: Arc<RwLock<Subscribers<Client>>>,
     epochs_ordered: Arc<Channel<(u64, Vec<H256>)>>,
+    pivot_chain_reorgs: Arc<Channel<PivotChainReorg>>,
+    sync: Arc<RwLock<Option<SharedSynchronizationService>>>,
 }
 
 impl PubSubClient {
@@ -91,7 +95,10 @@ impl PubSubClient {
             heads_subscribers,
             epochs_subscribers,
             logs_subscribers,
+            syncing_subscribers: Default::default(),
             epochs_ordered: notifications.epochs_ordered.clone(),
+            pivot_chain_reorgs: notifications.pivot_chain_reorgs.clone(),
+            sync: Default::default(),
         }
     }
 
@@ -100,6 +107,12 @@ impl PubSubClient {
         Arc::downgrade(&self.handler)
     }
 
+    /// Enables the `syncing` subscription. The sync service is created after
+    /// the pubsub client and does not exist on light nodes.
+    pub fn set_sync_service(&self, sync: SharedSynchronizationService) {
+        *self.sync.write() = Some(sync);
+    }
+
     // Start an async loop that continuously receives epoch notifications and
     // publishes the corresponding epochs to subscriber `id`, keeping their
     // original order. The loop terminates when subscriber `id` unsubscribes.
@@ -162,17 +175,22 @@ impl PubSubClient {
 
     // Start an async loop that continuously receives epoch notifications and
     // publishes the corresponding logs to subscriber `id`, keeping their
-    // original order. The loop terminates when subscriber `id` unsubscribes.
+    // original order. On pivot chain reorgs, the logs of the reverted epochs
+    // are published again as removed, the latest first. The loop terminates
+    // when subscriber `id` unsubscribes.
     fn start_logs_loop(&self, id: SubscriberId) {
         trace!("start_logs_loop({:?})", id);
 
         // clone everything we use in our async loop
         let subscribers = self.logs_subscribers.clone();
         let epochs_ordered = self.epochs_ordered.clone();
+        let pivot_chain_reorgs = self.pivot_chain_reorgs.clone();
         let handler = self.handler.clone();
 
-        // subscribe to the `epochs_ordered` channel
+        // subscribe to the `epochs_ordered` and `pivot_chain_reorgs` channels.
+        // a reorg is always sent before the epochs of the new pivot chain.
         let mut receiver = epochs_ordered.subscribe();
+        let mut reorg_receiver = pivot_chain_reorgs.subscribe();
 
         // use a queue to make sure we only process an epoch once it has been
         // executed for sure
@@ -183,6 +201,7 @@ impl PubSubClient {
         // loop asynchronously
         let fut = async move {
             let mut last_epoch = 0;
+            let mut tracker = ReorgTracker::default();
 
             while let Some(epoch) = receiver.recv().await {
                 trace!("logs_loop({:?}): {:?}", id, epoch);
@@ -193,10 +212,15 @@ impl PubSubClient {
                     None => {
                         // unsubscribed, terminate loop
                         epochs_ordered.unsubscribe(receiver.id);
+                        pivot_chain_reorgs.unsubscribe(reorg_receiver.id);
                         return;
                     }
                 };
 
+                while let Ok(reorg) = reorg_receiver.try_recv() {
+                    tracker.on_pivot_chain_reorg(reorg);
+                }
+
                 let epoch = match queue.push(epoch) {
                     None => continue,
                     Some(e) => e,
@@ -206,13 +230,89 @@ impl PubSubClient {
                 if epoch.0 <= last_epoch {
                     debug!("pivot chain reorg: {} -> {}", last_epoch, epoch.0);
                     assert!(epoch.0 > 0, "Unexpected epoch number received.");
-                    handler.notify_revert(&sub, epoch.0 - 1).await;
+                    let reverted = tracker.revert(epoch.0);
+                    handler.notify_revert(&sub, epoch.0 - 1, reverted).await;
                 }
 
                 last_epoch = epoch.0;
+                tracker
+                    .prune(handler.consensus.latest_finalized_epoch_number());
 
                 // publish matching logs
-                handler.notify_logs(&sub, filter, epoch).await;
+                let pivot = *epoch.1.last().expect("empty epoch in pubsub");
+                let logs =
+                    handler.notify_logs(&sub, filter, epoch.clone()).await;
+                tracker.push(epoch.0, pivot, logs);
+            }
+        };
+
+        // run futures@0.3 future on tokio@0.1 executor
+        let fut = fut.unit_error().boxed().compat();
+        self.handler.executor.spawn(fut);
+    }
+
+    // Start an async loop that publishes the sync progress to subscriber `id`
+    // whenever the sync phase or the best epoch changes. The loop terminates
+    // when subscriber `id` unsubscribes.
+    fn start_syncing_loop(
+        &self, id: SubscriberId, sync: SharedSynchronizationService,
+    ) {
+        trace!("start_syncing_loop({:?})", id);
+
+        // clone everything we use in our async loop
+        let subscribers = self.syncing_subscribers.clone();
+        let epochs_ordered = self.epochs_ordered.clone();
+        let phase_changes = sync.sync_phase_changes();
+
+        // subscribe to the `epochs_ordered` and sync phase channels
+        let mut receiver = epochs_ordered.subscribe();
+        let mut phase_receiver = phase_changes.subscribe();
+
+        // loop asynchronously
+        let fut = async move {
+            let mut last_status: Option<RpcSyncStatus> = None;
+
+            loop {
+                // retrieve subscriber
+                let sub = match subscribers.read().get(&id) {
+                    Some(sub) => sub.clone(),
+                    None => {
+                        // unsubscribed, terminate loop
+                        epochs_ordered.unsubscribe(receiver.id);
+                        phase_changes.unsubscribe(phase_receiver.id);
+                        return;
+                    }
+                };
+
+                let status = RpcSyncStatus::new(&sync.sync_progress());
+                let publish = match &last_status {
+                    None => true,
+                    Some(last) => {
+                        (status.catch_up_mode || last.catch_up_mode)
+                            && status != *last
+                    }
+                };
+
+                if publish {
+                    ChainNotificationHandler::notify_async(
+                        &sub,
+                        pubsub::Result::Syncing(status.clone()),
+                    )
+                    .await;
+                    last_status = Some(status);
+                }
+
+                // wait for the next change, and skip the changes that are
+                // already outdated
+                let changed = tokio::select! {
+                    epoch = receiver.recv() => epoch.is_some(),
+                    phase = phase_receiver.recv() => phase.is_some(),
+                };
+                if !changed {
+                    return;
+                }
+                while receiver.try_recv().is_ok() {}
+                while phase_receiver.try_recv().is_ok() {}
             }
         };
 
@@ -307,21 +407,37 @@ impl ChainNotificationHandler {
         .await
     }
 
-    async fn notify_revert(&self, subscriber: &Client, epoch: u64) {
+    async fn notify_revert(
+        &self, subscriber: &Client, epoch: u64,
+        reverted: Option<Reverted<RpcLog>>,
+    ) {
         trace!("notify_revert({:?})", epoch);
 
+        let (old_pivots, new_pivots, removed_logs) = match reverted {
+            Some(r) => (r.old_pivots, r.new_pivots, r.items),
+            None => Default::default(),
+        };
+
         Self::notify_async(
             subscriber,
             pubsub::Result::ChainReorg {
                 revert_to: epoch.into(),
+                old_pivots,
+                new_pivots,
             },
         )
-        .await
+        .await;
+
+        for mut log in removed_logs {
+            log.removed = true;
+            Self::notify_async(subscriber, pubsub::Result::Log(log)).await;
+        }
     }
 
+    // publish the logs of `epoch` matching `filter` and return them
     async fn notify_logs(
         &self, subscriber: &Client, filter: LogFilter, epoch: (u64, Vec<H256>),
-    ) {
+    ) -> Vec<RpcLog> {
         trace!("notify_logs({:?})", epoch);
 
         // NOTE: calls to DbManager are supposed to be cached
@@ -329,7 +445,7 @@ impl ChainNotificationHandler {
         // subscriber? would it be better to do this once for each epoch?
         let logs = match self.retrieve_epoch_logs(epoch).await {
             Some(logs) => logs,
-            None => return,
+            None => return vec![],
         };
 
         // apply filter to logs
@@ -342,10 +458,16 @@ impl ChainNotificationHandler {
         // send logs in order
         // FIXME(thegaram): Sink::notify flushes after each item.
         // consider sending them in a batch.
+        let mut sent = vec![];
         for log in logs {
             match log {
                 Ok(l) => {
-                    Self::notify_async(subscriber, pubsub::Result::Log(l)).await
+                    Self::notify_async(
+                        subscriber,
+                        pubsub::Result::Log(l.clone()),
+                    )
+                    .await;
+                    sent.push(l);
                 }
                 Err(e) => {
                     error!(
@@ -355,6 +477,7 @@ impl ChainNotificationHandler {
                 }
             }
         }
+        sent
     }
 
     // attempt to retrieve block receipts from BlockDataManager
@@ -539,6 +662,18 @@ impl PubSub for PubSubClient {
             (pubsub::Kind::Logs, _) => {
                 errors::invalid_params("logs", "Expected filter parameter.")
             }
+            // --------- syncing ---------
+            (pubsub::Kind::Syncing, None) => match self.sync.read().clone() {
+                Some(sync) => {
+                    let id = self.syncing_subscribers.write().push(subscriber);
+                    self.start_syncing_loop(id, sync);
+                    return;
+                }
+                None => errors::unimplemented(None),
+            },
+            (pubsub::Kind::Syncing, _) => {
+                errors::invalid_params("syncing", "Expected no parameters.")
+            }
             _ => errors::unimplemented(None),
         };
 
@@ -551,7 +686,8 @@ impl PubSub for PubSubClient {
         let res0 = self.heads_subscribers.write().remove(&id).is_some();
         let res1 = self.epochs_subscribers.write().remove(&id).is_some();
         let res2 = self.logs_subscribers.write().remove(&id).is_some();
+        let res3 = self.syncing_subscribers.write().remove(&id).is_some();
 
-        Ok(res0 || res1 || res2)
+        Ok(res0 || res1 || res2 || res3)
     }
 }
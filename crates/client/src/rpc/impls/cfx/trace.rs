@@ -15,8 +15,12 @@ use crate::{
         CoreResult,
     },
 };
+use alloy_rpc_types_trace::parity::{
+    TraceResults, TraceResultsWithTransactionHash, TraceType,
+};
 use cfx_addr::Network;
 use cfx_execute_helper::exec_tracer::TraceFilter as PrimitiveTraceFilter;
+use cfx_rpc::TraceApi;
 use cfx_types::{Space, H256};
 use cfxcore::{
     block_data_manager::DataVersionTuple, BlockDataManager, ConsensusGraph,
@@ -24,7 +28,7 @@ use cfxcore::{
 };
 use jsonrpc_core::{Error as JsonRpcError, Result as JsonRpcResult};
 use primitives::EpochNumber;
-use std::{convert::TryInto, sync::Arc};
+use std::{collections::HashSet, convert::TryInto, sync::Arc};
 
 #[derive(Clone)]
 pub struct TraceHandler {
@@ -45,6 +49,11 @@ impl TraceHandler {
         }
     }
 
+    /// The replays share the epoch re-execution of the eSpace trace API.
+    fn replay_handler(&self) -> TraceApi {
+        TraceApi::new(self.consensus.clone(), None)
+    }
+
     pub(crate) fn consensus_graph(&self) -> &ConsensusGraph {
         self.consensus
             .as_any()
@@ -280,4 +289,24 @@ impl Trace for TraceHandler {
     fn epoch_traces(&self, epoch: RpcEpochNumber) -> JsonRpcResult<EpochTrace> {
         into_jsonrpc_result(self.epoch_trace_impl(epoch.into_primitive()))
     }
+
+    fn replay_transaction(
+        &self, tx_hash: H256, trace_types: HashSet<TraceType>,
+    ) -> JsonRpcResult<TraceResults> {
+        self.replay_handler()
+            .replay_transaction(tx_hash, trace_types, Space::Native)
+            .map_err(|err| err.into())
+    }
+
+    fn replay_epoch_transactions(
+        &self, epoch: RpcEpochNumber, trace_types: HashSet<TraceType>,
+    ) -> JsonRpcResult<Vec<TraceResultsWithTransactionHash>> {
+        let epoch_num = self
+            .consensus_graph()
+            .get_height_from_epoch_number(epoch.into_primitive())
+            .map_err(JsonRpcError::invalid_params)?;
+        self.replay_handler()
+            .replay_epoch_transactions(epoch_num, trace_types, Space::Native)
+            .map_err(|err| err.into())
+    }
 }
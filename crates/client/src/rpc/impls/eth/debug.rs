@@ -1,14 +1,14 @@
 use crate::rpc::{
     errors::invalid_params_msg,
     traits::eth_space::debug::Debug,
-    types::eth::{BlockNumber, TransactionRequest},
+    types::eth::{AccountRange, BlockNumber, StorageRange, TransactionRequest},
 };
 use alloy_rpc_types_trace::geth::{
     GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace,
     TraceResult,
 };
 use cfx_rpc::DebugApi;
-use cfx_types::{H256, U256};
+use cfx_types::{H160, H256, U256};
 use cfxcore::{ConsensusGraph, SharedConsensusGraph};
 use jsonrpc_core::Result as JsonRpcResult;
 
@@ -77,4 +77,22 @@ impl Debug for GethDebugHandler {
             .trace_call(request, block_number, opts)
             .map_err(|err| err.into())
     }
+
+    fn debug_account_range(
+        &self, block: BlockNumber, start: Option<H160>,
+        max_results: Option<usize>, no_code: Option<bool>,
+    ) -> JsonRpcResult<AccountRange> {
+        self.inner
+            .account_range(block, start, max_results, no_code)
+            .map_err(|err| err.into())
+    }
+
+    fn debug_storage_range_at(
+        &self, block: BlockNumber, address: H160, key_start: Option<H256>,
+        max_result: Option<usize>,
+    ) -> JsonRpcResult<StorageRange> {
+        self.inner
+            .storage_range_at(block, address, key_start, max_result)
+            .map_err(|err| err.into())
+    }
 }
@@ -0,0 +1,148 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use crate::{
+    common::delegate_convert::into_jsonrpc_result,
+    rpc::{
+        impls::cfx::RpcImpl,
+        traits::eth_space::dev::EthDev,
+        types::{eth::TransactionRequest, Bytes, U64 as VariadicU64},
+    },
+};
+use cfx_executor::state::DevStateChange;
+use cfx_rpc::EthApi;
+use cfx_types::{AddressSpaceUtil, BigEndianHash, H160, H256, U256, U64};
+use jsonrpc_core::Result as JsonRpcResult;
+use std::sync::Arc;
+
+/// The eSpace namespace of the dev mode RPCs. The addresses are eSpace
+/// addresses, and the chain is controlled in the same way as `DevRpcImpl`.
+pub struct EthDevHandler {
+    inner: EthApi,
+    rpc_impl: Arc<RpcImpl>,
+}
+
+impl EthDevHandler {
+    pub fn new(rpc_impl: Arc<RpcImpl>) -> Self {
+        EthDevHandler {
+            inner: EthApi::new(
+                rpc_impl.config.clone(),
+                rpc_impl.consensus.clone(),
+                rpc_impl.sync.clone(),
+                rpc_impl.tx_pool.clone(),
+            ),
+            rpc_impl,
+        }
+    }
+}
+
+impl EthDev for EthDevHandler {
+    fn evm_mine(
+        &self, timestamp: Option<VariadicU64>,
+    ) -> JsonRpcResult<String> {
+        debug!("RPC Request: evm_mine({:?})", timestamp);
+        into_jsonrpc_result(
+            self.rpc_impl.dev_mine(timestamp.map(|t| t.as_u64())),
+        )?;
+        Ok("0x0".into())
+    }
+
+    fn evm_increase_time(&self, seconds: VariadicU64) -> JsonRpcResult<U64> {
+        debug!("RPC Request: evm_increaseTime({})", seconds);
+        into_jsonrpc_result(
+            self.rpc_impl
+                .dev_increase_time(seconds.as_u64())
+                .map(U64::from),
+        )
+    }
+
+    fn evm_set_next_block_timestamp(
+        &self, timestamp: VariadicU64,
+    ) -> JsonRpcResult<()> {
+        debug!("RPC Request: evm_setNextBlockTimestamp({})", timestamp);
+        into_jsonrpc_result(
+            self.rpc_impl
+                .dev_set_next_block_timestamp(timestamp.as_u64()),
+        )
+    }
+
+    fn evm_snapshot(&self) -> JsonRpcResult<U64> {
+        debug!("RPC Request: evm_snapshot()");
+        into_jsonrpc_result(self.rpc_impl.dev_snapshot().map(U64::from))
+    }
+
+    fn evm_revert(&self, snapshot_id: VariadicU64) -> JsonRpcResult<bool> {
+        debug!("RPC Request: evm_revert({})", snapshot_id);
+        into_jsonrpc_result(self.rpc_impl.dev_revert(snapshot_id.as_u64()))
+    }
+
+    fn set_balance(&self, address: H160, balance: U256) -> JsonRpcResult<()> {
+        into_jsonrpc_result(self.rpc_impl.dev_set_state(
+            DevStateChange::Balance {
+                address: address.with_evm_space(),
+                balance,
+            },
+        ))
+    }
+
+    fn set_code(&self, address: H160, code: Bytes) -> JsonRpcResult<()> {
+        into_jsonrpc_result(self.rpc_impl.dev_set_state(DevStateChange::Code {
+            address: address.with_evm_space(),
+            code: code.into_vec(),
+        }))
+    }
+
+    fn set_storage_at(
+        &self, address: H160, position: U256, value: H256,
+    ) -> JsonRpcResult<bool> {
+        into_jsonrpc_result(self.rpc_impl.dev_set_state(
+            DevStateChange::Storage {
+                address: address.with_evm_space(),
+                key: H256::from_uint(&position),
+                value: value.into_uint(),
+            },
+        ))?;
+        Ok(true)
+    }
+
+    fn impersonate_account(&self, address: H160) -> JsonRpcResult<()> {
+        into_jsonrpc_result(
+            self.rpc_impl
+                .dev_impersonate_account(address.with_evm_space(), true),
+        )
+    }
+
+    fn stop_impersonating_account(&self, address: H160) -> JsonRpcResult<()> {
+        into_jsonrpc_result(
+            self.rpc_impl
+                .dev_impersonate_account(address.with_evm_space(), false),
+        )
+    }
+
+    fn send_transaction(
+        &self, mut tx: TransactionRequest,
+    ) -> JsonRpcResult<H256> {
+        debug!("RPC Request: eth_sendTransaction({:?})", tx);
+        let from = tx.from.unwrap_or_default();
+        if tx.nonce.is_none() {
+            tx.nonce = Some(self.inner.next_nonce(from, None)?);
+        }
+        if !tx.has_gas_price() {
+            tx.gas_price = Some(self.inner.gas_price());
+        }
+        let gas = match tx.gas {
+            Some(gas) => gas,
+            None => {
+                let (_, estimated_gas) =
+                    self.inner.exec_transaction(tx.clone(), None)?;
+                estimated_gas
+            }
+        };
+        tx.gas = Some(gas);
+        let signed_tx = tx.sign_call(self.inner.chain_id(), Some(gas))?;
+        into_jsonrpc_result(
+            self.rpc_impl.dev_send_impersonated_transaction(signed_tx),
+        )
+    }
+}
@@ -9,8 +9,8 @@ use crate::rpc::{
     types::{
         eth::{
             AccountPendingTransactions, Block as RpcBlock, BlockNumber,
-            EthRpcLogFilter, Log, Receipt, SyncStatus, Transaction,
-            TransactionRequest,
+            EthRpcLogFilter, FinalityStatus, FinalityTarget, Log, Receipt,
+            SyncStatus, Transaction, TransactionRequest,
         },
         Bytes, FeeHistory, Index, U64 as HexU64,
     },
@@ -431,4 +431,28 @@ impl Eth for EthHandler {
             pending_count: pending_count.into(),
         })
     }
+
+    fn block_finality_status(
+        &self, block_hash: H256,
+    ) -> RpcResult<Option<FinalityStatus>> {
+        debug!(
+            "RPC Request: eth_getBlockFinalityStatus(hash={:?})",
+            block_hash
+        );
+        Ok(self
+            .inner
+            .finality_status(&FinalityTarget::Block { block_hash }))
+    }
+
+    fn transaction_finality_status(
+        &self, tx_hash: H256,
+    ) -> RpcResult<Option<FinalityStatus>> {
+        debug!(
+            "RPC Request: eth_getTransactionFinalityStatus(hash={:?})",
+            tx_hash
+        );
+        Ok(self.inner.finality_status(&FinalityTarget::Transaction {
+            transaction_hash: tx_hash,
+        }))
+    }
 }
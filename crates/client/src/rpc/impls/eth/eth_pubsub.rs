@@ -4,22 +4,26 @@
 
 use crate::rpc::{
     errors,
-    helpers::{EpochQueue, SubscriberId, Subscribers},
+    helpers::{EpochQueue, ReorgTracker, Reverted, SubscriberId, Subscribers},
     metadata::Metadata,
     traits::eth_space::eth_pubsub::EthPubSub as PubSub,
     types::eth::{
-        eth_pubsub as pubsub, Header as RpcHeader, Log as RpcLog, Log,
+        eth_pubsub as pubsub, FinalityStatus, FinalityTarget,
+        Header as RpcHeader, Log as RpcLog, Log, SyncStatus,
     },
 };
 use cfx_parameters::{
     consensus::DEFERRED_STATE_EPOCH_COUNT,
     consensus_internal::REWARD_EPOCH_COUNT,
 };
+use cfx_rpc::EthApi;
 use cfx_rpc_cfx_types::{traits::BlockProvider, PhantomBlock};
 use cfx_types::{Space, H256};
 use cfxcore::{
-    channel::Channel, BlockDataManager, ConsensusGraph, Notifications,
-    SharedConsensusGraph,
+    channel::{Channel, Receiver},
+    client::PivotChainReorg,
+    BlockDataManager, ConsensusGraph, Notifications, SharedConsensusGraph,
+    SharedSynchronizationService,
 };
 use futures::{
     compat::Future01CompatExt,
@@ -37,7 +41,6 @@ use primitives::{
 };
 use runtime::Executor;
 use std::{
-    collections::VecDeque,
     sync::{Arc, Weak},
     time::Duration,
 };
@@ -52,8 +55,12 @@ pub struct PubSubClient {
     heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
     logs_subscribers: Arc<RwLock<Subscribers<(Client, LogFilter)>>>,
     epochs_ordered: Arc<Channel<(u64, Vec<H256>)>>,
+    pivot_chain_reorgs: Arc<Channel<PivotChainReorg>>,
     consensus: SharedConsensusGraph,
     heads_loop_started: Arc<RwLock<bool>>,
+    syncing_subscribers: Arc<RwLock<Subscribers<Client>>>,
+    sync: Arc<RwLock<Option<SharedSynchronizationService>>>,
+    finality_subscribers: Arc<RwLock<Subscribers<(Client, FinalityTarget)>>>,
 }
 
 impl PubSubClient {
@@ -77,11 +84,21 @@ impl PubSubClient {
             heads_subscribers,
             logs_subscribers,
             epochs_ordered: notifications.epochs_ordered.clone(),
+            pivot_chain_reorgs: notifications.pivot_chain_reorgs.clone(),
             consensus: consensus.clone(),
             heads_loop_started: Arc::new(RwLock::new(false)),
+            syncing_subscribers: Default::default(),
+            sync: Default::default(),
+            finality_subscribers: Default::default(),
         }
     }
 
+    /// Enables the `syncing` subscription. The sync service is created after
+    /// the pubsub client and does not exist on light nodes.
+    pub fn set_sync_service(&self, sync: SharedSynchronizationService) {
+        *self.sync.write() = Some(sync);
+    }
+
     /// Returns a chain notification handler.
     pub fn handler(&self) -> Weak<ChainNotificationHandler> {
         Arc::downgrade(&self.handler)
@@ -137,17 +154,23 @@ impl PubSubClient {
 
     // Start an async loop that continuously receives epoch notifications and
     // publishes the corresponding logs to subscriber `id`, keeping their
-    // original order. The loop terminates when subscriber `id` unsubscribes.
+    // original order. On pivot chain reorgs, the reorg is published with the
+    // old and the new pivot blocks, and the logs of the reverted epochs are
+    // published again as removed, the latest first. The loop terminates when
+    // subscriber `id` unsubscribes.
     fn start_logs_loop(&self, id: SubscriberId) {
         trace!("start_logs_loop({:?})", id);
 
         // clone everything we use in our async loop
         let subscribers = self.logs_subscribers.clone();
         let epochs_ordered = self.epochs_ordered.clone();
+        let pivot_chain_reorgs = self.pivot_chain_reorgs.clone();
         let handler = self.handler.clone();
 
-        // subscribe to the `epochs_ordered` channel
+        // subscribe to the `epochs_ordered` and `pivot_chain_reorgs` channels.
+        // a reorg is always sent before the epochs of the new pivot chain.
         let mut receiver = epochs_ordered.subscribe();
+        let mut reorg_receiver = pivot_chain_reorgs.subscribe();
 
         // use a queue to make sure we only process an epoch once it has been
         // executed for sure
@@ -160,8 +183,7 @@ impl PubSubClient {
         // loop asynchronously
         let fut = async move {
             let mut last_epoch = 0;
-            let mut epochs: VecDeque<(u64, Vec<H256>, Vec<Log>)> =
-                VecDeque::new();
+            let mut tracker = ReorgTracker::default();
 
             while let Some(epoch) = receiver.recv().await {
                 trace!("logs_loop({:?}): {:?}", id, epoch);
@@ -172,10 +194,15 @@ impl PubSubClient {
                     None => {
                         // unsubscribed, terminate loop
                         epochs_ordered.unsubscribe(receiver.id);
+                        pivot_chain_reorgs.unsubscribe(reorg_receiver.id);
                         return;
                     }
                 };
 
+                while let Ok(reorg) = reorg_receiver.try_recv() {
+                    tracker.on_pivot_chain_reorg(reorg);
+                }
+
                 let epoch = match queue.push(epoch) {
                     None => continue,
                     Some(e) => e,
@@ -185,38 +212,19 @@ impl PubSubClient {
                 if epoch.0 <= last_epoch {
                     debug!("pivot chain reorg: {} -> {}", last_epoch, epoch.0);
                     assert!(epoch.0 > 0, "Unexpected epoch number received.");
-
-                    let mut reverted = vec![];
-                    while let Some(e) = epochs.back() {
-                        if e.0 >= epoch.0 {
-                            reverted.push(epochs.pop_back().unwrap());
-                        } else {
-                            break;
-                        }
-                    }
-
-                    for (_, _, logs) in reverted.into_iter() {
-                        handler.notify_removed_logs(&sub, logs).await;
-                    }
+                    let reverted = tracker.revert(epoch.0);
+                    handler.notify_revert(&sub, epoch.0 - 1, reverted).await;
                 }
 
                 last_epoch = epoch.0;
-
-                let latest_finalized_epoch_number =
-                    consensus.latest_finalized_epoch_number();
-                while let Some(e) = epochs.front() {
-                    if e.0 < latest_finalized_epoch_number {
-                        epochs.pop_front();
-                    } else {
-                        break;
-                    }
-                }
+                tracker.prune(consensus.latest_finalized_epoch_number());
 
                 // publish matching logs
+                let pivot = *epoch.1.last().expect("empty epoch in pubsub");
                 let logs = handler
                     .notify_logs(&sub, filter, epoch.clone(), false)
                     .await;
-                epochs.push_back((epoch.0, epoch.1, logs));
+                tracker.push(epoch.0, pivot, logs);
             }
         };
 
@@ -224,6 +232,164 @@ impl PubSubClient {
         let fut = fut.unit_error().boxed().compat();
         self.handler.executor.spawn(fut);
     }
+
+    // Start an async loop that publishes the sync status to subscriber `id`
+    // whenever the sync phase or the best epoch changes. The loop terminates
+    // when subscriber `id` unsubscribes.
+    fn start_syncing_loop(
+        &self, id: SubscriberId, sync: SharedSynchronizationService,
+    ) {
+        trace!("start_syncing_loop({:?})", id);
+
+        // clone everything we use in our async loop
+        let subscribers = self.syncing_subscribers.clone();
+        let epochs_ordered = self.epochs_ordered.clone();
+        let phase_changes = sync.sync_phase_changes();
+
+        // subscribe to the `epochs_ordered` and sync phase channels
+        let mut receiver = epochs_ordered.subscribe();
+        let mut phase_receiver = phase_changes.subscribe();
+
+        // loop asynchronously
+        let fut = async move {
+            let mut last_status: Option<pubsub::PubSyncStatus> = None;
+
+            loop {
+                // retrieve subscriber
+                let sub = match subscribers.read().get(&id) {
+                    Some(sub) => sub.clone(),
+                    None => {
+                        // unsubscribed, terminate loop
+                        epochs_ordered.unsubscribe(receiver.id);
+                        phase_changes.unsubscribe(phase_receiver.id);
+                        return;
+                    }
+                };
+
+                let status = match EthApi::sync_status_of(&sync) {
+                    SyncStatus::Info(info) => pubsub::PubSyncStatus {
+                        syncing: true,
+                        status: Some(info),
+                    },
+                    SyncStatus::None => pubsub::PubSyncStatus {
+                        syncing: false,
+                        status: None,
+                    },
+                };
+                let publish = match &last_status {
+                    None => true,
+                    Some(last) => status != *last,
+                };
+
+                if publish {
+                    ChainNotificationHandler::notify_async(
+                        &sub,
+                        pubsub::Result::SyncState(status.clone()),
+                    )
+                    .await;
+                    last_status = Some(status);
+                }
+
+                // wait for the next change, and skip the changes that are
+                // already outdated
+                let changed = tokio::select! {
+                    epoch = receiver.recv() => epoch.is_some(),
+                    phase = phase_receiver.recv() => phase.is_some(),
+                };
+                if !changed {
+                    return;
+                }
+                while receiver.try_recv().is_ok() {}
+                while phase_receiver.try_recv().is_ok() {}
+            }
+        };
+
+        // run futures@0.3 future on tokio@0.1 executor
+        let fut = fut.unit_error().boxed().compat();
+        self.handler.executor.spawn(fut);
+    }
+
+    // Start an async loop that publishes the finality status of the target of
+    // subscriber `id` once subscribed and whenever it changes on new epochs.
+    // The loop terminates when the target is finalized by PoS, or when
+    // subscriber `id` unsubscribes.
+    fn start_finality_loop(&self, id: SubscriberId) {
+        trace!("start_finality_loop({:?})", id);
+
+        // clone everything we use in our async loop
+        let subscribers = self.finality_subscribers.clone();
+        let epochs_ordered = self.epochs_ordered.clone();
+        let handler = self.handler.clone();
+
+        // subscribe to the `epochs_ordered` channel
+        let mut receiver = epochs_ordered.subscribe();
+
+        // loop asynchronously
+        let fut = async move {
+            publish_finality(
+                &mut receiver,
+                || subscribers.read().get(&id).cloned(),
+                |(_, target)| {
+                    EthApi::finality_status_of(
+                        handler.consensus_graph(),
+                        target,
+                    )
+                },
+                |(sub, _), status| async move {
+                    ChainNotificationHandler::notify_async(
+                        &sub,
+                        pubsub::Result::Finality(status),
+                    )
+                    .await
+                },
+            )
+            .await;
+
+            epochs_ordered.unsubscribe(receiver.id);
+        };
+
+        // run futures@0.3 future on tokio@0.1 executor
+        let fut = fut.unit_error().boxed().compat();
+        self.handler.executor.spawn(fut);
+    }
+}
+
+// Publish the finality status of the subscriber returned by `subscriber` via
+// `notify` once it is known and whenever it changes, checking it again on each
+// epoch received from `receiver`. Returns when the target is finalized by PoS,
+// when `subscriber` returns `None` (unsubscribed), or when `receiver` closes.
+async fn publish_finality<S, T, F>(
+    receiver: &mut Receiver<T>, subscriber: impl Fn() -> Option<S>,
+    status_of: impl Fn(&S) -> Option<FinalityStatus>,
+    notify: impl Fn(S, FinalityStatus) -> F,
+) where
+    F: std::future::Future<Output = ()>,
+{
+    let mut last_status = None;
+
+    loop {
+        // retrieve subscriber
+        let sub = match subscriber() {
+            Some(sub) => sub,
+            // unsubscribed, terminate loop
+            None => return,
+        };
+
+        if let Some(status) = status_of(&sub) {
+            if last_status.as_ref() != Some(&status) {
+                notify(sub, status.clone()).await;
+            }
+            if status.pos_finalized {
+                // finalized, terminate loop
+                return;
+            }
+            last_status = Some(status);
+        }
+
+        if receiver.recv().await.is_none() {
+            return;
+        }
+    }
 }
 
 /// PubSub notification handler.
@@ -317,9 +483,28 @@ impl ChainNotificationHandler {
         }
     }
 
-    async fn notify_removed_logs(&self, subscriber: &Client, logs: Vec<Log>) {
+    async fn notify_revert(
+        &self, subscriber: &Client, epoch: u64, reverted: Option<Reverted<Log>>,
+    ) {
+        trace!("notify_revert({:?})", epoch);
+
+        let (old_pivots, new_pivots, removed_logs) = match reverted {
+            Some(r) => (r.old_pivots, r.new_pivots, r.items),
+            None => Default::default(),
+        };
+
+        Self::notify_async(
+            subscriber,
+            pubsub::Result::ChainReorg {
+                revert_to: epoch.into(),
+                old_pivots,
+                new_pivots,
+            },
+        )
+        .await;
+
         // send logs in order
-        for mut log in logs.into_iter() {
+        for mut log in removed_logs {
             log.removed = true;
             Self::notify_async(subscriber, pubsub::Result::Log(log)).await;
         }
@@ -602,6 +787,39 @@ impl PubSub for PubSubClient {
             (pubsub::Kind::Logs, _) => {
                 errors::invalid_params("logs", "Expected filter parameter.")
             }
+            // --------- syncing ---------
+            (pubsub::Kind::Syncing, None) => match self.sync.read().clone() {
+                Some(sync) => {
+                    let id = self.syncing_subscribers.write().push(subscriber);
+                    self.start_syncing_loop(id, sync);
+                    return;
+                }
+                None => errors::unimplemented(None),
+            },
+            (pubsub::Kind::Syncing, _) => {
+                errors::invalid_params("syncing", "Expected no parameters.")
+            }
+            // --------- finality ---------
+            (pubsub::Kind::Finality, Some(params)) => {
+                match params.into_finality_target() {
+                    Some(target) => {
+                        let id = self
+                            .finality_subscribers
+                            .write()
+                            .push(subscriber, target);
+                        self.start_finality_loop(id);
+                        return;
+                    }
+                    None => errors::invalid_params(
+                        "finality",
+                        "Expected blockHash or transactionHash parameter.",
+                    ),
+                }
+            }
+            (pubsub::Kind::Finality, None) => errors::invalid_params(
+                "finality",
+                "Expected blockHash or transactionHash parameter.",
+            ),
             _ => errors::unimplemented(None),
         };
 
@@ -613,7 +831,111 @@ impl PubSub for PubSubClient {
     ) -> RpcResult<bool> {
         let res0 = self.heads_subscribers.write().remove(&id).is_some();
         let res1 = self.logs_subscribers.write().remove(&id).is_some();
+        let res2 = self.syncing_subscribers.write().remove(&id).is_some();
+        let res3 = self.finality_subscribers.write().remove(&id).is_some();
+
+        Ok(res0 || res1 || res2 || res3)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cfx_types::U256;
+    use std::{cell::RefCell, collections::VecDeque};
+
+    fn status(risk: u64, pos_finalized: bool) -> FinalityStatus {
+        FinalityStatus {
+            block_hash: H256::repeat_byte(1),
+            block_number: U256::from(10),
+            transaction_hash: None,
+            confirmation_risk: Some(U256::from(risk)),
+            pos_finalized,
+            latest_finalized_block: U256::zero(),
+            pos_reference: None,
+            pos_view: None,
+            expected_finality_seconds: None,
+        }
+    }
+
+    // Run `publish_finality` with the given statuses, one per iteration, and
+    // return the published statuses. `epochs` epochs are sent before the loop
+    // starts, then the epoch channel is closed if `close` is set. The loop is
+    // unsubscribed once `statuses` is exhausted.
+    fn run(
+        statuses: Vec<Option<FinalityStatus>>, epochs: u64, close: bool,
+    ) -> Vec<FinalityStatus> {
+        let channel = Channel::<u64>::new("test-epochs");
+        let mut receiver = channel.subscribe();
+        for epoch in 0..epochs {
+            assert!(channel.send(epoch));
+        }
+        if close {
+            drop(channel);
+        }
 
-        Ok(res0 || res1)
+        let statuses = RefCell::new(VecDeque::from(statuses));
+        let published = RefCell::new(vec![]);
+
+        futures::executor::block_on(publish_finality(
+            &mut receiver,
+            || statuses.borrow_mut().pop_front(),
+            |status| status.clone(),
+            |_, status| {
+                published.borrow_mut().push(status);
+                async {}
+            },
+        ));
+
+        published.into_inner()
+    }
+
+    #[test]
+    fn test_publish_changed_status() {
+        let published = run(
+            vec![
+                None,
+                Some(status(5, false)),
+                Some(status(5, false)),
+                Some(status(3, false)),
+                Some(status(0, true)),
+                Some(status(0, true)),
+            ],
+            10,
+            false,
+        );
+
+        // unknown and unchanged statuses are not published, and the loop
+        // stops at the first finalized status
+        assert_eq!(
+            published,
+            vec![status(5, false), status(3, false), status(0, true)]
+        );
+    }
+
+    #[test]
+    fn test_stop_on_unsubscribe() {
+        let published = run(
+            vec![Some(status(5, false)), Some(status(3, false))],
+            10,
+            false,
+        );
+        assert_eq!(published, vec![status(5, false), status(3, false)]);
+    }
+
+    #[test]
+    fn test_stop_on_closed_channel() {
+        // only one epoch arrives before the channel is closed, so the
+        // finalized status is never checked
+        let published = run(
+            vec![
+                Some(status(5, false)),
+                Some(status(3, false)),
+                Some(status(0, true)),
+            ],
+            1,
+            true,
+        );
+        assert_eq!(published, vec![status(5, false), status(3, false)]);
     }
 }
@@ -4,19 +4,25 @@ use crate::rpc::{
     types::{
         eth::{
             BlockNumber, LocalizedTrace as EthLocalizedTrace, Res as EthRes,
-            TraceFilter as EthTraceFilter,
+            TraceFilter as EthTraceFilter, TransactionRequest,
         },
         Action as RpcAction,
     },
 };
+use alloy_rpc_types_trace::parity::{
+    TraceResults, TraceResultsWithTransactionHash, TraceType,
+};
 use cfx_execute_helper::exec_tracer::TraceFilter as PrimitiveTraceFilter;
+use cfx_rpc::TraceApi;
 use cfx_types::{Space, H256};
 use cfx_util_macros::unwrap_option_or_return_result_none as unwrap_or_return;
 use jsonrpc_core::{Error as JsonRpcError, Result as JsonRpcResult};
 use primitives::EpochNumber;
+use std::collections::HashSet;
 
 pub struct EthTraceHandler {
     pub trace_handler: TraceHandler,
+    pub replay_handler: TraceApi,
 }
 
 impl EthTrace for EthTraceHandler {
@@ -183,4 +189,39 @@ impl EthTrace for EthTraceHandler {
 
         Ok(Some(eth_traces))
     }
+
+    fn trace_call(
+        &self, request: TransactionRequest, trace_types: HashSet<TraceType>,
+        block_number: Option<BlockNumber>,
+    ) -> JsonRpcResult<TraceResults> {
+        self.replay_handler
+            .trace_call(request, trace_types, block_number)
+            .map_err(|err| err.into())
+    }
+
+    fn trace_call_many(
+        &self, calls: Vec<(TransactionRequest, HashSet<TraceType>)>,
+        block_number: Option<BlockNumber>,
+    ) -> JsonRpcResult<Vec<TraceResults>> {
+        self.replay_handler
+            .trace_call_many(calls, block_number)
+            .map_err(|err| err.into())
+    }
+
+    fn replay_transaction(
+        &self, tx_hash: H256, trace_types: HashSet<TraceType>,
+    ) -> JsonRpcResult<TraceResults> {
+        self.replay_handler
+            .replay_transaction(tx_hash, trace_types, Space::Ethereum)
+            .map_err(|err| err.into())
+    }
+
+    fn replay_block_transactions(
+        &self, block_number: BlockNumber, trace_types: HashSet<TraceType>,
+    ) -> JsonRpcResult<Option<Vec<TraceResultsWithTransactionHash>>> {
+        self.replay_handler
+            .replay_block_transactions(block_number, trace_types)
+            .map(Some)
+            .map_err(|err| err.into())
+    }
 }
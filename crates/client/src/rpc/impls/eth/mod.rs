@@ -1,9 +1,11 @@
 pub mod debug;
+pub mod dev;
 pub mod eth_filter;
 pub mod eth_handler;
 pub mod eth_pubsub;
 pub mod eth_trace;
 
 pub use debug::GethDebugHandler;
+pub use dev::EthDevHandler;
 pub use eth_handler::EthHandler;
 pub use eth_trace::EthTraceHandler;
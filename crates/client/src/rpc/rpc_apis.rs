@@ -20,6 +20,8 @@ pub enum Api {
     Pos,
     EthPubsub,
     EthDebug,
+    Dev,
+    EthDev,
 }
 
 impl FromStr for Api {
@@ -38,6 +40,8 @@ impl FromStr for Api {
             "pos" => Ok(Pos),
             "ethpubsub" => Ok(EthPubsub),
             "ethdebug" => Ok(EthDebug),
+            "dev" => Ok(Dev),
+            "ethdev" => Ok(EthDev),
             _ => Err("Unknown api type".into()),
         }
     }
@@ -56,6 +60,8 @@ impl Display for Api {
             Api::Pos => write!(f, "pos"),
             Api::EthPubsub => write!(f, "ethpubsub"),
             Api::EthDebug => write!(f, "ethdebug"),
+            Api::Dev => write!(f, "dev"),
+            Api::EthDev => write!(f, "ethdev"),
         }
     }
 }
@@ -130,3 +136,17 @@ impl FromStr for ApiSet {
         Ok(ApiSet::List(apis))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_excludes_dev() {
+        let apis: ApiSet = "all".parse().unwrap();
+        assert!(!apis.list_apis().contains(&Api::Dev));
+
+        let apis: ApiSet = "all,dev".parse().unwrap();
+        assert!(apis.list_apis().contains(&Api::Dev));
+    }
+}
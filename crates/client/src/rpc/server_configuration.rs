@@ -84,3 +84,32 @@ impl WsConfiguration {
         }
     }
 }
+
+#[derive(Debug, PartialEq)]
+pub struct GraphQLConfiguration {
+    pub enabled: bool,
+    pub address: SocketAddr,
+    // The maximum complexity of a query, every resolved field costs 1 and
+    // list fields are multiplied by the number of requested items.
+    pub max_complexity: usize,
+    // The maximum nesting depth of a query.
+    pub max_depth: usize,
+}
+
+impl GraphQLConfiguration {
+    pub fn new(
+        ip: Option<(u8, u8, u8, u8)>, port: Option<u16>, max_complexity: usize,
+        max_depth: usize,
+    ) -> Self {
+        let ipv4 = match ip {
+            Some(ip) => Ipv4Addr::new(ip.0, ip.1, ip.2, ip.3),
+            None => Ipv4Addr::new(0, 0, 0, 0),
+        };
+        GraphQLConfiguration {
+            enabled: port.is_some(),
+            address: SocketAddr::V4(SocketAddrV4::new(ipv4, port.unwrap_or(0))),
+            max_complexity,
+            max_depth,
+        }
+    }
+}
@@ -4,12 +4,14 @@
 
 use crate::rpc::types::{
     pos::PoSEpochReward, Account as RpcAccount, AccountPendingInfo,
-    AccountPendingTransactions, Block, BlockHashOrEpochNumber, Bytes,
-    CfxFeeHistory, CfxRpcLogFilter, CheckBalanceAgainstTransactionResponse,
-    EpochNumber, EstimateGasAndCollateralResponse, Log as RpcLog, PoSEconomics,
+    AccountPendingTransactions, AccountProof, Block, BlockHashOrEpochNumber,
+    Bytes, CfxFeeHistory, CfxRpcLogFilter,
+    CheckBalanceAgainstTransactionResponse, EpochNumber,
+    EstimateGasAndCollateralResponse, Log as RpcLog, PoSEconomics,
     Receipt as RpcReceipt, RewardInfo as RpcRewardInfo, RpcAddress,
-    SponsorInfo, Status as RpcStatus, StorageCollateralInfo, TokenSupplyInfo,
-    Transaction, TransactionRequest, VoteParamsInfo, U64 as HexU64,
+    SponsorInfo, Status as RpcStatus, StorageCollateralInfo,
+    SyncStatus as RpcSyncStatus, TokenSupplyInfo, Transaction,
+    TransactionRequest, VoteParamsInfo, U64 as HexU64,
 };
 use cfx_types::{H256, U256, U64};
 use jsonrpc_core::{BoxFuture, Result as JsonRpcResult};
@@ -111,6 +113,15 @@ pub trait Cfx {
         &self, address: RpcAddress, epoch_num: Option<EpochNumber>,
     ) -> BoxFuture<Option<StorageRoot>>;
 
+    /// Returns the account and the given storage slots of `address` at the
+    /// given epoch, with the proofs against the state root and the pivot
+    /// block headers committing to it.
+    #[rpc(name = "cfx_getProof")]
+    fn get_proof(
+        &self, address: RpcAddress, storage_keys: Vec<U256>,
+        epoch_num: Option<EpochNumber>,
+    ) -> BoxFuture<AccountProof>;
+
     /// Returns block with given hash.
     #[rpc(name = "cfx_getBlockByHash")]
     fn block_by_hash(
@@ -261,6 +272,11 @@ pub trait Cfx {
     #[rpc(name = "cfx_getStatus")]
     fn get_status(&self) -> JsonRpcResult<RpcStatus>;
 
+    /// Returns the block and state sync progress, with the estimated time to
+    /// catch up with the peers.
+    #[rpc(name = "cfx_getSyncStatus")]
+    fn get_sync_status(&self) -> JsonRpcResult<RpcSyncStatus>;
+
     /// Returns block reward information in an epoch
     #[rpc(name = "cfx_getBlockRewardInfo")]
     fn get_block_reward_info(
@@ -3,10 +3,11 @@
 // See http://www.gnu.org/licenses/
 
 use crate::rpc::types::{
-    BlockHashOrEpochNumber, Bytes as RpcBytes, ConsensusGraphStates,
-    EpochNumber, Receipt as RpcReceipt, RpcAddress, StatOnGasLoad,
-    SyncGraphStates, Transaction as RpcTransaction, TransactionRequest,
-    WrapTransaction,
+    AccountRange, BlockHashOrEpochNumber, Bytes as RpcBytes, CheckpointSummary,
+    ConsensusGraphStates, EpochExecutionWitnessVerification, EpochNumber,
+    Receipt as RpcReceipt, RpcAddress, SnapshotArchiveSummary,
+    SnapshotVerification, StatOnGasLoad, StorageRange, SyncGraphStates,
+    Transaction as RpcTransaction, TransactionRequest, WrapTransaction,
 };
 use cfx_types::{H256, H520, U128, U64};
 use cfxcore::verification::EpochReceiptProof;
@@ -132,4 +133,63 @@ pub trait LocalRpc {
     fn transactions_by_block(
         &self, block_hash: H256,
     ) -> JsonRpcResult<Vec<WrapTransaction>>;
+
+    /// Returns the RLP encoded witness recorded for the execution of an
+    /// epoch, if the node records execution witnesses.
+    #[rpc(name = "debug_getEpochExecutionWitness")]
+    fn epoch_execution_witness(
+        &self, epoch: EpochNumber,
+    ) -> JsonRpcResult<Option<RpcBytes>>;
+
+    /// Re-executes an epoch from the RLP encoded witness and the blocks of the
+    /// epoch without the state, and returns the resulting roots, which are
+    /// checked against the roots of the epoch committed by the pivot chain.
+    #[rpc(name = "debug_verifyEpochExecutionWitness")]
+    fn verify_epoch_execution_witness(
+        &self, witness: RpcBytes,
+    ) -> JsonRpcResult<EpochExecutionWitnessVerification>;
+
+    /// Returns a page of the Core Space accounts at the state of an epoch in
+    /// the order of the addresses, starting from the address `start`. Pass the
+    /// returned `next` as `start` to read the next page.
+    #[rpc(name = "debug_accountRange")]
+    fn account_range(
+        &self, epoch: EpochNumber, start: Option<RpcAddress>,
+        max_results: Option<usize>, no_code: Option<bool>,
+    ) -> JsonRpcResult<AccountRange>;
+
+    /// Returns a page of the storage of a Core Space contract at the state of
+    /// an epoch in the order of the storage keys, starting from `key_start`.
+    #[rpc(name = "debug_storageRangeAt")]
+    fn storage_range_at(
+        &self, epoch: EpochNumber, address: RpcAddress,
+        key_start: Option<RpcBytes>, max_result: Option<usize>,
+    ) -> JsonRpcResult<StorageRange>;
+
+    /// Writes the snapshot of the stable checkpoint with what is needed to
+    /// verify it to the file `file_name` in the snapshot archive directory
+    /// of the node, which other nodes can restore instead of syncing the
+    /// snapshot from peers. There is no offline export, as the blame states
+    /// in the archive are read from the consensus graph of the running node.
+    #[rpc(name = "debug_exportSnapshotArchive")]
+    fn export_snapshot_archive(
+        &self, file_name: String,
+    ) -> BoxFuture<SnapshotArchiveSummary>;
+
+    /// Recomputes the merkle roots of the snapshots kept by the node and
+    /// checks them against the committed state roots. With `rebuild`, an
+    /// inconsistent snapshot is rebuilt from its parent snapshot.
+    #[rpc(name = "debug_verifySnapshots")]
+    fn verify_snapshots(
+        &self, rebuild: Option<bool>,
+    ) -> JsonRpcResult<Vec<SnapshotVerification>>;
+
+    /// Briefly pauses the execution of epochs and writes a consistent copy
+    /// of the block db, the storage and the PoS databases with a manifest to
+    /// the directory `path` on the node, which `conflux db
+    /// restore-checkpoint` restores.
+    #[rpc(name = "debug_createCheckpoint")]
+    fn create_checkpoint(
+        &self, path: String,
+    ) -> JsonRpcResult<CheckpointSummary>;
 }
@@ -0,0 +1,73 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use crate::rpc::types::{
+    Bytes, RpcAddress, TransactionRequest, U64 as VariadicU64,
+};
+use cfx_types::{H256, U256, U64};
+use jsonrpc_core::Result as JsonRpcResult;
+use jsonrpc_derive::rpc;
+
+/// Dev mode RPCs to control the chain of a local development node, like the
+/// `evm_*` and `anvil_*` methods of Hardhat and Anvil. They are only allowed
+/// when the node runs in the dev mode.
+#[rpc(server)]
+pub trait DevRpc {
+    /// Mines blocks until the transactions in the pool are executed, and
+    /// returns the best block hash. `timestamp` sets the timestamp of the
+    /// first mined block.
+    #[rpc(name = "dev_mine")]
+    fn mine(&self, timestamp: Option<VariadicU64>) -> JsonRpcResult<H256>;
+
+    /// Moves the timestamps of the blocks mined later forward, and returns
+    /// the total offset from the system time in seconds.
+    #[rpc(name = "dev_increaseTime")]
+    fn increase_time(&self, seconds: VariadicU64) -> JsonRpcResult<U64>;
+
+    #[rpc(name = "dev_setNextBlockTimestamp")]
+    fn set_next_block_timestamp(
+        &self, timestamp: VariadicU64,
+    ) -> JsonRpcResult<()>;
+
+    /// Takes a snapshot of the chain and returns its id.
+    #[rpc(name = "dev_snapshot")]
+    fn snapshot(&self) -> JsonRpcResult<U64>;
+
+    /// Reverts the state to a snapshot. The snapshot and the snapshots taken
+    /// after it are removed. Returns `false` if the snapshot does not exist.
+    ///
+    /// The blocks mined after the snapshot are not removed, but abandoned by
+    /// mining a longer chain of empty blocks on top of the snapshot block, so
+    /// the epoch number keeps increasing after a revert. It fails if more
+    /// than 1000 blocks are mined after the snapshot.
+    #[rpc(name = "dev_revert")]
+    fn revert(&self, snapshot_id: VariadicU64) -> JsonRpcResult<bool>;
+
+    #[rpc(name = "dev_setBalance")]
+    fn set_balance(
+        &self, address: RpcAddress, balance: U256,
+    ) -> JsonRpcResult<()>;
+
+    #[rpc(name = "dev_setCode")]
+    fn set_code(&self, address: RpcAddress, code: Bytes) -> JsonRpcResult<()>;
+
+    #[rpc(name = "dev_setStorageAt")]
+    fn set_storage_at(
+        &self, address: RpcAddress, position: U256, value: H256,
+    ) -> JsonRpcResult<()>;
+
+    /// Allows `dev_sendTransaction` to send transactions from `address`
+    /// without its private key.
+    #[rpc(name = "dev_impersonateAccount")]
+    fn impersonate_account(&self, address: RpcAddress) -> JsonRpcResult<()>;
+
+    #[rpc(name = "dev_stopImpersonatingAccount")]
+    fn stop_impersonating_account(
+        &self, address: RpcAddress,
+    ) -> JsonRpcResult<()>;
+
+    /// Sends and executes a transaction from an impersonated account.
+    #[rpc(name = "dev_sendTransaction")]
+    fn send_transaction(&self, tx: TransactionRequest) -> JsonRpcResult<H256>;
+}
@@ -1,6 +1,7 @@
 pub mod cfx;
 pub mod cfx_filter;
 pub mod debug;
+pub mod dev;
 pub mod pool;
 pub mod pos;
 pub mod pubsub;
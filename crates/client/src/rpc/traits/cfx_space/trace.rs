@@ -5,9 +5,13 @@
 use crate::rpc::types::{
     EpochNumber, EpochTrace, LocalizedBlockTrace, LocalizedTrace, TraceFilter,
 };
+use alloy_rpc_types_trace::parity::{
+    TraceResults, TraceResultsWithTransactionHash, TraceType,
+};
 use cfx_types::H256;
 use jsonrpc_core::Result as JsonRpcResult;
 use jsonrpc_derive::rpc;
+use std::collections::HashSet;
 
 /// Trace specific rpc interface.
 #[rpc(server)]
@@ -33,4 +37,18 @@ pub trait Trace {
     /// Return all traces of both spaces in an epoch.
     #[rpc(name = "trace_epoch")]
     fn epoch_traces(&self, epoch: EpochNumber) -> JsonRpcResult<EpochTrace>;
+
+    /// Replays a Core space transaction and returns the requested traces of
+    /// it. The addresses in the traces are hex encoded.
+    #[rpc(name = "trace_replayTransaction")]
+    fn replay_transaction(
+        &self, tx_hash: H256, trace_types: HashSet<TraceType>,
+    ) -> JsonRpcResult<TraceResults>;
+
+    /// Replays all the Core space transactions of an epoch and returns the
+    /// requested traces of them.
+    #[rpc(name = "trace_replayEpochTransactions")]
+    fn replay_epoch_transactions(
+        &self, epoch: EpochNumber, trace_types: HashSet<TraceType>,
+    ) -> JsonRpcResult<Vec<TraceResultsWithTransactionHash>>;
 }
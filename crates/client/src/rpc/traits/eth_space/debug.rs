@@ -1,9 +1,11 @@
-use crate::rpc::types::eth::{BlockNumber, TransactionRequest};
+use crate::rpc::types::eth::{
+    AccountRange, BlockNumber, StorageRange, TransactionRequest,
+};
 use alloy_rpc_types_trace::geth::{
     GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace,
     TraceResult,
 };
-use cfx_types::H256;
+use cfx_types::{H160, H256};
 use jsonrpc_core::Result as JsonRpcResult;
 use jsonrpc_derive::rpc;
 
@@ -38,4 +40,23 @@ pub trait Debug {
         &self, request: TransactionRequest, block_number: Option<BlockNumber>,
         opts: Option<GethDebugTracingCallOptions>,
     ) -> JsonRpcResult<GethTrace>;
+
+    /// Returns a page of the accounts at the state of a block in the order of
+    /// the addresses, starting from the address `start`. Pass the returned
+    /// `next` as `start` to read the next page.
+    #[rpc(name = "debug_accountRange")]
+    fn debug_account_range(
+        &self, block: BlockNumber, start: Option<H160>,
+        max_results: Option<usize>, no_code: Option<bool>,
+    ) -> JsonRpcResult<AccountRange>;
+
+    /// Returns a page of the storage of a contract at the state of a block in
+    /// the order of the storage keys, starting from `key_start`. Unlike geth,
+    /// the storage is only available at the end of a block, and the keys are
+    /// the storage slots rather than their hashes.
+    #[rpc(name = "debug_storageRangeAt")]
+    fn debug_storage_range_at(
+        &self, block: BlockNumber, address: H160, key_start: Option<H256>,
+        max_result: Option<usize>,
+    ) -> JsonRpcResult<StorageRange>;
 }
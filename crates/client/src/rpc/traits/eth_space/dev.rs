@@ -0,0 +1,67 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use crate::rpc::types::{eth::TransactionRequest, Bytes, U64 as VariadicU64};
+use cfx_types::{H160, H256, U256, U64};
+use jsonrpc_core::Result as JsonRpcResult;
+use jsonrpc_derive::rpc;
+
+/// Methods compatible with the dev node methods of Hardhat and Anvil. They
+/// are only allowed when the node runs in the dev mode.
+#[rpc(server)]
+pub trait EthDev {
+    /// Mines blocks until the transactions in the pool are executed.
+    #[rpc(name = "evm_mine")]
+    fn evm_mine(&self, timestamp: Option<VariadicU64>)
+        -> JsonRpcResult<String>;
+
+    #[rpc(name = "evm_increaseTime")]
+    fn evm_increase_time(&self, seconds: VariadicU64) -> JsonRpcResult<U64>;
+
+    #[rpc(name = "evm_setNextBlockTimestamp")]
+    fn evm_set_next_block_timestamp(
+        &self, timestamp: VariadicU64,
+    ) -> JsonRpcResult<()>;
+
+    #[rpc(name = "evm_snapshot")]
+    fn evm_snapshot(&self) -> JsonRpcResult<U64>;
+
+    /// Reverts the state to a snapshot. The snapshot and the snapshots taken
+    /// after it are removed. Returns `false` if the snapshot does not exist.
+    ///
+    /// Unlike Hardhat and Anvil, the blocks mined after the snapshot are not
+    /// removed, but abandoned by mining a longer chain of empty blocks on top
+    /// of the snapshot block, so the block number keeps increasing after a
+    /// revert. It fails if more than 1000 blocks are mined after the
+    /// snapshot.
+    #[rpc(name = "evm_revert")]
+    fn evm_revert(&self, snapshot_id: VariadicU64) -> JsonRpcResult<bool>;
+
+    #[rpc(name = "anvil_setBalance", alias("hardhat_setBalance"))]
+    fn set_balance(&self, address: H160, balance: U256) -> JsonRpcResult<()>;
+
+    #[rpc(name = "anvil_setCode", alias("hardhat_setCode"))]
+    fn set_code(&self, address: H160, code: Bytes) -> JsonRpcResult<()>;
+
+    #[rpc(name = "anvil_setStorageAt", alias("hardhat_setStorageAt"))]
+    fn set_storage_at(
+        &self, address: H160, position: U256, value: H256,
+    ) -> JsonRpcResult<bool>;
+
+    #[rpc(
+        name = "anvil_impersonateAccount",
+        alias("hardhat_impersonateAccount")
+    )]
+    fn impersonate_account(&self, address: H160) -> JsonRpcResult<()>;
+
+    #[rpc(
+        name = "anvil_stopImpersonatingAccount",
+        alias("hardhat_stopImpersonatingAccount")
+    )]
+    fn stop_impersonating_account(&self, address: H160) -> JsonRpcResult<()>;
+
+    /// Sends and executes a transaction from an impersonated account.
+    #[rpc(name = "eth_sendTransaction")]
+    fn send_transaction(&self, tx: TransactionRequest) -> JsonRpcResult<H256>;
+}
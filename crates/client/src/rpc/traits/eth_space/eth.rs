@@ -26,8 +26,9 @@ use jsonrpc_derive::rpc;
 
 use crate::rpc::types::{
     eth::{
-        AccountPendingTransactions, Block, BlockNumber, EthRpcLogFilter, Log,
-        Receipt, SyncStatus, Transaction, TransactionRequest,
+        AccountPendingTransactions, Block, BlockNumber, EthRpcLogFilter,
+        FinalityStatus, Log, Receipt, SyncStatus, Transaction,
+        TransactionRequest,
     },
     Bytes, FeeHistory, Index,
 };
@@ -228,4 +229,17 @@ pub trait Eth {
         &self, address: H160, maybe_start_nonce: Option<U256>,
         maybe_limit: Option<U64>,
     ) -> Result<AccountPendingTransactions>;
+
+    /// Returns the confirmation risk and the PoS finality status of a block.
+    #[rpc(name = "eth_getBlockFinalityStatus")]
+    fn block_finality_status(
+        &self, block_hash: H256,
+    ) -> Result<Option<FinalityStatus>>;
+
+    /// Returns the confirmation risk and the PoS finality status of the block
+    /// including a transaction.
+    #[rpc(name = "eth_getTransactionFinalityStatus")]
+    fn transaction_finality_status(
+        &self, tx_hash: H256,
+    ) -> Result<Option<FinalityStatus>>;
 }
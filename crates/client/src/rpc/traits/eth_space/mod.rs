@@ -1,4 +1,5 @@
 pub mod debug;
+pub mod dev;
 pub mod eth;
 pub mod eth_filter;
 pub mod eth_pubsub;
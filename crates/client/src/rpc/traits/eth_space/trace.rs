@@ -2,10 +2,16 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
-use crate::rpc::types::eth::{BlockNumber, LocalizedTrace, TraceFilter};
+use crate::rpc::types::eth::{
+    BlockNumber, LocalizedTrace, TraceFilter, TransactionRequest,
+};
+use alloy_rpc_types_trace::parity::{
+    TraceResults, TraceResultsWithTransactionHash, TraceType,
+};
 use cfx_types::H256;
 use jsonrpc_core::Result as JsonRpcResult;
 use jsonrpc_derive::rpc;
+use std::collections::HashSet;
 
 /// Trace specific rpc interface.
 #[rpc(server)]
@@ -27,4 +33,32 @@ pub trait Trace {
     fn transaction_traces(
         &self, tx_hash: H256,
     ) -> JsonRpcResult<Option<Vec<LocalizedTrace>>>;
+
+    /// Executes the given call and returns the requested traces of it.
+    #[rpc(name = "trace_call")]
+    fn trace_call(
+        &self, request: TransactionRequest, trace_types: HashSet<TraceType>,
+        block_number: Option<BlockNumber>,
+    ) -> JsonRpcResult<TraceResults>;
+
+    /// Executes the given calls one after another on top of the state of the
+    /// same block and returns the requested traces of each call.
+    #[rpc(name = "trace_callMany")]
+    fn trace_call_many(
+        &self, calls: Vec<(TransactionRequest, HashSet<TraceType>)>,
+        block_number: Option<BlockNumber>,
+    ) -> JsonRpcResult<Vec<TraceResults>>;
+
+    /// Replays a transaction and returns the requested traces of it.
+    #[rpc(name = "trace_replayTransaction")]
+    fn replay_transaction(
+        &self, tx_hash: H256, trace_types: HashSet<TraceType>,
+    ) -> JsonRpcResult<TraceResults>;
+
+    /// Replays all the transactions of a block and returns the requested
+    /// traces of them.
+    #[rpc(name = "trace_replayBlockTransactions")]
+    fn replay_block_transactions(
+        &self, block_number: BlockNumber, trace_types: HashSet<TraceType>,
+    ) -> JsonRpcResult<Option<Vec<TraceResultsWithTransactionHash>>>;
 }
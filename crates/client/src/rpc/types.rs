@@ -16,18 +16,27 @@ pub use self::{
         address::{check_two_rpc_address_network_match, RpcAddress},
         blame_info::BlameInfo,
         block::{Block, BlockTransactions, Header},
+        checkpoint::CheckpointSummary,
         consensus_graph_states::ConsensusGraphStates,
         epoch_number::{BlockHashOrEpochNumber, EpochNumber},
+        execution_witness::EpochExecutionWitnessVerification,
         filter::{CfxFilterChanges, CfxFilterLog, CfxRpcLogFilter, RevertTo},
         log::Log,
         pos_economics::PoSEconomics,
+        proof::{
+            AccountProof, StateRootCommitment, StateRootProof, StorageSlotProof,
+        },
         pubsub,
         receipt::Receipt,
         reward_info::RewardInfo,
+        snapshot_archive::SnapshotArchiveSummary,
+        snapshot_verification::SnapshotVerification,
         stat_on_gas_load::StatOnGasLoad,
+        state_range::{AccountRange, StorageRange},
         status::Status,
         storage_collateral_info::StorageCollateralInfo,
         sync_graph_states::SyncGraphStates,
+        sync_status::SyncStatus,
         token_supply_info::TokenSupplyInfo,
         trace::EpochTrace,
         transaction::{PackedOrExecuted, Transaction, WrapTransaction},
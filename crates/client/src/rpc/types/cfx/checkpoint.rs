@@ -0,0 +1,39 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_types::{H256, U64};
+use cfxcore::block_data_manager::checkpoint::CheckpointManifest;
+use serde_derive::Serialize;
+
+/// The epoch and the databases copied into a checkpoint.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointSummary {
+    pub epoch_hash: Option<H256>,
+    pub epoch_height: Option<U64>,
+    pub state_root: Option<H256>,
+    pub snapshot_epoch_ids: Vec<H256>,
+    pub pos_db: bool,
+    pub files: U64,
+    pub size: U64,
+}
+
+impl From<&CheckpointManifest> for CheckpointSummary {
+    fn from(manifest: &CheckpointManifest) -> Self {
+        Self {
+            epoch_hash: manifest.epoch_hash,
+            epoch_height: manifest.epoch_height.map(Into::into),
+            state_root: manifest.state_root,
+            snapshot_epoch_ids: manifest.snapshot_epoch_ids.clone(),
+            pos_db: manifest.pos_db,
+            files: (manifest.files.len() as u64).into(),
+            size: manifest
+                .files
+                .iter()
+                .map(|file| file.size)
+                .sum::<u64>()
+                .into(),
+        }
+    }
+}
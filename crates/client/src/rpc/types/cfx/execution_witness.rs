@@ -0,0 +1,26 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_internal_common::{EpochExecutionCommitment, StateRootWithAuxInfo};
+use cfx_types::H256;
+use serde_derive::Serialize;
+
+/// The result of the re-execution of an epoch from its witness.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochExecutionWitnessVerification {
+    pub state_root: StateRootWithAuxInfo,
+    pub receipts_root: H256,
+    pub logs_bloom_hash: H256,
+}
+
+impl From<EpochExecutionCommitment> for EpochExecutionWitnessVerification {
+    fn from(commitment: EpochExecutionCommitment) -> Self {
+        Self {
+            state_root: commitment.state_root_with_aux_info,
+            receipts_root: commitment.receipts_root,
+            logs_bloom_hash: commitment.logs_bloom_hash,
+        }
+    }
+}
@@ -46,6 +46,10 @@ pub struct Log {
     /// Log space
     #[serde(skip_serializing_if = "Option::is_none")]
     pub space: Option<Space>,
+
+    /// Whether the log is removed by a pivot chain reorg
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub removed: bool,
 }
 
 impl Log {
@@ -63,6 +67,7 @@ impl Log {
             log_index: Some(e.log_index.into()),
             transaction_log_index: Some(e.transaction_log_index.into()),
             space: None,
+            removed: false,
         })
     }
 
@@ -80,6 +85,7 @@ impl Log {
             log_index: None,
             transaction_log_index: None,
             space: if include_space { Some(e.space) } else { None },
+            removed: false,
         })
     }
 }
@@ -110,6 +116,7 @@ mod tests {
             transaction_log_index: Some(1.into()),
             log_index: Some(U256::from(1)),
             space: Some(Space::Ethereum),
+            removed: false,
         };
 
         let serialized = serde_json::to_string(&log).unwrap();
@@ -2,18 +2,25 @@ mod access_list;
 pub mod account;
 pub mod blame_info;
 pub mod block;
+pub mod checkpoint;
 pub mod consensus_graph_states;
+pub mod execution_witness;
 pub mod filter;
 pub mod log;
 pub mod pos_economics;
+pub mod proof;
 pub mod pubsub;
 pub mod receipt;
 pub mod reward_info;
+pub mod snapshot_archive;
+pub mod snapshot_verification;
 pub mod sponsor_info;
 pub mod stat_on_gas_load;
+pub mod state_range;
 pub mod status;
 pub mod storage_collateral_info;
 pub mod sync_graph_states;
+pub mod sync_status;
 pub mod token_supply_info;
 pub mod trace;
 pub mod transaction;
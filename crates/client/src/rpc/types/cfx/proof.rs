@@ -0,0 +1,85 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use crate::rpc::types::{Bytes, RpcAddress};
+use cfx_types::{H256, U256, U64};
+use primitives::{StateRoot, StorageRoot};
+use serde_derive::Serialize;
+
+/// The proof of a Core Space account and some of its storage slots at an
+/// epoch, returned by `cfx_getProof`.
+///
+/// All proofs are RLP encoded and can be checked offline with the routines
+/// exported from `cfx-storage`:
+/// - `accountProof` is a `StateProof` of `account` under the account key of
+///   `address`, checked with `verify_state_entry`.
+/// - `storageRootProof` is a `NodeMerkleProof` of `storageRoot`, checked with
+///   `verify_storage_root`.
+/// - `storageProof[i].proof` is a `StateProof` of `storageProof[i].entry`, the
+///   RLP encoded `StorageValue` under the storage key of `storageProof[i].key`.
+///
+/// These checks take `stateRoot.stateRoot` and, if present,
+/// `prevSnapshotStateRoot.stateRoot`, which pads the keys of the intermediate
+/// delta MPT. The state roots of the latest epochs are not committed on the
+/// pivot chain yet, so their `commitment` is `None`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProof {
+    pub address: RpcAddress,
+    pub epoch_number: U64,
+    /// The RLP encoded account, `None` if the account does not exist.
+    pub account: Option<Bytes>,
+    pub balance: U256,
+    pub nonce: U256,
+    pub code_hash: H256,
+    pub staking_balance: U256,
+    /// The collateral stored in the account entry, not including the storage
+    /// points used by sponsored contracts.
+    pub collateral_for_storage: U256,
+    pub accumulated_interest_return: U256,
+    pub account_proof: Bytes,
+    pub storage_root: StorageRoot,
+    pub storage_root_proof: Bytes,
+    pub storage_proof: Vec<StorageSlotProof>,
+    pub state_root: StateRootProof,
+    /// The state root of the epoch `epochNumber - snapshotEpochCount`, `None`
+    /// if there is no such epoch.
+    pub prev_snapshot_state_root: Option<StateRootProof>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageSlotProof {
+    pub key: U256,
+    pub value: Option<H256>,
+    /// The RLP encoded `StorageValue`, `None` if the slot is empty.
+    pub entry: Option<Bytes>,
+    pub proof: Bytes,
+}
+
+/// The state root of an epoch and its commitment in a pivot block header.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateRootProof {
+    pub epoch_number: U64,
+    pub state_root: StateRoot,
+    /// `None` if no pivot block header commits to `stateRoot` yet, which is
+    /// the case for the latest `DEFERRED_STATE_EPOCH_COUNT` epochs.
+    pub commitment: Option<StateRootCommitment>,
+}
+
+/// The pivot block header committing to a state root.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateRootCommitment {
+    /// The RLP encoded `StateRootWitness`, checked with
+    /// `StateRootWitness::is_valid`.
+    pub witness: Bytes,
+    /// The hash of the pivot block header in the witness.
+    pub block_hash: H256,
+    /// The PoS block referenced by the header in the witness.
+    pub pos_reference: Option<H256>,
+    /// Whether the header in the witness is finalized by PoS.
+    pub pos_finalized: bool,
+}
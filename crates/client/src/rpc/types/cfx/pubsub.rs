@@ -20,7 +20,7 @@
 
 //! Pub-Sub types.
 
-use crate::rpc::types::{CfxRpcLogFilter, Header, Log};
+use crate::rpc::types::{CfxRpcLogFilter, Header, Log, SyncStatus};
 use cfx_types::{H256, U256};
 use serde::{de::Error, Deserialize, Deserializer, Serialize};
 use serde_json::{from_value, Value};
@@ -50,7 +50,17 @@ pub enum Result {
 
     /// Chain reorg
     #[serde(rename_all = "camelCase")]
-    ChainReorg { revert_to: U256 },
+    ChainReorg {
+        revert_to: U256,
+        /// Pivot blocks of the reverted epochs after `revert_to`.
+        old_pivots: Vec<H256>,
+        /// Pivot blocks of the new pivot chain after `revert_to`, as far as
+        /// known when the reorg is published.
+        new_pivots: Vec<H256>,
+    },
+
+    /// Sync progress
+    Syncing(SyncStatus),
 }
 
 /// Subscription kind.
@@ -0,0 +1,30 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_types::{H256, U64};
+use cfxcore::sync::SnapshotArchiveManifest;
+use serde_derive::Serialize;
+
+/// The snapshot written to a snapshot archive.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotArchiveSummary {
+    pub snapshot_epoch_id: H256,
+    pub height: U64,
+    pub merkle_root: H256,
+    pub chunks: U64,
+}
+
+impl From<&SnapshotArchiveManifest> for SnapshotArchiveSummary {
+    fn from(manifest: &SnapshotArchiveManifest) -> Self {
+        Self {
+            snapshot_epoch_id: *manifest
+                .snapshot_to_sync
+                .get_snapshot_epoch_id(),
+            height: manifest.snapshot_to_sync.height().into(),
+            merkle_root: manifest.snapshot_merkle_root,
+            chunks: (manifest.number_chunks() as u64).into(),
+        }
+    }
+}
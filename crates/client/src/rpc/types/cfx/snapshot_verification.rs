@@ -0,0 +1,54 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_storage::SnapshotVerification as StorageSnapshotVerification;
+use cfx_types::{H256, U64};
+use serde_derive::Serialize;
+
+/// The result of verifying a snapshot kept by the node.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotVerification {
+    pub snapshot_epoch_id: H256,
+    pub height: U64,
+    /// The merkle root recorded for the snapshot.
+    pub expected_merkle_root: H256,
+    /// The merkle root recomputed from the key-values of the snapshot.
+    pub kv_merkle_root: H256,
+    /// The merkle root of the snapshot mpt, if it's kept.
+    pub mpt_merkle_root: Option<H256>,
+    pub key_value_count: U64,
+    /// The pivot epoch whose execution commits the snapshot root.
+    pub committed_epoch: Option<H256>,
+    pub committed_merkle_root: Option<H256>,
+    /// Whether the state root committed by `committed_epoch` is confirmed by
+    /// blames, null if not decided yet.
+    pub state_root_verified: Option<bool>,
+    pub consistent: bool,
+    /// The merkle root of the snapshot rebuilt because it's inconsistent.
+    pub rebuilt_merkle_root: Option<H256>,
+}
+
+impl From<&StorageSnapshotVerification> for SnapshotVerification {
+    fn from(verification: &StorageSnapshotVerification) -> Self {
+        let check = &verification.check;
+        Self {
+            snapshot_epoch_id: check.snapshot_epoch_id,
+            height: check.height.into(),
+            expected_merkle_root: check.expected_merkle_root,
+            kv_merkle_root: check.kv_merkle_root,
+            mpt_merkle_root: check.mpt_merkle_root,
+            key_value_count: check.key_value_count.into(),
+            committed_epoch: verification
+                .committed_by
+                .map(|(epoch_id, _)| epoch_id),
+            committed_merkle_root: verification
+                .committed_by
+                .map(|(_, merkle_root)| merkle_root),
+            state_root_verified: verification.state_root_verified,
+            consistent: verification.is_consistent(),
+            rebuilt_merkle_root: verification.rebuilt_merkle_root,
+        }
+    }
+}
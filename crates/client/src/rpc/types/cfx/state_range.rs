@@ -0,0 +1,89 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use super::{Account, RpcAddress};
+use crate::rpc::types::Bytes;
+use cfx_addr::Network;
+use cfx_rpc::helpers;
+use cfx_types::U256;
+
+/// An account returned by `debug_accountRange`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRangeEntry {
+    #[serde(flatten)]
+    pub account: Account,
+    /// The code of the account, unless it has no code or the code is not
+    /// requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+}
+
+/// A page of the accounts in the order of the addresses.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRange {
+    pub accounts: Vec<AccountRangeEntry>,
+    /// The address to continue from, `None` if all accounts are read.
+    pub next: Option<RpcAddress>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRangeEntry {
+    pub key: Bytes,
+    pub value: U256,
+    /// The owner of the storage entry, who pays the collateral for it.
+    pub owner: Option<RpcAddress>,
+}
+
+/// A page of the storage of a contract in the order of the storage keys.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRange {
+    pub storage: Vec<StorageRangeEntry>,
+    /// The storage key to continue from, `None` if all entries are read.
+    pub next_key: Option<Bytes>,
+}
+
+impl AccountRange {
+    pub fn try_from(
+        range: helpers::AccountRange, network: Network,
+    ) -> Result<Self, String> {
+        let mut accounts = Vec::with_capacity(range.accounts.len());
+        for (account, code) in range.accounts {
+            accounts.push(AccountRangeEntry {
+                account: Account::try_from(account, network)?,
+                code: code.map(Bytes::new),
+            });
+        }
+        let next = range
+            .next
+            .map(|address| RpcAddress::try_from_h160(address, network))
+            .transpose()?;
+        Ok(Self { accounts, next })
+    }
+}
+
+impl StorageRange {
+    pub fn try_from(
+        range: helpers::StorageRange, network: Network,
+    ) -> Result<Self, String> {
+        let mut storage = Vec::with_capacity(range.storage.len());
+        for (key, value) in range.storage {
+            storage.push(StorageRangeEntry {
+                key: Bytes::new(key),
+                value: value.value,
+                owner: value
+                    .owner
+                    .map(|owner| RpcAddress::try_from_h160(owner, network))
+                    .transpose()?,
+            });
+        }
+        Ok(Self {
+            storage,
+            next_key: range.next_key.map(Bytes::new),
+        })
+    }
+}
@@ -30,4 +30,16 @@ pub struct Status {
     pub latest_state: U64,
     /// The latest finalized epoch.
     pub latest_finalized: U64,
+    /// The pruning mode: `archive`, `full` or `pruned:N`.
+    pub pruning_mode: String,
+    /// The earliest epoch whose state is available.
+    pub earliest_state: U64,
+    /// The earliest epoch whose block bodies are available.
+    pub earliest_block_body: U64,
+    /// The earliest epoch whose receipts are available.
+    pub earliest_receipt: U64,
+    /// The earliest epoch whose traces are available.
+    pub earliest_trace: U64,
+    /// The earliest epoch whose transaction indices are available.
+    pub earliest_transaction_index: U64,
 }
@@ -0,0 +1,67 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_types::{H256, U64};
+use cfxcore::sync::{StateSyncProgress, SyncProgress};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateSyncStatus {
+    pub status: String,
+    pub snapshot_epoch_id: Option<H256>,
+    pub snapshot_height: Option<U64>,
+    /// The number of chunks in the manifest received so far.
+    pub manifest_chunks: U64,
+    pub downloaded_chunks: U64,
+    /// `None` until the whole manifest is received.
+    pub total_chunks: Option<U64>,
+}
+
+impl StateSyncStatus {
+    pub fn new(progress: &StateSyncProgress) -> Self {
+        Self {
+            status: progress.status.name().into(),
+            snapshot_epoch_id: progress.snapshot.map(|(epoch_id, _)| epoch_id),
+            snapshot_height: progress.snapshot.map(|(_, height)| height.into()),
+            manifest_chunks: progress.manifest_chunks.into(),
+            downloaded_chunks: progress.downloaded_chunks.into(),
+            total_chunks: progress.total_chunks.map(Into::into),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The progress of the block and state synchronization.
+pub struct SyncStatus {
+    pub phase: String,
+    pub catch_up_mode: bool,
+    pub starting_epoch: U64,
+    pub current_epoch: U64,
+    /// The median best epoch of the peers.
+    pub target_epoch: U64,
+    /// The number of blocks with downloaded headers.
+    pub header_count: U64,
+    /// The number of blocks with downloaded bodies.
+    pub block_count: U64,
+    pub state_sync: StateSyncStatus,
+    /// The estimated seconds to catch up with the peers.
+    pub eta: Option<U64>,
+}
+
+impl SyncStatus {
+    pub fn new(progress: &SyncProgress) -> Self {
+        Self {
+            phase: format!("{:?}", progress.phase),
+            catch_up_mode: progress.catch_up_mode,
+            starting_epoch: progress.starting_epoch.into(),
+            current_epoch: progress.current_epoch.into(),
+            target_epoch: progress.target_epoch.into(),
+            header_count: progress.header_count.into(),
+            block_count: progress.block_count.into(),
+            state_sync: StateSyncStatus::new(&progress.state_sync),
+            eta: progress.eta.map(|eta| eta.as_secs().into()),
+        }
+    }
+}
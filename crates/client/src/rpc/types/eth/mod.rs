@@ -6,7 +6,8 @@ pub use cfx_rpc_eth_types::{
     eth_pubsub,
     trace::{LocalizedTrace, Res},
     trace_filter::TraceFilter,
-    AccountPendingTransactions, Block, BlockNumber, EthRpcLogFilter,
-    FilterChanges, Header, Log, Receipt, SyncInfo, SyncStatus, Transaction,
+    AccountPendingTransactions, AccountRange, Block, BlockNumber,
+    EthRpcLogFilter, FilterChanges, FinalityStatus, FinalityTarget, Header,
+    Log, Receipt, StorageRange, SyncInfo, SyncStatus, Transaction,
     TransactionRequest,
 };
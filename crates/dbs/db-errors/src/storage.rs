@@ -107,6 +107,12 @@ pub enum Error {
     #[error("Snapshot sync proof is invalid")]
     InvalidSnapshotSyncProof,
 
+    #[error("State witness is invalid: {0}")]
+    InvalidStateWitness(String),
+
+    #[error("Key {0:?} is not covered by the state witness.")]
+    StateWitnessKeyNotFound(Vec<u8>),
+
     #[error("Failed to create unit test data dir.")]
     FailedToCreateUnitTestDataDir,
 
@@ -24,7 +24,8 @@
 extern crate log;
 
 pub use self::impls::{
-    db_config, open_database, DatabaseCompactionProfile, SystemDB,
+    db_config, open_database, open_database_read_only,
+    DatabaseCompactionProfile, SystemDB,
 };
 
 #[path = "rocksdb/mod.rs"]
@@ -108,3 +108,13 @@ pub fn open_database(
 
     Ok(Arc::new(sys_db))
 }
+
+/// Open an existing database without write access, e.g. for offline
+/// inspection of the database of a stopped node.
+pub fn open_database_read_only(
+    path: &str, config: &DatabaseConfig,
+) -> io::Result<Arc<SystemDB>> {
+    let db = Database::open_read_only(config, path)?;
+    info!("Open db read-only successfully ({:?})", path);
+    Ok(Arc::new(SystemDB::new(Arc::new(db))))
+}
@@ -23,7 +23,7 @@ use std::{cmp, collections::HashMap, error, fs, io, mem, path::Path, result};
 use parking_lot::{Mutex, MutexGuard, RwLock};
 use rocksdb::{
     BlockBasedOptions, CFHandle, ColumnFamilyOptions, DBOptions, ReadOptions,
-    Writable, WriteBatch, WriteOptions, DB,
+    SeekKey, Writable, WriteBatch, WriteOptions, DB,
 };
 
 use fs_swap::{swap, swap_nonatomic};
@@ -267,6 +267,15 @@ fn col_config(
     Ok(opts)
 }
 
+/// Estimated size information of a column, read from RocksDB properties.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnStatistics {
+    pub estimate_num_keys: u64,
+    pub estimate_live_data_size: u64,
+    pub total_sst_files_size: u64,
+    pub cur_size_all_mem_tables: u64,
+}
+
 unsafe impl Send for Database {}
 unsafe impl Sync for Database {}
 /// Key-Value database.
@@ -350,6 +359,20 @@ impl Database {
 
     /// Open database file. Creates if it does not exist.
     pub fn open(config: &DatabaseConfig, path: &str) -> io::Result<Database> {
+        Self::open_impl(config, path, /* read_only = */ false)
+    }
+
+    /// Open an existing database without write access. No repair is
+    /// attempted and all writes fail, so it's safe for offline inspection.
+    pub fn open_read_only(
+        config: &DatabaseConfig, path: &str,
+    ) -> io::Result<Database> {
+        Self::open_impl(config, path, /* read_only = */ true)
+    }
+
+    fn open_impl(
+        config: &DatabaseConfig, path: &str, read_only: bool,
+    ) -> io::Result<Database> {
         let mut block_opts = BlockBasedOptions::default();
         block_opts.set_block_size(config.compaction.block_size);
         // Set cache size as recommended by
@@ -362,7 +385,7 @@ impl Database {
 
         // attempt database repair if it has been previously marked as corrupted
         let db_corrupted = Path::new(path).join(Database::CORRUPTION_FILE_NAME);
-        if db_corrupted.exists() {
+        if db_corrupted.exists() && !read_only {
             warn!(
                 "DB has been previously marked as corrupted, attempting repair"
             );
@@ -392,6 +415,22 @@ impl Database {
         read_opts.set_prefix_same_as_start(true);
         read_opts.set_verify_checksums(false);
 
+        if read_only {
+            let db = DB::open_cf_for_read_only(
+                opts, path, cf_options,
+                /* error_if_log_file_exist = */ false,
+            )
+            .map_err(other_io_err)?;
+            return Ok(Self::new_with_db(
+                config,
+                path,
+                DBAndColumns { db, column_names },
+                read_opts,
+                write_opts,
+                block_opts,
+            ));
+        }
+
         let db = match DB::open_cf(opts.clone(), path, cf_options.clone()) {
             Ok(db) => {
                 for name in &cfnames {
@@ -435,9 +474,24 @@ impl Database {
             }
             Err(s) => return Err(other_io_err(s)),
         };
-        let num_cols = column_names.len();
-        Ok(Database {
-            db: RwLock::new(Some(DBAndColumns { db, column_names })),
+        Ok(Self::new_with_db(
+            config,
+            path,
+            DBAndColumns { db, column_names },
+            read_opts,
+            write_opts,
+            block_opts,
+        ))
+    }
+
+    fn new_with_db(
+        config: &DatabaseConfig, path: &str, db: DBAndColumns,
+        read_opts: ReadOptions, write_opts: WriteOptions,
+        block_opts: BlockBasedOptions,
+    ) -> Database {
+        let num_cols = db.column_names.len();
+        Database {
+            db: RwLock::new(Some(db)),
             config: config.clone(),
             overlay: RwLock::new(
                 (0..=num_cols).map(|_| HashMap::new()).collect(),
@@ -450,7 +504,7 @@ impl Database {
             read_opts,
             write_opts,
             block_opts,
-        })
+        }
     }
 
     /// Helper to create new transaction for this database.
@@ -601,6 +655,62 @@ impl Database {
         }
     }
 
+    /// Read at most `limit` key-value pairs of a column in key order,
+    /// starting from `lower_bound_incl` and stopping before
+    /// `upper_bound_excl`.
+    ///
+    /// Only flushed data is visible; pending `write_buffered` changes are
+    /// ignored. The RocksDB iterator is dropped before returning, so callers
+    /// page through large ranges by restarting after the last returned key.
+    pub fn iter_range(
+        &self, col: u32, lower_bound_incl: &[u8],
+        upper_bound_excl: Option<&[u8]>, limit: usize,
+    ) -> io::Result<Vec<KeyValuePair>> {
+        match *self.db.read() {
+            Some(ref cfs) => {
+                let mut result = Vec::new();
+                let mut iter = cfs.db.iter_cf_opt(
+                    cfs.get_cf(col as usize),
+                    ReadOptions::default(),
+                );
+                let mut valid = iter
+                    .seek(SeekKey::Key(lower_bound_incl))
+                    .map_err(other_io_err)?;
+                while valid && result.len() < limit {
+                    let key = iter.key();
+                    if let Some(upper_bound) = upper_bound_excl {
+                        if key >= upper_bound {
+                            break;
+                        }
+                    }
+                    result.push((key.into(), iter.value().into()));
+                    valid = iter.next().map_err(other_io_err)?;
+                }
+                Ok(result)
+            }
+            None => Err(other_io_err("Database is closed")),
+        }
+    }
+
+    /// Create a consistent point-in-time copy of the database at `path`.
+    ///
+    /// Buffered changes are flushed first. SST files are hard-linked when
+    /// `path` is on the same file system, so the checkpoint is cheap
+    /// regardless of the database size.
+    pub fn checkpoint(&self, path: &Path) -> io::Result<()> {
+        self.flush()?;
+        match *self.db.read() {
+            Some(ref cfs) => cfs
+                .db
+                .new_checkpointer()
+                .and_then(|mut checkpointer| {
+                    checkpointer.create_at(path, None, 0)
+                })
+                .map_err(other_io_err),
+            None => Err(other_io_err("Database is closed")),
+        }
+    }
+
     /// Close the database
     fn close(&self) {
         *self.db.write() = None;
@@ -662,6 +772,32 @@ impl Database {
             .unwrap_or(0)
     }
 
+    /// Get the estimated statistics of a column. Returns None if the database
+    /// is closed.
+    pub fn column_statistics(&self, col: u32) -> Option<ColumnStatistics> {
+        match *self.db.read() {
+            Some(ref cfs) => {
+                let cf = cfs.get_cf(col as usize);
+                let property = |prop: &str| {
+                    cfs.db.get_property_int_cf(cf, prop).unwrap_or(0) as u64
+                };
+                Some(ColumnStatistics {
+                    estimate_num_keys: property("rocksdb.estimate-num-keys"),
+                    estimate_live_data_size: property(
+                        "rocksdb.estimate-live-data-size",
+                    ),
+                    total_sst_files_size: property(
+                        "rocksdb.total-sst-files-size",
+                    ),
+                    cur_size_all_mem_tables: property(
+                        "rocksdb.cur-size-all-mem-tables",
+                    ),
+                })
+            }
+            None => None,
+        }
+    }
+
     /// Drop a column family.
     pub fn drop_column(&self) -> io::Result<()> {
         match *self.db.write() {
@@ -829,6 +965,39 @@ mod tests {
         test_db(&DatabaseConfig::default());
     }
 
+    #[test]
+    fn read_only() {
+        let tempdir = TempDir::new("").unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let config = DatabaseConfig::with_columns(2);
+        {
+            let db = Database::open(&config, path).unwrap();
+            let mut batch = db.transaction();
+            batch.put(1, b"key", b"value");
+            db.write(batch).unwrap();
+        }
+
+        let db = Database::open_read_only(&config, path).unwrap();
+        assert_eq!(db.num_columns(), 2);
+        assert_eq!(&*db.get(1, b"key").unwrap().unwrap(), b"value");
+        let mut batch = db.transaction();
+        batch.put(1, b"key", b"other");
+        assert!(db.write(batch).is_err());
+        assert_eq!(&*db.get(1, b"key").unwrap().unwrap(), b"value");
+    }
+
+    #[test]
+    fn read_only_does_not_create() {
+        let tempdir = TempDir::new("").unwrap();
+        let path = tempdir.path().join("missing");
+        assert!(Database::open_read_only(
+            &DatabaseConfig::default(),
+            path.to_str().unwrap()
+        )
+        .is_err());
+        assert!(!path.exists());
+    }
+
     #[test]
     #[cfg(target_os = "linux")]
     fn df_to_rotational() {
@@ -0,0 +1,142 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! A flat key-value cache of the latest executed state.
+//!
+//! Reading a cold key from a [`State`](super::state::State) traverses the
+//! delta MPT, then the intermediate MPT, and finally looks up the snapshot.
+//! The cache keeps the values of the keys recently read or written at the
+//! state of one epoch, so that reads from the states based on that epoch are
+//! served without touching the tries.
+//!
+//! When a state based on the cached epoch is committed, its writes are applied
+//! and the cache moves to the committed epoch. A commit on top of any other
+//! epoch, e.g. after a pivot chain switch, drops the cached values instead.
+//! Proofs are always generated from the tries.
+
+use parking_lot::RwLock;
+use primitives::EpochId;
+use std::{
+    collections::HashMap,
+    mem,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Values of keys at the state of an epoch, None for keys which don't exist.
+pub type FlatStateWrites = HashMap<Vec<u8>, Option<Box<[u8]>>>;
+
+pub struct FlatStateCache {
+    /// The max number of cached keys.
+    capacity: usize,
+    inner: RwLock<FlatStateCacheInner>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+#[derive(Default)]
+struct FlatStateCacheInner {
+    /// None if nothing is cached.
+    epoch_id: Option<EpochId>,
+    /// Keys are evicted in two generations: when `recent` is full it replaces
+    /// `old`, and the keys read from `old` are moved back to `recent`.
+    recent: FlatStateWrites,
+    old: FlatStateWrites,
+}
+
+impl FlatStateCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Default::default(),
+            hits: Default::default(),
+            misses: Default::default(),
+        }
+    }
+
+    /// Returns None on a miss, or when the cache is not at `epoch_id`.
+    pub fn get(
+        &self, epoch_id: &EpochId, key: &[u8],
+    ) -> Option<Option<Box<[u8]>>> {
+        let maybe_value = {
+            let inner = self.inner.read();
+            if inner.epoch_id.as_ref() != Some(epoch_id) {
+                return None;
+            }
+            if let Some(value) = inner.recent.get(key) {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Some(value.clone());
+            }
+            inner.old.get(key).cloned()
+        };
+        match maybe_value {
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            Some(value) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                self.insert(epoch_id, key.to_vec(), value.clone());
+                Some(value)
+            }
+        }
+    }
+
+    /// Cache the value read from the tries of the state at `epoch_id`.
+    pub fn insert(
+        &self, epoch_id: &EpochId, key: Vec<u8>, value: Option<Box<[u8]>>,
+    ) {
+        let mut inner = self.inner.write();
+        // The cache may have moved on since the value was read.
+        if inner.epoch_id.as_ref() != Some(epoch_id) {
+            return;
+        }
+        inner.insert(key, value, self.capacity);
+    }
+
+    /// Move the cache to the state committed for `epoch_id` on top of
+    /// `parent_epoch_id` with `writes`.
+    pub fn commit(
+        &self, parent_epoch_id: &EpochId, epoch_id: &EpochId,
+        writes: FlatStateWrites,
+    ) {
+        let mut inner = self.inner.write();
+        if inner.epoch_id.as_ref() != Some(parent_epoch_id) {
+            debug!(
+                "flat state cache: invalidated at {:?} for epoch {:?} on \
+                 top of {:?}",
+                inner.epoch_id, epoch_id, parent_epoch_id
+            );
+            inner.recent.clear();
+            inner.old.clear();
+        }
+        inner.epoch_id = Some(*epoch_id);
+        for (key, value) in writes {
+            inner.insert(key, value, self.capacity);
+        }
+    }
+
+    pub fn log_usage(&self) {
+        let inner = self.inner.read();
+        debug!(
+            "flat state cache: epoch {:?}, {} keys, {} hits, {} misses",
+            inner.epoch_id,
+            inner.recent.len() + inner.old.len(),
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        );
+    }
+}
+
+impl FlatStateCacheInner {
+    fn insert(
+        &mut self, key: Vec<u8>, value: Option<Box<[u8]>>, capacity: usize,
+    ) {
+        self.old.remove(&key);
+        if self.recent.len() >= capacity / 2 && !self.recent.contains_key(&key)
+        {
+            self.old = mem::take(&mut self.recent);
+        }
+        self.recent.insert(key, value);
+    }
+}
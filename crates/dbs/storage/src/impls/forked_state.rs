@@ -0,0 +1,288 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! A state which forks off a remote chain.
+//!
+//! Keys which have never been written locally are fetched on demand from a
+//! [`ForkStateSource`], typically the state of a remote node at a fixed epoch,
+//! and cached up to a fixed number of entries. All writes stay local, so the
+//! local chain executes on top of the remote state without modifying it.
+//!
+//! A local deletion must shadow the remote value. The deleted keys are kept
+//! in memory next to the cache rather than in the trie, recorded for each
+//! committed epoch until the state of the epoch is removed, so a forked node
+//! can not be restarted on the same data directory.
+
+use crate::{impls::errors::*, state::StateTrait, MptKeyValue};
+use cfx_internal_common::StateRootWithAuxInfo;
+use parking_lot::RwLock;
+use primitives::{EpochId, StorageKeyWithSpace};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    sync::Arc,
+};
+
+/// A read-only view of the state which a local chain forks from.
+pub trait ForkStateSource: Send + Sync {
+    /// Returns the raw value of the key as stored in the storage layer, i.e.
+    /// the rlp encoding of the account, code, storage value, etc.
+    fn get(&self, access_key: StorageKeyWithSpace)
+        -> Result<Option<Box<[u8]>>>;
+
+    /// Returns all the keys with the prefix and their raw values. Fails if
+    /// the source can not enumerate the prefix.
+    fn get_all(
+        &self, access_key_prefix: StorageKeyWithSpace,
+    ) -> Result<Vec<MptKeyValue>>;
+}
+
+/// The default max number of remote values kept by [`ForkStateCache`].
+pub const DEFAULT_FORK_STATE_CACHE_ENTRIES: usize = 100_000;
+
+/// Remote values already fetched from the [`ForkStateSource`], and the keys
+/// deleted locally.
+///
+/// The forked state is fixed at one epoch, so the entries never go stale and
+/// the cache is shared by all the local states. The oldest entries are
+/// evicted when the cache is full, and fetched again when they are read.
+pub struct ForkStateCache {
+    source: Box<dyn ForkStateSource>,
+    max_entries: usize,
+    entries: RwLock<FetchedEntries>,
+    /// The keys deleted locally as of each committed epoch, with the height
+    /// of the epoch if it's known. Epochs without any deleted key are
+    /// omitted.
+    deleted_keys: RwLock<HashMap<EpochId, DeletedKeys>>,
+}
+
+struct DeletedKeys {
+    height: Option<u64>,
+    keys: Arc<BTreeSet<Vec<u8>>>,
+}
+
+#[derive(Default)]
+struct FetchedEntries {
+    values: HashMap<Vec<u8>, Option<Box<[u8]>>>,
+    /// The keys of `values` in the order they are fetched.
+    order: VecDeque<Vec<u8>>,
+}
+
+impl FetchedEntries {
+    fn insert(
+        &mut self, key: Vec<u8>, value: Option<Box<[u8]>>, max_entries: usize,
+    ) {
+        if self.values.insert(key.clone(), value).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > max_entries {
+            if let Some(oldest) = self.order.pop_front() {
+                self.values.remove(&oldest);
+            }
+        }
+    }
+}
+
+impl ForkStateCache {
+    pub fn new(source: Box<dyn ForkStateSource>, max_entries: usize) -> Self {
+        Self {
+            source,
+            max_entries,
+            entries: Default::default(),
+            deleted_keys: Default::default(),
+        }
+    }
+
+    pub fn get(
+        &self, access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Box<[u8]>>> {
+        let key_bytes = access_key.to_key_bytes();
+        if let Some(value) = self.entries.read().values.get(&key_bytes) {
+            return Ok(value.clone());
+        }
+        let value = self.source.get(access_key)?;
+        trace!(
+            "forked_state: fetched key={:?} found={}",
+            access_key,
+            value.is_some()
+        );
+        self.entries
+            .write()
+            .insert(key_bytes, value.clone(), self.max_entries);
+        Ok(value)
+    }
+
+    /// Reads all the remote keys with the prefix from the source, including
+    /// the ones never fetched before.
+    pub fn get_all(
+        &self, access_key_prefix: StorageKeyWithSpace,
+    ) -> Result<Vec<MptKeyValue>> {
+        let kvs = self.source.get_all(access_key_prefix)?;
+        let mut entries = self.entries.write();
+        for (key, value) in &kvs {
+            entries.insert(key.clone(), Some(value.clone()), self.max_entries);
+        }
+        Ok(kvs)
+    }
+
+    /// The number of remote values in the cache.
+    pub fn cached_entries(&self) -> usize { self.entries.read().values.len() }
+
+    /// The number of committed epochs whose deleted keys are kept.
+    pub fn deleted_keys_epochs(&self) -> usize {
+        self.deleted_keys.read().len()
+    }
+
+    /// Drops the deleted keys of the epochs below `lower_bound`, whose state
+    /// is no longer available. The epochs of unknown height are kept.
+    pub fn prune_deleted_keys(&self, lower_bound: u64) {
+        self.deleted_keys.write().retain(|_, deleted_keys| {
+            deleted_keys
+                .height
+                .map_or(true, |height| height >= lower_bound)
+        });
+    }
+
+    fn deleted_keys_at(
+        &self, epoch_id: Option<&EpochId>,
+    ) -> Arc<BTreeSet<Vec<u8>>> {
+        epoch_id
+            .and_then(|epoch_id| {
+                self.deleted_keys
+                    .read()
+                    .get(epoch_id)
+                    .map(|deleted_keys| deleted_keys.keys.clone())
+            })
+            .unwrap_or_default()
+    }
+
+    fn commit_deleted_keys(
+        &self, epoch_id: EpochId, height: Option<u64>,
+        keys: Arc<BTreeSet<Vec<u8>>>,
+    ) {
+        if !keys.is_empty() {
+            self.deleted_keys
+                .write()
+                .insert(epoch_id, DeletedKeys { height, keys });
+        }
+    }
+}
+
+pub struct ForkedState {
+    state: Box<dyn StateTrait>,
+    fork: Arc<ForkStateCache>,
+    /// The keys deleted locally, which shadow the remote values.
+    deleted_keys: Arc<BTreeSet<Vec<u8>>>,
+    /// The height of the epoch committed by the state, if it's known.
+    height: Option<u64>,
+}
+
+impl ForkedState {
+    /// Wraps the local `state`, which starts from the state of `epoch_id`, or
+    /// from an empty state if `epoch_id` is `None`. `height` is the height of
+    /// the epoch the state commits.
+    pub fn new(
+        state: Box<dyn StateTrait>, fork: Arc<ForkStateCache>,
+        epoch_id: Option<&EpochId>, height: Option<u64>,
+    ) -> Self {
+        let deleted_keys = fork.deleted_keys_at(epoch_id);
+        Self {
+            state,
+            fork,
+            deleted_keys,
+            height,
+        }
+    }
+}
+
+impl StateTrait for ForkedState {
+    fn get(
+        &self, access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Box<[u8]>>> {
+        if let Some(value) = self.state.get(access_key)? {
+            return Ok(Some(value));
+        }
+        if self.deleted_keys.contains(&access_key.to_key_bytes()) {
+            return Ok(None);
+        }
+        self.fork.get(access_key)
+    }
+
+    fn set(
+        &mut self, access_key: StorageKeyWithSpace, value: Box<[u8]>,
+    ) -> Result<()> {
+        let key_bytes = access_key.to_key_bytes();
+        if self.deleted_keys.contains(&key_bytes) {
+            Arc::make_mut(&mut self.deleted_keys).remove(&key_bytes);
+        }
+        self.state.set(access_key, value)
+    }
+
+    fn delete(&mut self, access_key: StorageKeyWithSpace) -> Result<()> {
+        self.state.delete(access_key)?;
+        Arc::make_mut(&mut self.deleted_keys).insert(access_key.to_key_bytes());
+        Ok(())
+    }
+
+    fn delete_test_only(
+        &mut self, access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Box<[u8]>>> {
+        let old_value = self.get(access_key)?;
+        self.delete(access_key)?;
+        Ok(old_value)
+    }
+
+    fn delete_all(
+        &mut self, access_key_prefix: StorageKeyWithSpace,
+    ) -> Result<Option<Vec<MptKeyValue>>> {
+        let deleted = self.read_all(access_key_prefix)?;
+        self.state.delete_all(access_key_prefix)?;
+        if let Some(kvs) = &deleted {
+            let deleted_keys = Arc::make_mut(&mut self.deleted_keys);
+            for (key, _) in kvs {
+                deleted_keys.insert(key.clone());
+            }
+        }
+        Ok(deleted)
+    }
+
+    fn read_all(
+        &mut self, access_key_prefix: StorageKeyWithSpace,
+    ) -> Result<Option<Vec<MptKeyValue>>> {
+        // Local keys, and the deleted ones, shadow the remote keys.
+        let mut kvs: BTreeMap<_, _> = self
+            .state
+            .read_all(access_key_prefix)?
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        for (key, value) in self.fork.get_all(access_key_prefix)? {
+            if !kvs.contains_key(&key) && !self.deleted_keys.contains(&key) {
+                kvs.insert(key, value);
+            }
+        }
+        if kvs.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(kvs.into_iter().collect()))
+        }
+    }
+
+    fn compute_state_root(&mut self) -> Result<StateRootWithAuxInfo> {
+        self.state.compute_state_root()
+    }
+
+    fn get_state_root(&self) -> Result<StateRootWithAuxInfo> {
+        self.state.get_state_root()
+    }
+
+    fn commit(&mut self, epoch_id: EpochId) -> Result<StateRootWithAuxInfo> {
+        let state_root = self.state.commit(epoch_id)?;
+        self.fork.commit_deleted_keys(
+            epoch_id,
+            self.height,
+            self.deleted_keys.clone(),
+        );
+        Ok(state_root)
+    }
+}
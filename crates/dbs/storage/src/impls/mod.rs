@@ -5,8 +5,12 @@
 #[macro_use]
 pub(super) mod merkle_patricia_trie;
 pub(super) mod delta_mpt;
+pub(super) mod flat_state_cache;
+pub(super) mod forked_state;
 pub(super) mod node_merkle_proof;
+pub(super) mod partial_delta_mpt;
 pub(super) mod proof_merger;
+pub(super) mod proof_verifier;
 pub(super) mod recording_storage;
 pub(super) mod replicated_state;
 pub(super) mod single_mpt_state;
@@ -14,6 +18,7 @@ pub(super) mod snapshot_sync;
 pub(super) mod state;
 pub(super) mod state_manager;
 pub(super) mod state_proof;
+pub(super) mod state_witness;
 pub(super) mod storage_db;
 pub(super) mod storage_manager;
 
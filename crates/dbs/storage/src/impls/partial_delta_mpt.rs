@@ -0,0 +1,270 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+// `PartialDeltaMpt` is a delta MPT of which only the nodes on the paths of
+// some keys are known, taken from the proof nodes of the keys. The other
+// subtrees are known by their merkle hashes only. Key-values are inserted in
+// the same way as the delta MPT does, so that the merkle root after the
+// insertions is computed without the full delta MPT.
+//
+// A proof node is loaded only when an insertion passes through it, and it's
+// checked against the merkle hash in its parent, so the merkle root computed
+// is as trustworthy as the merkle root the partial delta MPT starts from.
+
+pub struct PartialDeltaMpt {
+    root: Option<PartialNodeRef>,
+    proof_nodes: HashMap<MerkleHash, TrieProofNode>,
+}
+
+enum PartialNodeRef {
+    /// A subtree which isn't loaded.
+    Hashed(MerkleHash),
+    Loaded(Box<PartialNode>),
+}
+
+#[derive(Default)]
+struct PartialNode {
+    /// The nibbles of the compressed path.
+    path: Vec<u8>,
+    value: Option<Box<[u8]>>,
+    children: [Option<PartialNodeRef>; CHILDREN_COUNT],
+}
+
+impl PartialDeltaMpt {
+    /// `merkle_root` is the merkle root of the delta MPT, which is
+    /// `MERKLE_NULL_NODE` for an empty one, and `proof` proves the keys to be
+    /// inserted.
+    pub fn new(merkle_root: &MerkleHash, proof: Option<&TrieProof>) -> Self {
+        let root = if *merkle_root == MERKLE_NULL_NODE {
+            None
+        } else {
+            Some(PartialNodeRef::Hashed(*merkle_root))
+        };
+        let proof_nodes = proof
+            .into_iter()
+            .flat_map(|proof| proof.get_proof_nodes())
+            .map(|node| (*node.get_merkle(), node.clone()))
+            .collect();
+        Self { root, proof_nodes }
+    }
+
+    pub fn insert(&mut self, key: &[u8], value: Box<[u8]>) -> Result<()> {
+        // The delta MPT creates an empty root node on the first insertion.
+        let root = self
+            .root
+            .get_or_insert_with(|| PartialNodeRef::Loaded(Default::default()));
+        let nibbles: Vec<u8> = key
+            .iter()
+            .flat_map(|byte| {
+                [
+                    CompressedPathRaw::first_nibble(*byte),
+                    CompressedPathRaw::second_nibble(*byte),
+                ]
+            })
+            .collect();
+        root.insert(&self.proof_nodes, 0, &nibbles, value)
+    }
+
+    pub fn merkle_root(&self) -> MerkleHash {
+        self.root
+            .as_ref()
+            .map_or(MERKLE_NULL_NODE, |root| root.merkle(0))
+    }
+}
+
+impl PartialNodeRef {
+    /// `depth` is the number of nibbles from the root to the node.
+    fn insert(
+        &mut self, proof_nodes: &HashMap<MerkleHash, TrieProofNode>,
+        depth: usize, key: &[u8], value: Box<[u8]>,
+    ) -> Result<()> {
+        let node = self.load(proof_nodes, depth)?;
+        let matched = node
+            .path
+            .iter()
+            .zip(key)
+            .take_while(|(x, y)| x == y)
+            .count();
+
+        if matched == node.path.len() {
+            if matched == key.len() {
+                node.value = Some(value);
+                return Ok(());
+            }
+            let child_index = key[matched] as usize;
+            let key_remaining = &key[matched + 1..];
+            return match &mut node.children[child_index] {
+                Some(child) => child.insert(
+                    proof_nodes,
+                    depth + matched + 1,
+                    key_remaining,
+                    value,
+                ),
+                None => {
+                    node.children[child_index] =
+                        Some(PartialNode::new_leaf(key_remaining, value));
+                    Ok(())
+                }
+            };
+        }
+
+        // The path diverts, so the node is replaced by a new node with the
+        // matched path, and becomes its child with the unmatched path.
+        let unmatched_child_index = node.path[matched] as usize;
+        let fork = PartialNode {
+            path: node.path[..matched].to_vec(),
+            ..Default::default()
+        };
+        node.path = node.path.split_off(matched + 1);
+        let unmatched = std::mem::replace(node, fork);
+        node.children[unmatched_child_index] =
+            Some(PartialNodeRef::Loaded(Box::new(unmatched)));
+        if matched == key.len() {
+            node.value = Some(value);
+        } else {
+            node.children[key[matched] as usize] =
+                Some(PartialNode::new_leaf(&key[matched + 1..], value));
+        }
+        Ok(())
+    }
+
+    fn load(
+        &mut self, proof_nodes: &HashMap<MerkleHash, TrieProofNode>,
+        depth: usize,
+    ) -> Result<&mut PartialNode> {
+        if let PartialNodeRef::Hashed(merkle) = *self {
+            let proof_node = proof_nodes.get(&merkle).ok_or_else(|| {
+                Error::InvalidStateWitness(format!(
+                    "delta MPT node {:?} is missing",
+                    merkle
+                ))
+            })?;
+            let node = PartialNode::from_proof_node(proof_node);
+            if node.merkle(depth) != merkle {
+                return Err(Error::InvalidStateWitness(format!(
+                    "delta MPT node {:?} mismatches its merkle hash",
+                    merkle
+                )));
+            }
+            *self = PartialNodeRef::Loaded(Box::new(node));
+        }
+        match self {
+            PartialNodeRef::Loaded(node) => Ok(&mut **node),
+            // Loaded above.
+            PartialNodeRef::Hashed(_) => unreachable!(),
+        }
+    }
+
+    fn merkle(&self, depth: usize) -> MerkleHash {
+        match self {
+            PartialNodeRef::Hashed(merkle) => *merkle,
+            PartialNodeRef::Loaded(node) => node.merkle(depth),
+        }
+    }
+}
+
+impl PartialNode {
+    fn new_leaf(path: &[u8], value: Box<[u8]>) -> PartialNodeRef {
+        PartialNodeRef::Loaded(Box::new(Self {
+            path: path.to_vec(),
+            value: Some(value),
+            ..Default::default()
+        }))
+    }
+
+    fn from_proof_node(proof_node: &TrieProofNode) -> Self {
+        let compressed_path = proof_node.compressed_path_ref();
+        let path_slice = compressed_path.path_slice();
+        let path_mask = compressed_path.path_mask();
+        let begin = (CompressedPathRaw::second_nibble(path_mask)
+            != CompressedPathRaw::NO_MISSING_NIBBLE)
+            as usize;
+        let end = (path_slice.len() * 2).saturating_sub(
+            CompressedPathRaw::no_second_nibble(path_mask) as usize,
+        );
+        let path = (begin..end)
+            .map(|i| {
+                if i % 2 == 0 {
+                    CompressedPathRaw::first_nibble(path_slice[i / 2])
+                } else {
+                    CompressedPathRaw::second_nibble(path_slice[i / 2])
+                }
+            })
+            .collect();
+
+        let mut children: [Option<PartialNodeRef>; CHILDREN_COUNT] =
+            Default::default();
+        for (child_index, child_merkle) in
+            proof_node.get_children_table_ref().iter()
+        {
+            children[child_index as usize] =
+                Some(PartialNodeRef::Hashed(*child_merkle));
+        }
+
+        Self {
+            path,
+            value: proof_node.value_as_slice().into_option().map(Into::into),
+            children,
+        }
+    }
+
+    /// The compressed path of the node as stored in the delta MPT, where the
+    /// first nibble is missing at an odd depth, and the excluded nibbles are
+    /// zero.
+    fn compressed_path(&self, without_first_nibble: bool) -> CompressedPathRaw {
+        let mut nibbles = Vec::with_capacity(self.path.len() + 2);
+        if without_first_nibble {
+            nibbles.push(0);
+        }
+        nibbles.extend_from_slice(&self.path);
+        let without_second_nibble = nibbles.len() % 2 == 1;
+        if without_second_nibble {
+            nibbles.push(0);
+        }
+        let path_slice: Vec<u8> = nibbles
+            .chunks(2)
+            .map(|pair| {
+                CompressedPathRaw::set_second_nibble(
+                    CompressedPathRaw::from_first_nibble(pair[0]),
+                    pair[1],
+                )
+            })
+            .collect();
+        let mut path_mask = CompressedPathRaw::NO_MISSING_NIBBLE;
+        if without_first_nibble {
+            path_mask |= CompressedPathRaw::first_nibble_mask();
+        }
+        if without_second_nibble {
+            path_mask |= CompressedPathRaw::second_nibble_mask();
+        }
+        CompressedPathRaw::new(&path_slice, path_mask)
+    }
+
+    fn merkle(&self, depth: usize) -> MerkleHash {
+        let mut children_merkles = [MERKLE_NULL_NODE; CHILDREN_COUNT];
+        for (child_index, child) in self.children.iter().enumerate() {
+            if let Some(child) = child {
+                children_merkles[child_index] =
+                    child.merkle(depth + self.path.len() + 1);
+            }
+        }
+        let without_first_nibble = depth % 2 == 1;
+        compute_merkle(
+            self.compressed_path(without_first_nibble).as_ref(),
+            without_first_nibble,
+            Some(&children_merkles),
+            self.value.as_deref(),
+        )
+    }
+}
+
+use super::{
+    errors::*,
+    merkle_patricia_trie::{
+        merkle::compute_merkle, trie_proof::TrieProofNode, CompressedPathRaw,
+        CompressedPathTrait, TrieNodeTrait, TrieProof, CHILDREN_COUNT,
+    },
+};
+use primitives::{MerkleHash, MERKLE_NULL_NODE};
+use std::collections::HashMap;
@@ -0,0 +1,110 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Offline verification of the proofs returned by `cfx_getProof`.
+//!
+//! The state root of epoch `e` is committed in the deferred state root of the
+//! pivot block at height `e + DEFERRED_STATE_EPOCH_COUNT`, or of a later pivot
+//! block if the blocks in between are blamed. The deferred state root of a
+//! block with blame `b` is the vector root of the state root hashes seen by
+//! the block and its `b` ancestors on the pivot chain.
+//!
+//! The keys in the intermediate delta MPT are padded with the state root of
+//! the epoch `e - snapshot_epoch_count`, so verifying a state entry also needs
+//! that state root, unless `e <= snapshot_epoch_count`.
+
+/// The pivot block header whose deferred state root commits to the state root
+/// of an epoch, together with the state root hashes of the epochs it covers.
+#[derive(Clone, Debug, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct StateRootWitness {
+    pub header: BlockHeader,
+    /// The state root hashes seen by the pivot blocks at the heights
+    /// `header.height()`, `header.height() - 1`, ...,
+    /// `header.height() - header.blame()`.
+    pub state_root_hashes: Vec<H256>,
+}
+
+impl StateRootWitness {
+    /// Check that `state_root` is the state root of `epoch` committed by the
+    /// header. The header itself is not checked, the caller should compare
+    /// its hash with a trusted pivot block, e.g. one finalized by PoS.
+    pub fn is_valid(&self, epoch: u64, state_root: &StateRoot) -> bool {
+        let height = self.header.height();
+        let blame = self.header.blame() as u64;
+
+        if self.state_root_hashes.len() as u64 != blame + 1 {
+            return false;
+        }
+
+        // The header at `height` commits to the state of the epoch
+        // `height - DEFERRED_STATE_EPOCH_COUNT`.
+        let index = match height.checked_sub(epoch + DEFERRED_STATE_EPOCH_COUNT)
+        {
+            Some(index) if index <= blame => index,
+            _ => return false,
+        };
+        if self.state_root_hashes[index as usize]
+            != state_root.compute_state_root_hash()
+        {
+            return false;
+        }
+
+        BlockHeaderBuilder::compute_blame_state_root_vec_root(
+            self.state_root_hashes.clone(),
+        ) == *self.header.deferred_state_root()
+    }
+}
+
+/// The padding of the intermediate delta MPT keys for a state whose
+/// intermediate delta MPT was the delta MPT of `prev_snapshot_state_root`.
+pub fn intermediate_padding_of(
+    prev_snapshot_state_root: Option<&StateRoot>,
+) -> Option<DeltaMptKeyPadding> {
+    prev_snapshot_state_root.map(|root| {
+        StorageKeyWithSpace::delta_mpt_padding(
+            &root.snapshot_root,
+            &root.intermediate_delta_root,
+        )
+    })
+}
+
+/// Verify that `value` is stored under the raw storage `key` in the state with
+/// `state_root`. `value` is `None` for a key proved to be absent.
+pub fn verify_state_entry(
+    key: &[u8], value: Option<&[u8]>, proof: &StateProof,
+    state_root: &StateRoot, prev_snapshot_state_root: Option<&StateRoot>,
+) -> bool {
+    proof.is_valid_kv(
+        &key.to_vec(),
+        value,
+        state_root.clone(),
+        intermediate_padding_of(prev_snapshot_state_root),
+    )
+}
+
+/// Verify the storage root of the native space contract `address` in the
+/// state with `state_root`.
+pub fn verify_storage_root(
+    address: &Address, storage_root: &StorageRoot, proof: &NodeMerkleProof,
+    state_root: &StateRoot, prev_snapshot_state_root: Option<&StateRoot>,
+) -> bool {
+    let key = StorageKey::new_storage_root_key(address)
+        .with_native_space()
+        .to_key_bytes();
+    proof.is_valid(
+        &key,
+        storage_root,
+        state_root.clone(),
+        intermediate_padding_of(prev_snapshot_state_root),
+    )
+}
+
+use crate::{NodeMerkleProof, StateProof};
+use cfx_parameters::consensus::DEFERRED_STATE_EPOCH_COUNT;
+use cfx_types::{Address, H256};
+use primitives::{
+    BlockHeader, BlockHeaderBuilder, DeltaMptKeyPadding, StateRoot, StorageKey,
+    StorageKeyWithSpace, StorageRoot,
+};
+use rlp_derive::{RlpDecodable, RlpEncodable};
@@ -91,9 +91,203 @@ impl<Storage: StateTrait + StateTraitExt> StateTrait
     }
 }
 
+// `WitnessRecordingStorage` is a wrapper around the storage used to execute an
+// epoch, which records the key-values read and written by the execution into a
+// `StateWitness`. The reads are proved by a `RecordingStorage` of the committed
+// state of the parent epoch rather than by the storage itself, because the
+// storage may have started a new delta MPT, or may have been modified when the
+// changes of the epoch are applied. The storages of the parallel executor,
+// which fork the state of the parent epoch, share the recorder of the epoch.
+pub struct WitnessRecordingStorage {
+    storage: Box<dyn StateTrait>,
+    recorder: Arc<Mutex<StateWitnessRecorder>>,
+}
+
+pub struct StateWitnessRecorder {
+    parent: RecordingStorage<State>,
+    reads: BTreeMap<Vec<u8>, Option<Box<[u8]>>>,
+    prefix_reads: BTreeMap<Vec<u8>, Vec<MptKeyValue>>,
+    writes: BTreeMap<Vec<u8>, Option<Box<[u8]>>>,
+}
+
+impl WitnessRecordingStorage {
+    pub fn new(storage: Box<dyn StateTrait>, parent: State) -> Self {
+        let recorder = StateWitnessRecorder {
+            parent: RecordingStorage::new(parent),
+            reads: Default::default(),
+            prefix_reads: Default::default(),
+            writes: Default::default(),
+        };
+        Self::with_recorder(storage, Arc::new(Mutex::new(recorder)))
+    }
+
+    pub fn with_recorder(
+        storage: Box<dyn StateTrait>,
+        recorder: Arc<Mutex<StateWitnessRecorder>>,
+    ) -> Self {
+        Self { storage, recorder }
+    }
+
+    /// The recorder is shared so that the witness can be taken after the
+    /// storage is consumed by the commit of the epoch.
+    pub fn recorder(&self) -> Arc<Mutex<StateWitnessRecorder>> {
+        self.recorder.clone()
+    }
+}
+
+impl StateWitnessRecorder {
+    fn record_read(&mut self, access_key: StorageKeyWithSpace) -> Result<()> {
+        let key = access_key.to_key_bytes();
+        // Keys written by the epoch are read from the storage afterwards.
+        if self.reads.contains_key(&key) || self.writes.contains_key(&key) {
+            return Ok(());
+        }
+        let value = self.parent.get(access_key)?;
+        self.reads.insert(key, value);
+        Ok(())
+    }
+
+    fn record_prefix_read(
+        &mut self, access_key_prefix: StorageKeyWithSpace,
+    ) -> Result<()> {
+        let prefix = access_key_prefix.to_key_bytes();
+        if self.prefix_reads.contains_key(&prefix) {
+            return Ok(());
+        }
+        let kvs = self.parent.read_all(access_key_prefix)?;
+        self.prefix_reads.insert(prefix, kvs.unwrap_or_default());
+        Ok(())
+    }
+
+    fn record_write(&mut self, key: Vec<u8>, value: Option<Box<[u8]>>) {
+        self.writes.insert(key, value);
+    }
+
+    /// Return the witness of the recorded accesses. The state root resulting
+    /// from the writes is recomputed from the witness, and it must match the
+    /// committed `state_root` of the epoch.
+    pub fn finish(
+        self, parent_state_root: &StateRootWithAuxInfo,
+        state_root: &StateRootWithAuxInfo,
+    ) -> Result<StateWitness> {
+        // Prove the delta MPT paths of the keys written, along which the
+        // writes are applied.
+        for key in self.writes.keys() {
+            if !self.reads.contains_key(key) {
+                self.parent.get(StorageKeyWithSpace::from_key_bytes::<
+                    CheckInput,
+                >(key)?)?;
+            }
+        }
+
+        let to_entry =
+            |(key, value): (Vec<u8>, Option<Box<[u8]>>)| WitnessEntry {
+                key,
+                value: value.map(Into::into),
+            };
+        let witness = StateWitness {
+            pre_state_proof: self.parent.try_into_proof()?,
+            reads: self.reads.into_iter().map(to_entry).collect(),
+            prefix_reads: self
+                .prefix_reads
+                .into_iter()
+                .map(|(prefix, kvs)| WitnessPrefixRead {
+                    prefix,
+                    entries: kvs
+                        .into_iter()
+                        .map(|(key, value)| to_entry((key, Some(value))))
+                        .collect(),
+                })
+                .collect(),
+        };
+        let computed_state_root = witness.compute_state_root(
+            parent_state_root,
+            state_root,
+            &self.writes,
+        )?;
+        if computed_state_root != *state_root {
+            return Err(Error::InvalidStateWitness(format!(
+                "state root {:?} recomputed from the witness mismatches the \
+                 committed state root {:?}",
+                computed_state_root, state_root
+            )));
+        }
+        Ok(witness)
+    }
+}
+
+impl StateTrait for WitnessRecordingStorage {
+    delegate! {
+        to self.storage {
+            fn compute_state_root(&mut self) -> Result<StateRootWithAuxInfo>;
+            fn get_state_root(&self) -> Result<StateRootWithAuxInfo>;
+            fn commit(&mut self, epoch_id: EpochId) -> Result<StateRootWithAuxInfo>;
+        }
+    }
+
+    fn get(
+        &self, access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Box<[u8]>>> {
+        let value = self.storage.get(access_key)?;
+        self.recorder.lock().record_read(access_key)?;
+        Ok(value)
+    }
+
+    fn set(
+        &mut self, access_key: StorageKeyWithSpace, value: Box<[u8]>,
+    ) -> Result<()> {
+        self.storage.set(access_key, value.clone())?;
+        self.recorder
+            .lock()
+            .record_write(access_key.to_key_bytes(), Some(value));
+        Ok(())
+    }
+
+    fn delete(&mut self, access_key: StorageKeyWithSpace) -> Result<()> {
+        self.storage.delete(access_key)?;
+        self.recorder
+            .lock()
+            .record_write(access_key.to_key_bytes(), None);
+        Ok(())
+    }
+
+    fn delete_test_only(
+        &mut self, access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Box<[u8]>>> {
+        let value = self.storage.delete_test_only(access_key)?;
+        self.recorder
+            .lock()
+            .record_write(access_key.to_key_bytes(), None);
+        Ok(value)
+    }
+
+    fn delete_all(
+        &mut self, access_key_prefix: StorageKeyWithSpace,
+    ) -> Result<Option<Vec<MptKeyValue>>> {
+        let kvs = self.storage.delete_all(access_key_prefix)?;
+        let mut recorder = self.recorder.lock();
+        recorder.record_prefix_read(access_key_prefix)?;
+        for (key, _) in kvs.iter().flatten() {
+            recorder.record_write(key.clone(), None);
+        }
+        Ok(kvs)
+    }
+
+    fn read_all(
+        &mut self, access_key_prefix: StorageKeyWithSpace,
+    ) -> Result<Option<Vec<MptKeyValue>>> {
+        let kvs = self.storage.read_all(access_key_prefix)?;
+        self.recorder.lock().record_prefix_read(access_key_prefix)?;
+        Ok(kvs)
+    }
+}
+
 use crate::{
     impls::{
-        errors::*, merkle_patricia_trie::MptKeyValue, state_proof::StateProof,
+        errors::*,
+        merkle_patricia_trie::MptKeyValue,
+        state_proof::StateProof,
+        state_witness::{StateWitness, WitnessEntry, WitnessPrefixRead},
     },
     state::*,
     StateProofMerger,
@@ -102,3 +296,4 @@ use cfx_internal_common::StateRootWithAuxInfo;
 use delegate::delegate;
 use parking_lot::Mutex;
 use primitives::{CheckInput, EpochId, StorageKeyWithSpace};
+use std::{collections::BTreeMap, sync::Arc};
@@ -33,6 +33,13 @@ pub struct State {
     // FIXME: snapshot. it should be done in consensus.
     parent_epoch_id: EpochId,
     recover_mpt_during_construct_pivot_state: bool,
+
+    /// The flat cache of the latest executed state. Reads are served from it
+    /// when it's at `parent_epoch_id`.
+    flat_state_cache: Option<Arc<FlatStateCache>>,
+    /// The values written to this state, which are applied to the flat cache
+    /// on commit. Only recorded when the flat cache is enabled.
+    flat_state_writes: FlatStateWrites,
 }
 
 impl State {
@@ -40,6 +47,7 @@ impl State {
         manager: Arc<StateManager>, state_trees: StateTrees,
         construct_pivot_state: bool,
     ) -> Self {
+        let flat_state_cache = manager.get_flat_state_cache().cloned();
         Self {
             manager,
             snapshot_db: state_trees.snapshot_db,
@@ -62,6 +70,8 @@ impl State {
             children_merkle_map: ChildrenMerkleMap::new(),
             parent_epoch_id: state_trees.parent_epoch_id,
             recover_mpt_during_construct_pivot_state: construct_pivot_state,
+            flat_state_cache,
+            flat_state_writes: Default::default(),
         }
     }
 
@@ -236,8 +246,24 @@ impl StateTrait for State {
     ) -> Result<Option<Box<[u8]>>> {
         self.ensure_temp_slab_for_db_load();
 
-        self.get_from_all_tries::<NoProof>(access_key)
-            .map(|(value, _)| value)
+        let flat_state_cache = match &self.flat_state_cache {
+            None => {
+                return self
+                    .get_from_all_tries::<NoProof>(access_key)
+                    .map(|(value, _)| value)
+            }
+            Some(flat_state_cache) => flat_state_cache,
+        };
+        let key = access_key.to_key_bytes();
+        if let Some(value) = self.flat_state_writes.get(&key) {
+            return Ok(value.clone());
+        }
+        if let Some(value) = flat_state_cache.get(&self.parent_epoch_id, &key) {
+            return Ok(value);
+        }
+        let (value, _) = self.get_from_all_tries::<NoProof>(access_key)?;
+        flat_state_cache.insert(&self.parent_epoch_id, key, value.clone());
+        Ok(value)
     }
 
     fn set(
@@ -245,6 +271,18 @@ impl StateTrait for State {
     ) -> Result<()> {
         self.pre_modification();
 
+        if self.flat_state_cache.is_some() {
+            // An empty value is a tombstone.
+            self.flat_state_writes.insert(
+                access_key.to_key_bytes(),
+                if value.is_empty() {
+                    None
+                } else {
+                    Some(value.clone())
+                },
+            );
+        }
+
         let root_node = self.get_or_create_delta_root_node()?;
         self.delta_trie_root = SubTrieVisitor::new(
             &self.delta_trie,
@@ -269,6 +307,10 @@ impl StateTrait for State {
         &mut self, access_key: StorageKeyWithSpace,
     ) -> Result<Option<Box<[u8]>>> {
         self.pre_modification();
+        // The key is removed from the delta trie rather than set to a
+        // tombstone, so the value of the key isn't known without reading the
+        // other tries.
+        self.flat_state_cache = None;
 
         match self.get_delta_root_node() {
             None => Ok(None),
@@ -328,6 +370,7 @@ impl StateTrait for State {
 
         let merkle_root = self.state_root_check()?;
 
+        let flat_state_writes = mem::take(&mut self.flat_state_writes);
         // TODO(yz): Think about leaving these node dirty and only commit when
         // the dirty node is removed from cache.
         let commit_result = self.do_db_commit(epoch_id, &merkle_root);
@@ -351,6 +394,13 @@ impl StateTrait for State {
 
             commit_result?;
         }
+        if let Some(flat_state_cache) = &self.flat_state_cache {
+            flat_state_cache.commit(
+                &self.parent_epoch_id,
+                &epoch_id,
+                flat_state_writes,
+            );
+        }
         if self.delta_trie_height.unwrap()
             >= self
                 .manager
@@ -794,6 +844,130 @@ impl State {
         inserter.iterate(dumper)
     }
 
+    /// Read the key-values under `access_key_prefix`, or in the whole state
+    /// if it's None, in the order of their key bytes from `start_key`.
+    ///
+    /// `visit` is called on each key-value read and returns the key to
+    /// continue the read from, which must be greater than the key visited, or
+    /// None to stop. It lets the caller skip over the key-values it isn't
+    /// interested in, e.g. the storage of an account when only the accounts
+    /// are read.
+    ///
+    /// The snapshot is seeked for each key to continue from. The tries aren't
+    /// ordered by the key bytes, so at most `page_size` of their key-values
+    /// from the key to continue from are kept at a time, and they are read
+    /// again when the read goes past them.
+    pub fn read_range(
+        &mut self, access_key_prefix: Option<StorageKeyWithSpace>,
+        start_key: &[u8], page_size: usize,
+        mut visit: impl FnMut(&[u8], &[u8]) -> Option<Vec<u8>>,
+    ) -> Result<()> {
+        self.ensure_temp_slab_for_db_load();
+
+        let maybe_intermediate_trie = match (
+            &self.maybe_intermediate_trie,
+            &self.intermediate_trie_root,
+            &self.maybe_intermediate_trie_key_padding,
+        ) {
+            (Some(trie), Some(root), Some(padding)) => {
+                Some((trie.clone(), root.clone(), padding.clone()))
+            }
+            _ => None,
+        };
+        let maybe_delta_trie = self.delta_trie_root.clone().map(|root| {
+            (
+                self.delta_trie.clone(),
+                root,
+                self.delta_trie_key_padding.clone(),
+            )
+        });
+
+        // The delta trie is the last to override the intermediate trie.
+        let tries: Vec<_> = maybe_intermediate_trie
+            .into_iter()
+            .chain(maybe_delta_trie)
+            .collect();
+
+        let key_prefix =
+            access_key_prefix.map_or(vec![], |prefix| prefix.to_key_bytes());
+        let upper_bound_excl = to_key_prefix_iter_upper_bound(&key_prefix);
+        let mut kv_iterator = self.snapshot_db.snapshot_kv_iterator()?.take();
+        let mut trie_kvs: Option<TrieRangeBuffer> = None;
+        let mut next_key =
+            Some(std::cmp::max(start_key, key_prefix.as_slice()).to_vec());
+        while let Some(lower_bound_incl) = next_key.take() {
+            let snapshot_kv = kv_iterator
+                .iter_range(
+                    lower_bound_incl.as_slice(),
+                    upper_bound_excl.as_ref().map(|v| &**v),
+                )?
+                .take()
+                .next()?;
+
+            if !trie_kvs
+                .as_ref()
+                .map_or(false, |kvs| kvs.covers(&lower_bound_incl))
+            {
+                trie_kvs = Some(Self::read_trie_range(
+                    &tries,
+                    &mut self.owned_node_set,
+                    access_key_prefix,
+                    lower_bound_incl.clone(),
+                    page_size,
+                )?);
+            }
+            let trie_kv = trie_kvs
+                .as_ref()
+                .and_then(|kvs| kvs.kvs.range(lower_bound_incl..).next());
+
+            // The key-values in the intermediate trie and the delta trie
+            // override the ones in the snapshot, and an empty value is a
+            // deletion.
+            let (key, value) = match (snapshot_kv, trie_kv) {
+                (Some((key, value)), Some((trie_key, _)))
+                    if key < *trie_key =>
+                {
+                    (key, value)
+                }
+                (_, Some((key, value))) => (key.clone(), value.clone()),
+                (Some(kv), None) => kv,
+                (None, None) => break,
+            };
+            if value.is_empty() {
+                next_key = Some([key.as_slice(), &[0]].concat());
+            } else {
+                next_key = visit(&key, &value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read the smallest `capacity` key-values of `tries` under
+    /// `access_key_prefix` from `lower_bound_incl`, where the later tries
+    /// override the earlier ones.
+    fn read_trie_range(
+        tries: &[(Arc<DeltaMpt>, NodeRefDeltaMpt, DeltaMptKeyPadding)],
+        owned_node_set: &mut Option<OwnedNodeSet>,
+        access_key_prefix: Option<StorageKeyWithSpace>,
+        lower_bound_incl: Vec<u8>, capacity: usize,
+    ) -> Result<TrieRangeBuffer> {
+        let mut kvs = TrieRangeBuffer {
+            lower_bound_incl,
+            capacity: std::cmp::max(capacity, 1),
+            kvs: BTreeMap::new(),
+            truncated: false,
+        };
+        for (trie, root, padding) in tries {
+            let mpt_key_prefix = access_key_prefix.map_or(vec![], |prefix| {
+                prefix.to_delta_mpt_key_bytes(padding)
+            });
+            SubTrieVisitor::new(trie, root.clone(), owned_node_set)?
+                .traversal_with(&mpt_key_prefix, &mpt_key_prefix, &mut kvs)?;
+        }
+        Ok(kvs)
+    }
+
     fn revert(&mut self) {
         self.dirty = false;
 
@@ -946,6 +1120,15 @@ impl State {
             }
         }
 
+        // The keys deleted from the delta trie are removed rather than set to
+        // tombstones, while their values in the other tries are set to
+        // tombstones above, so they are all deleted in the end.
+        if !AM::READ_ONLY && self.flat_state_cache.is_some() {
+            for key in deleted_keys {
+                self.flat_state_writes.insert(key, None);
+            }
+        }
+
         if result.is_empty() {
             Ok(None)
         } else {
@@ -954,10 +1137,59 @@ impl State {
     }
 }
 
+/// The smallest key-values of the tries from `lower_bound_incl` in the order
+/// of the key bytes, up to `capacity` of them.
+struct TrieRangeBuffer {
+    lower_bound_incl: Vec<u8>,
+    capacity: usize,
+    kvs: BTreeMap<Vec<u8>, Box<[u8]>>,
+    /// Whether there are key-values greater than the ones kept.
+    truncated: bool,
+}
+
+impl TrieRangeBuffer {
+    /// Whether all the key-values of the tries from `key` to the last one
+    /// kept are kept.
+    fn covers(&self, key: &[u8]) -> bool {
+        key >= self.lower_bound_incl.as_slice()
+            && (!self.truncated
+                || self
+                    .kvs
+                    .last_key_value()
+                    .map_or(false, |(last, _)| key <= last.as_slice()))
+    }
+}
+
+impl KVInserter<MptKeyValue> for TrieRangeBuffer {
+    fn push(&mut self, (mpt_key, value): MptKeyValue) -> Result<()> {
+        let key =
+            StorageKeyWithSpace::from_delta_mpt_key(&mpt_key).to_key_bytes();
+        if key < self.lower_bound_incl {
+            return Ok(());
+        }
+        if self.kvs.len() == self.capacity
+            && self
+                .kvs
+                .last_key_value()
+                .map_or(false, |(last, _)| *last < key)
+        {
+            self.truncated = true;
+            return Ok(());
+        }
+        self.kvs.insert(key, value);
+        if self.kvs.len() > self.capacity {
+            self.kvs.pop_last();
+            self.truncated = true;
+        }
+        Ok(())
+    }
+}
+
 use crate::{
     impls::{
         delta_mpt::{node_memory_manager::ActualSlabIndex, *},
         errors::*,
+        flat_state_cache::{FlatStateCache, FlatStateWrites},
         merkle_patricia_trie::{
             mpt_cursor::{BasicPathNode, CursorOpenPathTerminal, MptCursor},
             KVInserter, MptKeyValue, TrieProof, VanillaChildrenTable,
@@ -984,5 +1216,6 @@ use std::{
     cell::UnsafeCell,
     collections::{BTreeMap, HashSet},
     hint::unreachable_unchecked,
+    mem,
     sync::{atomic::Ordering, Arc},
 };
@@ -2,8 +2,8 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
-pub type DeltaDbManager = DeltaDbManagerRocksdb;
-pub type SnapshotDbManager = SnapshotDbManagerSqlite;
+pub type DeltaDbManager = DeltaDbManagerDispatch;
+pub type SnapshotDbManager = SnapshotDbManagerDispatch;
 pub type SnapshotDb = <SnapshotDbManager as SnapshotDbManagerTrait>::SnapshotDb;
 
 pub struct StateTrees {
@@ -37,6 +37,12 @@ pub struct StateTrees {
 pub struct StateManager {
     storage_manager: Arc<StorageManager>,
     single_mpt_storage_manager: Option<Arc<SingleMptStorageManager>>,
+    /// When set, every state is a [`ForkedState`] reading missing keys from
+    /// the forked chain.
+    #[ignore_malloc_size_of = "insignificant"]
+    fork: Option<Arc<ForkStateCache>>,
+    #[ignore_malloc_size_of = "bounded by flat_state_cache_size"]
+    flat_state_cache: Option<Arc<FlatStateCache>>,
     pub number_committed_nodes: AtomicUsize,
 }
 
@@ -64,22 +70,112 @@ impl StateManager {
             None
         };
 
+        let flat_state_cache = if conf.flat_state_cache_size > 0 {
+            Some(Arc::new(FlatStateCache::new(conf.flat_state_cache_size)))
+        } else {
+            None
+        };
+
         let storage_manager = StorageManager::new_arc(conf)?;
         Ok(Self {
             storage_manager,
             single_mpt_storage_manager,
+            fork: None,
+            flat_state_cache,
             number_committed_nodes: Default::default(),
         })
     }
 
+    /// Makes all the states read the keys which are missing locally from
+    /// `source`, so that the local chain runs on top of the forked state.
+    pub fn with_fork_source(
+        mut self, source: Box<dyn ForkStateSource>,
+    ) -> Self {
+        self.fork = Some(Arc::new(ForkStateCache::new(
+            source,
+            DEFAULT_FORK_STATE_CACHE_ENTRIES,
+        )));
+        self
+    }
+
+    pub fn is_forked(&self) -> bool { self.fork.is_some() }
+
+    /// Wraps `state`, which starts from the state of `epoch_id` and commits
+    /// the epoch at `height`, in a [`ForkedState`] if the chain is forked.
+    fn maybe_forked(
+        &self, state: Box<dyn StateTrait>, epoch_id: Option<&EpochId>,
+        height: Option<u64>,
+    ) -> Box<dyn StateTrait> {
+        match &self.fork {
+            Some(fork) => Box::new(ForkedState::new(
+                state,
+                fork.clone(),
+                epoch_id,
+                height,
+            )),
+            None => state,
+        }
+    }
+
+    /// Removes the state below the maintained height, see
+    /// `StorageManager::maintain_state_confirmed`, and the keys deleted from
+    /// the forked state in the removed epochs.
+    pub fn maintain_state_confirmed<ConsensusInner: StateMaintenanceTrait>(
+        &self, consensus_inner: &ConsensusInner, stable_checkpoint_height: u64,
+        era_epoch_count: u64, confirmed_height: u64,
+        state_availability_boundary: &RwLock<StateAvailabilityBoundary>,
+    ) -> Result<()> {
+        self.storage_manager.maintain_state_confirmed(
+            consensus_inner,
+            stable_checkpoint_height,
+            era_epoch_count,
+            confirmed_height,
+            state_availability_boundary,
+        )?;
+        if let Some(fork) = &self.fork {
+            fork.prune_deleted_keys(
+                state_availability_boundary.read().lower_bound,
+            );
+        }
+        Ok(())
+    }
+
     pub fn log_usage(&self) {
         self.storage_manager.log_usage();
+        if let Some(flat_state_cache) = &self.flat_state_cache {
+            flat_state_cache.log_usage();
+        }
         debug!(
             "number of nodes committed to db {}",
             self.number_committed_nodes.load(Ordering::Relaxed),
         );
     }
 
+    pub(super) fn get_flat_state_cache(&self) -> Option<&Arc<FlatStateCache>> {
+        self.flat_state_cache.as_ref()
+    }
+
+    /// Copy the mutable storage databases into `dest`, which has the layout
+    /// of the conflux data dir, and pin the snapshots for
+    /// `checkpoint_snapshots`. See `StorageManager::checkpoint`.
+    pub fn checkpoint(
+        &self, dest: &Path,
+    ) -> Result<(StorageCheckpoint, PinnedSnapshots)> {
+        let checkpoint = self.storage_manager.checkpoint(dest)?;
+        if let Some(single_mpt_manager) = &self.single_mpt_storage_manager {
+            single_mpt_manager.checkpoint(
+                &dest.join(&*storage_dir::STORAGE_DIR).join("single_mpt"),
+            )?;
+        }
+        Ok(checkpoint)
+    }
+
+    pub fn checkpoint_snapshots(
+        &self, pinned_snapshots: PinnedSnapshots,
+    ) -> Result<()> {
+        self.storage_manager.checkpoint_snapshots(pinned_snapshots)
+    }
+
     pub fn get_storage_manager(&self) -> &StorageManager {
         &*self.storage_manager
     }
@@ -718,8 +814,8 @@ impl StateManager {
     }
 }
 
-impl StateManagerTrait for StateManager {
-    fn get_state_no_commit(
+impl StateManager {
+    fn get_state_no_commit_unforked(
         self: &Arc<Self>, state_index: StateIndex, try_open: bool,
         space: Option<Space>,
     ) -> Result<Option<Box<dyn StateTrait>>> {
@@ -760,7 +856,9 @@ impl StateManagerTrait for StateManager {
         }
     }
 
-    fn get_state_for_genesis_write(self: &Arc<Self>) -> Box<dyn StateTrait> {
+    fn get_state_for_genesis_write_unforked(
+        self: &Arc<Self>,
+    ) -> Box<dyn StateTrait> {
         let state = self.get_state_for_genesis_write_inner();
         if self.single_mpt_storage_manager.is_none() {
             return Box::new(state);
@@ -791,7 +889,7 @@ impl StateManagerTrait for StateManager {
     //
     // Due to the complexity of the latter approach, we stay with the
     // simple approach.
-    fn get_state_for_next_epoch(
+    fn get_state_for_next_epoch_unforked(
         self: &Arc<Self>, parent_epoch_id: StateIndex,
         recover_mpt_during_construct_pivot_state: bool,
     ) -> Result<Option<Box<dyn StateTrait>>> {
@@ -842,33 +940,83 @@ impl StateManagerTrait for StateManager {
     }
 }
 
+impl StateManagerTrait for StateManager {
+    fn get_state_no_commit(
+        self: &Arc<Self>, state_index: StateIndex, try_open: bool,
+        space: Option<Space>,
+    ) -> Result<Option<Box<dyn StateTrait>>> {
+        let epoch_id = state_index.epoch_id;
+        let height = state_index.maybe_height;
+        Ok(self
+            .get_state_no_commit_unforked(state_index, try_open, space)?
+            .map(|state| self.maybe_forked(state, Some(&epoch_id), height)))
+    }
+
+    fn get_state_for_genesis_write(self: &Arc<Self>) -> Box<dyn StateTrait> {
+        self.maybe_forked(
+            self.get_state_for_genesis_write_unforked(),
+            None,
+            Some(0),
+        )
+    }
+
+    fn get_state_for_next_epoch(
+        self: &Arc<Self>, parent_epoch_id: StateIndex,
+        recover_mpt_during_construct_pivot_state: bool,
+    ) -> Result<Option<Box<dyn StateTrait>>> {
+        let parent_epoch = parent_epoch_id.epoch_id;
+        let height = parent_epoch_id.maybe_height.map(|height| height + 1);
+        Ok(self
+            .get_state_for_next_epoch_unforked(
+                parent_epoch_id,
+                recover_mpt_during_construct_pivot_state,
+            )?
+            .map(|state| self.maybe_forked(state, Some(&parent_epoch), height)))
+    }
+}
+
 use crate::{
     impls::{
         delta_mpt::*,
         errors::*,
+        flat_state_cache::FlatStateCache,
+        forked_state::{
+            ForkStateCache, ForkStateSource, ForkedState,
+            DEFAULT_FORK_STATE_CACHE_ENTRIES,
+        },
         replicated_state::ReplicatedState,
         storage_db::{
-            delta_db_manager_rocksdb::DeltaDbManagerRocksdb,
-            snapshot_db_manager_sqlite::SnapshotDbManagerSqlite,
+            delta_db_manager_dispatch::DeltaDbManagerDispatch,
+            snapshot_db_manager_dispatch::SnapshotDbManagerDispatch,
         },
         storage_manager::{
             single_mpt_storage_manager::SingleMptStorageManager,
-            storage_manager::StorageManager,
+            storage_manager::{
+                PinnedSnapshots, StorageCheckpoint, StorageManager,
+            },
         },
     },
     state::*,
     state_manager::*,
     storage_db::*,
+    storage_dir,
     utils::guarded_value::GuardedValue,
     StorageConfiguration,
 };
+use cfx_internal_common::{
+    consensus_api::StateMaintenanceTrait, StateAvailabilityBoundary,
+};
 use cfx_types::Space;
 use malloc_size_of_derive::MallocSizeOf as MallocSizeOfDerive;
+use parking_lot::RwLock;
 use primitives::{
     DeltaMptKeyPadding, EpochId, MerkleHash, StorageKeyWithSpace,
     GENESIS_DELTA_MPT_KEY_PADDING, MERKLE_NULL_NODE, NULL_EPOCH,
 };
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
 };
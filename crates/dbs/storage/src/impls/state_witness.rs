@@ -0,0 +1,284 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+// `StateWitness` contains the key-values read by the execution of an epoch,
+// which are proved by a merged proof against the state root of the parent
+// epoch. The proof also contains the delta MPT paths of the keys written by
+// the epoch, so that the resulting state root is recomputed by applying the
+// writes to the delta MPT nodes of the proof. `WitnessStorage` serves the
+// re-execution of the epoch from a `StateWitness` alone.
+//
+// Note that the completeness of the key-values returned by a prefix read can't
+// be proved by merged proofs, only their existence.
+
+#[derive(Clone, Debug, Default, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct WitnessEntry {
+    pub key: Vec<u8>,
+    pub value: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct WitnessPrefixRead {
+    pub prefix: Vec<u8>,
+    pub entries: Vec<WitnessEntry>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct StateWitness {
+    pub pre_state_proof: StateProof,
+    pub reads: Vec<WitnessEntry>,
+    pub prefix_reads: Vec<WitnessPrefixRead>,
+}
+
+impl WitnessEntry {
+    fn is_proved_by(
+        &self, proof: &StateProof, root: &StateRootWithAuxInfo,
+    ) -> bool {
+        proof.is_valid_kv(
+            &self.key,
+            self.value.as_deref(),
+            root.state_root.clone(),
+            root.aux_info.maybe_intermediate_mpt_key_padding.clone(),
+        )
+    }
+}
+
+impl StateWitness {
+    /// Check the key-values read against the state root of the parent epoch.
+    pub fn verify_reads(
+        &self, parent_state_root: &StateRootWithAuxInfo,
+    ) -> Result<()> {
+        let prefix_entries = self
+            .prefix_reads
+            .iter()
+            .flat_map(|read| read.entries.iter());
+        for entry in self.reads.iter().chain(prefix_entries) {
+            if !entry.is_proved_by(&self.pre_state_proof, parent_state_root) {
+                return Err(Error::InvalidStateWitness(format!(
+                    "read of key {:?} is not proved",
+                    entry.key
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the state root resulting from the `writes` of the epoch on
+    /// top of the state of the parent epoch, where a deleted key is `None`.
+    /// The delta MPT is recomputed from the nodes in the proof of the witness,
+    /// while the snapshot root and the intermediate delta root are taken from
+    /// `state_root`. They must either be the ones of the parent epoch, or
+    /// shift the snapshot with the delta MPT of the parent epoch as the new
+    /// intermediate delta MPT.
+    pub fn compute_state_root(
+        &self, parent_state_root: &StateRootWithAuxInfo,
+        state_root: &StateRootWithAuxInfo,
+        writes: &BTreeMap<Vec<u8>, Option<Box<[u8]>>>,
+    ) -> Result<StateRootWithAuxInfo> {
+        let parent_root = &parent_state_root.state_root;
+        let root = &state_root.state_root;
+        let mut delta_mpt = if root.snapshot_root == parent_root.snapshot_root
+            && root.intermediate_delta_root
+                == parent_root.intermediate_delta_root
+        {
+            PartialDeltaMpt::new(
+                &parent_root.delta_root,
+                self.pre_state_proof.delta_proof.as_ref(),
+            )
+        } else if root.intermediate_delta_root == parent_root.delta_root {
+            // A new delta MPT is started when the snapshot is shifted.
+            PartialDeltaMpt::new(&MERKLE_NULL_NODE, None)
+        } else {
+            return Err(Error::InvalidStateWitness(
+                "state root neither follows nor shifts the parent state root"
+                    .into(),
+            ));
+        };
+
+        let padding = StorageKeyWithSpace::delta_mpt_padding(
+            &root.snapshot_root,
+            &root.intermediate_delta_root,
+        );
+        for (key, value) in writes {
+            let access_key =
+                StorageKeyWithSpace::from_key_bytes::<CheckInput>(key)?;
+            // A deleted key is set to a tombstone in the delta MPT.
+            let value = value
+                .clone()
+                .unwrap_or_else(|| MptValue::<Box<[u8]>>::TombStone.unwrap());
+            delta_mpt
+                .insert(&access_key.to_delta_mpt_key_bytes(&padding), value)?;
+        }
+
+        let mut computed_state_root = state_root.clone();
+        computed_state_root.state_root.delta_root = delta_mpt.merkle_root();
+        computed_state_root.aux_info.state_root_hash =
+            computed_state_root.state_root.compute_state_root_hash();
+        Ok(computed_state_root)
+    }
+}
+
+/// A storage serving the re-execution of an epoch from its witness. The
+/// witness is checked against the state root of the parent epoch on
+/// construction. When the state root is computed, it's recomputed from the
+/// writes of the re-execution and checked against the resulting state root
+/// claimed, which must in turn be checked against the block header.
+pub struct WitnessStorage {
+    reads: HashMap<Vec<u8>, Option<Box<[u8]>>>,
+    prefix_reads: HashMap<Vec<u8>, Vec<MptKeyValue>>,
+    writes: BTreeMap<Vec<u8>, Option<Box<[u8]>>>,
+    witness: StateWitness,
+    parent_state_root: StateRootWithAuxInfo,
+    state_root: StateRootWithAuxInfo,
+}
+
+impl WitnessStorage {
+    pub fn new(
+        witness: StateWitness, parent_state_root: &StateRootWithAuxInfo,
+        state_root: StateRootWithAuxInfo,
+    ) -> Result<Self> {
+        witness.verify_reads(parent_state_root)?;
+
+        let reads = witness
+            .reads
+            .iter()
+            .map(|entry| {
+                (entry.key.clone(), entry.value.clone().map(Into::into))
+            })
+            .collect();
+        let prefix_reads = witness
+            .prefix_reads
+            .iter()
+            .map(|read| {
+                let kvs = read
+                    .entries
+                    .iter()
+                    .filter_map(|entry| {
+                        Some((entry.key.clone(), entry.value.clone()?.into()))
+                    })
+                    .collect();
+                (read.prefix.clone(), kvs)
+            })
+            .collect();
+
+        Ok(Self {
+            reads,
+            prefix_reads,
+            writes: Default::default(),
+            witness,
+            parent_state_root: parent_state_root.clone(),
+            state_root,
+        })
+    }
+}
+
+impl StateTrait for WitnessStorage {
+    fn get(
+        &self, access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Box<[u8]>>> {
+        let key = access_key.to_key_bytes();
+        if let Some(value) = self.writes.get(&key) {
+            return Ok(value.clone());
+        }
+        match self.reads.get(&key) {
+            Some(value) => Ok(value.clone()),
+            None => Err(Error::StateWitnessKeyNotFound(key)),
+        }
+    }
+
+    fn set(
+        &mut self, access_key: StorageKeyWithSpace, value: Box<[u8]>,
+    ) -> Result<()> {
+        self.writes.insert(access_key.to_key_bytes(), Some(value));
+        Ok(())
+    }
+
+    fn delete(&mut self, access_key: StorageKeyWithSpace) -> Result<()> {
+        self.writes.insert(access_key.to_key_bytes(), None);
+        Ok(())
+    }
+
+    fn delete_test_only(
+        &mut self, access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Box<[u8]>>> {
+        let value = self.get(access_key)?;
+        self.delete(access_key)?;
+        Ok(value)
+    }
+
+    /// The storage removes the keys from the delta MPT rather than setting
+    /// tombstones, which the delta MPT nodes of the witness can't follow. The
+    /// execution deletes keys one by one instead.
+    fn delete_all(
+        &mut self, _access_key_prefix: StorageKeyWithSpace,
+    ) -> Result<Option<Vec<MptKeyValue>>> {
+        Err(Error::InvalidStateWitness(
+            "delete_all can't be re-executed from a witness".into(),
+        ))
+    }
+
+    fn read_all(
+        &mut self, access_key_prefix: StorageKeyWithSpace,
+    ) -> Result<Option<Vec<MptKeyValue>>> {
+        let prefix = access_key_prefix.to_key_bytes();
+        let mut kvs: BTreeMap<_, _> = self
+            .prefix_reads
+            .get(&prefix)
+            .ok_or_else(|| Error::StateWitnessKeyNotFound(prefix.clone()))?
+            .iter()
+            .cloned()
+            .collect();
+        // Apply the writes of the epoch so far, as the storage does.
+        for (key, value) in self.writes.range(prefix.clone()..) {
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            match value {
+                Some(value) => kvs.insert(key.clone(), value.clone()),
+                None => kvs.remove(key),
+            };
+        }
+        if kvs.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(kvs.into_iter().collect()))
+        }
+    }
+
+    fn compute_state_root(&mut self) -> Result<StateRootWithAuxInfo> {
+        self.get_state_root()
+    }
+
+    fn get_state_root(&self) -> Result<StateRootWithAuxInfo> {
+        let state_root = self.witness.compute_state_root(
+            &self.parent_state_root,
+            &self.state_root,
+            &self.writes,
+        )?;
+        if state_root != self.state_root {
+            return Err(Error::InvalidStateWitness(
+                "state root of the execution mismatches the witness".into(),
+            ));
+        }
+        Ok(state_root)
+    }
+
+    fn commit(&mut self, _epoch_id: EpochId) -> Result<StateRootWithAuxInfo> {
+        self.get_state_root()
+    }
+}
+
+use crate::{
+    impls::{
+        errors::*, merkle_patricia_trie::MptKeyValue,
+        partial_delta_mpt::PartialDeltaMpt, state_proof::StateProof,
+    },
+    state::*,
+};
+use cfx_internal_common::StateRootWithAuxInfo;
+use primitives::{
+    CheckInput, EpochId, MptValue, StorageKeyWithSpace, MERKLE_NULL_NODE,
+};
+use rlp_derive::{RlpDecodable, RlpEncodable};
+use std::collections::{BTreeMap, HashMap};
@@ -0,0 +1,281 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+/// The delta db manager of the storage backend chosen by
+/// `snapshot_db_backend`. The delta dbs are kept in RocksDB for the on-disk
+/// snapshot backends, and in memory for the in-memory backend.
+pub enum DeltaDbManagerDispatch {
+    Rocksdb(DeltaDbManagerRocksdb),
+    Inmemory(DeltaDbManagerInmemory),
+}
+
+/// A delta db opened by `DeltaDbManagerDispatch`.
+pub enum DeltaDbDispatch {
+    Rocksdb(KvdbRocksdb),
+    Inmemory(KvdbInmemory),
+}
+
+pub enum DeltaDbTransactionDispatch {
+    Rocksdb(KvdbRocksDbTransaction),
+    Inmemory(KvdbInmemoryTransaction),
+}
+
+impl DeltaDbManagerDispatch {
+    pub fn new(
+        backend: SnapshotDbBackend, delta_db_path: PathBuf,
+    ) -> Result<Self> {
+        Ok(match backend {
+            SnapshotDbBackend::Sqlite | SnapshotDbBackend::Rocksdb => {
+                Self::Rocksdb(DeltaDbManagerRocksdb::new(delta_db_path)?)
+            }
+            SnapshotDbBackend::Inmemory => {
+                Self::Inmemory(DeltaDbManagerInmemory::new(delta_db_path)?)
+            }
+        })
+    }
+}
+
+impl DeltaDbManagerTrait for DeltaDbManagerDispatch {
+    type DeltaDb = DeltaDbDispatch;
+
+    fn get_delta_db_dir(&self) -> &Path {
+        match self {
+            Self::Rocksdb(manager) => manager.get_delta_db_dir(),
+            Self::Inmemory(manager) => manager.get_delta_db_dir(),
+        }
+    }
+
+    fn get_delta_db_name(&self, snapshot_epoch_id: &EpochId) -> String {
+        match self {
+            Self::Rocksdb(manager) => {
+                manager.get_delta_db_name(snapshot_epoch_id)
+            }
+            Self::Inmemory(manager) => {
+                manager.get_delta_db_name(snapshot_epoch_id)
+            }
+        }
+    }
+
+    fn get_delta_db_path(&self, delta_db_name: &str) -> PathBuf {
+        match self {
+            Self::Rocksdb(manager) => manager.get_delta_db_path(delta_db_name),
+            Self::Inmemory(manager) => manager.get_delta_db_path(delta_db_name),
+        }
+    }
+
+    fn scan_persist_state(
+        &self, snapshot_info_map: &HashMap<EpochId, SnapshotInfo>,
+    ) -> Result<(Vec<EpochId>, HashMap<EpochId, Self::DeltaDb>)> {
+        Ok(match self {
+            Self::Rocksdb(manager) => {
+                let (missing_delta_dbs, delta_mpts) =
+                    manager.scan_persist_state(snapshot_info_map)?;
+                (
+                    missing_delta_dbs,
+                    delta_mpts
+                        .into_iter()
+                        .map(|(epoch, db)| {
+                            (epoch, DeltaDbDispatch::Rocksdb(db))
+                        })
+                        .collect(),
+                )
+            }
+            Self::Inmemory(manager) => {
+                let (missing_delta_dbs, delta_mpts) =
+                    manager.scan_persist_state(snapshot_info_map)?;
+                (
+                    missing_delta_dbs,
+                    delta_mpts
+                        .into_iter()
+                        .map(|(epoch, db)| {
+                            (epoch, DeltaDbDispatch::Inmemory(db))
+                        })
+                        .collect(),
+                )
+            }
+        })
+    }
+
+    fn new_empty_delta_db(&self, delta_db_name: &str) -> Result<Self::DeltaDb> {
+        Ok(match self {
+            Self::Rocksdb(manager) => DeltaDbDispatch::Rocksdb(
+                manager.new_empty_delta_db(delta_db_name)?,
+            ),
+            Self::Inmemory(manager) => DeltaDbDispatch::Inmemory(
+                manager.new_empty_delta_db(delta_db_name)?,
+            ),
+        })
+    }
+
+    fn get_delta_db(
+        &self, delta_db_name: &str,
+    ) -> Result<Option<Self::DeltaDb>> {
+        Ok(match self {
+            Self::Rocksdb(manager) => manager
+                .get_delta_db(delta_db_name)?
+                .map(DeltaDbDispatch::Rocksdb),
+            Self::Inmemory(manager) => manager
+                .get_delta_db(delta_db_name)?
+                .map(DeltaDbDispatch::Inmemory),
+        })
+    }
+
+    fn destroy_delta_db(&self, delta_db_name: &str) -> Result<()> {
+        match self {
+            Self::Rocksdb(manager) => manager.destroy_delta_db(delta_db_name),
+            Self::Inmemory(manager) => manager.destroy_delta_db(delta_db_name),
+        }
+    }
+}
+
+impl KeyValueDbTypes for DeltaDbDispatch {
+    type ValueType = Box<[u8]>;
+}
+
+impl KeyValueDbTraitRead for DeltaDbDispatch {
+    fn get(&self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        match self {
+            Self::Rocksdb(kvdb) => kvdb.get(key),
+            Self::Inmemory(kvdb) => kvdb.get(key),
+        }
+    }
+}
+
+mark_kvdb_multi_reader!(DeltaDbDispatch);
+
+impl KeyValueDbTraitTransactional for DeltaDbDispatch {
+    type TransactionType = DeltaDbTransactionDispatch;
+
+    fn start_transaction(
+        &self, immediate_write: bool,
+    ) -> Result<Self::TransactionType> {
+        Ok(match self {
+            Self::Rocksdb(kvdb) => DeltaDbTransactionDispatch::Rocksdb(
+                kvdb.start_transaction(immediate_write)?,
+            ),
+            Self::Inmemory(kvdb) => DeltaDbTransactionDispatch::Inmemory(
+                kvdb.start_transaction(immediate_write)?,
+            ),
+        })
+    }
+}
+
+impl DeltaDbTrait for DeltaDbDispatch {
+    fn checkpoint(&self, path: &Path) -> Result<()> {
+        match self {
+            Self::Rocksdb(kvdb) => kvdb.checkpoint(path),
+            Self::Inmemory(kvdb) => kvdb.checkpoint(path),
+        }
+    }
+}
+
+impl MallocSizeOf for DeltaDbDispatch {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        match self {
+            Self::Rocksdb(kvdb) => kvdb.size_of(ops),
+            Self::Inmemory(kvdb) => kvdb.size_of(ops),
+        }
+    }
+}
+
+impl KeyValueDbTypes for DeltaDbTransactionDispatch {
+    type ValueType = Box<[u8]>;
+}
+
+impl KeyValueDbTraitSingleWriter for DeltaDbTransactionDispatch {
+    fn delete(&mut self, key: &[u8]) -> Result<Option<Option<Box<[u8]>>>> {
+        match self {
+            Self::Rocksdb(transaction) => transaction.delete(key),
+            Self::Inmemory(transaction) => transaction.delete(key),
+        }
+    }
+
+    fn put(
+        &mut self, key: &[u8], value: &[u8],
+    ) -> Result<Option<Option<Box<[u8]>>>> {
+        match self {
+            Self::Rocksdb(transaction) => transaction.put(key, value),
+            Self::Inmemory(transaction) => transaction.put(key, value),
+        }
+    }
+}
+
+impl KeyValueDbTraitOwnedRead for DeltaDbTransactionDispatch {
+    fn get_mut(&mut self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        match self {
+            Self::Rocksdb(transaction) => transaction.get_mut(key),
+            Self::Inmemory(transaction) => transaction.get_mut(key),
+        }
+    }
+}
+
+impl KeyValueDbTransactionTrait for DeltaDbTransactionDispatch {
+    /// The transaction of each backend downcasts `db` to its own kvdb.
+    fn commit(&mut self, db: &dyn Any) -> Result<()> {
+        match (self, db.downcast_ref::<DeltaDbDispatch>()) {
+            (
+                Self::Rocksdb(transaction),
+                Some(DeltaDbDispatch::Rocksdb(kvdb)),
+            ) => transaction.commit(kvdb),
+            (
+                Self::Inmemory(transaction),
+                Some(DeltaDbDispatch::Inmemory(kvdb)),
+            ) => transaction.commit(kvdb),
+            _ => {
+                unreachable!();
+            }
+        }
+    }
+
+    fn revert(&mut self) -> Result<()> {
+        match self {
+            Self::Rocksdb(transaction) => transaction.revert(),
+            Self::Inmemory(transaction) => transaction.revert(),
+        }
+    }
+
+    fn restart(
+        &mut self, immediate_write: bool, no_revert: bool,
+    ) -> Result<()> {
+        match self {
+            Self::Rocksdb(transaction) => {
+                transaction.restart(immediate_write, no_revert)
+            }
+            Self::Inmemory(transaction) => {
+                transaction.restart(immediate_write, no_revert)
+            }
+        }
+    }
+}
+
+impl Drop for DeltaDbTransactionDispatch {
+    fn drop(&mut self) {
+        // No-op
+    }
+}
+
+use crate::{
+    impls::{
+        errors::*,
+        storage_db::{
+            delta_db_manager_inmemory::DeltaDbManagerInmemory,
+            delta_db_manager_rocksdb::DeltaDbManagerRocksdb,
+            kvdb_inmemory::{KvdbInmemory, KvdbInmemoryTransaction},
+            kvdb_rocksdb::{KvdbRocksDbTransaction, KvdbRocksdb},
+        },
+    },
+    storage_db::{
+        delta_db_manager::{DeltaDbManagerTrait, DeltaDbTrait},
+        key_value_db::*,
+        SnapshotInfo,
+    },
+    SnapshotDbBackend,
+};
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use primitives::EpochId;
+use std::{
+    any::Any,
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
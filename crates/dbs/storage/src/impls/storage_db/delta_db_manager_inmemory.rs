@@ -0,0 +1,124 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+/// Keeps the delta dbs in memory by their names. Nothing is written under
+/// `delta_db_path`, which only serves to name the delta dbs.
+pub struct DeltaDbManagerInmemory {
+    delta_db_path: PathBuf,
+    delta_dbs: RwLock<HashMap<String, KvdbInmemory>>,
+}
+
+impl DeltaDbManagerInmemory {
+    const DELTA_DB_INMEMORY_NAME_PREFIX: &'static str = "inmemory_";
+
+    pub fn new(delta_db_path: PathBuf) -> Result<DeltaDbManagerInmemory> {
+        Ok(Self {
+            delta_db_path,
+            delta_dbs: Default::default(),
+        })
+    }
+}
+
+impl DeltaDbManagerTrait for DeltaDbManagerInmemory {
+    type DeltaDb = KvdbInmemory;
+
+    fn get_delta_db_dir(&self) -> &Path { self.delta_db_path.as_path() }
+
+    fn get_delta_db_name(&self, snapshot_epoch_id: &EpochId) -> String {
+        Self::DELTA_DB_INMEMORY_NAME_PREFIX.to_string()
+            + &snapshot_epoch_id.as_ref().to_hex::<String>()
+    }
+
+    fn get_delta_db_path(&self, delta_db_name: &str) -> PathBuf {
+        self.delta_db_path.join(delta_db_name)
+    }
+
+    /// Same as the default implementation, but scans the delta dbs in memory
+    /// instead of the delta db dir.
+    fn scan_persist_state(
+        &self, snapshot_info_map: &HashMap<EpochId, SnapshotInfo>,
+    ) -> Result<(Vec<EpochId>, HashMap<EpochId, Self::DeltaDb>)> {
+        let mut possible_delta_db_names = HashMap::new();
+        for (snapshot_epoch_id, snapshot_info) in snapshot_info_map {
+            // Delta MPT
+            possible_delta_db_names.insert(
+                self.get_delta_db_name(snapshot_epoch_id),
+                snapshot_epoch_id.clone(),
+            );
+            // Intermediate Delta MPT
+            possible_delta_db_names.insert(
+                self.get_delta_db_name(&snapshot_info.parent_snapshot_epoch_id),
+                snapshot_info.parent_snapshot_epoch_id.clone(),
+            );
+        }
+
+        let mut delta_mpts = HashMap::new();
+        self.delta_dbs.write().retain(|delta_db_name, delta_db| {
+            match possible_delta_db_names.get(delta_db_name) {
+                Some(snapshot_epoch_id) => {
+                    delta_mpts.insert(*snapshot_epoch_id, delta_db.clone());
+                    true
+                }
+                None => {
+                    error!("Unexpected delta db {}, deleted.", delta_db_name);
+                    false
+                }
+            }
+        });
+
+        let mut missing_delta_dbs = vec![];
+        for (snapshot_epoch_id, snapshot_info) in snapshot_info_map {
+            // Skip if the snapshot doesn't exist.
+            if snapshot_info.snapshot_info_kept_to_provide_sync
+                == SnapshotKeptToProvideSyncStatus::No
+                && !delta_mpts.contains_key(snapshot_epoch_id)
+            {
+                missing_delta_dbs.push(snapshot_epoch_id.clone())
+            }
+        }
+
+        Ok((missing_delta_dbs, delta_mpts))
+    }
+
+    fn new_empty_delta_db(&self, delta_db_name: &str) -> Result<Self::DeltaDb> {
+        let mut delta_dbs = self.delta_dbs.write();
+        if delta_dbs.contains_key(delta_db_name) {
+            Err(Error::DeltaMPTAlreadyExists.into())
+        } else {
+            let delta_db = KvdbInmemory::default();
+            delta_dbs.insert(delta_db_name.to_string(), delta_db.clone());
+            Ok(delta_db)
+        }
+    }
+
+    fn get_delta_db(
+        &self, delta_db_name: &str,
+    ) -> Result<Option<Self::DeltaDb>> {
+        Ok(self.delta_dbs.read().get(delta_db_name).cloned())
+    }
+
+    /// The data is released when the last user of the delta db drops it.
+    fn destroy_delta_db(&self, delta_db_name: &str) -> Result<()> {
+        self.delta_dbs.write().remove(delta_db_name);
+        Ok(())
+    }
+}
+
+use super::{
+    super::{
+        super::storage_db::{
+            delta_db_manager::DeltaDbManagerTrait, SnapshotInfo,
+            SnapshotKeptToProvideSyncStatus,
+        },
+        errors::*,
+    },
+    kvdb_inmemory::KvdbInmemory,
+};
+use parking_lot::RwLock;
+use primitives::EpochId;
+use rustc_hex::ToHex;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
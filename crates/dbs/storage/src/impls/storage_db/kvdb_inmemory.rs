@@ -0,0 +1,281 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+/// An ordered key-value db kept in memory. Clones share the same data, use
+/// `deep_clone` to copy the data.
+#[derive(Clone, Default)]
+pub struct KvdbInmemory {
+    map: Arc<RwLock<BTreeMap<Vec<u8>, Box<[u8]>>>>,
+}
+
+impl KvdbInmemory {
+    pub fn deep_clone(&self) -> Self {
+        Self {
+            map: Arc::new(RwLock::new(self.map.read().clone())),
+        }
+    }
+
+    pub fn clear(&self) { self.map.write().clear(); }
+
+    /// Return the key-values within the range in key order.
+    fn range(
+        &self, lower_bound_incl: &[u8], upper_bound_excl: Option<&[u8]>,
+    ) -> VecDeque<MptKeyValue> {
+        let upper_bound = match upper_bound_excl {
+            Some(key) => Bound::Excluded(key),
+            None => Bound::Unbounded,
+        };
+        self.map
+            .read()
+            .range::<[u8], _>((Bound::Included(lower_bound_incl), upper_bound))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+}
+
+impl MallocSizeOf for KvdbInmemory {
+    fn size_of(&self, _ops: &mut MallocSizeOfOps) -> usize {
+        self.map.read().iter().map(|(k, v)| k.len() + v.len()).sum()
+    }
+}
+
+pub struct KvdbInmemoryTransaction {
+    /// None value for deletion.
+    pending: Vec<(Vec<u8>, Option<Box<[u8]>>)>,
+}
+
+impl KeyValueDbTraitRead for KvdbInmemory {
+    fn get(&self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        Ok(self.map.read().get(key).cloned())
+    }
+}
+
+mark_kvdb_multi_reader!(KvdbInmemory);
+
+impl KeyValueDbTypes for KvdbInmemory {
+    type ValueType = Box<[u8]>;
+}
+
+impl KeyValueDbTrait for KvdbInmemory {
+    fn delete(&self, key: &[u8]) -> Result<Option<Option<Box<[u8]>>>> {
+        Ok(Some(self.map.write().remove(key)))
+    }
+
+    fn put(
+        &self, key: &[u8], value: &[u8],
+    ) -> Result<Option<Option<Box<[u8]>>>> {
+        Ok(Some(self.map.write().insert(key.to_vec(), value.into())))
+    }
+}
+
+impl KeyValueDbTypes for KvdbInmemoryTransaction {
+    type ValueType = Box<[u8]>;
+}
+
+impl KeyValueDbTraitSingleWriter for KvdbInmemoryTransaction {
+    fn delete(&mut self, key: &[u8]) -> Result<Option<Option<Box<[u8]>>>> {
+        self.pending.push((key.to_vec(), None));
+        Ok(None)
+    }
+
+    fn put(
+        &mut self, key: &[u8], value: &[u8],
+    ) -> Result<Option<Option<Box<[u8]>>>> {
+        self.pending.push((key.to_vec(), Some(value.into())));
+        Ok(None)
+    }
+}
+
+impl KeyValueDbTraitOwnedRead for KvdbInmemoryTransaction {
+    fn get_mut(&mut self, _key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        // Same as KvdbRocksDbTransaction, the user shouldn't read from the
+        // transaction.
+        unreachable!()
+    }
+}
+
+impl KeyValueDbTransactionTrait for KvdbInmemoryTransaction {
+    fn commit(&mut self, db: &dyn Any) -> Result<()> {
+        match db.downcast_ref::<KvdbInmemory>() {
+            Some(as_kvdb_inmemory) => {
+                let mut map = as_kvdb_inmemory.map.write();
+                for (key, maybe_value) in self.pending.drain(..) {
+                    match maybe_value {
+                        Some(value) => map.insert(key, value),
+                        None => map.remove(&key),
+                    };
+                }
+                Ok(())
+            }
+            None => {
+                unreachable!();
+            }
+        }
+    }
+
+    fn revert(&mut self) -> Result<()> {
+        self.pending.clear();
+        Ok(())
+    }
+
+    fn restart(
+        &mut self, _immediate_write: bool, no_revert: bool,
+    ) -> Result<()> {
+        if !no_revert {
+            self.revert()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for KvdbInmemoryTransaction {
+    fn drop(&mut self) {
+        // No-op
+    }
+}
+
+impl KeyValueDbTraitTransactional for KvdbInmemory {
+    type TransactionType = KvdbInmemoryTransaction;
+
+    fn start_transaction(
+        &self, _immediate_write: bool,
+    ) -> Result<Self::TransactionType> {
+        Ok(KvdbInmemoryTransaction {
+            pending: Default::default(),
+        })
+    }
+}
+
+impl DeltaDbTrait for KvdbInmemory {
+    fn checkpoint(&self, _path: &Path) -> Result<()> {
+        bail!("An in-memory delta db can not be checkpointed")
+    }
+}
+
+pub struct KvdbInmemoryIteratorTag();
+
+/// Iterates over a copy of the key-values in the range at the time the
+/// iterator is created.
+pub struct KvdbInmemoryIterator {
+    kvs: VecDeque<MptKeyValue>,
+}
+
+impl FallibleIterator for KvdbInmemoryIterator {
+    type Error = Error;
+    type Item = MptKeyValue;
+
+    fn next(&mut self) -> Result<Option<MptKeyValue>> {
+        Ok(self.kvs.pop_front())
+    }
+}
+
+impl WrappedTrait<dyn FallibleIterator<Item = MptKeyValue, Error = Error>>
+    for KvdbIterIterator<MptKeyValue, [u8], KvdbInmemoryIteratorTag>
+{
+}
+
+impl<'a>
+    WrappedLifetimeFamily<
+        'a,
+        dyn FallibleIterator<Item = MptKeyValue, Error = Error>,
+    > for KvdbIterIterator<MptKeyValue, [u8], KvdbInmemoryIteratorTag>
+{
+    type Out = KvdbInmemoryIterator;
+}
+
+/// None stands for a db which doesn't exist, e.g. in the empty snapshot, and
+/// iterates over nothing.
+impl KeyValueDbIterableTrait<MptKeyValue, [u8], KvdbInmemoryIteratorTag>
+    for Option<KvdbInmemory>
+{
+    fn iter_range(
+        &mut self, lower_bound_incl: &[u8], upper_bound_excl: Option<&[u8]>,
+    ) -> Result<
+        Wrap<
+            KvdbIterIterator<MptKeyValue, [u8], KvdbInmemoryIteratorTag>,
+            dyn FallibleIterator<Item = MptKeyValue, Error = Error>,
+        >,
+    > {
+        Ok(Wrap(KvdbInmemoryIterator {
+            kvs: match self {
+                Some(kvdb) => kvdb.range(lower_bound_incl, upper_bound_excl),
+                None => Default::default(),
+            },
+        }))
+    }
+
+    fn iter_range_excl(
+        &mut self, lower_bound_excl: &[u8], upper_bound_excl: &[u8],
+    ) -> Result<
+        Wrap<
+            KvdbIterIterator<MptKeyValue, [u8], KvdbInmemoryIteratorTag>,
+            dyn FallibleIterator<Item = MptKeyValue, Error = Error>,
+        >,
+    > {
+        let mut lower_bound_incl = lower_bound_excl.to_vec();
+        lower_bound_incl.push(0);
+        self.iter_range(&lower_bound_incl, Some(upper_bound_excl))
+    }
+}
+
+impl
+    ElementSatisfy<
+        dyn KeyValueDbIterableTrait<MptKeyValue, [u8], KvdbInmemoryIteratorTag>,
+    > for Option<KvdbInmemory>
+{
+    fn to_constrain_object(
+        &self,
+    ) -> &(dyn KeyValueDbIterableTrait<MptKeyValue, [u8], KvdbInmemoryIteratorTag>
+             + 'static) {
+        self
+    }
+
+    fn to_constrain_object_mut(
+        &mut self,
+    ) -> &mut (dyn KeyValueDbIterableTrait<
+        MptKeyValue,
+        [u8],
+        KvdbInmemoryIteratorTag,
+    > + 'static) {
+        self
+    }
+}
+
+impl
+    WrappedLifetimeFamily<
+        '_,
+        dyn KeyValueDbIterableTrait<MptKeyValue, [u8], KvdbInmemoryIteratorTag>,
+    > for Option<KvdbInmemory>
+{
+    type Out = Self;
+}
+
+impl
+    WrappedTrait<
+        dyn KeyValueDbIterableTrait<MptKeyValue, [u8], KvdbInmemoryIteratorTag>,
+    > for Option<KvdbInmemory>
+{
+}
+
+use super::super::{
+    super::{
+        storage_db::{delta_db_manager::DeltaDbTrait, key_value_db::*},
+        utils::{
+            tuple::ElementSatisfy,
+            wrap::{Wrap, WrappedLifetimeFamily, WrappedTrait},
+        },
+    },
+    errors::*,
+    merkle_patricia_trie::MptKeyValue,
+};
+use fallible_iterator::FallibleIterator;
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use parking_lot::RwLock;
+use std::{
+    any::Any,
+    collections::{BTreeMap, VecDeque},
+    ops::Bound,
+    path::Path,
+    sync::Arc,
+};
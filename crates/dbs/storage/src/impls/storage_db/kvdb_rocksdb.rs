@@ -131,14 +131,174 @@ impl KeyValueDbTraitTransactional for KvdbRocksdb {
     }
 }
 
-impl DeltaDbTrait for KvdbRocksdb {}
+impl DeltaDbTrait for KvdbRocksdb {
+    fn checkpoint(&self, path: &Path) -> Result<()> {
+        Ok(self.kvdb.checkpoint(path)?)
+    }
+}
+
+pub struct KvdbRocksdbIteratorTag();
+
+/// Iterates a key range of a column by reading it in pages, so that no RocksDB
+/// iterator is kept alive between calls of `next`.
+///
+/// Keys written after the iterator is created may or may not be visible.
+pub struct KvdbRocksdbIterator {
+    maybe_kvdb: Option<KvdbRocksdb>,
+    next_lower_bound_incl: Option<Vec<u8>>,
+    upper_bound_excl: Option<Vec<u8>>,
+    page: VecDeque<(Box<[u8]>, Box<[u8]>)>,
+}
+
+impl KvdbRocksdbIterator {
+    const PAGE_SIZE: usize = 1000;
+
+    fn new(
+        maybe_kvdb: Option<KvdbRocksdb>, lower_bound_incl: &[u8],
+        upper_bound_excl: Option<&[u8]>,
+    ) -> Self {
+        Self {
+            maybe_kvdb,
+            next_lower_bound_incl: Some(lower_bound_incl.to_vec()),
+            upper_bound_excl: upper_bound_excl.map(|k| k.to_vec()),
+            page: Default::default(),
+        }
+    }
+}
+
+impl FallibleIterator for KvdbRocksdbIterator {
+    type Error = Error;
+    type Item = MptKeyValue;
+
+    fn next(&mut self) -> Result<Option<MptKeyValue>> {
+        if self.page.is_empty() {
+            if let (Some(kvdb), Some(lower_bound_incl)) =
+                (&self.maybe_kvdb, self.next_lower_bound_incl.take())
+            {
+                let page = kvdb.kvdb.iter_range(
+                    kvdb.col,
+                    &lower_bound_incl,
+                    self.upper_bound_excl.as_ref().map(|k| &**k),
+                    Self::PAGE_SIZE,
+                )?;
+                if page.len() == Self::PAGE_SIZE {
+                    // The smallest key after the last key of the page.
+                    let mut next_key = page.last().unwrap().0.to_vec();
+                    next_key.push(0);
+                    self.next_lower_bound_incl = Some(next_key);
+                }
+                self.page = page.into();
+            }
+        }
+        Ok(self.page.pop_front().map(|(k, v)| (k.into_vec(), v)))
+    }
+}
+
+impl WrappedTrait<dyn FallibleIterator<Item = MptKeyValue, Error = Error>>
+    for KvdbIterIterator<MptKeyValue, [u8], KvdbRocksdbIteratorTag>
+{
+}
+
+impl<'a>
+    WrappedLifetimeFamily<
+        'a,
+        dyn FallibleIterator<Item = MptKeyValue, Error = Error>,
+    > for KvdbIterIterator<MptKeyValue, [u8], KvdbRocksdbIteratorTag>
+{
+    type Out = KvdbRocksdbIterator;
+}
+
+/// None stands for a column which doesn't exist, e.g. in the empty snapshot,
+/// and iterates over nothing.
+impl KeyValueDbIterableTrait<MptKeyValue, [u8], KvdbRocksdbIteratorTag>
+    for Option<KvdbRocksdb>
+{
+    fn iter_range(
+        &mut self, lower_bound_incl: &[u8], upper_bound_excl: Option<&[u8]>,
+    ) -> Result<
+        Wrap<
+            KvdbIterIterator<MptKeyValue, [u8], KvdbRocksdbIteratorTag>,
+            dyn FallibleIterator<Item = MptKeyValue, Error = Error>,
+        >,
+    > {
+        Ok(Wrap(KvdbRocksdbIterator::new(
+            self.clone(),
+            lower_bound_incl,
+            upper_bound_excl,
+        )))
+    }
+
+    fn iter_range_excl(
+        &mut self, lower_bound_excl: &[u8], upper_bound_excl: &[u8],
+    ) -> Result<
+        Wrap<
+            KvdbIterIterator<MptKeyValue, [u8], KvdbRocksdbIteratorTag>,
+            dyn FallibleIterator<Item = MptKeyValue, Error = Error>,
+        >,
+    > {
+        let mut lower_bound_incl = lower_bound_excl.to_vec();
+        lower_bound_incl.push(0);
+        Ok(Wrap(KvdbRocksdbIterator::new(
+            self.clone(),
+            &lower_bound_incl,
+            Some(upper_bound_excl),
+        )))
+    }
+}
+
+impl
+    ElementSatisfy<
+        dyn KeyValueDbIterableTrait<MptKeyValue, [u8], KvdbRocksdbIteratorTag>,
+    > for Option<KvdbRocksdb>
+{
+    fn to_constrain_object(
+        &self,
+    ) -> &(dyn KeyValueDbIterableTrait<MptKeyValue, [u8], KvdbRocksdbIteratorTag>
+             + 'static) {
+        self
+    }
+
+    fn to_constrain_object_mut(
+        &mut self,
+    ) -> &mut (dyn KeyValueDbIterableTrait<
+        MptKeyValue,
+        [u8],
+        KvdbRocksdbIteratorTag,
+    > + 'static) {
+        self
+    }
+}
+
+impl
+    WrappedLifetimeFamily<
+        '_,
+        dyn KeyValueDbIterableTrait<MptKeyValue, [u8], KvdbRocksdbIteratorTag>,
+    > for Option<KvdbRocksdb>
+{
+    type Out = Self;
+}
+
+impl
+    WrappedTrait<
+        dyn KeyValueDbIterableTrait<MptKeyValue, [u8], KvdbRocksdbIteratorTag>,
+    > for Option<KvdbRocksdb>
+{
+}
 
 use super::super::{
-    super::storage_db::{delta_db_manager::DeltaDbTrait, key_value_db::*},
+    super::{
+        storage_db::{delta_db_manager::DeltaDbTrait, key_value_db::*},
+        utils::{
+            tuple::ElementSatisfy,
+            wrap::{Wrap, WrappedLifetimeFamily, WrappedTrait},
+        },
+    },
     errors::*,
+    merkle_patricia_trie::MptKeyValue,
 };
+use fallible_iterator::FallibleIterator;
 use kvdb::DBTransaction;
 use kvdb_rocksdb::Database;
 use malloc_size_of_derive::MallocSizeOf as MallocSizeOfDerive;
 use random_crash::random_crash_if_enabled;
-use std::{any::Any, sync::Arc};
+use std::{any::Any, collections::VecDeque, path::Path, sync::Arc};
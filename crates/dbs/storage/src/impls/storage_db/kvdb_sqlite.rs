@@ -221,6 +221,14 @@ impl<ValueType> KvdbSqlite<ValueType> {
         })
     }
 
+    /// See [`SqliteConnection::backup`].
+    pub fn backup<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        match &self.connection {
+            None => Ok(()),
+            Some(conn) => conn.backup(path),
+        }
+    }
+
     pub fn open<P: AsRef<Path>>(
         path: P, readonly: bool, statements: Arc<KvdbSqliteStatements>,
     ) -> Result<Self> {
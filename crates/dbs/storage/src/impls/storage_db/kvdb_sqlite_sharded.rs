@@ -1019,7 +1019,17 @@ impl OwnedReadImplFamily for &KvdbSqliteSharded<Box<[u8]>> {
     type FamilyRepresentative =
         dyn KeyValueDbTraitMultiReader<ValueType = Box<[u8]>>;
 }
-impl DeltaDbTrait for KvdbSqliteSharded<Box<[u8]>> {}
+impl DeltaDbTrait for KvdbSqliteSharded<Box<[u8]>> {
+    fn checkpoint(&self, path: &Path) -> Result<()> {
+        fs::create_dir_all(path)?;
+        if let Some(connections) = &self.shards_connections {
+            for (shard_id, connection) in connections.iter().enumerate() {
+                connection.backup(Self::db_path(path, shard_id as u16))?;
+            }
+        }
+        Ok(())
+    }
+}
 
 impl<ValueType: DbValueType> KeyValueDbTypes for KvdbSqliteSharded<ValueType> {
     type ValueType = ValueType;
@@ -1213,6 +1223,7 @@ use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use sqlite::Statement;
 use std::{
     any::Any,
+    fs,
     marker::PhantomData,
     path::{Path, PathBuf},
     sync::Arc,
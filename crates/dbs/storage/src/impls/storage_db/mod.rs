@@ -3,14 +3,25 @@
 // See http://www.gnu.org/licenses/
 
 // TODO: check berkeley db as well.
+pub mod delta_db_manager_dispatch;
+pub mod delta_db_manager_inmemory;
 pub mod delta_db_manager_rocksdb;
 pub mod delta_db_manager_sqlite;
+pub mod kvdb_inmemory;
 pub mod kvdb_rocksdb;
 pub mod kvdb_sqlite;
 pub mod kvdb_sqlite_sharded;
+pub mod snapshot_db_inmemory;
+pub mod snapshot_db_manager_dispatch;
+pub mod snapshot_db_manager_inmemory;
+pub mod snapshot_db_manager_rocksdb;
 pub mod snapshot_db_manager_sqlite;
+pub mod snapshot_db_rocksdb;
 pub mod snapshot_db_sqlite;
+pub mod snapshot_kv_db_rocksdb;
 pub mod snapshot_kv_db_sqlite;
 pub mod snapshot_mpt;
 pub mod snapshot_mpt_db_sqlite;
+pub mod snapshot_mpt_inmemory;
+pub mod snapshot_mpt_rocksdb;
 pub mod sqlite;
@@ -0,0 +1,308 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+/// A snapshot kept in memory, with the key-values, the snapshot MPT and the
+/// dumped delta MPT each in their own db. Clones share the same data.
+///
+/// The empty snapshot is a snapshot with empty dbs.
+#[derive(Clone, Default)]
+pub struct SnapshotDbInmemory {
+    kv: KvdbInmemory,
+    mpt: KvdbInmemory,
+    delta_kv_set: KvdbInmemory,
+    /// The values of the deleted keys are empty.
+    delta_kv_delete: KvdbInmemory,
+}
+
+impl SnapshotDbInmemory {
+    /// Copy the snapshot, so that writes to the copy do not go to this
+    /// snapshot.
+    pub fn deep_clone(&self) -> Self {
+        Self {
+            kv: self.kv.deep_clone(),
+            mpt: self.mpt.deep_clone(),
+            delta_kv_set: self.delta_kv_set.deep_clone(),
+            delta_kv_delete: self.delta_kv_delete.deep_clone(),
+        }
+    }
+
+    /// The snapshot mpt is always kept in the snapshot db.
+    pub fn has_mpt_snapshot(&self) -> bool { true }
+
+    pub fn dumped_delta_kv_set_keys_iterator(
+        &self,
+    ) -> Result<Option<KvdbInmemory>> {
+        Ok(Some(self.delta_kv_set.clone()))
+    }
+
+    /// The values of the deleted keys are empty.
+    pub fn dumped_delta_kv_delete_keys_iterator(
+        &self,
+    ) -> Result<Option<KvdbInmemory>> {
+        Ok(Some(self.delta_kv_delete.clone()))
+    }
+
+    pub fn dump_delta_mpt(
+        &mut self, delta_mpt: &DeltaMptIterator,
+    ) -> Result<()> {
+        debug!("dump_delta_mpt starts");
+        delta_mpt
+            .iterate(&mut DeltaMptMergeDumperInmemory { snapshot_db: self })
+    }
+
+    /// Dropping is optional, because the dump is necessary to provide 1-step
+    /// syncing.
+    pub fn drop_delta_mpt_dump(&mut self) -> Result<()> {
+        self.delta_kv_set.clear();
+        self.delta_kv_delete.clear();
+        Ok(())
+    }
+
+    fn apply_update_to_kvdb(&mut self) -> Result<()> {
+        let mut delete_keys = self.dumped_delta_kv_delete_keys_iterator()?;
+        let mut delete_keys_iter = delete_keys.iter_range(&[], None)?.take();
+        while let Some((key, _)) = delete_keys_iter.next()? {
+            self.kv.delete(&key)?;
+        }
+        let mut set_keys = self.dumped_delta_kv_set_keys_iterator()?;
+        let mut set_keys_iter = set_keys.iter_range(&[], None)?.take();
+        while let Some((key, value)) = set_keys_iter.next()? {
+            self.kv.put(&key, &value)?;
+        }
+        Ok(())
+    }
+}
+
+impl KeyValueDbTypes for SnapshotDbInmemory {
+    type ValueType = Box<[u8]>;
+}
+
+impl KeyValueDbTraitRead for SnapshotDbInmemory {
+    fn get(&self, key: &[u8]) -> Result<Option<Box<[u8]>>> { self.kv.get(key) }
+}
+
+impl KeyValueDbTraitOwnedRead for SnapshotDbInmemory {
+    fn get_mut(&mut self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        self.kv.get(key)
+    }
+}
+
+impl KeyValueDbTraitSingleWriter for SnapshotDbInmemory {
+    fn delete(&mut self, key: &[u8]) -> Result<Option<Option<Box<[u8]>>>> {
+        self.kv.delete(key)
+    }
+
+    fn put(
+        &mut self, key: &[u8], value: &[u8],
+    ) -> Result<Option<Option<Box<[u8]>>>> {
+        self.kv.put(key, value)
+    }
+}
+
+impl<'db> OpenSnapshotMptTrait<'db> for SnapshotDbInmemory {
+    type SnapshotDbAsOwnedType = SnapshotMptInmemory;
+    type SnapshotDbBorrowMutType = SnapshotMptInmemory;
+    type SnapshotDbBorrowSharedType = SnapshotMptInmemory;
+
+    fn open_snapshot_mpt_owned(
+        &'db mut self,
+    ) -> Result<Self::SnapshotDbBorrowMutType> {
+        SnapshotMptInmemory::new(Some(self.mpt.clone()))
+    }
+
+    fn open_snapshot_mpt_as_owned(
+        &'db self,
+    ) -> Result<Self::SnapshotDbAsOwnedType> {
+        SnapshotMptInmemory::new(Some(self.mpt.clone()))
+    }
+
+    fn open_snapshot_mpt_shared(
+        &'db self,
+    ) -> Result<Self::SnapshotDbBorrowSharedType> {
+        SnapshotMptInmemory::new(Some(self.mpt.clone()))
+    }
+}
+
+impl SnapshotDbTrait for SnapshotDbInmemory {
+    type SnapshotKvdbIterTraitTag = KvdbInmemoryIteratorTag;
+    type SnapshotKvdbIterType = Option<KvdbInmemory>;
+    /// The snapshot MPT is always in the snapshot db.
+    type SnapshotMptDb = ();
+
+    fn get_null_snapshot() -> Self { Default::default() }
+
+    /// In-memory snapshots have no path and are only opened by
+    /// SnapshotDbManagerInmemory.
+    fn open(
+        _snapshot_path: &Path, _readonly: bool,
+        _already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        _open_semaphore: &Arc<Semaphore>,
+    ) -> Result<SnapshotDbInmemory> {
+        unreachable!()
+    }
+
+    fn create(
+        _snapshot_path: &Path,
+        _already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        _open_semaphore: &Arc<Semaphore>, _mpt_table_in_current_db: bool,
+    ) -> Result<SnapshotDbInmemory> {
+        unreachable!()
+    }
+
+    fn direct_merge(
+        &mut self, _old_snapshot_db: Option<&Arc<SnapshotDbInmemory>>,
+        _mpt_snapshot: &mut Option<()>,
+        recover_mpt_with_kv_snapshot_exist: bool,
+        in_reconstruct_snapshot_state: bool,
+    ) -> Result<MerkleHash> {
+        debug!("direct_merge begins.");
+
+        if !recover_mpt_with_kv_snapshot_exist {
+            self.apply_update_to_kvdb()?;
+        }
+
+        let mut set_keys_iter = self.dumped_delta_kv_set_keys_iterator()?;
+        let mut delete_keys_iter =
+            self.dumped_delta_kv_delete_keys_iterator()?;
+
+        // The MPT of the old snapshot is already in the copy, update it
+        // in-place.
+        let mut mpt_to_modify =
+            OpenSnapshotMptTrait::open_snapshot_mpt_owned(self)?;
+        MptMerger::new(None, &mut mpt_to_modify as &mut dyn SnapshotMptTraitRw)
+            .merge_insertion_deletion_separated(
+                delete_keys_iter
+                    .iter_range(&[], None)?
+                    .take()
+                    .map(|(key, _)| Ok((key, ()))),
+                set_keys_iter.iter_range(&[], None)?.take(),
+                in_reconstruct_snapshot_state,
+            )
+    }
+
+    fn copy_and_merge(
+        &mut self, old_snapshot_db: &Arc<SnapshotDbInmemory>,
+        _mpt_snapshot_db: &mut Option<()>, in_reconstruct_snapshot_state: bool,
+    ) -> Result<MerkleHash> {
+        debug!("copy_and_merge begins.");
+        {
+            let mut kv_iter = old_snapshot_db.snapshot_kv_iterator()?.take();
+            let mut iter = kv_iter.iter_range(&[], None)?.take();
+            while let Some((key, value)) = iter.next()? {
+                self.kv.put(&key, &value)?;
+            }
+        }
+        self.apply_update_to_kvdb()?;
+
+        let mut set_keys_iter = self.dumped_delta_kv_set_keys_iterator()?;
+        let mut delete_keys_iter =
+            self.dumped_delta_kv_delete_keys_iterator()?;
+        let mut base_mpt = old_snapshot_db.open_snapshot_mpt_as_owned()?;
+        let mut save_as_mpt =
+            OpenSnapshotMptTrait::open_snapshot_mpt_owned(self)?;
+        MptMerger::new(
+            Some(&mut base_mpt as &mut dyn SnapshotMptTraitReadAndIterate),
+            &mut save_as_mpt as &mut dyn SnapshotMptTraitRw,
+        )
+        .merge_insertion_deletion_separated(
+            delete_keys_iter
+                .iter_range(&[], None)?
+                .take()
+                .map(|(key, _)| Ok((key, ()))),
+            set_keys_iter.iter_range(&[], None)?.take(),
+            in_reconstruct_snapshot_state,
+        )
+    }
+
+    /// Writes go to the dbs immediately.
+    fn start_transaction(&mut self) -> Result<()> { Ok(()) }
+
+    fn commit_transaction(&mut self) -> Result<()> { Ok(()) }
+
+    fn is_mpt_table_in_current_db(&self) -> bool { true }
+
+    fn snapshot_kv_iterator(
+        &self,
+    ) -> Result<
+        Wrap<
+            Self::SnapshotKvdbIterType,
+            dyn KeyValueDbIterableTrait<
+                MptKeyValue,
+                [u8],
+                KvdbInmemoryIteratorTag,
+            >,
+        >,
+    > {
+        Ok(Wrap(Some(self.kv.clone())))
+    }
+}
+
+/// The snapshot being written by full sync.
+impl SnapshotDbWriteableTrait for SnapshotDbInmemory {
+    type SnapshotDbBorrowMutType = SnapshotMptInmemory;
+
+    fn start_transaction(&mut self) -> Result<()> {
+        SnapshotDbTrait::start_transaction(self)
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        SnapshotDbTrait::commit_transaction(self)
+    }
+
+    fn put_kv(
+        &mut self, key: &[u8], value: &<Self::ValueType as DbValueType>::Type,
+    ) -> Result<Option<Option<Self::ValueType>>> {
+        self.kv.put(key, value)
+    }
+
+    fn open_snapshot_mpt_owned(
+        &mut self,
+    ) -> Result<Self::SnapshotDbBorrowMutType> {
+        OpenSnapshotMptTrait::open_snapshot_mpt_owned(self)
+    }
+}
+
+pub struct DeltaMptMergeDumperInmemory<'a> {
+    snapshot_db: &'a SnapshotDbInmemory,
+}
+
+impl<'a> KVInserter<MptKeyValue> for DeltaMptMergeDumperInmemory<'a> {
+    fn push(&mut self, x: MptKeyValue) -> Result<()> {
+        let (mpt_key, value) = x;
+        let snapshot_key =
+            StorageKeyWithSpace::from_delta_mpt_key(&mpt_key).to_key_bytes();
+        if !value.is_empty() {
+            self.snapshot_db.delta_kv_set.put(&snapshot_key, &value)?;
+        } else {
+            self.snapshot_db.delta_kv_delete.put(&snapshot_key, &[])?;
+        }
+        Ok(())
+    }
+}
+
+use crate::{
+    impls::{
+        delta_mpt::DeltaMptIterator,
+        errors::*,
+        merkle_patricia_trie::{MptKeyValue, MptMerger},
+        storage_db::{
+            kvdb_inmemory::{KvdbInmemory, KvdbInmemoryIteratorTag},
+            snapshot_db_manager_sqlite::AlreadyOpenSnapshots,
+            snapshot_mpt_inmemory::SnapshotMptInmemory,
+        },
+    },
+    storage_db::{
+        DbValueType, KeyValueDbIterableTrait, KeyValueDbTrait,
+        KeyValueDbTraitOwnedRead, KeyValueDbTraitRead,
+        KeyValueDbTraitSingleWriter, KeyValueDbTypes, OpenSnapshotMptTrait,
+        SnapshotDbTrait, SnapshotDbWriteableTrait,
+        SnapshotMptTraitReadAndIterate, SnapshotMptTraitRw,
+    },
+    utils::wrap::Wrap,
+    KVInserter,
+};
+use fallible_iterator::FallibleIterator;
+use primitives::{MerkleHash, StorageKeyWithSpace};
+use std::{path::Path, sync::Arc};
+use tokio::sync::Semaphore;
@@ -0,0 +1,1072 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+/// The snapshot db manager of the backend chosen by `snapshot_db_backend`.
+///
+/// The snapshots of one backend can not be read by another, so the backend
+/// of a data dir can not be changed.
+pub enum SnapshotDbManagerDispatch {
+    Sqlite(SnapshotDbManagerSqlite),
+    Rocksdb(SnapshotDbManagerRocksdb),
+    Inmemory(SnapshotDbManagerInmemory),
+}
+
+/// A snapshot opened by `SnapshotDbManagerDispatch`.
+pub enum SnapshotDbDispatch {
+    Sqlite(SnapshotDbSqlite),
+    Rocksdb(SnapshotDbRocksdb),
+    Inmemory(SnapshotDbInmemory),
+}
+
+/// A snapshot being written by full sync.
+pub enum SnapshotDbWriteDispatch {
+    Sqlite(SnapshotDbWriteable),
+    Rocksdb(SnapshotKvDbRocksdb),
+    Inmemory(SnapshotDbInmemory),
+}
+
+/// The snapshot MPT of a `SnapshotDbDispatch`.
+pub enum SnapshotMptDispatch<SqliteMpt, RocksdbMpt> {
+    Sqlite(SqliteMpt),
+    Rocksdb(RocksdbMpt),
+    Inmemory(SnapshotMptInmemory),
+}
+
+/// The key-values of a `SnapshotDbDispatch`, or the keys of its dumped delta
+/// MPT.
+pub enum SnapshotKvdbIterDispatch {
+    Sqlite(KvdbSqliteSharded<Box<[u8]>>),
+    /// The deleted keys of the dumped delta MPT, iterated with empty values.
+    SqliteKeys(KvdbSqliteSharded<()>),
+    Rocksdb(Option<KvdbRocksdb>),
+    Inmemory(Option<KvdbInmemory>),
+}
+
+pub struct SnapshotKvdbIterDispatchTag();
+
+pub enum SnapshotKvIterDispatch<'a> {
+    Sqlite(
+        <KvdbIterIterator<MptKeyValue, [u8], KvdbSqliteShardedIteratorTag> as WrappedLifetimeFamily<
+            'a,
+            dyn FallibleIterator<Item = MptKeyValue, Error = Error>,
+        >>::Out,
+    ),
+    SqliteKeys(
+        <KvdbIterIterator<(Vec<u8>, ()), [u8], KvdbSqliteShardedIteratorTag> as WrappedLifetimeFamily<
+            'a,
+            dyn FallibleIterator<Item = (Vec<u8>, ()), Error = Error>,
+        >>::Out,
+    ),
+    Rocksdb(KvdbRocksdbIterator),
+    Inmemory(KvdbInmemoryIterator),
+}
+
+impl SnapshotDbManagerDispatch {
+    pub fn new(
+        backend: SnapshotDbBackend, snapshot_path: PathBuf,
+        max_open_snapshots: u16, use_isolated_db_for_mpt_table: bool,
+        use_isolated_db_for_mpt_table_height: Option<u64>,
+        era_epoch_count: u64,
+    ) -> Result<Self> {
+        Ok(match backend {
+            SnapshotDbBackend::Sqlite => {
+                Self::Sqlite(SnapshotDbManagerSqlite::new(
+                    snapshot_path,
+                    max_open_snapshots,
+                    use_isolated_db_for_mpt_table,
+                    use_isolated_db_for_mpt_table_height,
+                    era_epoch_count,
+                )?)
+            }
+            SnapshotDbBackend::Rocksdb => {
+                Self::Rocksdb(SnapshotDbManagerRocksdb::new(
+                    snapshot_path,
+                    max_open_snapshots,
+                    use_isolated_db_for_mpt_table,
+                    use_isolated_db_for_mpt_table_height,
+                    era_epoch_count,
+                )?)
+            }
+            SnapshotDbBackend::Inmemory => {
+                Self::Inmemory(SnapshotDbManagerInmemory::new(
+                    snapshot_path,
+                    max_open_snapshots,
+                    use_isolated_db_for_mpt_table,
+                    use_isolated_db_for_mpt_table_height,
+                    era_epoch_count,
+                )?)
+            }
+        })
+    }
+
+    pub fn update_latest_snapshot_id(&self, snapshot_id: EpochId, height: u64) {
+        match self {
+            Self::Sqlite(manager) => {
+                manager.update_latest_snapshot_id(snapshot_id, height)
+            }
+            Self::Rocksdb(manager) => {
+                manager.update_latest_snapshot_id(snapshot_id, height)
+            }
+            Self::Inmemory(manager) => {
+                manager.update_latest_snapshot_id(snapshot_id, height)
+            }
+        }
+    }
+
+    pub fn clean_snapshot_epoch_id_before_recovered(&self) {
+        match self {
+            Self::Sqlite(manager) => {
+                manager.clean_snapshot_epoch_id_before_recovered()
+            }
+            Self::Rocksdb(manager) => {
+                manager.clean_snapshot_epoch_id_before_recovered()
+            }
+            Self::Inmemory(manager) => {
+                manager.clean_snapshot_epoch_id_before_recovered()
+            }
+        }
+    }
+
+    pub fn set_reconstruct_snapshot_id(
+        &self, reconstruct_pivot: Option<EpochId>,
+    ) {
+        match self {
+            Self::Sqlite(manager) => {
+                manager.set_reconstruct_snapshot_id(reconstruct_pivot)
+            }
+            Self::Rocksdb(manager) => {
+                manager.set_reconstruct_snapshot_id(reconstruct_pivot)
+            }
+            Self::Inmemory(manager) => {
+                manager.set_reconstruct_snapshot_id(reconstruct_pivot)
+            }
+        }
+    }
+
+    pub fn recreate_latest_mpt_snapshot(&self) -> Result<()> {
+        match self {
+            Self::Sqlite(manager) => manager.recreate_latest_mpt_snapshot(),
+            Self::Rocksdb(manager) => manager.recreate_latest_mpt_snapshot(),
+            Self::Inmemory(manager) => manager.recreate_latest_mpt_snapshot(),
+        }
+    }
+}
+
+impl SnapshotDbManagerTrait for SnapshotDbManagerDispatch {
+    type SnapshotDb = SnapshotDbDispatch;
+    type SnapshotDbWrite = SnapshotDbWriteDispatch;
+
+    fn get_snapshot_dir(&self) -> &Path {
+        match self {
+            Self::Sqlite(manager) => manager.get_snapshot_dir(),
+            Self::Rocksdb(manager) => manager.get_snapshot_dir(),
+            Self::Inmemory(manager) => manager.get_snapshot_dir(),
+        }
+    }
+
+    fn get_snapshot_db_name(&self, snapshot_epoch_id: &EpochId) -> String {
+        match self {
+            Self::Sqlite(manager) => {
+                manager.get_snapshot_db_name(snapshot_epoch_id)
+            }
+            Self::Rocksdb(manager) => {
+                manager.get_snapshot_db_name(snapshot_epoch_id)
+            }
+            Self::Inmemory(manager) => {
+                manager.get_snapshot_db_name(snapshot_epoch_id)
+            }
+        }
+    }
+
+    fn get_snapshot_db_path(&self, snapshot_epoch_id: &EpochId) -> PathBuf {
+        match self {
+            Self::Sqlite(manager) => {
+                manager.get_snapshot_db_path(snapshot_epoch_id)
+            }
+            Self::Rocksdb(manager) => {
+                manager.get_snapshot_db_path(snapshot_epoch_id)
+            }
+            Self::Inmemory(manager) => {
+                manager.get_snapshot_db_path(snapshot_epoch_id)
+            }
+        }
+    }
+
+    fn get_mpt_snapshot_dir(&self) -> &Path {
+        match self {
+            Self::Sqlite(manager) => manager.get_mpt_snapshot_dir(),
+            Self::Rocksdb(manager) => manager.get_mpt_snapshot_dir(),
+            Self::Inmemory(manager) => manager.get_mpt_snapshot_dir(),
+        }
+    }
+
+    fn get_latest_mpt_snapshot_db_name(&self) -> String {
+        match self {
+            Self::Sqlite(manager) => manager.get_latest_mpt_snapshot_db_name(),
+            Self::Rocksdb(manager) => manager.get_latest_mpt_snapshot_db_name(),
+            Self::Inmemory(manager) => {
+                manager.get_latest_mpt_snapshot_db_name()
+            }
+        }
+    }
+
+    fn recovery_latest_mpt_snapshot_from_checkpoint(
+        &self, snapshot_epoch_id: &EpochId,
+        before_era_pivot_hash: Option<EpochId>,
+    ) -> Result<()> {
+        match self {
+            Self::Sqlite(manager) => manager
+                .recovery_latest_mpt_snapshot_from_checkpoint(
+                    snapshot_epoch_id,
+                    before_era_pivot_hash,
+                ),
+            Self::Rocksdb(manager) => manager
+                .recovery_latest_mpt_snapshot_from_checkpoint(
+                    snapshot_epoch_id,
+                    before_era_pivot_hash,
+                ),
+            Self::Inmemory(manager) => manager
+                .recovery_latest_mpt_snapshot_from_checkpoint(
+                    snapshot_epoch_id,
+                    before_era_pivot_hash,
+                ),
+        }
+    }
+
+    fn create_mpt_snapshot_from_latest(
+        &self, new_snapshot_epoch_id: &EpochId,
+    ) -> Result<()> {
+        match self {
+            Self::Sqlite(manager) => {
+                manager.create_mpt_snapshot_from_latest(new_snapshot_epoch_id)
+            }
+            Self::Rocksdb(manager) => {
+                manager.create_mpt_snapshot_from_latest(new_snapshot_epoch_id)
+            }
+            Self::Inmemory(manager) => {
+                manager.create_mpt_snapshot_from_latest(new_snapshot_epoch_id)
+            }
+        }
+    }
+
+    fn get_epoch_id_from_snapshot_db_name(
+        &self, snapshot_db_name: &str,
+    ) -> Result<EpochId> {
+        match self {
+            Self::Sqlite(manager) => {
+                manager.get_epoch_id_from_snapshot_db_name(snapshot_db_name)
+            }
+            Self::Rocksdb(manager) => {
+                manager.get_epoch_id_from_snapshot_db_name(snapshot_db_name)
+            }
+            Self::Inmemory(manager) => {
+                manager.get_epoch_id_from_snapshot_db_name(snapshot_db_name)
+            }
+        }
+    }
+
+    fn try_get_new_snapshot_epoch_from_temp_path(
+        &self, dir_name: &str,
+    ) -> Option<EpochId> {
+        match self {
+            Self::Sqlite(manager) => {
+                manager.try_get_new_snapshot_epoch_from_temp_path(dir_name)
+            }
+            Self::Rocksdb(manager) => {
+                manager.try_get_new_snapshot_epoch_from_temp_path(dir_name)
+            }
+            Self::Inmemory(manager) => {
+                manager.try_get_new_snapshot_epoch_from_temp_path(dir_name)
+            }
+        }
+    }
+
+    fn try_get_new_snapshot_epoch_from_mpt_temp_path(
+        &self, dir_name: &str,
+    ) -> Option<EpochId> {
+        match self {
+            Self::Sqlite(manager) => {
+                manager.try_get_new_snapshot_epoch_from_mpt_temp_path(dir_name)
+            }
+            Self::Rocksdb(manager) => {
+                manager.try_get_new_snapshot_epoch_from_mpt_temp_path(dir_name)
+            }
+            Self::Inmemory(manager) => {
+                manager.try_get_new_snapshot_epoch_from_mpt_temp_path(dir_name)
+            }
+        }
+    }
+
+    fn scan_persist_state(
+        &self, snapshot_info_map: &HashMap<EpochId, SnapshotInfo>,
+    ) -> Result<SnapshotPersistState> {
+        match self {
+            Self::Sqlite(manager) => {
+                manager.scan_persist_state(snapshot_info_map)
+            }
+            Self::Rocksdb(manager) => {
+                manager.scan_persist_state(snapshot_info_map)
+            }
+            Self::Inmemory(manager) => {
+                manager.scan_persist_state(snapshot_info_map)
+            }
+        }
+    }
+
+    fn new_snapshot_by_merging<'m>(
+        &self, old_snapshot_epoch_id: &EpochId, snapshot_epoch_id: EpochId,
+        delta_mpt: DeltaMptIterator, in_progress_snapshot_info: SnapshotInfo,
+        snapshot_info_map: &'m RwLock<PersistedSnapshotInfoMap>,
+        new_epoch_height: u64, recover_mpt_with_kv_snapshot_exist: bool,
+    ) -> Result<(RwLockWriteGuard<'m, PersistedSnapshotInfoMap>, SnapshotInfo)>
+    {
+        match self {
+            Self::Sqlite(manager) => manager.new_snapshot_by_merging(
+                old_snapshot_epoch_id,
+                snapshot_epoch_id,
+                delta_mpt,
+                in_progress_snapshot_info,
+                snapshot_info_map,
+                new_epoch_height,
+                recover_mpt_with_kv_snapshot_exist,
+            ),
+            Self::Rocksdb(manager) => manager.new_snapshot_by_merging(
+                old_snapshot_epoch_id,
+                snapshot_epoch_id,
+                delta_mpt,
+                in_progress_snapshot_info,
+                snapshot_info_map,
+                new_epoch_height,
+                recover_mpt_with_kv_snapshot_exist,
+            ),
+            Self::Inmemory(manager) => manager.new_snapshot_by_merging(
+                old_snapshot_epoch_id,
+                snapshot_epoch_id,
+                delta_mpt,
+                in_progress_snapshot_info,
+                snapshot_info_map,
+                new_epoch_height,
+                recover_mpt_with_kv_snapshot_exist,
+            ),
+        }
+    }
+
+    fn get_snapshot_by_epoch_id(
+        &self, epoch_id: &EpochId, try_open: bool, open_mpt_snapshot: bool,
+    ) -> Result<Option<Self::SnapshotDb>> {
+        Ok(match self {
+            Self::Sqlite(manager) => manager
+                .get_snapshot_by_epoch_id(
+                    epoch_id,
+                    try_open,
+                    open_mpt_snapshot,
+                )?
+                .map(SnapshotDbDispatch::Sqlite),
+            Self::Rocksdb(manager) => manager
+                .get_snapshot_by_epoch_id(
+                    epoch_id,
+                    try_open,
+                    open_mpt_snapshot,
+                )?
+                .map(SnapshotDbDispatch::Rocksdb),
+            Self::Inmemory(manager) => manager
+                .get_snapshot_by_epoch_id(
+                    epoch_id,
+                    try_open,
+                    open_mpt_snapshot,
+                )?
+                .map(SnapshotDbDispatch::Inmemory),
+        })
+    }
+
+    fn destroy_snapshot(&self, snapshot_epoch_id: &EpochId) -> Result<()> {
+        match self {
+            Self::Sqlite(manager) => {
+                manager.destroy_snapshot(snapshot_epoch_id)
+            }
+            Self::Rocksdb(manager) => {
+                manager.destroy_snapshot(snapshot_epoch_id)
+            }
+            Self::Inmemory(manager) => {
+                manager.destroy_snapshot(snapshot_epoch_id)
+            }
+        }
+    }
+
+    fn checkpoint_snapshot(
+        &self, snapshot_epoch_id: &EpochId, dest_snapshot_dir: &Path,
+    ) -> Result<()> {
+        match self {
+            Self::Sqlite(manager) => manager
+                .checkpoint_snapshot(snapshot_epoch_id, dest_snapshot_dir),
+            Self::Rocksdb(manager) => manager
+                .checkpoint_snapshot(snapshot_epoch_id, dest_snapshot_dir),
+            Self::Inmemory(manager) => manager
+                .checkpoint_snapshot(snapshot_epoch_id, dest_snapshot_dir),
+        }
+    }
+
+    fn checkpoint_latest_mpt_snapshot(
+        &self, dest_snapshot_dir: &Path,
+    ) -> Result<()> {
+        match self {
+            Self::Sqlite(manager) => {
+                manager.checkpoint_latest_mpt_snapshot(dest_snapshot_dir)
+            }
+            Self::Rocksdb(manager) => {
+                manager.checkpoint_latest_mpt_snapshot(dest_snapshot_dir)
+            }
+            Self::Inmemory(manager) => {
+                manager.checkpoint_latest_mpt_snapshot(dest_snapshot_dir)
+            }
+        }
+    }
+
+    fn replace_snapshot_if_closed(
+        &self, snapshot_epoch_id: &EpochId, rebuilt_snapshot_dir: &Path,
+    ) -> Result<bool> {
+        match self {
+            Self::Sqlite(manager) => manager.replace_snapshot_if_closed(
+                snapshot_epoch_id,
+                rebuilt_snapshot_dir,
+            ),
+            Self::Rocksdb(manager) => manager.replace_snapshot_if_closed(
+                snapshot_epoch_id,
+                rebuilt_snapshot_dir,
+            ),
+            Self::Inmemory(manager) => manager.replace_snapshot_if_closed(
+                snapshot_epoch_id,
+                rebuilt_snapshot_dir,
+            ),
+        }
+    }
+
+    fn new_temp_snapshot_for_full_sync(
+        &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
+        new_epoch_height: u64,
+    ) -> Result<Self::SnapshotDbWrite> {
+        Ok(match self {
+            Self::Sqlite(manager) => SnapshotDbWriteDispatch::Sqlite(
+                manager.new_temp_snapshot_for_full_sync(
+                    snapshot_epoch_id,
+                    merkle_root,
+                    new_epoch_height,
+                )?,
+            ),
+            Self::Rocksdb(manager) => SnapshotDbWriteDispatch::Rocksdb(
+                manager.new_temp_snapshot_for_full_sync(
+                    snapshot_epoch_id,
+                    merkle_root,
+                    new_epoch_height,
+                )?,
+            ),
+            Self::Inmemory(manager) => SnapshotDbWriteDispatch::Inmemory(
+                manager.new_temp_snapshot_for_full_sync(
+                    snapshot_epoch_id,
+                    merkle_root,
+                    new_epoch_height,
+                )?,
+            ),
+        })
+    }
+
+    fn finalize_full_sync_snapshot<'m>(
+        &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
+        snapshot_info_map_rwlock: &'m RwLock<PersistedSnapshotInfoMap>,
+    ) -> Result<RwLockWriteGuard<'m, PersistedSnapshotInfoMap>> {
+        match self {
+            Self::Sqlite(manager) => manager.finalize_full_sync_snapshot(
+                snapshot_epoch_id,
+                merkle_root,
+                snapshot_info_map_rwlock,
+            ),
+            Self::Rocksdb(manager) => manager.finalize_full_sync_snapshot(
+                snapshot_epoch_id,
+                merkle_root,
+                snapshot_info_map_rwlock,
+            ),
+            Self::Inmemory(manager) => manager.finalize_full_sync_snapshot(
+                snapshot_epoch_id,
+                merkle_root,
+                snapshot_info_map_rwlock,
+            ),
+        }
+    }
+}
+
+impl SnapshotDbDispatch {
+    pub fn has_mpt_snapshot(&self) -> bool {
+        match self {
+            Self::Sqlite(snapshot_db) => snapshot_db.has_mpt_snapshot(),
+            Self::Rocksdb(snapshot_db) => snapshot_db.has_mpt_snapshot(),
+            Self::Inmemory(snapshot_db) => snapshot_db.has_mpt_snapshot(),
+        }
+    }
+
+    pub fn dumped_delta_kv_set_keys_iterator(
+        &self,
+    ) -> Result<SnapshotKvdbIterDispatch> {
+        Ok(match self {
+            Self::Sqlite(snapshot_db) => SnapshotKvdbIterDispatch::Sqlite(
+                snapshot_db.dumped_delta_kv_set_keys_iterator()?,
+            ),
+            Self::Rocksdb(snapshot_db) => SnapshotKvdbIterDispatch::Rocksdb(
+                snapshot_db.dumped_delta_kv_set_keys_iterator()?,
+            ),
+            Self::Inmemory(snapshot_db) => SnapshotKvdbIterDispatch::Inmemory(
+                snapshot_db.dumped_delta_kv_set_keys_iterator()?,
+            ),
+        })
+    }
+
+    pub fn dumped_delta_kv_delete_keys_iterator(
+        &self,
+    ) -> Result<SnapshotKvdbIterDispatch> {
+        Ok(match self {
+            Self::Sqlite(snapshot_db) => SnapshotKvdbIterDispatch::SqliteKeys(
+                snapshot_db.dumped_delta_kv_delete_keys_iterator()?,
+            ),
+            Self::Rocksdb(snapshot_db) => SnapshotKvdbIterDispatch::Rocksdb(
+                snapshot_db.dumped_delta_kv_delete_keys_iterator()?,
+            ),
+            Self::Inmemory(snapshot_db) => SnapshotKvdbIterDispatch::Inmemory(
+                snapshot_db.dumped_delta_kv_delete_keys_iterator()?,
+            ),
+        })
+    }
+}
+
+impl KeyValueDbTypes for SnapshotDbDispatch {
+    type ValueType = Box<[u8]>;
+}
+
+impl KeyValueDbTraitRead for SnapshotDbDispatch {
+    fn get(&self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        match self {
+            Self::Sqlite(snapshot_db) => snapshot_db.get(key),
+            Self::Rocksdb(snapshot_db) => snapshot_db.get(key),
+            Self::Inmemory(snapshot_db) => snapshot_db.get(key),
+        }
+    }
+}
+
+impl KeyValueDbTraitOwnedRead for SnapshotDbDispatch {
+    fn get_mut(&mut self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        match self {
+            Self::Sqlite(snapshot_db) => snapshot_db.get_mut(key),
+            Self::Rocksdb(snapshot_db) => snapshot_db.get_mut(key),
+            Self::Inmemory(snapshot_db) => snapshot_db.get_mut(key),
+        }
+    }
+}
+
+impl KeyValueDbTraitSingleWriter for SnapshotDbDispatch {
+    fn delete(&mut self, key: &[u8]) -> Result<Option<Option<Box<[u8]>>>> {
+        match self {
+            Self::Sqlite(snapshot_db) => snapshot_db.delete(key),
+            Self::Rocksdb(snapshot_db) => snapshot_db.delete(key),
+            Self::Inmemory(snapshot_db) => snapshot_db.delete(key),
+        }
+    }
+
+    fn put(
+        &mut self, key: &[u8], value: &[u8],
+    ) -> Result<Option<Option<Box<[u8]>>>> {
+        match self {
+            Self::Sqlite(snapshot_db) => snapshot_db.put(key, value),
+            Self::Rocksdb(snapshot_db) => snapshot_db.put(key, value),
+            Self::Inmemory(snapshot_db) => snapshot_db.put(key, value),
+        }
+    }
+}
+
+impl<'db> OpenSnapshotMptTrait<'db> for SnapshotDbDispatch {
+    type SnapshotDbAsOwnedType = SnapshotMptDispatch<
+        <SnapshotDbSqlite as OpenSnapshotMptTrait<'db>>::SnapshotDbAsOwnedType,
+        <SnapshotDbRocksdb as OpenSnapshotMptTrait<'db>>::SnapshotDbAsOwnedType,
+    >;
+    type SnapshotDbBorrowMutType = SnapshotMptDispatch<
+        <SnapshotDbSqlite as OpenSnapshotMptTrait<'db>>::SnapshotDbBorrowMutType,
+        <SnapshotDbRocksdb as OpenSnapshotMptTrait<'db>>::SnapshotDbBorrowMutType,
+    >;
+    type SnapshotDbBorrowSharedType = SnapshotMptDispatch<
+        <SnapshotDbSqlite as OpenSnapshotMptTrait<'db>>::SnapshotDbBorrowSharedType,
+        <SnapshotDbRocksdb as OpenSnapshotMptTrait<'db>>::SnapshotDbBorrowSharedType,
+    >;
+
+    fn open_snapshot_mpt_owned(
+        &'db mut self,
+    ) -> Result<Self::SnapshotDbBorrowMutType> {
+        Ok(match self {
+            Self::Sqlite(snapshot_db) => SnapshotMptDispatch::Sqlite(
+                snapshot_db.open_snapshot_mpt_owned()?,
+            ),
+            Self::Rocksdb(snapshot_db) => SnapshotMptDispatch::Rocksdb(
+                snapshot_db.open_snapshot_mpt_owned()?,
+            ),
+            Self::Inmemory(snapshot_db) => SnapshotMptDispatch::Inmemory(
+                OpenSnapshotMptTrait::open_snapshot_mpt_owned(snapshot_db)?,
+            ),
+        })
+    }
+
+    fn open_snapshot_mpt_as_owned(
+        &'db self,
+    ) -> Result<Self::SnapshotDbAsOwnedType> {
+        Ok(match self {
+            Self::Sqlite(snapshot_db) => SnapshotMptDispatch::Sqlite(
+                snapshot_db.open_snapshot_mpt_as_owned()?,
+            ),
+            Self::Rocksdb(snapshot_db) => SnapshotMptDispatch::Rocksdb(
+                snapshot_db.open_snapshot_mpt_as_owned()?,
+            ),
+            Self::Inmemory(snapshot_db) => SnapshotMptDispatch::Inmemory(
+                snapshot_db.open_snapshot_mpt_as_owned()?,
+            ),
+        })
+    }
+
+    fn open_snapshot_mpt_shared(
+        &'db self,
+    ) -> Result<Self::SnapshotDbBorrowSharedType> {
+        Ok(match self {
+            Self::Sqlite(snapshot_db) => SnapshotMptDispatch::Sqlite(
+                snapshot_db.open_snapshot_mpt_shared()?,
+            ),
+            Self::Rocksdb(snapshot_db) => SnapshotMptDispatch::Rocksdb(
+                snapshot_db.open_snapshot_mpt_shared()?,
+            ),
+            Self::Inmemory(snapshot_db) => SnapshotMptDispatch::Inmemory(
+                snapshot_db.open_snapshot_mpt_shared()?,
+            ),
+        })
+    }
+}
+
+/// Snapshots are opened, created and merged by the snapshot db manager of
+/// their backend, never through the dispatch.
+impl SnapshotDbTrait for SnapshotDbDispatch {
+    type SnapshotKvdbIterTraitTag = SnapshotKvdbIterDispatchTag;
+    type SnapshotKvdbIterType = SnapshotKvdbIterDispatch;
+    type SnapshotMptDb = ();
+
+    /// The null snapshot of all backends is empty.
+    fn get_null_snapshot() -> Self {
+        Self::Sqlite(SnapshotDbSqlite::get_null_snapshot())
+    }
+
+    fn open(
+        _snapshot_path: &Path, _readonly: bool,
+        _already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        _open_semaphore: &Arc<Semaphore>,
+    ) -> Result<Self> {
+        bail!("A snapshot is opened by the snapshot db manager of its backend")
+    }
+
+    fn create(
+        _snapshot_path: &Path,
+        _already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        _open_semaphore: &Arc<Semaphore>, _mpt_table_in_current_db: bool,
+    ) -> Result<Self> {
+        bail!("A snapshot is created by the snapshot db manager of its backend")
+    }
+
+    fn direct_merge(
+        &mut self, _old_snapshot_db: Option<&Arc<Self>>,
+        _mpt_snapshot: &mut Option<()>,
+        _recover_mpt_with_kv_snapshot_exist: bool,
+        _in_reconstruct_snapshot_state: bool,
+    ) -> Result<MerkleHash> {
+        bail!("A snapshot is merged by the snapshot db manager of its backend")
+    }
+
+    fn copy_and_merge(
+        &mut self, _old_snapshot_db: &Arc<Self>,
+        _mpt_snapshot_db: &mut Option<()>,
+        _in_reconstruct_snapshot_state: bool,
+    ) -> Result<MerkleHash> {
+        bail!("A snapshot is merged by the snapshot db manager of its backend")
+    }
+
+    fn start_transaction(&mut self) -> Result<()> {
+        match self {
+            Self::Sqlite(snapshot_db) => snapshot_db.start_transaction(),
+            Self::Rocksdb(snapshot_db) => snapshot_db.start_transaction(),
+            Self::Inmemory(snapshot_db) => {
+                SnapshotDbTrait::start_transaction(snapshot_db)
+            }
+        }
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        match self {
+            Self::Sqlite(snapshot_db) => snapshot_db.commit_transaction(),
+            Self::Rocksdb(snapshot_db) => snapshot_db.commit_transaction(),
+            Self::Inmemory(snapshot_db) => {
+                SnapshotDbTrait::commit_transaction(snapshot_db)
+            }
+        }
+    }
+
+    fn is_mpt_table_in_current_db(&self) -> bool {
+        match self {
+            Self::Sqlite(snapshot_db) => {
+                snapshot_db.is_mpt_table_in_current_db()
+            }
+            Self::Rocksdb(snapshot_db) => {
+                snapshot_db.is_mpt_table_in_current_db()
+            }
+            Self::Inmemory(snapshot_db) => {
+                snapshot_db.is_mpt_table_in_current_db()
+            }
+        }
+    }
+
+    fn snapshot_kv_iterator(
+        &self,
+    ) -> Result<
+        Wrap<
+            Self::SnapshotKvdbIterType,
+            dyn KeyValueDbIterableTrait<
+                MptKeyValue,
+                [u8],
+                SnapshotKvdbIterDispatchTag,
+            >,
+        >,
+    > {
+        Ok(Wrap(match self {
+            Self::Sqlite(snapshot_db) => SnapshotKvdbIterDispatch::Sqlite(
+                snapshot_db.snapshot_kv_iterator()?.take(),
+            ),
+            Self::Rocksdb(snapshot_db) => SnapshotKvdbIterDispatch::Rocksdb(
+                snapshot_db.snapshot_kv_iterator()?.take(),
+            ),
+            Self::Inmemory(snapshot_db) => SnapshotKvdbIterDispatch::Inmemory(
+                snapshot_db.snapshot_kv_iterator()?.take(),
+            ),
+        }))
+    }
+}
+
+impl KeyValueDbTypes for SnapshotDbWriteDispatch {
+    type ValueType = Box<[u8]>;
+}
+
+impl SnapshotDbWriteableTrait for SnapshotDbWriteDispatch {
+    type SnapshotDbBorrowMutType = SnapshotMptDispatch<
+        <SnapshotDbWriteable as SnapshotDbWriteableTrait>::SnapshotDbBorrowMutType,
+        <SnapshotKvDbRocksdb as SnapshotDbWriteableTrait>::SnapshotDbBorrowMutType,
+    >;
+
+    fn start_transaction(&mut self) -> Result<()> {
+        match self {
+            Self::Sqlite(snapshot_db) => {
+                SnapshotDbWriteableTrait::start_transaction(snapshot_db)
+            }
+            Self::Rocksdb(snapshot_db) => {
+                SnapshotDbWriteableTrait::start_transaction(snapshot_db)
+            }
+            Self::Inmemory(snapshot_db) => {
+                SnapshotDbWriteableTrait::start_transaction(snapshot_db)
+            }
+        }
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        match self {
+            Self::Sqlite(snapshot_db) => {
+                SnapshotDbWriteableTrait::commit_transaction(snapshot_db)
+            }
+            Self::Rocksdb(snapshot_db) => {
+                SnapshotDbWriteableTrait::commit_transaction(snapshot_db)
+            }
+            Self::Inmemory(snapshot_db) => {
+                SnapshotDbWriteableTrait::commit_transaction(snapshot_db)
+            }
+        }
+    }
+
+    fn put_kv(
+        &mut self, key: &[u8], value: &<Self::ValueType as DbValueType>::Type,
+    ) -> Result<Option<Option<Self::ValueType>>> {
+        match self {
+            Self::Sqlite(snapshot_db) => snapshot_db.put_kv(key, value),
+            Self::Rocksdb(snapshot_db) => snapshot_db.put_kv(key, value),
+            Self::Inmemory(snapshot_db) => snapshot_db.put_kv(key, value),
+        }
+    }
+
+    fn open_snapshot_mpt_owned(
+        &mut self,
+    ) -> Result<Self::SnapshotDbBorrowMutType> {
+        Ok(match self {
+            Self::Sqlite(snapshot_db) => SnapshotMptDispatch::Sqlite(
+                SnapshotDbWriteableTrait::open_snapshot_mpt_owned(snapshot_db)?,
+            ),
+            Self::Rocksdb(snapshot_db) => SnapshotMptDispatch::Rocksdb(
+                SnapshotDbWriteableTrait::open_snapshot_mpt_owned(snapshot_db)?,
+            ),
+            Self::Inmemory(snapshot_db) => SnapshotMptDispatch::Inmemory(
+                SnapshotDbWriteableTrait::open_snapshot_mpt_owned(snapshot_db)?,
+            ),
+        })
+    }
+}
+
+impl<SqliteMpt: SnapshotMptTraitRead, RocksdbMpt: SnapshotMptTraitRead>
+    SnapshotMptTraitRead for SnapshotMptDispatch<SqliteMpt, RocksdbMpt>
+{
+    fn get_merkle_root(&self) -> MerkleHash {
+        match self {
+            Self::Sqlite(mpt) => mpt.get_merkle_root(),
+            Self::Rocksdb(mpt) => mpt.get_merkle_root(),
+            Self::Inmemory(mpt) => mpt.get_merkle_root(),
+        }
+    }
+
+    fn load_node(
+        &mut self, path: &dyn CompressedPathTrait,
+    ) -> Result<Option<SnapshotMptNode>> {
+        match self {
+            Self::Sqlite(mpt) => mpt.load_node(path),
+            Self::Rocksdb(mpt) => mpt.load_node(path),
+            Self::Inmemory(mpt) => mpt.load_node(path),
+        }
+    }
+}
+
+impl<
+        SqliteMpt: SnapshotMptTraitReadAndIterate,
+        RocksdbMpt: SnapshotMptTraitReadAndIterate,
+    > SnapshotMptTraitReadAndIterate
+    for SnapshotMptDispatch<SqliteMpt, RocksdbMpt>
+{
+    fn iterate_subtree_trie_nodes_without_root(
+        &mut self, path: &dyn CompressedPathTrait,
+    ) -> Result<Box<dyn SnapshotMptIteraterTrait + '_>> {
+        match self {
+            Self::Sqlite(mpt) => {
+                mpt.iterate_subtree_trie_nodes_without_root(path)
+            }
+            Self::Rocksdb(mpt) => {
+                mpt.iterate_subtree_trie_nodes_without_root(path)
+            }
+            Self::Inmemory(mpt) => {
+                mpt.iterate_subtree_trie_nodes_without_root(path)
+            }
+        }
+    }
+}
+
+impl<SqliteMpt: SnapshotMptTraitRw, RocksdbMpt: SnapshotMptTraitRw>
+    SnapshotMptTraitRw for SnapshotMptDispatch<SqliteMpt, RocksdbMpt>
+{
+    fn delete_node(&mut self, path: &dyn CompressedPathTrait) -> Result<()> {
+        match self {
+            Self::Sqlite(mpt) => mpt.delete_node(path),
+            Self::Rocksdb(mpt) => mpt.delete_node(path),
+            Self::Inmemory(mpt) => mpt.delete_node(path),
+        }
+    }
+
+    fn write_node(
+        &mut self, path: &dyn CompressedPathTrait, trie_node: &SnapshotMptNode,
+    ) -> Result<()> {
+        match self {
+            Self::Sqlite(mpt) => mpt.write_node(path, trie_node),
+            Self::Rocksdb(mpt) => mpt.write_node(path, trie_node),
+            Self::Inmemory(mpt) => mpt.write_node(path, trie_node),
+        }
+    }
+}
+
+impl FallibleIterator for SnapshotKvIterDispatch<'_> {
+    type Error = Error;
+    type Item = MptKeyValue;
+
+    fn next(&mut self) -> Result<Option<MptKeyValue>> {
+        match self {
+            Self::Sqlite(iter) => iter.next(),
+            Self::SqliteKeys(iter) => {
+                Ok(iter.next()?.map(|(key, ())| (key, Default::default())))
+            }
+            Self::Rocksdb(iter) => iter.next(),
+            Self::Inmemory(iter) => iter.next(),
+        }
+    }
+}
+
+impl WrappedTrait<dyn FallibleIterator<Item = MptKeyValue, Error = Error>>
+    for KvdbIterIterator<MptKeyValue, [u8], SnapshotKvdbIterDispatchTag>
+{
+}
+
+impl<'a>
+    WrappedLifetimeFamily<
+        'a,
+        dyn FallibleIterator<Item = MptKeyValue, Error = Error>,
+    > for KvdbIterIterator<MptKeyValue, [u8], SnapshotKvdbIterDispatchTag>
+{
+    type Out = SnapshotKvIterDispatch<'a>;
+}
+
+impl KeyValueDbIterableTrait<MptKeyValue, [u8], SnapshotKvdbIterDispatchTag>
+    for SnapshotKvdbIterDispatch
+{
+    fn iter_range(
+        &mut self, lower_bound_incl: &[u8], upper_bound_excl: Option<&[u8]>,
+    ) -> Result<
+        Wrap<
+            KvdbIterIterator<MptKeyValue, [u8], SnapshotKvdbIterDispatchTag>,
+            dyn FallibleIterator<Item = MptKeyValue, Error = Error>,
+        >,
+    > {
+        Ok(Wrap(match self {
+            Self::Sqlite(kvdb) => SnapshotKvIterDispatch::Sqlite(
+                kvdb.iter_range(lower_bound_incl, upper_bound_excl)?.take(),
+            ),
+            Self::SqliteKeys(kvdb) => SnapshotKvIterDispatch::SqliteKeys(
+                kvdb.iter_range(lower_bound_incl, upper_bound_excl)?.take(),
+            ),
+            Self::Rocksdb(kvdb) => SnapshotKvIterDispatch::Rocksdb(
+                kvdb.iter_range(lower_bound_incl, upper_bound_excl)?.take(),
+            ),
+            Self::Inmemory(kvdb) => SnapshotKvIterDispatch::Inmemory(
+                kvdb.iter_range(lower_bound_incl, upper_bound_excl)?.take(),
+            ),
+        }))
+    }
+
+    fn iter_range_excl(
+        &mut self, lower_bound_excl: &[u8], upper_bound_excl: &[u8],
+    ) -> Result<
+        Wrap<
+            KvdbIterIterator<MptKeyValue, [u8], SnapshotKvdbIterDispatchTag>,
+            dyn FallibleIterator<Item = MptKeyValue, Error = Error>,
+        >,
+    > {
+        Ok(Wrap(match self {
+            Self::Sqlite(kvdb) => SnapshotKvIterDispatch::Sqlite(
+                kvdb.iter_range_excl(lower_bound_excl, upper_bound_excl)?
+                    .take(),
+            ),
+            Self::SqliteKeys(kvdb) => SnapshotKvIterDispatch::SqliteKeys(
+                kvdb.iter_range_excl(lower_bound_excl, upper_bound_excl)?
+                    .take(),
+            ),
+            Self::Rocksdb(kvdb) => SnapshotKvIterDispatch::Rocksdb(
+                kvdb.iter_range_excl(lower_bound_excl, upper_bound_excl)?
+                    .take(),
+            ),
+            Self::Inmemory(kvdb) => SnapshotKvIterDispatch::Inmemory(
+                kvdb.iter_range_excl(lower_bound_excl, upper_bound_excl)?
+                    .take(),
+            ),
+        }))
+    }
+}
+
+impl
+    ElementSatisfy<
+        dyn KeyValueDbIterableTrait<
+            MptKeyValue,
+            [u8],
+            SnapshotKvdbIterDispatchTag,
+        >,
+    > for SnapshotKvdbIterDispatch
+{
+    fn to_constrain_object(
+        &self,
+    ) -> &(dyn KeyValueDbIterableTrait<
+        MptKeyValue,
+        [u8],
+        SnapshotKvdbIterDispatchTag,
+    > + 'static) {
+        self
+    }
+
+    fn to_constrain_object_mut(
+        &mut self,
+    ) -> &mut (dyn KeyValueDbIterableTrait<
+        MptKeyValue,
+        [u8],
+        SnapshotKvdbIterDispatchTag,
+    > + 'static) {
+        self
+    }
+}
+
+impl
+    WrappedLifetimeFamily<
+        '_,
+        dyn KeyValueDbIterableTrait<
+            MptKeyValue,
+            [u8],
+            SnapshotKvdbIterDispatchTag,
+        >,
+    > for SnapshotKvdbIterDispatch
+{
+    type Out = Self;
+}
+
+impl
+    WrappedTrait<
+        dyn KeyValueDbIterableTrait<
+            MptKeyValue,
+            [u8],
+            SnapshotKvdbIterDispatchTag,
+        >,
+    > for SnapshotKvdbIterDispatch
+{
+}
+
+use crate::{
+    impls::{
+        delta_mpt::DeltaMptIterator,
+        errors::*,
+        merkle_patricia_trie::{CompressedPathTrait, MptKeyValue},
+        storage_db::{
+            kvdb_inmemory::{KvdbInmemory, KvdbInmemoryIterator},
+            kvdb_rocksdb::{KvdbRocksdb, KvdbRocksdbIterator},
+            kvdb_sqlite_sharded::{
+                KvdbSqliteSharded, KvdbSqliteShardedIteratorTag,
+            },
+            snapshot_db_inmemory::SnapshotDbInmemory,
+            snapshot_db_manager_inmemory::SnapshotDbManagerInmemory,
+            snapshot_db_manager_rocksdb::SnapshotDbManagerRocksdb,
+            snapshot_db_manager_sqlite::{
+                AlreadyOpenSnapshots, SnapshotDbManagerSqlite,
+                SnapshotDbWriteable,
+            },
+            snapshot_db_rocksdb::SnapshotDbRocksdb,
+            snapshot_db_sqlite::SnapshotDbSqlite,
+            snapshot_kv_db_rocksdb::SnapshotKvDbRocksdb,
+            snapshot_mpt_inmemory::SnapshotMptInmemory,
+        },
+        storage_manager::PersistedSnapshotInfoMap,
+    },
+    storage_db::{
+        DbValueType, KeyValueDbIterableTrait, KeyValueDbTraitOwnedRead,
+        KeyValueDbTraitRead, KeyValueDbTraitSingleWriter, KeyValueDbTypes,
+        KvdbIterIterator, OpenSnapshotMptTrait, SnapshotDbManagerTrait,
+        SnapshotDbTrait, SnapshotDbWriteableTrait, SnapshotInfo,
+        SnapshotMptIteraterTrait, SnapshotMptNode, SnapshotMptTraitRead,
+        SnapshotMptTraitReadAndIterate, SnapshotMptTraitRw,
+        SnapshotPersistState,
+    },
+    utils::{
+        tuple::ElementSatisfy,
+        wrap::{Wrap, WrappedLifetimeFamily, WrappedTrait},
+    },
+    SnapshotDbBackend,
+};
+use fallible_iterator::FallibleIterator;
+use parking_lot::{RwLock, RwLockWriteGuard};
+use primitives::{EpochId, MerkleHash};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::sync::Semaphore;
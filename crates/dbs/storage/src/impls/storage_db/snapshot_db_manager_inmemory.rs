@@ -0,0 +1,348 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+/// Keeps the snapshots in memory by their names, with the snapshot MPT in the
+/// snapshot itself. Nothing is written under `snapshot_path`, which only
+/// serves to name the snapshots.
+///
+/// A new snapshot starts from a copy of its parent. A destroyed snapshot is
+/// released when the last user drops it, so there is no limit on the number
+/// of open snapshots.
+pub struct SnapshotDbManagerInmemory {
+    snapshot_path: PathBuf,
+    snapshots: RwLock<HashMap<String, SnapshotDbInmemory>>,
+    reconstruct_snapshot_id_for_reboot: RwLock<Option<EpochId>>,
+}
+
+impl SnapshotDbManagerInmemory {
+    const SNAPSHOT_DB_INMEMORY_NAME_PREFIX: &'static str = "inmemory_";
+
+    pub fn new(
+        snapshot_path: PathBuf, _max_open_snapshots: u16,
+        use_isolated_db_for_mpt_table: bool,
+        _use_isolated_db_for_mpt_table_height: Option<u64>,
+        _era_epoch_count: u64,
+    ) -> Result<Self> {
+        if use_isolated_db_for_mpt_table {
+            bail!(
+                "use_isolated_db_for_mpt_table is not supported by \
+                 in-memory snapshots"
+            );
+        }
+
+        Ok(Self {
+            snapshot_path,
+            snapshots: Default::default(),
+            reconstruct_snapshot_id_for_reboot: RwLock::new(None),
+        })
+    }
+
+    /// The latest MPT snapshot only exists with an isolated MPT db.
+    pub fn update_latest_snapshot_id(
+        &self, _snapshot_id: EpochId, _height: u64,
+    ) {
+    }
+
+    pub fn clean_snapshot_epoch_id_before_recovered(&self) {}
+
+    pub fn set_reconstruct_snapshot_id(
+        &self, reconstruct_pivot: Option<EpochId>,
+    ) {
+        debug!("set_reconstruct_snapshot_id to {:?}", reconstruct_pivot);
+        *self.reconstruct_snapshot_id_for_reboot.write() = reconstruct_pivot;
+    }
+
+    pub fn recreate_latest_mpt_snapshot(&self) -> Result<()> { Ok(()) }
+
+    fn is_in_reconstruct_snapshot_state(
+        &self, snapshot_epoch_id: &EpochId,
+    ) -> bool {
+        self.reconstruct_snapshot_id_for_reboot
+            .write()
+            .take()
+            .is_some_and(|v| &v == snapshot_epoch_id)
+    }
+
+    fn get_snapshot(
+        &self, snapshot_db_name: &str,
+    ) -> Option<SnapshotDbInmemory> {
+        self.snapshots.read().get(snapshot_db_name).cloned()
+    }
+
+    fn get_full_sync_temp_snapshot_db_name(
+        &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
+    ) -> String {
+        Self::SNAPSHOT_DB_INMEMORY_NAME_PREFIX.to_string()
+            + "full_sync_temp_"
+            + &snapshot_epoch_id.as_ref().to_hex::<String>()
+            + &merkle_root.as_ref().to_hex::<String>()
+    }
+}
+
+impl SnapshotDbManagerTrait for SnapshotDbManagerInmemory {
+    type SnapshotDb = SnapshotDbInmemory;
+    type SnapshotDbWrite = SnapshotDbInmemory;
+
+    fn get_snapshot_dir(&self) -> &Path { self.snapshot_path.as_path() }
+
+    /// There is no MPT snapshot db, the MPT is kept in the snapshot db.
+    fn get_mpt_snapshot_dir(&self) -> &Path { self.snapshot_path.as_path() }
+
+    fn get_latest_mpt_snapshot_db_name(&self) -> String {
+        Self::SNAPSHOT_DB_INMEMORY_NAME_PREFIX.to_string() + "latest"
+    }
+
+    fn get_snapshot_db_name(&self, snapshot_epoch_id: &EpochId) -> String {
+        Self::SNAPSHOT_DB_INMEMORY_NAME_PREFIX.to_string()
+            + &snapshot_epoch_id.as_ref().to_hex::<String>()
+    }
+
+    fn get_snapshot_db_path(&self, snapshot_epoch_id: &EpochId) -> PathBuf {
+        self.snapshot_path
+            .join(&self.get_snapshot_db_name(snapshot_epoch_id))
+    }
+
+    fn get_epoch_id_from_snapshot_db_name(
+        &self, snapshot_db_name: &str,
+    ) -> Result<EpochId> {
+        let prefix_len = Self::SNAPSHOT_DB_INMEMORY_NAME_PREFIX.len();
+        Ok(EpochId::from_str(&snapshot_db_name[prefix_len..])
+            .map_err(|_op| "not correct snapshot db name")?)
+    }
+
+    /// Same as the default implementation, but scans the snapshots in memory
+    /// instead of the snapshot dir. A snapshot being merged isn't kept in
+    /// the manager, so there is never a temporary snapshot to report.
+    fn scan_persist_state(
+        &self, snapshot_info_map: &HashMap<EpochId, SnapshotInfo>,
+    ) -> Result<SnapshotPersistState> {
+        let mut missing_snapshots = HashMap::new();
+        let mut all_snapshots = HashSet::new();
+        for (snapshot_epoch_id, snapshot_info) in snapshot_info_map {
+            all_snapshots.insert(self.get_snapshot_db_name(snapshot_epoch_id));
+            // See the default implementation.
+            if snapshot_info.snapshot_info_kept_to_provide_sync
+                != SnapshotKeptToProvideSyncStatus::InfoOnly
+            {
+                missing_snapshots.insert(
+                    self.get_snapshot_db_name(snapshot_epoch_id),
+                    (snapshot_epoch_id.clone(), snapshot_info.height),
+                );
+            }
+        }
+
+        let mut max_epoch_height = 0;
+        let mut max_epoch_id = NULL_EPOCH;
+        let mut removed_snapshots = HashSet::new();
+        let mut max_snapshot_epoch_height_has_mpt = None;
+        self.snapshots.write().retain(|snapshot_db_name, _| {
+            if !all_snapshots.contains(snapshot_db_name) {
+                error!("Unexpected snapshot {}, deleted.", snapshot_db_name);
+                if let Ok(epoch_id) =
+                    self.get_epoch_id_from_snapshot_db_name(snapshot_db_name)
+                {
+                    removed_snapshots.insert(epoch_id);
+                }
+                return false;
+            }
+            if let Some((epoch, height)) =
+                missing_snapshots.remove(snapshot_db_name)
+            {
+                if height > max_epoch_height {
+                    max_epoch_height = height;
+                    max_epoch_id = epoch;
+                }
+                // The snapshot mpt is in every snapshot.
+                if max_snapshot_epoch_height_has_mpt
+                    .map_or(true, |max_height| max_height < height)
+                {
+                    max_snapshot_epoch_height_has_mpt = Some(height);
+                }
+            }
+            true
+        });
+
+        Ok(SnapshotPersistState {
+            missing_snapshots: missing_snapshots
+                .into_iter()
+                .map(|(_name, (snapshot_epoch_id, _))| snapshot_epoch_id)
+                .collect(),
+            max_epoch_id,
+            max_epoch_height,
+            temp_snapshot_db_existing: None,
+            removed_snapshots,
+            max_snapshot_epoch_height_has_mpt,
+        })
+    }
+
+    fn new_snapshot_by_merging<'m>(
+        &self, old_snapshot_epoch_id: &EpochId, snapshot_epoch_id: EpochId,
+        delta_mpt: DeltaMptIterator,
+        mut in_progress_snapshot_info: SnapshotInfo,
+        snapshot_info_map_rwlock: &'m RwLock<PersistedSnapshotInfoMap>,
+        _new_epoch_height: u64, recover_mpt_with_kv_snapshot_exist: bool,
+    ) -> Result<(RwLockWriteGuard<'m, PersistedSnapshotInfoMap>, SnapshotInfo)>
+    {
+        info!(
+            "new_snapshot_by_merging: old={:?} new={:?} recovering mpt={}",
+            old_snapshot_epoch_id,
+            snapshot_epoch_id,
+            recover_mpt_with_kv_snapshot_exist
+        );
+        let new_snapshot_db_name =
+            self.get_snapshot_db_name(&snapshot_epoch_id);
+
+        let mut snapshot_db = if recover_mpt_with_kv_snapshot_exist {
+            self.get_snapshot(&new_snapshot_db_name)
+                .ok_or(Error::from(Error::SnapshotNotFound))?
+        } else if *old_snapshot_epoch_id == NULL_EPOCH {
+            SnapshotDbInmemory::default()
+        } else {
+            self.get_snapshot(&self.get_snapshot_db_name(old_snapshot_epoch_id))
+                .ok_or(Error::from(Error::SnapshotNotFound))?
+                .deep_clone()
+        };
+        // Drop the delta mpt dump of the old snapshot.
+        snapshot_db.drop_delta_mpt_dump()?;
+        snapshot_db.dump_delta_mpt(&delta_mpt)?;
+        let new_snapshot_root = snapshot_db.direct_merge(
+            None,
+            &mut None,
+            recover_mpt_with_kv_snapshot_exist,
+            self.is_in_reconstruct_snapshot_state(&snapshot_epoch_id),
+        )?;
+
+        in_progress_snapshot_info.merkle_root = new_snapshot_root;
+        let locked = snapshot_info_map_rwlock.write();
+
+        if !recover_mpt_with_kv_snapshot_exist {
+            self.snapshots
+                .write()
+                .insert(new_snapshot_db_name, snapshot_db);
+        }
+
+        Ok((locked, in_progress_snapshot_info))
+    }
+
+    fn get_snapshot_by_epoch_id(
+        &self, snapshot_epoch_id: &EpochId, _try_open: bool,
+        _open_mpt_snapshot: bool,
+    ) -> Result<Option<Self::SnapshotDb>> {
+        if snapshot_epoch_id.eq(&NULL_EPOCH) {
+            Ok(Some(Self::SnapshotDb::get_null_snapshot()))
+        } else {
+            Ok(
+                self.get_snapshot(
+                    &self.get_snapshot_db_name(snapshot_epoch_id),
+                ),
+            )
+        }
+    }
+
+    fn destroy_snapshot(&self, snapshot_epoch_id: &EpochId) -> Result<()> {
+        debug!("destroy snapshot {:?}", snapshot_epoch_id);
+        self.snapshots
+            .write()
+            .remove(&self.get_snapshot_db_name(snapshot_epoch_id));
+        Ok(())
+    }
+
+    fn checkpoint_snapshot(
+        &self, _snapshot_epoch_id: &EpochId, _dest_snapshot_dir: &Path,
+    ) -> Result<()> {
+        bail!("In-memory snapshots can not be checkpointed")
+    }
+
+    /// There is no latest MPT snapshot.
+    fn checkpoint_latest_mpt_snapshot(
+        &self, _dest_snapshot_dir: &Path,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn replace_snapshot_if_closed(
+        &self, _snapshot_epoch_id: &EpochId, _rebuilt_snapshot_dir: &Path,
+    ) -> Result<bool> {
+        bail!("In-memory snapshots can not be replaced from disk")
+    }
+
+    fn new_temp_snapshot_for_full_sync(
+        &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
+        _epoch_height: u64,
+    ) -> Result<Self::SnapshotDbWrite> {
+        let temp_db_name = self.get_full_sync_temp_snapshot_db_name(
+            snapshot_epoch_id,
+            merkle_root,
+        );
+        let snapshot_db = SnapshotDbInmemory::default();
+        self.snapshots
+            .write()
+            .insert(temp_db_name, snapshot_db.clone());
+        Ok(snapshot_db)
+    }
+
+    fn finalize_full_sync_snapshot<'m>(
+        &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
+        snapshot_info_map_rwlock: &'m RwLock<PersistedSnapshotInfoMap>,
+    ) -> Result<RwLockWriteGuard<'m, PersistedSnapshotInfoMap>> {
+        let temp_db_name = self.get_full_sync_temp_snapshot_db_name(
+            snapshot_epoch_id,
+            merkle_root,
+        );
+        let locked = snapshot_info_map_rwlock.write();
+        let mut snapshots = self.snapshots.write();
+        let snapshot_db = snapshots
+            .remove(&temp_db_name)
+            .ok_or(Error::from(Error::SnapshotNotFound))?;
+        snapshots
+            .insert(self.get_snapshot_db_name(snapshot_epoch_id), snapshot_db);
+        Ok(locked)
+    }
+
+    fn recovery_latest_mpt_snapshot_from_checkpoint(
+        &self, _snapshot_epoch_id: &EpochId,
+        _snapshot_epoch_id_before_recovered: Option<EpochId>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn create_mpt_snapshot_from_latest(
+        &self, _new_snapshot_epoch_id: &EpochId,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// A snapshot being merged is never kept in the manager.
+    fn try_get_new_snapshot_epoch_from_temp_path(
+        &self, _dir_name: &str,
+    ) -> Option<EpochId> {
+        None
+    }
+
+    fn try_get_new_snapshot_epoch_from_mpt_temp_path(
+        &self, _dir_name: &str,
+    ) -> Option<EpochId> {
+        None
+    }
+}
+
+use crate::{
+    impls::{
+        delta_mpt::DeltaMptIterator, errors::*,
+        storage_db::snapshot_db_inmemory::SnapshotDbInmemory,
+        storage_manager::PersistedSnapshotInfoMap,
+    },
+    storage_db::{
+        SnapshotDbManagerTrait, SnapshotDbTrait, SnapshotInfo,
+        SnapshotKeptToProvideSyncStatus, SnapshotPersistState,
+    },
+};
+use parking_lot::{RwLock, RwLockWriteGuard};
+use primitives::{EpochId, MerkleHash, NULL_EPOCH};
+use rustc_hex::ToHex;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
@@ -0,0 +1,627 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+/// Keeps each snapshot in a RocksDB database, with the snapshot MPT in a
+/// column family of the same database. A new snapshot starts from a RocksDB
+/// checkpoint of its parent, which hard-links the data files instead of
+/// copying them.
+///
+/// There is no separate MPT snapshot database, so
+/// `use_isolated_db_for_mpt_table` isn't supported.
+pub struct SnapshotDbManagerRocksdb {
+    snapshot_path: PathBuf,
+    already_open_snapshots: AlreadyOpenSnapshots<SnapshotKvDbRocksdb>,
+    /// Set a limit on the number of open snapshots. When the limit is reached,
+    /// consensus initiated open should wait, other non-critical opens such as
+    /// rpc initiated opens should simply abort when the limit is reached.
+    open_snapshot_semaphore: Arc<Semaphore>,
+    open_create_delete_lock: Mutex<()>,
+    reconstruct_snapshot_id_for_reboot: RwLock<Option<EpochId>>,
+}
+
+impl SnapshotDbManagerRocksdb {
+    /// RocksDB snapshots keep the MPT in the snapshot db, so there is no
+    /// latest MPT snapshot to recover or copy from.
+    const NO_LATEST_MPT_SNAPSHOT: &'static str =
+        "The latest MPT snapshot is not supported by RocksDB snapshots";
+    const SNAPSHOT_DB_ROCKSDB_DIR_PREFIX: &'static str = "rocksdb_";
+
+    pub fn new(
+        snapshot_path: PathBuf, max_open_snapshots: u16,
+        use_isolated_db_for_mpt_table: bool,
+        _use_isolated_db_for_mpt_table_height: Option<u64>,
+        _era_epoch_count: u64,
+    ) -> Result<Self> {
+        if use_isolated_db_for_mpt_table {
+            bail!(
+                "use_isolated_db_for_mpt_table is not supported by \
+                 RocksDB snapshots"
+            );
+        }
+        if !snapshot_path.exists() {
+            fs::create_dir_all(snapshot_path.clone())?;
+        }
+        // Snapshots of the other backend would be deleted as unexpected files
+        // by scan_persist_state.
+        for entry in fs::read_dir(&snapshot_path)? {
+            if entry?
+                .file_name()
+                .to_str()
+                .map_or(false, |name| name.starts_with("sqlite_"))
+            {
+                bail!(format!(
+                    "{} contains SQLite snapshots, which can not be \
+                     used by RocksDB snapshots",
+                    snapshot_path.display()
+                ));
+            }
+        }
+
+        Ok(Self {
+            snapshot_path,
+            already_open_snapshots: Default::default(),
+            open_snapshot_semaphore: Arc::new(Semaphore::new(
+                max_open_snapshots as usize,
+            )),
+            open_create_delete_lock: Default::default(),
+            reconstruct_snapshot_id_for_reboot: RwLock::new(None),
+        })
+    }
+
+    /// The latest MPT snapshot only exists with an isolated MPT db.
+    pub fn update_latest_snapshot_id(
+        &self, _snapshot_id: EpochId, _height: u64,
+    ) {
+    }
+
+    pub fn clean_snapshot_epoch_id_before_recovered(&self) {}
+
+    pub fn set_reconstruct_snapshot_id(
+        &self, reconstruct_pivot: Option<EpochId>,
+    ) {
+        debug!("set_reconstruct_snapshot_id to {:?}", reconstruct_pivot);
+        *self.reconstruct_snapshot_id_for_reboot.write() = reconstruct_pivot;
+    }
+
+    pub fn recreate_latest_mpt_snapshot(&self) -> Result<()> {
+        bail!(Self::NO_LATEST_MPT_SNAPSHOT)
+    }
+
+    fn is_in_reconstruct_snapshot_state(
+        &self, snapshot_epoch_id: &EpochId,
+    ) -> bool {
+        self.reconstruct_snapshot_id_for_reboot
+            .write()
+            .take()
+            .is_some_and(|v| &v == snapshot_epoch_id)
+    }
+
+    fn open_snapshot_readonly(
+        &self, snapshot_path: PathBuf, try_open: bool,
+    ) -> Result<Option<Arc<SnapshotKvDbRocksdb>>> {
+        // To serialize simultaneous opens.
+        let _open_lock = self.open_create_delete_lock.lock();
+
+        if let Some(already_open) =
+            self.already_open_snapshots.read().get(&snapshot_path)
+        {
+            match already_open {
+                None => {
+                    // Already open for exclusive write
+                    return Ok(None);
+                }
+                Some(open_shared_weak) => {
+                    match Weak::upgrade(open_shared_weak) {
+                        None => {}
+                        Some(already_open) => {
+                            return Ok(Some(already_open));
+                        }
+                    }
+                }
+            }
+        }
+        if !snapshot_path.exists() {
+            return Ok(None);
+        }
+        let semaphore_permit = if try_open {
+            self.open_snapshot_semaphore
+                .try_acquire()
+                // Unfortunately we have to use map_error because the
+                // TryAcquireError isn't public.
+                .map_err(|_err| Error::SemaphoreTryAcquireError)?
+        } else {
+            executor::block_on(self.open_snapshot_semaphore.acquire())
+        };
+
+        // If it's not in already_open_snapshots, the rocksdb must have been
+        // closed.
+        while let Some(already_open) =
+            self.already_open_snapshots.read().get(&snapshot_path)
+        {
+            match already_open {
+                None => {
+                    // Already open for exclusive write
+                    return Ok(None);
+                }
+                Some(open_shared_weak) => {
+                    match Weak::upgrade(open_shared_weak) {
+                        None => {
+                            // Wait for the db being dropped to be removed
+                            // from `already_open_snapshots`, see the same
+                            // loop in SnapshotDbManagerSqlite.
+                            thread::sleep(Duration::from_millis(5));
+                            continue;
+                        }
+                        Some(already_open) => {
+                            return Ok(Some(already_open));
+                        }
+                    }
+                }
+            }
+        }
+
+        let snapshot_db = Arc::new(SnapshotKvDbRocksdb::open(
+            snapshot_path.as_path(),
+            /* readonly = */ true,
+            &self.already_open_snapshots,
+            &self.open_snapshot_semaphore,
+        )?);
+
+        semaphore_permit.forget();
+        self.already_open_snapshots
+            .write()
+            .insert(snapshot_path.into(), Some(Arc::downgrade(&snapshot_db)));
+
+        Ok(Some(snapshot_db))
+    }
+
+    fn open_snapshot_write(
+        &self, snapshot_path: PathBuf, create: bool,
+    ) -> Result<SnapshotKvDbRocksdb> {
+        let _open_lock = self.open_create_delete_lock.lock();
+
+        if self
+            .already_open_snapshots
+            .read()
+            .get(&snapshot_path)
+            .is_some()
+        {
+            bail!(Error::SnapshotAlreadyExists)
+        }
+
+        let semaphore_permit =
+            executor::block_on(self.open_snapshot_semaphore.acquire());
+
+        let snapshot_db = if create {
+            SnapshotKvDbRocksdb::create(
+                snapshot_path.as_path(),
+                &self.already_open_snapshots,
+                &self.open_snapshot_semaphore,
+                /* mpt_table_in_current_db = */ true,
+            )
+        } else if snapshot_path.exists() {
+            SnapshotKvDbRocksdb::open(
+                snapshot_path.as_path(),
+                /* readonly = */ false,
+                &self.already_open_snapshots,
+                &self.open_snapshot_semaphore,
+            )
+        } else {
+            bail!(Error::SnapshotNotFound);
+        }?;
+
+        semaphore_permit.forget();
+        self.already_open_snapshots
+            .write()
+            .insert(snapshot_path, None);
+        Ok(snapshot_db)
+    }
+
+    pub fn on_close(
+        already_open_snapshots: &AlreadyOpenSnapshots<SnapshotKvDbRocksdb>,
+        open_semaphore: &Arc<Semaphore>, path: &Path, remove_on_close: bool,
+    ) {
+        // Destroy at close.
+        if remove_on_close {
+            // When removal fails, we can not raise the error because this
+            // function is called within a destructor. Conflux will remove
+            // orphan storage upon restart.
+            Self::fs_remove_snapshot(path);
+        }
+        already_open_snapshots.write().remove(path);
+        open_semaphore.add_permits(1);
+    }
+
+    fn fs_remove_snapshot(path: &Path) {
+        debug!("Remove snapshot at {}", path.display());
+        let path = path.to_owned();
+        thread::spawn(move || {
+            if let Err(e) = fs::remove_dir_all(&path) {
+                error!("remove snapshot err: path={:?} err={:?}", path, e);
+            }
+            debug!("Finish removing snapshot at {}", path.display());
+        });
+    }
+
+    fn get_merge_temp_snapshot_db_path(
+        &self, old_snapshot_epoch_id: &EpochId, new_snapshot_epoch_id: &EpochId,
+    ) -> PathBuf {
+        self.snapshot_path.join(
+            Self::SNAPSHOT_DB_ROCKSDB_DIR_PREFIX.to_string()
+                + "merge_temp_"
+                + &old_snapshot_epoch_id.as_ref().to_hex::<String>()
+                + &new_snapshot_epoch_id.as_ref().to_hex::<String>(),
+        )
+    }
+
+    fn get_full_sync_temp_snapshot_db_path(
+        &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
+    ) -> PathBuf {
+        self.snapshot_path.join(
+            Self::SNAPSHOT_DB_ROCKSDB_DIR_PREFIX.to_string()
+                + "full_sync_temp_"
+                + &snapshot_epoch_id.as_ref().to_hex::<String>()
+                + &merkle_root.as_ref().to_hex::<String>(),
+        )
+    }
+
+    /// Create the new snapshot db at `temp_db_path` from a checkpoint of the
+    /// old snapshot. Return None if the checkpoint can not be created.
+    fn try_checkpoint_snapshot(
+        &self, old_snapshot_epoch_id: &EpochId, temp_db_path: &Path,
+    ) -> Result<Option<SnapshotKvDbRocksdb>> {
+        let old_snapshot_db = self
+            .open_snapshot_readonly(
+                self.get_snapshot_db_path(old_snapshot_epoch_id),
+                /* try_open = */ false,
+            )?
+            .ok_or(Error::from(Error::SnapshotNotFound))?;
+        debug!("checkpoint into {:?}", temp_db_path);
+        if let Err(e) = old_snapshot_db.checkpoint(temp_db_path) {
+            warn!(
+                "Fail to checkpoint snapshot {:?}, err={:?}",
+                old_snapshot_epoch_id, e,
+            );
+            if temp_db_path.exists() {
+                fs::remove_dir_all(temp_db_path)?;
+            }
+            return Ok(None);
+        }
+        drop(old_snapshot_db);
+
+        Ok(Some(self.open_snapshot_write(
+            temp_db_path.to_path_buf(),
+            /* create = */ false,
+        )?))
+    }
+
+    fn copy_and_merge(
+        &self, temp_snapshot_db: &mut SnapshotKvDbRocksdb,
+        old_snapshot_epoch_id: &EpochId, snapshot_epoch_id: EpochId,
+    ) -> Result<MerkleHash> {
+        let old_snapshot_db = self
+            .open_snapshot_readonly(
+                self.get_snapshot_db_path(old_snapshot_epoch_id),
+                /* try_open = */ false,
+            )?
+            .ok_or(Error::from(Error::SnapshotNotFound))?;
+        temp_snapshot_db.copy_and_merge(
+            &old_snapshot_db,
+            &mut None,
+            self.is_in_reconstruct_snapshot_state(&snapshot_epoch_id),
+        )
+    }
+}
+
+impl SnapshotDbManagerTrait for SnapshotDbManagerRocksdb {
+    type SnapshotDb = SnapshotDbRocksdb;
+    type SnapshotDbWrite = SnapshotKvDbRocksdb;
+
+    fn get_snapshot_dir(&self) -> &Path { self.snapshot_path.as_path() }
+
+    /// There is no MPT snapshot db, the MPT is kept in the snapshot db.
+    fn get_mpt_snapshot_dir(&self) -> &Path { self.snapshot_path.as_path() }
+
+    fn get_latest_mpt_snapshot_db_name(&self) -> String {
+        Self::SNAPSHOT_DB_ROCKSDB_DIR_PREFIX.to_string() + "latest"
+    }
+
+    fn get_snapshot_db_name(&self, snapshot_epoch_id: &EpochId) -> String {
+        Self::SNAPSHOT_DB_ROCKSDB_DIR_PREFIX.to_string()
+            + &snapshot_epoch_id.as_ref().to_hex::<String>()
+    }
+
+    fn get_snapshot_db_path(&self, snapshot_epoch_id: &EpochId) -> PathBuf {
+        self.snapshot_path
+            .join(&self.get_snapshot_db_name(snapshot_epoch_id))
+    }
+
+    fn get_epoch_id_from_snapshot_db_name(
+        &self, snapshot_db_name: &str,
+    ) -> Result<EpochId> {
+        let prefix_len = Self::SNAPSHOT_DB_ROCKSDB_DIR_PREFIX.len();
+        Ok(EpochId::from_str(&snapshot_db_name[prefix_len..])
+            .map_err(|_op| "not correct snapshot db name")?)
+    }
+
+    fn new_snapshot_by_merging<'m>(
+        &self, old_snapshot_epoch_id: &EpochId, snapshot_epoch_id: EpochId,
+        delta_mpt: DeltaMptIterator,
+        mut in_progress_snapshot_info: SnapshotInfo,
+        snapshot_info_map_rwlock: &'m RwLock<PersistedSnapshotInfoMap>,
+        _new_epoch_height: u64, recover_mpt_with_kv_snapshot_exist: bool,
+    ) -> Result<(RwLockWriteGuard<'m, PersistedSnapshotInfoMap>, SnapshotInfo)>
+    {
+        info!(
+            "new_snapshot_by_merging: old={:?} new={:?} recovering mpt={}",
+            old_snapshot_epoch_id,
+            snapshot_epoch_id,
+            recover_mpt_with_kv_snapshot_exist
+        );
+        // FIXME: clean-up when error happens.
+        let temp_db_path = self.get_merge_temp_snapshot_db_path(
+            old_snapshot_epoch_id,
+            &snapshot_epoch_id,
+        );
+        let new_snapshot_db_path =
+            self.get_snapshot_db_path(&snapshot_epoch_id);
+
+        let mut snapshot_db = if recover_mpt_with_kv_snapshot_exist {
+            let mut snapshot_db = self.open_snapshot_write(
+                new_snapshot_db_path.clone(),
+                /* create = */ false,
+            )?;
+            snapshot_db.drop_delta_mpt_dump()?;
+            Some(snapshot_db)
+        } else if *old_snapshot_epoch_id == NULL_EPOCH {
+            Some(self.open_snapshot_write(
+                temp_db_path.clone(),
+                /* create = */ true,
+            )?)
+        } else {
+            match self
+                .try_checkpoint_snapshot(old_snapshot_epoch_id, &temp_db_path)?
+            {
+                Some(mut snapshot_db) => {
+                    // Drop the delta mpt dump of the old snapshot.
+                    snapshot_db.drop_delta_mpt_dump()?;
+                    Some(snapshot_db)
+                }
+                None => None,
+            }
+        };
+
+        let new_snapshot_root = match &mut snapshot_db {
+            Some(snapshot_db) => {
+                snapshot_db.dump_delta_mpt(&delta_mpt)?;
+                snapshot_db.direct_merge(
+                    None,
+                    &mut None,
+                    recover_mpt_with_kv_snapshot_exist,
+                    self.is_in_reconstruct_snapshot_state(&snapshot_epoch_id),
+                )?
+            }
+            None => {
+                let mut new_snapshot_db = self.open_snapshot_write(
+                    temp_db_path.clone(),
+                    /* create = */ true,
+                )?;
+                new_snapshot_db.dump_delta_mpt(&delta_mpt)?;
+                let new_snapshot_root = self.copy_and_merge(
+                    &mut new_snapshot_db,
+                    old_snapshot_epoch_id,
+                    snapshot_epoch_id,
+                )?;
+                snapshot_db = Some(new_snapshot_db);
+                new_snapshot_root
+            }
+        };
+
+        in_progress_snapshot_info.merkle_root = new_snapshot_root.clone();
+        drop(snapshot_db);
+        let locked = snapshot_info_map_rwlock.write();
+
+        if !recover_mpt_with_kv_snapshot_exist {
+            fs::rename(&temp_db_path, &new_snapshot_db_path)?;
+        }
+
+        Ok((locked, in_progress_snapshot_info))
+    }
+
+    fn get_snapshot_by_epoch_id(
+        &self, snapshot_epoch_id: &EpochId, try_open: bool,
+        _open_mpt_snapshot: bool,
+    ) -> Result<Option<Self::SnapshotDb>> {
+        if snapshot_epoch_id.eq(&NULL_EPOCH) {
+            Ok(Some(Self::SnapshotDb::get_null_snapshot()))
+        } else {
+            let path = self.get_snapshot_db_path(snapshot_epoch_id);
+            Ok(self
+                .open_snapshot_readonly(path, try_open)?
+                .map(|snapshot_db| SnapshotDbRocksdb { snapshot_db }))
+        }
+    }
+
+    fn destroy_snapshot(&self, snapshot_epoch_id: &EpochId) -> Result<()> {
+        debug!("destroy snapshot {:?}", snapshot_epoch_id);
+        let path = self.get_snapshot_db_path(snapshot_epoch_id);
+        let maybe_snapshot = loop {
+            match self.already_open_snapshots.read().get(&path) {
+                Some(Some(snapshot)) => {
+                    match Weak::upgrade(snapshot) {
+                        None => {
+                            // This is transient and we wait for the db to be
+                            // fully closed.
+                            thread::sleep(Duration::from_millis(5));
+                            continue;
+                        }
+                        Some(snapshot) => break Some(snapshot),
+                    }
+                }
+                Some(None) => {
+                    // Conflux always write on a snapshot db under a
+                    // temporary name. All completed snapshots are readonly.
+                    bail!(
+                        "Try to destroy the snapshot {:?} being open \
+                         exclusively for write.",
+                        snapshot_epoch_id
+                    )
+                }
+                None => break None,
+            };
+        };
+
+        match maybe_snapshot {
+            None => {
+                if snapshot_epoch_id.ne(&NULL_EPOCH) {
+                    Self::fs_remove_snapshot(&path);
+                }
+            }
+            Some(snapshot) => {
+                snapshot.set_remove_on_last_close();
+            }
+        };
+
+        Ok(())
+    }
+
+    fn checkpoint_snapshot(
+        &self, snapshot_epoch_id: &EpochId, dest_snapshot_dir: &Path,
+    ) -> Result<()> {
+        let snapshot_db = self
+            .open_snapshot_readonly(
+                self.get_snapshot_db_path(snapshot_epoch_id),
+                /* try_open = */ false,
+            )?
+            .ok_or(Error::from(Error::SnapshotNotFound))?;
+        fs::create_dir_all(dest_snapshot_dir)?;
+        snapshot_db.checkpoint(
+            &dest_snapshot_dir
+                .join(self.get_snapshot_db_name(snapshot_epoch_id)),
+        )
+    }
+
+    /// There is no latest MPT snapshot.
+    fn checkpoint_latest_mpt_snapshot(
+        &self, _dest_snapshot_dir: &Path,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn replace_snapshot_if_closed(
+        &self, snapshot_epoch_id: &EpochId, rebuilt_snapshot_dir: &Path,
+    ) -> Result<bool> {
+        let _open_lock = self.open_create_delete_lock.lock();
+        let path = self.get_snapshot_db_path(snapshot_epoch_id);
+        if self.already_open_snapshots.read().contains_key(&path) {
+            return Ok(false);
+        }
+        let snapshot_db_name = self.get_snapshot_db_name(snapshot_epoch_id);
+        let replaced_path =
+            rebuilt_snapshot_dir.join(snapshot_db_name.clone() + "_replaced");
+        fs::rename(&path, &replaced_path)?;
+        if let Err(e) =
+            fs::rename(rebuilt_snapshot_dir.join(&snapshot_db_name), &path)
+        {
+            fs::rename(&replaced_path, &path)?;
+            bail!(e);
+        }
+        Ok(true)
+    }
+
+    fn new_temp_snapshot_for_full_sync(
+        &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
+        _epoch_height: u64,
+    ) -> Result<Self::SnapshotDbWrite> {
+        let temp_db_path = self.get_full_sync_temp_snapshot_db_path(
+            snapshot_epoch_id,
+            merkle_root,
+        );
+        self.open_snapshot_write(temp_db_path, /* create = */ true)
+    }
+
+    fn finalize_full_sync_snapshot<'m>(
+        &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
+        snapshot_info_map_rwlock: &'m RwLock<PersistedSnapshotInfoMap>,
+    ) -> Result<RwLockWriteGuard<'m, PersistedSnapshotInfoMap>> {
+        let temp_db_path = self.get_full_sync_temp_snapshot_db_path(
+            snapshot_epoch_id,
+            merkle_root,
+        );
+        let final_db_path = self.get_snapshot_db_path(snapshot_epoch_id);
+        let locked = snapshot_info_map_rwlock.write();
+        fs::rename(&temp_db_path, &final_db_path)?;
+        Ok(locked)
+    }
+
+    fn recovery_latest_mpt_snapshot_from_checkpoint(
+        &self, _snapshot_epoch_id: &EpochId,
+        _snapshot_epoch_id_before_recovered: Option<EpochId>,
+    ) -> Result<()> {
+        bail!(Self::NO_LATEST_MPT_SNAPSHOT)
+    }
+
+    fn create_mpt_snapshot_from_latest(
+        &self, _new_snapshot_epoch_id: &EpochId,
+    ) -> Result<()> {
+        bail!(Self::NO_LATEST_MPT_SNAPSHOT)
+    }
+
+    fn try_get_new_snapshot_epoch_from_temp_path(
+        &self, dir_name: &str,
+    ) -> Option<EpochId> {
+        let prefix =
+            Self::SNAPSHOT_DB_ROCKSDB_DIR_PREFIX.to_string() + "merge_temp_";
+
+        if dir_name.starts_with(&prefix) {
+            match EpochId::from_str(
+                &dir_name[(prefix.len() + EpochId::len_bytes() * 2)..],
+            ) {
+                Ok(e) => Some(e),
+                Err(e) => {
+                    error!(
+                        "get new snapshot epoch id from temp path failed: {}",
+                        e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        }
+    }
+
+    fn try_get_new_snapshot_epoch_from_mpt_temp_path(
+        &self, _dir_name: &str,
+    ) -> Option<EpochId> {
+        None
+    }
+}
+
+use crate::{
+    impls::{
+        delta_mpt::DeltaMptIterator,
+        errors::*,
+        storage_db::{
+            snapshot_db_manager_sqlite::AlreadyOpenSnapshots,
+            snapshot_db_rocksdb::SnapshotDbRocksdb,
+            snapshot_kv_db_rocksdb::SnapshotKvDbRocksdb,
+        },
+        storage_manager::PersistedSnapshotInfoMap,
+    },
+    storage_db::{SnapshotDbManagerTrait, SnapshotDbTrait, SnapshotInfo},
+};
+use futures::executor;
+use parking_lot::{Mutex, RwLock, RwLockWriteGuard};
+use primitives::{EpochId, MerkleHash, NULL_EPOCH};
+use rustc_hex::ToHex;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Weak},
+    thread,
+    time::Duration,
+};
+use tokio::sync::Semaphore;
@@ -113,10 +113,8 @@ impl SnapshotDbManagerSqlite {
             fs::create_dir_all(snapshot_path.clone())?;
         }
 
-        let mpt_snapshot_path = snapshot_path
-            .parent()
-            .unwrap()
-            .join(SnapshotDbManagerSqlite::MPT_SNAPSHOT_DIR);
+        let mpt_snapshot_path =
+            Self::get_mpt_snapshot_dir_for(snapshot_path.as_path());
         let latest_mpt_snapshot_path = mpt_snapshot_path.join(
             Self::SNAPSHOT_DB_SQLITE_DIR_PREFIX.to_string()
                 + SnapshotDbManagerSqlite::LATEST_MPT_SNAPSHOT_DIR,
@@ -754,6 +752,11 @@ impl SnapshotDbManagerSqlite {
             .join(&self.get_snapshot_db_name(snapshot_epoch_id))
     }
 
+    /// The MPT snapshot dir next to `snapshot_dir`.
+    fn get_mpt_snapshot_dir_for(snapshot_dir: &Path) -> PathBuf {
+        snapshot_dir.parent().unwrap().join(Self::MPT_SNAPSHOT_DIR)
+    }
+
     fn get_full_sync_temp_mpt_snapshot_db_path(
         &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
     ) -> PathBuf {
@@ -1354,6 +1357,70 @@ impl SnapshotDbManagerTrait for SnapshotDbManagerSqlite {
         Ok(())
     }
 
+    fn checkpoint_snapshot(
+        &self, snapshot_epoch_id: &EpochId, dest_snapshot_dir: &Path,
+    ) -> Result<()> {
+        let snapshot_db_name = self.get_snapshot_db_name(snapshot_epoch_id);
+        let snapshot_path = self.get_snapshot_db_path(snapshot_epoch_id);
+        if !snapshot_path.exists() {
+            bail!(Error::SnapshotNotFound);
+        }
+        SqliteConnection::backup_dir(
+            snapshot_path.as_path(),
+            dest_snapshot_dir.join(&snapshot_db_name).as_path(),
+        )?;
+        let mpt_snapshot_path =
+            self.get_mpt_snapshot_db_path(snapshot_epoch_id);
+        if mpt_snapshot_path.exists() {
+            SqliteConnection::backup_dir(
+                mpt_snapshot_path.as_path(),
+                Self::get_mpt_snapshot_dir_for(dest_snapshot_dir)
+                    .join(&snapshot_db_name)
+                    .as_path(),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn checkpoint_latest_mpt_snapshot(
+        &self, dest_snapshot_dir: &Path,
+    ) -> Result<()> {
+        let latest_mpt_snapshot_path = self.get_latest_mpt_snapshot_db_path();
+        if !latest_mpt_snapshot_path.exists() {
+            return Ok(());
+        }
+        // Keep the latest MPT snapshot from being opened for write.
+        let _permit =
+            executor::block_on(self.latest_mpt_snapshot_semaphore.acquire());
+        SqliteConnection::backup_dir(
+            latest_mpt_snapshot_path.as_path(),
+            Self::get_mpt_snapshot_dir_for(dest_snapshot_dir)
+                .join(self.get_latest_mpt_snapshot_db_name())
+                .as_path(),
+        )
+    }
+
+    fn replace_snapshot_if_closed(
+        &self, snapshot_epoch_id: &EpochId, rebuilt_snapshot_dir: &Path,
+    ) -> Result<bool> {
+        let _open_lock = self.open_create_delete_lock.lock();
+        let path = self.get_snapshot_db_path(snapshot_epoch_id);
+        if self.already_open_snapshots.read().contains_key(&path) {
+            return Ok(false);
+        }
+        let snapshot_db_name = self.get_snapshot_db_name(snapshot_epoch_id);
+        let replaced_path =
+            rebuilt_snapshot_dir.join(snapshot_db_name.clone() + "_replaced");
+        fs::rename(&path, &replaced_path)?;
+        if let Err(e) =
+            fs::rename(rebuilt_snapshot_dir.join(&snapshot_db_name), &path)
+        {
+            fs::rename(&replaced_path, &path)?;
+            bail!(e);
+        }
+        Ok(true)
+    }
+
     fn new_temp_snapshot_for_full_sync(
         &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
         epoch_height: u64,
@@ -1581,5 +1648,5 @@ use tokio::sync::Semaphore;
 use super::{
     kvdb_sqlite_sharded::KvdbSqliteShardedBorrowMut,
     snapshot_db_sqlite::SnapshotDbSqlite, snapshot_mpt::SnapshotMpt,
-    snapshot_mpt_db_sqlite::SnapshotMptDbSqlite,
+    snapshot_mpt_db_sqlite::SnapshotMptDbSqlite, sqlite::SqliteConnection,
 };
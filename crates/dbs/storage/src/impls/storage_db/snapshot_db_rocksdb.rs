@@ -0,0 +1,196 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+pub struct SnapshotDbRocksdb {
+    pub snapshot_db: Arc<SnapshotKvDbRocksdb>,
+}
+
+impl KeyValueDbTypes for SnapshotDbRocksdb {
+    type ValueType = Box<[u8]>;
+}
+
+impl KeyValueDbTraitRead for SnapshotDbRocksdb {
+    fn get(&self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        self.snapshot_db.get(key)
+    }
+}
+
+impl KeyValueDbTraitOwnedRead for SnapshotDbRocksdb {
+    fn get_mut(&mut self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        self.snapshot_db.get(key)
+    }
+}
+
+/// A completed snapshot is readonly.
+impl KeyValueDbTraitSingleWriter for SnapshotDbRocksdb {
+    fn delete(&mut self, _key: &[u8]) -> Result<Option<Option<Box<[u8]>>>> {
+        bail!("A completed RocksDB snapshot is readonly")
+    }
+
+    fn put(
+        &mut self, _key: &[u8], _value: &[u8],
+    ) -> Result<Option<Option<Box<[u8]>>>> {
+        bail!("A completed RocksDB snapshot is readonly")
+    }
+}
+
+impl<'db> OpenSnapshotMptTrait<'db> for SnapshotDbRocksdb {
+    type SnapshotDbAsOwnedType = SnapshotMptRocksdb;
+    type SnapshotDbBorrowMutType = SnapshotMptRocksdb;
+    type SnapshotDbBorrowSharedType = SnapshotMptRocksdb;
+
+    fn open_snapshot_mpt_owned(
+        &'db mut self,
+    ) -> Result<Self::SnapshotDbBorrowMutType> {
+        bail!("A completed RocksDB snapshot is readonly")
+    }
+
+    fn open_snapshot_mpt_as_owned(
+        &'db self,
+    ) -> Result<Self::SnapshotDbAsOwnedType> {
+        self.snapshot_db.open_snapshot_mpt_as_owned()
+    }
+
+    fn open_snapshot_mpt_shared(
+        &'db self,
+    ) -> Result<Self::SnapshotDbBorrowSharedType> {
+        self.snapshot_db.open_snapshot_mpt_shared()
+    }
+}
+
+impl SnapshotDbRocksdb {
+    /// The snapshot mpt is always kept in the snapshot db.
+    pub fn has_mpt_snapshot(&self) -> bool { true }
+
+    pub fn dumped_delta_kv_set_keys_iterator(
+        &self,
+    ) -> Result<Option<KvdbRocksdb>> {
+        self.snapshot_db.dumped_delta_kv_set_keys_iterator()
+    }
+
+    pub fn dumped_delta_kv_delete_keys_iterator(
+        &self,
+    ) -> Result<Option<KvdbRocksdb>> {
+        self.snapshot_db.dumped_delta_kv_delete_keys_iterator()
+    }
+}
+
+impl SnapshotDbTrait for SnapshotDbRocksdb {
+    type SnapshotKvdbIterTraitTag = KvdbRocksdbIteratorTag;
+    type SnapshotKvdbIterType = Option<KvdbRocksdb>;
+    type SnapshotMptDb = ();
+
+    fn get_null_snapshot() -> Self {
+        Self {
+            snapshot_db: Arc::new(SnapshotKvDbRocksdb::get_null_snapshot()),
+        }
+    }
+
+    fn open(
+        _snapshot_path: &Path, _readonly: bool,
+        _already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        _open_semaphore: &Arc<Semaphore>,
+    ) -> Result<SnapshotDbRocksdb> {
+        bail!("A RocksDB snapshot is opened by its snapshot db manager")
+    }
+
+    fn create(
+        _snapshot_path: &Path,
+        _already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        _open_semaphore: &Arc<Semaphore>, _mpt_table_in_current_db: bool,
+    ) -> Result<SnapshotDbRocksdb> {
+        bail!("A RocksDB snapshot is created by its snapshot db manager")
+    }
+
+    fn direct_merge(
+        &mut self, _old_snapshot_db: Option<&Arc<SnapshotDbRocksdb>>,
+        _mpt_snapshot: &mut Option<()>,
+        _recover_mpt_with_kv_snapshot_exist: bool,
+        _in_reconstruct_snapshot_state: bool,
+    ) -> Result<MerkleHash> {
+        bail!("A RocksDB snapshot is merged by its snapshot db manager")
+    }
+
+    fn copy_and_merge(
+        &mut self, _old_snapshot_db: &Arc<SnapshotDbRocksdb>,
+        _mpt_snapshot_db: &mut Option<()>,
+        _in_reconstruct_snapshot_state: bool,
+    ) -> Result<MerkleHash> {
+        bail!("A RocksDB snapshot is merged by its snapshot db manager")
+    }
+
+    fn start_transaction(&mut self) -> Result<()> {
+        bail!("A completed RocksDB snapshot is readonly")
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        bail!("A completed RocksDB snapshot is readonly")
+    }
+
+    fn is_mpt_table_in_current_db(&self) -> bool {
+        self.snapshot_db.is_mpt_table_in_current_db()
+    }
+
+    fn snapshot_kv_iterator(
+        &self,
+    ) -> Result<
+        Wrap<
+            Self::SnapshotKvdbIterType,
+            dyn KeyValueDbIterableTrait<
+                MptKeyValue,
+                [u8],
+                KvdbRocksdbIteratorTag,
+            >,
+        >,
+    > {
+        self.snapshot_db.snapshot_kv_iterator()
+    }
+}
+
+/// The snapshot being written by full sync.
+impl SnapshotDbWriteableTrait for SnapshotKvDbRocksdb {
+    type SnapshotDbBorrowMutType = SnapshotMptRocksdb;
+
+    fn start_transaction(&mut self) -> Result<()> {
+        SnapshotDbTrait::start_transaction(self)
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        SnapshotDbTrait::commit_transaction(self)
+    }
+
+    fn put_kv(
+        &mut self, key: &[u8], value: &<Self::ValueType as DbValueType>::Type,
+    ) -> Result<Option<Option<Self::ValueType>>> {
+        self.put(key, value)
+    }
+
+    fn open_snapshot_mpt_owned(
+        &mut self,
+    ) -> Result<Self::SnapshotDbBorrowMutType> {
+        OpenSnapshotMptTrait::open_snapshot_mpt_owned(self)
+    }
+}
+
+use crate::{
+    impls::{
+        errors::*,
+        storage_db::{
+            kvdb_rocksdb::{KvdbRocksdb, KvdbRocksdbIteratorTag},
+            snapshot_db_manager_sqlite::AlreadyOpenSnapshots,
+            snapshot_kv_db_rocksdb::SnapshotKvDbRocksdb,
+            snapshot_mpt_rocksdb::SnapshotMptRocksdb,
+        },
+    },
+    storage_db::{
+        DbValueType, KeyValueDbIterableTrait, KeyValueDbTraitOwnedRead,
+        KeyValueDbTraitRead, KeyValueDbTraitSingleWriter, KeyValueDbTypes,
+        OpenSnapshotMptTrait, SnapshotDbTrait, SnapshotDbWriteableTrait,
+    },
+    utils::wrap::Wrap,
+    MptKeyValue,
+};
+use primitives::MerkleHash;
+use std::{path::Path, sync::Arc};
+use tokio::sync::Semaphore;
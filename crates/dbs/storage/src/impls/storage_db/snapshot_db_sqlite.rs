@@ -129,6 +129,12 @@ impl<'db> OpenSnapshotMptTrait<'db> for SnapshotDbSqlite {
 }
 
 impl SnapshotDbSqlite {
+    /// Whether the snapshot mpt is available, either in the snapshot db or in
+    /// the opened mpt snapshot db.
+    pub fn has_mpt_snapshot(&self) -> bool {
+        self.is_mpt_table_in_current_db() || self.mpt_snapshot_db.is_some()
+    }
+
     fn try_clone_connections(&self) -> Result<Option<Box<[SqliteConnection]>>> {
         self.snapshot_db.try_clone_connections()
     }
@@ -150,6 +156,7 @@ impl SnapshotDbTrait for SnapshotDbSqlite {
     type SnapshotKvdbIterTraitTag = KvdbSqliteShardedIteratorTag;
     type SnapshotKvdbIterType =
         KvdbSqliteSharded<<Self as KeyValueDbTypes>::ValueType>;
+    type SnapshotMptDb = SnapshotMptDbSqlite;
 
     fn get_null_snapshot() -> Self {
         Self {
@@ -0,0 +1,460 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+/// A snapshot kept in a RocksDB database, with the key-values, the snapshot
+/// MPT and the dumped delta MPT each in their own column family.
+pub struct SnapshotKvDbRocksdb {
+    // Option because we need an empty snapshot db for empty snapshot.
+    pub maybe_kvdb: Option<Arc<Database>>,
+    already_open_snapshots: AlreadyOpenSnapshots<Self>,
+    open_semaphore: Arc<Semaphore>,
+    path: PathBuf,
+    remove_on_close: AtomicBool,
+}
+
+impl Drop for SnapshotKvDbRocksdb {
+    fn drop(&mut self) {
+        if !self.path.as_os_str().is_empty() {
+            self.maybe_kvdb.take();
+            SnapshotDbManagerRocksdb::on_close(
+                &self.already_open_snapshots,
+                &self.open_semaphore,
+                &self.path,
+                self.remove_on_close.load(Ordering::Relaxed),
+            )
+        }
+    }
+}
+
+impl SnapshotKvDbRocksdb {
+    pub const COL_DELTA_KV_DELETE: u32 = 3;
+    /// These two columns are temporary for the merging process, but they
+    /// remain to help other nodes to do 1-step syncing. They must be the last
+    /// two columns, so that they can be dropped and recreated as a whole.
+    pub const COL_DELTA_KV_SET: u32 = 2;
+    /// Key-Value column. Key is unique key in this column.
+    pub const COL_KV: u32 = 0;
+    pub const COL_MPT: u32 = 1;
+    const ROCKSDB_CONFIG: DatabaseConfig = DatabaseConfig {
+        max_open_files: 256,
+        memory_budget: None,
+        compaction: CompactionProfile {
+            initial_file_size: 64 * 1048576 as u64,
+            block_size: 16 * 1024,
+            write_rate_limit: None,
+        },
+        columns: 4,
+        disable_wal: false,
+    };
+    const WRITE_BATCH_SIZE: usize = 10000;
+
+    fn column(&self, col: u32) -> Option<KvdbRocksdb> {
+        self.maybe_kvdb.as_ref().map(|kvdb| KvdbRocksdb {
+            kvdb: kvdb.clone(),
+            col,
+        })
+    }
+
+    pub fn set_remove_on_last_close(&self) {
+        self.remove_on_close.store(true, Ordering::Relaxed);
+    }
+
+    /// Create a checkpoint of the snapshot at `path`, which shares the
+    /// immutable data files with this snapshot through hard links.
+    pub fn checkpoint(&self, path: &Path) -> Result<()> {
+        // Safe to unwrap since the empty snapshot is never copied.
+        Ok(self.maybe_kvdb.as_ref().unwrap().checkpoint(path)?)
+    }
+
+    pub fn dumped_delta_kv_set_keys_iterator(
+        &self,
+    ) -> Result<Option<KvdbRocksdb>> {
+        Ok(self.column(Self::COL_DELTA_KV_SET))
+    }
+
+    /// The values of the deleted keys are empty.
+    pub fn dumped_delta_kv_delete_keys_iterator(
+        &self,
+    ) -> Result<Option<KvdbRocksdb>> {
+        Ok(self.column(Self::COL_DELTA_KV_DELETE))
+    }
+
+    // FIXME: add rate limit.
+    pub fn dump_delta_mpt(
+        &mut self, delta_mpt: &DeltaMptIterator,
+    ) -> Result<()> {
+        debug!("dump_delta_mpt starts");
+        // Safe to unwrap since we are not on a NULL snapshot.
+        let mut dumper = DeltaMptMergeDumperRocksdb {
+            writer: BatchWriter::new(self.maybe_kvdb.as_ref().unwrap()),
+        };
+        delta_mpt.iterate(&mut dumper)?;
+        dumper.writer.finish()
+    }
+
+    /// Dropping is optional, because these columns are necessary to provide
+    /// 1-step syncing.
+    pub fn drop_delta_mpt_dump(&mut self) -> Result<()> {
+        // Safe to unwrap since we are not on a NULL snapshot.
+        let kvdb = self.maybe_kvdb.as_ref().unwrap();
+        kvdb.flush()?;
+        // Drop the two last columns and create them again.
+        kvdb.drop_column()?;
+        kvdb.drop_column()?;
+        kvdb.add_column()?;
+        kvdb.add_column()?;
+        Ok(())
+    }
+
+    fn apply_update_to_kvdb(&mut self) -> Result<()> {
+        // Safe to unwrap since we are not on a NULL snapshot.
+        let mut writer = BatchWriter::new(self.maybe_kvdb.as_ref().unwrap());
+        let mut delete_keys = self.dumped_delta_kv_delete_keys_iterator()?;
+        let mut delete_keys_iter = delete_keys.iter_range(&[], None)?.take();
+        while let Some((key, _)) = delete_keys_iter.next()? {
+            writer.delete(Self::COL_KV, &key)?;
+        }
+        let mut set_keys = self.dumped_delta_kv_set_keys_iterator()?;
+        let mut set_keys_iter = set_keys.iter_range(&[], None)?.take();
+        while let Some((key, value)) = set_keys_iter.next()? {
+            writer.put(Self::COL_KV, &key, &value)?;
+        }
+        writer.finish()
+    }
+}
+
+impl KeyValueDbTypes for SnapshotKvDbRocksdb {
+    type ValueType = Box<[u8]>;
+}
+
+impl KeyValueDbTraitRead for SnapshotKvDbRocksdb {
+    fn get(&self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        match &self.maybe_kvdb {
+            None => Ok(None),
+            Some(kvdb) => Ok(kvdb
+                .get(Self::COL_KV, key)?
+                .map(|db_value| db_value.into_boxed_slice())),
+        }
+    }
+}
+
+impl KeyValueDbTraitOwnedRead for SnapshotKvDbRocksdb {
+    fn get_mut(&mut self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        self.get(key)
+    }
+}
+
+/// The writes are buffered until commit_transaction.
+impl KeyValueDbTraitSingleWriter for SnapshotKvDbRocksdb {
+    fn delete(&mut self, key: &[u8]) -> Result<Option<Option<Box<[u8]>>>> {
+        let mut transaction = DBTransaction::new();
+        transaction.delete(Self::COL_KV, key);
+        // Safe to unwrap since we are not on a NULL snapshot.
+        self.maybe_kvdb
+            .as_ref()
+            .unwrap()
+            .write_buffered(transaction);
+        Ok(None)
+    }
+
+    fn put(
+        &mut self, key: &[u8], value: &[u8],
+    ) -> Result<Option<Option<Box<[u8]>>>> {
+        let mut transaction = DBTransaction::new();
+        transaction.put(Self::COL_KV, key, value);
+        // Safe to unwrap since we are not on a NULL snapshot.
+        self.maybe_kvdb
+            .as_ref()
+            .unwrap()
+            .write_buffered(transaction);
+        Ok(None)
+    }
+}
+
+impl<'db> OpenSnapshotMptTrait<'db> for SnapshotKvDbRocksdb {
+    type SnapshotDbAsOwnedType = SnapshotMptRocksdb;
+    type SnapshotDbBorrowMutType = SnapshotMptRocksdb;
+    type SnapshotDbBorrowSharedType = SnapshotMptRocksdb;
+
+    fn open_snapshot_mpt_owned(
+        &'db mut self,
+    ) -> Result<Self::SnapshotDbBorrowMutType> {
+        SnapshotMptRocksdb::new(self.column(Self::COL_MPT))
+    }
+
+    fn open_snapshot_mpt_as_owned(
+        &'db self,
+    ) -> Result<Self::SnapshotDbAsOwnedType> {
+        SnapshotMptRocksdb::new(self.column(Self::COL_MPT))
+    }
+
+    fn open_snapshot_mpt_shared(
+        &'db self,
+    ) -> Result<Self::SnapshotDbBorrowSharedType> {
+        SnapshotMptRocksdb::new(self.column(Self::COL_MPT))
+    }
+}
+
+impl SnapshotDbTrait for SnapshotKvDbRocksdb {
+    type SnapshotKvdbIterTraitTag = KvdbRocksdbIteratorTag;
+    type SnapshotKvdbIterType = Option<KvdbRocksdb>;
+    /// The snapshot MPT is always in the snapshot db.
+    type SnapshotMptDb = ();
+
+    fn get_null_snapshot() -> Self {
+        Self {
+            maybe_kvdb: None,
+            already_open_snapshots: Default::default(),
+            open_semaphore: Arc::new(Semaphore::new(0)),
+            path: Default::default(),
+            remove_on_close: Default::default(),
+        }
+    }
+
+    /// RocksDB doesn't support to open the same db twice in a process, so
+    /// readonly is only a promise of the caller.
+    fn open(
+        snapshot_path: &Path, _readonly: bool,
+        already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        open_semaphore: &Arc<Semaphore>,
+    ) -> Result<SnapshotKvDbRocksdb> {
+        let kvdb = Database::open(
+            &Self::ROCKSDB_CONFIG,
+            snapshot_path.to_str().unwrap(),
+        )?;
+
+        Ok(Self {
+            maybe_kvdb: Some(Arc::new(kvdb)),
+            already_open_snapshots: already_open_snapshots.clone(),
+            open_semaphore: open_semaphore.clone(),
+            path: snapshot_path.to_path_buf(),
+            remove_on_close: Default::default(),
+        })
+    }
+
+    fn create(
+        snapshot_path: &Path,
+        already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        open_snapshots_semaphore: &Arc<Semaphore>,
+        _mpt_table_in_current_db: bool,
+    ) -> Result<SnapshotKvDbRocksdb> {
+        if snapshot_path.exists() {
+            bail!(Error::SnapshotAlreadyExists);
+        }
+        match Self::open(
+            snapshot_path,
+            /* readonly = */ false,
+            already_open_snapshots,
+            open_snapshots_semaphore,
+        ) {
+            Err(e) => {
+                if snapshot_path.exists() {
+                    fs::remove_dir_all(&snapshot_path)?;
+                }
+                bail!(e);
+            }
+            Ok(snapshot_db) => Ok(snapshot_db),
+        }
+    }
+
+    // FIXME: use a mechanism with rate limit.
+    fn direct_merge(
+        &mut self, _old_snapshot_db: Option<&Arc<SnapshotKvDbRocksdb>>,
+        _mpt_snapshot: &mut Option<()>,
+        recover_mpt_with_kv_snapshot_exist: bool,
+        in_reconstruct_snapshot_state: bool,
+    ) -> Result<MerkleHash> {
+        debug!("direct_merge begins.");
+
+        if !recover_mpt_with_kv_snapshot_exist {
+            self.apply_update_to_kvdb()?;
+        }
+
+        let mut set_keys_iter = self.dumped_delta_kv_set_keys_iterator()?;
+        let mut delete_keys_iter =
+            self.dumped_delta_kv_delete_keys_iterator()?;
+
+        // The MPT of the old snapshot is already in the checkpoint, update it
+        // in-place.
+        let mut mpt_to_modify = self.open_snapshot_mpt_owned()?;
+        let snapshot_root = MptMerger::new(
+            None,
+            &mut mpt_to_modify as &mut dyn SnapshotMptTraitRw,
+        )
+        .merge_insertion_deletion_separated(
+            delete_keys_iter
+                .iter_range(&[], None)?
+                .take()
+                .map(|(key, _)| Ok((key, ()))),
+            set_keys_iter.iter_range(&[], None)?.take(),
+            in_reconstruct_snapshot_state,
+        )?;
+        mpt_to_modify.flush()?;
+
+        Ok(snapshot_root)
+    }
+
+    fn copy_and_merge(
+        &mut self, old_snapshot_db: &Arc<SnapshotKvDbRocksdb>,
+        _mpt_snapshot_db: &mut Option<()>, in_reconstruct_snapshot_state: bool,
+    ) -> Result<MerkleHash> {
+        debug!("copy_and_merge begins.");
+        {
+            let mut kv_iter = old_snapshot_db.snapshot_kv_iterator()?.take();
+            let mut iter = kv_iter.iter_range(&[], None)?.take();
+            // Safe to unwrap since we are not on a NULL snapshot.
+            let mut writer =
+                BatchWriter::new(self.maybe_kvdb.as_ref().unwrap());
+            while let Some((key, value)) = iter.next()? {
+                writer.put(Self::COL_KV, &key, &value)?;
+            }
+            writer.finish()?;
+        }
+        self.apply_update_to_kvdb()?;
+
+        let mut set_keys_iter = self.dumped_delta_kv_set_keys_iterator()?;
+        let mut delete_keys_iter =
+            self.dumped_delta_kv_delete_keys_iterator()?;
+        let mut base_mpt = old_snapshot_db.open_snapshot_mpt_as_owned()?;
+        let mut save_as_mpt = self.open_snapshot_mpt_owned()?;
+        let snapshot_root = MptMerger::new(
+            Some(&mut base_mpt as &mut dyn SnapshotMptTraitReadAndIterate),
+            &mut save_as_mpt as &mut dyn SnapshotMptTraitRw,
+        )
+        .merge_insertion_deletion_separated(
+            delete_keys_iter
+                .iter_range(&[], None)?
+                .take()
+                .map(|(key, _)| Ok((key, ()))),
+            set_keys_iter.iter_range(&[], None)?.take(),
+            in_reconstruct_snapshot_state,
+        )?;
+        save_as_mpt.flush()?;
+
+        Ok(snapshot_root)
+    }
+
+    /// Writes are buffered in the db and flushed on commit.
+    fn start_transaction(&mut self) -> Result<()> { Ok(()) }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        if let Some(kvdb) = &self.maybe_kvdb {
+            kvdb.flush()?;
+        }
+        Ok(())
+    }
+
+    fn is_mpt_table_in_current_db(&self) -> bool { true }
+
+    fn snapshot_kv_iterator(
+        &self,
+    ) -> Result<
+        Wrap<
+            Self::SnapshotKvdbIterType,
+            dyn KeyValueDbIterableTrait<
+                MptKeyValue,
+                [u8],
+                KvdbRocksdbIteratorTag,
+            >,
+        >,
+    > {
+        Ok(Wrap(self.column(Self::COL_KV)))
+    }
+}
+
+/// Writes into the db in batches of WRITE_BATCH_SIZE operations.
+struct BatchWriter<'a> {
+    kvdb: &'a Database,
+    transaction: DBTransaction,
+}
+
+impl<'a> BatchWriter<'a> {
+    fn new(kvdb: &'a Database) -> Self {
+        Self {
+            kvdb,
+            transaction: DBTransaction::new(),
+        }
+    }
+
+    fn put(&mut self, col: u32, key: &[u8], value: &[u8]) -> Result<()> {
+        self.transaction.put(col, key, value);
+        self.maybe_write()
+    }
+
+    fn delete(&mut self, col: u32, key: &[u8]) -> Result<()> {
+        self.transaction.delete(col, key);
+        self.maybe_write()
+    }
+
+    fn maybe_write(&mut self) -> Result<()> {
+        if self.transaction.ops.len() >= SnapshotKvDbRocksdb::WRITE_BATCH_SIZE {
+            self.kvdb.write(std::mem::replace(
+                &mut self.transaction,
+                DBTransaction::new(),
+            ))?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> { Ok(self.kvdb.write(self.transaction)?) }
+}
+
+pub struct DeltaMptMergeDumperRocksdb<'a> {
+    writer: BatchWriter<'a>,
+}
+
+impl<'a> KVInserter<MptKeyValue> for DeltaMptMergeDumperRocksdb<'a> {
+    fn push(&mut self, x: MptKeyValue) -> Result<()> {
+        let (mpt_key, value) = x;
+        let snapshot_key =
+            StorageKeyWithSpace::from_delta_mpt_key(&mpt_key).to_key_bytes();
+        if value.len() > 0 {
+            self.writer.put(
+                SnapshotKvDbRocksdb::COL_DELTA_KV_SET,
+                &snapshot_key,
+                &value,
+            )
+        } else {
+            self.writer.put(
+                SnapshotKvDbRocksdb::COL_DELTA_KV_DELETE,
+                &snapshot_key,
+                &[],
+            )
+        }
+    }
+}
+
+use crate::{
+    impls::{
+        delta_mpt::DeltaMptIterator,
+        errors::*,
+        merkle_patricia_trie::{MptKeyValue, MptMerger},
+        storage_db::{
+            kvdb_rocksdb::{KvdbRocksdb, KvdbRocksdbIteratorTag},
+            snapshot_db_manager_rocksdb::SnapshotDbManagerRocksdb,
+            snapshot_db_manager_sqlite::AlreadyOpenSnapshots,
+            snapshot_mpt_rocksdb::SnapshotMptRocksdb,
+        },
+    },
+    storage_db::{
+        KeyValueDbIterableTrait, KeyValueDbTraitOwnedRead, KeyValueDbTraitRead,
+        KeyValueDbTraitSingleWriter, KeyValueDbTypes, OpenSnapshotMptTrait,
+        SnapshotDbTrait, SnapshotMptTraitReadAndIterate, SnapshotMptTraitRw,
+    },
+    utils::wrap::Wrap,
+    KVInserter,
+};
+use fallible_iterator::FallibleIterator;
+use kvdb::DBTransaction;
+use kvdb_rocksdb::{CompactionProfile, Database, DatabaseConfig};
+use primitives::{MerkleHash, StorageKeyWithSpace};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::Semaphore;
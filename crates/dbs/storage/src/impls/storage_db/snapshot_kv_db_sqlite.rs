@@ -251,6 +251,7 @@ impl SnapshotDbTrait for SnapshotKvDbSqlite {
     type SnapshotKvdbIterTraitTag = KvdbSqliteShardedIteratorTag;
     type SnapshotKvdbIterType =
         KvdbSqliteSharded<<Self as KeyValueDbTypes>::ValueType>;
+    type SnapshotMptDb = SnapshotMptDbSqlite;
 
     fn get_null_snapshot() -> Self {
         Self {
@@ -47,8 +47,11 @@ impl MptValueKind for Box<[u8]> {
     }
 }
 
-pub fn check_key_value_load<Value: MptValueKind>(
-    snapshot_db: &SnapshotDbSqlite,
+pub fn check_key_value_load<
+    SnapshotDb: SnapshotDbTrait,
+    Value: MptValueKind,
+>(
+    snapshot_db: &SnapshotDb,
     mut kv_iter: impl FallibleIterator<Item = (Vec<u8>, Value), Error = Error>,
     check_value: bool,
 ) -> Result<u64> {
@@ -94,7 +97,10 @@ use crate::{
         },
         storage_db::snapshot_db_sqlite::SnapshotDbSqlite,
     },
-    storage_db::{snapshot_db::OpenSnapshotMptTrait, SnapshotMptTraitRead},
+    storage_db::{
+        snapshot_db::OpenSnapshotMptTrait, SnapshotDbTrait,
+        SnapshotMptTraitRead,
+    },
     utils::access_mode,
 };
 use fallible_iterator::FallibleIterator;
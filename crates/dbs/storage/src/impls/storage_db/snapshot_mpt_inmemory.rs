@@ -0,0 +1,114 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+/// The snapshot MPT of an in-memory snapshot. The nodes are keyed in the same
+/// way as in the sqlite MPT table.
+pub struct SnapshotMptInmemory {
+    // None for the empty snapshot.
+    maybe_kvdb: Option<KvdbInmemory>,
+    merkle_root: MerkleHash,
+}
+
+impl SnapshotMptInmemory {
+    pub fn new(maybe_kvdb: Option<KvdbInmemory>) -> Result<Self> {
+        let mut mpt = Self {
+            maybe_kvdb,
+            merkle_root: MERKLE_NULL_NODE,
+        };
+        let path_to_root_node = CompressedPathRaw::default();
+        if let Some(root_node) = mpt.load_node(&path_to_root_node)? {
+            mpt.merkle_root = *root_node.get_merkle();
+        }
+        Ok(mpt)
+    }
+}
+
+impl SnapshotMptTraitRead for SnapshotMptInmemory {
+    fn get_merkle_root(&self) -> MerkleHash { self.merkle_root }
+
+    fn load_node(
+        &mut self, path: &dyn CompressedPathTrait,
+    ) -> Result<Option<SnapshotMptNode>> {
+        let kvdb = match &self.maybe_kvdb {
+            None => return Ok(None),
+            Some(kvdb) => kvdb,
+        };
+        match kvdb.get(&mpt_node_path_to_db_key(path))? {
+            None => Ok(None),
+            Some(rlp) => {
+                Ok(Some(SnapshotMptNode::load_rlp_and_check(&rlp, path)?))
+            }
+        }
+    }
+}
+
+impl SnapshotMptTraitReadAndIterate for SnapshotMptInmemory {
+    fn iterate_subtree_trie_nodes_without_root(
+        &mut self, path: &dyn CompressedPathTrait,
+    ) -> Result<Box<dyn SnapshotMptIteraterTrait + '_>> {
+        let begin_key_excl = mpt_node_path_to_db_key(path);
+
+        let mut end_key_excl = begin_key_excl.clone();
+        // The key is non empty. See also comment for compressed_path_to_db_key.
+        *end_key_excl.last_mut().unwrap() += 1;
+
+        Ok(Box::new(
+            self.maybe_kvdb
+                .clone()
+                .iter_range_excl(&begin_key_excl, &end_key_excl)?
+                .take()
+                .map(|(key, value)| {
+                    Ok((
+                        mpt_node_path_from_db_key(&key)?,
+                        SnapshotMptNode::decode(&Rlp::new(&value))?,
+                    ))
+                }),
+        ))
+    }
+}
+
+impl SnapshotMptTraitRw for SnapshotMptInmemory {
+    fn delete_node(&mut self, path: &dyn CompressedPathTrait) -> Result<()> {
+        // Safe to unwrap since nothing is written into the empty snapshot.
+        self.maybe_kvdb
+            .as_ref()
+            .unwrap()
+            .delete(&mpt_node_path_to_db_key(path))?;
+        Ok(())
+    }
+
+    fn write_node(
+        &mut self, path: &dyn CompressedPathTrait, trie_node: &SnapshotMptNode,
+    ) -> Result<()> {
+        // Safe to unwrap since nothing is written into the empty snapshot.
+        self.maybe_kvdb
+            .as_ref()
+            .unwrap()
+            .put(&mpt_node_path_to_db_key(path), &trie_node.rlp_bytes())?;
+        Ok(())
+    }
+}
+
+use crate::{
+    impls::{
+        errors::*,
+        merkle_patricia_trie::{CompressedPathRaw, CompressedPathTrait},
+        storage_db::{
+            kvdb_inmemory::KvdbInmemory,
+            snapshot_mpt::{
+                mpt_node_path_from_db_key, mpt_node_path_to_db_key,
+            },
+        },
+    },
+    storage_db::{
+        key_value_db::{
+            KeyValueDbIterableTrait, KeyValueDbTrait, KeyValueDbTraitRead,
+        },
+        snapshot_mpt::*,
+        SnapshotMptTraitRead,
+    },
+};
+use fallible_iterator::FallibleIterator;
+use primitives::{MerkleHash, MERKLE_NULL_NODE};
+use rlp::*;
@@ -0,0 +1,147 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+/// The snapshot MPT kept in a column of a RocksDB snapshot db. The nodes are
+/// keyed in the same way as in the sqlite MPT table.
+///
+/// Node writes go to the write buffer of the db, which is visible to
+/// `load_node` but not to iteration, therefore the buffer is flushed before
+/// iterating and must be flushed by `flush` after the last write.
+pub struct SnapshotMptRocksdb {
+    // None for the empty snapshot.
+    maybe_kvdb: Option<KvdbRocksdb>,
+    merkle_root: MerkleHash,
+    buffered_writes: usize,
+}
+
+impl SnapshotMptRocksdb {
+    const MAX_BUFFERED_WRITES: usize = 10000;
+
+    pub fn new(maybe_kvdb: Option<KvdbRocksdb>) -> Result<Self> {
+        let mut mpt = Self {
+            maybe_kvdb,
+            merkle_root: MERKLE_NULL_NODE,
+            buffered_writes: 0,
+        };
+        let path_to_root_node = CompressedPathRaw::default();
+        if let Some(root_node) = mpt.load_node(&path_to_root_node)? {
+            mpt.merkle_root = *root_node.get_merkle();
+        }
+        Ok(mpt)
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        if let Some(kvdb) = &self.maybe_kvdb {
+            kvdb.kvdb.flush()?;
+        }
+        self.buffered_writes = 0;
+        Ok(())
+    }
+
+    fn write_buffered(&mut self, transaction: DBTransaction) -> Result<()> {
+        // Safe to unwrap since nothing is written into the empty snapshot.
+        self.maybe_kvdb
+            .as_ref()
+            .unwrap()
+            .kvdb
+            .write_buffered(transaction);
+        self.buffered_writes += 1;
+        if self.buffered_writes >= Self::MAX_BUFFERED_WRITES {
+            self.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl SnapshotMptTraitRead for SnapshotMptRocksdb {
+    fn get_merkle_root(&self) -> MerkleHash { self.merkle_root }
+
+    fn load_node(
+        &mut self, path: &dyn CompressedPathTrait,
+    ) -> Result<Option<SnapshotMptNode>> {
+        let kvdb = match &self.maybe_kvdb {
+            None => return Ok(None),
+            Some(kvdb) => kvdb,
+        };
+        match kvdb.kvdb.get(kvdb.col, &mpt_node_path_to_db_key(path))? {
+            None => Ok(None),
+            Some(rlp) => {
+                Ok(Some(SnapshotMptNode::load_rlp_and_check(&rlp, path)?))
+            }
+        }
+    }
+}
+
+impl SnapshotMptTraitReadAndIterate for SnapshotMptRocksdb {
+    fn iterate_subtree_trie_nodes_without_root(
+        &mut self, path: &dyn CompressedPathTrait,
+    ) -> Result<Box<dyn SnapshotMptIteraterTrait + '_>> {
+        self.flush()?;
+
+        let begin_key_excl = mpt_node_path_to_db_key(path);
+
+        let mut end_key_excl = begin_key_excl.clone();
+        // The key is non empty. See also comment for compressed_path_to_db_key.
+        *end_key_excl.last_mut().unwrap() += 1;
+
+        Ok(Box::new(
+            self.maybe_kvdb
+                .clone()
+                .iter_range_excl(&begin_key_excl, &end_key_excl)?
+                .take()
+                .map(|(key, value)| {
+                    Ok((
+                        mpt_node_path_from_db_key(&key)?,
+                        SnapshotMptNode::decode(&Rlp::new(&value))?,
+                    ))
+                }),
+        ))
+    }
+}
+
+impl SnapshotMptTraitRw for SnapshotMptRocksdb {
+    fn delete_node(&mut self, path: &dyn CompressedPathTrait) -> Result<()> {
+        let mut transaction = DBTransaction::new();
+        transaction.delete(
+            // Safe to unwrap since nothing is written into the empty snapshot.
+            self.maybe_kvdb.as_ref().unwrap().col,
+            &mpt_node_path_to_db_key(path),
+        );
+        self.write_buffered(transaction)
+    }
+
+    fn write_node(
+        &mut self, path: &dyn CompressedPathTrait, trie_node: &SnapshotMptNode,
+    ) -> Result<()> {
+        let mut transaction = DBTransaction::new();
+        transaction.put(
+            // Safe to unwrap since nothing is written into the empty snapshot.
+            self.maybe_kvdb.as_ref().unwrap().col,
+            &mpt_node_path_to_db_key(path),
+            &trie_node.rlp_bytes(),
+        );
+        self.write_buffered(transaction)
+    }
+}
+
+use crate::{
+    impls::{
+        errors::*,
+        merkle_patricia_trie::{CompressedPathRaw, CompressedPathTrait},
+        storage_db::{
+            kvdb_rocksdb::KvdbRocksdb,
+            snapshot_mpt::{
+                mpt_node_path_from_db_key, mpt_node_path_to_db_key,
+            },
+        },
+    },
+    storage_db::{
+        key_value_db::KeyValueDbIterableTrait, snapshot_mpt::*,
+        SnapshotMptTraitRead,
+    },
+};
+use fallible_iterator::FallibleIterator;
+use kvdb::DBTransaction;
+use primitives::{MerkleHash, MERKLE_NULL_NODE};
+use rlp::*;
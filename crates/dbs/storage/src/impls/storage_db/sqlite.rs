@@ -158,6 +158,75 @@ impl SqliteConnection {
         Self::open(&self.info.path, self.info.readonly, self.info.open_flags)
     }
 
+    /// Copy the database into a new database at `path` with the sqlite online
+    /// backup api, which makes a consistent copy while the database is in
+    /// use.
+    pub fn backup<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let destination = Connection::open_with_flags(
+            path,
+            Self::default_open_flags().set_read_write().set_create(),
+        )?;
+        let source = self.lock_db();
+        let schema = b"main\0".as_ptr() as *const _;
+        unsafe {
+            let backup = sqlite_ffi::sqlite3_backup_init(
+                destination.as_raw(),
+                schema,
+                source.as_raw(),
+                schema,
+            );
+            if backup.is_null() {
+                bail!(sqlite::Error {
+                    code: Some(
+                        sqlite_ffi::sqlite3_errcode(destination.as_raw())
+                            as isize
+                    ),
+                    message: None
+                });
+            }
+            // Copy all pages in one step, so that the source is read in a
+            // single transaction.
+            let step_result = sqlite_ffi::sqlite3_backup_step(backup, -1);
+            let finish_result = sqlite_ffi::sqlite3_backup_finish(backup);
+            match (step_result, finish_result) {
+                (sqlite_ffi::SQLITE_DONE, sqlite_ffi::SQLITE_OK) => Ok(()),
+                (sqlite_ffi::SQLITE_DONE, code) | (code, _) => {
+                    bail!(sqlite::Error {
+                        code: Some(code as isize),
+                        message: None
+                    })
+                }
+            }
+        }
+    }
+
+    /// Backup every database file in `dir` into `dest_dir`, skipping the
+    /// temporary files of sqlite.
+    pub fn backup_dir<P: AsRef<Path>>(dir: P, dest_dir: P) -> Result<()> {
+        fs::create_dir_all(dest_dir.as_ref())?;
+        for entry in fs::read_dir(dir.as_ref())? {
+            let path = entry?.path();
+            let file_name = match path.file_name().and_then(|n| n.to_str()) {
+                Some(file_name) => file_name,
+                None => continue,
+            };
+            if !path.is_file()
+                || ["-wal", "-shm", "-journal"]
+                    .iter()
+                    .any(|suffix| file_name.ends_with(suffix))
+            {
+                continue;
+            }
+            Self::open(
+                &path,
+                /* readonly = */ true,
+                Self::default_open_flags(),
+            )?
+            .backup(dest_dir.as_ref().join(file_name))?;
+        }
+        Ok(())
+    }
+
     pub fn prepare<'db>(
         db: &'db mut Connection, statement_cache: &'db mut StatementCache,
         sql: &str,
@@ -723,6 +792,7 @@ use sqlite3_sys as sqlite_ffi;
 use std::{
     borrow::Borrow,
     collections::HashMap,
+    fs,
     ops::Deref,
     path::{Path, PathBuf},
     pin::Pin,
@@ -3,6 +3,7 @@
 // See http://www.gnu.org/licenses/
 
 pub mod single_mpt_storage_manager;
+pub mod snapshot_inspector;
 mod snapshot_manager;
 /// Storage manager manages the lifecycle of SnapshotMPTS and DeltaMPTs.
 pub mod storage_manager;
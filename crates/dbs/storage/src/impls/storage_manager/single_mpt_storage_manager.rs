@@ -8,14 +8,18 @@ use crate::{
         DeltaMptsCacheAlgorithm, DeltaMptsNodeMemoryManager,
     },
     replicated_state::StateFilter,
-    storage_db::DeltaDbManagerTrait,
+    storage_db::{DeltaDbManagerTrait, DeltaDbTrait},
     ArcDeltaDbWrapper, DeltaMpt, OpenableOnDemandOpenDeltaDbTrait,
 };
 use cfx_types::Space;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use parking_lot::Mutex;
 use primitives::EpochId;
-use std::{fs, path::PathBuf, sync::Arc};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 const DB_NAME: &str = "single_mpt";
 
@@ -80,6 +84,12 @@ impl SingleMptStorageManager {
         }
     }
 
+    /// Copy the db of the single mpt into `dest_dir` while it's in use.
+    pub fn checkpoint(&self, dest_dir: &Path) -> Result<()> {
+        fs::create_dir_all(dest_dir)?;
+        self.mpt.get_arc_db()?.checkpoint(&dest_dir.join(DB_NAME))
+    }
+
     pub fn get_state_for_genesis(&self) -> Result<SingleMptState> {
         Ok(SingleMptState::new_empty(self.mpt.clone()))
     }
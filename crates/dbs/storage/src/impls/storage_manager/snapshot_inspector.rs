@@ -0,0 +1,240 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+/// Read-only access to the snapshots of a stopped node, without going through
+/// the `StorageManager` initialization which cleans up unexpected files.
+pub struct SnapshotInspector {
+    snapshot_db_manager: SnapshotDbManager,
+    snapshot_info_map: HashMap<EpochId, SnapshotInfo>,
+}
+
+#[derive(Debug)]
+pub struct SnapshotCheckResult {
+    pub snapshot_epoch_id: EpochId,
+    pub height: u64,
+    /// The merkle root recorded in the snapshot_info db.
+    pub expected_merkle_root: MerkleHash,
+    /// The merkle root recomputed from all key-values of the snapshot.
+    pub kv_merkle_root: MerkleHash,
+    /// The merkle root stored in the snapshot mpt, None if the mpt of this
+    /// snapshot is not kept.
+    pub mpt_merkle_root: Option<MerkleHash>,
+    pub key_value_count: u64,
+}
+
+impl SnapshotCheckResult {
+    pub fn is_consistent(&self) -> bool {
+        self.kv_merkle_root == self.expected_merkle_root
+            && self
+                .mpt_merkle_root
+                .map_or(true, |root| root == self.expected_merkle_root)
+    }
+}
+
+impl SnapshotInspector {
+    pub fn open(storage_conf: &StorageConfiguration) -> Result<Self> {
+        let snapshot_info_map = PersistedSnapshotInfoMap::load_readonly(
+            &storage_conf.path_snapshot_info_db,
+        )?;
+        let snapshot_db_manager = SnapshotDbManager::new(
+            storage_conf.snapshot_db_backend,
+            storage_conf.path_snapshot_dir.clone(),
+            storage_conf.max_open_snapshots,
+            storage_conf.use_isolated_db_for_mpt_table,
+            storage_conf.use_isolated_db_for_mpt_table_height,
+            storage_conf.consensus_param.era_epoch_count,
+        )?;
+        // The latest mpt snapshot belongs to the snapshot with the max height,
+        // the same as what `StorageManager::load_persist_state` sets.
+        if let Some(latest) = snapshot_info_map
+            .values()
+            .filter(|info| {
+                info.snapshot_info_kept_to_provide_sync
+                    != SnapshotKeptToProvideSyncStatus::InfoOnly
+            })
+            .max_by_key(|info| info.height)
+        {
+            snapshot_db_manager.update_latest_snapshot_id(
+                latest.get_snapshot_epoch_id().clone(),
+                latest.height,
+            );
+        }
+
+        Ok(Self {
+            snapshot_db_manager,
+            snapshot_info_map,
+        })
+    }
+
+    /// All snapshot infos ordered by height.
+    pub fn snapshot_infos(&self) -> Vec<&SnapshotInfo> {
+        let mut infos: Vec<_> = self.snapshot_info_map.values().collect();
+        infos.sort_by_key(|info| info.height);
+        infos
+    }
+
+    pub fn snapshot_info(
+        &self, snapshot_epoch_id: &EpochId,
+    ) -> Option<&SnapshotInfo> {
+        self.snapshot_info_map.get(snapshot_epoch_id)
+    }
+
+    /// Recompute the merkle root of the snapshot from its key-values and
+    /// compare it with the recorded one and the root of the snapshot mpt.
+    ///
+    /// Return None if the snapshot info or the snapshot db is missing.
+    pub fn check_snapshot(
+        &self, snapshot_epoch_id: &EpochId,
+    ) -> Result<Option<SnapshotCheckResult>> {
+        let snapshot_info = match self.snapshot_info_map.get(snapshot_epoch_id)
+        {
+            Some(info) => info,
+            None => return Ok(None),
+        };
+        let snapshot_db =
+            match self.snapshot_db_manager.get_snapshot_by_epoch_id(
+                snapshot_epoch_id,
+                /* try_open = */ false,
+                /* open_mpt_snapshot = */ true,
+            )? {
+                Some(snapshot_db) => snapshot_db,
+                None => return Ok(None),
+            };
+
+        Ok(Some(check_snapshot_db(snapshot_info, &snapshot_db)?))
+    }
+
+    /// Visit all key-values of the snapshot in the order of the keys.
+    ///
+    /// Return false if the snapshot info or the snapshot db is missing.
+    pub fn iterate_snapshot(
+        &self, snapshot_epoch_id: &EpochId,
+        mut visit: impl FnMut(&[u8], &[u8]) -> Result<()>,
+    ) -> Result<bool> {
+        if !self.snapshot_info_map.contains_key(snapshot_epoch_id) {
+            return Ok(false);
+        }
+        let snapshot_db =
+            match self.snapshot_db_manager.get_snapshot_by_epoch_id(
+                snapshot_epoch_id,
+                /* try_open = */ false,
+                /* open_mpt_snapshot = */ false,
+            )? {
+                Some(snapshot_db) => snapshot_db,
+                None => return Ok(false),
+            };
+
+        let mut key_value_iter = snapshot_db.snapshot_kv_iterator()?.take();
+        let mut kvs = key_value_iter.iter_range(&[], None)?.take();
+        while let Some((key, value)) = kvs.next()? {
+            visit(&key, &value)?;
+        }
+        Ok(true)
+    }
+}
+
+pub(super) fn check_snapshot_db(
+    snapshot_info: &SnapshotInfo, snapshot_db: &SnapshotDb,
+) -> Result<SnapshotCheckResult> {
+    let mpt_merkle_root = if snapshot_db.has_mpt_snapshot() {
+        Some((&snapshot_db.open_snapshot_mpt_shared()?).get_merkle_root())
+    } else {
+        None
+    };
+    let (kv_merkle_root, key_value_count) =
+        recompute_snapshot_merkle_root(snapshot_db)?;
+
+    Ok(SnapshotCheckResult {
+        snapshot_epoch_id: snapshot_info.get_snapshot_epoch_id().clone(),
+        height: snapshot_info.height,
+        expected_merkle_root: snapshot_info.merkle_root,
+        kv_merkle_root,
+        mpt_merkle_root,
+        key_value_count,
+    })
+}
+
+/// Build the mpt of all key-values in the snapshot without writing it anywhere
+/// and return its merkle root together with the number of key-values.
+pub fn recompute_snapshot_merkle_root(
+    snapshot_db: &SnapshotDb,
+) -> Result<(MerkleHash, u64)> {
+    let mut key_value_count = 0;
+    let mut key_value_iter = snapshot_db.snapshot_kv_iterator()?.take();
+    let kv_iter = key_value_iter.iter_range(&[], None)?.take().inspect(|_| {
+        key_value_count += 1;
+        Ok(())
+    });
+
+    let mut discard_mpt = DiscardWriteSnapshotMpt;
+    let merkle_root = MptMerger::new(None, &mut discard_mpt)
+        .merge_insertion_deletion_separated(
+            fallible_iterator::convert(
+                std::iter::empty::<Result<(Vec<u8>, ())>>(),
+            ),
+            kv_iter,
+            /* in_reconstruct_snapshot_state = */ false,
+        )?;
+
+    Ok((merkle_root, key_value_count))
+}
+
+/// An empty snapshot mpt which drops all writes. The MptMerger in save-as
+/// mode without a base mpt only writes to it, so merging into it computes
+/// the merkle root of the merged key-values without keeping the nodes.
+struct DiscardWriteSnapshotMpt;
+
+impl SnapshotMptTraitRead for DiscardWriteSnapshotMpt {
+    fn get_merkle_root(&self) -> MerkleHash { MERKLE_NULL_NODE }
+
+    fn load_node(
+        &mut self, _path: &dyn CompressedPathTrait,
+    ) -> Result<Option<SnapshotMptNode>> {
+        Ok(None)
+    }
+}
+
+impl SnapshotMptTraitReadAndIterate for DiscardWriteSnapshotMpt {
+    fn iterate_subtree_trie_nodes_without_root(
+        &mut self, _path: &dyn CompressedPathTrait,
+    ) -> Result<Box<dyn SnapshotMptIteraterTrait + '_>> {
+        Ok(Box::new(fallible_iterator::convert(std::iter::empty::<
+            Result<(CompressedPathRaw, SnapshotMptNode)>,
+        >())))
+    }
+}
+
+impl SnapshotMptTraitRw for DiscardWriteSnapshotMpt {
+    fn delete_node(&mut self, _path: &dyn CompressedPathTrait) -> Result<()> {
+        Ok(())
+    }
+
+    fn write_node(
+        &mut self, _path: &dyn CompressedPathTrait,
+        _trie_node: &SnapshotMptNode,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+use super::PersistedSnapshotInfoMap;
+use crate::{
+    impls::{
+        errors::*,
+        merkle_patricia_trie::{
+            CompressedPathRaw, CompressedPathTrait, MptMerger,
+        },
+        state_manager::{SnapshotDb, SnapshotDbManager},
+    },
+    storage_db::{
+        KeyValueDbIterableTrait, OpenSnapshotMptTrait, SnapshotDbManagerTrait,
+        SnapshotDbTrait, SnapshotInfo, SnapshotKeptToProvideSyncStatus,
+        SnapshotMptIteraterTrait, SnapshotMptNode, SnapshotMptTraitRead,
+        SnapshotMptTraitReadAndIterate, SnapshotMptTraitRw,
+    },
+    StorageConfiguration,
+};
+use fallible_iterator::FallibleIterator;
+use primitives::{EpochId, MerkleHash, MERKLE_NULL_NODE};
+use std::collections::HashMap;
@@ -5,21 +5,80 @@
 /// The in mem snapshot_info map and the on disk snapshot_info_db is always in
 /// sync.
 pub struct PersistedSnapshotInfoMap {
-    // Db to persist snapshot_info.
-    snapshot_info_db: KvdbSqlite<Box<[u8]>>,
+    // Db to persist snapshot_info. None when the snapshot infos are kept in
+    // memory only.
+    snapshot_info_db: Option<KvdbSqlite<Box<[u8]>>>,
     // In memory snapshot_info_map_by_epoch.
     snapshot_info_map_by_epoch: HashMap<EpochId, SnapshotInfo>,
 }
 
 impl PersistedSnapshotInfoMap {
-    fn new(snapshot_info_db: KvdbSqlite<Box<[u8]>>) -> Result<Self> {
-        let mut result = Self {
-            // The map is loaded later
+    fn new(mut snapshot_info_db: KvdbSqlite<Box<[u8]>>) -> Result<Self> {
+        Ok(Self {
+            snapshot_info_map_by_epoch: Self::load_persist_state(
+                &mut snapshot_info_db,
+            )?,
+            snapshot_info_db: Some(snapshot_info_db),
+        })
+    }
+
+    /// The snapshot_info map of the in-memory storage, which doesn't outlive
+    /// the process.
+    pub fn new_inmemory() -> Self {
+        Self {
+            snapshot_info_db: None,
             snapshot_info_map_by_epoch: Default::default(),
-            snapshot_info_db,
-        };
-        result.load_persist_state()?;
-        Ok(result)
+        }
+    }
+
+    fn open_or_create(snapshot_info_db_path: &Path) -> Result<Self> {
+        let (_, snapshot_info_db) = KvdbSqlite::open_or_create(
+            snapshot_info_db_path,
+            SNAPSHOT_KVDB_STATEMENTS.clone(),
+            false, /* unsafe_mode */
+        )?;
+        Self::new(snapshot_info_db)
+    }
+
+    #[cfg(test)]
+    pub fn open_or_create_for_testing(
+        snapshot_info_db_path: &Path,
+    ) -> Result<Self> {
+        Self::open_or_create(snapshot_info_db_path)
+    }
+
+    /// Load the snapshot_info map without opening the db for write. Used by
+    /// offline tools when the node is stopped.
+    pub fn load_readonly(
+        snapshot_info_db_path: &Path,
+    ) -> Result<HashMap<EpochId, SnapshotInfo>> {
+        let mut snapshot_info_db = KvdbSqlite::open(
+            snapshot_info_db_path,
+            /* readonly = */ true,
+            SNAPSHOT_KVDB_STATEMENTS.clone(),
+        )?;
+        Self::load_persist_state(&mut snapshot_info_db)
+    }
+
+    /// Remove the snapshot infos above `height` from the snapshot_info_db of
+    /// a stopped node, so that these snapshots are made again when their
+    /// epochs are executed again. The snapshot dbs and the delta dbs left
+    /// without snapshot info are deleted by the cleanup at the next start.
+    pub fn remove_snapshot_infos_above(
+        snapshot_info_db_path: &Path, height: u64,
+    ) -> Result<Vec<SnapshotInfo>> {
+        let mut snapshot_info_map =
+            Self::open_or_create(snapshot_info_db_path)?;
+        let removed: Vec<SnapshotInfo> = snapshot_info_map
+            .get_map()
+            .values()
+            .filter(|info| info.height > height)
+            .cloned()
+            .collect();
+        for snapshot_info in &removed {
+            snapshot_info_map.remove(snapshot_info.get_snapshot_epoch_id())?;
+        }
+        Ok(removed)
     }
 
     fn insert(
@@ -28,7 +87,9 @@ impl PersistedSnapshotInfoMap {
         let rlp_bytes = snapshot_info.rlp_bytes();
         self.snapshot_info_map_by_epoch
             .insert(epoch.clone(), snapshot_info);
-        self.snapshot_info_db.put(epoch.as_ref(), &rlp_bytes)?;
+        if let Some(snapshot_info_db) = &self.snapshot_info_db {
+            snapshot_info_db.put(epoch.as_ref(), &rlp_bytes)?;
+        }
         Ok(())
     }
 
@@ -36,13 +97,24 @@ impl PersistedSnapshotInfoMap {
         &self.snapshot_info_map_by_epoch
     }
 
+    fn checkpoint(&self, path: &Path) -> Result<()> {
+        match &self.snapshot_info_db {
+            Some(snapshot_info_db) => snapshot_info_db.backup(path),
+            None => {
+                bail!("In-memory snapshot infos can not be checkpointed")
+            }
+        }
+    }
+
     fn get(&self, epoch: &EpochId) -> Option<&SnapshotInfo> {
         self.snapshot_info_map_by_epoch.get(epoch)
     }
 
     fn remove(&mut self, epoch: &EpochId) -> Result<()> {
         self.snapshot_info_map_by_epoch.remove(epoch);
-        self.snapshot_info_db.delete(epoch.as_ref())?;
+        if let Some(snapshot_info_db) = &self.snapshot_info_db {
+            snapshot_info_db.delete(epoch.as_ref())?;
+        }
         Ok(())
     }
 
@@ -53,10 +125,13 @@ impl PersistedSnapshotInfoMap {
         self.snapshot_info_map_by_epoch.remove(epoch)
     }
 
-    fn load_persist_state(&mut self) -> Result<()> {
+    fn load_persist_state(
+        snapshot_info_db: &mut KvdbSqlite<Box<[u8]>>,
+    ) -> Result<HashMap<EpochId, SnapshotInfo>> {
         // Load snapshot info from db.
+        let mut snapshot_info_map_by_epoch = HashMap::new();
         let (maybe_info_db_connection, statements) =
-            self.snapshot_info_db.destructure_mut();
+            snapshot_info_db.destructure_mut();
 
         let mut snapshot_info_iter = kvdb_sqlite_iter_range_impl(
             maybe_info_db_connection,
@@ -80,10 +155,9 @@ impl PersistedSnapshotInfoMap {
         while let Some((snapshot_epoch, snapshot_info)) =
             snapshot_info_iter.next()?
         {
-            self.snapshot_info_map_by_epoch
-                .insert(snapshot_epoch, snapshot_info);
+            snapshot_info_map_by_epoch.insert(snapshot_epoch, snapshot_info);
         }
-        Ok(())
+        Ok(snapshot_info_map_by_epoch)
     }
 }
 
@@ -108,11 +182,16 @@ pub struct StorageManager {
 
     // Lock order: while this is locked, in
     // check_make_register_snapshot_background, snapshot_info_map_by_epoch
-    // is locked later.
+    // is locked later, and in checkpoint and rebuild_snapshot_in,
+    // current_snapshots is locked later.
     pub in_progress_snapshotting_tasks:
         RwLock<HashMap<EpochId, Arc<RwLock<InProgressSnapshotTask>>>>,
     in_progress_snapshot_finish_signaler: Arc<Mutex<Sender<Option<EpochId>>>>,
     in_progress_snapshotting_joiner: Mutex<Option<JoinHandle<()>>>,
+    // The exit signaler and the thread of the background snapshot verifier.
+    snapshot_verifier: Mutex<Option<(Sender<()>, JoinHandle<()>)>>,
+    // Snapshots are rebuilt one at a time in the same scratch dir.
+    snapshot_rebuild_lock: Mutex<()>,
 
     // The order doesn't matter as long as parent snapshot comes before
     // children snapshots.
@@ -210,17 +289,19 @@ impl StorageManager {
             "new StorageManager within storage_dir {}",
             storage_dir.display()
         );
-        if !storage_dir.exists() {
+        let inmemory =
+            storage_conf.snapshot_db_backend == SnapshotDbBackend::Inmemory;
+        if !inmemory && !storage_dir.exists() {
             fs::create_dir_all(storage_dir)?;
         }
 
-        let (_, snapshot_info_db) = KvdbSqlite::open_or_create(
-            &storage_conf.path_snapshot_info_db,
-            SNAPSHOT_KVDB_STATEMENTS.clone(),
-            false, /* unsafe_mode */
-        )?;
-        let snapshot_info_map =
-            PersistedSnapshotInfoMap::new(snapshot_info_db)?;
+        let snapshot_info_map = if inmemory {
+            PersistedSnapshotInfoMap::new_inmemory()
+        } else {
+            PersistedSnapshotInfoMap::open_or_create(
+                &storage_conf.path_snapshot_info_db,
+            )?
+        };
 
         let (
             in_progress_snapshot_finish_signaler,
@@ -228,6 +309,7 @@ impl StorageManager {
         ) = channel();
 
         let delta_db_manager = Arc::new(DeltaDbManager::new(
+            storage_conf.snapshot_db_backend,
             storage_conf.path_delta_mpts_dir.clone(),
         )?);
         let new_storage_manager_result = Ok(Arc::new(Self {
@@ -238,6 +320,7 @@ impl StorageManager {
             )?),
             snapshot_manager: Box::new(SnapshotManager::<SnapshotDbManager> {
                 snapshot_db_manager: SnapshotDbManager::new(
+                    storage_conf.snapshot_db_backend,
                     storage_conf.path_snapshot_dir.clone(),
                     storage_conf.max_open_snapshots,
                     storage_conf.use_isolated_db_for_mpt_table,
@@ -264,6 +347,8 @@ impl StorageManager {
                 in_progress_snapshot_finish_signaler,
             )),
             in_progress_snapshotting_joiner: Default::default(),
+            snapshot_verifier: Default::default(),
+            snapshot_rebuild_lock: Default::default(),
             current_snapshots: Default::default(),
             snapshot_info_map_by_epoch: RwLock::new(snapshot_info_map),
             last_confirmed_snapshottable_epoch_id: Default::default(),
@@ -385,6 +470,12 @@ impl StorageManager {
         {
             joiner.join().ok();
         }
+        if let Some((exit_signaler, joiner)) =
+            self.snapshot_verifier.lock().take()
+        {
+            drop(exit_signaler);
+            joiner.join().ok();
+        }
     }
 
     pub fn get_snapshot_manager(
@@ -852,15 +943,11 @@ impl StorageManager {
         era_epoch_count: u64, confirmed_height: u64,
         state_availability_boundary: &RwLock<StateAvailabilityBoundary>,
     ) -> Result<()> {
-        let additional_state_height_gap =
-            (self.storage_conf.additional_maintained_snapshot_count
-                * self.get_snapshot_epoch_count()) as u64;
         let maintained_state_height_lower_bound =
-            if confirmed_height > additional_state_height_gap {
-                confirmed_height - additional_state_height_gap
-            } else {
-                0
-            };
+            self.storage_conf.maintained_state_height_lower_bound(
+                confirmed_height,
+                stable_checkpoint_height,
+            );
         if maintained_state_height_lower_bound
             <= state_availability_boundary.read().lower_bound
         {
@@ -1219,8 +1306,9 @@ impl StorageManager {
         if !non_pivot_snapshots_to_remove.is_empty()
             || !old_pivot_snapshots_to_remove.is_empty()
         {
-            {
-                // TODO: Archive node may do something different.
+            // An archive node only removes non-pivot snapshots, so the state
+            // of all pivot epochs remains available.
+            if self.storage_conf.pruning_mode != PruningMode::Archive {
                 let state_boundary = &mut *state_availability_boundary.write();
                 if first_available_state_height > state_boundary.lower_bound {
                     state_boundary
@@ -1468,11 +1556,517 @@ impl StorageManager {
     }
 }
 
+/// The result of verifying a snapshot of a running node.
+#[derive(Debug)]
+pub struct SnapshotVerification {
+    pub check: SnapshotCheckResult,
+    /// The first pivot epoch executed on top of the snapshot, and the
+    /// snapshot merkle root in its execution commitment.
+    pub committed_by: Option<(EpochId, MerkleHash)>,
+    /// Whether the state root of the epoch in `committed_by` is confirmed by
+    /// the blame of the pivot chain. None if it's not decided yet.
+    pub state_root_verified: Option<bool>,
+    /// The merkle root of the snapshot rebuilt after the verification failed.
+    pub rebuilt_merkle_root: Option<MerkleHash>,
+}
+
+/// The storage components copied by `StorageManager::checkpoint`.
+#[derive(Debug, Default)]
+pub struct StorageCheckpoint {
+    /// The snapshots copied by `StorageManager::checkpoint_snapshots`.
+    pub snapshot_epoch_ids: Vec<EpochId>,
+    /// The snapshot epochs on top of which the copied delta mpts are.
+    pub delta_mpt_epoch_ids: Vec<EpochId>,
+}
+
+/// The snapshots to be copied by `StorageManager::checkpoint_snapshots`. They
+/// are kept open, so that they aren't removed before they are copied.
+pub struct PinnedSnapshots {
+    dest_snapshot_dir: PathBuf,
+    snapshots: Vec<(EpochId, SnapshotDb)>,
+}
+
+impl SnapshotVerification {
+    pub fn is_consistent(&self) -> bool {
+        self.check.is_consistent()
+            && self.committed_by.as_ref().map_or(true, |(_, root)| {
+                *root == self.check.expected_merkle_root
+            })
+    }
+}
+
+impl StorageManager {
+    const SNAPSHOT_REBUILD_DIR_NAME: &'static str = "snapshot_rebuild";
+    const SNAPSHOT_REBUILD_REPLACE_RETRY_INTERVAL: Duration =
+        Duration::from_millis(100);
+    const SNAPSHOT_REBUILD_REPLACE_TIMEOUT: Duration = Duration::from_secs(60);
+
+    /// Verify all snapshots kept by the node, see `verify_snapshot`.
+    pub fn verify_snapshots<ConsensusInner: StateMaintenanceTrait>(
+        self: &Arc<Self>, consensus_inner: &RwLock<ConsensusInner>,
+        rebuild: bool,
+    ) -> Result<Vec<SnapshotVerification>> {
+        let snapshot_epoch_ids: Vec<EpochId> = self
+            .current_snapshots
+            .read()
+            .iter()
+            .map(|info| info.get_snapshot_epoch_id().clone())
+            .collect();
+        let mut verifications = Vec::with_capacity(snapshot_epoch_ids.len());
+        for snapshot_epoch_id in &snapshot_epoch_ids {
+            if let Some(verification) = self.verify_snapshot(
+                consensus_inner,
+                snapshot_epoch_id,
+                rebuild,
+            )? {
+                verifications.push(verification);
+            }
+        }
+        Ok(verifications)
+    }
+
+    /// Recompute the merkle root of the snapshot, and compare it with the
+    /// recorded one, the root of the snapshot mpt and the snapshot root
+    /// committed by the execution of the epochs based on the snapshot. With
+    /// `rebuild`, an inconsistent snapshot is rebuilt by `rebuild_snapshot`.
+    ///
+    /// Return None if the snapshot isn't kept by the node.
+    pub fn verify_snapshot<ConsensusInner: StateMaintenanceTrait>(
+        self: &Arc<Self>, consensus_inner: &RwLock<ConsensusInner>,
+        snapshot_epoch_id: &EpochId, rebuild: bool,
+    ) -> Result<Option<SnapshotVerification>> {
+        let snapshot_info =
+            match self.get_snapshot_info_at_epoch(snapshot_epoch_id) {
+                Some(snapshot_info)
+                    if *snapshot_epoch_id != NULL_EPOCH
+                        && snapshot_info.snapshot_info_kept_to_provide_sync
+                            != SnapshotKeptToProvideSyncStatus::InfoOnly =>
+                {
+                    snapshot_info
+                }
+                _ => return Ok(None),
+            };
+
+        // The consensus lock isn't held while the merkle root is recomputed.
+        let (committed_by, state_root_verified) = {
+            let consensus_inner = consensus_inner.read();
+            match self
+                .find_snapshot_commitment(&*consensus_inner, &snapshot_info)
+            {
+                Some((epoch_id, snapshot_root)) => {
+                    let verified =
+                        consensus_inner.is_epoch_state_root_verified(&epoch_id);
+                    (Some((epoch_id, snapshot_root)), verified)
+                }
+                None => (None, None),
+            }
+        };
+        let check = match self.wait_for_snapshot(
+            snapshot_epoch_id,
+            /* try_open = */ false,
+            /* open_mpt_snapshot = */ true,
+        )? {
+            None => return Ok(None),
+            Some(guarded_snapshot_db) => {
+                check_snapshot_db(&snapshot_info, guarded_snapshot_db.as_ref())?
+            }
+        };
+
+        let mut verification = SnapshotVerification {
+            check,
+            committed_by,
+            state_root_verified,
+            rebuilt_merkle_root: None,
+        };
+        SNAPSHOT_VERIFIED_COUNTER.inc(1);
+        if verification.state_root_verified == Some(false) {
+            SNAPSHOT_STATE_ROOT_BLAMED_COUNTER.inc(1);
+            warn!(
+                "The state root based on snapshot {:?} is blamed: {:?}",
+                snapshot_epoch_id, verification
+            );
+        }
+        if !verification.is_consistent() {
+            SNAPSHOT_INCONSISTENT_COUNTER.inc(1);
+            error!("Inconsistent snapshot: {:?}", verification);
+            if rebuild {
+                let merkle_root = self.rebuild_snapshot(snapshot_epoch_id)?;
+                SNAPSHOT_REBUILT_COUNTER.inc(1);
+                verification.rebuilt_merkle_root = Some(merkle_root);
+            }
+        }
+        Ok(Some(verification))
+    }
+
+    /// Find the first pivot epoch executed on top of the snapshot and return
+    /// the snapshot root in its execution commitment.
+    fn find_snapshot_commitment<ConsensusInner: StateMaintenanceTrait>(
+        &self, consensus_inner: &ConsensusInner, snapshot_info: &SnapshotInfo,
+    ) -> Option<(EpochId, MerkleHash)> {
+        let snapshot_epoch_count = self.get_snapshot_epoch_count() as u64;
+        // The snapshot becomes the base of the states after the next snapshot
+        // is made.
+        for epoch_number in snapshot_info.height + 1
+            ..=snapshot_info.height + 2 * snapshot_epoch_count
+        {
+            let epoch_id = consensus_inner
+                .get_pivot_hash_from_epoch_number(epoch_number)
+                .ok()?;
+            if let Some(commitment) = consensus_inner
+                .get_epoch_execution_commitment_with_db(&epoch_id)
+            {
+                let state_root = &commitment.state_root_with_aux_info;
+                if state_root.aux_info.snapshot_epoch_id
+                    == *snapshot_info.get_snapshot_epoch_id()
+                {
+                    return Some((
+                        epoch_id,
+                        state_root.state_root.snapshot_root,
+                    ));
+                }
+            }
+        }
+        None
+    }
+
+    /// Merge the snapshot again from its parent snapshot and the intermediate
+    /// delta mpt of the parent snapshot in a scratch dir, and replace the
+    /// snapshot with it once the snapshot isn't open. Return the merkle
+    /// root of the new snapshot.
+    ///
+    /// The snapshot is kept and an error is returned if the new merkle root
+    /// isn't the recorded one, or if the snapshot stays in use.
+    ///
+    /// The snapshot mpt must be kept in the snapshot db, and the parent
+    /// snapshot and the intermediate delta mpt must be still available.
+    pub fn rebuild_snapshot(
+        self: &Arc<Self>, snapshot_epoch_id: &EpochId,
+    ) -> Result<MerkleHash> {
+        if self.storage_conf.use_isolated_db_for_mpt_table {
+            bail!("Can not rebuild a snapshot with an isolated mpt db");
+        }
+        let snapshot_info = self
+            .get_snapshot_info_at_epoch(snapshot_epoch_id)
+            .ok_or(Error::SnapshotNotFound)?;
+        if *snapshot_epoch_id == NULL_EPOCH
+            || snapshot_info.snapshot_info_kept_to_provide_sync
+                == SnapshotKeptToProvideSyncStatus::InfoOnly
+        {
+            bail!(Error::SnapshotNotFound);
+        }
+        // The snapshot was merged from the intermediate delta mpt of the
+        // states based on the parent snapshot.
+        let intermediate_delta_mpt = self
+            .snapshot_associated_mpts_by_epoch
+            .read()
+            .get(&snapshot_info.parent_snapshot_epoch_id)
+            .and_then(|(intermediate_delta_mpt, _)| {
+                intermediate_delta_mpt.clone()
+            })
+            .ok_or_else(|| {
+                Error::from(format!(
+                    "Intermediate delta mpt of parent snapshot {:?} not found",
+                    snapshot_info.parent_snapshot_epoch_id
+                ))
+            })?;
+        let intermediate_delta_root = intermediate_delta_mpt
+            .get_root_node_ref_by_epoch(snapshot_epoch_id)?
+            .ok_or_else(|| {
+                Error::from(format!(
+                    "Intermediate delta root of snapshot {:?} not found",
+                    snapshot_epoch_id
+                ))
+            })?;
+
+        let _rebuild_lock = self.snapshot_rebuild_lock.lock();
+        let rebuild_dir = self
+            .storage_conf
+            .path_storage_dir
+            .join(Self::SNAPSHOT_REBUILD_DIR_NAME);
+        if rebuild_dir.exists() {
+            fs::remove_dir_all(&rebuild_dir)?;
+        }
+        let result = self.rebuild_snapshot_in(
+            &rebuild_dir,
+            snapshot_epoch_id,
+            &snapshot_info,
+            DeltaMptIterator {
+                mpt: intermediate_delta_mpt,
+                maybe_root_node: intermediate_delta_root,
+            },
+        );
+        // The replaced snapshot is moved into the rebuild dir.
+        if rebuild_dir.exists() {
+            if let Err(e) = fs::remove_dir_all(&rebuild_dir) {
+                warn!("Failed to remove {:?}: {}", rebuild_dir, e);
+            }
+        }
+        result
+    }
+
+    fn rebuild_snapshot_in(
+        &self, rebuild_dir: &Path, snapshot_epoch_id: &EpochId,
+        snapshot_info: &SnapshotInfo, delta_mpt_iterator: DeltaMptIterator,
+    ) -> Result<MerkleHash> {
+        let snapshot_db_manager =
+            self.snapshot_manager.get_snapshot_db_manager();
+        let rebuild_snapshot_db_manager = SnapshotDbManager::new(
+            self.storage_conf.snapshot_db_backend,
+            rebuild_dir.to_path_buf(),
+            self.storage_conf.max_open_snapshots,
+            /* use_isolated_db_for_mpt_table = */ false,
+            /* use_isolated_db_for_mpt_table_height = */ None,
+            self.storage_conf.consensus_param.era_epoch_count,
+        )?;
+        if snapshot_info.parent_snapshot_epoch_id != NULL_EPOCH {
+            snapshot_db_manager.checkpoint_snapshot(
+                &snapshot_info.parent_snapshot_epoch_id,
+                rebuild_dir,
+            )?;
+        }
+
+        info!(
+            "Rebuild snapshot {:?} from parent snapshot {:?}",
+            snapshot_epoch_id, snapshot_info.parent_snapshot_epoch_id
+        );
+        let (snapshot_info_map_locked, new_snapshot_info) =
+            rebuild_snapshot_db_manager.new_snapshot_by_merging(
+                &snapshot_info.parent_snapshot_epoch_id,
+                snapshot_epoch_id.clone(),
+                delta_mpt_iterator,
+                snapshot_info.clone(),
+                &self.snapshot_info_map_by_epoch,
+                snapshot_info.height,
+                /* recover_mpt_with_kv_snapshot_exist = */ false,
+            )?;
+        drop(snapshot_info_map_locked);
+        drop(rebuild_snapshot_db_manager);
+        if new_snapshot_info.merkle_root != snapshot_info.merkle_root {
+            bail!(format!(
+                "Rebuilt snapshot {:?} has merkle root {:?}, but {:?} is \
+                 expected",
+                snapshot_epoch_id,
+                new_snapshot_info.merkle_root,
+                snapshot_info.merkle_root
+            ));
+        }
+
+        // The snapshot is only replaced while it's closed and no snapshot is
+        // being made, which may read it as the parent snapshot.
+        let mut waited = Duration::from_secs(0);
+        loop {
+            {
+                let in_progress_snapshotting_tasks =
+                    self.in_progress_snapshotting_tasks.read();
+                let current_snapshots = self.current_snapshots.read();
+                if !current_snapshots.iter().any(|info| {
+                    info.get_snapshot_epoch_id() == snapshot_epoch_id
+                }) {
+                    bail!(Error::SnapshotNotFound);
+                }
+                if in_progress_snapshotting_tasks.is_empty()
+                    && snapshot_db_manager.replace_snapshot_if_closed(
+                        snapshot_epoch_id,
+                        rebuild_dir,
+                    )?
+                {
+                    break;
+                }
+            }
+            if waited >= Self::SNAPSHOT_REBUILD_REPLACE_TIMEOUT {
+                bail!(format!(
+                    "Can not replace snapshot {:?} which is still in use",
+                    snapshot_epoch_id
+                ));
+            }
+            thread::sleep(Self::SNAPSHOT_REBUILD_REPLACE_RETRY_INTERVAL);
+            waited += Self::SNAPSHOT_REBUILD_REPLACE_RETRY_INTERVAL;
+        }
+        Ok(new_snapshot_info.merkle_root)
+    }
+
+    /// Start verifying all snapshots every `snapshot_verify_interval` in
+    /// background, if configured.
+    pub fn start_snapshot_verifier<
+        ConsensusInner: 'static + StateMaintenanceTrait + Send + Sync,
+    >(
+        self: &Arc<Self>, consensus_inner: Arc<RwLock<ConsensusInner>>,
+    ) -> Result<()> {
+        let interval = match self.storage_conf.snapshot_verify_interval {
+            Some(interval) => interval,
+            None => return Ok(()),
+        };
+        let rebuild = self.storage_conf.snapshot_self_heal;
+        let storage_manager = Arc::downgrade(self);
+        let consensus_inner = Arc::downgrade(&consensus_inner);
+        let (exit_signaler, exit_signal_receiver) = channel();
+        let joiner = thread::Builder::new()
+            .name("Snapshot Verifier".into())
+            .spawn(move || loop {
+                match exit_signal_receiver.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    _ => break,
+                }
+                let (storage_manager, consensus_inner) = match (
+                    storage_manager.upgrade(),
+                    consensus_inner.upgrade(),
+                ) {
+                    (Some(storage_manager), Some(consensus_inner)) => {
+                        (storage_manager, consensus_inner)
+                    }
+                    _ => break,
+                };
+                match storage_manager
+                    .verify_snapshots(&consensus_inner, rebuild)
+                {
+                    Ok(verifications) => debug!(
+                        "Verified {} snapshots, {} inconsistent",
+                        verifications.len(),
+                        verifications
+                            .iter()
+                            .filter(|v| !v.is_consistent())
+                            .count()
+                    ),
+                    Err(e) => warn!("Failed to verify snapshots: {}", e),
+                }
+            })?;
+        *self.snapshot_verifier.lock() = Some((exit_signaler, joiner));
+        Ok(())
+    }
+}
+
+impl StorageManager {
+    /// Copy the snapshot info db, the latest MPT snapshot and the delta mpts
+    /// into `dest`, which has the layout of the conflux data dir, while the
+    /// node is running, and pin the snapshots for `checkpoint_snapshots`.
+    ///
+    /// The caller must pause the execution of epochs until this returns, so
+    /// that no delta mpt is written and no snapshot is started. The snapshots
+    /// already being made are waited for.
+    pub fn checkpoint(
+        &self, dest: &Path,
+    ) -> Result<(StorageCheckpoint, PinnedSnapshots)> {
+        let _in_progress_snapshotting_tasks = loop {
+            let in_progress_snapshotting_tasks =
+                self.in_progress_snapshotting_tasks.read();
+            if in_progress_snapshotting_tasks.is_empty() {
+                break in_progress_snapshotting_tasks;
+            }
+            drop(in_progress_snapshotting_tasks);
+            thread::sleep(Duration::from_millis(100));
+        };
+        // Keep the snapshots and the delta mpts from being removed.
+        let snapshot_info_map = self.snapshot_info_map_by_epoch.read();
+        let current_snapshots = self.current_snapshots.read();
+        let mut delta_mpts = HashMap::new();
+        for (snapshot_epoch_id, (intermediate_delta_mpt, delta_mpt)) in
+            &*self.snapshot_associated_mpts_by_epoch.read()
+        {
+            if let Some(delta_mpt) = delta_mpt {
+                delta_mpts.insert(*snapshot_epoch_id, delta_mpt.clone());
+            }
+            if let (Some(intermediate_delta_mpt), Some(snapshot_info)) = (
+                intermediate_delta_mpt,
+                snapshot_info_map.get(snapshot_epoch_id),
+            ) {
+                delta_mpts.insert(
+                    snapshot_info.parent_snapshot_epoch_id,
+                    intermediate_delta_mpt.clone(),
+                );
+            }
+        }
+
+        fs::create_dir_all(dest.join(&*storage_dir::STORAGE_DIR))?;
+        snapshot_info_map
+            .checkpoint(&dest.join(&*storage_dir::SNAPSHOT_INFO_DB_PATH))?;
+
+        let mut checkpoint = StorageCheckpoint::default();
+        let snapshot_db_manager =
+            self.snapshot_manager.get_snapshot_db_manager();
+        let dest_snapshot_dir = dest.join(&*storage_dir::SNAPSHOT_DIR);
+        fs::create_dir_all(&dest_snapshot_dir)?;
+        let mut pinned_snapshots = PinnedSnapshots {
+            dest_snapshot_dir,
+            snapshots: Vec::new(),
+        };
+        for snapshot_info in current_snapshots.iter() {
+            let snapshot_epoch_id = snapshot_info.get_snapshot_epoch_id();
+            if *snapshot_epoch_id == NULL_EPOCH
+                || snapshot_info.snapshot_info_kept_to_provide_sync
+                    == SnapshotKeptToProvideSyncStatus::InfoOnly
+            {
+                continue;
+            }
+            // An open snapshot is only removed when it's closed. Fail rather
+            // than wait for an open permit while the execution is paused.
+            let snapshot_db = snapshot_db_manager
+                .get_snapshot_by_epoch_id(
+                    snapshot_epoch_id,
+                    /* try_open = */ true,
+                    /* open_mpt_snapshot = */ false,
+                )?
+                .ok_or(Error::SnapshotNotFound)?;
+            checkpoint.snapshot_epoch_ids.push(*snapshot_epoch_id);
+            pinned_snapshots
+                .snapshots
+                .push((*snapshot_epoch_id, snapshot_db));
+        }
+        snapshot_db_manager.checkpoint_latest_mpt_snapshot(
+            &pinned_snapshots.dest_snapshot_dir,
+        )?;
+
+        let dest_delta_mpts_dir = dest.join(&*storage_dir::DELTA_MPTS_DIR);
+        fs::create_dir_all(&dest_delta_mpts_dir)?;
+        for (snapshot_epoch_id, delta_mpt) in delta_mpts {
+            delta_mpt
+                .get_arc_db()?
+                .checkpoint(&dest_delta_mpts_dir.join(
+                    self.delta_db_manager.get_delta_db_name(&snapshot_epoch_id),
+                ))?;
+            checkpoint.delta_mpt_epoch_ids.push(snapshot_epoch_id);
+        }
+
+        info!(
+            "Checkpoint storage into {:?}: {} delta mpts, {} snapshots pinned",
+            dest,
+            checkpoint.delta_mpt_epoch_ids.len(),
+            checkpoint.snapshot_epoch_ids.len()
+        );
+        Ok((checkpoint, pinned_snapshots))
+    }
+
+    /// Copy the snapshots pinned by `checkpoint`. The snapshots are
+    /// immutable, so the execution of epochs doesn't have to be paused.
+    pub fn checkpoint_snapshots(
+        &self, pinned_snapshots: PinnedSnapshots,
+    ) -> Result<()> {
+        let snapshot_db_manager =
+            self.snapshot_manager.get_snapshot_db_manager();
+        for (snapshot_epoch_id, _snapshot_db) in &pinned_snapshots.snapshots {
+            snapshot_db_manager.checkpoint_snapshot(
+                snapshot_epoch_id,
+                &pinned_snapshots.dest_snapshot_dir,
+            )?;
+        }
+        info!(
+            "Checkpoint {} snapshots into {:?}",
+            pinned_snapshots.snapshots.len(),
+            pinned_snapshots.dest_snapshot_dir
+        );
+        Ok(())
+    }
+}
+
 fn extra_snapshots_to_keep_predicate(
     storage_conf: &StorageConfiguration, stable_checkpoint_height: u64,
     era_epoch_count: u64, height: u64,
     find_epoch_nearest_multiple_of: &mut bool,
 ) -> bool {
+    // An archive node keeps the snapshots of all pivot epochs, which is how
+    // it keeps all the state.
+    if storage_conf.pruning_mode == PruningMode::Archive {
+        return true;
+    }
     for conf in &storage_conf.provide_more_snapshot_for_sync {
         match conf {
             ProvideExtraSnapshotSyncConfig::StableCheckpoint => {
@@ -1574,6 +2168,17 @@ lazy_static! {
         )
         .unwrap()
     );
+    static ref SNAPSHOT_VERIFIED_COUNTER: Arc<dyn Counter<usize>> =
+        CounterUsize::register_with_group("storage", "snapshot_verified");
+    static ref SNAPSHOT_INCONSISTENT_COUNTER: Arc<dyn Counter<usize>> =
+        CounterUsize::register_with_group("storage", "snapshot_inconsistent");
+    static ref SNAPSHOT_STATE_ROOT_BLAMED_COUNTER: Arc<dyn Counter<usize>> =
+        CounterUsize::register_with_group(
+            "storage",
+            "snapshot_state_root_blamed"
+        );
+    static ref SNAPSHOT_REBUILT_COUNTER: Arc<dyn Counter<usize>> =
+        CounterUsize::register_with_group("storage", "snapshot_rebuilt");
 }
 
 use crate::{
@@ -1593,24 +2198,28 @@ use crate::{
             },
             snapshot_kv_db_sqlite::test_lib::check_key_value_load,
         },
-        storage_manager::snapshot_manager::SnapshotManager,
+        storage_manager::{
+            snapshot_inspector::{check_snapshot_db, SnapshotCheckResult},
+            snapshot_manager::SnapshotManager,
+        },
     },
     snapshot_manager::SnapshotManagerTrait,
     storage_db::{
-        DeltaDbManagerTrait, KeyValueDbIterableTrait, SnapshotDbManagerTrait,
-        SnapshotInfo, SnapshotKeptToProvideSyncStatus,
+        DeltaDbManagerTrait, DeltaDbTrait, KeyValueDbIterableTrait,
+        SnapshotDbManagerTrait, SnapshotInfo, SnapshotKeptToProvideSyncStatus,
     },
     storage_dir,
     utils::guarded_value::GuardedValue,
     DeltaMpt, DeltaMptIdGen, DeltaMptIterator, KeyValueDbTrait, KvdbSqlite,
-    OpenDeltaDbLru, ProvideExtraSnapshotSyncConfig, StateIndex,
-    StateRootWithAuxInfo, StorageConfiguration,
+    OpenDeltaDbLru, ProvideExtraSnapshotSyncConfig, PruningMode,
+    SnapshotDbBackend, StateIndex, StateRootWithAuxInfo, StorageConfiguration,
 };
 use cfx_internal_common::{
     consensus_api::StateMaintenanceTrait, StateAvailabilityBoundary,
 };
 use fallible_iterator::FallibleIterator;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use metrics::{Counter, CounterUsize};
 use parking_lot::{Mutex, RwLock, RwLockReadGuard};
 use primitives::{EpochId, MerkleHash, MERKLE_NULL_NODE, NULL_EPOCH};
 use rlp::{Decodable, DecoderError, Encodable, Rlp};
@@ -1619,9 +2228,11 @@ use std::{
     cell::Cell,
     collections::{HashMap, HashSet},
     fs,
+    path::{Path, PathBuf},
     sync::{
-        mpsc::{channel, Sender},
+        mpsc::{channel, RecvTimeoutError, Sender},
         Arc, Weak,
     },
     thread::{self, JoinHandle},
+    time::Duration,
 };
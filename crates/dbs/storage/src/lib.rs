@@ -100,6 +100,93 @@ impl ProvideExtraSnapshotSyncConfig {
     }
 }
 
+/// How much history a node keeps.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum PruningMode {
+    /// Keep all state, block data, receipts, traces and transaction indices.
+    Archive,
+    /// Keep the block data after the stable checkpoint and the state of the
+    /// configured number of snapshots.
+    #[default]
+    Full,
+    /// Keep the state and the block data of at least the latest given number
+    /// of epochs.
+    Pruned(u64),
+}
+
+impl PruningMode {
+    pub fn from_str(config: &str) -> std::result::Result<Self, String> {
+        const PRUNED_PREFIX: &'static str = "pruned:";
+        if config == "archive" {
+            Ok(Self::Archive)
+        } else if config == "full" {
+            Ok(Self::Full)
+        } else if config.starts_with(PRUNED_PREFIX) {
+            let number_str = &config[PRUNED_PREFIX.len()..];
+            match number_str.parse::<u64>() {
+                Ok(num) if num > 0 => Ok(Self::Pruned(num)),
+                _ => Err(format!("{} is not a valid PruningMode", config)),
+            }
+        } else {
+            Err(format!("{} is not a valid PruningMode", config))
+        }
+    }
+
+    /// The number of epochs before the stable checkpoint whose block data,
+    /// receipts, rewards, traces and transaction indices are kept, None to
+    /// keep all.
+    pub fn block_data_defer_epochs(&self) -> Option<usize> {
+        match self {
+            Self::Archive => None,
+            Self::Full => Some(0),
+            Self::Pruned(epochs) => Some(*epochs as usize),
+        }
+    }
+}
+
+impl std::fmt::Display for PruningMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Archive => write!(f, "archive"),
+            Self::Full => write!(f, "full"),
+            Self::Pruned(epochs) => write!(f, "pruned:{}", epochs),
+        }
+    }
+}
+
+/// The database which keeps the snapshots. The snapshots of one backend can
+/// not be opened by the other.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum SnapshotDbBackend {
+    #[default]
+    Sqlite,
+    Rocksdb,
+    /// Keep the snapshots, the delta MPTs and the snapshot infos in memory.
+    /// Nothing is persisted, so it's only meant for tests and fuzzing.
+    Inmemory,
+}
+
+impl SnapshotDbBackend {
+    pub fn from_str(config: &str) -> std::result::Result<Self, String> {
+        match config {
+            "sqlite" => Ok(Self::Sqlite),
+            "rocksdb" => Ok(Self::Rocksdb),
+            "inmemory" => Ok(Self::Inmemory),
+            _ => Err(format!("{} is not a valid SnapshotDbBackend", config)),
+        }
+    }
+}
+
+impl std::fmt::Display for SnapshotDbBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sqlite => write!(f, "sqlite"),
+            Self::Rocksdb => write!(f, "rocksdb"),
+            Self::Inmemory => write!(f, "inmemory"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct StorageConfiguration {
     pub additional_maintained_snapshot_count: u32,
@@ -127,6 +214,16 @@ pub struct StorageConfiguration {
     pub use_isolated_db_for_mpt_table: bool,
     pub use_isolated_db_for_mpt_table_height: Option<u64>,
     pub keep_era_genesis_snapshot: bool,
+    pub pruning_mode: PruningMode,
+    pub snapshot_db_backend: SnapshotDbBackend,
+    /// Recompute the merkle roots of all snapshots at this interval in
+    /// background. Disabled if None.
+    pub snapshot_verify_interval: Option<Duration>,
+    /// Rebuild a snapshot found inconsistent by the background verifier.
+    pub snapshot_self_heal: bool,
+    /// The max number of keys kept in the flat cache of the latest executed
+    /// state. Disabled if 0.
+    pub flat_state_cache_size: usize,
 }
 
 impl StorageConfiguration {
@@ -175,6 +272,35 @@ impl StorageConfiguration {
             use_isolated_db_for_mpt_table: false,
             use_isolated_db_for_mpt_table_height: None,
             keep_era_genesis_snapshot: false,
+            pruning_mode: Default::default(),
+            snapshot_db_backend: Default::default(),
+            snapshot_verify_interval: None,
+            snapshot_self_heal: false,
+            flat_state_cache_size: 0,
+        }
+    }
+
+    /// The lowest height whose state is kept.
+    ///
+    /// `full` keeps the state of `additional_maintained_snapshot_count`
+    /// snapshots before the confirmed height. `pruned:N` keeps at least that,
+    /// and also the state of the N epochs before the stable checkpoint, which
+    /// is the same height the block data retention counts from, so the state
+    /// and the block data of an epoch are kept together. `archive` maintains
+    /// the snapshots from the same height as `full`, but never removes an old
+    /// pivot snapshot, so that all the state is kept.
+    pub fn maintained_state_height_lower_bound(
+        &self, confirmed_height: u64, stable_checkpoint_height: u64,
+    ) -> u64 {
+        let snapshot_gap = self.additional_maintained_snapshot_count as u64
+            * self.consensus_param.snapshot_epoch_count as u64;
+        let full_lower_bound = confirmed_height.saturating_sub(snapshot_gap);
+        match self.pruning_mode {
+            PruningMode::Archive | PruningMode::Full => full_lower_bound,
+            PruningMode::Pruned(epochs) => min(
+                full_lower_bound,
+                stable_checkpoint_height.saturating_sub(epochs),
+            ),
         }
     }
 
@@ -199,6 +325,7 @@ pub use self::{
         defaults,
         delta_mpt::*,
         errors::{Error, Result},
+        forked_state::{ForkStateCache, ForkStateSource, ForkedState},
         merkle_patricia_trie::{
             mpt_cursor::rlp_key_value_len, simple_mpt::*,
             trie_proof::TrieProofNode, CompressedPathRaw, KVInserter,
@@ -206,15 +333,39 @@ pub use self::{
         },
         node_merkle_proof::{NodeMerkleProof, StorageRootProof},
         proof_merger::StateProofMerger,
-        recording_storage::RecordingStorage,
+        proof_verifier::{
+            intermediate_padding_of, verify_state_entry, verify_storage_root,
+            StateRootWitness,
+        },
+        recording_storage::{
+            RecordingStorage, StateWitnessRecorder, WitnessRecordingStorage,
+        },
         snapshot_sync::{FullSyncVerifier, MptSlicer},
+        state_manager::{SnapshotDb, SnapshotDbManager},
         state_proof::StateProof,
+        state_witness::{
+            StateWitness, WitnessEntry, WitnessPrefixRead, WitnessStorage,
+        },
         storage_db::{
+            delta_db_manager_dispatch::DeltaDbManagerDispatch,
+            delta_db_manager_inmemory::DeltaDbManagerInmemory,
+            kvdb_inmemory::KvdbInmemory,
             kvdb_rocksdb::KvdbRocksdb,
             kvdb_sqlite::{KvdbSqlite, KvdbSqliteStatements},
+            snapshot_db_manager_dispatch::SnapshotDbManagerDispatch,
+            snapshot_db_manager_inmemory::SnapshotDbManagerInmemory,
+            snapshot_db_manager_rocksdb::SnapshotDbManagerRocksdb,
             snapshot_db_manager_sqlite::SnapshotDbManagerSqlite,
             sqlite::SqliteConnection,
         },
+        storage_manager::{
+            snapshot_inspector::{
+                recompute_snapshot_merkle_root, SnapshotCheckResult,
+                SnapshotInspector,
+            },
+            PersistedSnapshotInfoMap, PinnedSnapshots, SnapshotVerification,
+            StorageCheckpoint,
+        },
     },
     replicated_state::ReplicatedState,
     state::{
@@ -233,4 +384,8 @@ pub use self::tests::new_state_manager_for_unit_test as new_storage_manager_for_
 use crate::impls::replicated_state;
 use cfx_internal_common::StateRootWithAuxInfo;
 use cfx_types::Space;
-use std::path::{Path, PathBuf};
+use std::{
+    cmp::min,
+    path::{Path, PathBuf},
+    time::Duration,
+};
@@ -19,6 +19,9 @@ pub trait DeltaDbTrait:
     + Send
     + Sync
 {
+    /// Make a consistent copy of the delta db at `path`, while the delta db
+    /// is in use.
+    fn checkpoint(&self, path: &Path) -> Result<()>;
 }
 
 pub trait DeltaDbManagerTrait {
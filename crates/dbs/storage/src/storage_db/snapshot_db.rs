@@ -108,6 +108,10 @@ pub trait SnapshotDbTrait:
 {
     type SnapshotKvdbIterTraitTag;
 
+    /// The database holding the snapshot MPT when it isn't stored in the
+    /// snapshot db itself.
+    type SnapshotMptDb;
+
     type SnapshotKvdbIterType: WrappedTrait<
         dyn KeyValueDbIterableTrait<
             MptKeyValue,
@@ -138,14 +142,14 @@ pub trait SnapshotDbTrait:
 
     fn direct_merge(
         &mut self, old_snapshot_db: Option<&Arc<Self>>,
-        mpt_snapshot: &mut Option<SnapshotMptDbSqlite>,
+        mpt_snapshot: &mut Option<Self::SnapshotMptDb>,
         recover_mpt_with_kv_snapshot_exist: bool,
         in_reconstruct_snapshot_state: bool,
     ) -> StorageResult<MerkleHash>;
 
     fn copy_and_merge(
         &mut self, old_snapshot_db: &Arc<Self>,
-        mpt_snapshot_db: &mut Option<SnapshotMptDbSqlite>,
+        mpt_snapshot_db: &mut Option<Self::SnapshotMptDb>,
         in_reconstruct_snapshot_state: bool,
     ) -> StorageResult<MerkleHash>;
 
@@ -172,10 +176,7 @@ pub trait SnapshotDbTrait:
 use crate::{
     impls::{
         errors::Result as StorageResult,
-        storage_db::{
-            snapshot_db_manager_sqlite::AlreadyOpenSnapshots,
-            snapshot_mpt_db_sqlite::SnapshotMptDbSqlite,
-        },
+        storage_db::snapshot_db_manager_sqlite::AlreadyOpenSnapshots,
     },
     storage_db::{
         KeyValueDbIterableTrait, KeyValueDbTraitOwnedRead, KeyValueDbTraitRead,
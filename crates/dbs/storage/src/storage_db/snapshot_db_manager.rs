@@ -214,6 +214,27 @@ pub trait SnapshotDbManagerTrait {
     ) -> Result<Option<Self::SnapshotDb>>;
     fn destroy_snapshot(&self, snapshot_epoch_id: &EpochId) -> Result<()>;
 
+    /// Copy the snapshot db of `snapshot_epoch_id` into
+    /// `dest_snapshot_dir`, which has the layout of the snapshot dir. The
+    /// snapshot may be in use.
+    fn checkpoint_snapshot(
+        &self, snapshot_epoch_id: &EpochId, dest_snapshot_dir: &Path,
+    ) -> Result<()>;
+    /// Copy the latest MPT snapshot if there is one, as
+    /// `checkpoint_snapshot`. The caller makes sure that no snapshot is
+    /// being made.
+    fn checkpoint_latest_mpt_snapshot(
+        &self, dest_snapshot_dir: &Path,
+    ) -> Result<()>;
+
+    /// Replace the snapshot of `snapshot_epoch_id` with the one in
+    /// `rebuilt_snapshot_dir`, which has the layout of the snapshot dir, and
+    /// move the replaced snapshot into `rebuilt_snapshot_dir`. Return false
+    /// without any change if the snapshot is open.
+    fn replace_snapshot_if_closed(
+        &self, snapshot_epoch_id: &EpochId, rebuilt_snapshot_dir: &Path,
+    ) -> Result<bool>;
+
     fn new_temp_snapshot_for_full_sync(
         &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
         new_epoch_height: u64,
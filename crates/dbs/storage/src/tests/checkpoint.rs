@@ -0,0 +1,173 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+fn new_checkpoint_dir() -> PathBuf {
+    PathBuf::from(
+        "./conflux_unit_test_checkpoint_".to_string()
+            + &random::<u64>().to_string(),
+    )
+}
+
+#[test]
+fn test_sqlite_backup() {
+    let dir = new_checkpoint_dir();
+    fs::create_dir_all(&dir).unwrap();
+    let statements = Arc::new(
+        KvdbSqliteStatements::make_statements(
+            &["value"],
+            &["BLOB"],
+            "test",
+            false,
+        )
+        .unwrap(),
+    );
+    let (_, db) = KvdbSqlite::<Box<[u8]>>::open_or_create(
+        dir.join("db"),
+        statements.clone(),
+        /* unsafe_mode = */ false,
+    )
+    .unwrap();
+    db.put(b"key", b"value").unwrap();
+
+    db.backup(dir.join("backup")).unwrap();
+    db.put(b"key", b"new value").unwrap();
+
+    let backup =
+        KvdbSqlite::<Box<[u8]>>::open(dir.join("backup"), true, statements)
+            .unwrap();
+    assert_eq!(backup.get(b"key").unwrap(), Some(b"value".to_vec().into()));
+
+    drop(db);
+    drop(backup);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_state_manager_checkpoint() {
+    let state_manager = new_state_manager_for_unit_test();
+    let keys = generate_keys(100);
+    let mut state = state_manager.get_state_for_genesis_write();
+    for key in &keys {
+        state
+            .set(
+                StorageKey::AccountKey(key).with_native_space(),
+                key[..].into(),
+            )
+            .unwrap();
+    }
+    state.compute_state_root().unwrap();
+    state.commit(EpochId::repeat_byte(1)).unwrap();
+
+    let dir = new_checkpoint_dir();
+    let (checkpoint, pinned_snapshots) =
+        state_manager.checkpoint(&dir).unwrap();
+    state_manager
+        .checkpoint_snapshots(pinned_snapshots)
+        .unwrap();
+    assert!(checkpoint.snapshot_epoch_ids.is_empty());
+    assert_eq!(checkpoint.delta_mpt_epoch_ids, vec![NULL_EPOCH]);
+    assert!(dir.join(&*storage_dir::SNAPSHOT_INFO_DB_PATH).exists());
+
+    // The copied delta db has the committed trie.
+    let delta_db_manager =
+        DeltaDbManager::new(dir.join(&*storage_dir::DELTA_MPTS_DIR)).unwrap();
+    let delta_db = delta_db_manager
+        .get_delta_db(&delta_db_manager.get_delta_db_name(&NULL_EPOCH))
+        .unwrap()
+        .unwrap();
+    assert!(delta_db.get(b"last_row_number").unwrap().is_some());
+
+    drop(delta_db);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_checkpoint_pinned_snapshots() {
+    const SNAPSHOT_EPOCH_COUNT: u32 = 1;
+    let state_manager =
+        new_state_manager_for_unit_test_with_snapshot_epoch_count(
+            SNAPSHOT_EPOCH_COUNT,
+        );
+    let keys = generate_keys(400);
+    let mut maybe_parent: Option<(EpochId, StateRootWithAuxInfo)> = None;
+    for (height, keys) in keys.chunks(100).enumerate() {
+        let mut state: Box<dyn StateTrait> = match &maybe_parent {
+            None => state_manager.get_state_for_genesis_write(),
+            Some((parent_epoch_id, parent_state_root)) => Box::new(
+                state_manager
+                    .get_state_for_next_epoch_inner(
+                        StateIndex::new_for_next_epoch(
+                            parent_epoch_id,
+                            parent_state_root,
+                            height as u64,
+                            SNAPSHOT_EPOCH_COUNT,
+                        ),
+                        /* open_mpt_snapshot = */ true,
+                        /* recover_mpt_during_construct_pivot_state = */
+                        false,
+                    )
+                    .unwrap()
+                    .unwrap(),
+            ),
+        };
+        for key in keys {
+            state
+                .set(
+                    StorageKey::AccountKey(key).with_native_space(),
+                    key[..].into(),
+                )
+                .unwrap();
+        }
+        let epoch_id = EpochId::from_low_u64_be(height as u64 + 1);
+        let state_root = state.compute_state_root().unwrap();
+        state.commit(epoch_id).unwrap();
+        maybe_parent = Some((epoch_id, state_root));
+    }
+
+    let dir = new_checkpoint_dir();
+    let (checkpoint, pinned_snapshots) =
+        state_manager.checkpoint(&dir).unwrap();
+    assert!(!checkpoint.snapshot_epoch_ids.is_empty());
+    let snapshot_db_manager = state_manager
+        .get_storage_manager()
+        .get_snapshot_manager()
+        .get_snapshot_db_manager();
+    let dest_snapshot_dir = dir.join(&*storage_dir::SNAPSHOT_DIR);
+    // The snapshots are only copied after the execution is resumed.
+    for snapshot_epoch_id in &checkpoint.snapshot_epoch_ids {
+        assert!(!dest_snapshot_dir
+            .join(snapshot_db_manager.get_snapshot_db_name(snapshot_epoch_id))
+            .exists());
+    }
+    state_manager
+        .checkpoint_snapshots(pinned_snapshots)
+        .unwrap();
+    for snapshot_epoch_id in &checkpoint.snapshot_epoch_ids {
+        assert!(dest_snapshot_dir
+            .join(snapshot_db_manager.get_snapshot_db_name(snapshot_epoch_id))
+            .exists());
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+use crate::{
+    impls::state_manager::DeltaDbManager,
+    snapshot_manager::SnapshotManagerTrait,
+    state::*,
+    state_manager::*,
+    storage_db::{
+        DeltaDbManagerTrait, KeyValueDbTrait, KeyValueDbTraitRead,
+        SnapshotDbManagerTrait,
+    },
+    storage_dir,
+    tests::{
+        generate_keys, new_state_manager_for_unit_test,
+        new_state_manager_for_unit_test_with_snapshot_epoch_count,
+    },
+    KvdbSqlite, KvdbSqliteStatements, StateRootWithAuxInfo,
+};
+use primitives::{EpochId, StorageKey, NULL_EPOCH};
+use rand::random;
+use std::{fs, path::PathBuf, sync::Arc};
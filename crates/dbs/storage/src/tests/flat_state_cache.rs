@@ -0,0 +1,133 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+fn value(byte: u8) -> Option<Box<[u8]>> { Some(vec![byte].into()) }
+
+fn epoch(byte: u8) -> EpochId { EpochId::repeat_byte(byte) }
+
+#[test]
+fn test_flat_state_cache_commit() {
+    let cache = FlatStateCache::new(16);
+
+    // Nothing is cached before the first commit.
+    cache.insert(&epoch(1), b"a".to_vec(), value(1));
+    assert_eq!(cache.get(&epoch(1), b"a"), None);
+
+    cache.commit(&NULL_EPOCH, &epoch(1), FlatStateWrites::new());
+    cache.insert(&epoch(1), b"a".to_vec(), value(1));
+    cache.insert(&epoch(1), b"b".to_vec(), value(2));
+    assert_eq!(cache.get(&epoch(1), b"a"), Some(value(1)));
+
+    let mut writes = FlatStateWrites::new();
+    writes.insert(b"b".to_vec(), None);
+    cache.commit(&epoch(1), &epoch(2), writes);
+    assert_eq!(cache.get(&epoch(1), b"a"), None);
+    assert_eq!(cache.get(&epoch(2), b"a"), Some(value(1)));
+    assert_eq!(cache.get(&epoch(2), b"b"), Some(None));
+
+    // A commit on top of another epoch drops the cached values.
+    let mut writes = FlatStateWrites::new();
+    writes.insert(b"c".to_vec(), value(3));
+    cache.commit(&epoch(3), &epoch(4), writes);
+    assert_eq!(cache.get(&epoch(4), b"a"), None);
+    assert_eq!(cache.get(&epoch(4), b"c"), Some(value(3)));
+}
+
+#[test]
+fn test_flat_state_cache_eviction() {
+    let cache = FlatStateCache::new(4);
+    cache.commit(&NULL_EPOCH, &epoch(1), FlatStateWrites::new());
+    for key in 0..5u8 {
+        cache.insert(&epoch(1), vec![key], value(key));
+    }
+    assert_eq!(cache.get(&epoch(1), &[0]), None);
+    assert_eq!(cache.get(&epoch(1), &[1]), None);
+    assert_eq!(cache.get(&epoch(1), &[2]), Some(value(2)));
+    assert_eq!(cache.get(&epoch(1), &[4]), Some(value(4)));
+}
+
+#[test]
+fn test_state_with_flat_state_cache() {
+    let state_manager = new_state_manager_for_unit_test_with_flat_state_cache(
+        /* flat_state_cache_size = */ 1000,
+    );
+    let keys = generate_keys(3);
+    let key = |i: usize| StorageKey::AccountKey(&keys[i]).with_native_space();
+    let get = |state: &dyn StateTrait, i: usize| state.get(key(i)).unwrap();
+
+    let mut state_0 = state_manager.get_state_for_genesis_write();
+    for i in 0..3 {
+        state_0.set(key(i), value(i as u8).unwrap()).unwrap();
+    }
+    state_0.compute_state_root().unwrap();
+    state_0.commit(epoch(1)).unwrap();
+
+    let mut state_1 = state_manager
+        .get_state_for_next_epoch(
+            StateIndex::new_for_test_only_delta_mpt(&epoch(1)),
+            false,
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(get(&*state_1, 0), value(0));
+    state_1.set(key(1), value(11).unwrap()).unwrap();
+    state_1.delete(key(2)).unwrap();
+    assert_eq!(get(&*state_1, 1), value(11));
+    assert_eq!(get(&*state_1, 2), None);
+    state_1.compute_state_root().unwrap();
+    state_1.commit(epoch(2)).unwrap();
+
+    let state_2 = state_manager
+        .get_state_no_commit(
+            StateIndex::new_for_test_only_delta_mpt(&epoch(2)),
+            /* try_open = */ false,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(get(&*state_2, 0), value(0));
+    assert_eq!(get(&*state_2, 1), value(11));
+    assert_eq!(get(&*state_2, 2), None);
+
+    // Executing another branch on top of epoch 1.
+    let mut state_1_fork = state_manager
+        .get_state_for_next_epoch(
+            StateIndex::new_for_test_only_delta_mpt(&epoch(1)),
+            false,
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(get(&*state_1_fork, 1), value(1));
+    assert_eq!(get(&*state_1_fork, 2), value(2));
+    state_1_fork.set(key(0), value(10).unwrap()).unwrap();
+    state_1_fork.compute_state_root().unwrap();
+    state_1_fork.commit(epoch(3)).unwrap();
+
+    for (epoch_id, values) in &[
+        (epoch(2), [value(0), value(11), None]),
+        (epoch(3), [value(10), value(1), value(2)]),
+    ] {
+        let state = state_manager
+            .get_state_no_commit(
+                StateIndex::new_for_test_only_delta_mpt(epoch_id),
+                /* try_open = */ false,
+                None,
+            )
+            .unwrap()
+            .unwrap();
+        for i in 0..3 {
+            assert_eq!(&get(&*state, i), &values[i]);
+        }
+    }
+}
+
+use crate::{
+    impls::flat_state_cache::{FlatStateCache, FlatStateWrites},
+    state::*,
+    state_manager::*,
+    tests::{
+        generate_keys, new_state_manager_for_unit_test_with_flat_state_cache,
+    },
+};
+use primitives::{EpochId, StorageKey, NULL_EPOCH};
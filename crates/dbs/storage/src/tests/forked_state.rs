@@ -0,0 +1,269 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+struct FakeForkSource {
+    entries: BTreeMap<Vec<u8>, Box<[u8]>>,
+    fetches: Arc<AtomicUsize>,
+}
+
+impl ForkStateSource for FakeForkSource {
+    fn get(
+        &self, access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Box<[u8]>>> {
+        self.fetches.fetch_add(1, Ordering::SeqCst);
+        Ok(self.entries.get(&access_key.to_key_bytes()).cloned())
+    }
+
+    fn get_all(
+        &self, access_key_prefix: StorageKeyWithSpace,
+    ) -> Result<Vec<MptKeyValue>> {
+        let prefix = access_key_prefix.to_key_bytes();
+        Ok(self
+            .entries
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+}
+
+fn new_fork(
+    entries: Vec<(StorageKeyWithSpace, u8)>, max_entries: usize,
+) -> (Arc<ForkStateCache>, Arc<AtomicUsize>) {
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let entries = entries
+        .into_iter()
+        .map(|(key, value)| (key.to_key_bytes(), vec![value].into()))
+        .collect();
+    let fork = Arc::new(ForkStateCache::new(
+        Box::new(FakeForkSource {
+            entries,
+            fetches: fetches.clone(),
+        }),
+        max_entries,
+    ));
+    (fork, fetches)
+}
+
+#[test]
+fn test_forked_state() {
+    let address = Address::from_low_u64_be(1);
+    let slot_1 = [1u8; 32];
+    let key_1 =
+        StorageKey::new_storage_key(&address, &slot_1).with_native_space();
+    let slot_2 = [2u8; 32];
+    let key_2 =
+        StorageKey::new_storage_key(&address, &slot_2).with_native_space();
+    let slot_3 = [3u8; 32];
+    let key_3 =
+        StorageKey::new_storage_key(&address, &slot_3).with_native_space();
+    let slot_4 = [4u8; 32];
+    let key_4 =
+        StorageKey::new_storage_key(&address, &slot_4).with_native_space();
+    let prefix = StorageKey::new_storage_root_key(&address).with_native_space();
+
+    let (fork, fetches) =
+        new_fork(vec![(key_1, 1), (key_2, 2), (key_4, 4)], 100);
+
+    let state_manager = new_state_manager_for_unit_test();
+    let mut state = ForkedState::new(
+        state_manager.get_state_for_genesis_write(),
+        fork.clone(),
+        None,
+        Some(0),
+    );
+
+    // Remote values are fetched once and cached.
+    assert_eq!(state.get(key_1).unwrap().as_deref(), Some(&[1u8][..]));
+    assert_eq!(state.get(key_1).unwrap().as_deref(), Some(&[1u8][..]));
+    assert_eq!(state.get(key_2).unwrap().as_deref(), Some(&[2u8][..]));
+    assert_eq!(state.get(key_3).unwrap(), None);
+    assert_eq!(state.get(key_3).unwrap(), None);
+    assert_eq!(fetches.load(Ordering::SeqCst), 3);
+
+    // Local writes and deletions shadow the remote values, and the remote
+    // keys never fetched are read too.
+    state.set(key_1, vec![11u8].into_boxed_slice()).unwrap();
+    state.delete(key_2).unwrap();
+    assert_eq!(state.get(key_1).unwrap().as_deref(), Some(&[11u8][..]));
+    assert_eq!(state.get(key_2).unwrap(), None);
+    assert_eq!(
+        state.read_all(prefix).unwrap(),
+        Some(vec![
+            (key_1.to_key_bytes(), vec![11u8].into_boxed_slice()),
+            (key_4.to_key_bytes(), vec![4u8].into_boxed_slice()),
+        ])
+    );
+
+    let mut epoch_id = H256::default();
+    epoch_id.as_bytes_mut()[0] = 1;
+    state.compute_state_root().unwrap();
+    state.commit(epoch_id).unwrap();
+
+    // The deletion is not written into the local trie.
+    let local_state = state_manager
+        .get_state_no_commit(
+            StateIndex::new_for_test_only_delta_mpt(&epoch_id),
+            /* try_open = */ false,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(local_state.get(key_2).unwrap(), None);
+
+    // Deletions persist across epochs.
+    let state = ForkedState::new(local_state, fork, Some(&epoch_id), Some(1));
+    assert_eq!(state.get(key_1).unwrap().as_deref(), Some(&[11u8][..]));
+    assert_eq!(state.get(key_2).unwrap(), None);
+    assert_eq!(fetches.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_forked_state_delete_all() {
+    let address = Address::from_low_u64_be(1);
+    let slot_1 = [1u8; 32];
+    let key_1 =
+        StorageKey::new_storage_key(&address, &slot_1).with_native_space();
+    let slot_2 = [2u8; 32];
+    let key_2 =
+        StorageKey::new_storage_key(&address, &slot_2).with_native_space();
+    let slot_3 = [3u8; 32];
+    let key_3 =
+        StorageKey::new_storage_key(&address, &slot_3).with_native_space();
+    let prefix = StorageKey::new_storage_root_key(&address).with_native_space();
+
+    let (fork, _) = new_fork(vec![(key_1, 1), (key_2, 2)], 100);
+    let state_manager = new_state_manager_for_unit_test();
+    let mut state = ForkedState::new(
+        state_manager.get_state_for_genesis_write(),
+        fork,
+        None,
+        Some(0),
+    );
+    state.set(key_3, vec![3u8].into_boxed_slice()).unwrap();
+
+    // The remote keys are deleted without being fetched first.
+    assert_eq!(
+        state.delete_all(prefix).unwrap(),
+        Some(vec![
+            (key_1.to_key_bytes(), vec![1u8].into_boxed_slice()),
+            (key_2.to_key_bytes(), vec![2u8].into_boxed_slice()),
+            (key_3.to_key_bytes(), vec![3u8].into_boxed_slice()),
+        ])
+    );
+    assert_eq!(state.get(key_1).unwrap(), None);
+    assert_eq!(state.get(key_2).unwrap(), None);
+    assert_eq!(state.get(key_3).unwrap(), None);
+    assert_eq!(state.read_all(prefix).unwrap(), None);
+
+    // A deleted key can be written again.
+    state.set(key_1, vec![10u8].into_boxed_slice()).unwrap();
+    assert_eq!(state.get(key_1).unwrap().as_deref(), Some(&[10u8][..]));
+}
+
+#[test]
+fn test_fork_deleted_keys_pruning() {
+    let address = Address::from_low_u64_be(1);
+    let slot_1 = [1u8; 32];
+    let key_1 =
+        StorageKey::new_storage_key(&address, &slot_1).with_native_space();
+    let slot_2 = [2u8; 32];
+    let key_2 =
+        StorageKey::new_storage_key(&address, &slot_2).with_native_space();
+
+    let (fork, _) = new_fork(vec![(key_1, 1), (key_2, 2)], 100);
+    let state_manager = new_state_manager_for_unit_test();
+
+    // Delete a key in each of the epochs at height 0 and 1.
+    let mut state = ForkedState::new(
+        state_manager.get_state_for_genesis_write(),
+        fork.clone(),
+        None,
+        Some(0),
+    );
+    state.delete(key_1).unwrap();
+    let mut epoch_0 = H256::default();
+    epoch_0.as_bytes_mut()[0] = 1;
+    state.compute_state_root().unwrap();
+    state.commit(epoch_0).unwrap();
+
+    let local_state = state_manager
+        .get_state_no_commit(
+            StateIndex::new_for_test_only_delta_mpt(&epoch_0),
+            /* try_open = */ false,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+    let mut state =
+        ForkedState::new(local_state, fork.clone(), Some(&epoch_0), Some(1));
+    state.delete(key_2).unwrap();
+    let mut epoch_1 = H256::default();
+    epoch_1.as_bytes_mut()[0] = 2;
+    state.compute_state_root().unwrap();
+    state.commit(epoch_1).unwrap();
+    assert_eq!(fork.deleted_keys_epochs(), 2);
+
+    // Only the deleted keys of the epochs below the lower bound are dropped.
+    fork.prune_deleted_keys(1);
+    assert_eq!(fork.deleted_keys_epochs(), 1);
+    let local_state = state_manager
+        .get_state_no_commit(
+            StateIndex::new_for_test_only_delta_mpt(&epoch_1),
+            /* try_open = */ false,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+    let state =
+        ForkedState::new(local_state, fork.clone(), Some(&epoch_1), Some(2));
+    assert_eq!(state.get(key_1).unwrap(), None);
+    assert_eq!(state.get(key_2).unwrap(), None);
+
+    fork.prune_deleted_keys(2);
+    assert_eq!(fork.deleted_keys_epochs(), 0);
+}
+
+#[test]
+fn test_fork_state_cache_size() {
+    let address = Address::from_low_u64_be(1);
+    let slot_1 = [1u8; 32];
+    let key_1 =
+        StorageKey::new_storage_key(&address, &slot_1).with_native_space();
+    let slot_2 = [2u8; 32];
+    let key_2 =
+        StorageKey::new_storage_key(&address, &slot_2).with_native_space();
+    let slot_3 = [3u8; 32];
+    let key_3 =
+        StorageKey::new_storage_key(&address, &slot_3).with_native_space();
+
+    let (fork, fetches) = new_fork(vec![(key_1, 1), (key_2, 2)], 2);
+    fork.get(key_1).unwrap();
+    fork.get(key_2).unwrap();
+    fork.get(key_3).unwrap();
+    assert_eq!(fork.cached_entries(), 2);
+    assert_eq!(fetches.load(Ordering::SeqCst), 3);
+
+    // The oldest entry is evicted and fetched again.
+    assert_eq!(fork.get(key_1).unwrap().as_deref(), Some(&[1u8][..]));
+    assert_eq!(fetches.load(Ordering::SeqCst), 4);
+    assert_eq!(fork.get(key_3).unwrap(), None);
+    assert_eq!(fetches.load(Ordering::SeqCst), 4);
+    assert_eq!(fork.cached_entries(), 2);
+}
+
+use crate::{
+    impls::errors::*, state::*, state_manager::*,
+    tests::new_state_manager_for_unit_test, ForkStateCache, ForkStateSource,
+    ForkedState, MptKeyValue,
+};
+use cfx_types::{Address, H256};
+use primitives::{StorageKey, StorageKeyWithSpace};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
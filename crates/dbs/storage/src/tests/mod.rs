@@ -3,13 +3,23 @@
 // See http://www.gnu.org/licenses/
 
 mod snapshot;
-pub use snapshot::FakeSnapshotMptDb;
+pub use snapshot::{snapshot_merkle_root, FakeSnapshotMptDb};
 
+#[cfg(test)]
+mod checkpoint;
+#[cfg(test)]
+mod flat_state_cache;
+#[cfg(test)]
+mod forked_state;
 #[cfg(test)]
 mod proofs;
 #[cfg(test)]
+mod pruning;
+#[cfg(test)]
 mod sharded_iter_merger;
 #[cfg(test)]
+mod snapshot_verification;
+#[cfg(test)]
 mod state;
 
 #[cfg(test)]
@@ -63,12 +73,13 @@ pub struct FakeStateManager {
 impl FakeStateManager {
     fn new(
         conflux_data_dir: String, snapshot_epoch_count: u32,
+        flat_state_cache_size: usize,
+        update_storage_conf: impl FnOnce(&mut StorageConfiguration),
     ) -> Result<Self> {
         // Use a random directory to prevent conflicts in concurrently running
         // tests.
         let unit_test_data_dir =
             conflux_data_dir + &random::<u64>().to_string();
-        fs::create_dir_all(unit_test_data_dir.as_str())?;
         let mut storage_conf = StorageConfiguration::new_default(
             &unit_test_data_dir,
             snapshot_epoch_count,
@@ -78,6 +89,12 @@ impl FakeStateManager {
         storage_conf.delta_mpts_cache_start_size = 1_000_000;
         storage_conf.delta_mpts_node_map_vec_size = 20_000_000;
         storage_conf.delta_mpts_slab_idle_size = 200_000;
+        storage_conf.flat_state_cache_size = flat_state_cache_size;
+        update_storage_conf(&mut storage_conf);
+        // Nothing is written to disk by the in-memory storage.
+        if storage_conf.snapshot_db_backend != SnapshotDbBackend::Inmemory {
+            fs::create_dir_all(unit_test_data_dir.as_str())?;
+        }
 
         Ok(FakeStateManager {
             data_dir: unit_test_data_dir,
@@ -142,6 +159,35 @@ pub fn new_state_manager_for_unit_test_with_snapshot_epoch_count(
     FakeStateManager::new(
         "./conflux_unit_test_data_dir".to_string(),
         snapshot_epoch_count,
+        /* flat_state_cache_size = */ 0,
+        |_| {},
+    )
+    .unwrap()
+}
+
+#[cfg(any(test, feature = "testonly_code"))]
+pub fn new_state_manager_for_unit_test_with_storage_conf(
+    snapshot_epoch_count: u32,
+    update_storage_conf: impl FnOnce(&mut StorageConfiguration),
+) -> FakeStateManager {
+    FakeStateManager::new(
+        "./conflux_unit_test_data_dir".to_string(),
+        snapshot_epoch_count,
+        /* flat_state_cache_size = */ 0,
+        update_storage_conf,
+    )
+    .unwrap()
+}
+
+#[cfg(any(test, feature = "testonly_code"))]
+pub fn new_state_manager_for_unit_test_with_flat_state_cache(
+    flat_state_cache_size: usize,
+) -> FakeStateManager {
+    FakeStateManager::new(
+        "./conflux_unit_test_data_dir".to_string(),
+        /* snapshot_epoch_count = */ 10,
+        flat_state_cache_size,
+        |_| {},
     )
     .unwrap()
 }
@@ -252,7 +298,9 @@ pub fn print_mpt_key(key: &[u8]) {
 }
 
 #[cfg(any(test, feature = "testonly_code"))]
-use crate::{impls::state_manager::StateManager, StorageConfiguration};
+use crate::{
+    impls::state_manager::StateManager, SnapshotDbBackend, StorageConfiguration,
+};
 use crate::{
     impls::{
         errors::*,
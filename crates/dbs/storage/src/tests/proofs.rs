@@ -30,6 +30,20 @@ fn portion<T>(v: &Vec<T>, k: usize, n: usize) -> &[T] {
 fn generate_random_state(
     rng: &mut ChaChaRng,
 ) -> (FakeStateManager, State, DeltaMptKeyPadding, Vec<Vec<u8>>) {
+    let (state_manager, state, prev_root, keys) =
+        generate_random_state_with_prev_root(rng);
+    let intermediate_padding = StorageKeyWithSpace::delta_mpt_padding(
+        &prev_root.snapshot_root,
+        &prev_root.intermediate_delta_root,
+    );
+    (state_manager, state, intermediate_padding, keys)
+}
+
+/// Same as `generate_random_state`, but returns the state root whose delta
+/// MPT is the intermediate delta MPT of the generated state.
+fn generate_random_state_with_prev_root(
+    rng: &mut ChaChaRng,
+) -> (FakeStateManager, State, StateRoot, Vec<Vec<u8>>) {
     let snapshot_epoch_count = 1;
     let state_manager =
         new_state_manager_for_unit_test_with_snapshot_epoch_count(
@@ -201,11 +215,6 @@ fn generate_random_state(
 
     keys.shuffle(rng);
 
-    let intermediate_padding = StorageKeyWithSpace::delta_mpt_padding(
-        &root_2.state_root.snapshot_root,
-        &root_2.state_root.intermediate_delta_root,
-    );
-
     let new_state = state_manager
         .get_state_for_next_epoch_inner(
             StateIndex::new_for_next_epoch(
@@ -220,7 +229,7 @@ fn generate_random_state(
         .unwrap()
         .unwrap();
 
-    (state_manager, new_state, intermediate_padding, keys)
+    (state_manager, new_state, root_2.state_root, keys)
 }
 
 fn select_keys(
@@ -604,6 +613,125 @@ fn test_invalid_node_merkle_proof() {
     }
 }
 
+#[test]
+fn test_verify_state_entry_and_storage_root() {
+    let mut rng = get_rng_for_test();
+
+    // note: do not drop state_manager (_mgr)
+    let (_mgr, state, prev_root, keys) =
+        generate_random_state_with_prev_root(&mut rng);
+    let root = state.get_state_root().unwrap().state_root;
+    let nonexistent_keys = generate_nonexistent_keys(&mut rng, &keys);
+
+    for key in keys.iter().chain(nonexistent_keys.iter()) {
+        let (value, proof) = state
+            .get_with_proof(StorageKey::AccountKey(key).with_native_space())
+            .expect("kv lookup should succeed");
+        let value = value.as_ref().map(|b| &**b);
+        assert!(verify_state_entry(
+            key,
+            value,
+            &proof,
+            &root,
+            Some(&prev_root)
+        ));
+
+        // the proof is bound to the value and the state root
+        let other_value = [1u8];
+        assert!(!verify_state_entry(
+            key,
+            Some(&other_value[..]),
+            &proof,
+            &root,
+            Some(&prev_root)
+        ));
+        assert!(!verify_state_entry(
+            key,
+            value,
+            &proof,
+            &get_invalid_state_root(&mut rng, root.clone(), 1),
+            Some(&prev_root)
+        ));
+    }
+
+    let address = Address::random();
+    let (storage_root, proof) = state
+        .get_original_storage_root_with_proof(&address.with_native_space())
+        .expect("storage root lookup should succeed");
+    assert!(verify_storage_root(
+        &address,
+        &storage_root,
+        &proof,
+        &root,
+        Some(&prev_root)
+    ));
+    assert!(!verify_storage_root(
+        &address,
+        &storage_root,
+        &proof,
+        &get_invalid_state_root(&mut rng, root.clone(), 1),
+        Some(&prev_root)
+    ));
+}
+
+#[test]
+fn test_state_root_witness() {
+    let mut rng = get_rng_for_test();
+    let state_root = StateRoot {
+        snapshot_root: H256::random(),
+        intermediate_delta_root: H256::random(),
+        delta_root: H256::random(),
+    };
+
+    // The header at height 20 blames its 2 parents, so it commits to the
+    // state roots of the epochs 15, 14 and 13.
+    let epoch = 14;
+    let mut state_root_hashes = vec![
+        H256::random(),
+        state_root.compute_state_root_hash(),
+        H256::random(),
+    ];
+    let header = BlockHeaderBuilder::new()
+        .with_height(20)
+        .with_blame(2)
+        .with_deferred_state_root(
+            BlockHeaderBuilder::compute_blame_state_root_vec_root(
+                state_root_hashes.clone(),
+            ),
+        )
+        .build();
+    let witness = StateRootWitness {
+        header: header.clone(),
+        state_root_hashes: state_root_hashes.clone(),
+    };
+    assert!(witness.is_valid(epoch, &state_root));
+    assert_eq!(witness, rlp::decode(&rlp::encode(&witness)).unwrap());
+
+    // wrong epoch or state root
+    assert!(!witness.is_valid(epoch + 1, &state_root));
+    assert!(!witness.is_valid(epoch - 3, &state_root));
+    assert!(!witness.is_valid(
+        epoch,
+        &get_invalid_state_root(&mut rng, state_root.clone(), 3)
+    ));
+
+    // hashes not committed by the header
+    state_root_hashes[0] = get_invalid_hash(&mut rng, state_root_hashes[0]);
+    let witness = StateRootWitness {
+        header: header.clone(),
+        state_root_hashes: state_root_hashes.clone(),
+    };
+    assert!(!witness.is_valid(epoch, &state_root));
+
+    // wrong number of hashes
+    state_root_hashes.pop();
+    let witness = StateRootWitness {
+        header,
+        state_root_hashes,
+    };
+    assert!(!witness.is_valid(epoch, &state_root));
+}
+
 #[test]
 fn test_recording_storage() {
     let mut rng = get_rng_for_test();
@@ -694,6 +822,239 @@ fn test_recording_storage() {
     }
 }
 
+#[test]
+fn test_state_witness() {
+    check_state_witness(/* shift_snapshot = */ true);
+    check_state_witness(/* shift_snapshot = */ false);
+}
+
+fn check_state_witness(shift_snapshot: bool) {
+    let mut rng = get_rng_for_test();
+
+    // note: do not drop state_manager (_mgr)
+    let (mgr, parent, _padding, keys) = generate_random_state(&mut rng);
+    let parent_root = parent.get_state_root().unwrap();
+
+    // the epoch of the state returned by `generate_random_state`
+    let mut parent_epoch_id = H256::default();
+    parent_epoch_id.as_bytes_mut()[0] = 3;
+    // the snapshot is shifted when the delta MPT of the parent has as many
+    // epochs as the snapshot epoch count of the state manager, which is 1
+    let storage = mgr
+        .get_state_for_next_epoch_inner(
+            StateIndex::new_for_next_epoch(
+                &parent_epoch_id,
+                &parent_root,
+                3,
+                if shift_snapshot { 1 } else { 2 },
+            ),
+            true,
+            false,
+        )
+        .unwrap()
+        .unwrap();
+
+    let read_some = select_keys(&mut rng, &keys);
+    let read_none = generate_nonexistent_keys(&mut rng, &keys);
+    let write_some = select_keys(&mut rng, &keys);
+    let execute = |storage: &mut dyn StateTrait| {
+        for key in read_some.iter().chain(read_none.iter()) {
+            storage
+                .get(StorageKey::AccountKey(key).with_native_space())
+                .expect("kv lookup failed");
+        }
+        for key in &write_some {
+            let value: Vec<u8> = key.iter().map(|b| !b).collect();
+            storage
+                .set(
+                    StorageKey::AccountKey(key).with_native_space(),
+                    value.into(),
+                )
+                .expect("Inserting k-v should succeed");
+        }
+        for key in &read_none {
+            storage
+                .delete(StorageKey::AccountKey(key).with_native_space())
+                .expect("Deleting k-v should succeed");
+        }
+    };
+
+    // record the witness of the execution
+    let mut recording = WitnessRecordingStorage::new(Box::new(storage), parent);
+    let recorder = recording.recorder();
+    execute(&mut recording);
+
+    let mut epoch_id = H256::default();
+    epoch_id.as_bytes_mut()[0] = 4;
+    let root = recording.compute_state_root().unwrap();
+    recording.commit(epoch_id).unwrap();
+    drop(recording);
+    if shift_snapshot {
+        assert_eq!(
+            root.state_root.intermediate_delta_root,
+            parent_root.state_root.delta_root
+        );
+    } else {
+        assert_eq!(
+            root.state_root.intermediate_delta_root,
+            parent_root.state_root.intermediate_delta_root
+        );
+    }
+
+    // the state root is recomputed from the witness when it's finished
+    let witness = Arc::try_unwrap(recorder)
+        .ok()
+        .unwrap()
+        .into_inner()
+        .finish(&parent_root, &root)
+        .unwrap();
+
+    // the same execution from the witness results in the same root
+    let mut storage =
+        WitnessStorage::new(witness.clone(), &parent_root, root.clone())
+            .expect("witness is valid");
+    execute(&mut storage);
+    assert_eq!(storage.compute_state_root().unwrap(), root);
+
+    // a different execution is rejected
+    let mut storage =
+        WitnessStorage::new(witness.clone(), &parent_root, root.clone())
+            .unwrap();
+    execute(&mut storage);
+    storage
+        .set(
+            StorageKey::AccountKey(&read_none[0]).with_native_space(),
+            vec![1].into(),
+        )
+        .unwrap();
+    assert!(storage.compute_state_root().is_err());
+
+    // a forged resulting state root is rejected
+    let mut forged_root = root.clone();
+    forged_root.state_root.delta_root = H256::repeat_byte(1);
+    forged_root.aux_info.state_root_hash =
+        forged_root.state_root.compute_state_root_hash();
+    let mut storage =
+        WitnessStorage::new(witness.clone(), &parent_root, forged_root)
+            .unwrap();
+    execute(&mut storage);
+    assert!(storage.compute_state_root().is_err());
+
+    // a witness with a forged read is rejected
+    let mut forged = witness.clone();
+    let entry = forged
+        .reads
+        .iter_mut()
+        .find(|entry| entry.value.is_some())
+        .unwrap();
+    entry.value.as_mut().unwrap()[0] ^= 1;
+    assert!(WitnessStorage::new(forged, &parent_root, root.clone()).is_err());
+
+    // a witness against another root is rejected
+    assert!(WitnessStorage::new(witness, &root, root.clone()).is_err());
+}
+
+#[test]
+fn test_read_range() {
+    let mut rng = get_rng_for_test();
+
+    // note: do not drop state_manager (_mgr)
+    let (mgr, parent, _padding, keys) = generate_random_state(&mut rng);
+    let parent_root = parent.get_state_root().unwrap();
+
+    // the epoch of the state returned by `generate_random_state`
+    let mut parent_epoch_id = H256::default();
+    parent_epoch_id.as_bytes_mut()[0] = 3;
+    let mut state = mgr
+        .get_state_for_next_epoch_inner(
+            StateIndex::new_for_next_epoch(
+                &parent_epoch_id,
+                &parent_root,
+                3,
+                1,
+            ),
+            true,
+            false,
+        )
+        .unwrap()
+        .unwrap();
+
+    // delete and update some keys in the delta trie to shadow the older tries
+    let mut expected: BTreeMap<Vec<u8>, Box<[u8]>> = keys
+        .iter()
+        .map(|key| (key.clone(), key[..].into()))
+        .collect();
+    for key in select_keys(&mut rng, &keys) {
+        state
+            .delete(StorageKey::AccountKey(&key).with_native_space())
+            .expect("Deleting k-v should succeed");
+        expected.remove(&key);
+    }
+    for key in select_keys(&mut rng, &keys) {
+        let value: Box<[u8]> = key.iter().map(|b| !b).collect();
+        state
+            .set(
+                StorageKey::AccountKey(&key).with_native_space(),
+                value.clone(),
+            )
+            .expect("Inserting k-v should succeed");
+        expected.insert(key, value);
+    }
+    let expected: Vec<_> = expected.into_iter().collect();
+
+    let successor = |key: &[u8]| [key, &[0]].concat();
+
+    // the key-values of the tries are read again when the read goes past the
+    // ones kept for a page
+    for page_size in [1, 7, expected.len() + 1] {
+        let mut kvs = vec![];
+        state
+            .read_range(None, &[], page_size, |key, value| {
+                kvs.push((key.to_vec(), value.into()));
+                Some(successor(key))
+            })
+            .unwrap();
+        assert_eq!(kvs, expected);
+    }
+
+    // read in pages, continuing from the first key not read
+    let mut paged = vec![];
+    let mut start = vec![];
+    loop {
+        let mut next = None;
+        let mut page_len = 0;
+        state
+            .read_range(None, &start, 11, |key, value| {
+                if page_len == 10 {
+                    next = Some(key.to_vec());
+                    return None;
+                }
+                page_len += 1;
+                paged.push((key.to_vec(), value.into()));
+                Some(successor(key))
+            })
+            .unwrap();
+        match next {
+            Some(key) => start = key,
+            None => break,
+        }
+    }
+    assert_eq!(paged, expected);
+
+    // skip over the keys starting with the same byte
+    let mut first_bytes = vec![];
+    state
+        .read_range(None, &[], 4, |key, _| {
+            first_bytes.push(key[0]);
+            Some(vec![key[0].checked_add(1)?])
+        })
+        .unwrap();
+    let mut expected_first_bytes: Vec<_> =
+        expected.iter().map(|(key, _)| key[0]).collect();
+    expected_first_bytes.dedup();
+    assert_eq!(first_bytes, expected_first_bytes);
+}
+
 use crate::{
     state::*,
     state_manager::*,
@@ -702,12 +1063,14 @@ use crate::{
         new_state_manager_for_unit_test_with_snapshot_epoch_count,
         FakeStateManager, TEST_NUMBER_OF_KEYS,
     },
-    RecordingStorage,
+    verify_state_entry, verify_storage_root, RecordingStorage,
+    StateRootWitness, WitnessRecordingStorage, WitnessStorage,
 };
-use cfx_types::H256;
+use cfx_types::{Address, AddressSpaceUtil, H256};
 use primitives::{
-    DeltaMptKeyPadding, MptValue, NodeMerkleTriplet, StateRoot, StorageKey,
-    StorageKeyWithSpace,
+    BlockHeaderBuilder, DeltaMptKeyPadding, MptValue, NodeMerkleTriplet,
+    StateRoot, StorageKey, StorageKeyWithSpace,
 };
 use rand::{seq::SliceRandom, Rng};
 use rand_chacha::ChaChaRng;
+use std::{collections::BTreeMap, sync::Arc};
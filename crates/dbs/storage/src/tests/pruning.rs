@@ -0,0 +1,210 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+const SNAPSHOT_EPOCH_COUNT: u32 = 2000;
+
+fn storage_conf(
+    pruning_mode: PruningMode, additional_maintained_snapshot_count: u32,
+) -> StorageConfiguration {
+    let mut conf =
+        StorageConfiguration::new_default("", SNAPSHOT_EPOCH_COUNT, 20000);
+    conf.pruning_mode = pruning_mode;
+    conf.additional_maintained_snapshot_count =
+        additional_maintained_snapshot_count;
+    conf
+}
+
+#[test]
+fn test_pruning_mode_from_str() {
+    assert_eq!(PruningMode::from_str("archive"), Ok(PruningMode::Archive));
+    assert_eq!(PruningMode::from_str("full"), Ok(PruningMode::Full));
+    assert_eq!(
+        PruningMode::from_str("pruned:100"),
+        Ok(PruningMode::Pruned(100))
+    );
+    assert!(PruningMode::from_str("pruned:0").is_err());
+    assert!(PruningMode::from_str("pruned:").is_err());
+    assert!(PruningMode::from_str("light").is_err());
+
+    for mode in [
+        PruningMode::Archive,
+        PruningMode::Full,
+        PruningMode::Pruned(7),
+    ] {
+        assert_eq!(PruningMode::from_str(&mode.to_string()), Ok(mode));
+    }
+}
+
+#[test]
+fn test_default_pruning_mode() {
+    assert_eq!(PruningMode::default(), PruningMode::Full);
+    assert_eq!(
+        StorageConfiguration::new_default("", SNAPSHOT_EPOCH_COUNT, 20000)
+            .pruning_mode,
+        PruningMode::default()
+    );
+}
+
+#[test]
+fn test_archive_retention() {
+    assert_eq!(PruningMode::Archive.block_data_defer_epochs(), None);
+    for snapshot_count in [0, 1, 5] {
+        // The snapshots are maintained from the same height as `full`, but
+        // the old pivot snapshots are never removed.
+        let conf = storage_conf(PruningMode::Archive, snapshot_count);
+        let full_conf = storage_conf(PruningMode::Full, snapshot_count);
+        for (confirmed_height, stable_checkpoint_height) in
+            [(0, 0), (1_000_000, 900_000), (u64::MAX, u64::MAX)]
+        {
+            assert_eq!(
+                conf.maintained_state_height_lower_bound(
+                    confirmed_height,
+                    stable_checkpoint_height
+                ),
+                full_conf.maintained_state_height_lower_bound(
+                    confirmed_height,
+                    stable_checkpoint_height
+                ),
+            );
+        }
+    }
+}
+
+#[test]
+fn test_archive_removes_non_pivot_snapshots() {
+    const SNAPSHOT_EPOCH_COUNT: u32 = 1;
+    const PIVOT_EPOCH_COUNT: u64 = 8;
+    const FORK_HEIGHT: u64 = 3;
+    const KEYS_PER_EPOCH: usize = 10;
+
+    let state_manager = new_state_manager_for_unit_test_with_storage_conf(
+        SNAPSHOT_EPOCH_COUNT,
+        |storage_conf| {
+            storage_conf.snapshot_db_backend = SnapshotDbBackend::Inmemory;
+            storage_conf.pruning_mode = PruningMode::Archive;
+        },
+    );
+    let keys =
+        generate_account_keys(2 * PIVOT_EPOCH_COUNT as usize * KEYS_PER_EPOCH);
+    let mut keys = keys.chunks(KEYS_PER_EPOCH);
+    let mut commit_epoch =
+        |parent: Option<&(EpochId, StateRootWithAuxInfo)>,
+         height: u64,
+         epoch_id: EpochId| {
+            let mut state: Box<dyn StateTrait> = match parent {
+                None => state_manager.get_state_for_genesis_write(),
+                Some((parent_epoch_id, parent_state_root)) => Box::new(
+                    state_manager
+                        .get_state_for_next_epoch_inner(
+                            StateIndex::new_for_next_epoch(
+                                parent_epoch_id,
+                                parent_state_root,
+                                height,
+                                SNAPSHOT_EPOCH_COUNT,
+                            ),
+                            /* open_mpt_snapshot = */ true,
+                            /* recover_mpt_during_construct_pivot_state = */
+                            false,
+                        )
+                        .unwrap()
+                        .unwrap(),
+                ),
+            };
+            for key in keys.next().unwrap() {
+                state
+                    .set(
+                        StorageKey::AccountKey(key).with_native_space(),
+                        key[..].into(),
+                    )
+                    .unwrap();
+            }
+            let state_root = state.compute_state_root().unwrap();
+            state.commit(epoch_id).unwrap();
+            wait_for_snapshotting(&state_manager);
+            (epoch_id, state_root)
+        };
+
+    let mut consensus = FakeConsensus::default();
+    let mut pivot_epochs: Vec<(EpochId, StateRootWithAuxInfo)> = vec![];
+    for height in 0..PIVOT_EPOCH_COUNT {
+        let epoch = commit_epoch(
+            pivot_epochs.last(),
+            height,
+            EpochId::from_low_u64_be(height + 1),
+        );
+        consensus.pivot_chain.push(epoch.0);
+        consensus.commitments.insert(
+            epoch.0,
+            EpochExecutionCommitment {
+                state_root_with_aux_info: epoch.1.clone(),
+                receipts_root: Default::default(),
+                logs_bloom_hash: Default::default(),
+            },
+        );
+        pivot_epochs.push(epoch);
+    }
+    // A fork from the pivot chain, long enough to be snapshotted.
+    let mut fork_epoch_ids = vec![];
+    let mut fork_parent = pivot_epochs[FORK_HEIGHT as usize].clone();
+    for height in FORK_HEIGHT + 1..PIVOT_EPOCH_COUNT {
+        fork_parent = commit_epoch(
+            Some(&fork_parent),
+            height,
+            EpochId::from_low_u64_be(height + 1 + PIVOT_EPOCH_COUNT),
+        );
+        fork_epoch_ids.push(fork_parent.0);
+    }
+
+    let storage_manager = state_manager.get_storage_manager();
+    let has_snapshot = |epoch_id: &EpochId| {
+        storage_manager
+            .get_snapshot_info_at_epoch(epoch_id)
+            .is_some()
+    };
+    let pivot_snapshots: Vec<EpochId> = consensus
+        .pivot_chain
+        .iter()
+        .filter(|epoch_id| has_snapshot(epoch_id))
+        .cloned()
+        .collect();
+    assert!(!pivot_snapshots.is_empty());
+    assert!(fork_epoch_ids.iter().any(has_snapshot));
+
+    let mut state_availability_boundary =
+        StateAvailabilityBoundary::new(consensus.pivot_chain[0], 0, None, None);
+    state_availability_boundary.pivot_chain = consensus.pivot_chain.clone();
+    state_availability_boundary.upper_bound = PIVOT_EPOCH_COUNT - 1;
+    let state_availability_boundary = RwLock::new(state_availability_boundary);
+    storage_manager
+        .maintain_state_confirmed(
+            &consensus,
+            /* stable_checkpoint_height = */ 0,
+            /* era_epoch_count = */ 50000,
+            /* confirmed_height = */ PIVOT_EPOCH_COUNT - 1,
+            &state_availability_boundary,
+        )
+        .unwrap();
+
+    // The fork snapshots are removed, while all the pivot snapshots and the
+    // state of all the pivot epochs are kept.
+    assert!(!fork_epoch_ids.iter().any(has_snapshot));
+    assert!(pivot_snapshots.iter().all(has_snapshot));
+    assert_eq!(state_availability_boundary.read().lower_bound, 0);
+}
+
+use crate::{
+    state::StateTrait,
+    state_manager::{StateIndex, StateManagerTrait},
+    tests::{
+        generate_account_keys,
+        new_state_manager_for_unit_test_with_storage_conf,
+        snapshot_verification::{wait_for_snapshotting, FakeConsensus},
+    },
+    PruningMode, SnapshotDbBackend, StateRootWithAuxInfo, StorageConfiguration,
+};
+use cfx_internal_common::{
+    EpochExecutionCommitment, StateAvailabilityBoundary,
+};
+use parking_lot::RwLock;
+use primitives::{EpochId, StorageKey};
@@ -2,6 +2,12 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
+#[cfg(test)]
+mod backends;
+#[cfg(test)]
+mod common;
+#[cfg(test)]
+mod inmemory;
 #[cfg(test)]
 mod slicer;
 #[cfg(test)]
@@ -26,7 +32,6 @@ impl FakeSnapshotMptDb {
         }
     }
 
-    #[cfg(test)]
     fn reset(&mut self, in_place_mode: bool) {
         self.in_place_mode = in_place_mode;
         self.already_written.clear();
@@ -162,6 +167,14 @@ impl FallibleIterator for FakeSnapshotMptDbIter<'_> {
     }
 }
 
+/// Computes the snapshot merkle root of the sorted key-values, as the full
+/// sync verifier does for a complete snapshot.
+pub fn snapshot_merkle_root(kvs: &DumpedMptKvIterator) -> Result<MerkleHash> {
+    let mut snapshot_mpt = FakeSnapshotMptDb::default();
+    snapshot_mpt.reset(/* in_place_mode */ true);
+    MptMerger::new(None, &mut snapshot_mpt).merge(kvs)
+}
+
 #[cfg(test)]
 fn assert_snapshot_mpt_formation(mpt_kv_iter: &DumpedMptKvIterator) {
     let snapshot_mpt_nodes;
@@ -624,7 +637,9 @@ fn test_delta_subtree_size() {
 use crate::{
     impls::{
         errors::*,
-        merkle_patricia_trie::{CompressedPathRaw, CompressedPathTrait},
+        merkle_patricia_trie::{
+            CompressedPathRaw, CompressedPathTrait, MptMerger,
+        },
         storage_db::snapshot_mpt::{
             mpt_node_path_from_db_key, mpt_node_path_to_db_key,
         },
@@ -634,6 +649,7 @@ use crate::{
         SnapshotMptNode, SnapshotMptTraitRead, SnapshotMptTraitReadAndIterate,
         SnapshotMptTraitRw,
     },
+    tests::DumpedMptKvIterator,
 };
 use fallible_iterator::FallibleIterator;
 use primitives::MerkleHash;
@@ -644,13 +660,12 @@ use std::{
 
 #[cfg(test)]
 use crate::{
-    impls::merkle_patricia_trie::{MptMerger, TrieNodeTrait},
+    impls::merkle_patricia_trie::TrieNodeTrait,
     impls::storage_db::snapshot_mpt::tests::verify_snapshot_db,
     state_manager::StateManagerTrait,
     tests::{
         generate_keys, get_rng_for_test, new_state_manager_for_unit_test,
-        snapshot::verifier::FakeSnapshotDb, DumpedMptKvIterator,
-        TEST_NUMBER_OF_KEYS,
+        snapshot::verifier::FakeSnapshotDb, TEST_NUMBER_OF_KEYS,
     },
     StateIndex, StorageStateTraitExt,
 };
@@ -0,0 +1,153 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+const SNAPSHOT_DB_BACKENDS: [SnapshotDbBackend; 2] =
+    [SnapshotDbBackend::Sqlite, SnapshotDbBackend::Rocksdb];
+
+fn new_snapshot_db_manager(
+    backend: SnapshotDbBackend, snapshot_path: PathBuf,
+) -> SnapshotDbManagerDispatch {
+    SnapshotDbManagerDispatch::new(
+        backend,
+        snapshot_path,
+        DEFAULT_MAX_OPEN_SNAPSHOTS,
+        /* use_isolated_db_for_mpt_table = */ false,
+        /* use_isolated_db_for_mpt_table_height = */ None,
+        /* era_epoch_count = */ 50000,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_full_sync_snapshot() {
+    for backend in SNAPSHOT_DB_BACKENDS {
+        let unit_test_data_dir = UnitTestDataDir::new();
+        let snapshot_db_manager = new_snapshot_db_manager(
+            backend,
+            unit_test_data_dir.0.join("snapshot"),
+        );
+        let snapshot_info_map = RwLock::new(
+            PersistedSnapshotInfoMap::open_or_create_for_testing(
+                &unit_test_data_dir.0.join("snapshot_info_db"),
+            )
+            .unwrap(),
+        );
+
+        let mpt_kv_iter = generate_mpt_kv_iter();
+        let snapshot_epoch_id = EpochId::repeat_byte(1);
+        let merkle_root = restore_full_sync_snapshot(
+            &snapshot_db_manager,
+            &snapshot_info_map,
+            &snapshot_epoch_id,
+            &mpt_kv_iter,
+        );
+
+        let snapshot_db = snapshot_db_manager
+            .get_snapshot_by_epoch_id(
+                &snapshot_epoch_id,
+                /* try_open = */ false,
+                /* open_mpt_snapshot = */ true,
+            )
+            .unwrap()
+            .unwrap();
+        check_snapshot(&snapshot_db, &mpt_kv_iter, &merkle_root);
+
+        // The key-values are iterated in order.
+        let mut kv_iter = snapshot_db.snapshot_kv_iterator().unwrap().take();
+        let kvs: Vec<MptKeyValue> = kv_iter
+            .iter_range(&[], None)
+            .unwrap()
+            .take()
+            .collect()
+            .unwrap();
+        assert_eq!(kvs, mpt_kv_iter.kv, "backend {}", backend);
+    }
+}
+
+#[test]
+fn test_snapshot_checkpoint() {
+    for backend in SNAPSHOT_DB_BACKENDS {
+        let unit_test_data_dir = UnitTestDataDir::new();
+        let snapshot_db_manager = new_snapshot_db_manager(
+            backend,
+            unit_test_data_dir.0.join("snapshot"),
+        );
+        let snapshot_info_map = RwLock::new(
+            PersistedSnapshotInfoMap::open_or_create_for_testing(
+                &unit_test_data_dir.0.join("snapshot_info_db"),
+            )
+            .unwrap(),
+        );
+
+        let mpt_kv_iter = generate_mpt_kv_iter();
+        let snapshot_epoch_id = EpochId::repeat_byte(1);
+        let merkle_root = restore_full_sync_snapshot(
+            &snapshot_db_manager,
+            &snapshot_info_map,
+            &snapshot_epoch_id,
+            &mpt_kv_iter,
+        );
+
+        // The snapshot is checkpointed while it's open.
+        let snapshot_db = snapshot_db_manager
+            .get_snapshot_by_epoch_id(
+                &snapshot_epoch_id,
+                /* try_open = */ false,
+                /* open_mpt_snapshot = */ true,
+            )
+            .unwrap()
+            .unwrap();
+        let checkpoint_path = unit_test_data_dir.0.join("checkpoint");
+        snapshot_db_manager
+            .checkpoint_snapshot(&snapshot_epoch_id, &checkpoint_path)
+            .unwrap();
+
+        let checkpoint_manager =
+            new_snapshot_db_manager(backend, checkpoint_path);
+        let checkpoint = checkpoint_manager
+            .get_snapshot_by_epoch_id(
+                &snapshot_epoch_id,
+                /* try_open = */ false,
+                /* open_mpt_snapshot = */ true,
+            )
+            .unwrap()
+            .unwrap();
+        check_snapshot(&checkpoint, &mpt_kv_iter, &merkle_root);
+
+        // Removing the checkpoint leaves the original snapshot intact.
+        drop(checkpoint);
+        checkpoint_manager
+            .destroy_snapshot(&snapshot_epoch_id)
+            .unwrap();
+        assert!(checkpoint_manager
+            .get_snapshot_by_epoch_id(
+                &snapshot_epoch_id,
+                /* try_open = */ false,
+                /* open_mpt_snapshot = */ true,
+            )
+            .unwrap()
+            .is_none());
+        check_snapshot(&snapshot_db, &mpt_kv_iter, &merkle_root);
+    }
+}
+
+use crate::{
+    defaults::DEFAULT_MAX_OPEN_SNAPSHOTS,
+    impls::{
+        storage_db::snapshot_db_manager_dispatch::SnapshotDbManagerDispatch,
+        storage_manager::PersistedSnapshotInfoMap,
+    },
+    storage_db::{
+        KeyValueDbIterableTrait, SnapshotDbManagerTrait, SnapshotDbTrait,
+    },
+    tests::snapshot::common::{
+        check_snapshot, generate_mpt_kv_iter, restore_full_sync_snapshot,
+        UnitTestDataDir,
+    },
+    MptKeyValue, SnapshotDbBackend,
+};
+use fallible_iterator::FallibleIterator;
+use parking_lot::RwLock;
+use primitives::EpochId;
+use std::path::PathBuf;
@@ -0,0 +1,141 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+pub(super) struct UnitTestDataDir(pub(super) PathBuf);
+
+impl UnitTestDataDir {
+    pub(super) fn new() -> Self {
+        // Use a random directory to prevent conflicts in concurrently running
+        // tests.
+        let dir = PathBuf::from(
+            "./conflux_unit_test_data_dir".to_string()
+                + &random::<u64>().to_string(),
+        );
+        fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Drop for UnitTestDataDir {
+    fn drop(&mut self) { fs::remove_dir_all(&self.0).ok(); }
+}
+
+pub(super) fn generate_mpt_kv_iter() -> DumpedMptKvIterator {
+    let mut rng = get_rng_for_test();
+    let mut keys: Vec<Vec<u8>> = generate_keys(TEST_NUMBER_OF_KEYS)
+        .iter()
+        .filter(|_| rng.gen_bool(0.5))
+        .cloned()
+        .collect();
+    keys.sort();
+    DumpedMptKvIterator {
+        kv: keys
+            .iter()
+            .map(|k| {
+                (
+                    k[..].into(),
+                    [&k[..], &k[..], &k[..], &k[..]].concat()
+                        [0..(6 + rng.gen::<usize>() % 10)]
+                        .into(),
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Restore the key-values as a full sync snapshot of `snapshot_epoch_id` and
+/// return the merkle root.
+pub(super) fn restore_full_sync_snapshot<
+    SnapshotDbManager: SnapshotDbManagerTrait,
+>(
+    snapshot_db_manager: &SnapshotDbManager,
+    snapshot_info_map: &RwLock<PersistedSnapshotInfoMap>,
+    snapshot_epoch_id: &EpochId, mpt_kv_iter: &DumpedMptKvIterator,
+) -> MerkleHash {
+    let mut snapshot_mpt = FakeSnapshotMptDb::default();
+    let merkle_root = MptMerger::new(None, &mut snapshot_mpt)
+        .merge(mpt_kv_iter)
+        .unwrap();
+
+    let mut full_sync_verifier = FullSyncVerifier::new(
+        1,
+        vec![],
+        vec![],
+        merkle_root,
+        snapshot_db_manager,
+        snapshot_epoch_id,
+        0,
+    )
+    .unwrap();
+    assert!(full_sync_verifier
+        .restore_chunk(
+            &None,
+            &mpt_kv_iter.kv.iter().map(|kv| kv.0.clone()).collect(),
+            mpt_kv_iter.kv.iter().map(|kv| kv.1.to_vec()).collect(),
+        )
+        .unwrap());
+    full_sync_verifier.finalize().unwrap();
+    // Close the temporary snapshot before renaming it.
+    drop(full_sync_verifier);
+
+    drop(
+        snapshot_db_manager
+            .finalize_full_sync_snapshot(
+                snapshot_epoch_id,
+                &merkle_root,
+                snapshot_info_map,
+            )
+            .unwrap(),
+    );
+
+    merkle_root
+}
+
+pub(super) fn check_snapshot<
+    SnapshotDb: SnapshotDbTrait<ValueType = Box<[u8]>>,
+>(
+    snapshot_db: &SnapshotDb, mpt_kv_iter: &DumpedMptKvIterator,
+    merkle_root: &MerkleHash,
+) {
+    for (key, value) in &mpt_kv_iter.kv {
+        assert_eq!(snapshot_db.get(key).unwrap().as_ref(), Some(value));
+    }
+    assert_eq!(
+        snapshot_db
+            .open_snapshot_mpt_shared()
+            .unwrap()
+            .get_merkle_root(),
+        *merkle_root
+    );
+    assert_eq!(
+        check_key_value_load(
+            snapshot_db,
+            fallible_iterator::convert(mpt_kv_iter.kv.iter().cloned().map(Ok)),
+            /* check_value = */ true,
+        )
+        .unwrap(),
+        mpt_kv_iter.kv.len() as u64
+    );
+}
+
+use crate::{
+    impls::{
+        merkle_patricia_trie::MptMerger,
+        snapshot_sync::restoration::full_sync_verifier::FullSyncVerifier,
+        storage_db::snapshot_kv_db_sqlite::test_lib::check_key_value_load,
+        storage_manager::PersistedSnapshotInfoMap,
+    },
+    storage_db::{
+        KeyValueDbTraitRead, OpenSnapshotMptTrait, SnapshotDbManagerTrait,
+        SnapshotDbTrait, SnapshotMptTraitRead,
+    },
+    tests::{
+        generate_keys, get_rng_for_test, snapshot::FakeSnapshotMptDb,
+        DumpedMptKvIterator, TEST_NUMBER_OF_KEYS,
+    },
+};
+use parking_lot::RwLock;
+use primitives::{EpochId, MerkleHash};
+use rand::{random, Rng};
+use std::{fs, path::PathBuf};
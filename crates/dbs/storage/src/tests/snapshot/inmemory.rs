@@ -0,0 +1,234 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+#[test]
+fn test_kvdb_inmemory_transaction_and_iteration() {
+    let kvdb = KvdbInmemory::default();
+    kvdb.put(b"a", b"1").unwrap();
+    kvdb.put(b"c", b"3").unwrap();
+
+    let mut transaction = kvdb.start_transaction(false).unwrap();
+    transaction.put(b"b", b"2").unwrap();
+    transaction.delete(b"c").unwrap();
+    // Nothing is written before commit.
+    assert_eq!(kvdb.get(b"b").unwrap(), None);
+    assert!(kvdb.get(b"c").unwrap().is_some());
+    transaction.commit(kvdb.as_any()).unwrap();
+    assert_eq!(kvdb.get(b"b").unwrap().as_deref(), Some(&b"2"[..]));
+    assert_eq!(kvdb.get(b"c").unwrap(), None);
+
+    // A deep copy doesn't share the writes.
+    let copy = kvdb.deep_clone();
+    copy.put(b"d", b"4").unwrap();
+    assert_eq!(kvdb.get(b"d").unwrap(), None);
+
+    let kvs: Vec<MptKeyValue> = Some(copy.clone())
+        .iter_range(b"b", None)
+        .unwrap()
+        .take()
+        .collect()
+        .unwrap();
+    let expected: Vec<MptKeyValue> = vec![
+        (b"b".to_vec(), b"2"[..].into()),
+        (b"d".to_vec(), b"4"[..].into()),
+    ];
+    assert_eq!(kvs, expected);
+    let kvs: Vec<MptKeyValue> = Some(copy)
+        .iter_range_excl(b"a", b"d")
+        .unwrap()
+        .take()
+        .collect()
+        .unwrap();
+    assert_eq!(kvs, expected[0..1]);
+}
+
+#[test]
+fn test_snapshot_db_backend_from_str() {
+    for backend in [
+        SnapshotDbBackend::Sqlite,
+        SnapshotDbBackend::Rocksdb,
+        SnapshotDbBackend::Inmemory,
+    ] {
+        assert_eq!(
+            SnapshotDbBackend::from_str(&backend.to_string()),
+            Ok(backend)
+        );
+    }
+    assert!(SnapshotDbBackend::from_str("memory").is_err());
+}
+
+#[test]
+fn test_inmemory_full_sync_snapshot() {
+    // The path only names the snapshots.
+    let snapshot_path = PathBuf::from("./inmemory_snapshot");
+    let snapshot_db_manager = SnapshotDbManagerInmemory::new(
+        snapshot_path.clone(),
+        DEFAULT_MAX_OPEN_SNAPSHOTS,
+        /* use_isolated_db_for_mpt_table = */ false,
+        /* use_isolated_db_for_mpt_table_height = */ None,
+        /* era_epoch_count = */ 50000,
+    )
+    .unwrap();
+    let snapshot_info_map =
+        RwLock::new(PersistedSnapshotInfoMap::new_inmemory());
+
+    let mpt_kv_iter = generate_mpt_kv_iter();
+    let snapshot_epoch_id = EpochId::repeat_byte(1);
+    let merkle_root = restore_full_sync_snapshot(
+        &snapshot_db_manager,
+        &snapshot_info_map,
+        &snapshot_epoch_id,
+        &mpt_kv_iter,
+    );
+    assert!(!snapshot_path.exists());
+
+    let snapshot_db = snapshot_db_manager
+        .get_snapshot_by_epoch_id(
+            &snapshot_epoch_id,
+            /* try_open = */ false,
+            /* open_mpt_snapshot = */ true,
+        )
+        .unwrap()
+        .unwrap();
+    check_snapshot(&snapshot_db, &mpt_kv_iter, &merkle_root);
+
+    // The key-values are iterated in order.
+    let mut kv_iter = snapshot_db.snapshot_kv_iterator().unwrap().take();
+    let kvs: Vec<MptKeyValue> = kv_iter
+        .iter_range(&[], None)
+        .unwrap()
+        .take()
+        .collect()
+        .unwrap();
+    assert_eq!(kvs, mpt_kv_iter.kv);
+
+    // The open snapshot remains readable after it's destroyed.
+    snapshot_db_manager
+        .destroy_snapshot(&snapshot_epoch_id)
+        .unwrap();
+    assert!(snapshot_db_manager
+        .get_snapshot_by_epoch_id(
+            &snapshot_epoch_id,
+            /* try_open = */ false,
+            /* open_mpt_snapshot = */ true,
+        )
+        .unwrap()
+        .is_none());
+    check_snapshot(&snapshot_db, &mpt_kv_iter, &merkle_root);
+}
+
+#[test]
+fn test_inmemory_state_manager_commit() {
+    const SNAPSHOT_EPOCH_COUNT: u32 = 2;
+    const EPOCH_COUNT: u64 = 6;
+    const KEYS_PER_EPOCH: usize = 100;
+
+    let state_manager = new_state_manager_for_unit_test_with_storage_conf(
+        SNAPSHOT_EPOCH_COUNT,
+        |storage_conf| {
+            storage_conf.snapshot_db_backend = SnapshotDbBackend::Inmemory
+        },
+    );
+    let keys = generate_account_keys(EPOCH_COUNT as usize * KEYS_PER_EPOCH);
+    let mut maybe_parent: Option<(EpochId, StateRootWithAuxInfo)> = None;
+    for height in 0..EPOCH_COUNT {
+        let mut state: Box<dyn StateTrait> = match &maybe_parent {
+            None => state_manager.get_state_for_genesis_write(),
+            Some((parent_epoch_id, parent_state_root)) => Box::new(
+                state_manager
+                    .get_state_for_next_epoch_inner(
+                        StateIndex::new_for_next_epoch(
+                            parent_epoch_id,
+                            parent_state_root,
+                            height,
+                            SNAPSHOT_EPOCH_COUNT,
+                        ),
+                        /* open_mpt_snapshot = */ true,
+                        /* recover_mpt_during_construct_pivot_state = */
+                        false,
+                    )
+                    .unwrap()
+                    .unwrap(),
+            ),
+        };
+        for key in keys.chunks(KEYS_PER_EPOCH).nth(height as usize).unwrap() {
+            state
+                .set(
+                    StorageKey::AccountKey(key).with_native_space(),
+                    key[..].into(),
+                )
+                .unwrap();
+        }
+        let epoch_id = EpochId::from_low_u64_be(height + 1);
+        state.compute_state_root().unwrap();
+        let state_root = state.commit(epoch_id).unwrap();
+        while !state_manager
+            .get_storage_manager()
+            .in_progress_snapshotting_tasks
+            .read()
+            .is_empty()
+        {
+            thread::sleep(Duration::from_millis(10));
+        }
+        maybe_parent = Some((epoch_id, state_root));
+    }
+
+    // The keys of all epochs are read back through the in-memory snapshots
+    // and delta MPTs.
+    let (epoch_id, state_root) = maybe_parent.unwrap();
+    assert_ne!(state_root.aux_info.snapshot_epoch_id, NULL_EPOCH);
+    let state = state_manager
+        .get_state_no_commit_inner(
+            StateIndex::new_for_readonly(&epoch_id, &state_root),
+            /* try_open = */ false,
+            /* open_mpt_snapshot = */ true,
+        )
+        .unwrap()
+        .unwrap();
+    for key in &keys {
+        assert_eq!(
+            state
+                .get(StorageKey::AccountKey(key).with_native_space())
+                .unwrap()
+                .as_deref(),
+            Some(&key[..])
+        );
+    }
+    // Nothing is written to disk.
+    assert!(!Path::new(&state_manager.data_dir).exists());
+}
+
+use crate::{
+    defaults::DEFAULT_MAX_OPEN_SNAPSHOTS,
+    impls::{
+        storage_db::{
+            kvdb_inmemory::KvdbInmemory,
+            snapshot_db_manager_inmemory::SnapshotDbManagerInmemory,
+        },
+        storage_manager::PersistedSnapshotInfoMap,
+    },
+    state::StateTrait,
+    state_manager::{StateIndex, StateManagerTrait},
+    storage_db::{
+        KeyValueDbAsAnyTrait, KeyValueDbIterableTrait, KeyValueDbTraitRead,
+        KeyValueDbTraitSingleWriter, KeyValueDbTraitTransactional,
+        KeyValueDbTransactionTrait, SnapshotDbManagerTrait, SnapshotDbTrait,
+    },
+    tests::{
+        generate_account_keys,
+        new_state_manager_for_unit_test_with_storage_conf,
+        snapshot::common::{
+            check_snapshot, generate_mpt_kv_iter, restore_full_sync_snapshot,
+        },
+    },
+    KeyValueDbTrait, MptKeyValue, SnapshotDbBackend, StateRootWithAuxInfo,
+};
+use fallible_iterator::FallibleIterator;
+use parking_lot::RwLock;
+use primitives::{EpochId, StorageKey, NULL_EPOCH};
+use std::{
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
@@ -434,6 +434,7 @@ impl<'a>
 impl SnapshotDbTrait for Arc<Mutex<FakeSnapshotDb>> {
     type SnapshotKvdbIterTraitTag = FakeSnapshotDb;
     type SnapshotKvdbIterType = Self;
+    type SnapshotMptDb = SnapshotMptDbSqlite;
 
     fn get_null_snapshot() -> Self { unreachable!() }
 
@@ -568,6 +569,24 @@ impl SnapshotDbManagerTrait for FakeSnapshotDbManager {
         unreachable!()
     }
 
+    fn checkpoint_snapshot(
+        &self, _snapshot_epoch_id: &EpochId, _dest_snapshot_dir: &Path,
+    ) -> Result<()> {
+        unreachable!()
+    }
+
+    fn checkpoint_latest_mpt_snapshot(
+        &self, _dest_snapshot_dir: &Path,
+    ) -> Result<()> {
+        unreachable!()
+    }
+
+    fn replace_snapshot_if_closed(
+        &self, _snapshot_epoch_id: &EpochId, _rebuilt_snapshot_dir: &Path,
+    ) -> Result<bool> {
+        unreachable!()
+    }
+
     fn new_temp_snapshot_for_full_sync(
         &self, _snapshot_epoch_id: &EpochId, _merkle_root: &EpochId,
         _epoch_height: u64,
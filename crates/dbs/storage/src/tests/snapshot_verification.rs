@@ -0,0 +1,322 @@
+// Copyright 2026 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+const SNAPSHOT_EPOCH_COUNT: u32 = 1;
+const EPOCH_COUNT: u64 = 8;
+const CORRUPTED_KEY: &[u8] = b"corrupted key";
+
+/// The pivot chain and the execution commitments of the epochs committed to
+/// the state manager.
+#[derive(Default)]
+pub(super) struct FakeConsensus {
+    pub(super) pivot_chain: Vec<EpochId>,
+    pub(super) commitments: HashMap<EpochId, EpochExecutionCommitment>,
+}
+
+impl StateMaintenanceTrait for FakeConsensus {
+    fn get_pivot_hash_from_epoch_number(
+        &self, epoch_number: u64,
+    ) -> std::result::Result<EpochId, String> {
+        self.pivot_chain
+            .get(epoch_number as usize)
+            .cloned()
+            .ok_or_else(|| format!("Epoch {} not found", epoch_number))
+    }
+
+    fn get_epoch_execution_commitment_with_db(
+        &self, block_hash: &EpochId,
+    ) -> Option<EpochExecutionCommitment> {
+        self.commitments.get(block_hash).cloned()
+    }
+
+    fn remove_epoch_execution_commitment_from_db(&self, _block_hash: &EpochId) {
+    }
+
+    fn is_epoch_state_root_verified(
+        &self, _epoch_id: &EpochId,
+    ) -> Option<bool> {
+        Some(true)
+    }
+}
+
+pub(super) fn wait_for_snapshotting(state_manager: &FakeStateManager) {
+    while !state_manager
+        .get_storage_manager()
+        .in_progress_snapshotting_tasks
+        .read()
+        .is_empty()
+    {
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Commit `EPOCH_COUNT` epochs, so that a snapshot is made for every epoch.
+fn commit_epochs(state_manager: &FakeStateManager) -> FakeConsensus {
+    let keys = generate_account_keys(EPOCH_COUNT as usize * 100);
+    let mut consensus = FakeConsensus::default();
+    let mut maybe_parent: Option<(EpochId, StateRootWithAuxInfo)> = None;
+    for height in 0..EPOCH_COUNT {
+        let mut state: Box<dyn StateTrait> = match &maybe_parent {
+            None => state_manager.get_state_for_genesis_write(),
+            Some((parent_epoch_id, parent_state_root)) => Box::new(
+                state_manager
+                    .get_state_for_next_epoch_inner(
+                        StateIndex::new_for_next_epoch(
+                            parent_epoch_id,
+                            parent_state_root,
+                            height,
+                            SNAPSHOT_EPOCH_COUNT,
+                        ),
+                        /* open_mpt_snapshot = */ true,
+                        /* recover_mpt_during_construct_pivot_state = */
+                        false,
+                    )
+                    .unwrap()
+                    .unwrap(),
+            ),
+        };
+        for key in keys.chunks(100).nth(height as usize).unwrap() {
+            state
+                .set(
+                    StorageKey::AccountKey(key).with_native_space(),
+                    key[..].into(),
+                )
+                .unwrap();
+        }
+        let epoch_id = EpochId::from_low_u64_be(height + 1);
+        let state_root = state.compute_state_root().unwrap();
+        state.commit(epoch_id).unwrap();
+        wait_for_snapshotting(state_manager);
+
+        consensus.pivot_chain.push(epoch_id);
+        consensus.commitments.insert(
+            epoch_id,
+            EpochExecutionCommitment {
+                state_root_with_aux_info: state_root.clone(),
+                receipts_root: Default::default(),
+                logs_bloom_hash: Default::default(),
+            },
+        );
+        maybe_parent = Some((epoch_id, state_root));
+    }
+    consensus
+}
+
+/// The latest snapshot which isn't made from the empty snapshot and has
+/// states executed on top of it.
+fn latest_snapshot_info(
+    state_manager: &FakeStateManager, consensus: &RwLock<FakeConsensus>,
+) -> SnapshotInfo {
+    let storage_manager = state_manager.get_storage_manager_arc();
+    storage_manager
+        .verify_snapshots(consensus, /* rebuild = */ false)
+        .unwrap()
+        .iter()
+        .filter(|verification| verification.committed_by.is_some())
+        .filter_map(|verification| {
+            storage_manager.get_snapshot_info_at_epoch(
+                &verification.check.snapshot_epoch_id,
+            )
+        })
+        .filter(|info| info.parent_snapshot_epoch_id != NULL_EPOCH)
+        .max_by_key(|info| info.height)
+        .unwrap()
+}
+
+fn open_snapshot_for_write(
+    state_manager: &FakeStateManager, snapshot_epoch_id: &EpochId,
+) -> SnapshotKvDbSqlite {
+    let snapshot_path = state_manager
+        .get_storage_manager()
+        .get_snapshot_manager()
+        .get_snapshot_db_manager()
+        .get_snapshot_db_path(snapshot_epoch_id);
+    SnapshotKvDbSqlite::open(
+        &snapshot_path,
+        /* readonly = */ false,
+        &Default::default(),
+        &Arc::new(Semaphore::new(1)),
+    )
+    .unwrap()
+}
+
+fn corrupt_snapshot(
+    state_manager: &FakeStateManager, snapshot_epoch_id: &EpochId,
+) {
+    open_snapshot_for_write(state_manager, snapshot_epoch_id)
+        .put(CORRUPTED_KEY, b"value")
+        .unwrap();
+}
+
+fn repair_snapshot(
+    state_manager: &FakeStateManager, snapshot_epoch_id: &EpochId,
+) {
+    open_snapshot_for_write(state_manager, snapshot_epoch_id)
+        .delete(CORRUPTED_KEY)
+        .unwrap();
+}
+
+fn verify_snapshot(
+    state_manager: &FakeStateManager, consensus: &RwLock<FakeConsensus>,
+    snapshot_epoch_id: &EpochId, rebuild: bool,
+) -> Result<SnapshotVerification> {
+    state_manager
+        .get_storage_manager_arc()
+        .verify_snapshot(consensus, snapshot_epoch_id, rebuild)
+        .map(Option::unwrap)
+}
+
+#[test]
+fn test_verify_and_rebuild_snapshot() {
+    let state_manager =
+        new_state_manager_for_unit_test_with_snapshot_epoch_count(
+            SNAPSHOT_EPOCH_COUNT,
+        );
+    let consensus = RwLock::new(commit_epochs(&state_manager));
+    let snapshot_info = latest_snapshot_info(&state_manager, &consensus);
+    let snapshot_epoch_id = snapshot_info.get_snapshot_epoch_id();
+
+    let verifications = state_manager
+        .get_storage_manager_arc()
+        .verify_snapshots(&consensus, /* rebuild = */ false)
+        .unwrap();
+    assert!(!verifications.is_empty());
+    assert!(verifications.iter().all(|v| v.is_consistent()));
+    let verification = verify_snapshot(
+        &state_manager,
+        &consensus,
+        snapshot_epoch_id,
+        /* rebuild = */ false,
+    )
+    .unwrap();
+    assert_eq!(
+        verification.committed_by.as_ref().map(|(_, root)| root),
+        Some(&snapshot_info.merkle_root)
+    );
+    assert_eq!(verification.state_root_verified, Some(true));
+
+    corrupt_snapshot(&state_manager, snapshot_epoch_id);
+    let verification = verify_snapshot(
+        &state_manager,
+        &consensus,
+        snapshot_epoch_id,
+        /* rebuild = */ false,
+    )
+    .unwrap();
+    assert!(!verification.is_consistent());
+    assert_ne!(verification.check.kv_merkle_root, snapshot_info.merkle_root);
+    assert_eq!(
+        verification.check.mpt_merkle_root,
+        Some(snapshot_info.merkle_root)
+    );
+    assert_eq!(verification.rebuilt_merkle_root, None);
+    let corrupted_kv_merkle_root = verification.check.kv_merkle_root;
+
+    // The snapshot rebuilt from a corrupted parent snapshot has another
+    // merkle root, so the snapshot is kept.
+    corrupt_snapshot(&state_manager, &snapshot_info.parent_snapshot_epoch_id);
+    assert!(state_manager
+        .get_storage_manager_arc()
+        .rebuild_snapshot(snapshot_epoch_id)
+        .is_err());
+    let verification = verify_snapshot(
+        &state_manager,
+        &consensus,
+        snapshot_epoch_id,
+        /* rebuild = */ false,
+    )
+    .unwrap();
+    assert_eq!(verification.check.kv_merkle_root, corrupted_kv_merkle_root);
+
+    repair_snapshot(&state_manager, &snapshot_info.parent_snapshot_epoch_id);
+    let verification = verify_snapshot(
+        &state_manager,
+        &consensus,
+        snapshot_epoch_id,
+        /* rebuild = */ true,
+    )
+    .unwrap();
+    assert!(!verification.is_consistent());
+    assert_eq!(
+        verification.rebuilt_merkle_root,
+        Some(snapshot_info.merkle_root)
+    );
+    assert!(verify_snapshot(
+        &state_manager,
+        &consensus,
+        snapshot_epoch_id,
+        /* rebuild = */ false,
+    )
+    .unwrap()
+    .is_consistent());
+}
+
+#[test]
+fn test_snapshot_verifier_self_heal() {
+    let state_manager = new_state_manager_for_unit_test_with_storage_conf(
+        SNAPSHOT_EPOCH_COUNT,
+        |storage_conf| {
+            storage_conf.snapshot_verify_interval =
+                Some(Duration::from_millis(100));
+            storage_conf.snapshot_self_heal = true;
+        },
+    );
+    let consensus = Arc::new(RwLock::new(commit_epochs(&state_manager)));
+    let snapshot_info = latest_snapshot_info(&state_manager, &consensus);
+    let snapshot_epoch_id = snapshot_info.get_snapshot_epoch_id();
+    corrupt_snapshot(&state_manager, snapshot_epoch_id);
+
+    state_manager
+        .get_storage_manager_arc()
+        .start_snapshot_verifier(consensus.clone())
+        .unwrap();
+    let start = Instant::now();
+    while !verify_snapshot(
+        &state_manager,
+        &consensus,
+        snapshot_epoch_id,
+        /* rebuild = */ false,
+    )
+    .unwrap()
+    .is_consistent()
+    {
+        assert!(
+            start.elapsed() < Duration::from_secs(60),
+            "The corrupted snapshot isn't rebuilt by the verifier"
+        );
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+use crate::{
+    impls::{
+        errors::*, storage_db::snapshot_kv_db_sqlite::SnapshotKvDbSqlite,
+        storage_manager::SnapshotVerification,
+    },
+    snapshot_manager::SnapshotManagerTrait,
+    state::*,
+    state_manager::*,
+    storage_db::{
+        key_value_db::KeyValueDbTraitSingleWriter, SnapshotDbManagerTrait,
+        SnapshotDbTrait, SnapshotInfo,
+    },
+    tests::{
+        generate_account_keys,
+        new_state_manager_for_unit_test_with_snapshot_epoch_count,
+        new_state_manager_for_unit_test_with_storage_conf, FakeStateManager,
+    },
+    StateRootWithAuxInfo,
+};
+use cfx_internal_common::{
+    consensus_api::StateMaintenanceTrait, EpochExecutionCommitment,
+};
+use parking_lot::RwLock;
+use primitives::{EpochId, StorageKey, NULL_EPOCH};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::sync::Semaphore;
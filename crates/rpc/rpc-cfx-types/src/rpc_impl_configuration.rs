@@ -7,6 +7,9 @@ pub struct RpcImplConfiguration {
     /// receiving a new tx through RPC calling to pack and execute this
     /// transaction.
     pub dev_pack_tx_immediately: bool,
+    /// Enables the dev mode RPCs that mine blocks on demand and change the
+    /// chain state directly.
+    pub dev_mode: bool,
 
     // maximum response payload size allowed
     // note: currently we only handle this for `cfx_getEpochReceipts`,
@@ -2,8 +2,10 @@ use alloy_rpc_types_trace::geth::{
     GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace,
     TraceResult,
 };
-use cfx_rpc_eth_types::{BlockNumber, TransactionRequest};
-use cfx_types::H256;
+use cfx_rpc_eth_types::{
+    AccountRange, BlockNumber, StorageRange, TransactionRequest,
+};
+use cfx_types::{H160, H256};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 
 #[rpc(server, namespace = "debug")]
@@ -36,4 +38,23 @@ pub trait DebugApi {
         &self, request: TransactionRequest, block_number: Option<BlockNumber>,
         opts: Option<GethDebugTracingCallOptions>,
     ) -> RpcResult<GethTrace>;
+
+    /// Returns a page of the accounts at the state of a block in the order of
+    /// the addresses, starting from the address `start`. Pass the returned
+    /// `next` as `start` to read the next page.
+    #[method(name = "accountRange")]
+    async fn debug_account_range(
+        &self, block: BlockNumber, start: Option<H160>,
+        max_results: Option<usize>, no_code: Option<bool>,
+    ) -> RpcResult<AccountRange>;
+
+    /// Returns a page of the storage of a contract at the state of a block in
+    /// the order of the storage keys, starting from `key_start`. Unlike geth,
+    /// the storage is only available at the end of a block, and the keys are
+    /// the storage slots rather than their hashes.
+    #[method(name = "storageRangeAt")]
+    async fn debug_storage_range_at(
+        &self, block: BlockNumber, address: H160, key_start: Option<H256>,
+        max_result: Option<usize>,
+    ) -> RpcResult<StorageRange>;
 }
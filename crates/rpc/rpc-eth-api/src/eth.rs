@@ -1,6 +1,6 @@
 use cfx_rpc_eth_types::{
-    Block, BlockNumber as BlockId, FeeHistory, Header, Receipt, SyncStatus,
-    Transaction, TransactionRequest,
+    Block, BlockNumber as BlockId, FeeHistory, FinalityStatus, Header, Receipt,
+    SyncStatus, Transaction, TransactionRequest,
 };
 use cfx_rpc_primitives::{Bytes, Index};
 use cfx_types::{Address, H256, H64, U256, U64};
@@ -81,6 +81,19 @@ pub trait EthApi {
         &self, block_id: BlockId,
     ) -> RpcResult<Option<Vec<Receipt>>>;
 
+    /// Returns the confirmation risk and the PoS finality status of a block.
+    #[method(name = "getBlockFinalityStatus")]
+    async fn block_finality_status(
+        &self, block_hash: H256,
+    ) -> RpcResult<Option<FinalityStatus>>;
+
+    /// Returns the confirmation risk and the PoS finality status of the block
+    /// including a transaction.
+    #[method(name = "getTransactionFinalityStatus")]
+    async fn transaction_finality_status(
+        &self, tx_hash: H256,
+    ) -> RpcResult<Option<FinalityStatus>>;
+
     /// Returns an uncle block of the given block and index.
     #[method(name = "getUncleByBlockHashAndIndex")]
     async fn uncle_by_block_hash_and_index(
@@ -1,6 +1,12 @@
-use cfx_rpc_eth_types::{BlockNumber, LocalizedTrace, TraceFilter};
+use alloy_rpc_types_trace::parity::{
+    TraceResults, TraceResultsWithTransactionHash, TraceType,
+};
+use cfx_rpc_eth_types::{
+    BlockNumber, LocalizedTrace, TraceFilter, TransactionRequest,
+};
 use cfx_types::H256;
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use std::collections::HashSet;
 
 #[rpc(server, namespace = "trace")]
 pub trait TraceApi {
@@ -21,4 +27,32 @@ pub trait TraceApi {
     fn transaction_traces(
         &self, tx_hash: H256,
     ) -> RpcResult<Option<Vec<LocalizedTrace>>>;
+
+    /// Executes the given call and returns the requested traces of it.
+    #[method(name = "call")]
+    fn trace_call(
+        &self, request: TransactionRequest, trace_types: HashSet<TraceType>,
+        block_number: Option<BlockNumber>,
+    ) -> RpcResult<TraceResults>;
+
+    /// Executes the given calls one after another on top of the state of the
+    /// same block and returns the requested traces of each call.
+    #[method(name = "callMany")]
+    fn trace_call_many(
+        &self, calls: Vec<(TransactionRequest, HashSet<TraceType>)>,
+        block_number: Option<BlockNumber>,
+    ) -> RpcResult<Vec<TraceResults>>;
+
+    /// Replays a transaction and returns the requested traces of it.
+    #[method(name = "replayTransaction")]
+    fn replay_transaction(
+        &self, tx_hash: H256, trace_types: HashSet<TraceType>,
+    ) -> RpcResult<TraceResults>;
+
+    /// Replays all the transactions of a block and returns the requested
+    /// traces of them.
+    #[method(name = "replayBlockTransactions")]
+    fn replay_block_transactions(
+        &self, block_number: BlockNumber, trace_types: HashSet<TraceType>,
+    ) -> RpcResult<Option<Vec<TraceResultsWithTransactionHash>>>;
 }
@@ -4,8 +4,10 @@
 
 //! Pub-Sub types.
 
-use super::{EthRpcLogFilter, Header, Log};
-use cfx_types::H256;
+use super::{
+    EthRpcLogFilter, FinalityStatus, FinalityTarget, Header, Log, SyncInfo,
+};
+use cfx_types::{H256, U64};
 use serde::{de::Error, Deserialize, Deserializer, Serialize};
 use serde_json::{from_value, Value};
 
@@ -24,6 +26,33 @@ pub enum Result {
 
     /// Transaction hash
     TransactionHash(H256),
+
+    /// Sync status
+    SyncState(PubSyncStatus),
+
+    /// Finality status
+    Finality(FinalityStatus),
+
+    /// Chain reorg
+    #[serde(rename_all = "camelCase")]
+    ChainReorg {
+        revert_to: U64,
+        /// Pivot blocks of the reverted blocks after `revert_to`.
+        old_pivots: Vec<H256>,
+        /// Pivot blocks of the new pivot chain after `revert_to`, as far as
+        /// known when the reorg is published.
+        new_pivots: Vec<H256>,
+    },
+}
+
+/// Sync status published to the `syncing` subscribers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PubSyncStatus {
+    /// Whether the node is catching up with the peers.
+    pub syncing: bool,
+    /// Sync progress, only set while syncing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<SyncInfo>,
 }
 
 /// Subscription kind.
@@ -39,6 +68,8 @@ pub enum Kind {
     NewPendingTransactions,
     /// Node syncing status subscription.
     Syncing,
+    /// Block or transaction finality status subscription.
+    Finality,
 }
 
 /// Subscription kind.
@@ -48,6 +79,27 @@ pub enum Params {
     None,
     /// Log parameters.
     Logs(EthRpcLogFilter),
+    /// Finality parameters.
+    Finality(FinalityTarget),
+}
+
+impl Params {
+    /// Returns the target of a `finality` subscription. Note that
+    /// `{"blockHash": ...}` is also a valid log filter, so it is parsed as
+    /// one.
+    pub fn into_finality_target(self) -> Option<FinalityTarget> {
+        match self {
+            Params::Finality(target) => Some(target),
+            Params::Logs(EthRpcLogFilter {
+                from_block: None,
+                to_block: None,
+                block_hash: Some(block_hash),
+                address: None,
+                topics: None,
+            }) => Some(FinalityTarget::Block { block_hash }),
+            _ => None,
+        }
+    }
 }
 
 impl Default for Params {
@@ -65,9 +117,12 @@ impl<'a> Deserialize<'a> for Params {
             return Ok(Params::None);
         }
 
-        // try to interpret as a log filter
-        from_value(v.clone()).map(Params::Logs).map_err(|e| {
-            D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e))
-        })
+        // try to interpret as a log filter, then as a finality target
+        from_value(v.clone())
+            .map(Params::Logs)
+            .or_else(|_| from_value(v.clone()).map(Params::Finality))
+            .map_err(|e| {
+                D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e))
+            })
     }
 }
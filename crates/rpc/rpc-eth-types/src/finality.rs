@@ -0,0 +1,45 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_types::{H256, U256, U64};
+use serde::{Deserialize, Serialize};
+
+/// Confirmation and finality status of an eSpace block, or of the block which
+/// includes a transaction.
+#[derive(Debug, Serialize, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FinalityStatus {
+    /// Block hash
+    pub block_hash: H256,
+    /// Block number
+    pub block_number: U256,
+    /// Transaction hash, only set if the status is queried by a transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_hash: Option<H256>,
+    /// The probability that the block is reverted, scaled by `2^256 - 1`.
+    /// It is the same as the value of `cfx_getConfirmationRiskByHash`.
+    pub confirmation_risk: Option<U256>,
+    /// Whether the block is finalized by PoS.
+    pub pos_finalized: bool,
+    /// The latest block finalized by PoS.
+    pub latest_finalized_block: U256,
+    /// The latest committed PoS block, `None` if PoS is not started.
+    pub pos_reference: Option<H256>,
+    /// The view of `pos_reference`.
+    pub pos_view: Option<U64>,
+    /// The estimated seconds before the block is finalized by PoS, based on
+    /// the recent block rate. It is `0` for a finalized block and `None` if
+    /// it cannot be estimated.
+    pub expected_finality_seconds: Option<U64>,
+}
+
+/// The block or the transaction tracked by a `finality` subscription.
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[serde(untagged)]
+pub enum FinalityTarget {
+    #[serde(rename_all = "camelCase")]
+    Block { block_hash: H256 },
+    #[serde(rename_all = "camelCase")]
+    Transaction { transaction_hash: H256 },
+}
@@ -4,8 +4,10 @@ mod errors;
 pub mod eth_pubsub;
 mod fee_history;
 mod filter;
+mod finality;
 mod log;
 mod receipt;
+mod state_range;
 mod sync;
 pub mod trace;
 pub mod trace_filter;
@@ -20,8 +22,12 @@ pub use errors::Error;
 pub use eth_pubsub::*;
 pub use fee_history::FeeHistory;
 pub use filter::*;
+pub use finality::{FinalityStatus, FinalityTarget};
 pub use log::Log;
 pub use receipt::Receipt;
+pub use state_range::{
+    AccountRange, AccountRangeEntry, StorageRange, StorageRangeEntry,
+};
 pub use sync::{SyncInfo, SyncStatus};
 pub use trace::*;
 pub use trace_filter::TraceFilter;
@@ -0,0 +1,46 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use crate::Bytes;
+use cfx_types::{H160, H256, U256};
+use serde::Serialize;
+
+/// An account returned by `debug_accountRange`.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRangeEntry {
+    pub address: H160,
+    pub balance: U256,
+    pub nonce: U256,
+    pub code_hash: H256,
+    /// The code of the account, unless it has no code or the code is not
+    /// requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+}
+
+/// A page of the accounts in the order of the addresses.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRange {
+    pub accounts: Vec<AccountRangeEntry>,
+    /// The address to continue from, `None` if all accounts are read.
+    pub next: Option<H160>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRangeEntry {
+    pub key: H256,
+    pub value: U256,
+}
+
+/// A page of the storage of a contract in the order of the storage keys.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRange {
+    pub storage: Vec<StorageRangeEntry>,
+    /// The storage key to continue from, `None` if all entries are read.
+    pub next_key: Option<H256>,
+}
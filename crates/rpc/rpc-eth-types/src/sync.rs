@@ -22,7 +22,7 @@ use cfx_types::U256;
 use serde::{Serialize, Serializer};
 
 /// Sync info
-#[derive(Default, Debug, Serialize, PartialEq, Clone)]
+#[derive(Default, Debug, Serialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SyncInfo {
     /// Starting block
@@ -35,6 +35,15 @@ pub struct SyncInfo {
     pub warp_chunks_amount: Option<U256>,
     /// Warp sync snpashot chunks processed.
     pub warp_chunks_processed: Option<U256>,
+    /// Current sync phase
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sync_phase: Option<String>,
+    /// Number of block headers downloaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub known_headers: Option<U256>,
+    /// Estimated seconds to catch up with the peers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eta_seconds: Option<U256>,
 }
 
 /// Sync status
@@ -84,4 +93,19 @@ mod tests {
             r#"{"startingBlock":"0x0","currentBlock":"0x0","highestBlock":"0x0","warpChunksAmount":null,"warpChunksProcessed":null}"#
         );
     }
+
+    #[test]
+    fn test_serialize_sync_info_with_progress() {
+        let t = SyncInfo {
+            sync_phase: Some("CatchUpSyncBlock".into()),
+            known_headers: Some(16.into()),
+            eta_seconds: Some(60.into()),
+            ..Default::default()
+        };
+        let serialized = serde_json::to_string(&t).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"startingBlock":"0x0","currentBlock":"0x0","highestBlock":"0x0","warpChunksAmount":null,"warpChunksProcessed":null,"syncPhase":"CatchUpSyncBlock","knownHeaders":"0x10","etaSeconds":"0x3c"}"#
+        );
+    }
 }
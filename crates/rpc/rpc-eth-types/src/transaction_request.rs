@@ -36,7 +36,7 @@ pub const DEFAULT_ETH_GAS_CALL_REQUEST: u64 =
     DEFAULT_TARGET_BLOCK_GAS_LIMIT * 5 / 10;
 
 /// Call request
-#[derive(Debug, Default, PartialEq, Deserialize)]
+#[derive(Debug, Default, PartialEq, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionRequest {
     /// From
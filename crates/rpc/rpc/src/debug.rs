@@ -1,3 +1,4 @@
+use crate::helpers::{account_range, state_range_limit, storage_range};
 use alloy_rpc_types_trace::geth::{
     GethDebugBuiltInTracerType,
     GethDebugTracerType::{BuiltInTracer, JsTracer},
@@ -6,9 +7,13 @@ use alloy_rpc_types_trace::geth::{
 };
 use async_trait::async_trait;
 use cfx_rpc_eth_api::DebugApiServer;
-use cfx_rpc_eth_types::{BlockNumber, TransactionRequest};
+use cfx_rpc_eth_types::{
+    AccountRange, AccountRangeEntry, BlockNumber, StorageRange,
+    StorageRangeEntry, TransactionRequest,
+};
 use cfx_rpc_utils::error::jsonrpsee_error_helpers::invalid_params_msg;
-use cfx_types::{AddressSpaceUtil, Space, H256, U256};
+use cfx_storage::StorageState;
+use cfx_types::{Address, AddressSpaceUtil, Space, H160, H256, U256};
 use cfxcore::{
     errors::Error as CoreError, ConsensusGraph, ConsensusGraphTrait,
     SharedConsensusGraph,
@@ -18,7 +23,7 @@ use jsonrpsee::core::RpcResult;
 use primitives::{
     Block, BlockHashOrEpochNumber, BlockHeaderBuilder, EpochNumber,
 };
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 pub struct DebugApi {
     consensus: SharedConsensusGraph,
@@ -70,18 +75,42 @@ impl DebugApi {
     }
 
     pub fn trace_call(
-        &self, mut request: TransactionRequest,
-        block_number: Option<BlockNumber>,
+        &self, request: TransactionRequest, block_number: Option<BlockNumber>,
         opts: Option<GethDebugTracingCallOptions>,
     ) -> Result<GethTrace, CoreError> {
-        if request.from.is_none() {
+        let opts = opts.unwrap_or_default();
+        let (epoch_id, epoch_num, blocks) =
+            self.make_call_blocks(vec![request], block_number)?;
+
+        let traces = self.consensus_graph().collect_blocks_geth_trace(
+            epoch_id,
+            epoch_num,
+            &blocks,
+            opts.tracing_options,
+            None,
+        )?;
+
+        let res = traces
+            .first()
+            .ok_or(CoreError::Msg("trace generation failed".to_string()))?;
+
+        Ok(res.trace.clone())
+    }
+
+    /// Make a virtual block after the epoch of `block_number` which packs the
+    /// call requests in order. Return the pivot hash and the number of the
+    /// epoch whose state the block is executed on, and the block.
+    pub fn make_call_blocks(
+        &self, requests: Vec<TransactionRequest>,
+        block_number: Option<BlockNumber>,
+    ) -> Result<(H256, u64, Vec<Arc<Block>>), CoreError> {
+        if requests.iter().any(|request| request.from.is_none()) {
             return Err(CoreError::InvalidParam(
                 "from is required".to_string(),
                 Default::default(),
             ));
         }
 
-        let opts = opts.unwrap_or_default();
         let block_num = block_number.unwrap_or_default();
 
         let epoch_num = self
@@ -101,25 +130,36 @@ impl DebugApi {
             .last()
             .ok_or(CoreError::Msg("should have block hash".to_string()))?;
 
-        // nonce auto fill
-        if request.nonce.is_none() {
-            let nonce = self.consensus_graph().next_nonce(
-                request.from.unwrap().with_evm_space(),
-                BlockHashOrEpochNumber::EpochNumber(EpochNumber::Number(
-                    epoch_num,
-                )),
-                "num",
-            )?;
+        // construct blocks from call_request
+        let chain_id = self.consensus.best_chain_id();
+        // the nonces of the senders after the calls before
+        let mut next_nonces: HashMap<Address, U256> = HashMap::new();
+        let mut transactions = Vec::with_capacity(requests.len());
+        for mut request in requests {
+            let from = request.from.expect("checked");
+            // nonce auto fill
+            let nonce = match (request.nonce, next_nonces.get(&from)) {
+                (Some(nonce), _) => nonce,
+                (None, Some(nonce)) => *nonce,
+                (None, None) => self.consensus_graph().next_nonce(
+                    from.with_evm_space(),
+                    BlockHashOrEpochNumber::EpochNumber(EpochNumber::Number(
+                        epoch_num,
+                    )),
+                    "num",
+                )?,
+            };
             request.nonce = Some(nonce);
+            next_nonces.insert(from, nonce + 1);
+
+            // debug trace call has a fixed large gas limit.
+            let signed_tx = request.sign_call(
+                chain_id.in_evm_space(),
+                self.max_estimation_gas_limit,
+            )?;
+            transactions.push(Arc::new(signed_tx));
         }
 
-        // construct blocks from call_request
-        let chain_id = self.consensus.best_chain_id();
-        // debug trace call has a fixed large gas limit.
-        let signed_tx = request.sign_call(
-            chain_id.in_evm_space(),
-            self.max_estimation_gas_limit,
-        )?;
         let epoch_blocks = self
             .consensus_graph()
             .data_man
@@ -139,22 +179,84 @@ impl DebugApi {
             .with_timestamp(pivot_block.block_header.timestamp() + 1)
             .with_gas_limit(*pivot_block.block_header.gas_limit())
             .build();
-        let block = Block::new(header, vec![Arc::new(signed_tx)]);
-        let blocks: Vec<Arc<Block>> = vec![Arc::new(block)];
+        let block = Block::new(header, transactions);
 
-        let traces = self.consensus_graph().collect_blocks_geth_trace(
-            *epoch_id,
-            epoch_num,
-            &blocks,
-            opts.tracing_options,
-            None,
+        Ok((*epoch_id, epoch_num, vec![Arc::new(block)]))
+    }
+
+    /// Read a page of the eSpace accounts at the state of `block`.
+    pub fn account_range(
+        &self, block: BlockNumber, start: Option<H160>,
+        max_results: Option<usize>, no_code: Option<bool>,
+    ) -> Result<AccountRange, CoreError> {
+        let mut state = self.storage_state(block)?;
+        let range = account_range(
+            &mut state,
+            Space::Ethereum,
+            &start.unwrap_or_default(),
+            state_range_limit(max_results),
+            !no_code.unwrap_or(false),
         )?;
+        let accounts = range
+            .accounts
+            .into_iter()
+            .map(|(account, code)| AccountRangeEntry {
+                address: account.address().address,
+                balance: account.balance,
+                nonce: account.nonce,
+                code_hash: account.code_hash,
+                code: code.map(Into::into),
+            })
+            .collect();
+        Ok(AccountRange {
+            accounts,
+            next: range.next,
+        })
+    }
 
-        let res = traces
-            .first()
-            .ok_or(CoreError::Msg("trace generation failed".to_string()))?;
+    /// Read a page of the storage of an eSpace contract at the state of
+    /// `block`.
+    pub fn storage_range_at(
+        &self, block: BlockNumber, address: H160, key_start: Option<H256>,
+        max_result: Option<usize>,
+    ) -> Result<StorageRange, CoreError> {
+        let mut state = self.storage_state(block)?;
+        let range = storage_range(
+            &mut state,
+            &address.with_evm_space(),
+            key_start.unwrap_or_default().as_bytes(),
+            state_range_limit(max_result),
+        )?;
+        let to_h256 = |key: Vec<u8>| {
+            if key.len() == H256::len_bytes() {
+                Ok(H256::from_slice(&key))
+            } else {
+                Err(CoreError::Msg(format!("Invalid storage key {:?}", key)))
+            }
+        };
+        let mut storage = Vec::with_capacity(range.storage.len());
+        for (key, value) in range.storage {
+            storage.push(StorageRangeEntry {
+                key: to_h256(key)?,
+                value: value.value,
+            });
+        }
+        Ok(StorageRange {
+            storage,
+            next_key: range.next_key.map(to_h256).transpose()?,
+        })
+    }
 
-        Ok(res.trace.clone())
+    fn storage_state(
+        &self, block: BlockNumber,
+    ) -> Result<StorageState, CoreError> {
+        let epoch_num = self
+            .get_block_epoch_num(block)
+            .map_err(|e| CoreError::InvalidParam("block".into(), e))?;
+        self.consensus_graph().get_storage_state_by_epoch_number(
+            EpochNumber::Number(epoch_num),
+            "block",
+        )
     }
 
     pub fn trace_block_by_num(
@@ -287,4 +389,20 @@ impl DebugApiServer for DebugApi {
         self.trace_call(request, block_number, opts)
             .map_err(|e| e.into())
     }
+
+    async fn debug_account_range(
+        &self, block: BlockNumber, start: Option<H160>,
+        max_results: Option<usize>, no_code: Option<bool>,
+    ) -> RpcResult<AccountRange> {
+        self.account_range(block, start, max_results, no_code)
+            .map_err(|e| e.into())
+    }
+
+    async fn debug_storage_range_at(
+        &self, block: BlockNumber, address: H160, key_start: Option<H256>,
+        max_result: Option<usize>,
+    ) -> RpcResult<StorageRange> {
+        self.storage_range_at(block, address, key_start, max_result)
+            .map_err(|e| e.into())
+    }
 }
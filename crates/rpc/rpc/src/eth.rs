@@ -1,4 +1,7 @@
-use crate::helpers::{FeeHistoryCache, MAX_FEE_HISTORY_CACHE_BLOCK_COUNT};
+use crate::helpers::{
+    scaled_confirmation_risk, FeeHistoryCache,
+    MAX_FEE_HISTORY_CACHE_BLOCK_COUNT,
+};
 use async_trait::async_trait;
 use cfx_execute_helper::estimation::EstimateRequest;
 use cfx_executor::executive::{
@@ -11,8 +14,9 @@ use cfx_rpc_cfx_types::{
 };
 use cfx_rpc_eth_api::EthApiServer;
 use cfx_rpc_eth_types::{
-    Block, BlockNumber as BlockId, EthRpcLogFilter, FeeHistory, Header, Log,
-    Receipt, SyncInfo, SyncStatus, Transaction, TransactionRequest,
+    Block, BlockNumber as BlockId, EthRpcLogFilter, FeeHistory, FinalityStatus,
+    FinalityTarget, Header, Log, Receipt, SyncInfo, SyncStatus, Transaction,
+    TransactionRequest,
 };
 use cfx_rpc_primitives::{Bytes, Index, U64 as HexU64};
 use cfx_rpc_utils::error::{
@@ -28,6 +32,7 @@ use cfxcore::{
     errors::{Error as CoreError, Result as CoreResult},
     ConsensusGraph, ConsensusGraphTrait, SharedConsensusGraph,
     SharedSynchronizationService, SharedTransactionPool,
+    SynchronizationService,
 };
 use error_chain::bail;
 use jsonrpc_core::Error as RpcError;
@@ -45,6 +50,9 @@ type BlockNumberOrTag = BlockId;
 type JsonStorageKey = U256;
 type RpcBlock = Block;
 
+/// The number of recent blocks used to estimate the block interval.
+const FINALITY_ESTIMATION_BLOCK_WINDOW: u64 = 100;
+
 pub struct EthApi {
     config: RpcImplConfiguration,
     consensus: SharedConsensusGraph,
@@ -459,20 +467,144 @@ impl EthApi {
         }
     }
 
-    pub fn sync_status(&self) -> SyncStatus {
-        if self.sync.catch_up_mode() {
-            SyncStatus::Info(SyncInfo {
-                starting_block: U256::from(self.consensus.block_count()),
-                current_block: U256::from(self.consensus.block_count()),
-                highest_block: U256::from(
-                    self.sync.get_synchronization_graph().block_count(),
+    pub fn sync_status(&self) -> SyncStatus { Self::sync_status_of(&self.sync) }
+
+    pub fn sync_status_of(sync: &SynchronizationService) -> SyncStatus {
+        let progress = sync.sync_progress();
+        if !progress.catch_up_mode {
+            return SyncStatus::None;
+        }
+        let state_sync = &progress.state_sync;
+        let (warp_chunks_amount, warp_chunks_processed) =
+            match state_sync.total_chunks {
+                Some(total) => (
+                    Some(U256::from(total)),
+                    Some(U256::from(state_sync.downloaded_chunks)),
                 ),
-                warp_chunks_amount: None,
-                warp_chunks_processed: None,
-            })
+                None => (None, None),
+            };
+        SyncStatus::Info(SyncInfo {
+            starting_block: U256::from(progress.starting_epoch),
+            current_block: U256::from(progress.current_epoch),
+            highest_block: U256::from(progress.target_epoch),
+            warp_chunks_amount,
+            warp_chunks_processed,
+            sync_phase: Some(format!("{:?}", progress.phase)),
+            known_headers: Some(U256::from(progress.header_count)),
+            eta_seconds: progress.eta.map(|eta| U256::from(eta.as_secs())),
+        })
+    }
+
+    pub fn finality_status(
+        &self, target: &FinalityTarget,
+    ) -> Option<FinalityStatus> {
+        Self::finality_status_of(self.consensus_graph(), target)
+    }
+
+    /// Returns the confirmation risk and the PoS finality of an eSpace block,
+    /// or of the block including an eSpace transaction. It returns `None` if
+    /// the block is not on the pivot chain, or the transaction is not packed.
+    pub fn finality_status_of(
+        consensus: &ConsensusGraph, target: &FinalityTarget,
+    ) -> Option<FinalityStatus> {
+        let pivot_hash = |epoch: u64| -> Option<H256> {
+            consensus
+                .get_block_hashes_by_epoch(EpochNumber::Number(epoch))
+                .ok()?
+                .last()
+                .cloned()
+        };
+
+        let (block_hash, transaction_hash) = match *target {
+            FinalityTarget::Block { block_hash } => (block_hash, None),
+            FinalityTarget::Transaction { transaction_hash } => {
+                let data_man = consensus.get_data_manager();
+                let tx = data_man.transaction_by_hash(&transaction_hash)?;
+                if tx.space() != Space::Ethereum {
+                    return None;
+                }
+                let tx_index = data_man.transaction_index_by_hash(
+                    &transaction_hash,
+                    false, /* update_cache */
+                )?;
+                let epoch =
+                    consensus.get_block_epoch_number(&tx_index.block_hash)?;
+                (pivot_hash(epoch)?, Some(transaction_hash))
+            }
+        };
+
+        // Only the pivot blocks are exposed as eSpace blocks.
+        let block_number = consensus.get_block_epoch_number(&block_hash)?;
+        if pivot_hash(block_number)? != block_hash {
+            return None;
+        }
+
+        let block_interval = Self::average_block_interval(consensus);
+
+        let inner = &*consensus.inner.read();
+        let confirmation_risk = consensus
+            .confirmation_meter
+            .confirmation_risk_by_hash(inner, block_hash)
+            .map(scaled_confirmation_risk);
+
+        let latest_finalized = inner.latest_epoch_confirmed_by_pos().1;
+        let pos_finalized = block_number <= latest_finalized;
+        let pos_verifier = &inner.pos_verifier;
+        let pos_reference = pos_verifier
+            .pos_option()
+            .map(|_| pos_verifier.get_latest_pos_reference());
+        let pos_view = pos_reference
+            .as_ref()
+            .and_then(|pos_ref| pos_verifier.get_pos_view(pos_ref));
+
+        // PoS finalizes the blocks with a roughly constant lag behind the
+        // latest block, so the block is expected to be finalized after as
+        // many blocks as it is ahead of the latest finalized one.
+        let expected_finality_seconds = if pos_finalized {
+            Some(0)
+        } else if pos_reference.is_none() {
+            None
         } else {
-            SyncStatus::None
+            block_interval.map(|interval| {
+                ((block_number - latest_finalized) as f64 * interval).ceil()
+                    as u64
+            })
+        };
+
+        Some(FinalityStatus {
+            block_hash,
+            block_number: block_number.into(),
+            transaction_hash,
+            confirmation_risk,
+            pos_finalized,
+            latest_finalized_block: latest_finalized.into(),
+            pos_reference,
+            pos_view: pos_view.map(Into::into),
+            expected_finality_seconds: expected_finality_seconds
+                .map(Into::into),
+        })
+    }
+
+    /// The average interval in seconds between the recent pivot blocks.
+    fn average_block_interval(consensus: &ConsensusGraph) -> Option<f64> {
+        let best = consensus.best_epoch_number();
+        let start = best.saturating_sub(FINALITY_ESTIMATION_BLOCK_WINDOW);
+        if start == best {
+            return None;
         }
+
+        let timestamp = |epoch: u64| -> Option<u64> {
+            let hash = *consensus
+                .get_block_hashes_by_epoch(EpochNumber::Number(epoch))
+                .ok()?
+                .last()?;
+            consensus
+                .get_data_manager()
+                .block_header_by_hash(&hash)
+                .map(|header| header.timestamp())
+        };
+        let elapsed = timestamp(best)?.saturating_sub(timestamp(start)?);
+        Some(elapsed as f64 / (best - start) as f64)
     }
 
     pub fn chain_id(&self) -> u32 {
@@ -1086,6 +1218,23 @@ impl EthApiServer for EthApi {
             .map_err(|e| e.into())
     }
 
+    /// Returns the confirmation risk and the PoS finality status of a block.
+    async fn block_finality_status(
+        &self, block_hash: H256,
+    ) -> RpcResult<Option<FinalityStatus>> {
+        Ok(self.finality_status(&FinalityTarget::Block { block_hash }))
+    }
+
+    /// Returns the confirmation risk and the PoS finality status of the block
+    /// including a transaction.
+    async fn transaction_finality_status(
+        &self, tx_hash: H256,
+    ) -> RpcResult<Option<FinalityStatus>> {
+        Ok(self.finality_status(&FinalityTarget::Transaction {
+            transaction_hash: tx_hash,
+        }))
+    }
+
     /// Returns an uncle block of the given block and index.
     async fn uncle_by_block_hash_and_index(
         &self, hash: H256, index: Index,
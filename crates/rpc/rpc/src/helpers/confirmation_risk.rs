@@ -0,0 +1,23 @@
+use bigdecimal::BigDecimal;
+use cfx_types::U256;
+use num_bigint::{BigInt, ToBigInt};
+
+/// Scales the confirmation risk in `[0, 1]` by `2^256 - 1`, which is the
+/// representation of the risk in RPC responses.
+pub fn scaled_confirmation_risk(risk: f64) -> U256 {
+    let risk: BigDecimal = risk.into();
+    let scale = BigInt::parse_bytes(
+        b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+        16,
+    )
+    .expect("failed to unwrap U256::max into bigInt");
+
+    //TODO: there's a precision problem here, it should be fine under a
+    // (2^256 - 1) scale
+    let scaled_risk: BigInt = (risk * scale)
+        .to_bigint()
+        .expect("failed to convert scaled risk to bigInt");
+    let (sign, big_endian_bytes) = scaled_risk.to_bytes_be();
+    assert_ne!(sign, num_bigint::Sign::Minus);
+    U256::from(big_endian_bytes.as_slice())
+}
@@ -1,5 +1,12 @@
+mod confirmation_risk;
 mod fee_history_cache;
+mod state_range;
 
+pub use confirmation_risk::scaled_confirmation_risk;
 pub use fee_history_cache::{
     FeeHistoryCache, MAX_FEE_HISTORY_CACHE_BLOCK_COUNT,
 };
+pub use state_range::{
+    account_range, state_range_limit, storage_range, AccountRange,
+    StorageRange, MAX_STATE_RANGE_RESULTS,
+};
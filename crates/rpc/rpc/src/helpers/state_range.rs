@@ -0,0 +1,159 @@
+// Copyright 2024 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_storage::{
+    utils::to_key_prefix_iter_upper_bound, StorageState, StorageStateTrait,
+};
+use cfx_types::{Address, AddressWithSpace, Space};
+use cfxcore::errors::Error as CoreError;
+use keccak_hash::KECCAK_EMPTY;
+use primitives::{Account, CodeInfo, StorageKey, StorageValue};
+use rlp::Rlp;
+
+/// The max number of entries returned by a state range read.
+pub const MAX_STATE_RANGE_RESULTS: usize = 256;
+
+/// The number of entries to read in a page, at least 1 so that a page always
+/// makes progress, and capped by `MAX_STATE_RANGE_RESULTS`.
+pub fn state_range_limit(max_results: Option<usize>) -> usize {
+    max_results
+        .unwrap_or(MAX_STATE_RANGE_RESULTS)
+        .clamp(1, MAX_STATE_RANGE_RESULTS)
+}
+
+/// A page of the accounts of a space, in the order of the addresses.
+pub struct AccountRange {
+    /// The accounts with their code, if the code is requested and the account
+    /// has code.
+    pub accounts: Vec<(Account, Option<Vec<u8>>)>,
+    /// The address of the first account not read, if any.
+    pub next: Option<Address>,
+}
+
+/// A page of the storage of an account, in the order of the storage keys.
+pub struct StorageRange {
+    pub storage: Vec<(Vec<u8>, StorageValue)>,
+    /// The first storage key not read, if any.
+    pub next_key: Option<Vec<u8>>,
+}
+
+fn account_key(address: &[u8], space: Space) -> Vec<u8> {
+    StorageKey::AccountKey(address)
+        .with_space(space)
+        .to_key_bytes()
+}
+
+/// Read at most `limit` accounts of `space` from the address `start`.
+///
+/// The entries of an account follow its account entry in the key order, and
+/// the entries of the eSpace account of an address follow the ones of the
+/// Core Space account, so they are skipped over by seeking the account key of
+/// the next address.
+pub fn account_range(
+    state: &mut StorageState, space: Space, start: &Address, limit: usize,
+    with_code: bool,
+) -> Result<AccountRange, CoreError> {
+    let mut accounts = vec![];
+    let mut next = None;
+    let mut error = None;
+    // One more account is read to find the start of the next page.
+    state.read_range(
+        None,
+        &account_key(&start.0, space),
+        limit + 1,
+        |key, value| {
+            if key.len() < Address::len_bytes() {
+                return Some([key, &[0]].concat());
+            }
+            let address = Address::from_slice(&key[..Address::len_bytes()]);
+            let target = account_key(&address.0, space);
+            if key < target.as_slice() {
+                return Some(target);
+            }
+            if key == target.as_slice() {
+                if accounts.len() == limit {
+                    next = Some(address);
+                    return None;
+                }
+                match Account::new_from_rlp(address, &Rlp::new(value)) {
+                    Ok(account) => accounts.push(account),
+                    Err(e) => {
+                        error = Some(format!(
+                            "Invalid account {:?}: {:?}",
+                            address, e
+                        ));
+                        return None;
+                    }
+                }
+            }
+            let next_address = to_key_prefix_iter_upper_bound(&address.0)?;
+            Some(account_key(&next_address, space))
+        },
+    )?;
+    if let Some(e) = error {
+        return Err(e.into());
+    }
+
+    let mut accounts_with_code = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        let code = if with_code && account.code_hash != KECCAK_EMPTY {
+            let code_key = StorageKey::new_code_key(
+                &account.address().address,
+                &account.code_hash,
+            )
+            .with_space(space);
+            match state.get(code_key)? {
+                Some(raw) => Some(rlp::decode::<CodeInfo>(&raw)?.code.to_vec()),
+                None => None,
+            }
+        } else {
+            None
+        };
+        accounts_with_code.push((account, code));
+    }
+
+    Ok(AccountRange {
+        accounts: accounts_with_code,
+        next,
+    })
+}
+
+/// Read at most `limit` storage entries of an account from the storage key
+/// `start`.
+pub fn storage_range(
+    state: &mut StorageState, address: &AddressWithSpace, start: &[u8],
+    limit: usize,
+) -> Result<StorageRange, CoreError> {
+    let prefix = StorageKey::new_storage_root_key(&address.address)
+        .with_space(address.space);
+    let prefix_bytes = prefix.to_key_bytes();
+    let start_key = [prefix_bytes.as_slice(), start].concat();
+
+    let mut storage = vec![];
+    let mut next_key = None;
+    let mut error = None;
+    state.read_range(Some(prefix), &start_key, limit + 1, |key, value| {
+        let storage_key = &key[prefix_bytes.len()..];
+        // The storage root entry holds the storage layout.
+        if !storage_key.is_empty() {
+            if storage.len() == limit {
+                next_key = Some(storage_key.to_vec());
+                return None;
+            }
+            match rlp::decode::<StorageValue>(value) {
+                Ok(value) => storage.push((storage_key.to_vec(), value)),
+                Err(e) => {
+                    error = Some(e);
+                    return None;
+                }
+            }
+        }
+        Some([key, &[0]].concat())
+    })?;
+    if let Some(e) = error {
+        return Err(e.into());
+    }
+
+    Ok(StorageRange { storage, next_key })
+}
@@ -1,12 +1,168 @@
+use crate::DebugApi;
+use alloy_rpc_types_trace::parity::{
+    TraceResults, TraceResultsWithTransactionHash, TraceType,
+};
 use cfx_rpc_eth_api::TraceApiServer;
-use cfx_rpc_eth_types::{BlockNumber, LocalizedTrace, TraceFilter};
-use cfx_types::H256;
+use cfx_rpc_eth_types::{
+    BlockNumber, LocalizedTrace, TraceFilter, TransactionRequest,
+};
+use cfx_rpc_utils::error::jsonrpsee_error_helpers::internal_error as jsonrpsee_internal_error;
+use cfx_types::{Space, H256, U256};
+use cfxcore::{
+    errors::Error as CoreError, ConsensusGraphTrait, SharedConsensusGraph,
+};
+use geth_tracer::to_alloy_h256;
 use jsonrpsee::core::RpcResult;
+use std::collections::HashSet;
 
-pub struct TraceApi;
+pub struct TraceApi {
+    // Shares the epoch lookup and the call block construction of the debug
+    // namespace.
+    inner: DebugApi,
+}
 
 impl TraceApi {
-    pub fn new() -> TraceApi { TraceApi }
+    pub fn new(
+        consensus: SharedConsensusGraph, max_estimation_gas_limit: Option<U256>,
+    ) -> TraceApi {
+        TraceApi {
+            inner: DebugApi::new(consensus, max_estimation_gas_limit),
+        }
+    }
+
+    /// Replay the transaction `hash` of `space`.
+    pub fn replay_transaction(
+        &self, hash: H256, trace_types: HashSet<TraceType>, space: Space,
+    ) -> Result<TraceResults, CoreError> {
+        let consensus = self.inner.consensus_graph();
+        let data_man = consensus.get_data_manager();
+        let tx_index = data_man
+            .transaction_index_by_hash(&hash, false /* update_cache */)
+            .ok_or(CoreError::Msg("invalid tx hash".to_string()))?;
+        // Phantom transactions are not executed by themselves.
+        let tx_space = match data_man
+            .block_by_hash(&tx_index.block_hash, false /* update_cache */)
+        {
+            Some(block) if !tx_index.is_phantom => block
+                .transactions
+                .get(tx_index.real_index)
+                .map(|tx| tx.space()),
+            _ => None,
+        };
+        if tx_space != Some(space) {
+            return Err(CoreError::Msg(format!(
+                "transaction {:?} not found in {:?} space",
+                hash, space
+            )));
+        }
+
+        let epoch_num = consensus
+            .get_block_epoch_number(&tx_index.block_hash)
+            .ok_or(CoreError::Msg("invalid tx hash".to_string()))?;
+
+        let epoch_traces = consensus.collect_epoch_parity_trace(
+            epoch_num,
+            Some(hash),
+            trace_types,
+        )?;
+
+        epoch_traces
+            .into_iter()
+            .find(|val| val.tx_hash == hash && val.space == space)
+            .map(|val| val.trace)
+            .ok_or(CoreError::Msg("trace generation failed".to_string()))
+    }
+
+    pub fn replay_block_transactions(
+        &self, block_number: BlockNumber, trace_types: HashSet<TraceType>,
+    ) -> Result<Vec<TraceResultsWithTransactionHash>, CoreError> {
+        let epoch_num = self
+            .inner
+            .get_block_epoch_num(block_number)
+            .map_err(|err| CoreError::Msg(err))?;
+        self.replay_epoch_transactions(epoch_num, trace_types, Space::Ethereum)
+    }
+
+    /// Replay the transactions of `space` in the epoch `epoch_num`.
+    pub fn replay_epoch_transactions(
+        &self, epoch_num: u64, trace_types: HashSet<TraceType>, space: Space,
+    ) -> Result<Vec<TraceResultsWithTransactionHash>, CoreError> {
+        let epoch_traces = self
+            .inner
+            .consensus_graph()
+            .collect_epoch_parity_trace(epoch_num, None, trace_types)?;
+
+        Ok(epoch_traces
+            .into_iter()
+            .filter(|val| val.space == space)
+            .map(|val| TraceResultsWithTransactionHash {
+                full_trace: val.trace,
+                transaction_hash: to_alloy_h256(val.tx_hash),
+            })
+            .collect())
+    }
+
+    pub fn trace_call(
+        &self, request: TransactionRequest, trace_types: HashSet<TraceType>,
+        block_number: Option<BlockNumber>,
+    ) -> Result<TraceResults, CoreError> {
+        self.trace_call_many(vec![(request, trace_types)], block_number)?
+            .pop()
+            .ok_or(CoreError::Msg("trace generation failed".to_string()))
+    }
+
+    pub fn trace_call_many(
+        &self, calls: Vec<(TransactionRequest, HashSet<TraceType>)>,
+        block_number: Option<BlockNumber>,
+    ) -> Result<Vec<TraceResults>, CoreError> {
+        // All the calls are traced with the union of the requested trace
+        // types, and the results are filtered for each call afterwards.
+        let all_trace_types: HashSet<TraceType> = calls
+            .iter()
+            .flat_map(|(_, trace_types)| trace_types.iter().cloned())
+            .collect();
+        let (requests, trace_types): (Vec<_>, Vec<_>) =
+            calls.into_iter().unzip();
+
+        let (epoch_id, epoch_num, blocks) =
+            self.inner.make_call_blocks(requests, block_number)?;
+
+        let traces = self.inner.consensus_graph().collect_blocks_parity_trace(
+            epoch_id,
+            epoch_num,
+            &blocks,
+            all_trace_types,
+            None,
+        )?;
+
+        if traces.len() != trace_types.len() {
+            return Err(CoreError::Msg("trace generation failed".to_string()));
+        }
+
+        Ok(traces
+            .into_iter()
+            .zip(trace_types)
+            .map(|(val, trace_types)| {
+                filter_trace_results(val.trace, &trace_types)
+            })
+            .collect())
+    }
+}
+
+/// Remove the traces not requested from the results.
+fn filter_trace_results(
+    mut results: TraceResults, trace_types: &HashSet<TraceType>,
+) -> TraceResults {
+    if !trace_types.contains(&TraceType::Trace) {
+        results.trace = vec![];
+    }
+    if !trace_types.contains(&TraceType::VmTrace) {
+        results.vm_trace = None;
+    }
+    if !trace_types.contains(&TraceType::StateDiff) {
+        results.state_diff = None;
+    }
+    results
 }
 
 #[async_trait::async_trait]
@@ -15,20 +171,56 @@ impl TraceApiServer for TraceApi {
         &self, block_number: BlockNumber,
     ) -> RpcResult<Option<Vec<LocalizedTrace>>> {
         let _ = block_number;
-        todo!()
+        Err(jsonrpsee_internal_error("Not implemented"))
     }
 
     fn filter_traces(
         &self, filter: TraceFilter,
     ) -> RpcResult<Option<Vec<LocalizedTrace>>> {
         let _ = filter;
-        todo!()
+        Err(jsonrpsee_internal_error("Not implemented"))
     }
 
     fn transaction_traces(
         &self, tx_hash: H256,
     ) -> RpcResult<Option<Vec<LocalizedTrace>>> {
         let _ = tx_hash;
-        todo!()
+        Err(jsonrpsee_internal_error("Not implemented"))
+    }
+
+    fn trace_call(
+        &self, request: TransactionRequest, trace_types: HashSet<TraceType>,
+        block_number: Option<BlockNumber>,
+    ) -> RpcResult<TraceResults> {
+        TraceApi::trace_call(self, request, trace_types, block_number)
+            .map_err(|e| e.into())
+    }
+
+    fn trace_call_many(
+        &self, calls: Vec<(TransactionRequest, HashSet<TraceType>)>,
+        block_number: Option<BlockNumber>,
+    ) -> RpcResult<Vec<TraceResults>> {
+        TraceApi::trace_call_many(self, calls, block_number)
+            .map_err(|e| e.into())
+    }
+
+    fn replay_transaction(
+        &self, tx_hash: H256, trace_types: HashSet<TraceType>,
+    ) -> RpcResult<TraceResults> {
+        TraceApi::replay_transaction(
+            self,
+            tx_hash,
+            trace_types,
+            Space::Ethereum,
+        )
+        .map_err(|e| e.into())
+    }
+
+    fn replay_block_transactions(
+        &self, block_number: BlockNumber, trace_types: HashSet<TraceType>,
+    ) -> RpcResult<Option<Vec<TraceResultsWithTransactionHash>>> {
+        TraceApi::replay_block_transactions(self, block_number, trace_types)
+            .map(Some)
+            .map_err(|e| e.into())
     }
 }
@@ -194,6 +194,17 @@ class RpcClient:
 
         return res
 
+    def get_proof(self, addr: str, storage_keys: list, epoch: str = None) -> dict:
+        assert_is_hash_string(addr, length=40)
+        addr = hex_to_b32_address(addr)
+
+        if epoch is None:
+            res = self.node.cfx_getProof(addr, storage_keys)
+        else:
+            res = self.node.cfx_getProof(addr, storage_keys, epoch)
+
+        return res
+
     def get_code(self, address: str, epoch: Union[str, dict] = None) -> str:
         address = hex_to_b32_address(address)
         if epoch is None:
@@ -0,0 +1,122 @@
+#!/usr/bin/env python3
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+from conflux.address import b32_address_to_hex, hex_to_b32_address
+from conflux.rpc import RpcClient
+from test_framework.test_framework import ConfluxTestFramework
+from test_framework.util import *
+
+REMOTE_NETWORK_ID = 1029
+FORK_EPOCH = 100
+KECCAK_EMPTY = "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+FORKED_ACCOUNT = "0x1" + "55" * 19 + "5"
+FORKED_BALANCE = 10 ** 18
+FORKED_NONCE = 5
+
+
+class MockRemoteNode:
+    """Serves the state rpcs of a remote chain with a single funded account."""
+
+    def __init__(self):
+        self.requests = []
+        mock = self
+
+        class Handler(BaseHTTPRequestHandler):
+            def do_POST(self):
+                request = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+                mock.requests.append(request)
+                body = json.dumps({"jsonrpc": "2.0", "id": request["id"],
+                                   "result": mock.handle(request["method"], request["params"])}).encode()
+                self.send_response(200)
+                self.send_header("Content-Type", "application/json")
+                self.send_header("Content-Length", str(len(body)))
+                self.end_headers()
+                self.wfile.write(body)
+
+            def log_message(self, *args):
+                pass
+
+        self.server = HTTPServer(("127.0.0.1", 0), Handler)
+        self.url = "http://127.0.0.1:{}".format(self.server.server_address[1])
+        threading.Thread(target=self.server.serve_forever, daemon=True).start()
+
+    def handle(self, method, params):
+        null_address = hex_to_b32_address("0x" + "00" * 20, REMOTE_NETWORK_ID)
+        if method == "cfx_getStatus":
+            return {"networkId": hex(REMOTE_NETWORK_ID)}
+        if method == "cfx_epochNumber":
+            return hex(FORK_EPOCH * 2)
+        if method == "cfx_getAccount":
+            forked = b32_address_to_hex(params[0]) == FORKED_ACCOUNT
+            return {
+                "address": params[0],
+                "balance": hex(FORKED_BALANCE if forked else 0),
+                "nonce": hex(FORKED_NONCE if forked else 0),
+                "codeHash": KECCAK_EMPTY,
+                "stakingBalance": "0x0",
+                "collateralForStorage": "0x0",
+                "accumulatedInterestReturn": "0x0",
+                "admin": null_address,
+            }
+        if method == "cfx_getSponsorInfo":
+            return {
+                "sponsorForGas": null_address,
+                "sponsorForCollateral": null_address,
+                "sponsorGasBound": "0x0",
+                "sponsorBalanceForGas": "0x0",
+                "sponsorBalanceForCollateral": "0x0",
+                "availableStoragePoints": "0x0",
+                "usedStoragePoints": "0x0",
+            }
+        if method in ("cfx_getCode", "eth_getCode"):
+            return "0x"
+        if method in ("cfx_getAdmin", "cfx_getStorageAt"):
+            return None
+        if method in ("cfx_getDepositList", "cfx_getVoteList"):
+            return []
+        if method in ("eth_getBalance", "eth_getTransactionCount"):
+            return "0x0"
+        if method == "eth_getStorageAt":
+            return "0x" + "00" * 32
+        raise AssertionError("unexpected remote rpc {}".format(method))
+
+
+class DevForkTest(ConfluxTestFramework):
+    def set_test_params(self):
+        self.num_nodes = 1
+        self.remote = MockRemoteNode()
+        self.conf_parameters["mode"] = '"dev"'
+        self.conf_parameters["dev_fork_url"] = '"{}"'.format(self.remote.url)
+        self.conf_parameters["dev_fork_epoch"] = str(FORK_EPOCH)
+
+    def setup_network(self):
+        self.setup_nodes()
+
+    def run_test(self):
+        rpc = RpcClient(self.nodes[0])
+
+        # The forked account is read from the remote state.
+        assert_equal(rpc.get_balance(FORKED_ACCOUNT), FORKED_BALANCE)
+        assert_equal(rpc.get_nonce(FORKED_ACCOUNT), FORKED_NONCE)
+
+        # New blocks execute locally on top of the forked state.
+        tx = rpc.new_tx(receiver=FORKED_ACCOUNT, value=100)
+        tx_hash = rpc.send_tx(tx)
+        wait_until(lambda: checktx(self.nodes[0], tx_hash))
+        rpc.generate_empty_blocks(10)
+        assert_equal(rpc.get_balance(FORKED_ACCOUNT), FORKED_BALANCE + 100)
+
+        # Every remote read is at the forked epoch, and fetched only once.
+        for request in self.remote.requests:
+            if request["method"] not in ("cfx_getStatus", "cfx_epochNumber"):
+                assert_equal(request["params"][-1], hex(FORK_EPOCH))
+        forked_reads = [r for r in self.remote.requests
+                        if r["method"] == "cfx_getAccount"
+                        and b32_address_to_hex(r["params"][0]) == FORKED_ACCOUNT]
+        assert_equal(len(forked_reads), 1)
+
+
+if __name__ == '__main__':
+    DevForkTest().main()
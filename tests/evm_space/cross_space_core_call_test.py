@@ -0,0 +1,82 @@
+#!/usr/bin/env python3
+
+from base import Web3Base
+from conflux.utils import sha3 as keccak
+from test_framework.util import *
+from eth_abi import encode, decode
+from web3 import Web3
+
+CROSS_SPACE_ADDRESS = "0x0888000000000000000000000000000000000006"
+CONTEXT_ADDRESS = "0x0888000000000000000000000000000000000004"
+
+CALL_CORE_SIG = "a1069e1a"
+STATIC_CALL_CORE_SIG = "337be688"
+MAPPED_CORE_ADDRESS_SIG = "f3671330"
+EPOCH_NUMBER_SIG = "f4145a83"
+
+
+class CrossSpaceCoreCallTest(Web3Base):
+    def set_test_params(self):
+        super().set_test_params()
+        self.conf_parameters["cip146_transition_number"] = 1
+
+    def run_test(self):
+        super().run_test()
+
+        self.check_mapped_core_address()
+        self.check_static_call_core()
+        self.check_call_core()
+
+    def cross_space_data(self, sig, receiver, data):
+        args = encode(["bytes20", "bytes"], [bytes.fromhex(receiver[2:]), data])
+        return "0x" + sig + args.hex()
+
+    def check_mapped_core_address(self):
+        data = "0x" + MAPPED_CORE_ADDRESS_SIG + encode(["address"], [self.evmAccount.address]).hex()
+        result = self.w3.eth.call({"to": CROSS_SPACE_ADDRESS, "data": data})
+        mapped = bytearray(keccak(bytes.fromhex(self.evmAccount.address[2:]))[-20:])
+        # The mapped address is always a user account address.
+        mapped[0] = (mapped[0] & 0x0f) | 0x10
+        assert_equal(result[:20], bytes(mapped))
+        self.mapped_core_address = "0x" + mapped.hex()
+
+    def check_static_call_core(self):
+        data = self.cross_space_data(STATIC_CALL_CORE_SIG, CONTEXT_ADDRESS, bytes.fromhex(EPOCH_NUMBER_SIG))
+        result = self.w3.eth.call({"to": CROSS_SPACE_ADDRESS, "data": data})
+        (output,) = decode(["bytes"], result)
+        (epoch_number,) = decode(["uint256"], output)
+        assert_greater_than(epoch_number, 0)
+
+    def check_call_core(self):
+        receiver = "0x1" + "ab" * 19 + "c"
+        value = 10 ** 17
+        assert_equal(self.rpc.get_balance(receiver), 0)
+
+        nonce = self.w3.eth.get_transaction_count(self.evmAccount.address)
+        signed = self.evmAccount.sign_transaction({
+            "to": CROSS_SPACE_ADDRESS,
+            "value": value,
+            "gasPrice": 1,
+            "gas": 1000000,
+            "nonce": nonce,
+            "chainId": self.w3.eth.chain_id,
+            "data": self.cross_space_data(CALL_CORE_SIG, receiver, b""),
+        })
+        tx_hash = self.w3.eth.send_raw_transaction(signed["raw_transaction"])
+        self.rpc.generate_block(1)
+        self.rpc.generate_blocks(20, 1)
+
+        receipt = self.w3.eth.get_transaction_receipt(tx_hash)
+        assert_equal(receipt["status"], 1)
+        assert_equal(self.rpc.get_balance(receiver), value)
+        assert_equal(self.rpc.get_balance(self.mapped_core_address), 0)
+
+        # The Core Space frames are not shown in the eSpace traces.
+        traces = self.nodes[0].ethrpc.trace_transaction(Web3.to_hex(tx_hash))
+        assert_equal(len(traces), 1)
+        assert_equal(traces[0]["action"]["to"], CROSS_SPACE_ADDRESS)
+        assert_equal(traces[0]["subtraces"], 0)
+
+
+if __name__ == "__main__":
+    CrossSpaceCoreCallTest().main()
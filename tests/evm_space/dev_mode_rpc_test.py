@@ -0,0 +1,87 @@
+#!/usr/bin/env python3
+
+from base import Web3Base
+from conflux.address import hex_to_b32_address
+from test_framework.util import *
+
+
+class DevModeRpcTest(Web3Base):
+    def set_test_params(self):
+        super().set_test_params()
+        self.conf_parameters["mode"] = '"dev"'
+        self.conf_parameters["public_evm_rpc_apis"] = "\"eth,ethdev\""
+
+    def run_test(self):
+        eth = self.nodes[0].ethrpc
+        account = "0x" + "11" * 20
+        receiver = "0x" + "22" * 20
+        contract = "0x" + "33" * 20
+
+        # State overrides
+        eth.anvil_setBalance(account, hex(10 ** 18))
+        assert_equal(self.w3.eth.get_balance(account), 10 ** 18)
+        eth.hardhat_setBalance(account, hex(2 * 10 ** 18))
+        assert_equal(self.w3.eth.get_balance(account), 2 * 10 ** 18)
+
+        eth.anvil_setCode(contract, "0x60016000")
+        assert_equal(self.w3.eth.get_code(contract).to_0x_hex(), "0x60016000")
+        assert eth.anvil_setStorageAt(contract, "0x1", "0x" + "00" * 31 + "2a")
+        assert_equal(int(self.w3.eth.get_storage_at(contract, 1).hex(), 16), 42)
+
+        # Snapshot and revert. The state is reverted, but the blocks are not
+        # removed: the epoch and block numbers keep increasing.
+        snapshot = eth.evm_snapshot()
+        epoch_before = self.rpc.epoch_number()
+        block_number_before = self.w3.eth.block_number
+        eth.anvil_setBalance(account, hex(1))
+        assert_equal(self.w3.eth.get_balance(account), 1)
+        epoch_after_change = self.rpc.epoch_number()
+        assert eth.evm_revert(snapshot)
+        assert_equal(self.w3.eth.get_balance(account), 2 * 10 ** 18)
+        assert self.rpc.epoch_number() > epoch_after_change > epoch_before
+        assert self.w3.eth.block_number > block_number_before
+        assert not eth.evm_revert(snapshot)
+
+        # Impersonated senders
+        tx = {"from": account, "to": receiver, "value": hex(100), "gasPrice": "0x1"}
+        assert_raises_rpc_error(None, None, eth.eth_sendTransaction, tx)
+        eth.anvil_impersonateAccount(account)
+        tx_hash = eth.eth_sendTransaction(tx)
+        receipt = self.w3.eth.get_transaction_receipt(tx_hash)
+        assert_equal(receipt["status"], 1)
+        assert_equal(self.w3.eth.get_balance(receiver), 100)
+        eth.anvil_stopImpersonatingAccount(account)
+        assert_raises_rpc_error(None, None, eth.eth_sendTransaction, tx)
+
+        # Time controls
+        timestamp = self.rpc.block_by_epoch("latest_mined")["timestamp"]
+        eth.evm_increaseTime(3600)
+        eth.evm_mine()
+        assert int(self.rpc.block_by_epoch("latest_mined")["timestamp"], 16) >= int(timestamp, 16) + 3600
+        target = int(self.rpc.block_by_epoch("latest_mined")["timestamp"], 16) + 7200
+        eth.evm_setNextBlockTimestamp(target)
+        eth.evm_mine()
+        assert int(self.rpc.block_by_epoch("latest_mined")["timestamp"], 16) >= target
+
+        # Core Space namespace
+        core_account = "0x1" + "44" * 19 + "4"
+        self.nodes[0].dev_setBalance(hex_to_b32_address(core_account), hex(10 ** 18))
+        assert_equal(self.rpc.get_balance(core_account), 10 ** 18)
+        snapshot = int(self.nodes[0].dev_snapshot(), 16)
+        self.nodes[0].dev_setBalance(hex_to_b32_address(core_account), "0x0")
+        assert_equal(self.rpc.get_balance(core_account), 0)
+        assert self.nodes[0].dev_revert(hex(snapshot))
+        assert_equal(self.rpc.get_balance(core_account), 10 ** 18)
+
+        # Reverting also restores the impersonated accounts and the clock
+        snapshot = eth.evm_snapshot()
+        offset = int(eth.evm_increaseTime(0), 16)
+        eth.anvil_impersonateAccount(account)
+        eth.evm_increaseTime(3600)
+        assert eth.evm_revert(snapshot)
+        assert_raises_rpc_error(None, None, eth.eth_sendTransaction, tx)
+        assert_equal(int(eth.evm_increaseTime(0), 16), offset)
+
+
+if __name__ == "__main__":
+    DevModeRpcTest().main()
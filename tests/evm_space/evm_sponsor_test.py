@@ -0,0 +1,112 @@
+#!/usr/bin/env python3
+
+from base import Web3Base
+from test_framework.util import *
+from eth_abi import encode, decode
+
+SPONSOR_CONTROL_ADDRESS = "0x0888000000000000000000000000000000000001"
+
+SET_SPONSOR_FOR_GAS_SIG = "3e3e6428"
+GET_SPONSOR_FOR_GAS_SIG = "33a1af31"
+GET_SPONSORED_BALANCE_FOR_GAS_SIG = "b3b28fac"
+IS_ALL_WHITELISTED_SIG = "79b47faa"
+ADD_PRIVILEGE_BY_ADMIN_SIG = "22effe84"
+
+# A contract whose runtime code is a single `STOP`.
+SPONSORED_CONTRACT_BYTECODE = "0x600060005360016000f3"
+
+
+class EvmSponsorTest(Web3Base):
+    def set_test_params(self):
+        super().set_test_params()
+        self.conf_parameters["cip147_transition_number"] = 1
+
+    def run_test(self):
+        super().run_test()
+
+        self.contract = self.deploy_evm_space_by_code(SPONSORED_CONTRACT_BYTECODE)
+        self.add_privilege()
+
+        self.set_sponsor()
+        self.check_sponsored_tx()
+
+    def query(self, sig, types, args, output_type):
+        data = "0x" + sig + encode(types, args).hex()
+        result = self.w3.eth.call({"to": SPONSOR_CONTROL_ADDRESS, "data": data})
+        (output,) = decode([output_type], result)
+        return output
+
+    def sponsor_balance(self):
+        return self.query(GET_SPONSORED_BALANCE_FOR_GAS_SIG, ["address"], [self.contract], "uint256")
+
+    def send_evm_tx(self, account, tx):
+        tx["nonce"] = self.w3.eth.get_transaction_count(account.address)
+        tx["chainId"] = self.w3.eth.chain_id
+        signed = account.sign_transaction(tx)
+        tx_hash = self.w3.eth.send_raw_transaction(signed["raw_transaction"])
+        self.rpc.generate_block(1)
+        self.rpc.generate_blocks(20, 1)
+        return self.w3.eth.get_transaction_receipt(tx_hash)
+
+    def add_privilege(self):
+        data = "0x" + ADD_PRIVILEGE_BY_ADMIN_SIG + encode(["address", "address[]"], [self.contract, ["0x" + "00" * 20]]).hex()
+        tx = {
+            "to": SPONSOR_CONTROL_ADDRESS,
+            "value": 0,
+            "gasPrice": 1,
+            "gas": 1000000,
+            "data": data,
+        }
+
+        # Only the admin, the creator of the contract, sets the whitelist.
+        other = self.w3.eth.account.create()
+        self.send_evm_tx(self.evmAccount, {"to": other.address, "value": 10 ** 17, "gasPrice": 1, "gas": 21000})
+        receipt = self.send_evm_tx(other, dict(tx))
+        assert_equal(receipt["status"], 0)
+        assert_equal(self.query(IS_ALL_WHITELISTED_SIG, ["address"], [self.contract], "bool"), False)
+
+        receipt = self.send_evm_tx(self.evmAccount, tx)
+        assert_equal(receipt["status"], 1)
+        assert_equal(self.query(IS_ALL_WHITELISTED_SIG, ["address"], [self.contract], "bool"), True)
+
+    def set_sponsor(self):
+        value = 10 ** 17
+        data = "0x" + SET_SPONSOR_FOR_GAS_SIG + encode(["address", "uint256"], [self.contract, 10 ** 14]).hex()
+        receipt = self.send_evm_tx(self.evmAccount, {
+            "to": SPONSOR_CONTROL_ADDRESS,
+            "value": value,
+            "gasPrice": 1,
+            "gas": 1000000,
+            "data": data,
+        })
+        assert_equal(receipt["status"], 1)
+
+        sponsor = self.query(GET_SPONSOR_FOR_GAS_SIG, ["address"], [self.contract], "address")
+        assert_equal(sponsor, self.evmAccount.address)
+        assert_equal(self.sponsor_balance(), value)
+        # The deposit is not kept in the balance of the sponsor control contract.
+        assert_equal(self.w3.eth.get_balance(SPONSOR_CONTROL_ADDRESS), 0)
+
+    def check_sponsored_tx(self):
+        user = self.w3.eth.account.create()
+        assert_equal(self.w3.eth.get_balance(user.address), 0)
+
+        # The estimation succeeds for a sender without any balance.
+        gas = self.w3.eth.estimate_gas({"from": user.address, "to": self.contract, "gasPrice": 1})
+        assert_greater_than_or_equal(gas, 21000)
+
+        balance_before = self.sponsor_balance()
+        receipt = self.send_evm_tx(user, {
+            "to": self.contract,
+            "value": 0,
+            "gasPrice": 1,
+            "gas": 30000,
+            "data": "0x",
+        })
+        assert_equal(receipt["status"], 1)
+        assert_equal(self.w3.eth.get_balance(user.address), 0)
+        assert_greater_than(balance_before, self.sponsor_balance())
+
+
+if __name__ == "__main__":
+    EvmSponsorTest().main()
@@ -0,0 +1,43 @@
+#!/usr/bin/env python3
+
+from base import Web3Base
+from test_framework.util import *
+
+
+class FinalityStatusTest(Web3Base):
+    def run_test(self):
+        super().run_test()
+
+        nonce = self.w3.eth.get_transaction_count(self.evmAccount.address)
+        signed = self.evmAccount.sign_transaction({
+            "to": self.evmAccount.address,
+            "value": 1,
+            "gasPrice": 1,
+            "gas": 21000,
+            "nonce": nonce,
+            "chainId": self.TEST_CHAIN_ID,
+        })
+        tx_hash = self.w3.eth.send_raw_transaction(signed["raw_transaction"])
+        self.rpc.generate_block(1)
+        self.rpc.generate_blocks(20, 1)
+        receipt = self.w3.eth.wait_for_transaction_receipt(tx_hash)
+        block_hash = receipt["blockHash"].to_0x_hex()
+
+        status = self.nodes[0].eth_getBlockFinalityStatus(block_hash)
+        assert_equal(status["blockHash"], block_hash)
+        assert_equal(int(status["blockNumber"], 16), receipt["blockNumber"])
+        assert "transactionHash" not in status
+        assert_is_hex_string(status["confirmationRisk"])
+        assert_is_hex_string(status["latestFinalizedBlock"])
+        assert_equal(status["posFinalized"], int(status["latestFinalizedBlock"], 16) >= receipt["blockNumber"])
+
+        tx_status = self.nodes[0].eth_getTransactionFinalityStatus(tx_hash.to_0x_hex())
+        assert_equal(tx_status["blockHash"], block_hash)
+        assert_equal(tx_status["transactionHash"], tx_hash.to_0x_hex())
+
+        assert_equal(self.nodes[0].eth_getBlockFinalityStatus("0x" + "00" * 32), None)
+        assert_equal(self.nodes[0].eth_getTransactionFinalityStatus("0x" + "00" * 32), None)
+
+
+if __name__ == "__main__":
+    FinalityStatusTest().main()
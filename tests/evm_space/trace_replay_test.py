@@ -0,0 +1,155 @@
+#!/usr/bin/env python3
+
+from base import Web3Base
+from test_framework.util import *
+from web3 import Web3
+
+toHex = Web3.to_hex
+
+class TraceReplayTest(Web3Base):
+    def set_test_params(self):
+        super().set_test_params()
+        self.conf_parameters["public_evm_rpc_apis"] = "\"eth,ethdebug\""
+
+    def run_test(self):
+        super().run_test()
+
+        erc20_addr = self.deploy_erc20()
+        tx_hash = self.erc20_transfer(erc20_addr)
+
+        self.replay_transaction(tx_hash, erc20_addr)
+        self.replay_block_transactions(tx_hash)
+        self.trace_call(erc20_addr)
+        self.trace_call_many(erc20_addr)
+        self.replay_core_transaction(tx_hash)
+
+    def deploy_erc20(self):
+        bytecode_file = os.path.join(os.path.dirname(os.path.realpath(__file__)), "../contracts/erc20_bytecode.dat")
+        assert(os.path.isfile(bytecode_file))
+        bytecode = open(bytecode_file).read()
+
+        nonce = self.w3.eth.get_transaction_count(self.evmAccount.address)
+        signed = self.evmAccount.sign_transaction({
+            "to": None,
+            "value": 0,
+            "gasPrice": 1,
+            "gas": 10000000,
+            "nonce": nonce,
+            "chainId": self.w3.eth.chain_id,
+            "data": bytecode,
+        })
+        tx_hash = self.w3.eth.send_raw_transaction(signed["raw_transaction"])
+        self.rpc.generate_block(1)
+        self.rpc.generate_blocks(20, 1)
+
+        receipt = self.w3.eth.get_transaction_receipt(tx_hash)
+        assert_equal(receipt["status"], 1)
+        return receipt["contractAddress"]
+
+    def transfer_data(self, erc20_addr):
+        abi = self.load_abi_from_contracts_folder("erc20")
+        erc20 = self.w3.eth.contract(address=erc20_addr, abi=abi)
+        target_addr = Web3.to_checksum_address("0x8b14d287b4150ff22ac73df8be720e933f659abc")
+        return erc20.encode_abi(abi_element_identifier="transfer", args=[target_addr, 100])
+
+    def erc20_transfer(self, erc20_addr):
+        nonce = self.w3.eth.get_transaction_count(self.evmAccount.address)
+        signed = self.evmAccount.sign_transaction({
+            "to": erc20_addr,
+            "value": 0,
+            "gasPrice": 1,
+            "gas": 1000000,
+            "nonce": nonce,
+            "chainId": self.w3.eth.chain_id,
+            "data": self.transfer_data(erc20_addr),
+        })
+        tx_hash = self.w3.eth.send_raw_transaction(signed["raw_transaction"])
+        self.rpc.generate_block(1)
+        self.rpc.generate_blocks(20, 1)
+        return tx_hash
+
+    def replay_transaction(self, tx_hash, erc20_addr):
+        result = self.nodes[0].ethrpc.trace_replayTransaction(toHex(tx_hash), ["trace", "vmTrace", "stateDiff"])
+        assert_equal(result["output"], "0x0000000000000000000000000000000000000000000000000000000000000001")
+
+        trace = result["trace"]
+        assert_equal(len(trace), 1)
+        assert_equal(trace[0]["action"]["callType"], "call")
+        assert_equal(trace[0]["action"]["to"], erc20_addr.lower())
+        assert_equal(trace[0]["traceAddress"], [])
+
+        vm_trace = result["vmTrace"]
+        assert_greater_than(len(vm_trace["ops"]), 0)
+        assert_equal(vm_trace["ops"][-1]["op"], "RETURN")
+
+        state_diff = result["stateDiff"]
+        sender = state_diff[self.evmAccount.address.lower()]
+        assert "*" in sender["nonce"]
+        assert "*" in sender["balance"]
+        # the balances of the sender and the receiver
+        assert_equal(len(state_diff[erc20_addr.lower()]["storage"]), 2)
+
+        result = self.nodes[0].ethrpc.trace_replayTransaction(toHex(tx_hash), ["trace"])
+        assert_equal(result["vmTrace"], None)
+        assert_equal(result["stateDiff"], None)
+
+    def replay_block_transactions(self, tx_hash):
+        receipt = self.w3.eth.get_transaction_receipt(tx_hash)
+        results = self.nodes[0].ethrpc.trace_replayBlockTransactions(hex(receipt["blockNumber"]), ["trace"])
+        assert_equal(len(results), 1)
+        assert_equal(results[0]["transactionHash"], toHex(tx_hash))
+        assert_equal(len(results[0]["trace"]), 1)
+
+    def trace_call(self, erc20_addr):
+        request = {
+            "from": self.evmAccount.address,
+            "to": erc20_addr,
+            "data": self.transfer_data(erc20_addr),
+        }
+        result = self.nodes[0].ethrpc.trace_call(request, ["trace", "stateDiff"], "latest")
+        assert_equal(result["output"], "0x0000000000000000000000000000000000000000000000000000000000000001")
+        assert_equal(len(result["trace"]), 1)
+        assert_equal(result["vmTrace"], None)
+        assert_equal(len(result["stateDiff"][erc20_addr.lower()]["storage"]), 2)
+
+    def trace_call_many(self, erc20_addr):
+        request = {
+            "from": self.evmAccount.address,
+            "to": erc20_addr,
+            "data": self.transfer_data(erc20_addr),
+        }
+        results = self.nodes[0].ethrpc.trace_callMany([[request, ["trace"]], [request, ["stateDiff"]]], "latest")
+        assert_equal(len(results), 2)
+        assert_equal(len(results[0]["trace"]), 1)
+        assert_equal(results[0]["stateDiff"], None)
+        assert_equal(results[1]["trace"], [])
+        # the sender nonce is bumped by the call
+        sender_nonce = results[1]["stateDiff"][self.evmAccount.address.lower()]["nonce"]["*"]
+        assert_equal(int(sender_nonce["to"], 16), int(sender_nonce["from"], 16) + 1)
+
+    def replay_core_transaction(self, espace_tx_hash):
+        receiver = self.rpc.rand_addr()
+        tx = self.rpc.new_tx(receiver=receiver, value=100, nonce=self.rpc.get_nonce(self.cfxAccount))
+        self.rpc.send_tx(tx, True)
+        self.rpc.generate_blocks(20, 1)
+
+        result = self.nodes[0].trace_replayTransaction(tx.hash_hex(), ["trace", "stateDiff"])
+        trace = result["trace"]
+        assert_equal(len(trace), 1)
+        assert_equal(trace[0]["action"]["to"], receiver.lower())
+        assert_equal(int(trace[0]["action"]["value"], 16), 100)
+        # the receiver is a fresh account
+        assert "+" in result["stateDiff"][receiver.lower()]["balance"]
+
+        # each namespace only replays transactions of its own space
+        assert_raises_rpc_error(None, None, self.nodes[0].trace_replayTransaction, toHex(espace_tx_hash), ["trace"])
+        assert_raises_rpc_error(None, None, self.nodes[0].ethrpc.trace_replayTransaction, tx.hash_hex(), ["trace"])
+
+        epoch = self.rpc.get_transaction_receipt(tx.hash_hex())["epochNumber"]
+        results = self.nodes[0].trace_replayEpochTransactions(epoch, ["trace"])
+        assert_equal(len(results), 1)
+        assert_equal(results[0]["transactionHash"], tx.hash_hex())
+
+
+if __name__ == "__main__":
+    TraceReplayTest().main()
@@ -0,0 +1,49 @@
+#!/usr/bin/env python3
+"""Verify the execution witnesses of epochs deferred by blamed pivot headers.
+"""
+
+from test_framework.test_framework import ConfluxTestFramework
+from test_framework.util import *
+from conflux.rpc import RpcClient
+
+
+class ExecutionWitnessBlameTest(ConfluxTestFramework):
+    def set_test_params(self):
+        self.num_nodes = 1
+        self.conf_parameters["execution_witness"] = "true"
+
+    def setup_network(self):
+        self.setup_nodes()
+
+    def run_test(self):
+        client = RpcClient(self.nodes[0])
+        client.generate_empty_blocks(10)
+        assert_equal(client.epoch_number(), 10)
+
+        # The headers at the heights 11, 12 and 13 have wrong deferred state
+        # roots, so the header at 14 blames them, and its deferred roots are
+        # the vector roots of the epochs 6, 7, 8 and 9.
+        blame_info = {}
+        blame_info['blame'] = "0x1"
+        blame_info['deferredStateRoot'] = "0x1111111111111111111111111111111111111111111111111111111111111111"
+        for _ in range(3):
+            self.nodes[0].test_generateBlockWithBlameInfo(1, 0, blame_info)
+        blaming = client.block_by_hash(client.generate_empty_blocks(1)[0])
+        assert_equal(blaming['blame'], "0x3")
+        client.generate_empty_blocks(10)
+
+        # Epoch 9 is deferred by the header with blame 3, and the epochs 6, 7
+        # and 8 by the headers it blames. Epoch 10 is deferred by a header with
+        # blame 0.
+        for epoch in range(6, 11):
+            wait_until(lambda: self.nodes[0].debug_getEpochExecutionWitness(hex(epoch)) is not None)
+            witness = self.nodes[0].debug_getEpochExecutionWitness(hex(epoch))
+            verification = self.nodes[0].debug_verifyEpochExecutionWitness(witness)
+            self.log.info("Verified the witness of epoch %d", epoch)
+        deferring = client.block_by_epoch(15)
+        assert_equal(deferring['blame'], "0x0")
+        assert_equal(verification['receiptsRoot'], deferring['deferredReceiptsRoot'])
+        assert_equal(verification['logsBloomHash'], deferring['deferredLogsBloomHash'])
+
+if __name__ == '__main__':
+    ExecutionWitnessBlameTest().main()
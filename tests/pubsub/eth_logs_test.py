@@ -146,13 +146,25 @@ class PubSubTest(ConfluxTestFramework):
         # block order changed, some transactions need to be re-executed
         num_to_reexecute = sum(1 for r in receipts if int(r["blockNumber"], 16) > fork_epoch)
 
+        # the reorg is published before the removed logs
+        msg = await sub_all.next(timeout=5)
+        assert_equal(int(msg["revertTo"], 16), fork_epoch)
+        assert(len(msg["oldPivots"]) > 0)
+        assert(len(msg["newPivots"]) > 0)
+        for i, pivot in enumerate(msg["newPivots"]):
+            assert_equal(pivot, self.rpc[FULLNODE0].block_by_epoch(fork_epoch + 1 + i)["hash"])
+
         logs = [l async for l in sub_all.iter()]
         assert_equal(len(logs), num_to_reexecute * 2)
         assert_equal(len([l async for l in sub_all_core.iter()]), 1)
 
         for i in range(num_to_reexecute):
             assert(logs[i]["removed"])
-        
+
+        # removed logs are published in reverse order
+        removed_numbers = [int(l["blockNumber"], 16) for l in logs[:num_to_reexecute]]
+        assert_equal(removed_numbers, sorted(removed_numbers, reverse=True))
+
         for i in range(num_to_reexecute, num_to_reexecute * 2):
             assert(logs[i]["removed"] == False)
 
@@ -121,11 +121,26 @@ class PubSubTest(ConfluxTestFramework):
         msg = await sub_all.next(timeout=5)
         assert(msg["revertTo"] != None)
         assert_equal(int(msg["revertTo"], 16), fork_epoch)
+        assert(len(msg["oldPivots"]) > 0)
+        assert(len(msg["newPivots"]) > 0)
+        for i, pivot in enumerate(msg["newPivots"]):
+            assert_equal(pivot, self.rpc[FULLNODE0].block_by_epoch(fork_epoch + 1 + i)["hash"])
 
         assert_equal(len([l async for l in sub_all_eth.iter()]), 0)
 
+        # the reverted logs are published as removed in reverse order,
+        # followed by the re-executed logs
         logs = [l async for l in sub_all.iter()]
-        assert_equal(len(logs), num_to_reexecute)
+        assert_equal(len(logs), num_to_reexecute * 2)
+
+        for i in range(num_to_reexecute):
+            assert(logs[i]["removed"])
+
+        removed_epochs = [int(l["epochNumber"], 16) for l in logs[:num_to_reexecute]]
+        assert_equal(removed_epochs, sorted(removed_epochs, reverse=True))
+
+        for i in range(num_to_reexecute, num_to_reexecute * 2):
+            assert("removed" not in logs[i])
 
         self.log.info(f"Pass -- retrieved re-executed logs after fork")
 
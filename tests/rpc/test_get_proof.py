@@ -0,0 +1,27 @@
+import sys
+sys.path.append("..")
+
+from conflux.rpc import RpcClient
+from test_framework.util import assert_equal, assert_greater_than
+
+
+class TestGetProof(RpcClient):
+    def test_default_epoch(self):
+        self.generate_blocks_to_state()
+        proof = self.get_proof(self.GENESIS_ADDR, [])
+        assert_equal(int(proof["epochNumber"], 0), self.epoch_number(self.EPOCH_LATEST_STATE))
+        assert_equal(int(proof["nonce"], 0), self.get_nonce(self.GENESIS_ADDR))
+        # No pivot block header commits to the latest state root yet.
+        assert_equal(proof["stateRoot"]["commitment"], None)
+
+    def test_committed_epoch(self):
+        self.generate_blocks_to_state()
+        epoch = self.epoch_number(self.EPOCH_LATEST_STATE)
+        self.generate_empty_blocks(5)
+        proof = self.get_proof(self.GENESIS_ADDR, ["0x0"], self.EPOCH_NUM(epoch))
+        assert_equal(int(proof["epochNumber"], 0), epoch)
+        assert_equal(len(proof["storageProof"]), 1)
+        commitment = proof["stateRoot"]["commitment"]
+        assert commitment is not None
+        block = self.block_by_hash(commitment["blockHash"])
+        assert_greater_than(int(block["epochNumber"], 0), epoch)